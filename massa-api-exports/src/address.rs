@@ -34,6 +34,16 @@ pub struct AddressInfo {
     /// deferred credits
     pub deferred_credits: Vec<SlotAmount>,
 
+    /// final payout address, receiving the deferred credits and staking rewards
+    #[serde(default)]
+    pub final_payout_address: Option<Address>,
+    /// candidate payout address
+    #[serde(default)]
+    pub candidate_payout_address: Option<Address>,
+    /// addresses paying out to this address
+    #[serde(default)]
+    pub payout_address_sources: Vec<Address>,
+
     /// next block draws
     pub next_block_draws: Vec<Slot>,
     /// next endorsement draws
@@ -75,6 +85,24 @@ impl std::fmt::Display for AddressInfo {
                 )?;
             }
         }
+        if self.final_payout_address.is_some() || self.candidate_payout_address.is_some() {
+            let display_payout = |payout_address: &Option<Address>| match payout_address {
+                Some(payout_address) => payout_address.to_string(),
+                None => "none".to_string(),
+            };
+            writeln!(
+                f,
+                "\tPayout address: final={}, candidate={}",
+                display_payout(&self.final_payout_address),
+                display_payout(&self.candidate_payout_address)
+            )?;
+        }
+        if !self.payout_address_sources.is_empty() {
+            writeln!(f, "\tPaying out to this address:")?;
+            for source in &self.payout_address_sources {
+                writeln!(f, "\t\t{}", source)?;
+            }
+        }
         writeln!(f, "\tCycle infos:")?;
         for cycle_info in &self.cycle_infos {
             writeln!(
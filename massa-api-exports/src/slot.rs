@@ -9,6 +9,7 @@ use serde::{Deserialize, Serialize};
 pub struct SlotAmount {
     /// slot
     pub slot: Slot,
-    /// amount
+    /// amount, in its canonical form
+    #[serde(with = "massa_models::amount::canonical")]
     pub amount: Amount,
 }
@@ -21,6 +21,7 @@ use massa_models::slot::Slot;
 use massa_models::timeslots::get_latest_block_slot_at_timestamp;
 use massa_models::version::Version;
 use massa_pool_exports::PoolBroadcasts;
+use massa_pos_exports::DeferredCreditsWatcher;
 use massa_time::MassaTime;
 use serde::Serialize;
 use tokio_stream::wrappers::BroadcastStream;
@@ -32,6 +33,7 @@ impl API<ApiV2> {
         consensus_broadcasts: ConsensusBroadcasts,
         execution_controller: Box<dyn ExecutionController>,
         pool_broadcasts: PoolBroadcasts,
+        credits_watcher: DeferredCreditsWatcher,
         api_settings: APIConfig,
         version: Version,
     ) -> Self {
@@ -40,6 +42,7 @@ impl API<ApiV2> {
             consensus_broadcasts,
             execution_controller,
             pool_broadcasts,
+            credits_watcher,
             api_settings,
             version,
         })
@@ -110,7 +113,12 @@ impl MassaApiServer for API<ApiV2> {
     }
 
     async fn get_next_block_best_parents(&self) -> RpcResult<Vec<(BlockId, u64)>> {
-        Ok(self.0.consensus_controller.get_best_parents())
+        Ok(self
+            .0
+            .consensus_controller
+            .get_best_parents_snapshot()
+            .as_ref()
+            .clone())
     }
 
     async fn get_version(&self) -> RpcResult<Version> {
@@ -149,6 +157,13 @@ impl MassaApiServer for API<ApiV2> {
     ) -> SubscriptionResult {
         broadcast_via_ws(self.0.pool_broadcasts.operation_sender.clone(), pending).await
     }
+
+    async fn subscribe_deferred_credit_events(
+        &self,
+        pending: PendingSubscriptionSink,
+    ) -> SubscriptionResult {
+        broadcast_via_ws(self.0.credits_watcher.get_sender(), pending).await
+    }
 }
 
 // Brodcast the stream(sender) content via a WebSocket
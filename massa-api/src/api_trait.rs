@@ -57,4 +57,12 @@ pub trait MassaApi {
 		item = Operation
 	)]
     async fn subscribe_new_operations(&self) -> SubscriptionResult;
+
+    /// Changes of the final deferred credits of the addresses watched through the private API.
+    #[subscription(
+		name = "subscribe_deferred_credit_events" => "deferred_credit_events",
+		unsubscribe = "unsubscribe_deferred_credit_events",
+		item = DeferredCreditEvent
+	)]
+    async fn subscribe_deferred_credit_events(&self) -> SubscriptionResult;
 }
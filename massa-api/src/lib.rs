@@ -8,7 +8,7 @@ use api_trait::MassaApiServer;
 use hyper::Method;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use jsonrpsee::proc_macros::rpc;
-use jsonrpsee::server::middleware::HostFilterLayer;
+use jsonrpsee::server::middleware::{HostFilterLayer, ProxyGetRequestLayer};
 use jsonrpsee::server::{BatchRequestConfig, ServerBuilder, ServerHandle};
 use jsonrpsee::RpcModule;
 use massa_api_exports::{
@@ -26,6 +26,7 @@ use massa_api_exports::{
 };
 use massa_consensus_exports::{ConsensusBroadcasts, ConsensusController};
 use massa_execution_exports::ExecutionController;
+use massa_final_state::StateHashComposition;
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
 use massa_models::node::NodeId;
@@ -37,7 +38,7 @@ use massa_models::{
     execution::EventFilter, slot::Slot, version::Version,
 };
 use massa_pool_exports::{PoolBroadcasts, PoolController};
-use massa_pos_exports::SelectorController;
+use massa_pos_exports::{DeferredCreditsWatcher, SelectorController};
 use massa_protocol_exports::{ProtocolConfig, ProtocolController};
 use massa_storage::Storage;
 use massa_versioning::keypair_factory::KeyPairFactory;
@@ -96,6 +97,8 @@ pub struct Private {
     pub stop_cv: Arc<(Mutex<bool>, Condvar)>,
     /// User wallet
     pub node_wallet: Arc<RwLock<Wallet>>,
+    /// observer of the deferred credits of watched addresses
+    pub credits_watcher: DeferredCreditsWatcher,
 }
 
 /// API v2 content
@@ -108,6 +111,8 @@ pub struct ApiV2 {
     pub execution_controller: Box<dyn ExecutionController>,
     /// channels with informations broadcasted by the pool
     pub pool_broadcasts: PoolBroadcasts,
+    /// observer of the deferred credits of watched addresses
+    pub credits_watcher: DeferredCreditsWatcher,
     /// API settings
     pub api_settings: APIConfig,
     /// node version
@@ -182,9 +187,17 @@ async fn serve<T>(
 
     let allowed_hosts = HostFilterLayer::new(hosts).expect("failed to build allowed hosts filter");
 
+    // plain HTTP GET health probes, answered by the public API
+    let liveness_probe = ProxyGetRequestLayer::new("/health/live", "get_liveness")
+        .expect("failed to build liveness probe");
+    let readiness_probe = ProxyGetRequestLayer::new("/health/ready", "get_readiness")
+        .expect("failed to build readiness probe");
+
     let middleware = tower::ServiceBuilder::new()
         .layer(cors)
-        .layer(allowed_hosts);
+        .layer(allowed_hosts)
+        .layer(liveness_probe)
+        .layer(readiness_probe);
 
     let server = server_builder
         .set_middleware(middleware)
@@ -256,6 +269,28 @@ pub trait MassaRpc {
     #[method(name = "get_staking_addresses")]
     async fn get_staking_addresses(&self) -> RpcResult<PreHashSet<Address>>;
 
+    /// Add addresses to the watch-list of the deferred credits observer.
+    /// The changes of their final deferred credits are streamed by `subscribe_deferred_credit_events`.
+    #[method(name = "add_deferred_credits_watched_addresses")]
+    async fn add_deferred_credits_watched_addresses(&self, arg: Vec<Address>) -> RpcResult<()>;
+
+    /// Remove addresses from the watch-list of the deferred credits observer.
+    #[method(name = "remove_deferred_credits_watched_addresses")]
+    async fn remove_deferred_credits_watched_addresses(&self, arg: Vec<Address>) -> RpcResult<()>;
+
+    /// Return the addresses watched by the deferred credits observer.
+    #[method(name = "get_deferred_credits_watched_addresses")]
+    async fn get_deferred_credits_watched_addresses(&self) -> RpcResult<PreHashSet<Address>>;
+
+    /// Break the final state hash down into the hashes of its components (ledger, async pool,
+    /// cycle history, deferred credits, executed ops...) to find which one differs between diverging nodes.
+    /// If a slot is given, it must be the current final slot of the node.
+    #[method(name = "get_final_state_hash_composition")]
+    async fn get_final_state_hash_composition(
+        &self,
+        arg: Option<Slot>,
+    ) -> RpcResult<StateHashComposition>;
+
     /// Bans given IP address(es).
     /// No confirmation to expect.
     #[method(name = "node_ban_by_ip")]
@@ -325,6 +360,16 @@ pub trait MassaRpc {
     #[method(name = "get_status")]
     async fn get_status(&self) -> RpcResult<NodeStatus>;
 
+    /// Fails if the consensus worker is not alive.
+    /// Also answers plain HTTP `GET /health/live` requests, for liveness probes.
+    #[method(name = "get_liveness")]
+    async fn get_liveness(&self) -> RpcResult<()>;
+
+    /// Fails if the node is not ready to serve consensus data: graph not initialized, worker not alive or finality lagging.
+    /// Also answers plain HTTP `GET /health/ready` requests, for readiness probes.
+    #[method(name = "get_readiness")]
+    async fn get_readiness(&self) -> RpcResult<()>;
+
     /// Get cliques.
     #[method(name = "get_cliques")]
     async fn get_cliques(&self) -> RpcResult<Vec<Clique>>;
@@ -18,12 +18,14 @@ use massa_api_exports::{
     ListType, ScrudOperation, TimeInterval,
 };
 use massa_execution_exports::ExecutionController;
+use massa_final_state::StateHashComposition;
 use massa_hash::Hash;
 use massa_models::{
     address::Address, block::Block, block_id::BlockId, clique::Clique, composite::PubkeySig,
     endorsement::EndorsementId, execution::EventFilter, node::NodeId, operation::OperationId,
     output_event::SCOutputEvent, prehash::PreHashSet, slot::Slot,
 };
+use massa_pos_exports::DeferredCreditsWatcher;
 use massa_protocol_exports::{PeerId, ProtocolController};
 use massa_signature::KeyPair;
 use massa_wallet::Wallet;
@@ -46,6 +48,7 @@ impl API<Private> {
         api_settings: APIConfig,
         stop_cv: Arc<(Mutex<bool>, Condvar)>,
         node_wallet: Arc<RwLock<Wallet>>,
+        credits_watcher: DeferredCreditsWatcher,
     ) -> Self {
         API(Private {
             protocol_controller,
@@ -53,6 +56,7 @@ impl API<Private> {
             api_settings,
             stop_cv,
             node_wallet,
+            credits_watcher,
         })
     }
 }
@@ -147,6 +151,44 @@ impl MassaRpcServer for API<Private> {
         Ok(w_wallet.get_wallet_address_list())
     }
 
+    async fn add_deferred_credits_watched_addresses(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<()> {
+        self.0.credits_watcher.watch(addresses);
+        Ok(())
+    }
+
+    async fn remove_deferred_credits_watched_addresses(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<()> {
+        self.0.credits_watcher.unwatch(&addresses);
+        Ok(())
+    }
+
+    async fn get_deferred_credits_watched_addresses(&self) -> RpcResult<PreHashSet<Address>> {
+        Ok(self.0.credits_watcher.get_watch_list())
+    }
+
+    async fn get_final_state_hash_composition(
+        &self,
+        slot: Option<Slot>,
+    ) -> RpcResult<StateHashComposition> {
+        let composition = self
+            .0
+            .execution_controller
+            .get_final_state_hash_composition();
+        match slot {
+            Some(slot) if slot != composition.slot => Err(ApiError::BadRequest(format!(
+                "the final state is at slot {}, not at slot {}",
+                composition.slot, slot
+            ))
+            .into()),
+            _ => Ok(composition),
+        }
+    }
+
     async fn node_ban_by_ip(&self, _ips: Vec<IpAddr>) -> RpcResult<()> {
         //TODO: Reinvoke
         // let network_command_sender = self.0.network_command_sender.clone();
@@ -199,6 +241,14 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<NodeStatus>()
     }
 
+    async fn get_liveness(&self) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn get_readiness(&self) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
     async fn get_cliques(&self) -> RpcResult<Vec<Clique>> {
         crate::wrong_api::<Vec<Clique>>()
     }
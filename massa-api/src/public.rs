@@ -20,12 +20,15 @@ use massa_api_exports::{
     TimeInterval,
 };
 use massa_consensus_exports::block_status::DiscardReason;
+use massa_consensus_exports::error::ConsensusError;
+use massa_consensus_exports::query_quota::QueryCaller;
 use massa_consensus_exports::ConsensusController;
 use massa_execution_exports::{
     ExecutionController, ExecutionQueryRequest, ExecutionQueryRequestItem,
     ExecutionQueryResponseItem, ExecutionStackElement, ReadOnlyExecutionRequest,
     ReadOnlyExecutionTarget,
 };
+use massa_final_state::StateHashComposition;
 use massa_models::{
     address::Address,
     amount::Amount,
@@ -64,6 +67,9 @@ use massa_versioning::{
 use std::collections::BTreeMap;
 use std::net::{IpAddr, SocketAddr};
 
+/// Identity the public API passes to the consensus heavy queries, counted against their per-minute budgets
+const PUBLIC_API_CALLER: &str = "public api";
+
 impl API<Public> {
     /// generate a new public API
     pub fn new(
@@ -303,6 +309,25 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<PreHashSet<Address>>()
     }
 
+    async fn add_deferred_credits_watched_addresses(&self, _: Vec<Address>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn remove_deferred_credits_watched_addresses(&self, _: Vec<Address>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn get_deferred_credits_watched_addresses(&self) -> RpcResult<PreHashSet<Address>> {
+        crate::wrong_api::<PreHashSet<Address>>()
+    }
+
+    async fn get_final_state_hash_composition(
+        &self,
+        _: Option<Slot>,
+    ) -> RpcResult<StateHashComposition> {
+        crate::wrong_api::<StateHashComposition>()
+    }
+
     async fn node_ban_by_ip(&self, _: Vec<IpAddr>) -> RpcResult<()> {
         crate::wrong_api::<()>()
     }
@@ -427,9 +452,30 @@ impl MassaRpcServer for API<Public> {
         })
     }
 
+    async fn get_liveness(&self) -> RpcResult<()> {
+        if !self.0.consensus_controller.liveness() {
+            return Err(
+                ApiError::ConsensusError("consensus worker is not alive".to_string()).into(),
+            );
+        }
+        Ok(())
+    }
+
+    async fn get_readiness(&self) -> RpcResult<()> {
+        if !self.0.consensus_controller.is_ready() {
+            return Err(ApiError::ConsensusError("consensus is not ready".to_string()).into());
+        }
+        Ok(())
+    }
+
     /// get cliques
     async fn get_cliques(&self) -> RpcResult<Vec<Clique>> {
-        Ok(self.0.consensus_controller.get_cliques())
+        Ok(self
+            .0
+            .consensus_controller
+            .get_cliques_snapshot()
+            .as_ref()
+            .clone())
     }
 
     /// get stakers
@@ -655,34 +701,30 @@ impl MassaRpcServer for API<Public> {
     }
 
     /// get blocks
-    /// Returns only active blocks are returned
-    async fn get_blocks(&self, mut ids: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
-        let mut blocks: Vec<Block> = Vec::with_capacity(ids.len());
-        {
-            let block_storage_lock = self.0.storage.read_blocks();
-            ids.retain(|id| {
-                if let Some(wrapped_block) = block_storage_lock.get(id) {
-                    blocks.push(wrapped_block.content.clone());
-                    return true;
-                };
-                false
-            });
-        }
-        let block_statuses = self.0.consensus_controller.get_block_statuses(&ids);
+    /// Returns only the blocks held by consensus
+    async fn get_blocks(&self, ids: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
+        // statuses and storages are fetched atomically so that blocks can't be pruned in between
+        let blocks_with_storage = match self.0.consensus_controller.get_blocks_with_storage(&ids) {
+            Ok(blocks_with_storage) => blocks_with_storage,
+            Err(e) => return Err(ApiError::ConsensusError(e.to_string()).into()),
+        };
         let res = ids
             .into_iter()
-            .zip(blocks)
-            .zip(block_statuses)
-            .map(|((id, content), graph_status)| BlockInfo {
-                id,
-                content: Some(BlockInfoContent {
-                    is_final: graph_status == BlockGraphStatus::Final,
-                    is_in_blockclique: graph_status == BlockGraphStatus::ActiveInBlockclique,
-                    is_candidate: graph_status == BlockGraphStatus::ActiveInBlockclique
-                        || graph_status == BlockGraphStatus::ActiveInAlternativeCliques,
-                    is_discarded: graph_status == BlockGraphStatus::Discarded,
-                    block: content,
-                }),
+            .zip(blocks_with_storage)
+            .filter_map(|(id, block_with_storage)| {
+                let (graph_status, storage) = block_with_storage?;
+                let content: Block = storage.read_blocks().get(&id)?.content.clone();
+                Some(BlockInfo {
+                    id,
+                    content: Some(BlockInfoContent {
+                        is_final: graph_status == BlockGraphStatus::Final,
+                        is_in_blockclique: graph_status == BlockGraphStatus::ActiveInBlockclique,
+                        is_candidate: graph_status == BlockGraphStatus::ActiveInBlockclique
+                            || graph_status == BlockGraphStatus::ActiveInAlternativeCliques,
+                        is_discarded: graph_status == BlockGraphStatus::Discarded,
+                        block: content,
+                    }),
+                })
             })
             .collect();
         Ok(res)
@@ -727,12 +769,16 @@ impl MassaRpcServer for API<Public> {
             Err(e) => return Err(ApiError::ModelsError(e).into()),
         };
 
-        let graph = match self
-            .0
-            .consensus_controller
-            .get_block_graph_status(start_slot, end_slot)
-        {
+        // the methods do not see the connection of their caller: the public API is a single caller
+        let graph = match self.0.consensus_controller.get_block_graph_status_as(
+            &QueryCaller::new(PUBLIC_API_CALLER),
+            start_slot,
+            end_slot,
+        ) {
             Ok(graph) => graph,
+            Err(e @ ConsensusError::QuotaExceeded { .. }) => {
+                return Err(ApiError::QuotaExceeded(e.to_string()).into())
+            }
             Err(e) => return Err(ApiError::ConsensusError(e.to_string()).into()),
         };
 
@@ -942,6 +988,11 @@ impl MassaRpcServer for API<Public> {
                     .map(|(slot, amount)| SlotAmount { slot, amount })
                     .collect::<Vec<_>>(),
 
+                // payout addresses
+                final_payout_address: execution_infos.final_payout_address,
+                candidate_payout_address: execution_infos.candidate_payout_address,
+                payout_address_sources: execution_infos.payout_address_sources,
+
                 // selector info
                 next_block_draws,
                 next_endorsement_draws,
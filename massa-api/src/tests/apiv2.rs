@@ -1,4 +1,4 @@
-use std::{collections::BTreeMap, net::SocketAddr, str::FromStr, time::Duration};
+use std::{collections::BTreeMap, net::SocketAddr, str::FromStr, sync::Arc, time::Duration};
 
 use jsonrpsee::{
     async_client::ClientBuilder,
@@ -60,15 +60,17 @@ async fn get_next_block_best_parents() {
     let (mut api_server, api_config) = get_apiv2_server(&addr);
 
     let mut consensus_ctrl = MockConsensusController::new();
-    consensus_ctrl.expect_get_best_parents().returning(|| {
-        vec![(
-            massa_models::block_id::BlockId::from_str(
-                "B12oYMQEAX35HPeDVgGdW2fYRtDs4UJTpeXqW75QPYCdEdPUZ9oV",
-            )
-            .unwrap(),
-            100,
-        )]
-    });
+    consensus_ctrl
+        .expect_get_best_parents_snapshot()
+        .returning(|| {
+            Arc::new(vec![(
+                massa_models::block_id::BlockId::from_str(
+                    "B12oYMQEAX35HPeDVgGdW2fYRtDs4UJTpeXqW75QPYCdEdPUZ9oV",
+                )
+                .unwrap(),
+                100,
+            )])
+        });
 
     api_server.0.consensus_controller = Box::new(consensus_ctrl);
 
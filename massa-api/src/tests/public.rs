@@ -5,6 +5,7 @@ use std::{
     collections::{BTreeMap, HashMap},
     net::{IpAddr, SocketAddr},
     str::FromStr,
+    sync::Arc,
 };
 
 use jsonrpsee::{
@@ -74,6 +75,7 @@ async fn get_status() {
         final_executed_operations_count: 0,
         active_cursor: Slot::new(0, 0),
         final_cursor: Slot::new(0, 0),
+        pos_stats: Default::default(),
     });
 
     let mut consensus_ctrl = MockConsensusController::new();
@@ -84,6 +86,11 @@ async fn get_status() {
             final_block_count: 50,
             stale_block_count: 40,
             clique_count: 30,
+            pruned_clique_count: 0,
+            duplicate_block_count: 0,
+            header_only_count: 0,
+            expired_header_count: 0,
+            execution_finality_gap: None,
         })
     });
 
@@ -141,8 +148,8 @@ async fn get_cliques() {
 
     let mut consensus_ctrl = MockConsensusController::new();
     consensus_ctrl
-        .expect_get_cliques()
-        .returning(|| vec![Clique::default()]);
+        .expect_get_cliques_snapshot()
+        .returning(|| Arc::new(vec![Clique::default()]));
 
     api_public.0.consensus_controller = Box::new(consensus_ctrl);
 
@@ -277,11 +284,17 @@ async fn get_blocks() {
     let block = create_block(&keypair);
 
     api_public.0.storage.store_block(block.clone());
+    let storage = api_public.0.storage.clone();
 
     let mut consensus_ctrl = MockConsensusController::new();
     consensus_ctrl
-        .expect_get_block_statuses()
-        .returning(|param| param.iter().map(|_| BlockGraphStatus::Final).collect());
+        .expect_get_blocks_with_storage()
+        .returning(move |param| {
+            Ok(param
+                .iter()
+                .map(|_| Some((BlockGraphStatus::Final, storage.clone())))
+                .collect())
+        });
 
     api_public.0.consensus_controller = Box::new(consensus_ctrl);
 
@@ -369,8 +382,8 @@ async fn get_graph_interval() {
 
     let mut consensus_ctrl = MockConsensusController::new();
     consensus_ctrl
-        .expect_get_block_graph_status()
-        .returning(|_start, _end| {
+        .expect_get_block_graph_status_as()
+        .returning(|_caller, _start, _end| {
             let block = create_block(&KeyPair::generate(0).unwrap());
             let id = block.id;
 
@@ -381,6 +394,7 @@ async fn get_graph_interval() {
                     header: block.content.header.clone(),
                     children: vec![],
                     is_final: false,
+                    provenance: None,
                 },
             );
 
@@ -631,6 +645,7 @@ async fn execute_read_only_bytecode() {
                     block_info: None,
                     state_changes: massa_final_state::StateChanges::default(),
                     events: massa_execution_exports::EventStore::default(),
+                    scheduled_credits: Default::default(),
                 },
                 gas_cost: 100,
                 call_result: "toto".as_bytes().to_vec(),
@@ -711,6 +726,7 @@ async fn execute_read_only_call() {
                     block_info: None,
                     state_changes: massa_final_state::StateChanges::default(),
                     events: massa_execution_exports::EventStore::default(),
+                    scheduled_credits: Default::default(),
                 },
                 gas_cost: 100,
                 call_result: "toto".as_bytes().to_vec(),
@@ -772,6 +788,9 @@ async fn get_addresses() {
                 candidate_roll_count: 12,
                 candidate_datastore_keys: std::collections::BTreeSet::new(),
                 future_deferred_credits: BTreeMap::new(),
+                final_payout_address: None,
+                candidate_payout_address: None,
+                payout_address_sources: vec![],
                 cycle_infos: vec![],
             })
             .collect()
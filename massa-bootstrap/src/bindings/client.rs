@@ -3,8 +3,8 @@
 use crate::bindings::{BindingReadExact, BindingWriteExact};
 use crate::error::BootstrapError;
 use crate::messages::{
-    BootstrapClientMessage, BootstrapClientMessageSerializer, BootstrapServerMessage,
-    BootstrapServerMessageDeserializer,
+    handshake_has_pos_stream_version, BootstrapClientMessage, BootstrapClientMessageSerializer,
+    BootstrapServerMessage, BootstrapServerMessageDeserializer, MAX_POS_STREAM_VERSION,
 };
 use crate::settings::BootstrapClientConfig;
 use massa_hash::Hash;
@@ -64,7 +64,7 @@ impl BootstrapClientBinder {
     /// Performs a handshake. Should be called after connection
     /// NOT cancel-safe
     pub fn handshake(&mut self, version: Version) -> Result<(), BootstrapError> {
-        // send version and randomn bytes
+        // send version and randomn bytes, the first one holding the highest supported PoS stream version
         let msg_hash = {
             let mut version_ser = Vec::new();
             self.version_serializer
@@ -73,6 +73,11 @@ impl BootstrapClientBinder {
                 vec![0u8; version_ser.len() + self.cfg.randomness_size_bytes];
             version_random_bytes[..version_ser.len()].clone_from_slice(&version_ser);
             StdRng::from_entropy().fill_bytes(&mut version_random_bytes[version_ser.len()..]);
+            if handshake_has_pos_stream_version(&version) {
+                if let Some(pos_stream_version) = version_random_bytes.get_mut(version_ser.len()) {
+                    *pos_stream_version = MAX_POS_STREAM_VERSION;
+                }
+            }
             self.write_all_timeout(&version_random_bytes, None)
                 .map_err(|(e, _)| e)?;
             Hash::compute_from(&version_random_bytes)
@@ -3,8 +3,9 @@
 use crate::bindings::BindingReadExact;
 use crate::error::BootstrapError;
 use crate::messages::{
-    BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapServerMessage,
-    BootstrapServerMessageSerializer,
+    handshake_has_pos_stream_version, negotiate_pos_stream_version, BootstrapClientMessage,
+    BootstrapClientMessageDeserializer, BootstrapServerMessage, BootstrapServerMessageSerializer,
+    MAX_POS_STREAM_VERSION, MIN_POS_STREAM_VERSION,
 };
 use crate::settings::BootstrapSrvBindCfg;
 use massa_hash::Hash;
@@ -92,14 +93,17 @@ impl BootstrapServerBinder {
     }
     /// Performs a handshake. Should be called after connection
     /// MUST always be followed by a send of the `BootstrapMessage::BootstrapTime`
+    ///
+    /// Returns the PoS stream version negotiated with the client
     pub fn handshake_timeout(
         &mut self,
         version: Version,
         duration: Option<Duration>,
-    ) -> Result<(), BootstrapError> {
+    ) -> Result<u8, BootstrapError> {
         let deadline = duration.map(|d| Instant::now() + d);
-        // read version and random bytes, send signature
-        let msg_hash = {
+        // read version and random bytes, the first one holding the highest PoS stream version
+        // supported by the client if its version carries it, send signature
+        let (msg_hash, pos_stream_version) = {
             let mut version_bytes = Vec::new();
             self.version_serializer
                 .serialize(&version, &mut version_bytes)?;
@@ -113,13 +117,28 @@ impl BootstrapServerBinder {
             if !received_version.is_compatible(&version) {
                 return Err(BootstrapError::IncompatibleVersionError(format!("Received a bad incompatible version in handshake. (excepted: {}, received: {})", version, received_version)));
             }
-            Hash::compute_from(&msg_bytes)
+            let remote_pos_stream_version = if handshake_has_pos_stream_version(&received_version) {
+                msg_bytes
+                    .get(version_bytes.len())
+                    .copied()
+                    .unwrap_or(MIN_POS_STREAM_VERSION)
+            } else {
+                MIN_POS_STREAM_VERSION
+            };
+            let Some(pos_stream_version) = negotiate_pos_stream_version(remote_pos_stream_version)
+            else {
+                return Err(BootstrapError::IncompatibleVersionError(format!(
+                    "No common PoS stream version in handshake. (supported: {}..={}, received max: {})",
+                    MIN_POS_STREAM_VERSION, MAX_POS_STREAM_VERSION, remote_pos_stream_version
+                )));
+            };
+            (Hash::compute_from(&msg_bytes), pos_stream_version)
         };
 
         // save prev sig
         self.prev_message = Some(msg_hash);
 
-        Ok(())
+        Ok(pos_stream_version)
     }
 
     pub fn send_msg(
@@ -1,9 +1,17 @@
 use humantime::format_duration;
-use massa_db_exports::DBBatch;
+use massa_consensus_exports::bootstrapable_graph::BootstrapableGraph;
+use massa_db_exports::{
+    DBBatch, MassaIteratorMode, CYCLE_HISTORY_PREFIX, DEFERRED_CREDITS_PREFIX,
+    PAYOUT_ADDRESSES_PREFIX, STATE_CF, STATE_HASH_INITIAL_BYTES,
+};
 use massa_final_state::{FinalStateController, FinalStateError};
+use massa_hash::HashXof;
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
-use massa_models::{node::NodeId, slot::Slot, streaming_step::StreamingStep, version::Version};
+use massa_models::{
+    block_id::BlockId, node::NodeId, prehash::PreHashSet, slot::Slot,
+    streaming_step::StreamingStep, version::Version,
+};
 use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use massa_versioning::versioning::{ComponentStateTypeId, MipInfo, MipState, StateAtError};
@@ -25,8 +33,11 @@ use tracing::{debug, info, warn};
 use crate::{
     bindings::BootstrapClientBinder,
     error::BootstrapError,
-    messages::{BootstrapClientMessage, BootstrapServerMessage},
-    settings::IpType,
+    messages::{
+        BootstrapClientMessage, BootstrapServerMessage, MAX_POS_STREAM_VERSION,
+        MIN_POS_STREAM_VERSION, MULTIPLEXED_STREAMS_VERSION, RANGED_STATE_STREAM_VERSION,
+    },
+    settings::{FastSyncCheckpoint, IpType, ResyncTarget},
     BootstrapConfig, GlobalBootstrapState,
 };
 
@@ -70,6 +81,7 @@ fn stream_final_state_and_consensus(
     client: &mut BootstrapClientBinder,
     next_bootstrap_message: &mut BootstrapClientMessage,
     global_bootstrap_state: &mut GlobalBootstrapState,
+    negotiated_pos_stream_version: u8,
 ) -> Result<(), BootstrapError> {
     if let BootstrapClientMessage::AskBootstrapPart { .. } = &next_bootstrap_message {
         client.send_timeout(
@@ -81,6 +93,7 @@ fn stream_final_state_and_consensus(
             match client.next_timeout(Some(cfg.read_timeout.to_duration()))? {
                 BootstrapServerMessage::BootstrapPart {
                     slot,
+                    pos_stream_version,
                     state_part,
                     versioning_part,
                     consensus_part,
@@ -88,6 +101,13 @@ fn stream_final_state_and_consensus(
                     last_start_period,
                     last_slot_before_downtime,
                 } => {
+                    if pos_stream_version != negotiated_pos_stream_version {
+                        return Err(BootstrapError::IncompatibleVersionError(format!(
+                            "received a bootstrap part with PoS stream version {} (negotiated: {})",
+                            pos_stream_version, negotiated_pos_stream_version
+                        )));
+                    }
+
                     // Set final state
                     let mut write_final_state = global_bootstrap_state.final_state.write();
 
@@ -109,30 +129,23 @@ fn stream_final_state_and_consensus(
                                 e
                             ))
                         })?;
+                    drop(write_final_state);
 
                     // Set consensus blocks
-                    if let Some(graph) = global_bootstrap_state.graph.as_mut() {
-                        // Extend the final blocks with the received part
-                        graph.final_blocks.extend(consensus_part.final_blocks);
-                        // Remove every outdated block
-                        graph.final_blocks.retain(|block_export| {
-                            !consensus_outdated_ids.contains(&block_export.block.id)
-                        });
-                    } else {
-                        global_bootstrap_state.graph = Some(consensus_part);
-                    }
-                    let last_consensus_step = StreamingStep::Ongoing(
-                        // Note that this unwrap call is safe because of the above conditional statement
-                        global_bootstrap_state
-                            .graph
-                            .as_ref()
-                            .unwrap()
-                            .final_blocks
-                            .iter()
-                            .map(|b_export| b_export.block.id)
-                            .collect(),
+                    let last_consensus_step = extend_bootstrap_graph(
+                        global_bootstrap_state,
+                        consensus_part,
+                        consensus_outdated_ids,
                     );
 
+                    // Keep streaming the same range of the state
+                    let state_end_key = match next_bootstrap_message {
+                        BootstrapClientMessage::AskBootstrapPart { state_end_key, .. } => {
+                            state_end_key.take()
+                        }
+                        _ => None,
+                    };
+
                     // Set new message in case of disconnection
                     *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
                         last_slot: Some(slot),
@@ -140,6 +153,7 @@ fn stream_final_state_and_consensus(
                         last_versioning_step,
                         last_consensus_step,
                         send_last_start_period: false,
+                        state_end_key,
                     };
 
                     // Logs for an easier diagnostic if needed
@@ -148,7 +162,50 @@ fn stream_final_state_and_consensus(
                         next_bootstrap_message
                     );
                 }
+                BootstrapServerMessage::BootstrapConsensusPart {
+                    consensus_part,
+                    consensus_outdated_ids,
+                } if negotiated_pos_stream_version >= MULTIPLEXED_STREAMS_VERSION => {
+                    // the consensus graph is streamed on its own sub-channel, with its own cursor
+                    let consensus_step = extend_bootstrap_graph(
+                        global_bootstrap_state,
+                        consensus_part,
+                        consensus_outdated_ids,
+                    );
+                    if let BootstrapClientMessage::AskBootstrapPart {
+                        last_consensus_step,
+                        ..
+                    } = next_bootstrap_message
+                    {
+                        *last_consensus_step = consensus_step;
+                    }
+                }
                 BootstrapServerMessage::BootstrapFinished => {
+                    // a resync streams its ranges of the state one after the other
+                    if let BootstrapClientMessage::AskBootstrapPart {
+                        last_slot,
+                        last_consensus_step,
+                        state_end_key: Some(end_key),
+                        ..
+                    } = &*next_bootstrap_message
+                    {
+                        if let Some((start_key, end_key)) = next_pos_resync_range(end_key) {
+                            *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
+                                last_slot: *last_slot,
+                                last_state_step: StreamingStep::Ongoing(start_key),
+                                last_versioning_step: StreamingStep::Finished(None),
+                                last_consensus_step: last_consensus_step.clone(),
+                                send_last_start_period: false,
+                                state_end_key: Some(end_key),
+                            };
+                            client.send_timeout(
+                                next_bootstrap_message,
+                                Some(cfg.write_timeout.to_duration()),
+                            )?;
+                            continue;
+                        }
+                    }
+
                     info!("State bootstrap complete");
                     // Set next bootstrap message
                     *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPeers;
@@ -166,6 +223,7 @@ fn stream_final_state_and_consensus(
                     return Ok(());
                 }
                 BootstrapServerMessage::SlotTooOld => {
+                    // also the case when the fast sync checkpoint is older than the history of the server
                     info!("Slot is too old retry bootstrap from scratch");
                     *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
                         last_slot: None,
@@ -173,6 +231,7 @@ fn stream_final_state_and_consensus(
                         last_versioning_step: StreamingStep::Started,
                         last_consensus_step: StreamingStep::Started,
                         send_last_start_period: true,
+                        state_end_key: None,
                     };
                     let mut write_final_state = global_bootstrap_state.final_state.write();
                     write_final_state.reset();
@@ -197,6 +256,36 @@ fn stream_final_state_and_consensus(
     }
 }
 
+/// Add a part of the consensus graph to the bootstrap graph and remove its outdated blocks
+///
+/// # Returns
+/// The cursor of the consensus stream, holding the ids of the blocks of the bootstrap graph
+fn extend_bootstrap_graph(
+    global_bootstrap_state: &mut GlobalBootstrapState,
+    consensus_part: BootstrapableGraph,
+    consensus_outdated_ids: PreHashSet<BlockId>,
+) -> StreamingStep<PreHashSet<BlockId>> {
+    let graph = match global_bootstrap_state.graph.as_mut() {
+        Some(graph) => {
+            // Extend the final blocks with the received part
+            graph.final_blocks.extend(consensus_part.final_blocks);
+            // Remove every outdated block
+            graph
+                .final_blocks
+                .retain(|block_export| !consensus_outdated_ids.contains(&block_export.block.id));
+            graph
+        }
+        None => global_bootstrap_state.graph.insert(consensus_part),
+    };
+    StreamingStep::Ongoing(
+        graph
+            .final_blocks
+            .iter()
+            .map(|b_export| b_export.block.id)
+            .collect(),
+    )
+}
+
 /// Gets the state from a bootstrap server (internal private function)
 /// needs to be CANCELLABLE
 pub(crate) fn bootstrap_from_server(
@@ -239,11 +328,13 @@ pub(crate) fn bootstrap_from_server(
 
     // First, clock and version.
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
-    let server_time = match client.next_timeout(Some(cfg.read_timeout.into())) {
+    let (server_time, pos_stream_version) = match client.next_timeout(Some(cfg.read_timeout.into()))
+    {
         Err(e) => return Err(e),
         Ok(BootstrapServerMessage::BootstrapTime {
             server_time,
             version,
+            pos_stream_version,
         }) => {
             if !our_version.is_compatible(&version) {
                 return Err(BootstrapError::IncompatibleVersionError(format!(
@@ -251,7 +342,26 @@ pub(crate) fn bootstrap_from_server(
                     version, our_version
                 )));
             }
-            server_time
+            if !(MIN_POS_STREAM_VERSION..=MAX_POS_STREAM_VERSION).contains(&pos_stream_version) {
+                return Err(BootstrapError::IncompatibleVersionError(format!(
+                    "remote negotiated unsupported PoS stream version: {} (supported: {}..={})",
+                    pos_stream_version, MIN_POS_STREAM_VERSION, MAX_POS_STREAM_VERSION
+                )));
+            }
+            // only the servers knowing ranged state streams can serve a resync
+            if let BootstrapClientMessage::AskBootstrapPart {
+                state_end_key: Some(_),
+                ..
+            } = &*next_bootstrap_message
+            {
+                if pos_stream_version < RANGED_STATE_STREAM_VERSION {
+                    return Err(BootstrapError::IncompatibleVersionError(format!(
+                        "remote negotiated PoS stream version {} but resynchronizing requires at least {}",
+                        pos_stream_version, RANGED_STATE_STREAM_VERSION
+                    )));
+                }
+            }
+            (server_time, pos_stream_version)
         }
         Ok(BootstrapServerMessage::BootstrapError { error }) => {
             return Err(BootstrapError::ReceivedError(error))
@@ -296,6 +406,7 @@ pub(crate) fn bootstrap_from_server(
                     client,
                     next_bootstrap_message,
                     global_bootstrap_state,
+                    pos_stream_version,
                 )?;
             }
             BootstrapClientMessage::AskBootstrapPeers => {
@@ -478,14 +589,38 @@ pub fn get_state(
     // we filter the bootstrap list to keep only the ip addresses we are compatible with
     let filtered_bootstrap_list = get_bootstrap_list_iter(bootstrap_config)?;
 
-    let mut next_bootstrap_message: BootstrapClientMessage =
-        BootstrapClientMessage::AskBootstrapPart {
+    let mut next_bootstrap_message: BootstrapClientMessage = match (
+        bootstrap_config.fast_sync_checkpoint,
+        bootstrap_config.resync,
+    ) {
+        (Some(checkpoint), _) => {
+            check_fast_sync_snapshot(&checkpoint, &*final_state.read())?;
+            info!(
+                "Fast sync from the trusted final state at slot {}",
+                checkpoint.slot
+            );
+            // only the changes of the final state since the checkpoint are streamed,
+            // the server answering `SlotTooOld` if they are not in its history anymore.
+            // The consensus graph is streamed in full, as in a regular bootstrap.
+            BootstrapClientMessage::AskBootstrapPart {
+                last_slot: Some(checkpoint.slot),
+                last_state_step: StreamingStep::Finished(None),
+                last_versioning_step: StreamingStep::Finished(None),
+                last_consensus_step: StreamingStep::Started,
+                send_last_start_period: true,
+                state_end_key: None,
+            }
+        }
+        (None, Some(target)) => start_resync(target, &mut *final_state.write()),
+        (None, None) => BootstrapClientMessage::AskBootstrapPart {
             last_slot: None,
             last_state_step: StreamingStep::Started,
             last_versioning_step: StreamingStep::Started,
             last_consensus_step: StreamingStep::Started,
             send_last_start_period: true,
-        };
+            state_end_key: None,
+        },
+    };
     let mut global_bootstrap_state = GlobalBootstrapState::new(final_state);
 
     let limit = bootstrap_config.rate_limit;
@@ -535,7 +670,12 @@ pub fn get_state(
                                 Some(bootstrap_config.write_error_timeout.into()),
                             );
                         }
-                        Ok(()) => return Ok(global_bootstrap_state),
+                        Ok(()) => {
+                            if bootstrap_config.resync.is_some() {
+                                check_resync(bootstrap_config, &global_bootstrap_state)?;
+                            }
+                            return Ok(global_bootstrap_state);
+                        }
                     }
                 }
                 Err(e) => {
@@ -579,6 +719,145 @@ pub fn get_state(
     }
 }
 
+/// Check that the local final state is the trusted snapshot of the fast sync checkpoint
+pub(crate) fn check_fast_sync_snapshot(
+    checkpoint: &FastSyncCheckpoint,
+    final_state: &dyn FinalStateController,
+) -> Result<(), BootstrapError> {
+    let slot = final_state.get_slot();
+    if slot != checkpoint.slot {
+        return Err(BootstrapError::UntrustedSnapshot(format!(
+            "the final state is at slot {} instead of {}",
+            slot, checkpoint.slot
+        )));
+    }
+    let fingerprint = final_state.get_fingerprint();
+    if fingerprint != checkpoint.fingerprint {
+        return Err(BootstrapError::UntrustedSnapshot(format!(
+            "the fingerprint of the final state is {} instead of {}",
+            fingerprint, checkpoint.fingerprint
+        )));
+    }
+    Ok(())
+}
+
+/// Cursor and end key of the streams of the PoS entries resynchronized by `ResyncTarget::Pos`, streamed one
+/// after the other: the entries of the cycle history and of the deferred credits, whose prefixes are adjacent
+/// in the key order, then the entries of the payout addresses
+pub(crate) fn pos_resync_ranges() -> [(Vec<u8>, Vec<u8>); 2] {
+    [
+        prefix_range(CYCLE_HISTORY_PREFIX, DEFERRED_CREDITS_PREFIX),
+        prefix_range(PAYOUT_ADDRESSES_PREFIX, PAYOUT_ADDRESSES_PREFIX),
+    ]
+}
+
+/// Cursor and end key of the stream of the entries whose prefixes are between `first_prefix` and `last_prefix`, included
+fn prefix_range(first_prefix: &str, last_prefix: &str) -> (Vec<u8>, Vec<u8>) {
+    // every key of the first prefix is after it without the trailing separator
+    let start_key = first_prefix.trim_end_matches('/').as_bytes().to_vec();
+    // and every key of the last prefix is before it with an incremented separator
+    let mut end_key = last_prefix.as_bytes().to_vec();
+    if let Some(separator) = end_key.last_mut() {
+        *separator += 1;
+    }
+    (start_key, end_key)
+}
+
+/// Get the PoS resync range streamed after the one ending at `end_key`, if any
+fn next_pos_resync_range(end_key: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let mut ranges = pos_resync_ranges().into_iter();
+    ranges.find(|(_, range_end_key)| range_end_key == end_key)?;
+    ranges.next()
+}
+
+/// Prepare the local final state for the resync of `target`, and get the first message asking for it.
+/// The final state stays attached to its slot, the server streaming the changes of all its entries since then.
+fn start_resync(
+    target: ResyncTarget,
+    final_state: &mut dyn FinalStateController,
+) -> BootstrapClientMessage {
+    let slot = final_state.get_slot();
+    info!(
+        "Resynchronizing the {:?} state on top of the final state at slot {}",
+        target, slot
+    );
+    let (last_state_step, state_end_key) = match target {
+        ResyncTarget::Consensus => (StreamingStep::Finished(None), None),
+        ResyncTarget::Pos => {
+            // the local PoS entries are dropped, so that none of the corrupted ones survives the resync
+            let mut db = final_state.get_database().write();
+            db.delete_prefix(CYCLE_HISTORY_PREFIX, STATE_CF, None);
+            db.delete_prefix(DEFERRED_CREDITS_PREFIX, STATE_CF, None);
+            db.delete_prefix(PAYOUT_ADDRESSES_PREFIX, STATE_CF, None);
+            let [(start_key, end_key), _] = pos_resync_ranges();
+            (StreamingStep::Ongoing(start_key), Some(end_key))
+        }
+    };
+    BootstrapClientMessage::AskBootstrapPart {
+        last_slot: Some(slot),
+        last_state_step,
+        last_versioning_step: StreamingStep::Finished(None),
+        last_consensus_step: StreamingStep::Started,
+        send_last_start_period: true,
+        state_end_key,
+    }
+}
+
+/// Check that the resynchronized components are consistent with the kept ones
+fn check_resync(
+    bootstrap_config: &BootstrapConfig,
+    global_bootstrap_state: &GlobalBootstrapState,
+) -> Result<(), BootstrapError> {
+    let final_state = global_bootstrap_state.final_state.read();
+    let slot = final_state.get_slot();
+
+    // the final state hash, updated along with the streamed entries, must be the one of all the entries
+    {
+        let db = final_state.get_database().read();
+        let mut entries_hash = HashXof(*STATE_HASH_INITIAL_BYTES);
+        for (key, value) in db.iterator_cf(STATE_CF, MassaIteratorMode::Start) {
+            entries_hash ^= HashXof::compute_from_tuple(&[key.as_slice(), value.as_slice()]);
+        }
+        if entries_hash != db.get_xof_db_hash() {
+            return Err(BootstrapError::ResyncMismatch(format!(
+                "the final state hash at slot {} is not the hash of its entries",
+                slot
+            )));
+        }
+    }
+
+    // the PoS state must cover the cycle of the final state
+    let cycle = slot.get_cycle(bootstrap_config.periods_per_cycle);
+    if final_state
+        .get_pos_state()
+        .is_cycle_complete(cycle)
+        .is_none()
+    {
+        return Err(BootstrapError::ResyncMismatch(format!(
+            "the cycle history does not contain the cycle {} of the final state at slot {}",
+            cycle, slot
+        )));
+    }
+
+    // the final blocks must not be ahead of the final state
+    let Some(graph) = global_bootstrap_state.graph.as_ref() else {
+        return Err(BootstrapError::ResyncMismatch(
+            "no final block was bootstrapped".to_string(),
+        ));
+    };
+    if let Some(block) = graph
+        .final_blocks
+        .iter()
+        .find(|b_export| b_export.block.content.header.content.slot > slot)
+    {
+        return Err(BootstrapError::ResyncMismatch(format!(
+            "the final block {} is after the slot {} of the final state",
+            block.block.id, slot
+        )));
+    }
+    Ok(())
+}
+
 fn get_bootstrap_list_iter(
     bootstrap_config: &BootstrapConfig,
 ) -> Result<Vec<(SocketAddr, NodeId)>, BootstrapError> {
@@ -67,6 +67,10 @@ pub enum BootstrapError {
     WhiteListed(String),
     /// The bootstrap process ended prematurely - e.g. too much time elapsed
     Interrupted(String),
+    /// the local final state does not match the fast sync checkpoint: {0}
+    UntrustedSnapshot(String),
+    /// the resynchronized state is inconsistent with the kept one: {0}
+    ResyncMismatch(String),
 }
 
 /// # Platform-specific behavior
@@ -34,12 +34,17 @@ pub mod white_black_list;
 pub use client::{get_state, DefaultConnector};
 pub use listener::BootstrapTcpListener;
 pub use messages::{
-    BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapClientMessageSerializer,
-    BootstrapServerMessage, BootstrapServerMessageDeserializer, BootstrapServerMessageSerializer,
+    handshake_has_pos_stream_version, negotiate_pos_stream_version, BootstrapClientMessage,
+    BootstrapClientMessageDeserializer, BootstrapClientMessageSerializer, BootstrapServerMessage,
+    BootstrapServerMessageDeserializer, BootstrapServerMessageSerializer, MAX_POS_STREAM_VERSION,
+    MIN_POS_STREAM_VERSION, MULTIPLEXED_STREAMS_VERSION, POS_STREAM_HANDSHAKE_MIN_VERSION,
+    RANGED_STATE_STREAM_VERSION,
 };
 pub use server::{start_bootstrap_server, BootstrapManager};
 pub use settings::IpType;
-pub use settings::{BootstrapConfig, BootstrapServerMessageDeserializerArgs};
+pub use settings::{
+    BootstrapConfig, BootstrapServerMessageDeserializerArgs, FastSyncCheckpoint, ResyncTarget,
+};
 
 #[cfg(test)]
 pub(crate) mod tests;
@@ -40,6 +40,47 @@ use num_enum::{IntoPrimitive, TryFromPrimitive};
 use std::convert::TryInto;
 use std::ops::Bound::{Excluded, Included};
 
+/// Lowest version of the PoS bootstrap streams format (deferred credits, cycle history...)
+/// that this node is able to read
+pub const MIN_POS_STREAM_VERSION: u8 = 0;
+
+/// Highest version of the PoS bootstrap streams format that this node is able to read and write.
+/// Must be bumped each time the serialization of a PoS stream, or of the requests for it, changes.
+pub const MAX_POS_STREAM_VERSION: u8 = 2;
+
+/// PoS stream version from which a client can ask for a range of the state keys only,
+/// with the `state_end_key` of `BootstrapClientMessage::AskBootstrapPart`
+pub const RANGED_STATE_STREAM_VERSION: u8 = 1;
+
+/// PoS stream version from which the server streams the consensus graph and the final state on
+/// independent sub-channels: the consensus parts are sent in `BootstrapServerMessage::BootstrapConsensusPart`
+/// as soon as they are ready, and the parts of the final state carry an empty consensus part
+pub const MULTIPLEXED_STREAMS_VERSION: u8 = 2;
+
+/// Lowest node version, as `(major, minor)`, from which the handshake of a bootstrap client carries
+/// the highest PoS stream version it supports, in place of its first random byte so that the handshake
+/// length is unchanged. Older clients are served with `MIN_POS_STREAM_VERSION`.
+pub const POS_STREAM_HANDSHAKE_MIN_VERSION: (u32, u32) = (2, 2);
+
+/// Whether the bootstrap handshake of a client running `version` carries its highest PoS stream version
+pub fn handshake_has_pos_stream_version(version: &Version) -> bool {
+    (version.major(), version.minor()) >= POS_STREAM_HANDSHAKE_MIN_VERSION
+}
+
+/// Negotiates the PoS stream version to use with a remote node.
+///
+/// # Arguments
+/// * `remote_max_version`: highest PoS stream version supported by the remote node
+///
+/// # Returns
+/// The highest version supported by both nodes, or None if there is none
+pub fn negotiate_pos_stream_version(remote_max_version: u8) -> Option<u8> {
+    let version = std::cmp::min(remote_max_version, MAX_POS_STREAM_VERSION);
+    (MIN_POS_STREAM_VERSION..=MAX_POS_STREAM_VERSION)
+        .contains(&version)
+        .then_some(version)
+}
+
 /// Messages used during bootstrap by server
 #[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
@@ -50,6 +91,8 @@ pub enum BootstrapServerMessage {
         server_time: MassaTime,
         /// The version of the bootstrap server.
         version: Version,
+        /// The PoS stream version negotiated during the handshake
+        pos_stream_version: u8,
     },
     /// Bootstrap peers
     BootstrapPeers {
@@ -60,6 +103,8 @@ pub enum BootstrapServerMessage {
     BootstrapPart {
         /// Slot the state changes are attached to
         slot: Slot,
+        /// Version of the PoS streams format used in `state_part`
+        pos_stream_version: u8,
         /// Part of the state in a serialized way
         state_part: StreamBatch<Slot>,
         /// Part of the state (specific to versioning) in a serialized way
@@ -73,6 +118,14 @@ pub enum BootstrapServerMessage {
         /// Last Slot before downtime for network restart management
         last_slot_before_downtime: Option<Option<Slot>>,
     },
+    /// Part of the consensus graph, streamed independently of the final state,
+    /// see `MULTIPLEXED_STREAMS_VERSION`
+    BootstrapConsensusPart {
+        /// Part of the consensus graph
+        consensus_part: BootstrapableGraph,
+        /// Outdated block ids in the current consensus graph bootstrap
+        consensus_outdated_ids: PreHashSet<BlockId>,
+    },
     /// Message sent when the final state and consensus bootstrap are finished
     BootstrapFinished,
     /// Slot sent to get state changes is too old
@@ -90,6 +143,9 @@ impl ToString for BootstrapServerMessage {
             BootstrapServerMessage::BootstrapTime { .. } => "BootstrapTime".to_string(),
             BootstrapServerMessage::BootstrapPeers { .. } => "BootstrapPeers".to_string(),
             BootstrapServerMessage::BootstrapPart { .. } => "BootstrapPart".to_string(),
+            BootstrapServerMessage::BootstrapConsensusPart { .. } => {
+                "BootstrapConsensusPart".to_string()
+            }
             BootstrapServerMessage::BootstrapFinished => "BootstrapFinished".to_string(),
             BootstrapServerMessage::SlotTooOld => "SlotTooOld".to_string(),
             BootstrapServerMessage::BootstrapError { error } => {
@@ -108,6 +164,7 @@ enum MessageServerTypeId {
     FinalStateFinished = 3u32,
     SlotTooOld = 4u32,
     BootstrapError = 5u32,
+    ConsensusPart = 6u32,
 }
 
 /// Serializer for `BootstrapServerMessage`
@@ -168,6 +225,7 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
     /// let bootstrap_server_message = BootstrapServerMessage::BootstrapTime {
     ///    server_time: MassaTime::from_millis(0),
     ///    version: Version::from_str("TEST.1.10").unwrap(),
+    ///    pos_stream_version: 0,
     /// };
     /// let mut message_serialized = Vec::new();
     /// message_serializer.serialize(&bootstrap_server_message, &mut message_serialized).unwrap();
@@ -181,11 +239,13 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
             BootstrapServerMessage::BootstrapTime {
                 server_time,
                 version,
+                pos_stream_version,
             } => {
                 self.u32_serializer
                     .serialize(&u32::from(MessageServerTypeId::BootstrapTime), buffer)?;
                 self.time_serializer.serialize(server_time, buffer)?;
                 self.version_serializer.serialize(version, buffer)?;
+                buffer.push(*pos_stream_version);
             }
             BootstrapServerMessage::BootstrapPeers { peers } => {
                 self.u32_serializer
@@ -194,6 +254,7 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
             }
             BootstrapServerMessage::BootstrapPart {
                 slot,
+                pos_stream_version,
                 state_part,
                 versioning_part,
                 consensus_part,
@@ -206,6 +267,8 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
                     .serialize(&u32::from(MessageServerTypeId::FinalStatePart), buffer)?;
                 // slot
                 self.slot_serializer.serialize(slot, buffer)?;
+                // PoS stream version
+                buffer.push(*pos_stream_version);
                 // state new_elements
                 let mut state_new_element_buffer: Vec<u8> = Vec::new();
                 for (key, value) in state_part.new_elements.iter() {
@@ -287,6 +350,17 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
                 self.opt_last_slot_before_downtime_serializer
                     .serialize(last_slot_before_downtime, buffer)?;
             }
+            BootstrapServerMessage::BootstrapConsensusPart {
+                consensus_part,
+                consensus_outdated_ids,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::ConsensusPart), buffer)?;
+                self.bootstrapable_graph_serializer
+                    .serialize(consensus_part, buffer)?;
+                self.block_id_set_serializer
+                    .serialize(consensus_outdated_ids, buffer)?;
+            }
             BootstrapServerMessage::BootstrapFinished => {
                 self.u32_serializer
                     .serialize(&u32::from(MessageServerTypeId::FinalStateFinished), buffer)?;
@@ -431,6 +505,7 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
     /// let bootstrap_server_message = BootstrapServerMessage::BootstrapTime {
     ///    server_time: MassaTime::from_millis(0),
     ///    version: Version::from_str("TEST.1.10").unwrap(),
+    ///    pos_stream_version: 0,
     /// };
     /// let mut message_serialized = Vec::new();
     /// message_serializer.serialize(&bootstrap_server_message, &mut message_serialized).unwrap();
@@ -439,9 +514,11 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
     ///     BootstrapServerMessage::BootstrapTime {
     ///        server_time,
     ///        version,
+    ///        pos_stream_version,
     ///    } => {
     ///     assert_eq!(server_time, MassaTime::from_millis(0));
     ///     assert_eq!(version, Version::from_str("TEST.1.10").unwrap());
+    ///     assert_eq!(pos_stream_version, 0);
     ///   }
     ///   _ => panic!("Unexpected message"),
     /// }
@@ -472,13 +549,18 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     context("Failed version deserialization", |input| {
                         self.version_deserializer.deserialize(input)
                     }),
+                    context(
+                        "Failed pos_stream_version deserialization",
+                        nom::number::complete::be_u8,
+                    ),
                 ))
-                .map(
-                    |(server_time, version)| BootstrapServerMessage::BootstrapTime {
+                .map(|(server_time, version, pos_stream_version)| {
+                    BootstrapServerMessage::BootstrapTime {
                         server_time,
                         version,
-                    },
-                )
+                        pos_stream_version,
+                    }
+                })
                 .parse(input),
                 MessageServerTypeId::Peers => context("Failed peers deserialization", |input| {
                     self.peers_deserializer.deserialize(input)
@@ -489,6 +571,10 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     context("Failed slot deserialization", |input| {
                         self.slot_deserializer.deserialize(input)
                     }),
+                    context(
+                        "Failed pos_stream_version deserialization",
+                        nom::number::complete::be_u8,
+                    ),
                     context(
                         "Failed state_part deserialization",
                         tuple((
@@ -577,6 +663,7 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                 .map(
                     |(
                         slot,
+                        pos_stream_version,
                         (state_part_new_elems, state_part_updates, state_part_change_id),
                         (
                             versioning_part_new_elems,
@@ -603,6 +690,7 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
 
                         BootstrapServerMessage::BootstrapPart {
                             slot,
+                            pos_stream_version,
                             state_part,
                             versioning_part,
                             consensus_part,
@@ -613,6 +701,21 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     },
                 )
                 .parse(input),
+                MessageServerTypeId::ConsensusPart => tuple((
+                    context("Failed consensus_part deserialization", |input| {
+                        self.bootstrapable_graph_deserializer.deserialize(input)
+                    }),
+                    context("Failed consensus_outdated_ids deserialization", |input| {
+                        self.block_id_set_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(|(consensus_part, consensus_outdated_ids)| {
+                    BootstrapServerMessage::BootstrapConsensusPart {
+                        consensus_part,
+                        consensus_outdated_ids,
+                    }
+                })
+                .parse(input),
                 MessageServerTypeId::FinalStateFinished => {
                     Ok((input, BootstrapServerMessage::BootstrapFinished))
                 }
@@ -651,6 +754,10 @@ pub enum BootstrapClientMessage {
         last_consensus_step: StreamingStep<PreHashSet<BlockId>>,
         /// Should be true only for the first part, false later
         send_last_start_period: bool,
+        /// End of the range of state keys to stream, excluded: only the state entries before it are streamed,
+        /// along with the changes of all the entries. `None` to stream the state up to its end.
+        /// Only sent if the negotiated PoS stream version is at least `RANGED_STATE_STREAM_VERSION`
+        state_end_key: Option<Vec<u8>>,
     },
     /// Bootstrap error
     BootstrapError {
@@ -680,6 +787,7 @@ pub struct BootstrapClientMessageSerializer {
         PreHashSetSerializer<BlockId, BlockIdSerializer>,
     >,
     bool_serializer: BoolSerializer,
+    key_serializer: VecU8Serializer,
 }
 
 impl BootstrapClientMessageSerializer {
@@ -693,6 +801,7 @@ impl BootstrapClientMessageSerializer {
                 BlockIdSerializer::new(),
             )),
             bool_serializer: BoolSerializer::new(),
+            key_serializer: VecU8Serializer::new(),
         }
     }
 }
@@ -733,6 +842,7 @@ impl Serializer<BootstrapClientMessage> for BootstrapClientMessageSerializer {
                 last_versioning_step,
                 last_consensus_step,
                 send_last_start_period,
+                state_end_key,
             } => {
                 self.u32_serializer
                     .serialize(&u32::from(MessageClientTypeId::AskFinalStatePart), buffer)?;
@@ -746,6 +856,10 @@ impl Serializer<BootstrapClientMessage> for BootstrapClientMessageSerializer {
                         .serialize(last_consensus_step, buffer)?;
                     self.bool_serializer
                         .serialize(send_last_start_period, buffer)?;
+                    // only sent when set, for the messages of the clients streaming the whole state to stay unchanged
+                    if let Some(end_key) = state_end_key {
+                        self.key_serializer.serialize(end_key, buffer)?;
+                    }
                 }
             }
             BootstrapClientMessage::BootstrapError { error } => {
@@ -779,6 +893,7 @@ pub struct BootstrapClientMessageDeserializer {
         PreHashSetDeserializer<BlockId, BlockIdDeserializer>,
     >,
     bool_deserializer: BoolDeserializer,
+    key_deserializer: VecU8Deserializer,
 }
 
 impl BootstrapClientMessageDeserializer {
@@ -807,6 +922,10 @@ impl BootstrapClientMessageDeserializer {
                 ),
             ),
             bool_deserializer: BoolDeserializer::new(),
+            key_deserializer: VecU8Deserializer::new(
+                Included(0),
+                Included(max_datastore_key_length.into()),
+            ),
         }
     }
 }
@@ -863,6 +982,7 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                                 last_versioning_step: StreamingStep::Started,
                                 last_consensus_step: StreamingStep::Started,
                                 send_last_start_period: true,
+                                state_end_key: None,
                             },
                         ))
                     } else {
@@ -882,6 +1002,17 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                             context("Failed send_last_start_period deserialization", |input| {
                                 self.bool_deserializer.deserialize(input)
                             }),
+                            context(
+                                "Failed state_end_key deserialization",
+                                |input: &'a [u8]| {
+                                    if input.is_empty() {
+                                        return Ok((input, None));
+                                    }
+                                    self.key_deserializer
+                                        .deserialize(input)
+                                        .map(|(rest, end_key)| (rest, Some(end_key)))
+                                },
+                            ),
                         ))
                         .map(
                             |(
@@ -890,6 +1021,7 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                                 last_versioning_step,
                                 last_consensus_step,
                                 send_last_start_period,
+                                state_end_key,
                             )| {
                                 BootstrapClientMessage::AskBootstrapPart {
                                     last_slot: Some(last_slot),
@@ -897,6 +1029,7 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                                     last_versioning_step,
                                     last_consensus_step,
                                     send_last_start_period,
+                                    state_end_key,
                                 }
                             },
                         )
@@ -27,8 +27,10 @@
 
 use crossbeam::channel::tick;
 use humantime::format_duration;
-use massa_consensus_exports::{bootstrapable_graph::BootstrapableGraph, ConsensusController};
-use massa_db_exports::CHANGE_ID_DESER_ERROR;
+use massa_consensus_exports::{
+    bootstrapable_graph::BootstrapableGraph, query_quota::QueryCaller, ConsensusController,
+};
+use massa_db_exports::{MassaDBController, MassaDBError, StreamBatch, CHANGE_ID_DESER_ERROR};
 use massa_final_state::FinalStateController;
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
@@ -59,7 +61,10 @@ use crate::{
     bindings::BootstrapServerBinder,
     error::BootstrapError,
     listener::{BootstrapListenerStopHandle, PollEvent},
-    messages::{BootstrapClientMessage, BootstrapServerMessage},
+    messages::{
+        BootstrapClientMessage, BootstrapServerMessage, MULTIPLEXED_STREAMS_VERSION,
+        RANGED_STATE_STREAM_VERSION,
+    },
     white_black_list::SharedWhiteBlackList,
     BootstrapConfig,
 };
@@ -418,10 +423,13 @@ fn run_bootstrap_session(
 ) {
     debug!("running bootstrap for peer {}", remote_addr);
     let deadline = Instant::now() + config.bootstrap_timeout.to_duration();
+    // the consensus bootstrap parts are counted against the budget of the client address
+    let caller = QueryCaller::new(format!("bootstrap {}", remote_addr.ip()));
     // TODO: reinstate prevention of bootstrap slot camping. Deadline cancellation is one option
     let res = manage_bootstrap(
         &config,
         &mut server,
+        &caller,
         data_execution,
         version,
         consensus_command_sender,
@@ -467,177 +475,121 @@ fn run_bootstrap_session(
     }
 }
 
+/// Number of parts of each stream produced in advance when streaming on independent sub-channels
+const MULTIPLEXED_PARTS_BUFFER: usize = 4;
+
+/// Cursors of the final state stream of a client
+struct StateCursors {
+    /// slot the streamed entries are attached to
+    last_slot: Option<Slot>,
+    /// last streamed state key
+    last_state_step: StreamingStep<Vec<u8>>,
+    /// last streamed versioning key
+    last_versioning_step: StreamingStep<Vec<u8>>,
+    /// whether the initial state must be sent with the next part
+    send_last_start_period: bool,
+}
+
+impl StateCursors {
+    /// Global cursor of the final state streamed up to `slot`, followed by the consensus stream
+    fn global_step(&self, slot: Slot) -> StreamingStep<Slot> {
+        if self.last_state_step.finished() && self.last_versioning_step.finished() {
+            StreamingStep::Finished(Some(slot))
+        } else {
+            StreamingStep::Ongoing(slot)
+        }
+    }
+}
+
+/// Part of the final state to send to a client
+struct StatePart {
+    /// slot the state changes are attached to
+    slot: Slot,
+    /// part of the state
+    state_part: StreamBatch<Slot>,
+    /// part of the versioning state
+    versioning_part: StreamBatch<Slot>,
+    /// last start period, only sent with the first part
+    last_start_period: Option<u64>,
+    /// last slot before downtime, only sent with the first part
+    last_slot_before_downtime: Option<Option<Slot>>,
+}
+
+impl StatePart {
+    /// Message sending the part along with a part of the consensus graph
+    fn into_message(
+        self,
+        pos_stream_version: u8,
+        consensus_part: BootstrapableGraph,
+        consensus_outdated_ids: PreHashSet<BlockId>,
+    ) -> BootstrapServerMessage {
+        BootstrapServerMessage::BootstrapPart {
+            slot: self.slot,
+            pos_stream_version,
+            state_part: self.state_part,
+            versioning_part: self.versioning_part,
+            consensus_part,
+            consensus_outdated_ids,
+            last_start_period: self.last_start_period,
+            last_slot_before_downtime: self.last_slot_before_downtime,
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn stream_bootstrap_information(
     server: &mut BootstrapServerBinder,
+    caller: &QueryCaller,
     final_state: Arc<RwLock<dyn FinalStateController>>,
     consensus_controller: Box<dyn ConsensusController>,
-    mut last_slot: Option<Slot>,
-    mut last_state_step: StreamingStep<Vec<u8>>,
-    mut last_versioning_step: StreamingStep<Vec<u8>>,
+    last_slot: Option<Slot>,
+    last_state_step: StreamingStep<Vec<u8>>,
+    last_versioning_step: StreamingStep<Vec<u8>>,
     mut last_consensus_step: StreamingStep<PreHashSet<BlockId>>,
-    mut send_last_start_period: bool,
+    send_last_start_period: bool,
+    state_end_key: Option<Vec<u8>>,
+    pos_stream_version: u8,
     bs_deadline: &Instant,
     write_timeout: Duration,
 ) -> Result<(), BootstrapError> {
-    loop {
-        let current_slot;
-        let state_part;
-        let versioning_part;
-        let last_start_period;
-        let last_slot_before_downtime;
-
-        let slot_too_old = false;
-
-        // Scope of the final state read
-        {
-            let final_state_read = final_state.read();
-
-            last_start_period = if send_last_start_period {
-                Some(final_state_read.get_last_start_period())
-            } else {
-                None
-            };
-            last_slot_before_downtime = if send_last_start_period {
-                Some(*final_state_read.get_last_slot_before_downtime())
-            } else {
-                None
-            };
-
-            state_part = final_state_read
-                .get_database()
-                .read()
-                .get_batch_to_stream(&last_state_step, last_slot)
-                .map_err(|e| {
-                    BootstrapError::GeneralError(format!("Error get_batch_to_stream: {}", e))
-                })?;
-
-            let new_state_step = match (&last_state_step, state_part.is_empty()) {
-                // We already finished streaming the state
-                (StreamingStep::Finished(_), _) => StreamingStep::Finished(None),
-
-                // We receive our first empty state batch
-                (StreamingStep::Ongoing(_), true) => StreamingStep::Finished(None),
-
-                // We receive our first empty state batch, but we've just started streaming: warn the user
-                (StreamingStep::Started, true) => {
-                    warn!("State bootstrap is finished but nothing has been streamed yet");
-                    StreamingStep::Finished(None)
-                }
-
-                // We still need to stream the state, we update the current reference to the last_key if needed
-                (StreamingStep::Ongoing(last_key), false) => {
-                    match state_part.new_elements.last_key_value() {
-                        Some((new_last_key, _)) => StreamingStep::Ongoing(new_last_key.clone()), // We received new elements
-                        None => StreamingStep::Ongoing(last_key.clone()), // We only received changes
-                    }
-                }
-
-                // We still need to stream the state
-                (StreamingStep::Started, false) => match state_part.new_elements.last_key_value() {
-                    Some((new_last_key, _)) => StreamingStep::Ongoing(new_last_key.clone()), // We received new elements
-                    None => {
-                        // We only received changes
-                        return Err(BootstrapError::GeneralError(String::from(
-                            "State bootstrap started but we have no new elements to stream",
-                        )));
-                    }
-                },
-            };
-
-            versioning_part = final_state_read
-                .get_database()
-                .read()
-                .get_versioning_batch_to_stream(&last_versioning_step, last_slot)
-                .map_err(|e| {
-                    BootstrapError::GeneralError(format!(
-                        "Error get_versioning_batch_to_stream: {}",
-                        e
-                    ))
-                })?;
-
-            let new_versioning_step = match (&last_versioning_step, versioning_part.is_empty()) {
-                // We already finished streaming the versioning
-                (StreamingStep::Finished(_), _) => StreamingStep::Finished(None),
-
-                // We receive our first empty versioning batch
-                (StreamingStep::Ongoing(_), true) => StreamingStep::Finished(None),
-
-                // We receive our first empty versioning batch, but we've just started streaming: warn the user
-                (StreamingStep::Started, true) => {
-                    warn!("Versioning bootstrap is finished but nothing has been streamed yet");
-                    StreamingStep::Finished(None)
-                }
-
-                // We still need to stream the versioning, we update the current reference to the last_key if needed
-                (StreamingStep::Ongoing(last_key), false) => {
-                    match versioning_part.new_elements.last_key_value() {
-                        Some((new_last_key, _)) => StreamingStep::Ongoing(new_last_key.clone()), // We received new elements
-                        None => StreamingStep::Ongoing(last_key.clone()), // We only received changes
-                    }
-                }
-
-                // We still need to stream the versioning
-                (StreamingStep::Started, false) => {
-                    match versioning_part.new_elements.last_key_value() {
-                        Some((new_last_key, _)) => StreamingStep::Ongoing(new_last_key.clone()), // We received new elements
-                        None => {
-                            // We only received changes
-                            return Err(BootstrapError::GeneralError(String::from(
-                                "Versioning bootstrap started but we have no new elements to stream",
-                            )));
-                        }
-                    }
-                }
-            };
-
-            let db_slot = final_state_read
-                .get_database()
-                .read()
-                .get_change_id()
-                .expect(CHANGE_ID_DESER_ERROR);
-
-            if let Some(slot) = last_slot {
-                if slot > db_slot {
-                    return Err(BootstrapError::GeneralError(
-                        "Bootstrap cursor set to future slot".to_string(),
-                    ));
-                }
-            }
-
-            // Update cursors for next turn
-            last_state_step = new_state_step;
-            last_versioning_step = new_versioning_step;
-            last_slot = Some(db_slot);
-            current_slot = db_slot;
-            send_last_start_period = false;
-        }
+    let mut cursors = StateCursors {
+        last_slot,
+        last_state_step,
+        last_versioning_step,
+        send_last_start_period,
+    };
+    if pos_stream_version >= MULTIPLEXED_STREAMS_VERSION {
+        return stream_multiplexed_bootstrap_information(
+            server,
+            caller,
+            &*final_state,
+            &*consensus_controller,
+            cursors,
+            last_consensus_step,
+            state_end_key,
+            pos_stream_version,
+            bs_deadline,
+            write_timeout,
+        );
+    }
 
-        if slot_too_old {
+    loop {
+        let Some(state_part) =
+            next_state_part(&*final_state, &mut cursors, state_end_key.as_deref())?
+        else {
             return server.send_msg(write_timeout, BootstrapServerMessage::SlotTooOld);
-        }
+        };
 
         // Setup final state global cursor
-        let final_state_global_step =
-            if last_state_step.finished() && last_versioning_step.finished() {
-                StreamingStep::Finished(Some(current_slot))
-            } else {
-                StreamingStep::Ongoing(current_slot)
-            };
-
-        // Stream consensus blocks if final state base bootstrap is finished
-        let mut consensus_part = BootstrapableGraph {
-            final_blocks: Default::default(),
-        };
-        let mut consensus_outdated_ids: PreHashSet<BlockId> = PreHashSet::default();
-
-        if final_state_global_step.finished() {
-            let (part, outdated_ids, new_consensus_step) = consensus_controller
-                .get_bootstrap_part(last_consensus_step, final_state_global_step)?;
-            consensus_part = part;
-            consensus_outdated_ids = outdated_ids;
-            last_consensus_step = new_consensus_step;
-        }
+        let final_state_global_step = cursors.global_step(state_part.slot);
+
+        // Stream consensus blocks alongside the final state, each stream having its own cursor.
+        let (consensus_part, consensus_outdated_ids, _) = next_consensus_part(
+            &*consensus_controller,
+            caller,
+            &mut last_consensus_step,
+            final_state_global_step,
+        )?;
 
         // Logs for an easier diagnostic if needed
         debug!(
@@ -667,20 +619,349 @@ pub fn stream_bootstrap_information(
         // At this point we know that consensus, final state or both are not finished
         server.send_msg(
             write_timeout,
-            BootstrapServerMessage::BootstrapPart {
-                slot: current_slot,
-                state_part,
-                versioning_part,
-                consensus_part,
-                consensus_outdated_ids,
-                last_start_period,
-                last_slot_before_downtime,
-            },
+            state_part.into_message(pos_stream_version, consensus_part, consensus_outdated_ids),
         )?;
     }
     Ok(())
 }
 
+/// Stream the final state and the consensus graph on independent sub-channels, see `MULTIPLEXED_STREAMS_VERSION`.
+///
+/// The parts of both streams are produced concurrently, each stream having its own cursor, and sent as soon as
+/// they are ready: the latency of the link is no longer paid once per part of each stream. The consensus stream
+/// follows the slot of the final state: once it has caught up, it waits for the next part of the final state,
+/// and the final state stream only stops once the consensus graph is up to date with its final slot.
+#[allow(clippy::too_many_arguments)]
+fn stream_multiplexed_bootstrap_information(
+    server: &mut BootstrapServerBinder,
+    caller: &QueryCaller,
+    final_state: &RwLock<dyn FinalStateController>,
+    consensus_controller: &dyn ConsensusController,
+    mut cursors: StateCursors,
+    mut last_consensus_step: StreamingStep<PreHashSet<BlockId>>,
+    state_end_key: Option<Vec<u8>>,
+    pos_stream_version: u8,
+    bs_deadline: &Instant,
+    write_timeout: Duration,
+) -> Result<(), BootstrapError> {
+    let (parts_tx, parts_rx) = crossbeam::channel::bounded::<
+        Result<BootstrapServerMessage, BootstrapError>,
+    >(MULTIPLEXED_PARTS_BUFFER);
+    // global cursor of the final state after each of its parts
+    let (state_steps_tx, state_steps_rx) = crossbeam::channel::unbounded();
+    // whether the consensus graph is up to date with the final slot of the final state
+    let (consensus_done_tx, consensus_done_rx) = crossbeam::channel::bounded(1);
+
+    thread::scope(|scope| {
+        let state_parts_tx = parts_tx.clone();
+        scope.spawn(move || {
+            let res = (|| -> Result<(), BootstrapError> {
+                loop {
+                    let Some(state_part) =
+                        next_state_part(final_state, &mut cursors, state_end_key.as_deref())?
+                    else {
+                        let _ = state_parts_tx.send(Ok(BootstrapServerMessage::SlotTooOld));
+                        return Ok(());
+                    };
+                    let final_state_global_step = cursors.global_step(state_part.slot);
+                    let message = state_part.into_message(
+                        pos_stream_version,
+                        BootstrapableGraph {
+                            final_blocks: Vec::new(),
+                        },
+                        PreHashSet::default(),
+                    );
+                    if state_parts_tx.send(Ok(message)).is_err()
+                        || state_steps_tx.send(final_state_global_step).is_err()
+                    {
+                        return Ok(());
+                    }
+                    // the final slot is kept once the consensus graph is up to date with it
+                    if final_state_global_step.finished() && consensus_done_rx.recv() != Ok(false) {
+                        return Ok(());
+                    }
+                }
+            })();
+            if let Err(err) = res {
+                let _ = state_parts_tx.send(Err(err));
+            }
+        });
+
+        let consensus_parts_tx = parts_tx;
+        scope.spawn(move || {
+            let res = (|| -> Result<(), BootstrapError> {
+                let Ok(mut final_state_global_step) = state_steps_rx.recv() else {
+                    return Ok(());
+                };
+                loop {
+                    // follow the latest part of the final state
+                    while let Ok(step) = state_steps_rx.try_recv() {
+                        final_state_global_step = step;
+                    }
+                    let (consensus_part, consensus_outdated_ids, up_to_date) = next_consensus_part(
+                        consensus_controller,
+                        caller,
+                        &mut last_consensus_step,
+                        final_state_global_step,
+                    )?;
+                    if (!consensus_part.final_blocks.is_empty()
+                        || !consensus_outdated_ids.is_empty())
+                        && consensus_parts_tx
+                            .send(Ok(BootstrapServerMessage::BootstrapConsensusPart {
+                                consensus_part,
+                                consensus_outdated_ids,
+                            }))
+                            .is_err()
+                    {
+                        return Ok(());
+                    }
+                    if final_state_global_step.finished() {
+                        let done = last_consensus_step.finished();
+                        if consensus_done_tx.send(done).is_err() || done {
+                            return Ok(());
+                        }
+                    }
+                    if up_to_date || final_state_global_step.finished() {
+                        // wait for the next part of the final state
+                        let Ok(step) = state_steps_rx.recv() else {
+                            return Ok(());
+                        };
+                        final_state_global_step = step;
+                    }
+                }
+            })();
+            if let Err(err) = res {
+                let _ = consensus_parts_tx.send(Err(err));
+            }
+        });
+
+        let res = (|| -> Result<(), BootstrapError> {
+            for message in parts_rx.iter() {
+                let message = message?;
+                if let BootstrapServerMessage::SlotTooOld = message {
+                    return server.send_msg(write_timeout, message);
+                }
+                let Some(write_timeout) = step_timeout_duration(bs_deadline, &write_timeout) else {
+                    return Err(BootstrapError::Interrupted(
+                        "insufficient time left to provide next bootstrap part".to_string(),
+                    ));
+                };
+                server.send_msg(write_timeout, message)?;
+            }
+            // both streams are finished
+            server.send_msg(write_timeout, BootstrapServerMessage::BootstrapFinished)
+        })();
+        // stop the streams that are still running before waiting for them
+        drop(parts_rx);
+        res
+    })
+}
+
+/// Get the next part of the final state to stream, and advance the cursors
+///
+/// # Returns
+/// The part, or `None` if the changes since the slot of the client are not in our history anymore
+fn next_state_part(
+    final_state: &RwLock<dyn FinalStateController>,
+    cursors: &mut StateCursors,
+    state_end_key: Option<&[u8]>,
+) -> Result<Option<StatePart>, BootstrapError> {
+    let final_state_read = final_state.read();
+
+    let last_start_period = if cursors.send_last_start_period {
+        Some(final_state_read.get_last_start_period())
+    } else {
+        None
+    };
+    let last_slot_before_downtime = if cursors.send_last_start_period {
+        Some(*final_state_read.get_last_slot_before_downtime())
+    } else {
+        None
+    };
+
+    let db_slot = final_state_read
+        .get_database()
+        .read()
+        .get_change_id()
+        .expect(CHANGE_ID_DESER_ERROR);
+
+    let state_batch = match state_end_key {
+        Some(end_key) => get_batch_to_stream_before(
+            &**final_state_read.get_database().read(),
+            &cursors.last_state_step,
+            cursors.last_slot,
+            end_key,
+        ),
+        None => final_state_read
+            .get_database()
+            .read()
+            .get_batch_to_stream(&cursors.last_state_step, cursors.last_slot),
+    };
+    if matches!(state_batch, Err(MassaDBError::CacheMissError(_)))
+        && cursors.last_slot.map_or(false, |slot| slot < db_slot)
+    {
+        // the changes since the slot of the client are not in our history anymore,
+        // typically when it fast syncs from an old checkpoint: it has to bootstrap from scratch
+        return Ok(None);
+    }
+    let state_part = state_batch
+        .map_err(|e| BootstrapError::GeneralError(format!("Error get_batch_to_stream: {}", e)))?;
+
+    let new_state_step = match (&cursors.last_state_step, state_part.is_empty()) {
+        // The entries of the range are all streamed, the changes of the other ones being streamed with them
+        (StreamingStep::Started | StreamingStep::Ongoing(_), _)
+            if state_end_key.is_some() && state_part.new_elements.is_empty() =>
+        {
+            StreamingStep::Finished(None)
+        }
+
+        // We already finished streaming the state
+        (StreamingStep::Finished(_), _) => StreamingStep::Finished(None),
+
+        // We receive our first empty state batch
+        (StreamingStep::Ongoing(_), true) => StreamingStep::Finished(None),
+
+        // We receive our first empty state batch, but we've just started streaming: warn the user
+        (StreamingStep::Started, true) => {
+            warn!("State bootstrap is finished but nothing has been streamed yet");
+            StreamingStep::Finished(None)
+        }
+
+        // We still need to stream the state, we update the current reference to the last_key if needed
+        (StreamingStep::Ongoing(last_key), false) => {
+            match state_part.new_elements.last_key_value() {
+                Some((new_last_key, _)) => StreamingStep::Ongoing(new_last_key.clone()), // We received new elements
+                None => StreamingStep::Ongoing(last_key.clone()), // We only received changes
+            }
+        }
+
+        // We still need to stream the state
+        (StreamingStep::Started, false) => match state_part.new_elements.last_key_value() {
+            Some((new_last_key, _)) => StreamingStep::Ongoing(new_last_key.clone()), // We received new elements
+            None => {
+                // We only received changes
+                return Err(BootstrapError::GeneralError(String::from(
+                    "State bootstrap started but we have no new elements to stream",
+                )));
+            }
+        },
+    };
+
+    let versioning_part = final_state_read
+        .get_database()
+        .read()
+        .get_versioning_batch_to_stream(&cursors.last_versioning_step, cursors.last_slot)
+        .map_err(|e| {
+            BootstrapError::GeneralError(format!("Error get_versioning_batch_to_stream: {}", e))
+        })?;
+
+    let new_versioning_step = match (&cursors.last_versioning_step, versioning_part.is_empty()) {
+        // We already finished streaming the versioning
+        (StreamingStep::Finished(_), _) => StreamingStep::Finished(None),
+
+        // We receive our first empty versioning batch
+        (StreamingStep::Ongoing(_), true) => StreamingStep::Finished(None),
+
+        // We receive our first empty versioning batch, but we've just started streaming: warn the user
+        (StreamingStep::Started, true) => {
+            warn!("Versioning bootstrap is finished but nothing has been streamed yet");
+            StreamingStep::Finished(None)
+        }
+
+        // We still need to stream the versioning, we update the current reference to the last_key if needed
+        (StreamingStep::Ongoing(last_key), false) => {
+            match versioning_part.new_elements.last_key_value() {
+                Some((new_last_key, _)) => StreamingStep::Ongoing(new_last_key.clone()), // We received new elements
+                None => StreamingStep::Ongoing(last_key.clone()), // We only received changes
+            }
+        }
+
+        // We still need to stream the versioning
+        (StreamingStep::Started, false) => {
+            match versioning_part.new_elements.last_key_value() {
+                Some((new_last_key, _)) => StreamingStep::Ongoing(new_last_key.clone()), // We received new elements
+                None => {
+                    // We only received changes
+                    return Err(BootstrapError::GeneralError(String::from(
+                        "Versioning bootstrap started but we have no new elements to stream",
+                    )));
+                }
+            }
+        }
+    };
+
+    if let Some(slot) = cursors.last_slot {
+        if slot > db_slot {
+            return Err(BootstrapError::GeneralError(
+                "Bootstrap cursor set to future slot".to_string(),
+            ));
+        }
+    }
+
+    // Update cursors for next turn
+    cursors.last_state_step = new_state_step;
+    cursors.last_versioning_step = new_versioning_step;
+    cursors.last_slot = Some(db_slot);
+    cursors.send_last_start_period = false;
+
+    Ok(Some(StatePart {
+        slot: db_slot,
+        state_part,
+        versioning_part,
+        last_start_period,
+        last_slot_before_downtime,
+    }))
+}
+
+/// Get the next part of the consensus graph to stream along with the final state at `final_state_global_step`,
+/// and advance the consensus cursor
+///
+/// # Returns
+/// The part, its outdated block ids, and whether the consensus graph is up to date with the final state
+fn next_consensus_part(
+    consensus_controller: &dyn ConsensusController,
+    caller: &QueryCaller,
+    last_consensus_step: &mut StreamingStep<PreHashSet<BlockId>>,
+    final_state_global_step: StreamingStep<Slot>,
+) -> Result<(BootstrapableGraph, PreHashSet<BlockId>, bool), BootstrapError> {
+    // The consensus blocks are those required at the slot of the streamed final state:
+    // blocks that become outdated as that slot advances are removed by the client.
+    let (consensus_part, consensus_outdated_ids, new_consensus_step) = consensus_controller
+        .get_bootstrap_part_as(caller, last_consensus_step.clone(), final_state_global_step)?;
+    let up_to_date = new_consensus_step.finished();
+
+    // The consensus stream can only finish once the final state stream is finished,
+    // as new final blocks can still be required while the final state slot advances.
+    if final_state_global_step.finished() || !up_to_date {
+        *last_consensus_step = new_consensus_step;
+    } else if let StreamingStep::Ongoing(ids) = last_consensus_step {
+        ids.retain(|id| !consensus_outdated_ids.contains(id));
+    }
+    Ok((consensus_part, consensus_outdated_ids, up_to_date))
+}
+
+/// Get a batch of the state entries before `end_key` to stream, with the changes of all the entries since `last_slot`.
+///
+/// The client streaming only a range of keys keeps its other entries, attached to `last_slot`:
+/// they are kept up to date by streaming their changes as well, whatever the position of the cursor.
+fn get_batch_to_stream_before(
+    db: &dyn MassaDBController,
+    last_state_step: &StreamingStep<Vec<u8>>,
+    last_slot: Option<Slot>,
+    end_key: &[u8],
+) -> Result<StreamBatch<Slot>, MassaDBError> {
+    let mut batch = db.get_batch_to_stream(last_state_step, last_slot)?;
+    if !last_state_step.finished() {
+        if last_slot.is_some() {
+            batch.updates_on_previous_elements = db
+                .get_batch_to_stream(&StreamingStep::Finished(None), last_slot)?
+                .updates_on_previous_elements;
+        }
+        batch.new_elements.retain(|key, _| key.as_slice() < end_key);
+    }
+    Ok(batch)
+}
+
 // derives the duration allowed for a step in the bootstrap process.
 // Returns None if the deadline for the entire bs-process has been reached
 fn step_timeout_duration(bs_deadline: &Instant, step_timeout: &Duration) -> Option<Duration> {
@@ -696,6 +977,7 @@ fn step_timeout_duration(bs_deadline: &Instant, step_timeout: &Duration) -> Opti
 pub(crate) fn manage_bootstrap(
     bootstrap_config: &BootstrapConfig,
     server: &mut BootstrapServerBinder,
+    caller: &QueryCaller,
     final_state: Arc<RwLock<dyn FinalStateController>>,
     version: Version,
     consensus_controller: Box<dyn ConsensusController>,
@@ -713,7 +995,7 @@ pub(crate) fn manage_bootstrap(
         ));
     };
 
-    server.handshake_timeout(version, Some(hs_timeout))?;
+    let pos_stream_version = server.handshake_timeout(version, Some(hs_timeout))?;
 
     // Check for error from client
     if Instant::now() + read_error_timeout >= deadline {
@@ -743,6 +1025,7 @@ pub(crate) fn manage_bootstrap(
         BootstrapServerMessage::BootstrapTime {
             server_time: MassaTime::now(),
             version,
+            pos_stream_version,
         },
     )?;
 
@@ -781,9 +1064,17 @@ pub(crate) fn manage_bootstrap(
                     last_versioning_step,
                     last_consensus_step,
                     send_last_start_period,
+                    state_end_key,
                 } => {
+                    if state_end_key.is_some() && pos_stream_version < RANGED_STATE_STREAM_VERSION {
+                        break Err(BootstrapError::IncompatibleVersionError(format!(
+                            "client asked for a range of the state with PoS stream version {} (required: {})",
+                            pos_stream_version, RANGED_STATE_STREAM_VERSION
+                        )));
+                    }
                     stream_bootstrap_information(
                         server,
+                        caller,
                         final_state.clone(),
                         consensus_controller.clone(),
                         last_slot,
@@ -791,6 +1082,8 @@ pub(crate) fn manage_bootstrap(
                         last_versioning_step,
                         last_consensus_step,
                         send_last_start_period,
+                        state_end_key,
+                        pos_stream_version,
                         &deadline,
                         bootstrap_config.write_timeout.to_duration(),
                     )?;
@@ -1,7 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_hash::Hash;
 use massa_models::block::BlockDeserializerArgs;
 use massa_models::node::NodeId;
+use massa_models::slot::Slot;
 use massa_time::MassaTime;
 use serde::Deserialize;
 use std::{net::SocketAddr, path::PathBuf};
@@ -19,6 +21,49 @@ pub enum IpType {
     IPv6,
 }
 
+/// Trusted final state to fast sync from.
+///
+/// A node holding a snapshot of the final state at `slot` (a backup of the ledger of another node for example)
+/// only bootstraps the changes of the final state since that slot instead of the whole final state,
+/// once the fingerprint of the snapshot was checked against the trusted one.
+/// The block graph is bootstrapped as usual: the final blocks are streamed with their operations.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct FastSyncCheckpoint {
+    /// slot of the trusted final state
+    pub slot: Slot,
+    /// fingerprint of the trusted final state, as reported by the `final_state_fingerprint` of the nodes at that slot
+    pub fingerprint: Hash,
+}
+
+/// Part of the local state to resynchronize from a bootstrap server, the rest of the local state being kept.
+///
+/// Used to recover from the partial corruption of a component without bootstrapping the whole final state:
+/// the final state is attached to its local slot and only receives the changes since that slot, along with
+/// the entries of the resynchronized component. The final blocks are bootstrapped in both cases.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResyncTarget {
+    /// Only bootstrap the final blocks
+    Consensus,
+    /// Bootstrap the cycle history and the deferred credits of the PoS state again, along with the final blocks
+    Pos,
+}
+
+impl std::str::FromStr for ResyncTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "consensus" => Ok(ResyncTarget::Consensus),
+            "pos" => Ok(ResyncTarget::Pos),
+            _ => Err(format!(
+                "unknown resync target {}, expected consensus or pos",
+                s
+            )),
+        }
+    }
+}
+
 /// Bootstrap configuration.
 #[derive(Debug, Deserialize, Clone)]
 pub struct BootstrapConfig {
@@ -55,6 +100,10 @@ pub struct BootstrapConfig {
     pub cache_duration: MassaTime,
     /// Keep ledger or not if not bootstrap
     pub keep_ledger: bool,
+    /// Trusted checkpoint of the local final state to fast sync from, `None` to bootstrap the whole final state
+    pub fast_sync_checkpoint: Option<FastSyncCheckpoint>,
+    /// Part of the local state to resynchronize, `None` to bootstrap normally
+    pub resync: Option<ResyncTarget>,
     /// Max simultaneous bootstraps
     pub max_simultaneous_bootstraps: u32,
     /// Minimum interval between two bootstrap attempts from a given IP
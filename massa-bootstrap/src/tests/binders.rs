@@ -1,4 +1,6 @@
-use crate::messages::{BootstrapClientMessage, BootstrapServerMessage};
+use crate::messages::{
+    BootstrapClientMessage, BootstrapServerMessage, MAX_POS_STREAM_VERSION, MIN_POS_STREAM_VERSION,
+};
 use crate::server::manage_bootstrap;
 use crate::settings::{BootstrapClientConfig, BootstrapSrvBindCfg};
 
@@ -345,6 +347,96 @@ fn test_binders_multiple_send() {
     let _ = client_thread.join();
 }
 
+/// Every server message, including the consensus graph and deferred credits parts, is signed with the server node key.
+/// A message signed with another key than the one expected for this bootstrap server must be rejected by the client.
+#[test]
+fn test_client_rejects_message_signed_by_unexpected_key() {
+    let (bootstrap_config, _): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+    let timeout = Duration::from_secs(30);
+    let server = std::net::TcpListener::bind("localhost:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let client = std::net::TcpStream::connect(addr).unwrap();
+    let server = server.accept().unwrap();
+    let version = || Version::from_str("TEST.1.10").unwrap();
+
+    // the server signs with a key that is not in the bootstrap list of the client
+    let mut server = BootstrapServerBinder::new(
+        server.0,
+        KeyPair::generate(0).unwrap(),
+        BootstrapSrvBindCfg {
+            rate_limit: std::u64::MAX,
+            thread_count: THREAD_COUNT,
+            max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+            randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+            consensus_bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
+            write_error_timeout: MassaTime::from_millis(1000),
+        },
+        Some(u64::MAX),
+    );
+    let mut client = BootstrapClientBinder::test_default(
+        client,
+        bootstrap_config.bootstrap_list[0].1.get_public_key(),
+    );
+    client.handshake(version()).unwrap();
+    server.handshake_timeout(version(), None).unwrap();
+
+    server
+        .send_timeout(
+            BootstrapServerMessage::BootstrapTime {
+                server_time: MassaTime::now(),
+                version: version(),
+                pos_stream_version: 0,
+            },
+            Some(timeout),
+        )
+        .unwrap();
+    match client.next_timeout(Some(timeout)) {
+        Err(BootstrapError::MassaSignatureError(_)) => {}
+        other => panic!("expected a signature error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_handshake_negotiates_pos_stream_version() {
+    let (bootstrap_config, server_keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+    // the PoS stream version is only read from the handshake of clients whose version carries it
+    for (client_version, server_version, expected_pos_stream_version) in [
+        ("TEST.2.10", "TEST.2.10", MAX_POS_STREAM_VERSION),
+        ("TEST.2.1", "TEST.2.10", MIN_POS_STREAM_VERSION),
+        ("TEST.3.0", "TEST.3.0", MAX_POS_STREAM_VERSION),
+    ] {
+        let server = std::net::TcpListener::bind("localhost:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let client = std::net::TcpStream::connect(addr).unwrap();
+        let server = server.accept().unwrap();
+
+        let mut server = BootstrapServerBinder::new(
+            server.0,
+            server_keypair.clone(),
+            BootstrapSrvBindCfg {
+                rate_limit: std::u64::MAX,
+                thread_count: THREAD_COUNT,
+                max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+                randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+                consensus_bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
+                write_error_timeout: MassaTime::from_millis(1000),
+            },
+            Some(u64::MAX),
+        );
+        let mut client = BootstrapClientBinder::test_default(
+            client,
+            bootstrap_config.bootstrap_list[0].1.get_public_key(),
+        );
+        client
+            .handshake(Version::from_str(client_version).unwrap())
+            .unwrap();
+        let pos_stream_version = server
+            .handshake_timeout(Version::from_str(server_version).unwrap(), None)
+            .unwrap();
+        assert_eq!(pos_stream_version, expected_pos_stream_version);
+    }
+}
+
 #[test]
 fn test_partial_msg() {
     let (bootstrap_config, server_keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
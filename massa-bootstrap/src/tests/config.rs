@@ -41,6 +41,8 @@ impl Default for BootstrapConfig {
             max_listeners_per_peer: 100,
             bootstrap_list: vec![(SocketAddr::new(BASE_BOOTSTRAP_IP, 8069), node_id)],
             keep_ledger: false,
+            fast_sync_checkpoint: None,
+            resync: None,
             bootstrap_whitelist_path: PathBuf::from("bootstrap_whitelist.json"),
             bootstrap_blacklist_path: PathBuf::from("bootstrap_blacklist.json"),
             max_clock_delta: MassaTime::from_millis(1000),
@@ -1,3 +1,4 @@
+use std::str::FromStr;
 use std::time::Duration;
 
 use crate::settings::BootstrapClientConfig;
@@ -5,10 +6,13 @@ use crate::tests::tools::{
     parametric_test, BootstrapClientMessageFaultyPart, BootstrapServerMessageFaultyPart,
 };
 use crate::{
-    BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapClientMessageSerializer,
-    BootstrapServerMessage, BootstrapServerMessageDeserializer, BootstrapServerMessageSerializer,
+    handshake_has_pos_stream_version, negotiate_pos_stream_version, BootstrapClientMessage,
+    BootstrapClientMessageDeserializer, BootstrapClientMessageSerializer, BootstrapServerMessage,
+    BootstrapServerMessageDeserializer, BootstrapServerMessageSerializer, MAX_POS_STREAM_VERSION,
+    RANGED_STATE_STREAM_VERSION,
 };
 use massa_models::config::*;
+use massa_models::version::Version;
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 
 #[test]
@@ -200,3 +204,36 @@ fn test_serialize_error_cases_servermsg() {
         println!("===========");
     }
 }
+
+#[test]
+fn test_negotiate_pos_stream_version() {
+    // same version on both sides
+    assert_eq!(
+        negotiate_pos_stream_version(MAX_POS_STREAM_VERSION),
+        Some(MAX_POS_STREAM_VERSION)
+    );
+    // a more recent remote falls back to our highest version
+    assert_eq!(
+        negotiate_pos_stream_version(u8::MAX),
+        Some(MAX_POS_STREAM_VERSION)
+    );
+    // an older remote negotiates its own version, without ranged state streams
+    let older_version = RANGED_STATE_STREAM_VERSION - 1;
+    assert_eq!(
+        negotiate_pos_stream_version(older_version),
+        Some(older_version)
+    );
+}
+
+#[test]
+fn test_handshake_has_pos_stream_version() {
+    let has =
+        |version: &str| handshake_has_pos_stream_version(&Version::from_str(version).unwrap());
+    assert!(has("TEST.2.2"));
+    assert!(has("TEST.2.10"));
+    // a higher major version carries it whatever its minor version
+    assert!(has("TEST.3.0"));
+    assert!(!has("TEST.2.1"));
+    // a lower major version does not carry it even with a higher minor version
+    assert!(!has("TEST.1.10"));
+}
@@ -2,12 +2,20 @@
 
 use super::universe_client::{BootstrapClientForeignControllers, BootstrapClientTestUniverse};
 use super::universe_server::BootstrapServerTestUniverseBuilder;
+use crate::client::{check_fast_sync_snapshot, pos_resync_ranges};
 use crate::BootstrapConfig;
 use crate::BootstrapError;
+use crate::FastSyncCheckpoint;
+use massa_db_exports::{
+    ASYNC_POOL_PREFIX, CYCLE_HISTORY_PREFIX, DEFERRED_CREDITS_PREFIX,
+    EXECUTED_DENUNCIATIONS_PREFIX, LEDGER_PREFIX, PAYOUT_ADDRESSES_PREFIX,
+};
+use massa_final_state::MockFinalStateController;
+use massa_hash::Hash;
 use massa_models::amount::Amount;
 use massa_models::bytecode::Bytecode;
 use massa_models::datastore::Datastore;
-use massa_models::{address::Address, node::NodeId};
+use massa_models::{address::Address, node::NodeId, slot::Slot};
 use massa_signature::KeyPair;
 use massa_test_framework::TestUniverse;
 use serial_test::serial;
@@ -78,3 +86,65 @@ fn test_bootstrap_accept_err() {
         .build();
     drop(server_universe);
 }
+
+#[test]
+fn test_fast_sync_snapshot_check() {
+    let checkpoint = FastSyncCheckpoint {
+        slot: Slot::new(10, 3),
+        fingerprint: Hash::compute_from(b"trusted final state"),
+    };
+    let mut final_state = MockFinalStateController::new();
+    final_state.expect_get_slot().return_const(checkpoint.slot);
+    final_state
+        .expect_get_fingerprint()
+        .return_const(checkpoint.fingerprint);
+    check_fast_sync_snapshot(&checkpoint, &final_state).unwrap();
+
+    // the snapshot is at another slot
+    let other_slot = FastSyncCheckpoint {
+        slot: Slot::new(11, 3),
+        ..checkpoint
+    };
+    assert!(matches!(
+        check_fast_sync_snapshot(&other_slot, &final_state),
+        Err(BootstrapError::UntrustedSnapshot(_))
+    ));
+
+    // the snapshot has another content
+    let other_fingerprint = FastSyncCheckpoint {
+        fingerprint: Hash::compute_from(b"tampered final state"),
+        ..checkpoint
+    };
+    assert!(matches!(
+        check_fast_sync_snapshot(&other_fingerprint, &final_state),
+        Err(BootstrapError::UntrustedSnapshot(_))
+    ));
+}
+
+#[test]
+fn test_pos_resync_ranges() {
+    let [pos_range, payout_range] = pos_resync_ranges();
+    let in_range = |(start_key, end_key): &(Vec<u8>, Vec<u8>), prefix: &str, suffix: &[u8]| {
+        let key = [prefix.as_bytes(), suffix].concat();
+        key > *start_key && key < *end_key
+    };
+    for suffix in [&[][..], &[0u8][..], &[0xffu8; 40][..]] {
+        for (range, is_payout_range) in [(&pos_range, false), (&payout_range, true)] {
+            assert_eq!(
+                in_range(range, CYCLE_HISTORY_PREFIX, suffix),
+                !is_payout_range
+            );
+            assert_eq!(
+                in_range(range, DEFERRED_CREDITS_PREFIX, suffix),
+                !is_payout_range
+            );
+            assert_eq!(
+                in_range(range, PAYOUT_ADDRESSES_PREFIX, suffix),
+                is_payout_range
+            );
+            assert!(!in_range(range, ASYNC_POOL_PREFIX, suffix));
+            assert!(!in_range(range, EXECUTED_DENUNCIATIONS_PREFIX, suffix));
+            assert!(!in_range(range, LEDGER_PREFIX, suffix));
+        }
+    }
+}
@@ -139,7 +139,8 @@ fn get_random_deferred_credits(r_limit: u64) -> DeferredCredits {
             Slot {
                 period: i,
                 thread: 0,
-            },
+            }
+            .into(),
             credits,
         );
     }
@@ -158,6 +159,8 @@ fn get_random_pos_state(r_limit: u64, mut pos: PoSFinalState) -> PoSFinalState {
         roll_changes: roll_counts.into_iter().collect(),
         production_stats,
         deferred_credits,
+        payout_addresses: Default::default(),
+        delegations: Default::default(),
     };
 
     let mut batch = DBBatch::new();
@@ -342,6 +345,8 @@ pub fn get_bootstrap_config(bootstrap_public_key: NodeId) -> BootstrapConfig {
             bootstrap_public_key,
         )],
         keep_ledger: false,
+        fast_sync_checkpoint: None,
+        resync: None,
         bootstrap_whitelist_path: PathBuf::from(
             "../massa-node/base_config/bootstrap_whitelist.json",
         ),
@@ -485,6 +490,17 @@ fn stream_batch_equal<T: PartialOrd + Ord + PartialEq + Eq + Clone + std::fmt::D
     new_elements_equal && update_prevels_equal && (s1.change_id == s2.change_id)
 }
 
+fn bootstrapable_graph_equal(g1: &BootstrapableGraph, g2: &BootstrapableGraph) -> bool {
+    g1.final_blocks.len() == g2.final_blocks.len()
+        && g1.final_blocks.iter().zip(g2.final_blocks.iter()).all(
+            |(active_block1, active_block2)| {
+                active_block1.parents == active_block2.parents
+                    && active_block1.is_final == active_block2.is_final
+                    && active_block1.block.serialized_data == active_block2.block.serialized_data
+            },
+        )
+}
+
 fn gen_random_hash<R: Rng>(rng: &mut R) -> Hash {
     let bytes: [u8; HASH_SIZE_BYTES] = rng.gen();
     Hash::from_bytes(&bytes)
@@ -531,6 +547,7 @@ fn gen_random_block<R: Rng>(keypair: &KeyPair, rng: &mut R) -> Block {
         operation_merkle_root: gen_random_hash(rng),
         endorsements,
         denunciations,
+        extensions: Vec::new(),
     }
     .new_verifiable(BlockHeaderSerializer::new(), keypair, *CHAINID)
     .unwrap();
@@ -610,7 +627,7 @@ impl BootstrapServerMessageFaultyPart {
 
 impl BootstrapServerMessage {
     pub fn generate<R: Rng>(rng: &mut R) -> Self {
-        let variant = rng.gen_range(0..6);
+        let variant = rng.gen_range(0..7);
         match variant {
             0 => {
                 let t: u64 = rng.gen();
@@ -626,6 +643,7 @@ impl BootstrapServerMessage {
                 BootstrapServerMessage::BootstrapTime {
                     server_time,
                     version,
+                    pos_stream_version: rng.gen(),
                 }
             }
             1 => {
@@ -667,6 +685,7 @@ impl BootstrapServerMessage {
                 let slot = gen_random_slot(rng);
                 BootstrapServerMessage::BootstrapPart {
                     slot,
+                    pos_stream_version: rng.gen(),
                     state_part,
                     versioning_part,
                     consensus_part,
@@ -680,6 +699,19 @@ impl BootstrapServerMessage {
             5 => BootstrapServerMessage::BootstrapError {
                 error: gen_random_string(MAX_BOOTSTRAP_ERROR_LENGTH as usize, rng),
             },
+            6 => {
+                let block_nb = rng.gen_range(0..100);
+                let final_blocks = (0..block_nb)
+                    .map(|_| gen_export_active_blocks(rng))
+                    .collect();
+                let outdated_nb = rng.gen_range(0..MAX_BOOTSTRAP_BLOCKS);
+                let consensus_outdated_ids =
+                    (0..outdated_nb).map(|_| gen_random_block_id(rng)).collect();
+                BootstrapServerMessage::BootstrapConsensusPart {
+                    consensus_part: BootstrapableGraph { final_blocks },
+                    consensus_outdated_ids,
+                }
+            }
             _ => unreachable!(),
         }
     }
@@ -929,6 +961,7 @@ impl BootstrapServerMessage {
                 operation_merkle_root: gen_random_hash(rng),
                 endorsements: endorsements.clone(),
                 denunciations,
+                extensions: Vec::new(),
             }
             .new_verifiable(BlockHeaderSerializer::new(), &keypair, *CHAINID)
             .unwrap();
@@ -973,6 +1006,7 @@ impl BootstrapServerMessage {
             };
             BootstrapServerMessage::BootstrapPart {
                 slot,
+                pos_stream_version: rng.gen(),
                 state_part,
                 versioning_part,
                 consensus_part: BootstrapableGraph { final_blocks },
@@ -989,12 +1023,14 @@ impl BootstrapServerMessage {
                 BootstrapServerMessage::BootstrapTime {
                     server_time: t1,
                     version: v1,
+                    pos_stream_version: p1,
                 },
                 BootstrapServerMessage::BootstrapTime {
                     server_time: t2,
                     version: v2,
+                    pos_stream_version: p2,
                 },
-            ) => (t1 == t2) && (v1 == v2),
+            ) => (t1 == t2) && (v1 == v2) && (p1 == p2),
             (
                 BootstrapServerMessage::BootstrapPeers { peers: p1 },
                 BootstrapServerMessage::BootstrapPeers { peers: p2 },
@@ -1002,6 +1038,7 @@ impl BootstrapServerMessage {
             (
                 BootstrapServerMessage::BootstrapPart {
                     slot: s1,
+                    pos_stream_version: p1,
                     state_part: state1,
                     versioning_part: v1,
                     consensus_part: c1,
@@ -1011,6 +1048,7 @@ impl BootstrapServerMessage {
                 },
                 BootstrapServerMessage::BootstrapPart {
                     slot: s2,
+                    pos_stream_version: p2,
                     state_part: state2,
                     versioning_part: v2,
                     consensus_part: c2,
@@ -1021,25 +1059,25 @@ impl BootstrapServerMessage {
             ) => {
                 let state_equal = stream_batch_equal(state1, state2);
                 let versionning_equal = stream_batch_equal(v1, v2);
-                let mut consensus_equal = true;
-                if c1.final_blocks.len() != c2.final_blocks.len() {
-                    return false;
-                }
-                for (n, active_block1) in c1.final_blocks.iter().enumerate() {
-                    let active_block2 = c2.final_blocks.get(n).unwrap();
-                    consensus_equal &= active_block1.parents == active_block2.parents;
-                    consensus_equal &= active_block1.is_final == active_block2.is_final;
-                    consensus_equal &=
-                        active_block1.block.serialized_data == active_block2.block.serialized_data;
-                }
                 (s1 == s2)
+                    && (p1 == p2)
                     && state_equal
                     && versionning_equal
-                    && consensus_equal
+                    && bootstrapable_graph_equal(c1, c2)
                     && (co1 == co2)
                     && (lp1 == lp2)
                     && (ls1 == ls2)
             }
+            (
+                BootstrapServerMessage::BootstrapConsensusPart {
+                    consensus_part: c1,
+                    consensus_outdated_ids: co1,
+                },
+                BootstrapServerMessage::BootstrapConsensusPart {
+                    consensus_part: c2,
+                    consensus_outdated_ids: co2,
+                },
+            ) => bootstrapable_graph_equal(c1, c2) && (co1 == co2),
             (
                 BootstrapServerMessage::BootstrapFinished,
                 BootstrapServerMessage::BootstrapFinished,
@@ -1090,6 +1128,7 @@ impl BootstrapClientMessage {
                     last_versioning_step: lv1,
                     last_consensus_step: lcs1,
                     send_last_start_period: slp1,
+                    state_end_key: sek1,
                 },
                 BootstrapClientMessage::AskBootstrapPart {
                     last_slot: ls2,
@@ -1097,6 +1136,7 @@ impl BootstrapClientMessage {
                     last_versioning_step: lv2,
                     last_consensus_step: lcs2,
                     send_last_start_period: slp2,
+                    state_end_key: sek2,
                 },
             ) => {
                 (ls1 == ls2)
@@ -1104,6 +1144,7 @@ impl BootstrapClientMessage {
                     && (lv1 == lv2)
                     && (lcs1 == lcs2)
                     && (slp1 == slp2)
+                    && (sek1 == sek2)
             }
             (
                 BootstrapClientMessage::BootstrapError { error: e1 },
@@ -1159,12 +1200,19 @@ impl BootstrapClientMessage {
                 } else {
                     rng.gen_bool(0.5)
                 };
+
+                let state_end_key = if last_slot.is_some() && rng.gen_bool(0.5) {
+                    Some(gen_random_vector(10, rng))
+                } else {
+                    None
+                };
                 BootstrapClientMessage::AskBootstrapPart {
                     last_slot,
                     last_state_step,
                     last_versioning_step,
                     last_consensus_step,
                     send_last_start_period,
+                    state_end_key,
                 }
             }
             2 => BootstrapClientMessage::BootstrapError {
@@ -1226,6 +1274,7 @@ impl BootstrapClientMessage {
             last_versioning_step,
             last_consensus_step,
             send_last_start_period: false,
+            state_end_key: None,
         }
     }
 }
@@ -121,6 +121,7 @@ impl BootstrapClientTestUniverse {
                 last_versioning_step: StreamingStep::Started,
                 last_consensus_step: StreamingStep::Started,
                 send_last_start_period: true,
+                state_end_key: None,
             };
 
         let mut conn = connect_to_server(
@@ -154,24 +154,26 @@ impl Default for BootstrapServerTestUniverseBuilder {
         controllers
             .consensus_controller
             .set_expectations(|consensus_controller| {
-                consensus_controller.expect_get_bootstrap_part().returning(
-                    move |last_consensus_step, _slot| match last_consensus_step {
-                        StreamingStep::Started => Ok((
-                            BootstrapableGraph {
-                                final_blocks: vec![],
-                            },
-                            PreHashSet::default(),
-                            StreamingStep::Ongoing(PreHashSet::default()),
-                        )),
-                        _ => Ok((
-                            BootstrapableGraph {
-                                final_blocks: vec![],
-                            },
-                            PreHashSet::default(),
-                            StreamingStep::Finished(None),
-                        )),
-                    },
-                );
+                consensus_controller
+                    .expect_get_bootstrap_part_as()
+                    .returning(
+                        move |_caller, last_consensus_step, _slot| match last_consensus_step {
+                            StreamingStep::Started => Ok((
+                                BootstrapableGraph {
+                                    final_blocks: vec![],
+                                },
+                                PreHashSet::default(),
+                                StreamingStep::Ongoing(PreHashSet::default()),
+                            )),
+                            _ => Ok((
+                                BootstrapableGraph {
+                                    final_blocks: vec![],
+                                },
+                                PreHashSet::default(),
+                                StreamingStep::Finished(None),
+                            )),
+                        },
+                    );
             });
         controllers
             .protocol_controller
@@ -0,0 +1,176 @@
+//! Typed publish/subscribe bus built on top of `MassaChannel`.
+//!
+//! Producers publish events without knowing who listens to them.
+//! Each subscriber gets its own bounded queue: a slow subscriber only loses its own events
+//! and never blocks the producer or the other subscribers.
+//!
+//! # Example
+//! ```
+//! use massa_channel::event_bus::MassaEventBus;
+//! let bus = MassaEventBus::<u64>::new("test_bus".to_string());
+//! let receiver = bus.subscribe("metrics".to_string(), 16);
+//! bus.publish(42);
+//! assert_eq!(receiver.try_recv().unwrap(), 42);
+//! ```
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, RwLock,
+};
+
+use crossbeam::channel::TrySendError;
+use tracing::{debug, trace};
+
+use crate::{receiver::MassaReceiver, sender::MassaSender, MassaChannel};
+
+/// A subscriber of the bus
+struct Subscriber<E> {
+    /// name of the subscriber, used for logs and channel metrics
+    name: String,
+    /// sending side of the subscriber queue
+    sender: MassaSender<E>,
+    /// set when all the receivers of the subscriber were dropped, the subscriber is then removed from the bus
+    left: AtomicBool,
+}
+
+/// Typed event bus with bounded per-subscriber queues
+#[derive(Clone)]
+pub struct MassaEventBus<E> {
+    /// name of the bus, used as a prefix for subscriber channel names
+    name: String,
+    /// current subscribers
+    subscribers: Arc<RwLock<Vec<Subscriber<E>>>>,
+    /// number of events dropped because a subscriber queue was full
+    dropped: Arc<AtomicU64>,
+}
+
+impl<E: Clone> MassaEventBus<E> {
+    /// Creates a new bus without subscribers
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            subscribers: Default::default(),
+            dropped: Default::default(),
+        }
+    }
+
+    /// Registers a new subscriber.
+    ///
+    /// # Arguments
+    /// * `name`: name of the subscriber
+    /// * `capacity`: maximal number of pending events in the subscriber queue
+    ///
+    /// # Returns
+    /// The receiving side of the subscriber queue.
+    /// The subscription ends when all the clones of the receiver are dropped.
+    pub fn subscribe(&self, name: String, capacity: usize) -> MassaReceiver<E> {
+        let (sender, receiver) =
+            MassaChannel::new(format!("{}_{}", self.name, name), Some(capacity));
+        self.subscribers
+            .write()
+            .expect("event bus lock poisoned")
+            .push(Subscriber {
+                name,
+                sender,
+                left: AtomicBool::new(false),
+            });
+        receiver
+    }
+
+    /// Publishes an event to all the subscribers.
+    ///
+    /// This never blocks: if a subscriber queue is full, the event is dropped for that subscriber.
+    /// Subscribers whose receivers were all dropped are removed from the bus.
+    pub fn publish(&self, event: E) {
+        // publishers share the lock, it is only taken exclusively to add or remove subscribers
+        let mut any_left = false;
+        for subscriber in self
+            .subscribers
+            .read()
+            .expect("event bus lock poisoned")
+            .iter()
+        {
+            match subscriber.sender.try_send(event.clone()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    debug!(
+                        "event bus {}: queue of subscriber {} is full, event dropped",
+                        self.name, subscriber.name
+                    );
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    trace!(
+                        "event bus {}: subscriber {} left",
+                        self.name,
+                        subscriber.name
+                    );
+                    subscriber.left.store(true, Ordering::Relaxed);
+                    any_left = true;
+                }
+            }
+        }
+        if any_left {
+            self.subscribers
+                .write()
+                .expect("event bus lock poisoned")
+                .retain(|subscriber| !subscriber.left.load(Ordering::Relaxed));
+        }
+    }
+
+    /// Returns the number of current subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers
+            .read()
+            .expect("event bus lock poisoned")
+            .len()
+    }
+
+    /// Returns the number of events dropped since the creation of the bus
+    /// because a subscriber queue was full
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MassaEventBus;
+
+    #[test]
+    fn test_publish_to_all_subscribers() {
+        let bus = MassaEventBus::<u64>::new("test_publish_bus".to_string());
+        let first = bus.subscribe("first".to_string(), 4);
+        let second = bus.subscribe("second".to_string(), 4);
+        bus.publish(1);
+        bus.publish(2);
+        assert_eq!(first.try_recv().unwrap(), 1);
+        assert_eq!(first.try_recv().unwrap(), 2);
+        assert_eq!(second.try_recv().unwrap(), 1);
+        assert_eq!(second.try_recv().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_full_queue_drops_events() {
+        let bus = MassaEventBus::<u64>::new("test_full_bus".to_string());
+        let slow = bus.subscribe("slow".to_string(), 1);
+        let fast = bus.subscribe("fast".to_string(), 4);
+        bus.publish(1);
+        bus.publish(2);
+        assert_eq!(bus.dropped_count(), 1);
+        assert_eq!(slow.try_recv().unwrap(), 1);
+        assert!(slow.try_recv().is_err());
+        assert_eq!(fast.try_recv().unwrap(), 1);
+        assert_eq!(fast.try_recv().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_dropped_subscriber_is_removed() {
+        let bus = MassaEventBus::<u64>::new("test_drop_bus".to_string());
+        let receiver = bus.subscribe("gone".to_string(), 4);
+        assert_eq!(bus.subscriber_count(), 1);
+        drop(receiver);
+        bus.publish(1);
+        assert_eq!(bus.subscriber_count(), 0);
+    }
+}
@@ -19,6 +19,7 @@ use std::sync::Arc;
 use receiver::MassaReceiver;
 use sender::MassaSender;
 
+pub mod event_bus;
 pub mod receiver;
 pub mod sender;
 
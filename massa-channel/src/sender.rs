@@ -6,7 +6,7 @@ use std::{
 use crossbeam::channel::{SendError, SendTimeoutError, Sender, TrySendError};
 use prometheus::Gauge;
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct MassaSender<T> {
     pub(crate) sender: Sender<T>,
     #[allow(dead_code)]
@@ -15,6 +15,17 @@ pub struct MassaSender<T> {
     pub(crate) actual_len: Gauge,
 }
 
+// not derived: the messages do not need to be `Clone` for the sender to be cloned
+impl<T> Clone for MassaSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            name: self.name.clone(),
+            actual_len: self.actual_len.clone(),
+        }
+    }
+}
+
 impl<T> MassaSender<T> {
     /// Send a message to the channel
     pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
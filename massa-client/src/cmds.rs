@@ -256,6 +256,27 @@ pub enum Command {
     )]
     sell_rolls,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address PayoutAddress Fee"),
+        message = "send the deferred credits and staking rewards of a wallet address to PayoutAddress (\"none\" to receive them again)"
+    )]
+    set_payout_address,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address OperatorAddress Fee"),
+        message = "let OperatorAddress be drawn with the rolls of a wallet address, sharing its block rewards with it"
+    )]
+    delegate,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address Fee"),
+        message = "stop delegating the rolls of a wallet address"
+    )]
+    undelegate,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "SenderAddress ReceiverAddress Amount Fee"),
@@ -935,6 +956,80 @@ impl Command {
                 .await
             }
 
+            Command::set_payout_address => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() != 3 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = parameters[0].parse::<Address>()?;
+                let payout_address = if parameters[1].eq_ignore_ascii_case("none") {
+                    None
+                } else {
+                    Some(parameters[1].parse::<Address>()?)
+                };
+                let fee = parameters[2].parse::<Amount>()?;
+
+                if payout_address == Some(addr) {
+                    bail!("an address cannot be its own payout address, use \"none\" instead");
+                }
+
+                send_operation(
+                    client,
+                    wallet,
+                    OperationType::SetPayoutAddress { payout_address },
+                    fee,
+                    addr,
+                    json,
+                )
+                .await
+            }
+
+            Command::delegate => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() != 3 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = parameters[0].parse::<Address>()?;
+                let operator = parameters[1].parse::<Address>()?;
+                let fee = parameters[2].parse::<Amount>()?;
+
+                if operator == addr {
+                    bail!("an address cannot delegate its rolls to itself, use undelegate instead");
+                }
+
+                send_operation(
+                    client,
+                    wallet,
+                    OperationType::Delegate { operator },
+                    fee,
+                    addr,
+                    json,
+                )
+                .await
+            }
+
+            Command::undelegate => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = parameters[0].parse::<Address>()?;
+                let fee = parameters[1].parse::<Amount>()?;
+
+                send_operation(
+                    client,
+                    wallet,
+                    OperationType::Undelegate {},
+                    fee,
+                    addr,
+                    json,
+                )
+                .await
+            }
+
             Command::send_transaction => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
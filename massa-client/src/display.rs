@@ -11,7 +11,7 @@ use massa_api_exports::{
 use massa_models::composite::PubkeySig;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
-use massa_models::stats::{ConsensusStats, ExecutionStats, NetworkStats};
+use massa_models::stats::{ConsensusStats, ExecutionStats, NetworkStats, PoSStats};
 use massa_models::{address::Address, config::CompactConfig, operation::OperationId};
 use massa_signature::{KeyPair, PublicKey};
 use massa_wallet::Wallet;
@@ -305,6 +305,35 @@ impl Output for ExecutionStats {
             "\tFinal cursor: {}",
             Style::Protocol.style(self.final_cursor)
         );
+        self.pos_stats.pretty_print();
+    }
+}
+
+impl Output for PoSStats {
+    fn pretty_print(&self) {
+        println!("PoS stats:");
+        println!(
+            "\tApplied slot count: {}",
+            Style::Protocol.style(self.applied_slot_count)
+        );
+        println!(
+            "\tHash computation count: {} ({} per slot)",
+            Style::Protocol.style(self.hash_computation_count),
+            Style::Protocol.style(format!("{:.2}", self.average_hash_computations_per_slot()))
+        );
+        println!(
+            "\tPending deferred credits: {} over {} slots ({} per slot)",
+            Style::Protocol.style(self.deferred_credit_count),
+            Style::Protocol.style(self.deferred_credit_slot_count),
+            Style::Protocol.style(format!("{:.2}", self.average_credits_per_slot()))
+        );
+        for (cycle, count) in &self.deferred_credit_count_history {
+            println!(
+                "\t\tCycle {}: {} pending deferred credits",
+                Style::Protocol.style(cycle),
+                Style::Protocol.style(count)
+            );
+        }
     }
 }
 
@@ -390,6 +419,28 @@ impl Output for ConsensusStats {
             "\tClique count: {}",
             Style::Protocol.style(self.clique_count)
         );
+        println!(
+            "\tPruned clique count: {}",
+            Style::Protocol.style(self.pruned_clique_count)
+        );
+        println!(
+            "\tDuplicate block registrations: {}",
+            Style::Block.style(self.duplicate_block_count)
+        );
+        println!(
+            "\tHeader-only blocks: {}",
+            Style::Block.style(self.header_only_count)
+        );
+        println!(
+            "\tExpired headers: {}",
+            Style::Block.style(self.expired_header_count)
+        );
+        if let Some(gap) = self.execution_finality_gap {
+            println!(
+                "\tFinal slots not yet executed: {}",
+                Style::Block.style(gap)
+            );
+        }
     }
 }
 
@@ -0,0 +1,436 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Append-only, hash-chained log of the consensus decisions.
+//!
+//! Each entry commits to the hash of the previous one, so that modifying, removing or reordering
+//! entries breaks the chain. Checkpoints signed by the node key are appended periodically
+//! so that operators can prove to auditors which history their node has seen.
+//! The log is a file with one JSON entry per line. The hashes cover a canonical binary encoding
+//! of the entries (see `AuditEventSerializer`), not their JSON representation.
+//! A trailing line without line break is the remainder of an interrupted write: it is ignored
+//! by the verification and removed when the log is reopened.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use massa_hash::Hash;
+use massa_models::{
+    block_id::{BlockId, BlockIdSerializer},
+    slot::{Slot, SlotSerializer},
+};
+use massa_serialization::{SerializeError, Serializer, U64VarIntSerializer};
+use massa_signature::{KeyPair, PublicKey, Signature};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{block_status::DiscardReason, error::ConsensusError};
+
+/// A consensus decision recorded in the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditEvent {
+    /// a block was added to the graph
+    BlockAccepted {
+        /// id of the block
+        block_id: BlockId,
+        /// slot of the block
+        slot: Slot,
+    },
+    /// a block was discarded
+    BlockDiscarded {
+        /// id of the block
+        block_id: BlockId,
+        /// slot of the block
+        slot: Slot,
+        /// why the block was discarded
+        reason: DiscardReason,
+    },
+    /// a block became final
+    BlockFinal {
+        /// id of the block
+        block_id: BlockId,
+        /// slot of the block
+        slot: Slot,
+    },
+    /// the blockclique changed and some of its previous blocks were left out
+    BlockcliqueSwitch {
+        /// blocks that left the blockclique, sorted
+        removed: Vec<BlockId>,
+        /// blocks that joined the blockclique, sorted
+        added: Vec<BlockId>,
+    },
+    /// signature of the hash of the previous entry by the node key
+    Checkpoint {
+        /// public key of the node
+        public_key: PublicKey,
+        /// signature of the hash of the previous entry
+        signature: Signature,
+    },
+}
+
+/// Canonical binary encoding of the `AuditEvent`s, hashed in the chain
+#[derive(Default)]
+pub struct AuditEventSerializer {
+    u64_serializer: U64VarIntSerializer,
+    slot_serializer: SlotSerializer,
+    block_id_serializer: BlockIdSerializer,
+}
+
+impl AuditEventSerializer {
+    /// Creates an `AuditEventSerializer`
+    pub fn new() -> Self {
+        Self {
+            u64_serializer: U64VarIntSerializer::new(),
+            slot_serializer: SlotSerializer::new(),
+            block_id_serializer: BlockIdSerializer::new(),
+        }
+    }
+
+    fn serialize_block_ids(
+        &self,
+        block_ids: &[BlockId],
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.u64_serializer
+            .serialize(&(block_ids.len() as u64), buffer)?;
+        for block_id in block_ids {
+            self.block_id_serializer.serialize(block_id, buffer)?;
+        }
+        Ok(())
+    }
+
+    fn serialize_discard_reason(
+        &self,
+        reason: &DiscardReason,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        match reason {
+            DiscardReason::Invalid(message) => {
+                buffer.push(0);
+                self.u64_serializer
+                    .serialize(&(message.len() as u64), buffer)?;
+                buffer.extend(message.as_bytes());
+            }
+            DiscardReason::Stale => buffer.push(1),
+            DiscardReason::Final => buffer.push(2),
+            DiscardReason::ParentTooOld {
+                parent,
+                age,
+                max_age,
+            } => {
+                buffer.push(3);
+                self.block_id_serializer.serialize(parent, buffer)?;
+                self.u64_serializer.serialize(age, buffer)?;
+                self.u64_serializer.serialize(max_age, buffer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serializer<AuditEvent> for AuditEventSerializer {
+    fn serialize(&self, value: &AuditEvent, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        match value {
+            AuditEvent::BlockAccepted { block_id, slot } => {
+                buffer.push(0);
+                self.block_id_serializer.serialize(block_id, buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+            }
+            AuditEvent::BlockDiscarded {
+                block_id,
+                slot,
+                reason,
+            } => {
+                buffer.push(1);
+                self.block_id_serializer.serialize(block_id, buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+                self.serialize_discard_reason(reason, buffer)?;
+            }
+            AuditEvent::BlockFinal { block_id, slot } => {
+                buffer.push(2);
+                self.block_id_serializer.serialize(block_id, buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+            }
+            AuditEvent::BlockcliqueSwitch { removed, added } => {
+                buffer.push(3);
+                self.serialize_block_ids(removed, buffer)?;
+                self.serialize_block_ids(added, buffer)?;
+            }
+            AuditEvent::Checkpoint {
+                public_key,
+                signature,
+            } => {
+                buffer.push(4);
+                buffer.extend(public_key.to_bytes());
+                buffer.extend(signature.to_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An entry of the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// position of the entry in the log, starting at 0
+    pub index: u64,
+    /// time at which the entry was appended
+    pub timestamp: MassaTime,
+    /// the recorded decision
+    pub event: AuditEvent,
+    /// hash of the previous entry, zero for the first entry
+    pub previous_hash: Hash,
+    /// hash of the previous hash, index, timestamp and event of this entry
+    pub hash: Hash,
+}
+
+impl AuditEntry {
+    /// Compute the hash an entry must have
+    fn compute_hash(
+        index: u64,
+        timestamp: MassaTime,
+        event: &AuditEvent,
+        previous_hash: &Hash,
+    ) -> Result<Hash, ConsensusError> {
+        let mut event_bytes = Vec::new();
+        AuditEventSerializer::new()
+            .serialize(event, &mut event_bytes)
+            .map_err(|err| ConsensusError::SerializationError(err.to_string()))?;
+        Ok(Hash::compute_from_tuple(&[
+            previous_hash.to_bytes(),
+            &index.to_be_bytes(),
+            &timestamp.as_millis().to_be_bytes(),
+            &event_bytes,
+        ]))
+    }
+}
+
+/// Writer of the audit log
+pub struct AuditLog {
+    /// file the entries are appended to
+    file: File,
+    /// key signing the checkpoints, no checkpoint is written without it
+    keypair: Option<KeyPair>,
+    /// number of entries between two checkpoints
+    checkpoint_interval: u64,
+    /// index of the next entry
+    next_index: u64,
+    /// hash of the last entry
+    last_hash: Hash,
+    /// number of entries since the last checkpoint
+    since_checkpoint: u64,
+}
+
+impl AuditLog {
+    /// Open the audit log, creating it if needed. The existing entries are verified first,
+    /// and the remainder of an interrupted write is removed.
+    ///
+    /// # Arguments
+    /// * `path`: file of the log
+    /// * `keypair`: key signing the checkpoints
+    /// * `checkpoint_interval`: number of entries between two checkpoints, 0 to disable checkpoints
+    pub fn open(
+        path: PathBuf,
+        keypair: Option<KeyPair>,
+        checkpoint_interval: u64,
+    ) -> Result<Self, ConsensusError> {
+        let (mut next_index, mut last_hash) = (0, Hash::zero());
+        if path.exists() {
+            let (last, complete_len) = AuditLog::read_chain(&path)?;
+            if let Some(last) = last {
+                next_index = last.index + 1;
+                last_hash = last.hash;
+            }
+            OpenOptions::new()
+                .write(true)
+                .open(&path)?
+                .set_len(complete_len)?;
+        } else if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let log = AuditLog {
+            file: OpenOptions::new().create(true).append(true).open(&path)?,
+            keypair,
+            checkpoint_interval,
+            next_index,
+            last_hash,
+            since_checkpoint: 0,
+        };
+        Ok(log)
+    }
+
+    /// Append a decision to the log, followed by a checkpoint if one is due
+    pub fn append(&mut self, event: AuditEvent) -> Result<(), ConsensusError> {
+        self.write_entry(event)?;
+        self.since_checkpoint += 1;
+        if self.checkpoint_interval > 0 && self.since_checkpoint >= self.checkpoint_interval {
+            if let Some(keypair) = &self.keypair {
+                let signature = keypair.sign(&self.last_hash).map_err(|err| {
+                    ConsensusError::AuditLogError(format!("could not sign checkpoint: {}", err))
+                })?;
+                let public_key = keypair.get_public_key();
+                self.write_entry(AuditEvent::Checkpoint {
+                    public_key,
+                    signature,
+                })?;
+            }
+            self.since_checkpoint = 0;
+        }
+        Ok(())
+    }
+
+    /// Write an entry chained to the previous one
+    fn write_entry(&mut self, event: AuditEvent) -> Result<(), ConsensusError> {
+        let timestamp = MassaTime::now();
+        let hash = AuditEntry::compute_hash(self.next_index, timestamp, &event, &self.last_hash)?;
+        let entry = AuditEntry {
+            index: self.next_index,
+            timestamp,
+            event,
+            previous_hash: self.last_hash,
+            hash,
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.flush()?;
+        self.next_index += 1;
+        self.last_hash = hash;
+        Ok(())
+    }
+
+    /// Check the hash chain and the checkpoint signatures of a log file.
+    /// A trailing line without line break, left by an interrupted write, is ignored.
+    ///
+    /// # Returns
+    /// The last entry of the log, `None` if it is empty,
+    /// or an error describing the first entry that breaks the chain
+    pub fn verify(path: &Path) -> Result<Option<AuditEntry>, ConsensusError> {
+        Ok(AuditLog::read_chain(path)?.0)
+    }
+
+    /// Check the complete lines of a log file
+    ///
+    /// # Returns
+    /// The last entry of the log and the length of its complete lines
+    fn read_chain(path: &Path) -> Result<(Option<AuditEntry>, u64), ConsensusError> {
+        let content = std::fs::read(path)?;
+        // every entry is written with its line break: what follows the last one is a torn write
+        let complete_len = content
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |position| position + 1);
+        let mut last: Option<AuditEntry> = None;
+        for line in content[..complete_len].split(|byte| *byte == b'\n') {
+            if line.is_empty() {
+                continue;
+            }
+            let entry: AuditEntry = serde_json::from_slice(line)?;
+            let (expected_index, expected_previous) = match &last {
+                Some(last) => (last.index + 1, last.hash),
+                None => (0, Hash::zero()),
+            };
+            if entry.index != expected_index || entry.previous_hash != expected_previous {
+                return Err(ConsensusError::AuditLogError(format!(
+                    "entry {} is not chained to the previous entry",
+                    entry.index
+                )));
+            }
+            let hash = AuditEntry::compute_hash(
+                entry.index,
+                entry.timestamp,
+                &entry.event,
+                &entry.previous_hash,
+            )?;
+            if hash != entry.hash {
+                return Err(ConsensusError::AuditLogError(format!(
+                    "entry {} does not match its hash",
+                    entry.index
+                )));
+            }
+            if let AuditEvent::Checkpoint {
+                public_key,
+                signature,
+            } = &entry.event
+            {
+                public_key
+                    .verify_signature(&entry.previous_hash, signature)
+                    .map_err(|err| {
+                        ConsensusError::AuditLogError(format!(
+                            "invalid checkpoint signature at entry {}: {}",
+                            entry.index, err
+                        ))
+                    })?;
+            }
+            last = Some(entry);
+        }
+        Ok((last, complete_len as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_log_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+        let block_id = BlockId::generate_from_hash(Hash::compute_from("block".as_bytes()));
+        let slot = Slot::new(1, 0);
+
+        let mut log = AuditLog::open(path.clone(), Some(KeyPair::generate(0).unwrap()), 2).unwrap();
+        log.append(AuditEvent::BlockAccepted { block_id, slot })
+            .unwrap();
+        log.append(AuditEvent::BlockFinal { block_id, slot })
+            .unwrap();
+        drop(log);
+
+        // reopening continues the chain
+        let mut log = AuditLog::open(path.clone(), None, 2).unwrap();
+        log.append(AuditEvent::BlockDiscarded {
+            block_id,
+            slot,
+            reason: DiscardReason::Stale,
+        })
+        .unwrap();
+        drop(log);
+        let last = AuditLog::verify(&path).unwrap().unwrap();
+        assert_eq!(last.index, 3);
+
+        // any modification breaks the chain
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, content.replacen("BlockFinal", "BlockAccepted", 1)).unwrap();
+        assert!(AuditLog::verify(&path).is_err());
+    }
+
+    #[test]
+    fn test_audit_log_torn_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+        let block_id = BlockId::generate_from_hash(Hash::compute_from("block".as_bytes()));
+        let slot = Slot::new(1, 0);
+
+        let mut log = AuditLog::open(path.clone(), None, 0).unwrap();
+        log.append(AuditEvent::BlockAccepted { block_id, slot })
+            .unwrap();
+        drop(log);
+        let complete = std::fs::read(&path).unwrap();
+
+        // a write interrupted in the middle of an entry
+        let mut torn = complete.clone();
+        torn.extend_from_slice(b"{\"index\":1,\"timest");
+        std::fs::write(&path, &torn).unwrap();
+        assert_eq!(AuditLog::verify(&path).unwrap().unwrap().index, 0);
+
+        // reopening removes it and continues the chain
+        let mut log = AuditLog::open(path.clone(), None, 0).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), complete);
+        log.append(AuditEvent::BlockFinal { block_id, slot })
+            .unwrap();
+        drop(log);
+        assert_eq!(AuditLog::verify(&path).unwrap().unwrap().index, 1);
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Persistent list of the block creators banned by consensus.
+//!
+//! Creators whose blocks were found invalid are banned for a while: their headers are dropped
+//! as soon as they are received. The list is saved on disk so that bans survive restarts.
+
+use std::path::PathBuf;
+
+use massa_models::{address::Address, prehash::PreHashMap};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ConsensusError;
+
+/// A banned block creator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreatorBan {
+    /// address of the creator
+    pub address: Address,
+    /// why the creator was banned
+    pub reason: String,
+    /// time at which the ban ends
+    pub expires_at: MassaTime,
+}
+
+/// List of banned block creators, saved to a file on each change
+#[derive(Debug, Clone, Default)]
+pub struct CreatorBanList {
+    /// file the list is saved to, `None` to keep it in memory only
+    path: Option<PathBuf>,
+    /// bans indexed by creator address
+    bans: PreHashMap<Address, CreatorBan>,
+}
+
+impl CreatorBanList {
+    /// Load the list from its file, expired bans are dropped.
+    /// A missing file gives an empty list.
+    pub fn load(path: Option<PathBuf>) -> Result<Self, ConsensusError> {
+        let mut list = CreatorBanList {
+            path,
+            bans: Default::default(),
+        };
+        if let Some(path) = list.path.as_ref().filter(|path| path.exists()) {
+            let bans: Vec<CreatorBan> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+            let now = MassaTime::now();
+            list.bans = bans
+                .into_iter()
+                .filter(|ban| ban.expires_at > now)
+                .map(|ban| (ban.address, ban))
+                .collect();
+        }
+        Ok(list)
+    }
+
+    /// Write the list to its file
+    fn save(&self) -> Result<(), ConsensusError> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_string_pretty(&self.list())?)?;
+        }
+        Ok(())
+    }
+
+    /// Ban a creator until `expires_at`, an existing ban is replaced
+    pub fn ban(
+        &mut self,
+        address: Address,
+        reason: String,
+        expires_at: MassaTime,
+    ) -> Result<(), ConsensusError> {
+        self.bans.insert(
+            address,
+            CreatorBan {
+                address,
+                reason,
+                expires_at,
+            },
+        );
+        self.save()
+    }
+
+    /// Lift the bans of some creators
+    pub fn unban(&mut self, addresses: &[Address]) -> Result<(), ConsensusError> {
+        let mut changed = false;
+        for address in addresses {
+            changed |= self.bans.remove(address).is_some();
+        }
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Whether a creator is banned at a given time
+    pub fn is_banned(&self, address: &Address, now: MassaTime) -> bool {
+        self.bans
+            .get(address)
+            .map_or(false, |ban| ban.expires_at > now)
+    }
+
+    /// Drop the expired bans
+    pub fn prune_expired(&mut self, now: MassaTime) -> Result<(), ConsensusError> {
+        let len = self.bans.len();
+        self.bans.retain(|_, ban| ban.expires_at > now);
+        if self.bans.len() != len {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Get the bans, sorted by expiry time
+    pub fn list(&self) -> Vec<CreatorBan> {
+        let mut bans: Vec<CreatorBan> = self.bans.values().cloned().collect();
+        bans.sort_unstable_by_key(|ban| (ban.expires_at, ban.address));
+        bans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_creator_ban_list_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("creator_bans.json");
+        let address = || Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let (addr1, addr2) = (address(), address());
+        let now = MassaTime::now();
+        let later = now.saturating_add(MassaTime::from_millis(60_000));
+
+        let mut list = CreatorBanList::load(Some(path.clone())).unwrap();
+        list.ban(addr1, "invalid block".to_string(), later).unwrap();
+        list.ban(addr2, "invalid block".to_string(), later).unwrap();
+        list.unban(&[addr2]).unwrap();
+
+        // the bans survive a reload
+        let list = CreatorBanList::load(Some(path)).unwrap();
+        assert!(list.is_banned(&addr1, now));
+        assert!(!list.is_banned(&addr2, now));
+        assert!(!list.is_banned(&addr1, later));
+        assert_eq!(list.list().len(), 1);
+    }
+}
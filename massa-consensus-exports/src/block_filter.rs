@@ -0,0 +1,54 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::{address::Address, block_id::BlockId, slot::Slot};
+use serde::{Deserialize, Serialize};
+
+/// Properties searched in the active blocks of the graph.
+/// A block matches if it has all the given properties, an empty filter matches all the blocks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockFilter {
+    /// address of the creator of the block
+    pub creator: Option<Address>,
+    /// first slot of the block, included
+    pub start_slot: Option<Slot>,
+    /// last slot of the block, included
+    pub end_slot: Option<Slot>,
+    /// minimum number of endorsements included in the block
+    pub min_endorsement_count: Option<usize>,
+    /// block referenced as a parent by the block
+    pub parent: Option<BlockId>,
+    /// finality of the block
+    pub is_final: Option<bool>,
+}
+
+impl BlockFilter {
+    /// Check if a block matches the filter
+    ///
+    /// # Arguments
+    /// * `creator`: address of the creator of the block
+    /// * `slot`: slot of the block
+    /// * `endorsement_count`: number of endorsements included in the block
+    /// * `parents`: parents of the block with their periods, one per thread
+    /// * `is_final`: finality of the block
+    pub fn matches(
+        &self,
+        creator: &Address,
+        slot: &Slot,
+        endorsement_count: usize,
+        parents: &[(BlockId, u64)],
+        is_final: bool,
+    ) -> bool {
+        self.creator.map_or(true, |address| &address == creator)
+            && self.start_slot.map_or(true, |start| slot >= &start)
+            && self.end_slot.map_or(true, |end| slot <= &end)
+            && self
+                .min_endorsement_count
+                .map_or(true, |min| endorsement_count >= min)
+            && self.parent.map_or(true, |parent| {
+                parents
+                    .iter()
+                    .any(|(parent_id, _period)| parent_id == &parent)
+            })
+            && self.is_final.map_or(true, |wanted| wanted == is_final)
+    }
+}
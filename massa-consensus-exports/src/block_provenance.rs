@@ -0,0 +1,79 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Where the blocks known by consensus come from, and how fast the peers relay them.
+
+use massa_protocol_exports::PeerId;
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Module a block was first registered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlockOrigin {
+    /// produced by the block factory of the node
+    Local,
+    /// submitted through the API of the node
+    Api,
+    /// received from a peer
+    Peer(#[serde(with = "peer_id_string")] PeerId),
+    /// loaded from the bootstrap graph
+    Bootstrap,
+    /// registered without telling where it comes from
+    Unknown,
+}
+
+impl BlockOrigin {
+    /// Whether the block was produced by the node
+    pub fn is_local(&self) -> bool {
+        matches!(self, BlockOrigin::Local)
+    }
+}
+
+/// Origin of a block, recorded at its first registration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockProvenance {
+    /// module that registered the block or its header first
+    pub origin: BlockOrigin,
+    /// time of the first registration
+    pub first_seen: MassaTime,
+}
+
+impl BlockProvenance {
+    /// Provenance of a block registered now
+    pub fn now(origin: BlockOrigin) -> Self {
+        BlockProvenance {
+            origin,
+            first_seen: MassaTime::now(),
+        }
+    }
+}
+
+/// Delays between the slot timestamps of the blocks first received from a peer and their reception
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerPropagationStats {
+    /// the peer
+    #[serde(with = "peer_id_string")]
+    pub peer_id: PeerId,
+    /// number of blocks first received from the peer
+    pub block_count: u64,
+    /// mean delay between the slot timestamp of those blocks and their reception
+    pub mean_latency: MassaTime,
+    /// maximum delay between the slot timestamp of those blocks and their reception
+    pub max_latency: MassaTime,
+}
+
+/// Peer ids are serialized as their public key string
+mod peer_id_string {
+    use std::str::FromStr;
+
+    use massa_protocol_exports::PeerId;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(peer_id: &PeerId, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(peer_id)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PeerId, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        PeerId::from_str(&s).map_err(D::Error::custom)
+    }
+}
@@ -9,6 +9,9 @@ use massa_models::{
 };
 use massa_storage::Storage;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{block_provenance::BlockProvenance, block_summary::BlockSummary};
 
 #[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
@@ -40,6 +43,36 @@ pub enum DiscardReason {
     Stale,
     /// Block has enough fitness.
     Final,
+    /// A parent of the block is more periods behind it than allowed by the `MAX_PARENT_AGE` network constant.
+    ParentTooOld {
+        /// the parent that is too old
+        parent: BlockId,
+        /// number of periods between the parent and the block
+        age: u64,
+        /// maximum age allowed when the block was checked
+        max_age: u64,
+    },
+}
+
+/// Number of blocks known by consensus in each status
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockCountsByStatus {
+    /// active final blocks
+    pub active_final: usize,
+    /// active non-final blocks
+    pub active_non_final: usize,
+    /// blocks discarded because they are invalid or have a parent that is too old
+    pub discarded_invalid: usize,
+    /// blocks discarded because they are incompatible with a final block
+    pub discarded_stale: usize,
+    /// final blocks discarded after being pruned from the graph
+    pub discarded_final: usize,
+    /// incoming blocks and headers, not processed yet
+    pub incoming: usize,
+    /// blocks and headers waiting for their slot
+    pub waiting_for_slot: usize,
+    /// blocks and headers waiting for their dependencies
+    pub waiting_for_dependencies: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -69,6 +102,14 @@ impl From<&BlockStatus> for BlockStatusId {
 pub enum StorageOrBlock {
     /// Keep a full storage with operations
     Storage(Storage),
+    /// Keep a storage with the block and its endorsements,
+    /// the operations being owned by a storage shared with the blocks that have the same operations
+    SharedOperations {
+        /// storage owning the block and its endorsements
+        storage: Storage,
+        /// storage owning the operations of the block
+        operations: Arc<Storage>,
+    },
     /// Keep only the block header and list of ops (but not the ops)
     Block(Box<SecureShareBlock>),
 }
@@ -79,29 +120,86 @@ impl StorageOrBlock {
     /// (and not a copy of the block status itself)
     pub fn clone_block(&self, block_id: &BlockId) -> SecureShareBlock {
         match self {
-            StorageOrBlock::Storage(storage) => storage
-                .read_blocks()
-                .get(block_id)
-                .expect("block absent from its own storage")
-                .clone(),
+            StorageOrBlock::Storage(storage) | StorageOrBlock::SharedOperations { storage, .. } => {
+                storage
+                    .read_blocks()
+                    .get(block_id)
+                    .expect("block absent from its own storage")
+                    .clone()
+            }
             StorageOrBlock::Block(block) => *block.clone(),
         }
     }
 
+    /// Call `f` with a reference to the underlying block, without cloning it
+    pub fn with_block<R>(&self, block_id: &BlockId, f: impl FnOnce(&SecureShareBlock) -> R) -> R {
+        match self {
+            StorageOrBlock::Storage(storage) | StorageOrBlock::SharedOperations { storage, .. } => {
+                f(storage
+                    .read_blocks()
+                    .get(block_id)
+                    .expect("block absent from its own storage"))
+            }
+            StorageOrBlock::Block(block) => f(block),
+        }
+    }
+
     /// Convert any StorageOrBlock variant into a StorageOrBlock::Block variant.
     /// This effectively drops the operations of the block.
     pub fn strip_to_block(&mut self, block_id: &BlockId) {
-        let block = if let StorageOrBlock::Storage(storage) = self {
-            storage
-                .read_blocks()
-                .get(block_id)
-                .expect("block absent from its own storage")
-                .clone()
-        } else {
-            return;
+        let block = match self {
+            StorageOrBlock::Storage(storage) | StorageOrBlock::SharedOperations { storage, .. } => {
+                storage
+                    .read_blocks()
+                    .get(block_id)
+                    .expect("block absent from its own storage")
+                    .clone()
+            }
+            StorageOrBlock::Block(_) => return,
         };
         *self = StorageOrBlock::Block(Box::new(block));
     }
+
+    /// Summarize the underlying block, the fees being known only if the operations were kept
+    pub fn summarize(&self, block_id: &BlockId) -> BlockSummary {
+        match self {
+            StorageOrBlock::Storage(storage) => BlockSummary::new(
+                storage
+                    .read_blocks()
+                    .get(block_id)
+                    .expect("block absent from its own storage"),
+                Some(storage),
+            ),
+            StorageOrBlock::SharedOperations {
+                storage,
+                operations,
+            } => BlockSummary::new(
+                storage
+                    .read_blocks()
+                    .get(block_id)
+                    .expect("block absent from its own storage"),
+                Some(operations.as_ref()),
+            ),
+            StorageOrBlock::Block(block) => BlockSummary::new(block, None),
+        }
+    }
+
+    /// Return a storage owning the block, its endorsements and its operations,
+    /// or `None` if the operations were dropped
+    pub fn clone_storage(&self) -> Option<Storage> {
+        match self {
+            StorageOrBlock::Storage(storage) => Some(storage.clone()),
+            StorageOrBlock::SharedOperations {
+                storage,
+                operations,
+            } => {
+                let mut res = storage.clone();
+                res.extend(operations.as_ref().clone());
+                Some(res)
+            }
+            StorageOrBlock::Block(_) => None,
+        }
+    }
 }
 
 /// Enum used in `BlockGraph`'s state machine
@@ -126,6 +224,8 @@ pub enum BlockStatus {
     Active {
         a_block: Box<ActiveBlock>,
         storage_or_block: StorageOrBlock,
+        /// Summary computed when the block was added to the graph
+        summary: BlockSummary,
     },
     /// The block was discarded and is kept to avoid reprocessing it
     Discarded {
@@ -142,6 +242,58 @@ pub enum BlockStatus {
     },
 }
 
+/// What consensus holds of a block, whatever its status in the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HeaderKnowledge {
+    /// the block is not known
+    Unknown,
+    /// only the header of the block is known, its body is still needed
+    HeaderOnly,
+    /// the full block is held
+    Full,
+    /// the block is final
+    Final,
+    /// the block was discarded, its body is not needed anymore
+    Discarded,
+}
+
+impl HeaderKnowledge {
+    /// Whether the body of the block does not need to be retrieved anymore
+    pub fn is_body_known(&self) -> bool {
+        matches!(
+            self,
+            HeaderKnowledge::Full | HeaderKnowledge::Final | HeaderKnowledge::Discarded
+        )
+    }
+}
+
+impl From<&BlockStatus> for HeaderKnowledge {
+    fn from(status: &BlockStatus) -> Self {
+        match status {
+            BlockStatus::Incoming(HeaderOrBlock::Header(_))
+            | BlockStatus::WaitingForSlot(HeaderOrBlock::Header(_))
+            | BlockStatus::WaitingForDependencies {
+                header_or_block: HeaderOrBlock::Header(_),
+                ..
+            } => HeaderKnowledge::HeaderOnly,
+            BlockStatus::Incoming(HeaderOrBlock::Block { .. })
+            | BlockStatus::WaitingForSlot(HeaderOrBlock::Block { .. })
+            | BlockStatus::WaitingForDependencies {
+                header_or_block: HeaderOrBlock::Block { .. },
+                ..
+            } => HeaderKnowledge::Full,
+            BlockStatus::Active { a_block, .. } if a_block.is_final => HeaderKnowledge::Final,
+            BlockStatus::Active { .. } => HeaderKnowledge::Full,
+            // final blocks pruned from the graph are kept as discarded
+            BlockStatus::Discarded {
+                reason: DiscardReason::Final,
+                ..
+            } => HeaderKnowledge::Final,
+            BlockStatus::Discarded { .. } => HeaderKnowledge::Discarded,
+        }
+    }
+}
+
 /// Block status in the graph that can be exported.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExportBlockStatus {
@@ -172,6 +324,8 @@ pub struct ExportCompiledBlock {
     pub children: Vec<PreHashSet<BlockId>>,
     /// Active or final
     pub is_final: bool,
+    /// Origin and first-seen time of the block, unknown for the genesis blocks
+    pub provenance: Option<BlockProvenance>,
 }
 
 /// Status
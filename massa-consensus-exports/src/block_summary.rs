@@ -0,0 +1,52 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::{amount::Amount, block::SecureShareBlock, block_id::BlockId, slot::Slot};
+use massa_storage::Storage;
+use serde::{Deserialize, Serialize};
+
+/// Compact description of an active block, computed once when the block is added to the graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockSummary {
+    /// id of the block
+    pub block_id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// number of operations included in the block
+    pub operation_count: usize,
+    /// sum of the fees of the operations of the block,
+    /// `None` if the operations were not available when the block was added (e.g. bootstrapped blocks)
+    pub total_fees: Option<Amount>,
+    /// number of endorsements included in the block
+    pub endorsement_count: usize,
+    /// size of the serialized signed block, in bytes
+    pub size: usize,
+}
+
+impl BlockSummary {
+    /// Summarize a block
+    ///
+    /// # Arguments
+    /// * `block`: the block to summarize
+    /// * `operations`: a storage owning the operations of the block, if they are available
+    pub fn new(block: &SecureShareBlock, operations: Option<&Storage>) -> Self {
+        let total_fees = operations.map(|storage| {
+            let ops = storage.read_operations();
+            block
+                .content
+                .operations
+                .iter()
+                .filter_map(|op_id| ops.get(op_id))
+                .fold(Amount::zero(), |total, op| {
+                    total.saturating_add(op.content.fee)
+                })
+        });
+        BlockSummary {
+            block_id: block.id,
+            slot: block.content.header.content.slot,
+            operation_count: block.content.operations.len(),
+            total_fees,
+            endorsement_count: block.content.header.content.endorsements.len(),
+            size: block.serialized_size(),
+        }
+    }
+}
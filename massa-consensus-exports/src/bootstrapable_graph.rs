@@ -1,14 +1,15 @@
 use crate::export_active_block::{
     ExportActiveBlock, ExportActiveBlockDeserializer, ExportActiveBlockSerializer,
 };
-use massa_models::block::BlockDeserializerArgs;
+use massa_models::{block::BlockDeserializerArgs, block_id::BlockId, slot::Slot};
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U32VarIntDeserializer, U32VarIntSerializer,
 };
 use nom::error::{ContextError, ParseError};
 use nom::{error::context, multi::length_count, sequence::tuple, IResult, Parser};
 use serde::{Deserialize, Serialize};
-use std::ops::Bound::Included;
+use std::collections::BTreeMap;
+use std::ops::{Bound::Included, RangeBounds};
 
 /// Bootstrap graph
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +18,79 @@ pub struct BootstrapableGraph {
     pub final_blocks: Vec<ExportActiveBlock>,
 }
 
+/// Differences between two bootstrap graphs, each list being sorted by block id
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootstrapableGraphDiff {
+    /// blocks of the first graph that are not in the other one
+    pub only_in_self: Vec<BlockId>,
+    /// blocks of the other graph that are not in the first one
+    pub only_in_other: Vec<BlockId>,
+    /// blocks of both graphs whose parents differ
+    pub mismatched_parents: Vec<BlockId>,
+    /// blocks of both graphs whose finality flags differ
+    pub mismatched_finality: Vec<BlockId>,
+}
+
+impl BootstrapableGraphDiff {
+    /// Whether the two graphs hold the same blocks with the same parents and finality
+    pub fn is_empty(&self) -> bool {
+        self.only_in_self.is_empty()
+            && self.only_in_other.is_empty()
+            && self.mismatched_parents.is_empty()
+            && self.mismatched_finality.is_empty()
+    }
+}
+
+impl BootstrapableGraph {
+    /// Compare the blocks of two bootstrap graphs, e.g. the graphs exported by two bootstrap servers
+    pub fn diff(&self, other: &BootstrapableGraph) -> BootstrapableGraphDiff {
+        self.diff_in_slots(other, ..)
+    }
+
+    /// Compare the blocks of two bootstrap graphs whose slots are in `slots`,
+    /// e.g. the slots final in both graphs when they were exported at different slots
+    pub fn diff_in_slots(
+        &self,
+        other: &BootstrapableGraph,
+        slots: impl RangeBounds<Slot>,
+    ) -> BootstrapableGraphDiff {
+        let blocks = self.blocks_in_slots(&slots);
+        let other_blocks = other.blocks_in_slots(&slots);
+
+        let mut diff = BootstrapableGraphDiff::default();
+        for (block_id, export_block) in blocks.iter() {
+            let Some(other_export_block) = other_blocks.get(block_id) else {
+                diff.only_in_self.push(*block_id);
+                continue;
+            };
+            if export_block.parents != other_export_block.parents {
+                diff.mismatched_parents.push(*block_id);
+            }
+            if export_block.is_final != other_export_block.is_final {
+                diff.mismatched_finality.push(*block_id);
+            }
+        }
+        diff.only_in_other = other_blocks
+            .keys()
+            .filter(|block_id| !blocks.contains_key(block_id))
+            .copied()
+            .collect();
+        diff
+    }
+
+    /// Index the blocks whose slots are in `slots` by id
+    fn blocks_in_slots(
+        &self,
+        slots: &impl RangeBounds<Slot>,
+    ) -> BTreeMap<BlockId, &ExportActiveBlock> {
+        self.final_blocks
+            .iter()
+            .filter(|export_block| slots.contains(&export_block.block.content.header.content.slot))
+            .map(|export_block| (export_block.block.id, export_block))
+            .collect()
+    }
+}
+
 /// Basic serializer for `BootstrapableGraph`
 #[derive(Default)]
 pub struct BootstrapableGraphSerializer {
@@ -136,3 +210,172 @@ impl Deserializer<BootstrapableGraph> for BootstrapableGraphDeserializer {
         .parse(buffer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_models::{
+        block::{Block, BlockSerializer},
+        block_header::{BlockHeader, BlockHeaderSerializer},
+        config::CHAINID,
+        secure_share::SecureShareContent,
+        test_exports::{assert_golden_vector, read_golden_vector},
+    };
+    use massa_serialization::DeserializeError;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    fn export_block(keypair: &KeyPair, slot: Slot, is_final: bool) -> ExportActiveBlock {
+        let header = BlockHeader::new_verifiable(
+            BlockHeader {
+                current_version: 0,
+                announced_version: None,
+                slot,
+                parents: Vec::new(),
+                operation_merkle_root: Hash::compute_from(&[]),
+                endorsements: Vec::new(),
+                denunciations: Vec::new(),
+                extensions: Vec::new(),
+            },
+            BlockHeaderSerializer::new(),
+            keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        let block = Block::new_verifiable(
+            Block {
+                header,
+                operations: Vec::new(),
+            },
+            BlockSerializer::new(),
+            keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        ExportActiveBlock {
+            block,
+            parents: Vec::new(),
+            is_final,
+        }
+    }
+
+    #[test]
+    fn test_bootstrapable_graph_diff() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let common = export_block(&keypair, Slot::new(0, 0), true);
+        let only_in_first = export_block(&keypair, Slot::new(0, 1), true);
+        let only_in_second = export_block(&keypair, Slot::new(1, 0), true);
+        let other_finality = export_block(&keypair, Slot::new(1, 1), true);
+        let other_parents = export_block(&keypair, Slot::new(2, 0), true);
+
+        let graph = BootstrapableGraph {
+            final_blocks: vec![
+                common.clone(),
+                only_in_first.clone(),
+                other_finality.clone(),
+                other_parents.clone(),
+            ],
+        };
+        assert!(graph.diff(&graph).is_empty());
+
+        let other_graph = BootstrapableGraph {
+            final_blocks: vec![
+                only_in_second.clone(),
+                common,
+                ExportActiveBlock {
+                    is_final: false,
+                    ..other_finality.clone()
+                },
+                ExportActiveBlock {
+                    parents: vec![(only_in_first.block.id, 0)],
+                    ..other_parents.clone()
+                },
+            ],
+        };
+        let diff = graph.diff(&other_graph);
+        assert_eq!(diff.only_in_self, vec![only_in_first.block.id]);
+        assert_eq!(diff.only_in_other, vec![only_in_second.block.id]);
+        assert_eq!(diff.mismatched_finality, vec![other_finality.block.id]);
+        assert_eq!(diff.mismatched_parents, vec![other_parents.block.id]);
+
+        // only the blocks of the given slots are compared
+        let diff = graph.diff_in_slots(&other_graph, Slot::new(0, 1)..=Slot::new(1, 0));
+        assert_eq!(diff.only_in_self, vec![only_in_first.block.id]);
+        assert_eq!(diff.only_in_other, vec![only_in_second.block.id]);
+        assert!(diff.mismatched_finality.is_empty());
+        assert!(diff.mismatched_parents.is_empty());
+    }
+
+    #[test]
+    fn test_bootstrapable_graph_golden_vector() {
+        // signatures are deterministic, so a fixed keypair gives a fixed vector
+        let keypair =
+            KeyPair::from_str("S1bXjyPwrssNmG4oUG5SEqaUhQkVArQi7rzQDWpCprTSmEgZDGG").unwrap();
+        let header = BlockHeader::new_verifiable(
+            BlockHeader {
+                current_version: 0,
+                announced_version: None,
+                slot: Slot::new(0, 0),
+                parents: Vec::new(),
+                operation_merkle_root: Hash::from_bytes(&[0x33; 32]),
+                endorsements: Vec::new(),
+                denunciations: Vec::new(),
+                extensions: Vec::new(),
+            },
+            BlockHeaderSerializer::new(),
+            &keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        let block = Block::new_verifiable(
+            Block {
+                header,
+                operations: Vec::new(),
+            },
+            BlockSerializer::new(),
+            &keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        let graph = BootstrapableGraph {
+            final_blocks: vec![ExportActiveBlock {
+                block,
+                parents: Vec::new(),
+                is_final: true,
+            }],
+        };
+
+        let mut buf = Vec::new();
+        BootstrapableGraphSerializer::new()
+            .serialize(&graph, &mut buf)
+            .unwrap();
+        assert_golden_vector(env!("CARGO_MANIFEST_DIR"), "bootstrapable_graph", &buf);
+
+        let golden = read_golden_vector(env!("CARGO_MANIFEST_DIR"), "bootstrapable_graph");
+        let args = BlockDeserializerArgs {
+            thread_count: 2,
+            max_operations_per_block: 16,
+            endorsement_count: 16,
+            max_denunciations_per_block_header: 0,
+            last_start_period: Some(0),
+            chain_id: *CHAINID,
+        };
+        let (rest, graph_der) = BootstrapableGraphDeserializer::new(args, 1)
+            .deserialize::<DeserializeError>(&golden)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(graph_der.final_blocks.len(), 1);
+        let block_der = &graph_der.final_blocks[0];
+        let block = &graph.final_blocks[0];
+        assert_eq!(block_der.block.id, block.block.id);
+        assert_eq!(block_der.block.content.header.content.slot, Slot::new(0, 0));
+        assert_eq!(
+            block_der.block.content_creator_address,
+            block.block.content_creator_address
+        );
+        block_der.block.content.header.verify_signature().unwrap();
+        assert!(block_der.parents.is_empty());
+        assert!(block_der.is_final);
+    }
+}
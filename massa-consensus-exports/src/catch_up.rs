@@ -0,0 +1,59 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Progress of the catch-up of the final slots with the wall clock, refreshed by the worker at each slot tick.
+///
+/// The worker enters the catch-up mode when the oldest latest final block lags more than `catch_up_lag_periods`
+/// periods behind the current slot, typically after a network partition or a long downtime, and leaves it once
+/// the lag is back under half of this threshold.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatchUpStatus {
+    /// whether the worker is in catch-up mode
+    pub active: bool,
+    /// number of periods the oldest latest final block lagged behind the current slot at the latest slot tick
+    pub lag_periods: u64,
+    /// lag when the catch-up mode was entered, 0 when not catching up
+    pub initial_lag_periods: u64,
+    /// time the catch-up mode was entered, `None` when not catching up
+    pub started_at: Option<MassaTime>,
+    /// number of block and header registrations processed in batches since the catch-up mode was entered
+    pub batched_registrations: u64,
+}
+
+impl CatchUpStatus {
+    /// Share of the initial lag caught up since the catch-up mode was entered, in percent.
+    /// 100 when not catching up.
+    pub fn progress_percent(&self) -> u8 {
+        if !self.active || self.initial_lag_periods == 0 {
+            return 100;
+        }
+        let caught_up = self.initial_lag_periods.saturating_sub(self.lag_periods);
+        (caught_up.saturating_mul(100) / self.initial_lag_periods).min(100) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch_up_progress() {
+        assert_eq!(CatchUpStatus::default().progress_percent(), 100);
+
+        let mut status = CatchUpStatus {
+            active: true,
+            lag_periods: 200,
+            initial_lag_periods: 200,
+            started_at: Some(MassaTime::from_millis(1000)),
+            batched_registrations: 0,
+        };
+        assert_eq!(status.progress_percent(), 0);
+        status.lag_periods = 50;
+        assert_eq!(status.progress_percent(), 75);
+        // the lag grew since the mode was entered
+        status.lag_periods = 300;
+        assert_eq!(status.progress_percent(), 0);
+    }
+}
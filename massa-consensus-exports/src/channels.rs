@@ -1,12 +1,16 @@
+use massa_channel::event_bus::MassaEventBus;
 use massa_channel::sender::MassaSender;
+use massa_db_exports::ShareableMassaDBController;
 use massa_execution_exports::ExecutionController;
 use massa_models::block::{FilledBlock, SecureShareBlock};
 use massa_models::block_header::BlockHeader;
 use massa_models::block_id::BlockId;
+use massa_models::node_event::NodeEvent;
 use massa_models::secure_share::SecureShare;
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::ProtocolController;
+use massa_versioning::versioning::MipStore;
 
 use crate::events::ConsensusEvent;
 
@@ -21,10 +25,16 @@ pub struct ConsensusChannels {
     pub pool_controller: Box<dyn PoolController>,
     /// Interface to interact with Protocol module
     pub protocol_controller: Box<dyn ProtocolController>,
+    /// Versioning store, giving the network version active at the slot of the incoming headers
+    pub mip_store: MipStore,
+    /// Node database the consensus checkpoints are written to, `None` to disable checkpointing
+    pub db: Option<ShareableMassaDBController>,
     /// Channel used by the consensus to send events to the node globally
     pub controller_event_tx: MassaSender<ConsensusEvent>,
     /// Structure used by consensus to broadcast all the information about the blocks
     pub broadcasts: ConsensusBroadcasts,
+    /// Bus used by consensus to publish block finality and reorganization events
+    pub event_bus: MassaEventBus<NodeEvent>,
 }
 
 /// Structure used to broadcast all the information about the blocks
@@ -0,0 +1,62 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Compact summary of the consensus state, checkpointed into the node database.
+//!
+//! The worker periodically writes the best parents, the latest final blocks and the discarded blocks
+//! into the metadata column family, which is neither part of the state hash nor bootstrapped.
+//! After a restart, the checkpoint of the previous run answers read-only queries without waiting
+//! for the graph to be rebuilt.
+
+use massa_db_exports::{ShareableMassaDBController, CONSENSUS_CHECKPOINT_KEY, METADATA_CF};
+use massa_models::{block_id::BlockId, slot::Slot};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{block_status::DiscardReason, error::ConsensusError};
+
+/// Discarded block, as recorded in a checkpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointDiscardedBlock {
+    /// id of the block
+    pub block_id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// why the block was discarded
+    pub reason: DiscardReason,
+}
+
+/// Compact summary of the consensus state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsensusCheckpoint {
+    /// time at which the checkpoint was taken
+    pub timestamp: MassaTime,
+    /// latest slot processed by the worker, `None` before the first slot tick
+    pub slot: Option<Slot>,
+    /// best parent of each thread, with its period
+    pub best_parents: Vec<(BlockId, u64)>,
+    /// latest final block of each thread, with its period
+    pub latest_final_blocks_periods: Vec<(BlockId, u64)>,
+    /// blocks of the discarded index
+    pub discarded_blocks: Vec<CheckpointDiscardedBlock>,
+}
+
+impl ConsensusCheckpoint {
+    /// Write the checkpoint into the database, replacing the previous one
+    pub fn save(&self, db: &ShareableMassaDBController) -> Result<(), ConsensusError> {
+        let bytes = serde_json::to_vec(self)?;
+        db.read()
+            .put_metadata(CONSENSUS_CHECKPOINT_KEY.to_vec(), bytes)?;
+        Ok(())
+    }
+
+    /// Read the latest checkpoint from the database, `None` if no checkpoint was ever written
+    pub fn load(db: &ShareableMassaDBController) -> Result<Option<Self>, ConsensusError> {
+        match db
+            .read()
+            .get_cf(METADATA_CF, CONSENSUS_CHECKPOINT_KEY.to_vec())?
+        {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+use crate::ConsensusConfig;
+
+/// Consensus constants of the network the node runs on,
+/// for the clients that need them to interpret slots and finality
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsensusConfigSummary {
+    /// number of threads
+    pub thread_count: u8,
+    /// delta time between two periods
+    pub t0: MassaTime,
+    /// number of endorsements per block
+    pub endorsement_count: u32,
+    /// fitness threshold above which the blocks of the other cliques become final (`delta_f0`)
+    pub finality_threshold: u64,
+    /// genesis timestamp
+    pub genesis_timestamp: MassaTime,
+}
+
+impl From<&ConsensusConfig> for ConsensusConfigSummary {
+    fn from(config: &ConsensusConfig) -> Self {
+        Self {
+            thread_count: config.thread_count,
+            t0: config.t0,
+            endorsement_count: config.endorsement_count,
+            finality_threshold: config.delta_f0,
+            genesis_timestamp: config.genesis_timestamp,
+        }
+    }
+}
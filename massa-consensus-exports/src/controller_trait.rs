@@ -1,14 +1,43 @@
+use crate::ban_list::CreatorBan;
+use crate::block_filter::BlockFilter;
 use crate::block_graph_export::BlockGraphExport;
+use crate::block_provenance::{BlockOrigin, PeerPropagationStats};
+use crate::block_status::{BlockCountsByStatus, ExportCompiledBlock, HeaderKnowledge};
+use crate::block_summary::BlockSummary;
+use crate::catch_up::CatchUpStatus;
+use crate::checkpoint::ConsensusCheckpoint;
+use crate::config_summary::ConsensusConfigSummary;
+use crate::divergence::{BlockcliqueSummary, DivergentPeer};
+use crate::endorser_reliability::EndorserReliability;
+use crate::external_candidates::ExternalBlockCandidate;
+use crate::fitness_explanation::FitnessExplanation;
+use crate::graph_repair::GraphRepairReport;
+use crate::parent_candidates::ParentCandidates;
+use crate::query_quota::QueryCaller;
+use crate::reorg_report::ReorgReport;
+use crate::required_blocks::RequiredBlockExplanation;
+use crate::slot_occupancy::SlotOccupancy;
+use crate::stale_block::StaleBlock;
+use crate::submission::{ProcessingResultReceiver, SubmissionTicket};
+use crate::ConsensusConfig;
 use crate::{bootstrapable_graph::BootstrapableGraph, error::ConsensusError};
+use massa_models::pagination::{PageRequest, PageResponse};
 use massa_models::prehash::PreHashSet;
 use massa_models::streaming_step::StreamingStep;
 use massa_models::{
-    block::BlockGraphStatus, block_header::BlockHeader, block_id::BlockId, clique::Clique,
-    secure_share::SecureShare, slot::Slot, stats::ConsensusStats,
+    address::Address,
+    block::BlockGraphStatus,
+    block_header::BlockHeader,
+    block_id::BlockId,
+    clique::Clique,
+    operation::{OperationId, SecureShareOperation},
+    secure_share::SecureShare,
+    slot::Slot,
+    stats::ConsensusStats,
 };
+use massa_protocol_exports::PeerId;
 use massa_storage::Storage;
-
-#[cfg(feature = "test-exports")]
+use massa_time::MassaTime;
 use std::sync::Arc;
 
 /// Interface that communicates with the graph worker thread
@@ -28,6 +57,44 @@ pub trait ConsensusController: Send + Sync {
         end_slot: Option<Slot>,
     ) -> Result<BlockGraphExport, ConsensusError>;
 
+    /// Get an export of a part of the graph on behalf of an external caller,
+    /// counted against the per-minute budget of graph exports of the caller
+    ///
+    /// # Arguments
+    /// * `caller`: identity of the caller, given by the module serving the query
+    /// * `start_slot`: the slot to start the export from, if None, the export starts from the genesis
+    /// * `end_slot`: the slot to end the export at, if None, the export ends at the current slot
+    ///
+    /// # Returns
+    /// The export of the graph, or `QuotaExceeded` if the caller exhausted its budget
+    fn get_block_graph_status_as(
+        &self,
+        caller: &QueryCaller,
+        start_slot: Option<Slot>,
+        end_slot: Option<Slot>,
+    ) -> Result<BlockGraphExport, ConsensusError>;
+
+    /// Get a page of the active blocks of the graph, ordered by slot then block id
+    ///
+    /// # Arguments
+    /// * `request`: the page to get, the first one or the one following a cursor
+    ///
+    /// # Returns
+    /// The exported active blocks of the page, with the cursor of the next page
+    fn get_block_graph_page(
+        &self,
+        request: PageRequest,
+    ) -> PageResponse<(BlockId, ExportCompiledBlock)>;
+
+    /// Get a page of the stale blocks still remembered among the discarded blocks, ordered by slot then block id
+    ///
+    /// # Arguments
+    /// * `request`: the page to get, the first one or the one following a cursor
+    ///
+    /// # Returns
+    /// The stale blocks of the page, with the cursor of the next page
+    fn get_stale_blocks(&self, request: PageRequest) -> PageResponse<StaleBlock>;
+
     /// Get statuses of a list of blocks
     ///
     /// # Arguments
@@ -37,11 +104,47 @@ pub trait ConsensusController: Send + Sync {
     /// The statuses of the blocks sorted by the order of the input list
     fn get_block_statuses(&self, ids: &[BlockId]) -> Vec<BlockGraphStatus>;
 
-    /// Get all the cliques of the graph
+    /// Get what consensus holds of a block, so that protocol only requests the bodies it misses
+    ///
+    /// # Arguments
+    /// * `block_id`: the id of the block
+    ///
+    /// # Returns
+    /// Whether the block is unknown, known by its header only, held fully, final or discarded
+    fn has_block(&self, block_id: BlockId) -> HeaderKnowledge;
+
+    /// Get the statuses of a list of blocks along with storages referencing them, in a single atomic read,
+    /// so that the blocks cannot be pruned between the status check and the storage fetch
+    ///
+    /// # Arguments
+    /// * `ids`: the list of block ids to get
+    ///
+    /// # Returns
+    /// For each block in the order of the input list, its status and a storage owning the block, its endorsements and operations,
+    /// or `None` if the block is unknown, discarded, only known by its header or if its operations were dropped.
+    /// In light mode or if the operations of the executed final blocks are dropped, an error is returned if the operations of one of the blocks were dropped.
+    fn get_blocks_with_storage(
+        &self,
+        ids: &[BlockId],
+    ) -> Result<Vec<Option<(BlockGraphStatus, Storage)>>, ConsensusError>;
+
+    /// Get the cliques of the graph, without the cliques dominated by the blockclique beyond
+    /// `exposed_clique_fitness_margin` and at most `max_exposed_cliques` of them.
+    /// The list is a snapshot published by the worker at each graph change: it is shared between
+    /// the callers instead of being rebuilt under the lock of the graph at each call.
     ///
     /// # Returns
-    /// The list of cliques
-    fn get_cliques(&self) -> Vec<Clique>;
+    /// The list of cliques, the blockclique included
+    fn get_cliques_snapshot(&self) -> Arc<Vec<Clique>>;
+
+    /// Get the cliques of the graph, see `get_cliques_snapshot`
+    ///
+    /// # Returns
+    /// A copy of the list of cliques, the blockclique included
+    #[deprecated(note = "clones the whole list at each call, use `get_cliques_snapshot` instead")]
+    fn get_cliques(&self) -> Vec<Clique> {
+        self.get_cliques_snapshot().as_ref().clone()
+    }
 
     /// Get a part of the graph to send to a node for it to setup its graph.
     /// Used for bootstrap.
@@ -68,19 +171,239 @@ pub trait ConsensusController: Send + Sync {
         ConsensusError,
     >;
 
+    /// Get a part of the graph to send to a bootstrapping node on behalf of an external caller,
+    /// counted against the per-minute budget of bootstrap parts of the caller
+    ///
+    /// # Arguments:
+    /// * `caller`: identity of the caller, given by the module serving the query
+    /// * `cursor`, `execution_cursor`: see `get_bootstrap_part`
+    ///
+    /// # Returns:
+    /// See `get_bootstrap_part`, or `QuotaExceeded` if the caller exhausted its budget
+    #[allow(clippy::type_complexity)]
+    fn get_bootstrap_part_as(
+        &self,
+        caller: &QueryCaller,
+        cursor: StreamingStep<PreHashSet<BlockId>>,
+        execution_cursor: StreamingStep<Slot>,
+    ) -> Result<
+        (
+            BootstrapableGraph,
+            PreHashSet<BlockId>,
+            StreamingStep<PreHashSet<BlockId>>,
+        ),
+        ConsensusError,
+    >;
+
     /// Get the stats of the consensus
     ///
     /// # Returns
     /// The stats of the consensus
     fn get_stats(&self) -> Result<ConsensusStats, ConsensusError>;
 
-    /// Get the best parents for the next block to be produced
+    /// Get the non-final blocks that were in the blockclique at a past slot.
+    /// Only the latest `blockclique_history_length` blockclique changes are kept.
+    ///
+    /// # Arguments
+    /// * `slot`: the slot at which the blockclique is queried
+    ///
+    /// # Returns
+    /// The blockclique at that slot, or `None` if the slot is older than the kept history
+    fn get_blockclique_at(&self, slot: Slot) -> Option<PreHashSet<BlockId>>;
+
+    /// Get the best parents as they were after the tick of a past slot,
+    /// i.e. the parents a block created at that slot should have referenced.
+    /// Only the latest `best_parents_history_length` slots are kept.
+    ///
+    /// # Arguments
+    /// * `slot`: the slot at which the best parents are queried
+    ///
+    /// # Returns
+    /// One `(block id, period)` per thread, or `None` if the slot is older than the kept history
+    fn get_best_parents_at(&self, slot: Slot) -> Option<Vec<(BlockId, u64)>>;
+
+    /// Get the blocks of the graph that include an operation.
+    /// Final blocks are only listed until they are pruned from the graph.
+    ///
+    /// # Arguments
+    /// * `op_id`: the id of the operation
+    ///
+    /// # Returns
+    /// The ids of the blocks including the operation, along with their status
+    fn get_operation_inclusion(&self, op_id: OperationId) -> Vec<(BlockId, BlockGraphStatus)>;
+
+    /// Get the serialized header of a block, made of the content bytes it was registered with,
+    /// its signature and the public key of its creator, so that proofs can be built on the exact signed bytes
+    ///
+    /// # Arguments
+    /// * `block_id`: the id of the block
+    ///
+    /// # Returns
+    /// The header bytes, `None` if the block is unknown or was discarded
+    fn get_block_header_bytes(&self, block_id: BlockId) -> Result<Option<Vec<u8>>, ConsensusError>;
+
+    /// Get the impact of the latest reorganization of the blockclique on the inclusion of the operations
+    ///
+    /// # Returns
+    /// The blocks that left and joined the blockclique and the operations that went back to pending,
+    /// `None` if the blockclique was never reorganized since the node started
+    fn get_last_reorg_report(&self) -> Option<ReorgReport>;
+
+    /// Get the number of blocks in each status.
+    /// The counts are refreshed by the worker each time the graph changes, making this query cheap.
+    ///
+    /// # Returns
+    /// The number of blocks in each status, as of the last graph change
+    fn get_active_block_count_by_status(&self) -> BlockCountsByStatus;
+
+    /// Whether the consensus worker is alive: it ticked during the last `liveness_timeout`.
+    /// Meant for liveness probes, does not lock the consensus state.
+    ///
+    /// # Returns
+    /// true if the worker is alive
+    fn liveness(&self) -> bool;
+
+    /// Whether the node is ready to serve consensus data: the graph was initialized (after bootstrap),
+    /// the worker is alive, every thread had a final block during the last `readiness_max_final_lag`
+    /// and execution has not applied at most `readiness_max_execution_gap` final slots.
+    /// Meant for readiness probes, does not lock the consensus state.
+    ///
+    /// # Returns
+    /// true if the node is ready
+    fn is_ready(&self) -> bool;
+
+    /// Get the progress of the catch-up of the final slots with the wall clock, refreshed at each slot tick.
+    /// Does not lock the consensus state.
+    ///
+    /// # Returns
+    /// The catch-up status, inactive when the final slots are close to the current slot
+    fn get_catch_up_status(&self) -> CatchUpStatus;
+
+    /// Get the number of final slots of consensus that execution has not applied yet,
+    /// to detect an execution stall while consensus keeps finalizing blocks
+    ///
+    /// # Returns
+    /// The number of slots between the final execution cursor and the latest slot up to which all the slots are final
+    fn get_finality_gap(&self) -> u64;
+
+    /// Render the consensus metrics (block counts, command queue depth, finality lag, clique count)
+    /// in the OpenMetrics text format, so that they can be served on a `/metrics` HTTP endpoint.
+    ///
+    /// # Returns
+    /// The metrics in the OpenMetrics text format
+    fn render_metrics(&self) -> String;
+
+    /// Get the summary of the local blockclique, gossiped to the peers to detect divergences
+    ///
+    /// # Returns
+    /// The highest final slot and the blockclique tips
+    fn get_blockclique_summary(&self) -> BlockcliqueSummary;
+
+    /// Register the latest blockclique summary received from a peer
+    ///
+    /// # Arguments
+    /// * `peer_id`: the peer that sent the summary
+    /// * `summary`: the summary of its blockclique
+    fn register_peer_blockclique_summary(&self, peer_id: PeerId, summary: BlockcliqueSummary);
+
+    /// Get the peers whose blockclique diverges from the local one: some of their blockclique tips
+    /// are discarded locally or out of the local blockclique. Blocks unknown locally are not counted.
+    ///
+    /// # Returns
+    /// The divergent peers with their diverging tips, sorted by peer id
+    fn get_divergent_peers(&self) -> Vec<DivergentPeer>;
+
+    /// Ban a block creator: its headers are dropped until the ban expires.
+    /// Creators of blocks discarded as invalid are banned automatically.
+    ///
+    /// # Arguments
+    /// * `address`: the creator to ban
+    /// * `reason`: why the creator is banned
+    /// * `duration`: duration of the ban, `creator_ban_duration` if `None`
+    ///
+    /// # Returns
+    /// An error if the ban list could not be saved
+    fn ban(
+        &self,
+        address: Address,
+        reason: String,
+        duration: Option<MassaTime>,
+    ) -> Result<(), ConsensusError>;
+
+    /// Lift the bans of some block creators
+    ///
+    /// # Arguments
+    /// * `addresses`: the creators to unban
+    ///
+    /// # Returns
+    /// An error if the ban list could not be saved
+    fn unban(&self, addresses: Vec<Address>) -> Result<(), ConsensusError>;
+
+    /// Get the banned block creators
+    ///
+    /// # Returns
+    /// The active bans, sorted by expiry time
+    fn list_bans(&self) -> Vec<CreatorBan>;
+
+    /// Get the latest consensus checkpoint written to the node database.
+    /// It is read from the database without locking the graph, so that it is available right after a restart,
+    /// possibly from the previous run of the node.
+    ///
+    /// # Returns
+    /// The latest checkpoint, `None` if checkpointing is disabled or no checkpoint was written yet
+    fn get_checkpoint(&self) -> Result<Option<ConsensusCheckpoint>, ConsensusError>;
+
+    /// Get the best parents for the next block to be produced.
+    /// The blocks too far ahead of the speculative execution are not proposed (see the `MAX_PARENT_EXECUTION_LAG` network constant).
+    /// The list is a snapshot published by the worker at each graph change and slot tick, so it
+    /// follows the progress of the speculative execution with a delay of at most one slot.
     ///
     /// # Returns
     /// The id of best parents for the next block to be produced along with their period
-    fn get_best_parents(&self) -> Vec<(BlockId, u64)>;
+    fn get_best_parents_snapshot(&self) -> Arc<Vec<(BlockId, u64)>>;
 
-    /// Get the block id of the block at a specific slot in the blockclique
+    /// Get the best parents for the next block to be produced, see `get_best_parents_snapshot`
+    ///
+    /// # Returns
+    /// A copy of the id of best parents for the next block to be produced along with their period
+    #[deprecated(
+        note = "clones the whole list at each call, use `get_best_parents_snapshot` instead"
+    )]
+    fn get_best_parents(&self) -> Vec<(BlockId, u64)> {
+        self.get_best_parents_snapshot().as_ref().clone()
+    }
+
+    /// Get the blocks that can be referenced as parents by the next block to be produced
+    ///
+    /// # Returns
+    /// The best parents along with, for each thread, the candidate parents and their metadata
+    fn get_parent_candidates(&self) -> ParentCandidates;
+
+    /// Get the generation of the graph: a number incremented each time the cliques or the best parents change.
+    /// The reads made for a decision, like `get_parent_candidates`, return the generation they were made at.
+    fn get_graph_generation(&self) -> u64;
+
+    /// Check that the graph did not change since a read, before acting on a decision made from it
+    ///
+    /// # Arguments
+    /// * `generation`: the generation returned along with the read
+    ///
+    /// # Returns
+    /// `GraphChanged` if the graph changed since the read, in which case the decision may rely on outdated data
+    /// and should be made again
+    fn if_unchanged(&self, generation: u64) -> Result<(), ConsensusError> {
+        let current = self.get_graph_generation();
+        if current != generation {
+            return Err(ConsensusError::GraphChanged {
+                read: generation,
+                current,
+            });
+        }
+        Ok(())
+    }
+
+    /// Get the block id of the block at a specific slot in the blockclique.
+    /// The final blocks pruned from the graph are found if the final block index is enabled.
     ///
     /// # Arguments
     /// * `slot`: the slot to get the block id of
@@ -98,21 +421,226 @@ pub trait ConsensusController: Send + Sync {
     /// The block id of the latest block in the thread of the given slot and before this slot
     fn get_latest_blockclique_block_at_slot(&self, slot: Slot) -> BlockId;
 
+    /// Get the latest block, that is in the blockclique or final, whose slot timestamp is at or before a given timestamp.
+    /// Missed slots are skipped.
+    ///
+    /// # Arguments:
+    /// * `timestamp`: the upper bound of the slot timestamp of the block
+    ///
+    /// # Returns:
+    /// The block id of the latest such block if it is still in the graph, or an error if the timestamp can't be converted to a slot
+    fn get_latest_blockclique_block_before_time(
+        &self,
+        timestamp: MassaTime,
+    ) -> Result<Option<BlockId>, ConsensusError>;
+
+    /// Explain the fitness of an active block and its contribution to the fitness of the cliques
+    ///
+    /// # Arguments
+    /// * `block_id`: the id of the block to explain the fitness of
+    ///
+    /// # Returns
+    /// The components that contributed to the fitness of the block and of its cliques,
+    /// or an error if the block is not active in the graph
+    fn explain_fitness(&self, block_id: BlockId) -> Result<FitnessExplanation, ConsensusError>;
+
+    /// Get the active blocks that are retained in the graph, with the reason why each one is retained
+    ///
+    /// # Arguments
+    /// * `end_slot`: if set, the blocks required for a bootstrap export up to that slot (included)
+    ///
+    /// # Returns
+    /// The retained blocks sorted by slot, or an error if the graph is inconsistent
+    fn list_required_active_blocks_explained(
+        &self,
+        end_slot: Option<Slot>,
+    ) -> Result<Vec<RequiredBlockExplanation>, ConsensusError>;
+
+    /// Get the summaries of the active blocks in a slot range
+    ///
+    /// # Arguments
+    /// * `slot_range`: range of slots to get the block summaries of
+    ///
+    /// # Returns
+    /// The summaries (operation count, total fees, endorsement count, size) of the active blocks, sorted by slot
+    fn get_block_summaries(&self, slot_range: std::ops::RangeInclusive<Slot>) -> Vec<BlockSummary>;
+
+    /// Search the active blocks of the graph by header properties, ordered by slot then block id
+    ///
+    /// # Arguments
+    /// * `filter`: the properties the blocks must all have
+    /// * `request`: the page to get, the first one or the one following a cursor
+    ///
+    /// # Returns
+    /// The summaries of the matching blocks of the page, with the cursor of the next page
+    fn find_blocks(&self, filter: BlockFilter, request: PageRequest) -> PageResponse<BlockSummary>;
+
+    /// Get the filled and missed slots of a cycle per thread, with the addresses that were drawn for the missed ones.
+    /// Only the final slots still covered by the graph are inspected.
+    ///
+    /// # Arguments
+    /// * `cycle`: the cycle to get the occupancy of
+    ///
+    /// # Returns
+    /// The occupancy of each thread, or an error if the draws of a missed slot are unavailable
+    fn get_slot_occupancy(&self, cycle: u64) -> Result<SlotOccupancy, ConsensusError>;
+
+    /// Get the endorsement draws of an address that were not fulfilled in final blocks, per cycle,
+    /// to score the reliability of stakers as endorsers.
+    /// Only the latest `endorser_stats_cycles` cycles are tracked.
+    ///
+    /// # Arguments
+    /// * `address`: the endorser address
+    /// * `cycles`: the range of cycles to get the statistics of
+    ///
+    /// # Returns
+    /// The draws and misses of the address in each tracked cycle of the range in which it was drawn
+    fn get_endorser_reliability(
+        &self,
+        address: Address,
+        cycles: std::ops::RangeInclusive<u64>,
+    ) -> EndorserReliability;
+
+    /// Get the delay with which each peer relayed the blocks first received from it,
+    /// over the blocks still in the graph.
+    ///
+    /// # Returns
+    /// The propagation latency statistics of each peer, sorted by peer id
+    fn get_peer_propagation_stats(&self) -> Vec<PeerPropagationStats>;
+
+    /// Reload the non-structural parameters of the consensus configuration without restart
+    ///
+    /// # Arguments
+    /// * `config`: the new configuration
+    ///
+    /// # Returns
+    /// An error if a structural parameter (e.g. `thread_count`) was changed, in which case nothing is applied
+    fn update_config(&self, config: ConsensusConfig) -> Result<(), ConsensusError>;
+
+    /// Get the consensus constants in effect (thread count, t0, endorsement count, finality threshold, genesis timestamp),
+    /// so that clients can discover them instead of hardcoding them for each network
+    fn get_config_summary(&self) -> ConsensusConfigSummary;
+
+    /// Get the timestamp of a slot, computed with the thread count, t0 and genesis timestamp in effect
+    ///
+    /// # Arguments
+    /// * `slot`: the slot
+    ///
+    /// # Returns
+    /// The timestamp of the slot, or an error if it overflows
+    fn slot_to_timestamp(&self, slot: Slot) -> Result<MassaTime, ConsensusError>;
+
+    /// Get the latest slot at a timestamp (inclusive), computed with the thread count, t0 and genesis timestamp in effect
+    ///
+    /// # Arguments
+    /// * `timestamp`: the timestamp
+    ///
+    /// # Returns
+    /// The slot, `None` if the timestamp is before the genesis
+    fn timestamp_to_slot(&self, timestamp: MassaTime) -> Result<Option<Slot>, ConsensusError>;
+
+    /// Scan the graph for recoverable inconsistencies and fix them
+    ///
+    /// # Returns
+    /// A report of the inconsistencies that were fixed and of those that could not be fixed
+    fn repair_graph(&self) -> GraphRepairReport;
+
+    /// Submit block contents proposed by an external builder, to be signed by the local block factory
+    ///
+    /// The parents, the operations and the endorsements of the candidate are checked against the graph.
+    /// The draws and the denunciations are checked when the signed block is registered.
+    ///
+    /// # Arguments
+    /// * `header_template`: unsigned header of the block, its versions are replaced when it is signed
+    /// * `operations`: operations of the block, in block order
+    ///
+    /// # Returns
+    /// The id of the accepted candidate, or an error if it is invalid or if too many candidates are kept for its slot
+    fn submit_external_block_candidate(
+        &self,
+        header_template: BlockHeader,
+        operations: Vec<SecureShareOperation>,
+    ) -> Result<u64, ConsensusError>;
+
+    /// Get the external block candidates accepted for a slot
+    ///
+    /// # Arguments
+    /// * `slot`: the slot of the block being produced
+    ///
+    /// # Returns
+    /// The candidates in submission order
+    fn get_external_block_candidates(&self, slot: Slot) -> Vec<ExternalBlockCandidate>;
+
     /// Register a block in the graph
     ///
     /// # Arguments
     /// * `block_id`: the id of the block to register
     /// * `slot`: the slot of the block
     /// * `block_storage`: the storage that contains all the objects of the block
-    /// * `created`: is the block created by our node ?
-    fn register_block(&self, block_id: BlockId, slot: Slot, block_storage: Storage, created: bool);
+    /// * `origin`: where the block comes from, recorded if it is its first registration
+    ///
+    /// # Returns
+    /// A ticket following the block until it reaches a terminal graph status
+    fn register_block(
+        &self,
+        block_id: BlockId,
+        slot: Slot,
+        block_storage: Storage,
+        origin: BlockOrigin,
+    ) -> SubmissionTicket;
+
+    /// Register a block in the graph and get the result of the processing of the registration.
+    /// Meant for the callers that need to know how their block was handled, like the block factory or the tests.
+    ///
+    /// # Arguments
+    /// * `block_id`: the id of the block to register
+    /// * `slot`: the slot of the block
+    /// * `block_storage`: the storage that contains all the objects of the block
+    /// * `origin`: where the block comes from, recorded if it is its first registration
+    ///
+    /// # Returns
+    /// A ticket following the block until it reaches a terminal graph status,
+    /// and a receiver of the processing result, sent once the worker processed the registration
+    fn register_block_with_result(
+        &self,
+        block_id: BlockId,
+        slot: Slot,
+        block_storage: Storage,
+        origin: BlockOrigin,
+    ) -> (SubmissionTicket, ProcessingResultReceiver);
 
     /// Register a block header in the graph
     ///
     /// # Arguments
     /// * `block_id`: the id of the block to register
     /// * `header`: the header of the block to register
-    fn register_block_header(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>);
+    /// * `origin`: where the header comes from, recorded if it is the first registration of the block
+    ///
+    /// # Returns
+    /// A ticket following the block until it reaches a terminal graph status
+    fn register_block_header(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        origin: BlockOrigin,
+    ) -> SubmissionTicket;
+
+    /// Register a block header in the graph and get the result of the processing of the registration
+    ///
+    /// # Arguments
+    /// * `block_id`: the id of the block to register
+    /// * `header`: the header of the block to register
+    /// * `origin`: where the header comes from, recorded if it is the first registration of the block
+    ///
+    /// # Returns
+    /// A ticket following the block until it reaches a terminal graph status,
+    /// and a receiver of the processing result, sent once the worker processed the registration
+    fn register_block_header_with_result(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        origin: BlockOrigin,
+    ) -> (SubmissionTicket, ProcessingResultReceiver);
 
     /// Mark a block as invalid in the graph
     ///
@@ -0,0 +1,36 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::{block_id::BlockId, slot::Slot};
+use massa_protocol_exports::PeerId;
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Summary of the blockclique of a node, periodically gossiped to its peers
+/// to detect blockclique divergences
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockcliqueSummary {
+    /// highest slot among the latest final blocks of all threads
+    pub final_slot: Slot,
+    /// tips of the blockclique: the best parent of each thread
+    pub blockclique_tips: Vec<BlockId>,
+}
+
+/// Blockclique summary received from a peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerBlockcliqueSummary {
+    /// the summary
+    pub summary: BlockcliqueSummary,
+    /// time at which it was received
+    pub received_at: MassaTime,
+}
+
+/// A peer whose blockclique diverges from the local one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DivergentPeer {
+    /// id of the peer
+    pub peer_id: PeerId,
+    /// final slot announced by the peer
+    pub final_slot: Slot,
+    /// tips of the blockclique of the peer that are discarded locally or out of the local blockclique
+    pub diverging_tips: Vec<BlockId>,
+}
@@ -0,0 +1,45 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::address::Address;
+use serde::{Deserialize, Serialize};
+
+/// Endorsement draws of an address over a cycle, counted on the final blocks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndorserCycleStats {
+    /// cycle number
+    pub cycle: u64,
+    /// number of endorsement draws of the address in the slots of the final blocks of the cycle
+    pub drawn: u64,
+    /// number of those draws whose endorsement was not included in the final block of the slot
+    pub missed: u64,
+}
+
+/// Endorsement misses of an address over a range of cycles
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndorserReliability {
+    /// endorser address
+    pub address: Address,
+    /// statistics of each tracked cycle of the range in which the address was drawn, sorted by cycle
+    pub cycles: Vec<EndorserCycleStats>,
+}
+
+impl EndorserReliability {
+    /// Total number of endorsement draws over the cycles
+    pub fn drawn(&self) -> u64 {
+        self.cycles.iter().map(|cycle| cycle.drawn).sum()
+    }
+
+    /// Total number of missed endorsements over the cycles
+    pub fn missed(&self) -> u64 {
+        self.cycles.iter().map(|cycle| cycle.missed).sum()
+    }
+
+    /// Share of the draws whose endorsement was included, `None` if the address was never drawn
+    pub fn fulfillment_rate(&self) -> Option<f64> {
+        let drawn = self.drawn();
+        if drawn == 0 {
+            return None;
+        }
+        Some(drawn.saturating_sub(self.missed()) as f64 / drawn as f64)
+    }
+}
@@ -1,7 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
+use crate::query_quota::{HeavyQuery, QueryCaller};
 use displaydoc::Display;
+use massa_db_exports::MassaDBError;
 use massa_execution_exports::ExecutionError;
-use massa_models::error::ModelsError;
+use massa_models::{block_id::BlockId, error::ModelsError, slot::Slot};
 use massa_protocol_exports::ProtocolError;
 use massa_time::TimeError;
 use std::array::TryFromSliceError;
@@ -33,6 +35,8 @@ pub enum ConsensusError {
     IOError(#[from] std::io::Error),
     /// serde error
     SerdeError(#[from] serde_json::Error),
+    /// database error: {0}
+    MassaDBError(#[from] MassaDBError),
     /// Proof of stake cycle unavailable {0}
     PosCycleUnavailable(String),
     /// Ledger error {0}
@@ -45,6 +49,41 @@ pub enum ConsensusError {
     ProtocolError(#[from] ProtocolError),
     /// Invalid transition {0}
     InvalidTransition(String),
+    /// structural configuration parameters cannot be changed at runtime: {0}
+    StructuralConfigChange(String),
+    /// audit log error: {0}
+    AuditLogError(String),
+    /// not available in light mode, block bodies are not kept: {0}
+    NotAvailableInLightMode(String),
+    /// not available, the operations of the executed final blocks are not kept: {0}
+    ExecutedBlockBodiesDropped(String),
+    /// invalid external block candidate: {0}
+    InvalidExternalCandidate(String),
+    /// block {block_id} was registered for slot {registered_slot} but its header is for slot {header_slot}
+    BlockSlotMismatch {
+        /// id of the registered block
+        block_id: BlockId,
+        /// slot provided at registration
+        registered_slot: Slot,
+        /// slot found in the block header
+        header_slot: Slot,
+    },
+    /// {caller} exceeded its quota of {budget} {query} queries per minute
+    QuotaExceeded {
+        /// identity of the caller
+        caller: QueryCaller,
+        /// the limited query
+        query: HeavyQuery,
+        /// number of queries of this kind allowed per minute and per caller
+        budget: u32,
+    },
+    /// the graph changed since it was read: generation {read} was read, the current one is {current}
+    GraphChanged {
+        /// generation of the graph at the time of the read
+        read: u64,
+        /// current generation of the graph
+        current: u64,
+    },
 }
 
 /// Internal error
@@ -188,6 +188,7 @@ impl Deserializer<ExportActiveBlock> for ExportActiveBlockDeserializer {
     ///             .unwrap(),
     ///         ],
     ///     denunciations: vec![],},
+    ///     extensions: Vec::new(),
     ///     BlockHeaderSerializer::new(),
     ///     &keypair,
     ///     *CHAINID
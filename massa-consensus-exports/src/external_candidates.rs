@@ -0,0 +1,24 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Block contents proposed by external builders (proposer/builder separation).
+//!
+//! A builder process proposes a header template and the operations of a block through
+//! `ConsensusController::submit_external_block_candidate`. Consensus checks them against the graph
+//! and keeps them until their slot: the block factory may then pick one with its candidate selection
+//! policy and sign it in place of the contents it builds itself.
+
+use massa_models::{block_header::BlockHeader, operation::OperationId};
+use massa_storage::Storage;
+
+/// Block contents proposed by an external builder and accepted by consensus
+#[derive(Debug, Clone)]
+pub struct ExternalBlockCandidate {
+    /// id of the candidate, in submission order
+    pub id: u64,
+    /// unsigned header of the block, its versions are replaced by the ones of the signing node
+    pub header_template: BlockHeader,
+    /// ids of the operations of the block, in block order
+    pub operations: Vec<OperationId>,
+    /// storage referencing the operations and the endorsements of the block
+    pub storage: Storage,
+}
@@ -0,0 +1,39 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::{block_id::BlockId, slot::Slot};
+use serde::{Deserialize, Serialize};
+
+/// Details of the fitness accounting of an active block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FitnessExplanation {
+    /// id of the block
+    pub block_id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// number of endorsements included in the block
+    pub endorsement_count: u64,
+    /// own fitness of the block: one for the block itself plus one per endorsement
+    pub own_fitness: u64,
+    /// true if the block is final
+    pub is_final: bool,
+    /// descendant fitness that must be exceeded within a clique for the block to become final
+    pub delta_f0: u64,
+    /// contribution of the block to each of the cliques it belongs to.
+    /// Empty for final blocks as they are not part of the cliques anymore.
+    pub cliques: Vec<CliqueFitnessExplanation>,
+}
+
+/// Details of the fitness accounting of a block within one clique
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CliqueFitnessExplanation {
+    /// index of the clique in the list of cliques
+    pub clique_index: usize,
+    /// true if the clique is the blockclique
+    pub is_blockclique: bool,
+    /// total fitness of the clique: sum of the own fitness of its blocks
+    pub clique_fitness: u64,
+    /// number of descendants of the block that belong to the clique
+    pub descendants_counted: usize,
+    /// sum of the own fitness of the descendants of the block that belong to the clique
+    pub descendants_fitness: u64,
+}
@@ -0,0 +1,49 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Commands generated by the fuzz targets of the consensus worker.
+//!
+//! They describe the blocks by small indexes rather than by ids, so that random inputs build
+//! connected graphs: the consensus fuzz harness turns them into signed headers and blocks
+//! whose parents are blocks it generated before.
+
+use arbitrary::Arbitrary;
+
+/// Synthetic block, turned into a signed header by the fuzz harness
+#[derive(Debug, Clone, Arbitrary)]
+pub struct FuzzBlock {
+    /// period of the block, as an offset from the current period of the harness
+    pub period_offset: u8,
+    /// thread of the block, modulo the thread count
+    pub thread: u8,
+    /// for each thread, index of the parent among the blocks previously generated in that thread,
+    /// counted from the latest one. The genesis block of the thread is used when out of range
+    pub parents: Vec<u8>,
+    /// register the block under another slot than the one of its header
+    pub wrong_slot: bool,
+    /// seed of the operation merkle root, to generate distinct blocks at the same slot
+    pub seed: u8,
+}
+
+/// Command applied to the consensus state by the fuzz harness
+#[derive(Debug, Clone, Arbitrary)]
+pub enum FuzzCommand {
+    /// register a full block
+    RegisterBlock(FuzzBlock),
+    /// register the header of a block
+    RegisterBlockHeader(FuzzBlock),
+    /// register the full block of a previously generated block, counted from the latest one
+    RegisterKnownBlock {
+        /// index of the block
+        index: u8,
+    },
+    /// mark a previously generated block as invalid, counted from the latest one
+    MarkInvalidBlock {
+        /// index of the block
+        index: u8,
+    },
+    /// advance the current slot and tick
+    SlotTick {
+        /// number of slots to advance
+        slots: u8,
+    },
+}
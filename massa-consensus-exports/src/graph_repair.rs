@@ -0,0 +1,27 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use serde::{Deserialize, Serialize};
+
+/// Summary of the inconsistencies found and fixed by a repair of the block graph
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphRepairReport {
+    /// number of block status index entries that were added or removed
+    pub status_index_entries_fixed: usize,
+    /// number of references to missing blocks removed from the children and descendants of active blocks
+    pub dangling_references_removed: usize,
+    /// number of incompatibility graph entries that were added or removed
+    pub incompatibility_entries_fixed: usize,
+    /// number of blocks that are not active anymore removed from the cliques
+    pub clique_entries_removed: usize,
+    /// number of blocks that are not active anymore removed from the index of active blocks without operations
+    pub without_ops_entries_removed: usize,
+    /// inconsistencies that were found but could not be repaired
+    pub unrecoverable: Vec<String>,
+}
+
+impl GraphRepairReport {
+    /// Returns true if no inconsistency was found
+    pub fn is_clean(&self) -> bool {
+        self == &GraphRepairReport::default()
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Health of the consensus worker, refreshed by the worker at each slot tick
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsensusHealth {
+    /// time of the latest slot tick of the worker, `None` until the first tick after the initialization of the graph
+    pub last_tick: Option<MassaTime>,
+    /// timestamp of the slot of the oldest latest final block among all threads
+    pub oldest_latest_final_timestamp: MassaTime,
+    /// the worker is not alive anymore if it did not tick during this time
+    pub liveness_timeout: MassaTime,
+    /// the node is not ready if a thread has no final block during this time
+    pub readiness_max_final_lag: MassaTime,
+    /// number of final slots of consensus not yet applied by execution, `None` if not monitored
+    pub execution_finality_gap: Option<u64>,
+    /// the node is not ready if execution has not applied more than this number of final slots
+    pub readiness_max_execution_gap: u64,
+}
+
+impl ConsensusHealth {
+    /// Whether the worker ticked recently
+    pub fn is_alive(&self, now: MassaTime) -> bool {
+        matches!(self.last_tick, Some(last_tick) if now.saturating_sub(last_tick) <= self.liveness_timeout)
+    }
+
+    /// Time elapsed since the slot of the oldest latest final block among all threads, `None` before the first tick
+    pub fn finality_lag(&self, now: MassaTime) -> Option<MassaTime> {
+        self.last_tick
+            .map(|_| now.saturating_sub(self.oldest_latest_final_timestamp))
+    }
+
+    /// Whether the worker is alive, the finality of all threads lags less than the maximum lag behind `now`
+    /// and execution is not stalled behind the final slots
+    pub fn is_ready(&self, now: MassaTime) -> bool {
+        self.is_alive(now)
+            && now.saturating_sub(self.oldest_latest_final_timestamp)
+                <= self.readiness_max_final_lag
+            && self
+                .execution_finality_gap
+                .map_or(true, |gap| gap <= self.readiness_max_execution_gap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consensus_health() {
+        let mut health = ConsensusHealth {
+            last_tick: None,
+            oldest_latest_final_timestamp: MassaTime::from_millis(1000),
+            liveness_timeout: MassaTime::from_millis(100),
+            readiness_max_final_lag: MassaTime::from_millis(500),
+            execution_finality_gap: Some(3),
+            readiness_max_execution_gap: 10,
+        };
+        // not ready until the first tick
+        assert!(!health.is_alive(MassaTime::from_millis(1000)));
+        assert!(!health.is_ready(MassaTime::from_millis(1000)));
+
+        health.last_tick = Some(MassaTime::from_millis(1400));
+        assert!(health.is_alive(MassaTime::from_millis(1500)));
+        assert!(health.is_ready(MassaTime::from_millis(1500)));
+
+        // execution is stalled behind the final slots
+        health.execution_finality_gap = Some(11);
+        assert!(health.is_alive(MassaTime::from_millis(1500)));
+        assert!(!health.is_ready(MassaTime::from_millis(1500)));
+        health.execution_finality_gap = None;
+        assert!(health.is_ready(MassaTime::from_millis(1500)));
+
+        // finality lags too much
+        health.last_tick = Some(MassaTime::from_millis(1600));
+        assert!(health.is_alive(MassaTime::from_millis(1600)));
+        assert!(!health.is_ready(MassaTime::from_millis(1600)));
+
+        // the worker stopped ticking
+        assert!(!health.is_alive(MassaTime::from_millis(1800)));
+    }
+}
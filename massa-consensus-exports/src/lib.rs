@@ -2,15 +2,40 @@
 //! Definition and exports of the graph types and errors.
 
 mod channels;
+// the generated mock wrapper forwards the deprecated getters too
+#[allow(deprecated)]
 mod controller_trait;
 mod settings;
 
+pub mod audit_log;
+pub mod ban_list;
+pub mod block_filter;
 pub mod block_graph_export;
+pub mod block_provenance;
 pub mod block_status;
+pub mod block_summary;
 pub mod bootstrapable_graph;
+pub mod catch_up;
+pub mod checkpoint;
+pub mod config_summary;
+pub mod divergence;
+pub mod endorser_reliability;
 pub mod error;
 pub mod events;
 pub mod export_active_block;
+pub mod external_candidates;
+pub mod fitness_explanation;
+pub mod graph_repair;
+pub mod health;
+pub mod metrics;
+pub mod parent_candidates;
+pub mod query_quota;
+pub mod reorg_report;
+pub mod required_blocks;
+pub mod slot_occupancy;
+pub mod stale_block;
+pub mod stall_diagnostics;
+pub mod submission;
 
 pub use channels::{ConsensusBroadcasts, ConsensusChannels};
 pub use controller_trait::{ConsensusController, ConsensusManager};
@@ -23,3 +48,7 @@ pub use controller_trait::{MockConsensusController, MockConsensusControllerWrapp
 #[cfg(feature = "test-exports")]
 /// Exports related to tests as Mocks and configurations
 pub mod test_exports;
+
+/// Command sequences fed to the consensus state by the fuzz targets
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
@@ -0,0 +1,114 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::fmt::Write;
+
+use massa_time::MassaTime;
+
+use crate::block_status::BlockCountsByStatus;
+
+/// Snapshot of the consensus metrics exposed in the OpenMetrics text format
+#[derive(Debug, Clone, Default)]
+pub struct ConsensusMetrics {
+    /// number of blocks in each status
+    pub block_counts: BlockCountsByStatus,
+    /// number of commands waiting to be processed by the worker
+    pub command_queue_depth: usize,
+    /// time elapsed since the slot of the oldest latest final block among all threads, `None` before the first tick
+    pub finality_lag: Option<MassaTime>,
+    /// number of final slots not yet applied by execution at the latest slot tick, `None` if not monitored
+    pub execution_finality_gap: Option<u64>,
+    /// number of max cliques
+    pub clique_count: usize,
+}
+
+impl ConsensusMetrics {
+    /// Render the metrics in the OpenMetrics text format, ready to be served on a `/metrics` endpoint
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let counts = &self.block_counts;
+
+        out.push_str("# TYPE massa_consensus_blocks gauge\n");
+        out.push_str(
+            "# HELP massa_consensus_blocks Number of blocks known by consensus by status.\n",
+        );
+        for (status, count) in [
+            ("active_final", counts.active_final),
+            ("active_non_final", counts.active_non_final),
+            ("discarded_invalid", counts.discarded_invalid),
+            ("discarded_stale", counts.discarded_stale),
+            ("discarded_final", counts.discarded_final),
+            ("incoming", counts.incoming),
+            ("waiting_for_slot", counts.waiting_for_slot),
+            ("waiting_for_dependencies", counts.waiting_for_dependencies),
+        ] {
+            let _ = writeln!(
+                out,
+                "massa_consensus_blocks{{status=\"{}\"}} {}",
+                status, count
+            );
+        }
+
+        out.push_str("# TYPE massa_consensus_command_queue_depth gauge\n");
+        out.push_str("# HELP massa_consensus_command_queue_depth Number of commands waiting to be processed by the consensus worker.\n");
+        let _ = writeln!(
+            out,
+            "massa_consensus_command_queue_depth {}",
+            self.command_queue_depth
+        );
+
+        if let Some(finality_lag) = self.finality_lag {
+            out.push_str("# TYPE massa_consensus_finality_lag_seconds gauge\n");
+            out.push_str("# UNIT massa_consensus_finality_lag_seconds seconds\n");
+            out.push_str("# HELP massa_consensus_finality_lag_seconds Time elapsed since the slot of the oldest latest final block among all threads.\n");
+            let _ = writeln!(
+                out,
+                "massa_consensus_finality_lag_seconds {}",
+                finality_lag.to_duration().as_secs_f64()
+            );
+        }
+
+        if let Some(gap) = self.execution_finality_gap {
+            out.push_str("# TYPE massa_consensus_execution_finality_gap_slots gauge\n");
+            out.push_str("# HELP massa_consensus_execution_finality_gap_slots Number of final slots not yet applied by execution.\n");
+            let _ = writeln!(out, "massa_consensus_execution_finality_gap_slots {}", gap);
+        }
+
+        out.push_str("# TYPE massa_consensus_cliques gauge\n");
+        out.push_str("# HELP massa_consensus_cliques Number of max cliques in the block graph.\n");
+        let _ = writeln!(out, "massa_consensus_cliques {}", self.clique_count);
+
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let metrics = ConsensusMetrics {
+            block_counts: BlockCountsByStatus {
+                active_final: 3,
+                ..Default::default()
+            },
+            command_queue_depth: 2,
+            finality_lag: Some(MassaTime::from_millis(1500)),
+            execution_finality_gap: Some(4),
+            clique_count: 1,
+        };
+        let rendered = metrics.render();
+        assert!(rendered.contains("massa_consensus_blocks{status=\"active_final\"} 3\n"));
+        assert!(rendered.contains("massa_consensus_command_queue_depth 2\n"));
+        assert!(rendered.contains("massa_consensus_finality_lag_seconds 1.5\n"));
+        assert!(rendered.contains("massa_consensus_execution_finality_gap_slots 4\n"));
+        assert!(rendered.contains("massa_consensus_cliques 1\n"));
+        assert!(rendered.ends_with("# EOF\n"));
+
+        // the lag is unknown before the first tick
+        let rendered = ConsensusMetrics::default().render();
+        assert!(!rendered.contains("finality_lag"));
+        assert!(!rendered.contains("finality_gap"));
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::{address::Address, block_id::BlockId, slot::Slot};
+use serde::{Deserialize, Serialize};
+
+/// A block that can be referenced as a parent by a new block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParentCandidate {
+    /// id of the block
+    pub block_id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// address of the creator of the block
+    pub creator_address: Address,
+    /// number of endorsements included in the block
+    pub endorsement_count: u64,
+    /// parents of the block: one (block id, period) per thread, empty for genesis blocks
+    pub parents: Vec<(BlockId, u64)>,
+    /// true if the block is final
+    pub is_final: bool,
+}
+
+/// Parents that can be referenced by the next block, as seen by consensus
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParentCandidates {
+    /// parents chosen by consensus: the latest blockclique block of each thread
+    pub best_parents: Vec<(BlockId, u64)>,
+    /// for each thread, the blockclique blocks of that thread and its latest final block,
+    /// from the latest to the oldest
+    pub candidates: Vec<Vec<ParentCandidate>>,
+    /// generation of the graph the candidates were read from, see `ConsensusController::get_graph_generation`
+    pub generation: u64,
+}
+
+impl ParentCandidates {
+    /// Finds the periods of a list of parents, given as one block id per thread
+    ///
+    /// # Returns
+    /// The parents with their periods, or `None` if one of them is not a candidate of its thread
+    pub fn with_periods(&self, parent_ids: &[BlockId]) -> Option<Vec<(BlockId, u64)>> {
+        if parent_ids.len() != self.candidates.len() {
+            return None;
+        }
+        self.candidates
+            .iter()
+            .zip(parent_ids.iter())
+            .map(|(thread_candidates, parent_id)| {
+                thread_candidates
+                    .iter()
+                    .find(|candidate| &candidate.block_id == parent_id)
+                    .map(|candidate| (candidate.block_id, candidate.slot.period))
+            })
+            .collect()
+    }
+
+    /// Checks that a choice of parents can be referenced by a block at `slot`.
+    ///
+    /// The choice is valid if it contains exactly one candidate per thread, all earlier than `slot`,
+    /// and if no chosen parent references a block more recent than the chosen parent of the same thread.
+    pub fn is_valid_choice(&self, slot: Slot, parents: &[(BlockId, u64)]) -> bool {
+        if parents.len() != self.candidates.len() {
+            return false;
+        }
+        let mut chosen = Vec::with_capacity(parents.len());
+        for (thread_candidates, (parent_id, parent_period)) in
+            self.candidates.iter().zip(parents.iter())
+        {
+            match thread_candidates
+                .iter()
+                .find(|candidate| &candidate.block_id == parent_id)
+            {
+                Some(candidate)
+                    if candidate.slot.period == *parent_period && candidate.slot < slot =>
+                {
+                    chosen.push(candidate)
+                }
+                _ => return false,
+            }
+        }
+        chosen.iter().all(|candidate| {
+            candidate
+                .parents
+                .iter()
+                .zip(parents.iter())
+                .all(|((_, gp_period), (_, parent_period))| gp_period <= parent_period)
+        })
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Per-caller budgets of the heavy read queries of the controller.
+//!
+//! Graph exports and bootstrap parts walk and clone large parts of the graph while holding its lock.
+//! The modules serving them to the outside (API, bootstrap server) pass the identity of their caller,
+//! and each caller can run at most a configured number of each of these queries per minute.
+
+use displaydoc::Display;
+use serde::{Deserialize, Serialize};
+
+/// Identity of the caller of a heavy query, as given by the module serving it: an API key, a remote address...
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct QueryCaller(String);
+
+impl QueryCaller {
+    /// Create a new `QueryCaller`
+    pub fn new(identity: impl Into<String>) -> Self {
+        QueryCaller(identity.into())
+    }
+}
+
+impl std::fmt::Display for QueryCaller {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Heavy read queries limited per caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Display)]
+pub enum HeavyQuery {
+    /// graph export
+    GraphExport,
+    /// bootstrap part
+    BootstrapPart,
+}
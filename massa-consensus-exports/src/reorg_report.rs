@@ -0,0 +1,24 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::{block_id::BlockId, operation::OperationId, slot::Slot};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Impact of the latest reorganization of the blockclique on the inclusion of the operations.
+///
+/// Services crediting their users once an operation is included in the blockclique can reconcile
+/// with the operations that went back to pending, instead of re-scanning the blocks themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReorgReport {
+    /// time at which the reorganization was processed
+    pub time: MassaTime,
+    /// latest slot of the blocks that left the blockclique
+    pub slot: Slot,
+    /// blocks that left the blockclique without becoming final, sorted
+    pub removed_blocks: Vec<BlockId>,
+    /// blocks that joined the blockclique, sorted
+    pub added_blocks: Vec<BlockId>,
+    /// operations that were included in a removed block and are included in no block of the new blockclique
+    /// nor in a final block: they went back from included to pending, sorted
+    pub reverted_operations: Vec<OperationId>,
+}
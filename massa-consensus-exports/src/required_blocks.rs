@@ -0,0 +1,31 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::{block_id::BlockId, slot::Slot};
+use serde::{Deserialize, Serialize};
+
+/// Why an active block is retained in the graph and in bootstrap exports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RequiredBlockReason {
+    /// latest final block of its thread
+    LatestFinal,
+    /// block at or after the latest final block of its thread, still within the finality window
+    AfterLatestFinal,
+    /// parent of another retained block
+    ParentOfRequired {
+        /// the retained child
+        child: BlockId,
+    },
+    /// block at or after the earliest retained block of its thread, kept so that threads have no holes
+    FillsHole,
+}
+
+/// A retained active block and the first reason it was retained for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequiredBlockExplanation {
+    /// id of the block
+    pub block_id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// why the block is retained
+    pub reason: RequiredBlockReason,
+}
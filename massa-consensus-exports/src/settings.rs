@@ -1,6 +1,11 @@
+use massa_models::amount::Amount;
+use massa_models::block_header_extension::BlockHeaderExtensionSpec;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::error::ConsensusError;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ConsensusConfig {
@@ -18,12 +23,22 @@ pub struct ConsensusConfig {
     pub max_future_processing_blocks: usize,
     /// Maximum number of blocks allowed in `DependencyWaitingBlocks`.
     pub max_dependency_blocks: usize,
+    /// Maximum number of headers of network versions not activated yet held in quarantine.
+    pub max_quarantined_headers: usize,
+    /// headers whose block never arrived are dropped when their slot is this number of periods old, 0 to keep them
+    pub header_only_expiry_periods: u64,
     /// old blocks are pruned every `block_db_prune_interval`
     pub block_db_prune_interval: MassaTime,
+    /// a summary of the consensus state is written to the node database every `checkpoint_interval`, 0 to disable
+    pub checkpoint_interval: MassaTime,
     /// Max gas per block for the execution configuration
     pub max_gas_per_block: u64,
     /// Threshold for fitness.
     pub delta_f0: u64,
+    /// cliques whose fitness is more than this margin below the blockclique's are not returned by `get_cliques`
+    pub exposed_clique_fitness_margin: u64,
+    /// maximum number of cliques returned by `get_cliques`, blockclique included, 0 for no limit
+    pub max_exposed_cliques: usize,
     /// Maximum operation validity period count
     pub operation_validity_periods: u64,
     /// cycle duration in periods
@@ -34,10 +49,75 @@ pub struct ConsensusConfig {
     pub force_keep_final_periods_without_ops: u64,
     /// target number of endorsement per block
     pub endorsement_count: u32,
+    /// base reward of a block, split between its creator and endorsers
+    pub block_reward: Amount,
+    /// header extensions known by the node, validated when present in a header (unknown ones are ignored)
+    pub header_extensions: Vec<BlockHeaderExtensionSpec>,
     /// TESTNET: time when the blockclique is ended.
     pub end_timestamp: Option<MassaTime>,
     /// stats time span
     pub stats_timespan: MassaTime,
+    /// number of blockclique changes kept in the blockclique history
+    pub blockclique_history_length: usize,
+    /// number of slots for which the best parents seen at the slot tick are kept
+    pub best_parents_history_length: usize,
+    /// number of cycles for which the endorsement misses of each address are kept
+    pub endorser_stats_cycles: u64,
+    /// the worker is considered dead if it did not tick during this time
+    pub liveness_timeout: MassaTime,
+    /// the node is not ready if a thread has no final block during this time
+    pub readiness_max_final_lag: MassaTime,
+    /// the node is not ready if execution lags more than this number of slots behind the final slots of consensus.
+    /// 0 to disable the monitoring of the execution finality gap
+    pub readiness_max_execution_gap: u64,
+    /// the worker enters the catch-up mode when a thread has no final block for more than this number of periods,
+    /// and leaves it once the lag is back under half of it. 0 to disable the catch-up mode
+    pub catch_up_lag_periods: u64,
+    /// maximum number of pending registrations processed together, with a single graph update, in catch-up mode
+    pub catch_up_batch_size: usize,
+    /// blockclique summaries received from peers are ignored after this time
+    pub peer_blockclique_summary_lifetime: MassaTime,
+    /// a diagnostics bundle is collected when no block becomes final during this time, 0 to disable
+    pub finality_stall_timeout: MassaTime,
+    /// directory the diagnostics bundles of the finality stalls are written to, `None` to only publish the stall event
+    pub finality_stall_diagnostics_dir: Option<PathBuf>,
+    /// file the banned block creators are saved to, `None` to keep them in memory only
+    pub creator_ban_list_path: Option<PathBuf>,
+    /// time during which the creator of an invalid block is banned
+    pub creator_ban_duration: MassaTime,
+    /// maximum number of periods between a block and its parents (genesis parents excepted), 0 for no limit.
+    /// Network constant (`MAX_PARENT_AGE`): not reloadable
+    pub max_parent_age: u64,
+    /// blocks more than this number of periods ahead of the speculative execution are not proposed as parents
+    /// to the block factory, 0 for no limit. Network constant (`MAX_PARENT_EXECUTION_LAG`): not reloadable
+    pub max_parent_execution_lag: u64,
+    /// number of periods of draws fetched from the selector at each cycle rollover and cached for the block checks, 0 to disable
+    pub draw_prefetch_periods: u64,
+    /// one insertion shard per thread checks the draws and header extensions of the registered blocks
+    /// in parallel before queuing them for the consensus worker
+    pub insertion_shards: bool,
+    /// light mode for follower and API nodes: the operations of final blocks are dropped
+    /// as soon as they are handed to execution, and the node does not produce blocks
+    pub light_mode: bool,
+    /// the operations of final blocks are dropped once execution applied them to the final state,
+    /// only the headers and operation ids being kept. Bootstrap is not served in this mode
+    pub drop_executed_block_bodies: bool,
+    /// registrations of a block already registered during this time are dropped
+    pub block_dedup_window: MassaTime,
+    /// maximum number of recently registered blocks remembered to drop duplicate registrations, 0 to disable
+    pub block_dedup_capacity: usize,
+    /// number of graph exports each caller can request per minute, 0 for no limit
+    pub graph_export_quota_per_minute: u32,
+    /// number of consensus bootstrap parts each caller can request per minute, 0 for no limit
+    pub bootstrap_part_quota_per_minute: u32,
+    /// file the hash-chained log of the consensus decisions is appended to, `None` to disable it
+    pub audit_log_path: Option<PathBuf>,
+    /// node key signing the audit log checkpoints, `None` to write no checkpoint
+    pub audit_log_keypair: Option<KeyPair>,
+    /// number of audit log entries between two signed checkpoints, 0 to write no checkpoint
+    pub audit_log_checkpoint_interval: u64,
+    /// directory of the on-disk index of the final block ids by slot, `None` to disable it
+    pub final_block_index_path: Option<PathBuf>,
     /// channel size
     pub channel_size: usize,
     /// size of a consensus bootstrap streaming part
@@ -55,3 +135,182 @@ pub struct ConsensusConfig {
     /// chain id
     pub chain_id: u64,
 }
+
+impl ConsensusConfig {
+    /// Applies the runtime-reloadable parameters of `new_config` to this configuration.
+    ///
+    /// Reloadable parameters are the queue sizes, the header-only expiry, the future-slot tolerance, the bootstrap part size,
+    /// the number of kept final periods, the checkpoint interval, the stats time span, the blockclique history length,
+    /// the number of cycles of endorser statistics, the health thresholds, the catch-up mode thresholds, the lifetime of the peer blockclique summaries,
+    /// the finality stall timeout, the creator ban duration,
+    /// the maximum parent age, the maximum parent execution lag and the limits of the cliques returned by `get_cliques`.
+    /// All the other parameters are structural: if any of them differs, nothing is applied
+    /// and an error listing the changed structural parameters is returned.
+    pub fn apply_reloadable(&mut self, new_config: &ConsensusConfig) -> Result<(), ConsensusError> {
+        let structural_changes: Vec<&str> = [
+            (
+                "genesis_timestamp",
+                self.genesis_timestamp != new_config.genesis_timestamp,
+            ),
+            ("t0", self.t0 != new_config.t0),
+            ("thread_count", self.thread_count != new_config.thread_count),
+            (
+                "genesis_key",
+                self.genesis_key.get_public_key() != new_config.genesis_key.get_public_key(),
+            ),
+            (
+                "block_db_prune_interval",
+                self.block_db_prune_interval != new_config.block_db_prune_interval,
+            ),
+            (
+                "max_gas_per_block",
+                self.max_gas_per_block != new_config.max_gas_per_block,
+            ),
+            ("delta_f0", self.delta_f0 != new_config.delta_f0),
+            (
+                "operation_validity_periods",
+                self.operation_validity_periods != new_config.operation_validity_periods,
+            ),
+            (
+                "periods_per_cycle",
+                self.periods_per_cycle != new_config.periods_per_cycle,
+            ),
+            (
+                "endorsement_count",
+                self.endorsement_count != new_config.endorsement_count,
+            ),
+            ("block_reward", self.block_reward != new_config.block_reward),
+            (
+                "header_extensions",
+                self.header_extensions != new_config.header_extensions,
+            ),
+            (
+                "creator_ban_list_path",
+                self.creator_ban_list_path != new_config.creator_ban_list_path,
+            ),
+            (
+                "draw_prefetch_periods",
+                self.draw_prefetch_periods != new_config.draw_prefetch_periods,
+            ),
+            (
+                "insertion_shards",
+                self.insertion_shards != new_config.insertion_shards,
+            ),
+            ("light_mode", self.light_mode != new_config.light_mode),
+            (
+                "drop_executed_block_bodies",
+                self.drop_executed_block_bodies != new_config.drop_executed_block_bodies,
+            ),
+            (
+                "block_dedup_window",
+                self.block_dedup_window != new_config.block_dedup_window,
+            ),
+            (
+                "block_dedup_capacity",
+                self.block_dedup_capacity != new_config.block_dedup_capacity,
+            ),
+            (
+                "graph_export_quota_per_minute",
+                self.graph_export_quota_per_minute != new_config.graph_export_quota_per_minute,
+            ),
+            (
+                "bootstrap_part_quota_per_minute",
+                self.bootstrap_part_quota_per_minute != new_config.bootstrap_part_quota_per_minute,
+            ),
+            (
+                "audit_log_path",
+                self.audit_log_path != new_config.audit_log_path,
+            ),
+            (
+                "audit_log_keypair",
+                self.audit_log_keypair.as_ref().map(KeyPair::get_public_key)
+                    != new_config
+                        .audit_log_keypair
+                        .as_ref()
+                        .map(KeyPair::get_public_key),
+            ),
+            (
+                "audit_log_checkpoint_interval",
+                self.audit_log_checkpoint_interval != new_config.audit_log_checkpoint_interval,
+            ),
+            (
+                "final_block_index_path",
+                self.final_block_index_path != new_config.final_block_index_path,
+            ),
+            (
+                "finality_stall_diagnostics_dir",
+                self.finality_stall_diagnostics_dir != new_config.finality_stall_diagnostics_dir,
+            ),
+            (
+                "end_timestamp",
+                self.end_timestamp != new_config.end_timestamp,
+            ),
+            ("channel_size", self.channel_size != new_config.channel_size),
+            (
+                "max_parent_age",
+                self.max_parent_age != new_config.max_parent_age,
+            ),
+            (
+                "max_parent_execution_lag",
+                self.max_parent_execution_lag != new_config.max_parent_execution_lag,
+            ),
+            (
+                "broadcast_enabled",
+                self.broadcast_enabled != new_config.broadcast_enabled,
+            ),
+            (
+                "broadcast_blocks_headers_channel_capacity",
+                self.broadcast_blocks_headers_channel_capacity
+                    != new_config.broadcast_blocks_headers_channel_capacity,
+            ),
+            (
+                "broadcast_blocks_channel_capacity",
+                self.broadcast_blocks_channel_capacity
+                    != new_config.broadcast_blocks_channel_capacity,
+            ),
+            (
+                "broadcast_filled_blocks_channel_capacity",
+                self.broadcast_filled_blocks_channel_capacity
+                    != new_config.broadcast_filled_blocks_channel_capacity,
+            ),
+            (
+                "last_start_period",
+                self.last_start_period != new_config.last_start_period,
+            ),
+            ("chain_id", self.chain_id != new_config.chain_id),
+        ]
+        .into_iter()
+        .filter_map(|(name, changed)| changed.then_some(name))
+        .collect();
+        if !structural_changes.is_empty() {
+            return Err(ConsensusError::StructuralConfigChange(
+                structural_changes.join(", "),
+            ));
+        }
+
+        self.max_discarded_blocks = new_config.max_discarded_blocks;
+        self.max_future_processing_blocks = new_config.max_future_processing_blocks;
+        self.max_dependency_blocks = new_config.max_dependency_blocks;
+        self.max_quarantined_headers = new_config.max_quarantined_headers;
+        self.header_only_expiry_periods = new_config.header_only_expiry_periods;
+        self.force_keep_final_periods = new_config.force_keep_final_periods;
+        self.force_keep_final_periods_without_ops = new_config.force_keep_final_periods_without_ops;
+        self.checkpoint_interval = new_config.checkpoint_interval;
+        self.stats_timespan = new_config.stats_timespan;
+        self.blockclique_history_length = new_config.blockclique_history_length;
+        self.best_parents_history_length = new_config.best_parents_history_length;
+        self.endorser_stats_cycles = new_config.endorser_stats_cycles;
+        self.liveness_timeout = new_config.liveness_timeout;
+        self.readiness_max_final_lag = new_config.readiness_max_final_lag;
+        self.readiness_max_execution_gap = new_config.readiness_max_execution_gap;
+        self.catch_up_lag_periods = new_config.catch_up_lag_periods;
+        self.catch_up_batch_size = new_config.catch_up_batch_size;
+        self.peer_blockclique_summary_lifetime = new_config.peer_blockclique_summary_lifetime;
+        self.finality_stall_timeout = new_config.finality_stall_timeout;
+        self.creator_ban_duration = new_config.creator_ban_duration;
+        self.exposed_clique_fitness_margin = new_config.exposed_clique_fitness_margin;
+        self.max_exposed_cliques = new_config.max_exposed_cliques;
+        self.bootstrap_part_size = new_config.bootstrap_part_size;
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::collections::BTreeMap;
+
+use massa_models::address::Address;
+use serde::{Deserialize, Serialize};
+
+/// Block production of a thread over the final slots of a cycle that are still covered by the graph
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadSlotOccupancy {
+    /// thread number
+    pub thread: u8,
+    /// first and last periods (included) of the thread that were inspected, `None` if none could be
+    pub covered_periods: Option<(u64, u64)>,
+    /// number of slots with a final block
+    pub filled_slots: u64,
+    /// number of final slots without a block
+    pub missed_slots: u64,
+    /// number of missed slots per address that was drawn to produce them
+    pub misses_by_address: BTreeMap<Address, u64>,
+}
+
+/// Filled and missed slots of a cycle, per thread
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotOccupancy {
+    /// cycle number
+    pub cycle: u64,
+    /// occupancy of each thread, indexed by thread number
+    pub threads: Vec<ThreadSlotOccupancy>,
+}
+
+impl SlotOccupancy {
+    /// Total number of filled slots over all threads
+    pub fn filled_slots(&self) -> u64 {
+        self.threads.iter().map(|thread| thread.filled_slots).sum()
+    }
+
+    /// Total number of missed slots over all threads
+    pub fn missed_slots(&self) -> u64 {
+        self.threads.iter().map(|thread| thread.missed_slots).sum()
+    }
+}
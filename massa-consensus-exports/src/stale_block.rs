@@ -0,0 +1,17 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::{address::Address, block_id::BlockId, slot::Slot};
+use serde::{Deserialize, Serialize};
+
+/// Block discarded because it became incompatible with a final block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaleBlock {
+    /// id of the block
+    pub block_id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// address of the creator of the block
+    pub creator: Address,
+    /// parents of the block
+    pub parents: Vec<BlockId>,
+}
@@ -0,0 +1,133 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Diagnostics bundle collected by the consensus worker when finality stalls.
+//!
+//! If no block becomes final during `finality_stall_timeout`, the worker collects the state that is usually
+//! needed to analyze the incident (statistics, cliques, queue depths, peer divergences) and writes it to a file,
+//! so that the analysis does not depend on operators capturing it by hand while the node is stalled.
+
+use std::path::{Path, PathBuf};
+
+use massa_models::{block_id::BlockId, clique::Clique, slot::Slot, stats::ConsensusStats};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block_status::BlockCountsByStatus, catch_up::CatchUpStatus, divergence::DivergentPeer,
+    error::ConsensusError, health::ConsensusHealth,
+};
+
+/// State of the consensus worker when finality stalled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinalityStallDiagnostics {
+    /// time at which the bundle was collected
+    pub timestamp: MassaTime,
+    /// time at which a block last became final, or at which the worker was launched if none did since
+    pub last_finalization: MassaTime,
+    /// latest slot processed by the worker
+    pub slot: Slot,
+    /// statistics of consensus over the stats time span
+    pub stats: ConsensusStats,
+    /// health of the worker at its latest tick
+    pub health: ConsensusHealth,
+    /// catch-up mode of the worker
+    pub catch_up: CatchUpStatus,
+    /// number of blocks in each status, the incoming and waiting ones being the processing queues of the graph
+    pub block_counts: BlockCountsByStatus,
+    /// number of commands waiting in the channel of the worker
+    pub command_queue_length: usize,
+    /// number of headers of network versions not activated yet held in quarantine
+    pub quarantined_header_count: usize,
+    /// number of blocks wanted from the network
+    pub wishlist_length: usize,
+    /// latest final block of each thread, with its period
+    pub latest_final_blocks_periods: Vec<(BlockId, u64)>,
+    /// best parent of each thread, with its period
+    pub best_parents: Vec<(BlockId, u64)>,
+    /// all the cliques of the graph
+    pub cliques: Vec<Clique>,
+    /// number of peers whose blockclique summary is still valid
+    pub peer_summary_count: usize,
+    /// peers whose blockclique diverges from the local one
+    pub divergent_peers: Vec<DivergentPeer>,
+}
+
+impl FinalityStallDiagnostics {
+    /// Time elapsed without finalization when the bundle was collected
+    pub fn stalled_for(&self) -> MassaTime {
+        self.timestamp.saturating_sub(self.last_finalization)
+    }
+
+    /// Write the bundle to a new JSON file of `dir`, named after the time it was collected
+    ///
+    /// # Returns
+    /// The path of the written file
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, ConsensusError> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "finality_stall_{}.json",
+            self.timestamp.as_millis()
+        ));
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// Read a bundle written by `save`
+    pub fn load(path: &Path) -> Result<Self, ConsensusError> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::prehash::PreHashSet;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_diagnostics() {
+        let tmp_dir = TempDir::new().unwrap();
+        let diagnostics = FinalityStallDiagnostics {
+            timestamp: MassaTime::from_millis(5000),
+            last_finalization: MassaTime::from_millis(2000),
+            slot: Slot::new(10, 3),
+            stats: ConsensusStats {
+                start_timespan: MassaTime::from_millis(0),
+                end_timespan: MassaTime::from_millis(5000),
+                final_block_count: 0,
+                stale_block_count: 0,
+                clique_count: 1,
+                pruned_clique_count: 0,
+                duplicate_block_count: 0,
+                header_only_count: 0,
+                expired_header_count: 0,
+                execution_finality_gap: None,
+            },
+            health: ConsensusHealth::default(),
+            catch_up: CatchUpStatus::default(),
+            block_counts: BlockCountsByStatus::default(),
+            command_queue_length: 2,
+            quarantined_header_count: 0,
+            wishlist_length: 1,
+            latest_final_blocks_periods: Vec::new(),
+            best_parents: Vec::new(),
+            cliques: vec![Clique {
+                block_ids: PreHashSet::default(),
+                fitness: 0,
+                is_blockclique: true,
+            }],
+            peer_summary_count: 0,
+            divergent_peers: Vec::new(),
+        };
+        assert_eq!(diagnostics.stalled_for(), MassaTime::from_millis(3000));
+
+        // the bundle is written to a new directory
+        let dir = tmp_dir.path().join("diagnostics");
+        let path = diagnostics.save(&dir).unwrap();
+        assert_eq!(path, dir.join("finality_stall_5000.json"));
+        let saved = FinalityStallDiagnostics::load(&path).unwrap();
+        assert_eq!(saved.slot, diagnostics.slot);
+        assert_eq!(saved.command_queue_length, 2);
+        assert_eq!(saved.cliques, diagnostics.cliques);
+    }
+}
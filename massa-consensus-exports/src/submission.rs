@@ -0,0 +1,147 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Tickets returned when a block or a header is submitted to consensus.
+//!
+//! Submissions are queued for the consensus worker and processed asynchronously. The ticket tells
+//! the submitter how busy the worker queue was and lets it follow the block until it reaches a
+//! terminal graph status, so that retransmissions can be decided on actual outcomes.
+//! Callers that need to know how their registration itself was processed (e.g. the block factory or
+//! the tests) can also ask for a processing result, sent once the worker handled the command.
+
+use massa_models::block_id::BlockId;
+use tokio::sync::{oneshot, watch};
+
+use crate::block_status::DiscardReason;
+
+/// Terminal outcome of a submitted block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmissionOutcome {
+    /// the block became final
+    Final,
+    /// the block was discarded
+    Discarded(DiscardReason),
+    /// the block left the graph before becoming final or being discarded (e.g. pruned while waiting)
+    Dropped,
+    /// the submission duplicated a recent one whose outcome was already notified
+    Duplicate,
+    /// the submission could not be queued for the consensus worker
+    Rejected,
+}
+
+/// Result of the processing of a registration by the consensus worker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessingResult {
+    /// the block or header entered the graph, or completed a header that was waiting
+    Accepted,
+    /// the block was already known, the registration changed nothing
+    Duplicate,
+    /// the block was discarded while processing the registration
+    Discarded(DiscardReason),
+    /// the registration was ignored and the block is not in the graph (e.g. registered under another slot)
+    Dropped,
+    /// the registration could not be processed
+    Failed(String),
+}
+
+/// Worker side of the processing result of a registration
+pub type ProcessingResultSender = oneshot::Sender<ProcessingResult>;
+
+/// Caller side of the processing result of a registration,
+/// closed without a result if the worker stopped before processing the registration
+pub type ProcessingResultReceiver = oneshot::Receiver<ProcessingResult>;
+
+/// Create the channel of the processing result of a registration
+pub fn processing_result_channel() -> (ProcessingResultSender, ProcessingResultReceiver) {
+    oneshot::channel()
+}
+
+/// Handle on a block submitted to consensus
+#[derive(Debug, Clone)]
+pub struct SubmissionTicket {
+    /// id of the submitted block
+    pub block_id: BlockId,
+    /// number of commands that were ahead of the submission in the consensus queue, `None` if it was not queued
+    pub queue_position: Option<usize>,
+    /// outcome of the block, `None` until it is reached
+    watch: watch::Receiver<Option<SubmissionOutcome>>,
+}
+
+impl SubmissionTicket {
+    /// Ticket of a submission that already has an outcome
+    pub fn resolved(block_id: BlockId, outcome: SubmissionOutcome) -> Self {
+        let (_, watch) = watch::channel(Some(outcome));
+        SubmissionTicket {
+            block_id,
+            queue_position: None,
+            watch,
+        }
+    }
+
+    /// Outcome of the block if it was reached
+    pub fn outcome(&self) -> Option<SubmissionOutcome> {
+        self.watch.borrow().clone()
+    }
+
+    /// Wait for the outcome of the block
+    ///
+    /// # Returns
+    /// The outcome, or `None` if the block stopped being followed before reaching one (e.g. the worker stopped)
+    pub async fn wait(&mut self) -> Option<SubmissionOutcome> {
+        loop {
+            if let Some(outcome) = self.watch.borrow_and_update().clone() {
+                return Some(outcome);
+            }
+            if self.watch.changed().await.is_err() {
+                return self.watch.borrow().clone();
+            }
+        }
+    }
+}
+
+/// Consensus side of the tickets of a submitted block
+#[derive(Debug)]
+pub struct SubmissionNotifier {
+    sender: watch::Sender<Option<SubmissionOutcome>>,
+    armed: bool,
+}
+
+impl Default for SubmissionNotifier {
+    fn default() -> Self {
+        let (sender, _) = watch::channel(None);
+        SubmissionNotifier {
+            sender,
+            armed: false,
+        }
+    }
+}
+
+impl SubmissionNotifier {
+    /// Issue a new ticket following the block
+    pub fn ticket(&self, block_id: BlockId, queue_position: Option<usize>) -> SubmissionTicket {
+        SubmissionTicket {
+            block_id,
+            queue_position,
+            watch: self.sender.subscribe(),
+        }
+    }
+
+    /// Mark the submission as processed by the worker: from now on, a block absent from the graph has been dropped
+    pub fn arm(&mut self) {
+        self.armed = true;
+    }
+
+    /// Whether the submission was processed by the worker
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    /// Whether a ticket is still held for the block
+    pub fn is_observed(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    /// Notify the outcome to all the tickets of the block
+    pub fn resolve(self, outcome: SubmissionOutcome) {
+        self.sender.send_replace(Some(outcome));
+    }
+}
@@ -1,6 +1,6 @@
 use massa_models::config::{
     constants::{
-        CHANNEL_SIZE, DELTA_F0, ENDORSEMENT_COUNT, GENESIS_KEY, GENESIS_TIMESTAMP,
+        BLOCK_REWARD, CHANNEL_SIZE, DELTA_F0, ENDORSEMENT_COUNT, GENESIS_KEY, GENESIS_TIMESTAMP,
         MAX_GAS_PER_BLOCK, OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE, T0, THREAD_COUNT,
     },
     CHAINID, CONSENSUS_BOOTSTRAP_PART_SIZE,
@@ -19,16 +19,50 @@ impl Default for ConsensusConfig {
             max_discarded_blocks: 10000,
             max_future_processing_blocks: 100,
             max_dependency_blocks: 2048,
+            max_quarantined_headers: 256,
+            header_only_expiry_periods: 0,
             block_db_prune_interval: MassaTime::from_millis(5000),
+            checkpoint_interval: MassaTime::from_millis(0),
             max_gas_per_block: MAX_GAS_PER_BLOCK,
             delta_f0: DELTA_F0,
+            exposed_clique_fitness_margin: DELTA_F0,
+            max_exposed_cliques: 0,
             operation_validity_periods: OPERATION_VALIDITY_PERIODS,
             periods_per_cycle: PERIODS_PER_CYCLE,
             force_keep_final_periods: 20,
             force_keep_final_periods_without_ops: 128,
             endorsement_count: ENDORSEMENT_COUNT,
+            block_reward: BLOCK_REWARD,
+            header_extensions: Vec::new(),
             end_timestamp: None,
             stats_timespan: MassaTime::from_millis(60000),
+            blockclique_history_length: 1000,
+            best_parents_history_length: 1000,
+            endorser_stats_cycles: 10,
+            liveness_timeout: MassaTime::from_millis(10000),
+            readiness_max_final_lag: MassaTime::from_millis(60000),
+            readiness_max_execution_gap: 0,
+            catch_up_lag_periods: 0,
+            catch_up_batch_size: 64,
+            peer_blockclique_summary_lifetime: MassaTime::from_millis(30000),
+            finality_stall_timeout: MassaTime::from_millis(0),
+            finality_stall_diagnostics_dir: None,
+            creator_ban_list_path: None,
+            creator_ban_duration: MassaTime::from_millis(3600000),
+            max_parent_age: 0,
+            max_parent_execution_lag: 0,
+            draw_prefetch_periods: 0,
+            insertion_shards: false,
+            light_mode: false,
+            drop_executed_block_bodies: false,
+            block_dedup_window: MassaTime::from_millis(10000),
+            block_dedup_capacity: 1000,
+            graph_export_quota_per_minute: 0,
+            bootstrap_part_quota_per_minute: 0,
+            audit_log_path: None,
+            audit_log_keypair: None,
+            audit_log_checkpoint_interval: 1000,
+            final_block_index_path: None,
             channel_size: CHANNEL_SIZE,
             bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
             broadcast_enabled: true,
@@ -0,0 +1,21 @@
+//! Feed random sequences of block registrations, invalid block marks and slot ticks
+//! to the consensus state, checking the consistency of the graph after each of them.
+//!
+//! Run with `cargo fuzz run command_sequence` from `massa-consensus-worker`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use massa_consensus_exports::fuzzing::FuzzCommand;
+use massa_consensus_worker::fuzzing::FuzzHarness;
+
+/// Maximum number of commands applied per input, to keep the runs short
+const MAX_COMMANDS: usize = 256;
+
+fuzz_target!(|commands: Vec<FuzzCommand>| {
+    let mut harness = FuzzHarness::new();
+    for command in commands.iter().take(MAX_COMMANDS) {
+        harness.apply(command);
+        harness.check_invariants();
+    }
+});
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use massa_consensus_exports::query_quota::QueryCaller;
+use massa_time::MassaTime;
+
+/// A session is over if its client did not request a part during this time
+const SESSION_IDLE_TIMEOUT: MassaTime = MassaTime::from_millis(60_000);
+
+/// Clients streaming the graph for bootstrap, used by the controller to share the parts fairly between them.
+///
+/// Each client streams the graph with its own cursor, kept by the bootstrap server.
+/// When several clients stream at once, `bootstrap_part_size` is split equally between their sessions,
+/// so that each part holds the lock of the consensus state for a similar time
+/// and a client streaming a large graph does not delay the others.
+#[derive(Default)]
+pub(crate) struct BootstrapSessions {
+    /// time of the latest part requested by each ongoing session
+    last_parts: HashMap<QueryCaller, MassaTime>,
+}
+
+impl BootstrapSessions {
+    /// Note a part requested by `caller` at `now`, opening its session if needed
+    ///
+    /// # Returns
+    /// The number of ongoing sessions, the one of the caller included
+    pub(crate) fn begin_part(&mut self, caller: &QueryCaller, now: MassaTime) -> usize {
+        self.last_parts
+            .retain(|_, last_part| now.saturating_sub(*last_part) <= SESSION_IDLE_TIMEOUT);
+        self.last_parts.insert(caller.clone(), now);
+        self.last_parts.len()
+    }
+
+    /// Close the session of `caller` once its stream is finished
+    pub(crate) fn end_session(&mut self, caller: &QueryCaller) {
+        self.last_parts.remove(caller);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bootstrap_sessions() {
+        let mut sessions = BootstrapSessions::default();
+        let alice = QueryCaller::new("alice");
+        let bob = QueryCaller::new("bob");
+        let now = MassaTime::from_millis(100_000);
+
+        assert_eq!(sessions.begin_part(&alice, now), 1);
+        assert_eq!(sessions.begin_part(&bob, now), 2);
+        // a session is counted once whatever its number of parts
+        assert_eq!(sessions.begin_part(&alice, now), 2);
+
+        // finished sessions are closed
+        sessions.end_session(&bob);
+        assert_eq!(sessions.begin_part(&alice, now), 1);
+
+        // idle sessions are over
+        assert_eq!(sessions.begin_part(&bob, now), 2);
+        let later = now.saturating_add(MassaTime::from_millis(60_001));
+        assert_eq!(sessions.begin_part(&alice, later), 1);
+    }
+}
@@ -1,12 +1,48 @@
+use massa_consensus_exports::{
+    block_provenance::BlockProvenance, submission::ProcessingResultSender,
+};
 use massa_models::{
     block_header::BlockHeader, block_id::BlockId, secure_share::SecureShare, slot::Slot,
 };
 use massa_storage::Storage;
 
+/// Commands sent by the controller to the consensus worker.
+/// The registrations carry an optional sender of their processing result,
+/// `None` for the fire-and-forget registrations of the network.
 #[allow(clippy::large_enum_variant)]
-#[derive(Clone)]
 pub enum ConsensusCommand {
-    RegisterBlock(BlockId, Slot, Storage, bool),
-    RegisterBlockHeader(BlockId, SecureShare<BlockHeader, BlockId>),
+    RegisterBlock(
+        BlockId,
+        Slot,
+        Storage,
+        BlockProvenance,
+        Option<ProcessingResultSender>,
+    ),
+    RegisterBlockHeader(
+        BlockId,
+        SecureShare<BlockHeader, BlockId>,
+        BlockProvenance,
+        Option<ProcessingResultSender>,
+    ),
     MarkInvalidBlock(BlockId, SecureShare<BlockHeader, BlockId>),
 }
+
+impl ConsensusCommand {
+    /// Take the sender of the processing result of the command, if any
+    pub fn into_result_tx(self) -> Option<ProcessingResultSender> {
+        match self {
+            ConsensusCommand::RegisterBlock(_, _, _, _, result_tx)
+            | ConsensusCommand::RegisterBlockHeader(_, _, _, result_tx) => result_tx,
+            ConsensusCommand::MarkInvalidBlock(..) => None,
+        }
+    }
+
+    /// Thread of the block the command is about
+    pub fn thread(&self) -> u8 {
+        match self {
+            ConsensusCommand::RegisterBlock(_, slot, ..) => slot.thread,
+            ConsensusCommand::RegisterBlockHeader(_, header, ..)
+            | ConsensusCommand::MarkInvalidBlock(_, header) => header.content.slot.thread,
+        }
+    }
+}
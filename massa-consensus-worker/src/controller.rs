@@ -1,28 +1,68 @@
 use massa_channel::sender::MassaSender;
 use massa_consensus_exports::ConsensusBroadcasts;
 use massa_consensus_exports::{
-    block_graph_export::BlockGraphExport, block_status::BlockStatus,
-    bootstrapable_graph::BootstrapableGraph, error::ConsensusError,
-    export_active_block::ExportActiveBlock, ConsensusController,
+    ban_list::CreatorBan,
+    block_filter::BlockFilter,
+    block_graph_export::BlockGraphExport,
+    block_provenance::{BlockOrigin, BlockProvenance, PeerPropagationStats},
+    block_status::{BlockCountsByStatus, ExportCompiledBlock, HeaderKnowledge},
+    block_summary::BlockSummary,
+    bootstrapable_graph::BootstrapableGraph,
+    catch_up::CatchUpStatus,
+    checkpoint::ConsensusCheckpoint,
+    config_summary::ConsensusConfigSummary,
+    divergence::{BlockcliqueSummary, DivergentPeer, PeerBlockcliqueSummary},
+    endorser_reliability::EndorserReliability,
+    error::ConsensusError,
+    external_candidates::ExternalBlockCandidate,
+    fitness_explanation::FitnessExplanation,
+    graph_repair::GraphRepairReport,
+    health::ConsensusHealth,
+    metrics::ConsensusMetrics,
+    parent_candidates::ParentCandidates,
+    query_quota::{HeavyQuery, QueryCaller},
+    reorg_report::ReorgReport,
+    required_blocks::RequiredBlockExplanation,
+    slot_occupancy::SlotOccupancy,
+    stale_block::StaleBlock,
+    submission::{
+        processing_result_channel, ProcessingResult, ProcessingResultReceiver,
+        ProcessingResultSender, SubmissionNotifier, SubmissionOutcome, SubmissionTicket,
+    },
+    ConsensusConfig, ConsensusController,
 };
+use massa_db_exports::ShareableMassaDBController;
 use massa_models::{
+    address::Address,
     block::{BlockGraphStatus, FilledBlock},
     block_header::BlockHeader,
     block_id::BlockId,
     clique::Clique,
-    operation::{Operation, OperationId},
-    prehash::PreHashSet,
-    secure_share::SecureShare,
+    operation::{Operation, OperationId, SecureShareOperation},
+    pagination::{PageRequest, PageResponse},
+    prehash::{PreHashMap, PreHashSet},
+    secure_share::{SecureShare, SecureShareSerializer},
     slot::Slot,
     stats::ConsensusStats,
     streaming_step::StreamingStep,
+    timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp},
 };
+use massa_protocol_exports::PeerId;
+use massa_serialization::Serializer;
 use massa_storage::Storage;
-use parking_lot::RwLock;
-use std::sync::Arc;
+use massa_time::MassaTime;
+use parking_lot::{Mutex, RwLock};
+use std::{collections::HashMap, sync::Arc};
 use tracing::{debug, trace, warn};
 
-use crate::{commands::ConsensusCommand, state::ConsensusState};
+use crate::{
+    bootstrap_sessions::BootstrapSessions,
+    commands::ConsensusCommand,
+    dedup::IncomingBlockDedup,
+    external_candidates::{check_external_candidate, ExternalCandidatePool},
+    query_quota::QueryQuotas,
+    state::{ConsensusState, GraphSnapshots},
+};
 
 /// The retrieval of data is made using a shared state and modifications are asked by sending message to a channel.
 /// This is done mostly to be able to:
@@ -34,28 +74,201 @@ use crate::{commands::ConsensusCommand, state::ConsensusState};
 #[derive(Clone)]
 pub struct ConsensusControllerImpl {
     command_sender: MassaSender<ConsensusCommand>,
+    /// senders of the insertion shards indexed by thread, empty if the shards are disabled
+    shard_senders: Vec<MassaSender<ConsensusCommand>>,
     broadcasts: ConsensusBroadcasts,
     shared_state: Arc<RwLock<ConsensusState>>,
-    bootstrap_part_size: u64,
+    block_counts: Arc<RwLock<BlockCountsByStatus>>,
+    snapshots: Arc<RwLock<GraphSnapshots>>,
+    health: Arc<RwLock<ConsensusHealth>>,
+    catch_up: Arc<RwLock<CatchUpStatus>>,
+    peer_blockclique_summaries: Arc<RwLock<HashMap<PeerId, PeerBlockcliqueSummary>>>,
+    incoming_block_dedup: Arc<Mutex<IncomingBlockDedup>>,
+    query_quotas: Arc<Mutex<QueryQuotas>>,
+    submission_notifiers: Arc<Mutex<PreHashMap<BlockId, SubmissionNotifier>>>,
+    external_candidates: Arc<Mutex<ExternalCandidatePool>>,
+    bootstrap_sessions: Arc<Mutex<BootstrapSessions>>,
+    db: Option<ShareableMassaDBController>,
     broadcast_enabled: bool,
 }
 
 impl ConsensusControllerImpl {
     pub fn new(
         command_sender: MassaSender<ConsensusCommand>,
+        shard_senders: Vec<MassaSender<ConsensusCommand>>,
         broadcasts: ConsensusBroadcasts,
         shared_state: Arc<RwLock<ConsensusState>>,
-        bootstrap_part_size: u64,
+        block_counts: Arc<RwLock<BlockCountsByStatus>>,
+        snapshots: Arc<RwLock<GraphSnapshots>>,
+        health: Arc<RwLock<ConsensusHealth>>,
+        catch_up: Arc<RwLock<CatchUpStatus>>,
+        peer_blockclique_summaries: Arc<RwLock<HashMap<PeerId, PeerBlockcliqueSummary>>>,
+        incoming_block_dedup: Arc<Mutex<IncomingBlockDedup>>,
+        query_quotas: Arc<Mutex<QueryQuotas>>,
+        submission_notifiers: Arc<Mutex<PreHashMap<BlockId, SubmissionNotifier>>>,
+        db: Option<ShareableMassaDBController>,
         broadcast_enabled: bool,
     ) -> Self {
         Self {
             command_sender,
+            shard_senders,
             broadcasts,
             shared_state,
-            bootstrap_part_size,
+            block_counts,
+            snapshots,
+            health,
+            catch_up,
+            peer_blockclique_summaries,
+            incoming_block_dedup,
+            query_quotas,
+            submission_notifiers,
+            external_candidates: Default::default(),
+            bootstrap_sessions: Default::default(),
+            db,
             broadcast_enabled,
         }
     }
+
+    /// Sender of a command: the insertion shard of the thread of its block if the shards are enabled,
+    /// the worker otherwise
+    fn command_sender_of(&self, command: &ConsensusCommand) -> &MassaSender<ConsensusCommand> {
+        self.shard_senders
+            .get(command.thread() as usize)
+            .unwrap_or(&self.command_sender)
+    }
+
+    /// Queue the registration of a block for the worker and issue a ticket following the block
+    fn submit(&self, block_id: BlockId, command: ConsensusCommand) -> SubmissionTicket {
+        // keep the notifiers locked until the command is queued, so that the worker cannot process it unfollowed
+        let mut notifiers = self.submission_notifiers.lock();
+        let sender = self.command_sender_of(&command);
+        // the commands waiting in the insertion shard of the block come before it too
+        let queue_position = if self.shard_senders.is_empty() {
+            self.command_sender.len()
+        } else {
+            self.command_sender.len() + sender.len()
+        };
+        let notifier = notifiers.entry(block_id).or_default();
+        let ticket = notifier.ticket(block_id, Some(queue_position));
+        if let Err(err) = sender.try_send(command) {
+            warn!("error trying to register block {}: {}", block_id, err);
+            let reason = err.to_string();
+            if let Some(result_tx) = err.into_inner().into_result_tx() {
+                let _ = result_tx.send(ProcessingResult::Failed(reason));
+            }
+            if !notifier.is_armed() {
+                notifiers.remove(&block_id);
+            }
+            return SubmissionTicket::resolved(block_id, SubmissionOutcome::Rejected);
+        }
+        ticket
+    }
+
+    /// Register a block, sending its processing result to `result_tx` if any
+    fn register_block_inner(
+        &self,
+        block_id: BlockId,
+        slot: Slot,
+        block_storage: Storage,
+        origin: BlockOrigin,
+        result_tx: Option<ProcessingResultSender>,
+    ) -> SubmissionTicket {
+        let provenance = BlockProvenance::now(origin);
+        // drop the registrations of a block received from several peers
+        if !self
+            .incoming_block_dedup
+            .lock()
+            .insert(block_id, MassaTime::now())
+        {
+            trace!("dropping duplicate registration of block {}", block_id);
+            if let Some(result_tx) = result_tx {
+                let _ = result_tx.send(ProcessingResult::Duplicate);
+            }
+            // follow the registration that is still pending, if any
+            return match self.submission_notifiers.lock().get(&block_id) {
+                Some(notifier) => notifier.ticket(block_id, None),
+                None => SubmissionTicket::resolved(block_id, SubmissionOutcome::Duplicate),
+            };
+        }
+
+        if self.broadcast_enabled {
+            if let Some(verifiable_block) = block_storage.read_blocks().get(&block_id) {
+                let operations: Vec<(OperationId, Option<SecureShare<Operation, OperationId>>)> =
+                    verifiable_block
+                        .content
+                        .operations
+                        .iter()
+                        .map(|operation_id| {
+                            match block_storage.read_operations().get(operation_id).cloned() {
+                                Some(verifiable_operation) => {
+                                    (*operation_id, Some(verifiable_operation))
+                                }
+                                None => (*operation_id, None),
+                            }
+                        })
+                        .collect();
+
+                if let Err(err) = self.broadcasts.block_sender.send(verifiable_block.clone()) {
+                    trace!(
+                        "error, failed to broadcast block with id {} due to: {}",
+                        block_id,
+                        err
+                    );
+                }
+
+                if let Err(err) = self.broadcasts.filled_block_sender.send(FilledBlock {
+                    header: verifiable_block.content.header.clone(),
+                    operations,
+                }) {
+                    trace!(
+                        "error, failed to broadcast filled block with id {} due to: {}",
+                        block_id,
+                        err
+                    );
+                }
+            } else {
+                debug!(
+                    "error, no broadcast event sent, block with id {} not found",
+                    block_id
+                );
+            };
+        }
+
+        let ticket = self.submit(
+            block_id,
+            ConsensusCommand::RegisterBlock(block_id, slot, block_storage, provenance, result_tx),
+        );
+        if ticket.outcome() == Some(SubmissionOutcome::Rejected) {
+            // let a later registration of the block through
+            self.incoming_block_dedup.lock().remove(&block_id);
+        }
+        ticket
+    }
+
+    /// Register a block header, sending its processing result to `result_tx` if any
+    fn register_block_header_inner(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        origin: BlockOrigin,
+        result_tx: Option<ProcessingResultSender>,
+    ) -> SubmissionTicket {
+        let provenance = BlockProvenance::now(origin);
+        if self.broadcast_enabled {
+            if let Err(err) = self.broadcasts.block_header_sender.send(header.clone()) {
+                trace!(
+                    "error, failed to broadcast block header with block id {}: {}",
+                    block_id,
+                    err
+                );
+            }
+        }
+
+        self.submit(
+            block_id,
+            ConsensusCommand::RegisterBlockHeader(block_id, header, provenance, result_tx),
+        )
+    }
 }
 
 impl ConsensusController for ConsensusControllerImpl {
@@ -72,9 +285,55 @@ impl ConsensusController for ConsensusControllerImpl {
         start_slot: Option<Slot>,
         end_slot: Option<Slot>,
     ) -> Result<BlockGraphExport, ConsensusError> {
-        self.shared_state
-            .read()
-            .extract_block_graph_part(start_slot, end_slot)
+        // served from the snapshot published by the worker, without locking the consensus state
+        let graph_export = self.snapshots.read().graph_export.clone();
+        Ok(graph_export.export(start_slot, end_slot))
+    }
+
+    /// Get a block graph export in a given period on behalf of an external caller, within its budget
+    ///
+    /// # Arguments:
+    /// * `caller`: the identity of the caller
+    /// * `start_slot`: the start slot
+    /// * `end_slot`: the end slot
+    ///
+    /// # Returns:
+    /// An export of the block graph in this period, or `QuotaExceeded`
+    fn get_block_graph_status_as(
+        &self,
+        caller: &QueryCaller,
+        start_slot: Option<Slot>,
+        end_slot: Option<Slot>,
+    ) -> Result<BlockGraphExport, ConsensusError> {
+        self.query_quotas
+            .lock()
+            .consume(caller, HeavyQuery::GraphExport, MassaTime::now())?;
+        self.get_block_graph_status(start_slot, end_slot)
+    }
+
+    /// Get a page of the active blocks of the graph, ordered by slot then block id
+    ///
+    /// # Arguments:
+    /// * `request`: the page to get
+    ///
+    /// # Returns:
+    /// The exported active blocks of the page, with the cursor of the next page
+    fn get_block_graph_page(
+        &self,
+        request: PageRequest,
+    ) -> PageResponse<(BlockId, ExportCompiledBlock)> {
+        self.shared_state.read().get_block_graph_page(&request)
+    }
+
+    /// Get a page of the remembered stale blocks, ordered by slot then block id
+    ///
+    /// # Arguments:
+    /// * `request`: the page to get
+    ///
+    /// # Returns:
+    /// The stale blocks of the page, with the cursor of the next page
+    fn get_stale_blocks(&self, request: PageRequest) -> PageResponse<StaleBlock> {
+        self.shared_state.read().get_stale_blocks(&request)
     }
 
     /// Get statuses of blocks present in the graph
@@ -91,12 +350,54 @@ impl ConsensusController for ConsensusControllerImpl {
             .collect()
     }
 
-    /// Get all the cliques possible in the block graph.
+    fn has_block(&self, block_id: BlockId) -> HeaderKnowledge {
+        self.shared_state
+            .read()
+            .blocks_state
+            .get(&block_id)
+            .map_or(HeaderKnowledge::Unknown, HeaderKnowledge::from)
+    }
+
+    /// Get statuses of blocks along with their storage, under a single read lock
+    ///
+    /// # Arguments:
+    /// * `block_ids`: the block ids to get
     ///
     /// # Returns:
-    /// A vector of cliques
-    fn get_cliques(&self) -> Vec<Clique> {
-        self.shared_state.read().max_cliques.clone()
+    /// A vector of optional statuses and storages sorted by the order of the block ids,
+    /// or an error in light mode or if the operations of the executed blocks are dropped,
+    /// when the operations of one of the blocks were dropped
+    fn get_blocks_with_storage(
+        &self,
+        ids: &[BlockId],
+    ) -> Result<Vec<Option<(BlockGraphStatus, Storage)>>, ConsensusError> {
+        let read_shared_state = self.shared_state.read();
+        let config = &read_shared_state.config;
+        if config.light_mode || config.drop_executed_block_bodies {
+            if let Some(block_id) = ids
+                .iter()
+                .find(|id| read_shared_state.active_index_without_ops.contains(id))
+            {
+                let reason = format!("the operations of block {} were dropped", block_id);
+                return Err(if config.light_mode {
+                    ConsensusError::NotAvailableInLightMode(reason)
+                } else {
+                    ConsensusError::ExecutedBlockBodiesDropped(reason)
+                });
+            }
+        }
+        Ok(ids
+            .iter()
+            .map(|id| read_shared_state.get_block_with_storage(id))
+            .collect())
+    }
+
+    /// Get the cliques of the block graph that are not dominated by the blockclique, without locking the consensus state
+    ///
+    /// # Returns:
+    /// A shared vector of cliques, see `max_exposed_cliques` and `exposed_clique_fitness_margin` in the config
+    fn get_cliques_snapshot(&self) -> Arc<Vec<Clique>> {
+        self.snapshots.read().cliques.clone()
     }
 
     /// Get a part of the graph to send to a node so that he can setup his graph.
@@ -112,7 +413,7 @@ impl ConsensusController for ConsensusControllerImpl {
     /// * The streaming step value after the current iteration
     fn get_bootstrap_part(
         &self,
-        mut cursor: StreamingStep<PreHashSet<BlockId>>,
+        cursor: StreamingStep<PreHashSet<BlockId>>,
         execution_cursor: StreamingStep<Slot>,
     ) -> Result<
         (
@@ -122,86 +423,192 @@ impl ConsensusController for ConsensusControllerImpl {
         ),
         ConsensusError,
     > {
-        let mut final_blocks: Vec<ExportActiveBlock> = Vec::new();
-        let mut retrieved_ids: PreHashSet<BlockId> = PreHashSet::default();
-        let read_shared_state = self.shared_state.read();
-        let required_blocks: PreHashSet<BlockId> = match execution_cursor {
-            StreamingStep::Ongoing(slot) | StreamingStep::Finished(Some(slot)) => {
-                read_shared_state.list_required_active_blocks(Some(slot))?
-            }
-            _ => PreHashSet::default(),
-        };
+        // the blocks are copied once the lock is released
+        let pending_part =
+            self.shared_state
+                .read()
+                .extract_bootstrap_part(cursor, execution_cursor, 1)?;
+        Ok(pending_part.finish())
+    }
 
-        let (current_ids, previous_ids, outdated_ids) = match cursor {
-            StreamingStep::Started => (
-                required_blocks,
-                PreHashSet::default(),
-                PreHashSet::default(),
-            ),
-            StreamingStep::Ongoing(ref cursor_ids) => (
-                // ids that are contained in required_blocks but not in the download cursor => current_ids
-                required_blocks.difference(cursor_ids).cloned().collect(),
-                // ids previously downloaded => previous_ids
-                cursor_ids.clone(),
-                // ids previously downloaded but not contained in required_blocks anymore => outdated_ids
-                cursor_ids.difference(&required_blocks).cloned().collect(),
-            ),
-            StreamingStep::Finished(_) => {
-                return Ok((
-                    BootstrapableGraph { final_blocks },
-                    PreHashSet::default(),
-                    cursor,
-                ))
-            }
+    /// Get a part of the graph for bootstrap on behalf of an external caller, within its budget.
+    /// The caller streams in its own session, `bootstrap_part_size` being shared with the other ongoing sessions.
+    ///
+    /// # Arguments:
+    /// * `caller`: the identity of the caller
+    /// * `cursor`, `execution_cursor`: see `get_bootstrap_part`
+    ///
+    /// # Returns:
+    /// See `get_bootstrap_part`, or `QuotaExceeded`
+    fn get_bootstrap_part_as(
+        &self,
+        caller: &QueryCaller,
+        cursor: StreamingStep<PreHashSet<BlockId>>,
+        execution_cursor: StreamingStep<Slot>,
+    ) -> Result<
+        (
+            BootstrapableGraph,
+            PreHashSet<BlockId>,
+            StreamingStep<PreHashSet<BlockId>>,
+        ),
+        ConsensusError,
+    > {
+        let now = MassaTime::now();
+        self.query_quotas
+            .lock()
+            .consume(caller, HeavyQuery::BootstrapPart, now)?;
+        let concurrent_sessions = self.bootstrap_sessions.lock().begin_part(caller, now);
+        // the blocks are copied once the lock is released
+        let pending_part = self.shared_state.read().extract_bootstrap_part(
+            cursor,
+            execution_cursor,
+            concurrent_sessions,
+        )?;
+        let (part, outdated_ids, cursor) = pending_part.finish();
+        // the stream of the client is over once both the graph and the final state are streamed
+        if cursor.finished() && execution_cursor.finished() {
+            self.bootstrap_sessions.lock().end_session(caller);
+        }
+        Ok((part, outdated_ids, cursor))
+    }
+
+    /// Get the stats of the consensus
+    fn get_stats(&self) -> Result<ConsensusStats, ConsensusError> {
+        self.shared_state.read().get_stats()
+    }
+
+    fn get_blockclique_at(&self, slot: Slot) -> Option<PreHashSet<BlockId>> {
+        self.shared_state.read().get_blockclique_at(&slot)
+    }
+
+    fn get_best_parents_at(&self, slot: Slot) -> Option<Vec<(BlockId, u64)>> {
+        self.shared_state.read().get_best_parents_at(&slot)
+    }
+
+    fn get_operation_inclusion(&self, op_id: OperationId) -> Vec<(BlockId, BlockGraphStatus)> {
+        self.shared_state.read().get_operation_inclusion(&op_id)
+    }
+
+    fn get_block_header_bytes(&self, block_id: BlockId) -> Result<Option<Vec<u8>>, ConsensusError> {
+        let Some(header) = self.shared_state.read().get_secured_header(&block_id) else {
+            return Ok(None);
         };
+        // the content is not serialized again: the bytes it was received and signed with are kept in the header
+        let mut bytes = Vec::new();
+        SecureShareSerializer::new()
+            .serialize(&header, &mut bytes)
+            .map_err(|err| ConsensusError::SerializationError(err.to_string()))?;
+        Ok(Some(bytes))
+    }
 
-        for b_id in &current_ids {
-            if let Some(BlockStatus::Active {
-                a_block,
-                storage_or_block,
-            }) = read_shared_state.blocks_state.get(b_id)
-            {
-                if final_blocks.len() as u64 >= self.bootstrap_part_size {
-                    break;
-                }
-                match execution_cursor {
-                    StreamingStep::Ongoing(slot) | StreamingStep::Finished(Some(slot)) => {
-                        if a_block.slot > slot {
-                            continue;
-                        }
-                    }
-                    _ => (),
-                }
-                if a_block.is_final {
-                    let export = ExportActiveBlock::from_active_block(a_block, storage_or_block);
-                    final_blocks.push(export);
-                    retrieved_ids.insert(*b_id);
-                }
-            }
-        }
+    fn get_last_reorg_report(&self) -> Option<ReorgReport> {
+        self.shared_state.read().last_reorg_report.clone()
+    }
 
-        if final_blocks.is_empty() {
-            cursor = StreamingStep::Finished(None);
-        } else {
-            let pruned_previous_ids = previous_ids.difference(&outdated_ids);
-            retrieved_ids.extend(pruned_previous_ids);
-            cursor = StreamingStep::Ongoing(retrieved_ids);
+    /// Get the number of blocks in each status, without locking the consensus state
+    fn get_active_block_count_by_status(&self) -> BlockCountsByStatus {
+        self.block_counts.read().clone()
+    }
+
+    /// Whether the worker ticked recently, without locking the consensus state
+    fn liveness(&self) -> bool {
+        self.health.read().is_alive(MassaTime::now())
+    }
+
+    /// Whether the worker is alive and the finality lag is under the threshold, without locking the consensus state
+    fn is_ready(&self) -> bool {
+        self.health.read().is_ready(MassaTime::now())
+    }
+
+    /// Get the catch-up status refreshed at the latest slot tick, without locking the consensus state
+    fn get_catch_up_status(&self) -> CatchUpStatus {
+        self.catch_up.read().clone()
+    }
+
+    fn get_finality_gap(&self) -> u64 {
+        self.shared_state.read().get_execution_finality_gap()
+    }
+
+    fn render_metrics(&self) -> String {
+        ConsensusMetrics {
+            block_counts: self.block_counts.read().clone(),
+            command_queue_depth: self.command_sender.len()
+                + self
+                    .shard_senders
+                    .iter()
+                    .map(|sender| sender.len())
+                    .sum::<usize>(),
+            finality_lag: self.health.read().finality_lag(MassaTime::now()),
+            execution_finality_gap: self.health.read().execution_finality_gap,
+            clique_count: self.shared_state.read().get_clique_count(),
         }
+        .render()
+    }
 
-        Ok((BootstrapableGraph { final_blocks }, outdated_ids, cursor))
+    fn get_blockclique_summary(&self) -> BlockcliqueSummary {
+        self.shared_state.read().get_blockclique_summary()
     }
 
-    /// Get the stats of the consensus
-    fn get_stats(&self) -> Result<ConsensusStats, ConsensusError> {
-        self.shared_state.read().get_stats()
+    /// Store the summary without going through the worker, it is only read at the next slot tick
+    fn register_peer_blockclique_summary(&self, peer_id: PeerId, summary: BlockcliqueSummary) {
+        self.peer_blockclique_summaries.write().insert(
+            peer_id,
+            PeerBlockcliqueSummary {
+                summary,
+                received_at: MassaTime::now(),
+            },
+        );
+    }
+
+    fn get_divergent_peers(&self) -> Vec<DivergentPeer> {
+        self.shared_state.read().get_divergent_peers()
     }
 
-    /// Get the current best parents for a block creation
+    fn ban(
+        &self,
+        address: Address,
+        reason: String,
+        duration: Option<MassaTime>,
+    ) -> Result<(), ConsensusError> {
+        self.shared_state
+            .write()
+            .ban_creator(address, reason, duration)
+    }
+
+    fn unban(&self, addresses: Vec<Address>) -> Result<(), ConsensusError> {
+        self.shared_state.write().unban_creators(&addresses)
+    }
+
+    fn list_bans(&self) -> Vec<CreatorBan> {
+        self.shared_state.read().list_creator_bans()
+    }
+
+    fn get_checkpoint(&self) -> Result<Option<ConsensusCheckpoint>, ConsensusError> {
+        match &self.db {
+            Some(db) => ConsensusCheckpoint::load(db),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the current best parents for a block creation, without locking the consensus state
     ///
     /// # Returns:
-    /// A block id and a period for each thread of the graph
-    fn get_best_parents(&self) -> Vec<(BlockId, u64)> {
-        self.shared_state.read().best_parents.clone()
+    /// A shared vector of a block id and a period for each thread of the graph
+    fn get_best_parents_snapshot(&self) -> Arc<Vec<(BlockId, u64)>> {
+        self.snapshots.read().best_parents.clone()
+    }
+
+    /// Get the blocks that can be referenced as parents by the next block to be produced
+    ///
+    /// # Returns
+    /// The best parents along with, for each thread, the candidate parents and their metadata
+    fn get_parent_candidates(&self) -> ParentCandidates {
+        self.shared_state.read().get_parent_candidates()
+    }
+
+    /// Get the generation of the graph, incremented each time the cliques or the best parents change
+    fn get_graph_generation(&self) -> u64 {
+        self.snapshots.read().generation
     }
 
     /// Get the block, that is in the blockclique, at a given slot.
@@ -230,87 +637,241 @@ impl ConsensusController for ConsensusControllerImpl {
             .get_latest_blockclique_block_at_slot(&slot)
     }
 
-    fn register_block(&self, block_id: BlockId, slot: Slot, block_storage: Storage, created: bool) {
-        if self.broadcast_enabled {
-            if let Some(verifiable_block) = block_storage.read_blocks().get(&block_id) {
-                let operations: Vec<(OperationId, Option<SecureShare<Operation, OperationId>>)> =
-                    verifiable_block
-                        .content
-                        .operations
-                        .iter()
-                        .map(|operation_id| {
-                            match block_storage.read_operations().get(operation_id).cloned() {
-                                Some(verifiable_operation) => {
-                                    (*operation_id, Some(verifiable_operation))
-                                }
-                                None => (*operation_id, None),
-                            }
-                        })
-                        .collect();
+    /// Get the latest block, that is in the blockclique or final, whose slot timestamp is at or before a given timestamp.
+    ///
+    /// # Arguments:
+    /// * `timestamp`: the upper bound of the slot timestamp of the block
+    ///
+    /// # Returns:
+    /// The block id of the latest such block if it is still in the graph
+    fn get_latest_blockclique_block_before_time(
+        &self,
+        timestamp: MassaTime,
+    ) -> Result<Option<BlockId>, ConsensusError> {
+        self.shared_state
+            .read()
+            .get_latest_blockclique_block_before_time(timestamp)
+    }
 
-                if let Err(err) = self.broadcasts.block_sender.send(verifiable_block.clone()) {
-                    trace!(
-                        "error, failed to broadcast block with id {} due to: {}",
-                        block_id,
-                        err
-                    );
-                }
+    /// Explain the fitness of an active block.
+    ///
+    /// # Arguments:
+    /// * `block_id`: the id of the block
+    ///
+    /// # Returns:
+    /// The components of the fitness of the block and of its cliques
+    fn explain_fitness(&self, block_id: BlockId) -> Result<FitnessExplanation, ConsensusError> {
+        self.shared_state.read().explain_fitness(&block_id)
+    }
 
-                if let Err(err) = self.broadcasts.filled_block_sender.send(FilledBlock {
-                    header: verifiable_block.content.header.clone(),
-                    operations,
-                }) {
-                    trace!(
-                        "error, failed to broadcast filled block with id {} due to: {}",
-                        block_id,
-                        err
-                    );
-                }
-            } else {
-                debug!(
-                    "error, no broadcast event sent, block with id {} not found",
-                    block_id
-                );
-            };
-        }
+    /// Get the retained active blocks with the reason why each one is retained.
+    ///
+    /// # Arguments:
+    /// * `end_slot`: if set, the blocks required for a bootstrap export up to that slot
+    ///
+    /// # Returns:
+    /// The retained blocks sorted by slot
+    fn list_required_active_blocks_explained(
+        &self,
+        end_slot: Option<Slot>,
+    ) -> Result<Vec<RequiredBlockExplanation>, ConsensusError> {
+        self.shared_state
+            .read()
+            .list_required_active_blocks_explained(end_slot)
+    }
 
-        if let Err(err) = self
-            .command_sender
-            .try_send(ConsensusCommand::RegisterBlock(
-                block_id,
-                slot,
-                block_storage,
-                created,
-            ))
-        {
-            warn!("error trying to register a block: {}", err);
-        }
+    /// Get the summaries of the active blocks in a slot range.
+    ///
+    /// # Arguments:
+    /// * `slot_range`: the range of slots to look into
+    ///
+    /// # Returns:
+    /// The summaries computed when the blocks were added to the graph, sorted by slot
+    fn get_block_summaries(&self, slot_range: std::ops::RangeInclusive<Slot>) -> Vec<BlockSummary> {
+        self.shared_state.read().get_block_summaries(&slot_range)
     }
 
-    fn register_block_header(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>) {
-        if self.broadcast_enabled {
-            if let Err(err) = self.broadcasts.block_header_sender.send(header.clone()) {
-                trace!(
-                    "error, failed to broadcast block header with block id {}: {}",
-                    block_id,
-                    err
-                );
-            }
-        }
+    /// Search the active blocks of the graph by header properties, ordered by slot then block id
+    ///
+    /// # Arguments:
+    /// * `filter`: the properties the blocks must all have
+    /// * `request`: the page to get, the first one or the one following a cursor
+    ///
+    /// # Returns:
+    /// The summaries of the matching blocks of the page, with the cursor of the next page
+    fn find_blocks(&self, filter: BlockFilter, request: PageRequest) -> PageResponse<BlockSummary> {
+        self.shared_state.read().find_blocks(&filter, &request)
+    }
 
-        if let Err(err) = self
-            .command_sender
-            .try_send(ConsensusCommand::RegisterBlockHeader(block_id, header))
-        {
-            warn!("error trying to register a block header: {}", err);
-        }
+    /// Get the filled and missed slots of a cycle per thread.
+    ///
+    /// # Arguments:
+    /// * `cycle`: the cycle to inspect
+    ///
+    /// # Returns:
+    /// The occupancy of each thread over the final slots of the cycle still covered by the graph
+    fn get_slot_occupancy(&self, cycle: u64) -> Result<SlotOccupancy, ConsensusError> {
+        self.shared_state.read().get_slot_occupancy(cycle)
+    }
+
+    /// Get the endorsement draws of an address that were not fulfilled in final blocks, per cycle.
+    ///
+    /// # Arguments:
+    /// * `address`: the endorser to score
+    /// * `cycles`: the cycles to inspect
+    ///
+    /// # Returns:
+    /// The draws and misses of the address in each tracked cycle of the range
+    fn get_endorser_reliability(
+        &self,
+        address: Address,
+        cycles: std::ops::RangeInclusive<u64>,
+    ) -> EndorserReliability {
+        self.shared_state
+            .read()
+            .get_endorser_reliability(address, cycles)
+    }
+
+    /// Get the delay with which each peer relayed the blocks first received from it.
+    ///
+    /// # Returns:
+    /// The propagation latency statistics of each peer, sorted by peer id
+    fn get_peer_propagation_stats(&self) -> Vec<PeerPropagationStats> {
+        self.shared_state.read().get_peer_propagation_stats()
+    }
+
+    /// Apply the runtime-reloadable parameters of a new consensus configuration.
+    /// The new parameters are applied atomically under the state lock.
+    ///
+    /// # Arguments:
+    /// * `config`: the new configuration
+    ///
+    /// # Returns:
+    /// An error if a structural parameter (e.g. `thread_count`) differs from the current configuration
+    fn update_config(&self, config: ConsensusConfig) -> Result<(), ConsensusError> {
+        self.shared_state.write().update_config(&config)
+    }
+
+    fn get_config_summary(&self) -> ConsensusConfigSummary {
+        ConsensusConfigSummary::from(&self.shared_state.read().config)
+    }
+
+    fn slot_to_timestamp(&self, slot: Slot) -> Result<MassaTime, ConsensusError> {
+        let read_shared_state = self.shared_state.read();
+        let config = &read_shared_state.config;
+        Ok(get_block_slot_timestamp(
+            config.thread_count,
+            config.t0,
+            config.genesis_timestamp,
+            slot,
+        )?)
+    }
+
+    fn timestamp_to_slot(&self, timestamp: MassaTime) -> Result<Option<Slot>, ConsensusError> {
+        let read_shared_state = self.shared_state.read();
+        let config = &read_shared_state.config;
+        Ok(get_latest_block_slot_at_timestamp(
+            config.thread_count,
+            config.t0,
+            config.genesis_timestamp,
+            timestamp,
+        )?)
+    }
+
+    /// Scan the graph for recoverable inconsistencies and fix them under the state lock.
+    ///
+    /// # Returns:
+    /// A report of the fixed and unrecoverable inconsistencies
+    fn repair_graph(&self) -> GraphRepairReport {
+        self.shared_state.write().repair_graph()
+    }
+
+    fn submit_external_block_candidate(
+        &self,
+        header_template: BlockHeader,
+        operations: Vec<SecureShareOperation>,
+    ) -> Result<u64, ConsensusError> {
+        let (current_slot, mut storage) = {
+            let read_shared_state = self.shared_state.read();
+            let config = &read_shared_state.config;
+            let current_slot = get_latest_block_slot_at_timestamp(
+                config.thread_count,
+                config.t0,
+                config.genesis_timestamp,
+                MassaTime::now(),
+            )?;
+            check_external_candidate(
+                config,
+                current_slot,
+                &read_shared_state.get_parent_candidates(),
+                &header_template,
+                &operations,
+            )?;
+            (current_slot, read_shared_state.storage.clone_without_refs())
+        };
+
+        storage.store_endorsements(header_template.endorsements.clone());
+        let operation_ids = operations.iter().map(|operation| operation.id).collect();
+        storage.store_operations(operations);
+
+        let slot = header_template.slot;
+        let mut external_candidates = self.external_candidates.lock();
+        external_candidates.prune(current_slot);
+        let id = external_candidates.insert(header_template, operation_ids, storage)?;
+        debug!("external block candidate {} accepted for slot {}", id, slot);
+        Ok(id)
+    }
+
+    fn get_external_block_candidates(&self, slot: Slot) -> Vec<ExternalBlockCandidate> {
+        self.external_candidates.lock().get(&slot)
+    }
+
+    fn register_block(
+        &self,
+        block_id: BlockId,
+        slot: Slot,
+        block_storage: Storage,
+        origin: BlockOrigin,
+    ) -> SubmissionTicket {
+        self.register_block_inner(block_id, slot, block_storage, origin, None)
+    }
+
+    fn register_block_with_result(
+        &self,
+        block_id: BlockId,
+        slot: Slot,
+        block_storage: Storage,
+        origin: BlockOrigin,
+    ) -> (SubmissionTicket, ProcessingResultReceiver) {
+        let (result_tx, result_rx) = processing_result_channel();
+        let ticket =
+            self.register_block_inner(block_id, slot, block_storage, origin, Some(result_tx));
+        (ticket, result_rx)
+    }
+
+    fn register_block_header(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        origin: BlockOrigin,
+    ) -> SubmissionTicket {
+        self.register_block_header_inner(block_id, header, origin, None)
+    }
+
+    fn register_block_header_with_result(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        origin: BlockOrigin,
+    ) -> (SubmissionTicket, ProcessingResultReceiver) {
+        let (result_tx, result_rx) = processing_result_channel();
+        let ticket = self.register_block_header_inner(block_id, header, origin, Some(result_tx));
+        (ticket, result_rx)
     }
 
     fn mark_invalid_block(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>) {
-        if let Err(err) = self
-            .command_sender
-            .try_send(ConsensusCommand::MarkInvalidBlock(block_id, header))
-        {
+        let command = ConsensusCommand::MarkInvalidBlock(block_id, header);
+        if let Err(err) = self.command_sender_of(&command).try_send(command) {
             warn!("error trying to mark block as invalid: {}", err);
         }
     }
@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+
+use massa_models::{block_id::BlockId, prehash::PreHashMap};
+use massa_time::MassaTime;
+
+/// Recently registered blocks, used by the controller to drop the registrations of a block
+/// received from several peers before they are queued for the worker.
+/// Entries are forgotten after `window` or when more than `capacity` blocks are remembered.
+pub(crate) struct IncomingBlockDedup {
+    /// time during which a registered block is remembered
+    window: MassaTime,
+    /// maximum number of remembered blocks
+    capacity: usize,
+    /// registration time of the remembered blocks
+    seen: PreHashMap<BlockId, MassaTime>,
+    /// remembered blocks in registration order
+    order: VecDeque<(MassaTime, BlockId)>,
+    /// number of registrations dropped since launch
+    hit_count: u64,
+}
+
+impl IncomingBlockDedup {
+    pub(crate) fn new(window: MassaTime, capacity: usize) -> Self {
+        IncomingBlockDedup {
+            window,
+            capacity,
+            seen: Default::default(),
+            order: Default::default(),
+            hit_count: 0,
+        }
+    }
+
+    /// Remember a block registered at `now`
+    ///
+    /// # Returns
+    /// false if the block was already registered within the window, in which case the registration must be dropped
+    pub(crate) fn insert(&mut self, block_id: BlockId, now: MassaTime) -> bool {
+        self.prune(now);
+        if self.capacity == 0 {
+            return true;
+        }
+        if self.seen.contains_key(&block_id) {
+            self.hit_count += 1;
+            return false;
+        }
+        if self.seen.len() >= self.capacity {
+            if let Some((_, oldest)) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(block_id, now);
+        self.order.push_back((now, block_id));
+        true
+    }
+
+    /// Forget a block, so that its next registration is not dropped
+    pub(crate) fn remove(&mut self, block_id: &BlockId) {
+        if self.seen.remove(block_id).is_some() {
+            self.order.retain(|(_, id)| id != block_id);
+        }
+    }
+
+    /// Number of registrations dropped since launch
+    pub(crate) fn hit_count(&self) -> u64 {
+        self.hit_count
+    }
+
+    /// Forget the blocks registered before the window
+    fn prune(&mut self, now: MassaTime) {
+        let start = now.saturating_sub(self.window);
+        while let Some((time, block_id)) = self.order.front() {
+            if *time >= start {
+                break;
+            }
+            self.seen.remove(block_id);
+            self.order.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+
+    #[test]
+    fn test_incoming_block_dedup() {
+        let id = |i: u8| BlockId::generate_from_hash(Hash::compute_from(&[i]));
+        let mut dedup = IncomingBlockDedup::new(MassaTime::from_millis(1000), 2);
+        let now = MassaTime::from_millis(10_000);
+
+        assert!(dedup.insert(id(1), now));
+        assert!(!dedup.insert(id(1), now));
+        assert_eq!(dedup.hit_count(), 1);
+
+        // forgotten after the window
+        assert!(dedup.insert(id(1), now.saturating_add(MassaTime::from_millis(1001))));
+
+        // forgotten when over capacity
+        let now = now.saturating_add(MassaTime::from_millis(2000));
+        assert!(dedup.insert(id(2), now));
+        assert!(dedup.insert(id(3), now));
+        assert!(dedup.insert(id(4), now));
+        assert!(dedup.insert(id(2), now));
+
+        dedup.remove(&id(2));
+        assert!(dedup.insert(id(2), now));
+        assert_eq!(dedup.hit_count(), 1);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::{BTreeMap, HashSet};
+
+use massa_consensus_exports::{
+    error::ConsensusError, external_candidates::ExternalBlockCandidate,
+    parent_candidates::ParentCandidates, ConsensusConfig,
+};
+use massa_models::{
+    block_header::BlockHeader,
+    config::MAX_OPERATIONS_PER_BLOCK,
+    operation::{
+        compute_operations_hash, OperationId, OperationIdSerializer, SecureShareOperation,
+    },
+    prehash::PreHashSet,
+    slot::Slot,
+};
+use massa_storage::Storage;
+
+/// Maximum number of external candidates kept for a slot
+const MAX_EXTERNAL_CANDIDATES_PER_SLOT: usize = 16;
+
+/// External block candidates accepted by the controller, kept until their slot is passed
+#[derive(Default)]
+pub(crate) struct ExternalCandidatePool {
+    /// id of the next accepted candidate
+    next_id: u64,
+    /// accepted candidates per slot, in submission order
+    candidates: BTreeMap<Slot, Vec<ExternalBlockCandidate>>,
+}
+
+impl ExternalCandidatePool {
+    /// Forget the candidates of the slots before `current_slot`
+    pub(crate) fn prune(&mut self, current_slot: Option<Slot>) {
+        if let Some(current_slot) = current_slot {
+            self.candidates = self.candidates.split_off(&current_slot);
+        }
+    }
+
+    /// Keep a candidate that was checked with `check_external_candidate`
+    ///
+    /// # Returns
+    /// The id of the candidate, or an error if too many candidates are kept for its slot
+    pub(crate) fn insert(
+        &mut self,
+        header_template: BlockHeader,
+        operations: Vec<OperationId>,
+        storage: Storage,
+    ) -> Result<u64, ConsensusError> {
+        let slot_candidates = self.candidates.entry(header_template.slot).or_default();
+        if slot_candidates.len() >= MAX_EXTERNAL_CANDIDATES_PER_SLOT {
+            return Err(ConsensusError::InvalidExternalCandidate(format!(
+                "slot {}: {} candidates are already kept for this slot",
+                header_template.slot, MAX_EXTERNAL_CANDIDATES_PER_SLOT
+            )));
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        slot_candidates.push(ExternalBlockCandidate {
+            id,
+            header_template,
+            operations,
+            storage,
+        });
+        Ok(id)
+    }
+
+    /// Get the candidates of a slot, in submission order
+    pub(crate) fn get(&self, slot: &Slot) -> Vec<ExternalBlockCandidate> {
+        self.candidates.get(slot).cloned().unwrap_or_default()
+    }
+}
+
+/// Check the contents proposed by an external builder against the graph
+///
+/// # Arguments
+/// * `config`: consensus configuration
+/// * `current_slot`: latest slot at the current time, the candidate must be for a later slot
+/// * `parent_candidates`: blocks that can currently be referenced as parents
+/// * `header`: header template of the candidate
+/// * `operations`: operations of the candidate, in block order
+pub(crate) fn check_external_candidate(
+    config: &ConsensusConfig,
+    current_slot: Option<Slot>,
+    parent_candidates: &ParentCandidates,
+    header: &BlockHeader,
+    operations: &[SecureShareOperation],
+) -> Result<(), ConsensusError> {
+    let slot = header.slot;
+    let invalid = |reason: String| {
+        Err(ConsensusError::InvalidExternalCandidate(format!(
+            "slot {}: {}",
+            slot, reason
+        )))
+    };
+
+    if slot.thread >= config.thread_count {
+        return invalid("invalid thread".to_string());
+    }
+    if current_slot.map_or(false, |current_slot| slot <= current_slot) {
+        return invalid("the slot is not in the future".to_string());
+    }
+
+    // parents
+    let parents = match parent_candidates.with_periods(&header.parents) {
+        Some(parents) if parent_candidates.is_valid_choice(slot, &parents) => parents,
+        _ => {
+            return invalid(
+                "the parents are not a valid choice among the parent candidates".to_string(),
+            )
+        }
+    };
+
+    // operations
+    if operations.len() > MAX_OPERATIONS_PER_BLOCK as usize {
+        return invalid(format!("more than {} operations", MAX_OPERATIONS_PER_BLOCK));
+    }
+    let mut seen_operations = PreHashSet::<OperationId>::default();
+    for operation in operations {
+        if !seen_operations.insert(operation.id) {
+            return invalid(format!("operation {} is included twice", operation.id));
+        }
+        if operation
+            .content_creator_address
+            .get_thread(config.thread_count)
+            != slot.thread
+        {
+            return invalid(format!(
+                "operation {} was created in another thread",
+                operation.id
+            ));
+        }
+        if !operation
+            .get_validity_range(config.operation_validity_periods)
+            .contains(&slot.period)
+        {
+            return invalid(format!(
+                "operation {} is not valid at this period",
+                operation.id
+            ));
+        }
+        if let Err(err) = operation.verify_signature() {
+            return invalid(format!(
+                "invalid signature of operation {}: {}",
+                operation.id, err
+            ));
+        }
+    }
+    let operation_ids: Vec<OperationId> = operations.iter().map(|operation| operation.id).collect();
+    if header.operation_merkle_root
+        != compute_operations_hash(&operation_ids, &OperationIdSerializer::new())
+    {
+        return invalid("the operation merkle root does not match the operations".to_string());
+    }
+
+    // endorsements
+    let (same_thread_parent, _) = parents[slot.thread as usize];
+    let mut seen_indexes = HashSet::new();
+    for endorsement in &header.endorsements {
+        if endorsement.content.slot != slot
+            || endorsement.content.endorsed_block != same_thread_parent
+        {
+            return invalid(format!(
+                "endorsement {} does not endorse the parent of the thread of the slot",
+                endorsement.id
+            ));
+        }
+        if endorsement.content.index >= config.endorsement_count
+            || !seen_indexes.insert(endorsement.content.index)
+        {
+            return invalid(format!(
+                "endorsement {} has an invalid or duplicate index",
+                endorsement.id
+            ));
+        }
+        if let Err(err) = endorsement.verify_signature() {
+            return invalid(format!(
+                "invalid signature of endorsement {}: {}",
+                endorsement.id, err
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+
+    #[test]
+    fn test_external_candidate_pool() {
+        let header = |slot: Slot| BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            slot,
+            parents: Vec::new(),
+            operation_merkle_root: Hash::compute_from(&[]),
+            endorsements: Vec::new(),
+            denunciations: Vec::new(),
+            extensions: Vec::new(),
+        };
+        let storage = Storage::create_root();
+        let mut pool = ExternalCandidatePool::default();
+
+        for expected_id in 0..MAX_EXTERNAL_CANDIDATES_PER_SLOT as u64 {
+            let id = pool
+                .insert(header(Slot::new(1, 0)), Vec::new(), storage.clone())
+                .unwrap();
+            assert_eq!(id, expected_id);
+        }
+        assert!(pool
+            .insert(header(Slot::new(1, 0)), Vec::new(), storage.clone())
+            .is_err());
+        pool.insert(header(Slot::new(2, 0)), Vec::new(), storage)
+            .unwrap();
+        assert_eq!(
+            pool.get(&Slot::new(1, 0)).len(),
+            MAX_EXTERNAL_CANDIDATES_PER_SLOT
+        );
+
+        // the candidates of passed slots are forgotten
+        pool.prune(Some(Slot::new(2, 0)));
+        assert!(pool.get(&Slot::new(1, 0)).is_empty());
+        let candidates = pool.get(&Slot::new(2, 0));
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].id, MAX_EXTERNAL_CANDIDATES_PER_SLOT as u64);
+    }
+}
@@ -0,0 +1,335 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Harness of the fuzz targets of the consensus command processing.
+//!
+//! The commands are applied directly to a `ConsensusState`, in the calling thread and with a
+//! virtual current slot, the same way the worker main loop applies them. The other modules are
+//! mocks accepting any call, and all the blocks are produced by the single drawn staker.
+//! After each command, the graph must be consistent: no repair is needed, each thread has a best
+//! parent and a latest final block, and no best parent is older than the final block of its thread.
+
+use std::{sync::Arc, time::Duration};
+
+use massa_channel::{event_bus::MassaEventBus, MassaChannel};
+use massa_consensus_exports::{
+    fuzzing::{FuzzBlock, FuzzCommand},
+    ConsensusBroadcasts, ConsensusChannels, ConsensusConfig,
+};
+use massa_execution_exports::MockExecutionController;
+use massa_hash::Hash;
+use massa_metrics::MassaMetrics;
+use massa_models::{
+    address::Address,
+    block::{Block, BlockSerializer, SecureShareBlock},
+    block_header::{BlockHeader, BlockHeaderSerializer},
+    block_id::BlockId,
+    config::{CHAINID, ENDORSEMENT_COUNT, MIP_STORE_STATS_BLOCK_CONSIDERED},
+    secure_share::SecureShareContent,
+    slot::Slot,
+};
+use massa_pool_exports::MockPoolController;
+use massa_pos_exports::{MockSelectorController, Selection};
+use massa_protocol_exports::MockProtocolController;
+use massa_signature::KeyPair;
+use massa_storage::Storage;
+use massa_time::MassaTime;
+use massa_versioning::versioning::{MipStatsConfig, MipStore};
+use num::rational::Ratio;
+use parking_lot::RwLock;
+
+use crate::{
+    state::ConsensusState,
+    worker::{new_consensus_state, ConsensusWorker},
+};
+
+/// Thread count of the fuzzed graphs, low so that random parents are often compatible
+const FUZZ_THREAD_COUNT: u8 = 2;
+
+/// Maximum period offset of the generated blocks from the current slot
+const MAX_PERIOD_OFFSET: u8 = 4;
+
+/// Maximum number of slots the current slot advances by at each tick
+const MAX_TICK_SLOTS: u8 = 8;
+
+/// Consensus state driven by fuzzed commands
+pub struct FuzzHarness {
+    /// fuzzed state, shared with the worker that loaded the genesis blocks
+    state: Arc<RwLock<ConsensusState>>,
+    /// key of the only staker
+    keypair: KeyPair,
+    /// virtual current slot
+    current_slot: Slot,
+    /// genesis block of each thread
+    genesis: Vec<BlockId>,
+    /// generated blocks of each thread, in generation order
+    thread_blocks: Vec<Vec<BlockId>>,
+    /// all the generated blocks, in generation order
+    blocks: Vec<SecureShareBlock>,
+    /// storage the blocks are registered with
+    storage: Storage,
+}
+
+impl Default for FuzzHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FuzzHarness {
+    /// Create a consensus state holding the genesis blocks, with mocks accepting any call
+    pub fn new() -> Self {
+        let keypair = KeyPair::generate(0).unwrap();
+        let staker = Address::from_public_key(&keypair.get_public_key());
+        let config = ConsensusConfig {
+            thread_count: FUZZ_THREAD_COUNT,
+            genesis_timestamp: MassaTime::now(),
+            genesis_key: keypair.clone(),
+            ..ConsensusConfig::default()
+        };
+
+        let mut execution_controller = Box::new(MockExecutionController::new());
+        execution_controller
+            .expect_update_blockclique_status()
+            .returning(|_, _, _| {});
+        execution_controller
+            .expect_get_final_cursor()
+            .returning(|| Slot::new(0, 0));
+        execution_controller
+            .expect_get_candidate_cursor()
+            .returning(|| Slot::new(0, 0));
+        let mut pool_controller = Box::new(MockPoolController::new());
+        pool_controller
+            .expect_add_denunciation_precursor()
+            .returning(|_| {});
+        pool_controller
+            .expect_notify_final_cs_periods()
+            .returning(|_| {});
+        pool_controller
+            .expect_notify_endorsement_feedback()
+            .returning(|_| {});
+        let mut protocol_controller = Box::new(MockProtocolController::new());
+        protocol_controller
+            .expect_integrated_block()
+            .returning(|_, _| Ok(()));
+        protocol_controller
+            .expect_send_wishlist_delta()
+            .returning(|_, _| Ok(()));
+        protocol_controller
+            .expect_notify_block_attack()
+            .returning(|_| Ok(()));
+        let mut selector_controller = Box::new(MockSelectorController::new());
+        selector_controller
+            .expect_get_producer()
+            .returning(move |_| Ok(staker));
+        selector_controller
+            .expect_get_selection()
+            .returning(move |_| {
+                Ok(Selection {
+                    producer: staker,
+                    endorsements: vec![staker; ENDORSEMENT_COUNT as usize],
+                })
+            });
+
+        let (controller_event_tx, _) = MassaChannel::new(String::from("consensus_event"), None);
+        let channels = ConsensusChannels {
+            execution_controller,
+            selector_controller,
+            pool_controller,
+            protocol_controller,
+            mip_store: MipStore::try_from((
+                [],
+                MipStatsConfig {
+                    block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+                    warn_announced_version_ratio: Ratio::new_raw(30, 100),
+                },
+            ))
+            .expect("could not create the MIP store"),
+            db: None,
+            controller_event_tx,
+            broadcasts: ConsensusBroadcasts {
+                block_sender: tokio::sync::broadcast::channel(1).0,
+                block_header_sender: tokio::sync::broadcast::channel(1).0,
+                filled_block_sender: tokio::sync::broadcast::channel(1).0,
+            },
+            event_bus: MassaEventBus::new("fuzz_node_event".to_string()),
+        };
+        let storage = Storage::create_root();
+        let massa_metrics = MassaMetrics::new(
+            false,
+            "0.0.0.0:9898".parse().unwrap(),
+            FUZZ_THREAD_COUNT,
+            Duration::from_secs(1),
+        )
+        .0;
+        let state = Arc::new(RwLock::new(new_consensus_state(
+            &config,
+            channels,
+            &storage,
+            massa_metrics,
+        )));
+        // the worker loads the genesis blocks into the state, its command channel is left unused
+        let (_, command_receiver) = MassaChannel::new(String::from("consensus_command"), None);
+        ConsensusWorker::new(
+            config,
+            command_receiver,
+            state.clone(),
+            None,
+            storage.clone(),
+        )
+        .expect("could not initialize the fuzzed consensus state");
+        let genesis = state
+            .read()
+            .best_parents
+            .iter()
+            .map(|(id, _)| *id)
+            .collect();
+
+        FuzzHarness {
+            state,
+            keypair,
+            current_slot: Slot::new(0, FUZZ_THREAD_COUNT - 1),
+            genesis,
+            thread_blocks: vec![Vec::new(); FUZZ_THREAD_COUNT as usize],
+            blocks: Vec::new(),
+            storage,
+        }
+    }
+
+    /// Apply a command to the state. Errors are expected with random inputs and ignored
+    pub fn apply(&mut self, command: &FuzzCommand) {
+        let current_slot = Some(self.current_slot);
+        let shared_state = self.state.clone();
+        let mut state = shared_state.write();
+        let _ = match command {
+            FuzzCommand::RegisterBlock(fuzz_block) => {
+                let (block, slot) = self.generate_block(fuzz_block);
+                let mut storage = self.storage.clone_without_refs();
+                storage.store_block(block.clone());
+                state.register_block(block.id, slot, current_slot, storage, false)
+            }
+            FuzzCommand::RegisterBlockHeader(fuzz_block) => {
+                let (block, _) = self.generate_block(fuzz_block);
+                state.register_block_header(block.id, block.content.header, current_slot)
+            }
+            FuzzCommand::RegisterKnownBlock { index } => match self.known_block(*index) {
+                Some(block) => {
+                    let mut storage = self.storage.clone_without_refs();
+                    storage.store_block(block.clone());
+                    state.register_block(
+                        block.id,
+                        block.content.header.content.slot,
+                        current_slot,
+                        storage,
+                        false,
+                    )
+                }
+                None => Ok(()),
+            },
+            FuzzCommand::MarkInvalidBlock { index } => {
+                if let Some(block) = self.known_block(*index) {
+                    state.mark_invalid_block(&block.id, block.content.header);
+                }
+                Ok(())
+            }
+            FuzzCommand::SlotTick { slots } => {
+                for _ in 0..(slots % MAX_TICK_SLOTS) + 1 {
+                    if let Ok(next_slot) = self.current_slot.get_next_slot(FUZZ_THREAD_COUNT) {
+                        self.current_slot = next_slot;
+                    }
+                }
+                state
+                    .slot_tick(self.current_slot)
+                    .and_then(|_| state.prune())
+            }
+        }
+        .and_then(|_| state.block_db_changed());
+    }
+
+    /// Check the consistency of the graph, panicking on the first broken invariant
+    pub fn check_invariants(&self) {
+        let mut state = self.state.write();
+        let report = state.repair_graph();
+        assert!(report.is_clean(), "inconsistent graph: {:?}", report);
+        assert_eq!(state.best_parents.len(), FUZZ_THREAD_COUNT as usize);
+        assert_eq!(
+            state.latest_final_blocks_periods.len(),
+            FUZZ_THREAD_COUNT as usize
+        );
+        for (thread, ((_, best_period), (_, final_period))) in state
+            .best_parents
+            .iter()
+            .zip(state.latest_final_blocks_periods.iter())
+            .enumerate()
+        {
+            assert!(
+                best_period >= final_period,
+                "best parent of thread {} at period {} is older than its final block at period {}",
+                thread,
+                best_period,
+                final_period
+            );
+        }
+    }
+
+    /// Previously generated block, counted from the latest one
+    fn known_block(&self, index: u8) -> Option<SecureShareBlock> {
+        self.blocks.iter().rev().nth(index as usize).cloned()
+    }
+
+    /// Turn a fuzzed block into a signed block, and return the slot to register it with
+    fn generate_block(&mut self, fuzz_block: &FuzzBlock) -> (SecureShareBlock, Slot) {
+        let slot = Slot::new(
+            self.current_slot
+                .period
+                .saturating_add((fuzz_block.period_offset % MAX_PERIOD_OFFSET) as u64)
+                .max(1),
+            fuzz_block.thread % FUZZ_THREAD_COUNT,
+        );
+        let parents = (0..FUZZ_THREAD_COUNT as usize)
+            .map(|thread| {
+                let index = fuzz_block.parents.get(thread).copied().unwrap_or(0);
+                self.thread_blocks[thread]
+                    .iter()
+                    .rev()
+                    .nth(index as usize)
+                    .copied()
+                    .unwrap_or(self.genesis[thread])
+            })
+            .collect();
+        let header = BlockHeader::new_verifiable(
+            BlockHeader {
+                current_version: 0,
+                announced_version: None,
+                denunciations: Vec::new(),
+                extensions: Vec::new(),
+                slot,
+                parents,
+                operation_merkle_root: Hash::compute_from(&[fuzz_block.seed]),
+                endorsements: Vec::new(),
+            },
+            BlockHeaderSerializer::new(),
+            &self.keypair,
+            *CHAINID,
+        )
+        .expect("could not sign the fuzzed header");
+        let block = Block::new_verifiable(
+            Block {
+                header,
+                operations: Default::default(),
+            },
+            BlockSerializer::new(),
+            &self.keypair,
+            *CHAINID,
+        )
+        .expect("could not sign the fuzzed block");
+        self.thread_blocks[slot.thread as usize].push(block.id);
+        self.blocks.push(block.clone());
+
+        let registered_slot = if fuzz_block.wrong_slot {
+            Slot::new(slot.period.saturating_add(1), slot.thread)
+        } else {
+            slot
+        };
+        (block, registered_slot)
+    }
+}
@@ -0,0 +1,221 @@
+//! Per-thread insertion shards in front of the consensus worker.
+//!
+//! Every block has a parent in each thread and the clique update is global, so the graph itself is not split:
+//! the consensus worker stays the coordinator that checks the parents, updates the cliques and finalizes blocks.
+//! What is spread are the checks of an incoming header that do not access the graph: the draw of its creator,
+//! its header extensions and the draws of its endorsers. When `insertion_shards` is enabled, the controller
+//! queues each registration to the shard of the thread of the block, which runs these checks in parallel with
+//! the other shards, records the headers that passed them and forwards the registration to the worker.
+//! The worker then only runs the checks that were not passed, in the same order as without shards.
+//! The registrations of a given thread keep their order, those of different threads may be reordered.
+
+use std::{sync::Arc, thread::JoinHandle};
+
+use massa_channel::{receiver::MassaReceiver, sender::MassaSender, MassaChannel};
+use massa_consensus_exports::ConsensusConfig;
+use massa_models::{
+    block_header::SecuredHeader, block_id::BlockId, prehash::PreHashMap, slot::Slot,
+};
+use massa_pos_exports::SelectorController;
+use massa_versioning::versioning::MipStore;
+use parking_lot::Mutex;
+use tracing::warn;
+
+use crate::{
+    commands::ConsensusCommand,
+    state::{check_endorsement_draws, check_header_creator, EndorsementsCheckOutcome},
+};
+
+/// Graph-free checks passed by a header in an insertion shard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HeaderPrecheck {
+    /// slot of the header
+    pub slot: Slot,
+    /// the creator was drawn for the slot and the header extensions are accepted
+    pub creator: bool,
+    /// the endorsers were drawn for their indexes
+    pub endorsements: bool,
+}
+
+/// Headers checked by the insertion shards, shared with the consensus worker that skips the checks they passed.
+/// Only passed checks are recorded: a draw that was not available yet is checked again by the worker.
+/// Entries are forgotten once the slot of their header is final.
+#[derive(Debug, Default)]
+pub(crate) struct PrecheckedHeaders {
+    headers: PreHashMap<BlockId, HeaderPrecheck>,
+}
+
+impl PrecheckedHeaders {
+    /// Record the checks passed by a header
+    pub(crate) fn insert(&mut self, block_id: BlockId, precheck: HeaderPrecheck) {
+        if precheck.creator || precheck.endorsements {
+            self.headers.insert(block_id, precheck);
+        }
+    }
+
+    /// Get the checks passed by a header, if it went through an insertion shard
+    pub(crate) fn get(&self, block_id: &BlockId) -> Option<HeaderPrecheck> {
+        self.headers.get(block_id).copied()
+    }
+
+    /// Forget the headers whose slot is not after the latest final period of its thread
+    pub(crate) fn prune(&mut self, latest_final_blocks_periods: &[(BlockId, u64)]) {
+        self.headers.retain(|_, precheck| {
+            latest_final_blocks_periods
+                .get(precheck.slot.thread as usize)
+                .map_or(true, |(_, period)| precheck.slot.period > *period)
+        });
+    }
+}
+
+/// Run the graph-free checks of a header
+fn precheck_header(
+    config: &ConsensusConfig,
+    selector: &dyn SelectorController,
+    mip_store: &MipStore,
+    header: &SecuredHeader,
+) -> HeaderPrecheck {
+    let selection = selector.get_selection(header.content.slot).ok();
+    let creator = check_header_creator(
+        config,
+        mip_store,
+        header,
+        selection.as_ref().map(|selection| selection.producer),
+    )
+    .is_none();
+    let endorsements = matches!(
+        check_endorsement_draws(
+            header,
+            selection
+                .as_ref()
+                .map(|selection| selection.endorsements.as_slice()),
+        ),
+        EndorsementsCheckOutcome::Proceed
+    );
+    HeaderPrecheck {
+        slot: header.content.slot,
+        creator,
+        endorsements,
+    }
+}
+
+/// Loop of an insertion shard: check the headers of the registrations and forward them to the worker
+fn run_shard(
+    config: ConsensusConfig,
+    selector: Box<dyn SelectorController>,
+    mip_store: MipStore,
+    prechecked_headers: Arc<Mutex<PrecheckedHeaders>>,
+    receiver: MassaReceiver<ConsensusCommand>,
+    worker_sender: MassaSender<ConsensusCommand>,
+) {
+    // the shard stops once all the controllers are dropped
+    while let Ok(command) = receiver.recv() {
+        let checked = match &command {
+            ConsensusCommand::RegisterBlockHeader(block_id, header, ..) => Some((
+                *block_id,
+                precheck_header(&config, selector.as_ref(), &mip_store, header),
+            )),
+            ConsensusCommand::RegisterBlock(block_id, _, storage, ..) => {
+                let header = storage
+                    .read_blocks()
+                    .get(block_id)
+                    .map(|block| block.content.header.clone());
+                // a block registered without its content is rejected by the worker
+                header.map(|header| {
+                    (
+                        *block_id,
+                        precheck_header(&config, selector.as_ref(), &mip_store, &header),
+                    )
+                })
+            }
+            ConsensusCommand::MarkInvalidBlock(..) => None,
+        };
+        // recorded before forwarding, so that the worker finds it when processing the registration
+        if let Some((block_id, precheck)) = checked {
+            prechecked_headers.lock().insert(block_id, precheck);
+        }
+        if let Err(err) = worker_sender.send(command) {
+            warn!("insertion shard could not forward a registration: {}", err);
+            break;
+        }
+    }
+}
+
+/// Start one insertion shard per thread, forwarding the registrations to `worker_sender`
+///
+/// # Returns:
+/// * the senders of the registrations of each thread, indexed by thread
+/// * the handles of the shard threads
+pub(crate) fn start_insertion_shards(
+    config: &ConsensusConfig,
+    selector: &dyn SelectorController,
+    mip_store: &MipStore,
+    prechecked_headers: &Arc<Mutex<PrecheckedHeaders>>,
+    worker_sender: &MassaSender<ConsensusCommand>,
+) -> (Vec<MassaSender<ConsensusCommand>>, Vec<JoinHandle<()>>) {
+    (0..config.thread_count)
+        .map(|thread| {
+            let (tx, rx) = MassaChannel::new(
+                format!("consensus_insertion_shard_{}", thread),
+                Some(config.channel_size),
+            );
+            let config = config.clone();
+            let selector = selector.clone_box();
+            let mip_store = mip_store.clone();
+            let prechecked_headers = prechecked_headers.clone();
+            let worker_sender = worker_sender.clone();
+            let handle = std::thread::Builder::new()
+                .name(format!("consensus insertion shard {}", thread))
+                .spawn(move || {
+                    run_shard(
+                        config,
+                        selector,
+                        mip_store,
+                        prechecked_headers,
+                        rx,
+                        worker_sender,
+                    )
+                })
+                .expect("Can't spawn consensus insertion shard thread.");
+            (tx, handle)
+        })
+        .unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn precheck(slot: Slot, creator: bool, endorsements: bool) -> HeaderPrecheck {
+        HeaderPrecheck {
+            slot,
+            creator,
+            endorsements,
+        }
+    }
+
+    #[test]
+    fn test_prechecked_headers_prune() {
+        let ids: Vec<BlockId> = (0..4u8)
+            .map(|i| BlockId::generate_from_hash(massa_hash::Hash::compute_from(&[i])))
+            .collect();
+        let mut prechecked = PrecheckedHeaders::default();
+        prechecked.insert(ids[0], precheck(Slot::new(3, 0), true, true));
+        prechecked.insert(ids[1], precheck(Slot::new(5, 0), true, false));
+        prechecked.insert(ids[2], precheck(Slot::new(3, 1), false, true));
+        // no passed check: not recorded
+        prechecked.insert(ids[3], precheck(Slot::new(9, 1), false, false));
+        assert_eq!(prechecked.get(&ids[3]), None);
+
+        prechecked.prune(&[(ids[0], 3), (ids[0], 2)]);
+        assert_eq!(prechecked.get(&ids[0]), None);
+        assert_eq!(
+            prechecked.get(&ids[1]),
+            Some(precheck(Slot::new(5, 0), true, false))
+        );
+        assert_eq!(
+            prechecked.get(&ids[2]),
+            Some(precheck(Slot::new(3, 1), false, true))
+        );
+    }
+}
@@ -16,6 +16,8 @@
 //!
 //! The consensus worker is launched and initializes a shared state that contains caches, counters and other info.
 //! The consensus worker wakes up at each slot or when it receives a command.
+//! When the insertion shards are enabled, the registrations first go through the shard of the thread of their block,
+//! which checks the draws and header extensions of the block in parallel with the other threads (see `insertion_shards`).
 //! If a command is received, the worker executes it and goes back to sleep.
 //!  * When an incoming block header is fed to the module, it registers it and asks the Protocol module for the dependencies of the block (including the full block itself) if needed.
 //!  * When an incoming full block is fed to the module, it registers it and asks the Protocol module for its dependencies if needed.
@@ -25,13 +27,30 @@
 //! If a queued block reaches the slot time at which it should be processed, the worker wakes up to check it and trigger, if necessary, the consensus algorithm.
 //! It then prunes the block graph and the caches.
 
+mod bootstrap_sessions;
 mod commands;
 mod controller;
+mod dedup;
+mod external_candidates;
+mod insertion_shards;
 mod manager;
+mod query_quota;
 mod state;
 mod worker;
 
 pub use worker::start_consensus_worker;
 
+/// In-process multi-node simulation
+#[cfg(feature = "simulation")]
+pub mod simulation;
+
+/// Forced block status transitions, for the tests of the modules driven by consensus
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+/// Harness of the fuzz targets
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+
 #[cfg(test)]
 pub mod tests;
@@ -7,6 +7,8 @@ use crate::commands::ConsensusCommand;
 
 pub struct ConsensusManagerImpl {
     pub consensus_thread: Option<(MassaSender<ConsensusCommand>, JoinHandle<()>)>,
+    /// threads of the insertion shards, which hold senders to the consensus thread
+    pub shard_threads: Vec<JoinHandle<()>>,
 }
 
 impl ConsensusManager for ConsensusManagerImpl {
@@ -15,6 +17,12 @@ impl ConsensusManager for ConsensusManagerImpl {
         // join the consensus thread
         if let Some((tx, join_handle)) = self.consensus_thread.take() {
             drop(tx);
+            // the shards stop with the controllers, then release the consensus thread
+            for shard_thread in self.shard_threads.drain(..) {
+                shard_thread
+                    .join()
+                    .expect("consensus insertion shard panicked on try to join");
+            }
             join_handle
                 .join()
                 .expect("consensus thread panicked on try to join");
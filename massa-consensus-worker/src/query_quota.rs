@@ -0,0 +1,108 @@
+use std::collections::{HashMap, VecDeque};
+
+use massa_consensus_exports::{
+    error::ConsensusError,
+    query_quota::{HeavyQuery, QueryCaller},
+};
+use massa_time::MassaTime;
+
+/// Time over which the queries of a caller are counted against its budget
+const QUOTA_WINDOW: MassaTime = MassaTime::from_millis(60_000);
+
+/// Heavy queries run by each caller during the last minute, used by the controller
+/// to refuse the queries of a caller that exhausted its budget.
+pub(crate) struct QueryQuotas {
+    /// number of graph exports allowed per caller and per minute, 0 for no limit
+    graph_export_budget: u32,
+    /// number of bootstrap parts allowed per caller and per minute, 0 for no limit
+    bootstrap_part_budget: u32,
+    /// times of the queries of each caller during the last minute, in query order
+    calls: HashMap<(QueryCaller, HeavyQuery), VecDeque<MassaTime>>,
+    /// last time the callers without query during the last minute were forgotten
+    last_prune: MassaTime,
+}
+
+impl QueryQuotas {
+    pub(crate) fn new(graph_export_budget: u32, bootstrap_part_budget: u32) -> Self {
+        QueryQuotas {
+            graph_export_budget,
+            bootstrap_part_budget,
+            calls: Default::default(),
+            last_prune: MassaTime::from_millis(0),
+        }
+    }
+
+    /// Count a query of `caller` run at `now` against its budget
+    ///
+    /// # Returns
+    /// `QuotaExceeded` if the caller already ran its budget of such queries during the last minute,
+    /// in which case the query must not be run
+    pub(crate) fn consume(
+        &mut self,
+        caller: &QueryCaller,
+        query: HeavyQuery,
+        now: MassaTime,
+    ) -> Result<(), ConsensusError> {
+        let budget = match query {
+            HeavyQuery::GraphExport => self.graph_export_budget,
+            HeavyQuery::BootstrapPart => self.bootstrap_part_budget,
+        };
+        if budget == 0 {
+            return Ok(());
+        }
+        let start = now.saturating_sub(QUOTA_WINDOW);
+        if now.saturating_sub(self.last_prune) >= QUOTA_WINDOW {
+            self.calls
+                .retain(|_, times| times.back().map_or(false, |time| *time >= start));
+            self.last_prune = now;
+        }
+        let times = self.calls.entry((caller.clone(), query)).or_default();
+        while times.front().map_or(false, |time| *time < start) {
+            times.pop_front();
+        }
+        if times.len() >= budget as usize {
+            return Err(ConsensusError::QuotaExceeded {
+                caller: caller.clone(),
+                query,
+                budget,
+            });
+        }
+        times.push_back(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_quotas() {
+        let mut quotas = QueryQuotas::new(2, 0);
+        let alice = QueryCaller::new("alice");
+        let bob = QueryCaller::new("bob");
+        let now = MassaTime::from_millis(100_000);
+
+        assert!(quotas.consume(&alice, HeavyQuery::GraphExport, now).is_ok());
+        assert!(quotas.consume(&alice, HeavyQuery::GraphExport, now).is_ok());
+        assert!(matches!(
+            quotas.consume(&alice, HeavyQuery::GraphExport, now),
+            Err(ConsensusError::QuotaExceeded { budget: 2, .. })
+        ));
+
+        // the budgets are per caller and per query, 0 for no limit
+        assert!(quotas.consume(&bob, HeavyQuery::GraphExport, now).is_ok());
+        for _ in 0..10 {
+            assert!(quotas
+                .consume(&alice, HeavyQuery::BootstrapPart, now)
+                .is_ok());
+        }
+
+        // the queries are forgotten after a minute
+        let later = now.saturating_add(MassaTime::from_millis(60_001));
+        assert!(quotas
+            .consume(&alice, HeavyQuery::GraphExport, later)
+            .is_ok());
+        assert!(!quotas.calls.contains_key(&(bob, HeavyQuery::GraphExport)));
+    }
+}
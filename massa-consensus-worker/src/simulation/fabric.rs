@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use massa_time::MassaTime;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Parameters of the simulated network
+#[derive(Debug, Clone)]
+pub struct FabricConfig {
+    /// minimum delay before a message is delivered
+    pub latency: MassaTime,
+    /// maximum random delay added to `latency`
+    pub jitter: MassaTime,
+    /// probability for a message to be lost, between 0 and 1
+    pub loss_rate: f64,
+    /// seed of the random generator: runs with the same seed deliver the same messages in the same order
+    pub seed: u64,
+}
+
+impl Default for FabricConfig {
+    fn default() -> Self {
+        Self {
+            latency: MassaTime::from_millis(50),
+            jitter: MassaTime::from_millis(50),
+            loss_rate: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+/// In-memory network between simulated nodes, with latency, packet loss and partitions.
+/// Time is virtual: messages are delivered when the caller advances the clock past their delivery time.
+pub struct MessageFabric<M> {
+    config: FabricConfig,
+    rng: StdRng,
+    /// group of each node, nodes only reach the nodes of their group
+    groups: Vec<usize>,
+    /// messages in flight, indexed by `(delivery time, sequence number)` to keep the delivery order deterministic
+    in_flight: BTreeMap<(MassaTime, u64), (usize, M)>,
+    next_seq: u64,
+    lost: u64,
+}
+
+impl<M> MessageFabric<M> {
+    /// Creates a fabric connecting `node_count` nodes with each other
+    pub fn new(node_count: usize, config: FabricConfig) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(config.seed),
+            config,
+            groups: vec![0; node_count],
+            in_flight: Default::default(),
+            next_seq: 0,
+            lost: 0,
+        }
+    }
+
+    /// Change the probability for a message to be lost
+    pub fn set_loss_rate(&mut self, loss_rate: f64) {
+        self.config.loss_rate = loss_rate;
+    }
+
+    /// Split the network: nodes only reach the nodes of their group, unlisted nodes are isolated.
+    /// Messages already in flight are still delivered.
+    pub fn partition(&mut self, groups: &[Vec<usize>]) {
+        let isolated = groups.len();
+        for (node, group) in self.groups.iter_mut().enumerate() {
+            *group = isolated + node;
+        }
+        for (index, group) in groups.iter().enumerate() {
+            for node in group {
+                self.groups[*node] = index;
+            }
+        }
+    }
+
+    /// Reconnect all the nodes
+    pub fn heal(&mut self) {
+        self.groups.iter_mut().for_each(|group| *group = 0);
+    }
+
+    /// Whether a message sent by `from` can reach `to`
+    pub fn can_reach(&self, from: usize, to: usize) -> bool {
+        self.groups[from] == self.groups[to]
+    }
+
+    /// Send a message at time `now`.
+    ///
+    /// # Returns
+    /// false if the message was lost or the destination is unreachable
+    pub fn send(&mut self, from: usize, to: usize, message: M, now: MassaTime) -> bool {
+        if !self.can_reach(from, to) || self.rng.gen_bool(self.config.loss_rate) {
+            self.lost += 1;
+            return false;
+        }
+        let jitter = self.rng.gen_range(0..=self.config.jitter.as_millis());
+        let deliver_at = now
+            .saturating_add(self.config.latency)
+            .saturating_add(MassaTime::from_millis(jitter));
+        self.in_flight
+            .insert((deliver_at, self.next_seq), (to, message));
+        self.next_seq += 1;
+        true
+    }
+
+    /// Take the messages due at `now`, in delivery order
+    ///
+    /// # Returns
+    /// The destination node and the message
+    pub fn deliver_until(&mut self, now: MassaTime) -> Vec<(usize, M)> {
+        let pending = self
+            .in_flight
+            .split_off(&(now.saturating_add(MassaTime::from_millis(1)), 0));
+        std::mem::replace(&mut self.in_flight, pending)
+            .into_values()
+            .collect()
+    }
+
+    /// Number of messages in flight
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Number of messages lost so far, including the ones sent to unreachable nodes
+    pub fn lost_count(&self) -> u64 {
+        self.lost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fabric_delivery_and_partitions() {
+        let config = FabricConfig {
+            latency: MassaTime::from_millis(10),
+            jitter: MassaTime::from_millis(0),
+            loss_rate: 0.0,
+            seed: 1,
+        };
+        let mut fabric = MessageFabric::new(3, config);
+        let now = MassaTime::from_millis(0);
+        assert!(fabric.send(0, 1, "a", now));
+        assert!(fabric.send(0, 2, "b", now));
+        assert!(fabric.deliver_until(MassaTime::from_millis(9)).is_empty());
+        assert_eq!(
+            fabric.deliver_until(MassaTime::from_millis(10)),
+            vec![(1, "a"), (2, "b")]
+        );
+
+        fabric.partition(&[vec![0, 1]]);
+        assert!(fabric.send(0, 1, "c", now));
+        assert!(!fabric.send(0, 2, "d", now));
+        assert!(!fabric.send(2, 1, "e", now));
+        assert_eq!(fabric.lost_count(), 2);
+        fabric.heal();
+        assert!(fabric.send(2, 1, "f", now));
+        assert_eq!(fabric.in_flight_count(), 2);
+    }
+
+    #[test]
+    fn test_fabric_is_deterministic() {
+        let run = || {
+            let mut fabric = MessageFabric::new(
+                2,
+                FabricConfig {
+                    loss_rate: 0.5,
+                    seed: 42,
+                    ..Default::default()
+                },
+            );
+            for i in 0..100 {
+                fabric.send(0, 1, i, MassaTime::from_millis(0));
+            }
+            fabric.deliver_until(MassaTime::from_millis(1000))
+        };
+        let delivered = run();
+        assert!(!delivered.is_empty() && delivered.len() < 100);
+        assert_eq!(delivered, run());
+    }
+}
@@ -0,0 +1,434 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use massa_channel::{event_bus::MassaEventBus, MassaChannel};
+use massa_consensus_exports::{
+    block_provenance::BlockOrigin, ConsensusBroadcasts, ConsensusChannels, ConsensusConfig,
+    ConsensusController, ConsensusManager,
+};
+use massa_execution_exports::MockExecutionController;
+use massa_hash::Hash;
+use massa_metrics::MassaMetrics;
+use massa_models::{
+    address::Address,
+    block::{Block, BlockGraphStatus, BlockSerializer, SecureShareBlock},
+    block_header::{BlockHeader, BlockHeaderSerializer},
+    block_id::BlockId,
+    config::MIP_STORE_STATS_BLOCK_CONSIDERED,
+    prehash::{PreHashMap, PreHashSet},
+    secure_share::SecureShareContent,
+    slot::Slot,
+};
+use massa_pool_exports::MockPoolController;
+use massa_pos_exports::{MockSelectorController, Selection};
+use massa_protocol_exports::MockProtocolController;
+use massa_signature::KeyPair;
+use massa_storage::Storage;
+use massa_time::MassaTime;
+use massa_versioning::versioning::{MipStatsConfig, MipStore};
+use num::rational::Ratio;
+use parking_lot::Mutex;
+use tracing::warn;
+
+use super::fabric::{FabricConfig, MessageFabric};
+use crate::start_consensus_worker;
+
+/// Maximum time to wait for a node to process the blocks it was given
+const PROCESSING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Parameters of a simulation
+#[derive(Clone)]
+pub struct SimulationConfig {
+    /// number of simulated nodes, the producer of each slot is picked among them in turn
+    pub node_count: usize,
+    /// consensus configuration shared by all the nodes, its `genesis_timestamp` is overridden
+    pub consensus: ConsensusConfig,
+    /// network parameters
+    pub fabric: FabricConfig,
+    /// maximum number of periods that can be simulated
+    pub max_periods: u64,
+}
+
+/// A consensus worker and the mocks standing for the other modules of its node
+struct SimulatedNode {
+    controller: Box<dyn ConsensusController>,
+    manager: Box<dyn ConsensusManager>,
+    storage: Storage,
+    /// blocks that consensus asked protocol for
+    wishlist: Arc<Mutex<PreHashSet<BlockId>>>,
+    /// final blocks seen so far, kept after consensus prunes them
+    final_blocks: BTreeMap<Slot, BlockId>,
+}
+
+/// Deterministic in-process network of consensus workers.
+///
+/// Each node runs a real consensus worker whose other modules are mocked. Blocks are produced by the
+/// harness on behalf of the slot producers and exchanged through a [`MessageFabric`] driven by a virtual clock,
+/// so that fork choice and finality can be checked under packet loss and partitions.
+///
+/// The genesis timestamp is moved back far enough for every simulated slot to be in the past
+/// of the real clock: the workers process the blocks as soon as they receive them.
+pub struct SimulationHarness {
+    nodes: Vec<SimulatedNode>,
+    keys: Vec<KeyPair>,
+    fabric: MessageFabric<SecureShareBlock>,
+    /// produced blocks with the index of their producer
+    blocks: PreHashMap<BlockId, (usize, SecureShareBlock)>,
+    thread_count: u8,
+    chain_id: u64,
+    /// duration of a slot
+    slot_duration: MassaTime,
+    /// next slot to be produced
+    next_slot: Slot,
+    /// virtual clock
+    now: MassaTime,
+}
+
+impl SimulationHarness {
+    /// Start the nodes of a simulation
+    pub fn new(config: SimulationConfig) -> Self {
+        let mut consensus = config.consensus;
+        consensus.genesis_timestamp = MassaTime::now().saturating_sub(
+            consensus
+                .t0
+                .saturating_mul(config.max_periods.saturating_add(1)),
+        );
+        let slot_duration = consensus
+            .t0
+            .checked_div_u64(consensus.thread_count as u64)
+            .expect("invalid thread count");
+
+        // derive the keys from the seed so that the block ids are the same from one run to another
+        let keys: Vec<KeyPair> = (0..config.node_count)
+            .map(|index| {
+                let mut bytes = vec![0u8];
+                bytes.extend(
+                    Hash::compute_from(
+                        format!("simulation node {} {}", config.fabric.seed, index).as_bytes(),
+                    )
+                    .to_bytes(),
+                );
+                KeyPair::from_bytes(&bytes).expect("could not derive a node key")
+            })
+            .collect();
+        let producers: Vec<Address> = keys
+            .iter()
+            .map(|key| Address::from_public_key(&key.get_public_key()))
+            .collect();
+        let thread_count = consensus.thread_count;
+        let chain_id = consensus.chain_id;
+
+        let nodes = (0..config.node_count)
+            .map(|_| start_node(consensus.clone(), producers.clone()))
+            .collect();
+        Self {
+            nodes,
+            keys,
+            fabric: MessageFabric::new(config.node_count, config.fabric),
+            blocks: Default::default(),
+            thread_count,
+            chain_id,
+            slot_duration,
+            next_slot: Slot::new(consensus.last_start_period + 1, 0),
+            now: MassaTime::from_millis(0),
+        }
+    }
+
+    /// Controller of a node
+    pub fn node(&self, index: usize) -> &dyn ConsensusController {
+        self.nodes[index].controller.as_ref()
+    }
+
+    /// Current time of the virtual clock, relative to the first simulated slot
+    pub fn now(&self) -> MassaTime {
+        self.now
+    }
+
+    /// Next slot to be produced
+    pub fn next_slot(&self) -> Slot {
+        self.next_slot
+    }
+
+    /// The network between the nodes
+    pub fn fabric(&mut self) -> &mut MessageFabric<SecureShareBlock> {
+        &mut self.fabric
+    }
+
+    /// Produce the block of the next slot, advance the clock by one slot
+    /// and deliver the messages that are due
+    pub fn step(&mut self) {
+        let slot = self.next_slot;
+        let producer = producer_index(slot, self.thread_count, self.nodes.len());
+
+        // the producer builds on its own view of the graph
+        let parents = self.nodes[producer]
+            .controller
+            .get_best_parents_snapshot()
+            .iter()
+            .map(|(id, _)| *id)
+            .collect();
+        let block = create_block(slot, parents, &self.keys[producer], self.chain_id);
+        self.blocks.insert(block.id, (producer, block.clone()));
+        self.register_blocks(producer, vec![block.clone()], BlockOrigin::Local);
+        for node in (0..self.nodes.len()).filter(|node| *node != producer) {
+            self.fabric.send(producer, node, block.clone(), self.now);
+        }
+
+        self.next_slot = slot
+            .get_next_slot(self.thread_count)
+            .expect("slot overflow");
+        self.now = self.now.saturating_add(self.slot_duration);
+
+        // answer the block requests from the producers, lost answers are retried at the next step
+        self.serve_wishlists();
+
+        let mut deliveries: BTreeMap<usize, Vec<SecureShareBlock>> = BTreeMap::new();
+        for (node, block) in self.fabric.deliver_until(self.now) {
+            deliveries.entry(node).or_default().push(block);
+        }
+        for (node, blocks) in deliveries {
+            self.register_blocks(node, blocks, BlockOrigin::Unknown);
+        }
+        self.record_final_blocks();
+    }
+
+    /// Run `count` steps
+    pub fn run_slots(&mut self, count: u64) {
+        for _ in 0..count {
+            self.step();
+        }
+    }
+
+    /// Final blocks seen by a node since the start of the simulation, by slot
+    pub fn final_blocks(&self, index: usize) -> &BTreeMap<Slot, BlockId> {
+        &self.nodes[index].final_blocks
+    }
+
+    /// Check that no two nodes finalized different blocks at the same slot
+    pub fn check_finality_agreement(&self) -> Result<(), String> {
+        for (index, node) in self.nodes.iter().enumerate() {
+            for other in self.nodes.iter().skip(index + 1) {
+                for (slot, block_id) in &node.final_blocks {
+                    if let Some(other_block_id) = other.final_blocks.get(slot) {
+                        if other_block_id != block_id {
+                            return Err(format!(
+                                "conflicting final blocks at slot {}: {} and {}",
+                                slot, block_id, other_block_id
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Send the wished blocks to the nodes that asked for them
+    fn serve_wishlists(&mut self) {
+        for node in 0..self.nodes.len() {
+            let mut wishlist: Vec<BlockId> =
+                self.nodes[node].wishlist.lock().iter().copied().collect();
+            wishlist.sort_unstable();
+            for block_id in wishlist {
+                if let Some((producer, block)) = self.blocks.get(&block_id) {
+                    self.fabric.send(*producer, node, block.clone(), self.now);
+                }
+            }
+        }
+    }
+
+    /// Give blocks to a node and wait for it to process them
+    fn register_blocks(&mut self, node: usize, blocks: Vec<SecureShareBlock>, origin: BlockOrigin) {
+        let node = &self.nodes[node];
+        let ids: Vec<BlockId> = blocks.iter().map(|block| block.id).collect();
+        for block in blocks {
+            let mut storage = node.storage.clone_without_refs();
+            storage.store_block(block.clone());
+            node.controller.register_block(
+                block.id,
+                block.content.header.content.slot,
+                storage,
+                origin,
+            );
+        }
+        let start = Instant::now();
+        while node
+            .controller
+            .get_block_statuses(&ids)
+            .contains(&BlockGraphStatus::NotFound)
+        {
+            if start.elapsed() > PROCESSING_TIMEOUT {
+                warn!("simulated node did not process blocks {:?} in time", ids);
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn record_final_blocks(&mut self) {
+        for node in self.nodes.iter_mut() {
+            let export = node
+                .controller
+                .get_block_graph_status(None, None)
+                .expect("could not export the block graph");
+            node.final_blocks.extend(
+                export
+                    .active_blocks
+                    .iter()
+                    .filter(|(_, block)| block.is_final)
+                    .map(|(id, block)| (block.header.content.slot, *id)),
+            );
+        }
+    }
+}
+
+impl Drop for SimulationHarness {
+    fn drop(&mut self) {
+        for node in self.nodes.iter_mut() {
+            node.manager.stop();
+        }
+    }
+}
+
+/// Index of the node producing the block of a slot
+fn producer_index(slot: Slot, thread_count: u8, node_count: usize) -> usize {
+    ((slot.period * thread_count as u64 + slot.thread as u64) % node_count as u64) as usize
+}
+
+/// Start a consensus worker with mocked execution, pool, selector and protocol
+fn start_node(config: ConsensusConfig, producers: Vec<Address>) -> SimulatedNode {
+    let thread_count = config.thread_count;
+    let endorsement_count = config.endorsement_count as usize;
+    let node_count = producers.len();
+    let storage = Storage::create_root();
+    let wishlist: Arc<Mutex<PreHashSet<BlockId>>> = Default::default();
+
+    let mut execution_controller = Box::new(MockExecutionController::new());
+    execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    let mut pool_controller = Box::new(MockPoolController::new());
+    pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let mut selector_controller = Box::new(MockSelectorController::new());
+    let selection_producers = producers.clone();
+    selector_controller
+        .expect_get_producer()
+        .returning(move |slot| Ok(producers[producer_index(slot, thread_count, node_count)]));
+    selector_controller
+        .expect_get_selection()
+        .returning(move |slot| {
+            let producer = selection_producers[producer_index(slot, thread_count, node_count)];
+            Ok(Selection {
+                producer,
+                endorsements: vec![producer; endorsement_count],
+            })
+        });
+    let mut protocol_controller = Box::new(MockProtocolController::new());
+    protocol_controller
+        .expect_integrated_block()
+        .returning(|_, _| Ok(()));
+    protocol_controller
+        .expect_notify_block_attack()
+        .returning(|_| Ok(()));
+    let protocol_wishlist = wishlist.clone();
+    protocol_controller
+        .expect_send_wishlist_delta()
+        .returning(move |new, remove| {
+            let mut wishlist = protocol_wishlist.lock();
+            wishlist.extend(new.into_keys());
+            for block_id in remove {
+                wishlist.remove(&block_id);
+            }
+            Ok(())
+        });
+
+    let (controller_event_tx, _) = MassaChannel::new(String::from("consensus_event"), Some(10));
+    let (block_sender, _) = tokio::sync::broadcast::channel(10);
+    let (block_header_sender, _) = tokio::sync::broadcast::channel(10);
+    let (filled_block_sender, _) = tokio::sync::broadcast::channel(10);
+    let (controller, manager) = start_consensus_worker(
+        config,
+        ConsensusChannels {
+            broadcasts: ConsensusBroadcasts {
+                block_sender,
+                block_header_sender,
+                filled_block_sender,
+            },
+            controller_event_tx,
+            execution_controller,
+            protocol_controller,
+            pool_controller,
+            selector_controller,
+            // the simulated nodes all run network version 0
+            mip_store: MipStore::try_from((
+                [],
+                MipStatsConfig {
+                    block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+                    warn_announced_version_ratio: Ratio::new_raw(30, 100),
+                },
+            ))
+            .expect("could not create the MIP store"),
+            db: None,
+            event_bus: MassaEventBus::new("simulation_node_event".to_string()),
+        },
+        None,
+        storage.clone(),
+        MassaMetrics::new(
+            false,
+            "0.0.0.0:9898".parse().unwrap(),
+            thread_count,
+            Duration::from_secs(1),
+        )
+        .0,
+    );
+    SimulatedNode {
+        controller,
+        manager,
+        storage,
+        wishlist,
+        final_blocks: Default::default(),
+    }
+}
+
+/// Create a block without operations nor endorsements
+fn create_block(
+    slot: Slot,
+    parents: Vec<BlockId>,
+    creator: &KeyPair,
+    chain_id: u64,
+) -> SecureShareBlock {
+    let header = BlockHeader::new_verifiable(
+        BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            denunciations: vec![],
+            extensions: Vec::new(),
+            slot,
+            parents,
+            operation_merkle_root: Hash::compute_from(&[]),
+            endorsements: Vec::new(),
+        },
+        BlockHeaderSerializer::new(),
+        creator,
+        chain_id,
+    )
+    .expect("could not create a simulated block header");
+    Block::new_verifiable(
+        Block {
+            header,
+            operations: Default::default(),
+        },
+        BlockSerializer::new(),
+        creator,
+        chain_id,
+    )
+    .expect("could not create a simulated block")
+}
@@ -0,0 +1,13 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Deterministic multi-node consensus simulation.
+//!
+//! Several consensus workers run in the same process and exchange their blocks through an in-memory
+//! network with configurable latency, packet loss and partitions, driven by a virtual clock.
+//! It is meant to check fork choice and finality properties in CI, without sockets.
+
+mod fabric;
+mod harness;
+
+pub use fabric::{FabricConfig, MessageFabric};
+pub use harness::{SimulationConfig, SimulationHarness};
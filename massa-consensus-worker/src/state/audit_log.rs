@@ -0,0 +1,15 @@
+use massa_consensus_exports::audit_log::AuditEvent;
+use tracing::warn;
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Record a consensus decision in the audit log if it is enabled, failures are only logged
+    pub(crate) fn audit(&mut self, event: AuditEvent) {
+        if let Some(audit_log) = self.audit_log.as_ref() {
+            if let Err(err) = audit_log.lock().append(event) {
+                warn!("could not append to the consensus audit log: {}", err);
+            }
+        }
+    }
+}
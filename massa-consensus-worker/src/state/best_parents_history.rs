@@ -0,0 +1,31 @@
+use massa_models::{block_id::BlockId, slot::Slot};
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Record the current best parents in the best parents history, at the slot that just ticked.
+    ///
+    /// The oldest entries are dropped beyond `best_parents_history_length` entries.
+    pub fn record_best_parents(&mut self, slot: Slot) {
+        if matches!(self.best_parents_history.back(), Some((last_slot, _)) if *last_slot >= slot) {
+            self.best_parents_history.pop_back();
+        }
+        self.best_parents_history
+            .push_back((slot, self.best_parents.clone()));
+        while self.best_parents_history.len() > self.config.best_parents_history_length {
+            self.best_parents_history.pop_front();
+        }
+    }
+
+    /// Get the best parents as they were after the tick of a past slot.
+    /// If that tick was missed, the best parents of the latest earlier tick are returned.
+    ///
+    /// Returns `None` if the slot is older than the oldest recorded tick
+    pub fn get_best_parents_at(&self, slot: &Slot) -> Option<Vec<(BlockId, u64)>> {
+        self.best_parents_history
+            .iter()
+            .rev()
+            .find(|(tick_slot, _)| tick_slot <= slot)
+            .map(|(_, best_parents)| best_parents.clone())
+    }
+}
@@ -0,0 +1,93 @@
+//! Origin and first-seen time of the blocks, and the propagation latency of the peers.
+//!
+//! The provenance of a block is recorded at its first registration, header or full block,
+//! and forgotten once the block leaves the graph. The latency of a peer is the delay between
+//! the slot timestamp of the blocks first received from it and their reception,
+//! over the blocks still in the graph.
+
+use std::collections::BTreeMap;
+
+use massa_consensus_exports::{
+    block_provenance::{BlockOrigin, BlockProvenance, PeerPropagationStats},
+    block_status::BlockStatus,
+};
+use massa_models::{block_id::BlockId, slot::Slot, timeslots::get_block_slot_timestamp};
+use massa_protocol_exports::PeerId;
+use massa_time::MassaTime;
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Record the provenance of a block, if it is its first registration. Genesis blocks are ignored.
+    ///
+    /// # Arguments
+    /// * `block_id`: the registered block
+    /// * `provenance`: origin and time of the registration
+    pub(crate) fn note_block_provenance(&mut self, block_id: BlockId, provenance: BlockProvenance) {
+        if self.genesis_hashes.contains(&block_id) {
+            return;
+        }
+        self.block_provenance.entry(block_id).or_insert(provenance);
+    }
+
+    /// Forget the provenance of the blocks that left the graph
+    pub(crate) fn prune_block_provenance(&mut self) {
+        let blocks_state = &self.blocks_state;
+        let quarantined_headers = &self.quarantined_headers;
+        self.block_provenance.retain(|block_id, _| {
+            blocks_state.get(block_id).is_some() || quarantined_headers.contains_key(block_id)
+        });
+    }
+
+    /// Get the propagation latency of each peer blocks were first received from, sorted by peer id
+    pub fn get_peer_propagation_stats(&self) -> Vec<PeerPropagationStats> {
+        // (block count, total latency in milliseconds, max latency) of each peer
+        let mut latencies: BTreeMap<PeerId, (u64, u64, MassaTime)> = BTreeMap::new();
+        for (block_id, provenance) in self.block_provenance.iter() {
+            let BlockOrigin::Peer(peer_id) = provenance.origin else {
+                continue;
+            };
+            let Some(slot) = self.blocks_state.get(block_id).and_then(status_slot) else {
+                continue;
+            };
+            let Ok(slot_timestamp) = get_block_slot_timestamp(
+                self.config.thread_count,
+                self.config.t0,
+                self.config.genesis_timestamp,
+                slot,
+            ) else {
+                continue;
+            };
+            let latency = provenance.first_seen.saturating_sub(slot_timestamp);
+            let (count, total, max) =
+                latencies
+                    .entry(peer_id)
+                    .or_insert((0, 0, MassaTime::from_millis(0)));
+            *count += 1;
+            *total = total.saturating_add(latency.as_millis());
+            *max = std::cmp::max(*max, latency);
+        }
+        latencies
+            .into_iter()
+            .map(|(peer_id, (count, total, max))| PeerPropagationStats {
+                peer_id,
+                block_count: count,
+                mean_latency: MassaTime::from_millis(total / count),
+                max_latency: max,
+            })
+            .collect()
+    }
+}
+
+/// Slot of a block, whatever its status
+fn status_slot(status: &BlockStatus) -> Option<Slot> {
+    match status {
+        BlockStatus::Incoming(header_or_block)
+        | BlockStatus::WaitingForSlot(header_or_block)
+        | BlockStatus::WaitingForDependencies {
+            header_or_block, ..
+        } => Some(header_or_block.get_slot()),
+        BlockStatus::Active { a_block, .. } => Some(a_block.slot),
+        BlockStatus::Discarded { slot, .. } => Some(*slot),
+    }
+}
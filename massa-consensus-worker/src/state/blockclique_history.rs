@@ -0,0 +1,40 @@
+use massa_models::{block_id::BlockId, prehash::PreHashSet, slot::Slot, timeslots};
+use massa_time::MassaTime;
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Record the current blockclique in the blockclique history, at the current slot.
+    ///
+    /// Several changes during the same slot only keep the latest composition.
+    /// The oldest entries are dropped beyond `blockclique_history_length` entries.
+    pub fn record_blockclique_change(&mut self, blockclique: PreHashSet<BlockId>) {
+        let slot = timeslots::get_latest_block_slot_at_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            MassaTime::now(),
+        )
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| Slot::new(self.config.last_start_period, 0));
+        if matches!(self.blockclique_history.back(), Some((last_slot, _)) if *last_slot >= slot) {
+            self.blockclique_history.pop_back();
+        }
+        self.blockclique_history.push_back((slot, blockclique));
+        while self.blockclique_history.len() > self.config.blockclique_history_length {
+            self.blockclique_history.pop_front();
+        }
+    }
+
+    /// Get the non-final blocks that were in the blockclique at a past slot
+    ///
+    /// Returns `None` if the slot is older than the oldest recorded blockclique change
+    pub fn get_blockclique_at(&self, slot: &Slot) -> Option<PreHashSet<BlockId>> {
+        self.blockclique_history
+            .iter()
+            .rev()
+            .find(|(change_slot, _)| change_slot <= slot)
+            .map(|(_, blockclique)| blockclique.clone())
+    }
+}
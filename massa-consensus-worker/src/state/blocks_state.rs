@@ -1,7 +1,10 @@
 use core::panic;
 
-use massa_consensus_exports::block_status::{BlockStatus, BlockStatusId};
+use massa_consensus_exports::block_status::{
+    BlockCountsByStatus, BlockStatus, BlockStatusId, DiscardReason,
+};
 use massa_models::{
+    active_block::ActiveBlock,
     block_id::BlockId,
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
@@ -23,6 +26,10 @@ pub struct BlocksState {
     discarded_index: PreHashSet<BlockId>,
     /// ids of active blocks
     active_index: PreHashSet<BlockId>,
+    /// Incremented each time a status may have changed, to detect the changes since a snapshot
+    generation: u64,
+    /// Number of blocks in each status, updated at each transition
+    counts: BlockCountsByStatus,
 }
 
 impl BlocksState {
@@ -36,6 +43,8 @@ impl BlocksState {
             waiting_for_dependencies_index: PreHashSet::default(),
             discarded_index: PreHashSet::default(),
             active_index: PreHashSet::default(),
+            generation: 0,
+            counts: BlockCountsByStatus::default(),
         }
     }
 
@@ -46,9 +55,38 @@ impl BlocksState {
 
     /// Get a mutable reference on a `BlockStatus` from a `BlockId`
     pub fn get_mut(&mut self, block_id: &BlockId) -> Option<&mut BlockStatus> {
+        self.generation = self.generation.wrapping_add(1);
         self.block_statuses.get_mut(block_id)
     }
 
+    /// Mark an active block as final
+    ///
+    /// Returns the block, or `None` if it is not active
+    pub fn mark_final(&mut self, block_id: &BlockId) -> Option<&mut ActiveBlock> {
+        self.generation = self.generation.wrapping_add(1);
+        match self.block_statuses.get_mut(block_id) {
+            Some(BlockStatus::Active { a_block, .. }) => {
+                if !a_block.is_final {
+                    a_block.is_final = true;
+                    self.counts.active_non_final = self.counts.active_non_final.saturating_sub(1);
+                    self.counts.active_final += 1;
+                }
+                Some(a_block.as_mut())
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the number of blocks in each status
+    pub fn counts(&self) -> &BlockCountsByStatus {
+        &self.counts
+    }
+
+    /// Get the generation of the statuses, that changes each time a status may have changed
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     /// Get the sequence counter
     pub fn sequence_counter(&self) -> u64 {
         self.sequence_counter
@@ -79,6 +117,27 @@ impl BlocksState {
         &self.active_index
     }
 
+    // Internal function to get the count of the blocks that have the same status as `block_status`
+    fn status_count<'a>(
+        counts: &'a mut BlockCountsByStatus,
+        block_status: &BlockStatus,
+    ) -> &'a mut usize {
+        match block_status {
+            BlockStatus::Incoming(_) => &mut counts.incoming,
+            BlockStatus::WaitingForSlot(_) => &mut counts.waiting_for_slot,
+            BlockStatus::WaitingForDependencies { .. } => &mut counts.waiting_for_dependencies,
+            BlockStatus::Active { a_block, .. } if a_block.is_final => &mut counts.active_final,
+            BlockStatus::Active { .. } => &mut counts.active_non_final,
+            BlockStatus::Discarded { reason, .. } => match reason {
+                DiscardReason::Invalid(_) | DiscardReason::ParentTooOld { .. } => {
+                    &mut counts.discarded_invalid
+                }
+                DiscardReason::Stale => &mut counts.discarded_stale,
+                DiscardReason::Final => &mut counts.discarded_final,
+            },
+        }
+    }
+
     // Internal function to update the indexes
     fn update_indexes(
         &mut self,
@@ -164,6 +223,44 @@ impl BlocksState {
         }
     }
 
+    /// Rebuild the status indexes from the block statuses
+    ///
+    /// Returns the number of index entries that were added or removed
+    pub fn repair_indexes(&mut self) -> usize {
+        self.generation = self.generation.wrapping_add(1);
+        let previous_indexes = [
+            std::mem::take(&mut self.incoming_index),
+            std::mem::take(&mut self.waiting_for_slot_index),
+            std::mem::take(&mut self.waiting_for_dependencies_index),
+            std::mem::take(&mut self.discarded_index),
+            std::mem::take(&mut self.active_index),
+        ];
+        let block_status_ids: Vec<(BlockId, BlockStatusId)> = self
+            .block_statuses
+            .iter()
+            .map(|(block_id, block_status)| (*block_id, BlockStatusId::from(block_status)))
+            .collect();
+        for (block_id, block_status_id) in block_status_ids.iter() {
+            self.update_indexes(block_id, None, Some(block_status_id));
+        }
+        self.counts = BlockCountsByStatus::default();
+        for block_status in self.block_statuses.values() {
+            *Self::status_count(&mut self.counts, block_status) += 1;
+        }
+        let rebuilt_indexes = [
+            &self.incoming_index,
+            &self.waiting_for_slot_index,
+            &self.waiting_for_dependencies_index,
+            &self.discarded_index,
+            &self.active_index,
+        ];
+        previous_indexes
+            .iter()
+            .zip(rebuilt_indexes)
+            .map(|(previous, rebuilt)| previous.symmetric_difference(rebuilt).count())
+            .sum()
+    }
+
     /// Get an iterator over all the blocks stored in the `BlocksState`
     pub fn iter(&self) -> impl Iterator<Item = (&BlockId, &BlockStatus)> + '_ {
         self.block_statuses.iter()
@@ -187,10 +284,13 @@ impl BlocksState {
         block_id: &BlockId,
         callback: F,
     ) {
+        self.generation = self.generation.wrapping_add(1);
         match self.block_statuses.remove(block_id) {
             Some(block) => {
                 let old_state_id = BlockStatusId::from(&block);
                 self.update_indexes(block_id, Some(&old_state_id), None);
+                let old_count = Self::status_count(&mut self.counts, &block);
+                *old_count = old_count.saturating_sub(1);
                 let Some(mut new_state) = callback(Some(block), &mut self.block_statuses) else {
                     return;
                 };
@@ -308,6 +408,9 @@ impl BlocksState {
                         );
                     }
                 }
+                if let Some(new_state) = self.block_statuses.get(block_id) {
+                    *Self::status_count(&mut self.counts, new_state) += 1;
+                }
                 self.update_indexes(block_id, None, Some(&new_state_id));
             }
             None => {
@@ -320,6 +423,7 @@ impl BlocksState {
                             state, block_id
                         );
                     }
+                    *Self::status_count(&mut self.counts, &new_state) += 1;
                     self.block_statuses.insert(*block_id, new_state);
                     self.update_indexes(block_id, None, Some(&state));
                 }
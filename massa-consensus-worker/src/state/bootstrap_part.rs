@@ -0,0 +1,190 @@
+//! Bootstrap parts of the graph built in two phases, like the graph exports.
+//!
+//! The final blocks of a part are selected under the read lock of the consensus state, and the blocks stored in the
+//! shared storage are claimed instead of being copied. They are copied once the lock is released, so that the clients
+//! streaming the graph concurrently only hold the lock for the selection of their blocks.
+
+use massa_consensus_exports::{
+    block_status::{BlockStatus, StorageOrBlock},
+    bootstrapable_graph::BootstrapableGraph,
+    error::ConsensusError,
+    export_active_block::ExportActiveBlock,
+};
+use massa_models::{
+    block::SecureShareBlock,
+    block_id::BlockId,
+    prehash::{PreHashMap, PreHashSet},
+    slot::Slot,
+    streaming_step::StreamingStep,
+};
+use massa_storage::Storage;
+
+use super::ConsensusState;
+
+/// Final block of a bootstrap part whose content may still have to be read from the storage
+struct PendingBootstrapBlock {
+    /// one `(block id, period)` per thread
+    parents: Vec<(BlockId, u64)>,
+    /// the block, `None` if it is read from the claimed storage
+    block: Option<SecureShareBlock>,
+}
+
+/// Bootstrap part selected under the lock of the consensus state, completed by `finish` once the lock is released
+pub(crate) struct PendingBootstrapPart {
+    /// final blocks of the part
+    blocks: PreHashMap<BlockId, PendingBootstrapBlock>,
+    /// storage holding a reference to the blocks of the part that are stored
+    storage: Storage,
+    /// blocks sent previously that are not required anymore
+    outdated_ids: PreHashSet<BlockId>,
+    /// cursor after the part
+    cursor: StreamingStep<PreHashSet<BlockId>>,
+}
+
+impl PendingBootstrapPart {
+    /// Copy the blocks of the part, without holding the lock of the consensus state
+    ///
+    /// # Returns
+    /// The part of the graph, the outdated block ids and the cursor after the part
+    pub(crate) fn finish(
+        self,
+    ) -> (
+        BootstrapableGraph,
+        PreHashSet<BlockId>,
+        StreamingStep<PreHashSet<BlockId>>,
+    ) {
+        let PendingBootstrapPart {
+            blocks,
+            storage,
+            outdated_ids,
+            cursor,
+        } = self;
+        let stored_blocks = storage.read_blocks();
+        let final_blocks = blocks
+            .into_iter()
+            .map(|(block_id, pending)| ExportActiveBlock {
+                block: pending.block.unwrap_or_else(|| {
+                    stored_blocks
+                        .get(&block_id)
+                        .expect("claimed block absent from the storage")
+                        .clone()
+                }),
+                parents: pending.parents,
+                is_final: true,
+            })
+            .collect();
+        (BootstrapableGraph { final_blocks }, outdated_ids, cursor)
+    }
+}
+
+impl ConsensusState {
+    /// Select the final blocks of the next bootstrap part, see `PendingBootstrapPart`
+    ///
+    /// # Arguments
+    /// * `cursor`: streaming cursor containing the blocks already sent to the client
+    /// * `execution_cursor`: streaming cursor of the final state, the part only holds blocks up to its slot
+    /// * `concurrent_sessions`: number of clients streaming the graph at the same time, sharing `bootstrap_part_size`
+    pub(crate) fn extract_bootstrap_part(
+        &self,
+        cursor: StreamingStep<PreHashSet<BlockId>>,
+        execution_cursor: StreamingStep<Slot>,
+        concurrent_sessions: usize,
+    ) -> Result<PendingBootstrapPart, ConsensusError> {
+        if self.config.light_mode {
+            return Err(ConsensusError::NotAvailableInLightMode(
+                "bootstrap requires the operations of the final blocks".to_string(),
+            ));
+        }
+        if self.config.drop_executed_block_bodies {
+            return Err(ConsensusError::ExecutedBlockBodiesDropped(
+                "bootstrap requires the operations of the final blocks".to_string(),
+            ));
+        }
+        let max_block_count =
+            (self.config.bootstrap_part_size / concurrent_sessions.max(1) as u64).max(1);
+        let execution_slot = match execution_cursor {
+            StreamingStep::Ongoing(slot) | StreamingStep::Finished(Some(slot)) => Some(slot),
+            _ => None,
+        };
+        let required_blocks: PreHashSet<BlockId> = match execution_slot {
+            Some(slot) => self.list_required_active_blocks(Some(slot))?,
+            None => PreHashSet::default(),
+        };
+
+        let (current_ids, previous_ids, outdated_ids) = match cursor {
+            StreamingStep::Started => (
+                required_blocks,
+                PreHashSet::default(),
+                PreHashSet::default(),
+            ),
+            StreamingStep::Ongoing(ref cursor_ids) => (
+                // ids that are contained in required_blocks but not in the download cursor => current_ids
+                required_blocks.difference(cursor_ids).cloned().collect(),
+                // ids previously downloaded => previous_ids
+                cursor_ids.clone(),
+                // ids previously downloaded but not contained in required_blocks anymore => outdated_ids
+                cursor_ids.difference(&required_blocks).cloned().collect(),
+            ),
+            StreamingStep::Finished(_) => {
+                return Ok(PendingBootstrapPart {
+                    blocks: PreHashMap::default(),
+                    storage: self.storage.clone_without_refs(),
+                    outdated_ids: PreHashSet::default(),
+                    cursor,
+                })
+            }
+        };
+
+        let mut blocks = PreHashMap::default();
+        let mut stored_ids = PreHashSet::default();
+        for b_id in &current_ids {
+            if blocks.len() as u64 >= max_block_count {
+                break;
+            }
+            let Some(BlockStatus::Active {
+                a_block,
+                storage_or_block,
+                ..
+            }) = self.blocks_state.get(b_id)
+            else {
+                continue;
+            };
+            if !a_block.is_final || execution_slot.map_or(false, |slot| a_block.slot > slot) {
+                continue;
+            }
+            let block = match storage_or_block {
+                StorageOrBlock::Storage(_) | StorageOrBlock::SharedOperations { .. } => {
+                    stored_ids.insert(*b_id);
+                    None
+                }
+                StorageOrBlock::Block(block) => Some(*block.clone()),
+            };
+            blocks.insert(
+                *b_id,
+                PendingBootstrapBlock {
+                    parents: a_block.parents.clone(),
+                    block,
+                },
+            );
+        }
+
+        let cursor = if blocks.is_empty() {
+            StreamingStep::Finished(None)
+        } else {
+            let mut retrieved_ids: PreHashSet<BlockId> = blocks.keys().copied().collect();
+            retrieved_ids.extend(previous_ids.difference(&outdated_ids));
+            StreamingStep::Ongoing(retrieved_ids)
+        };
+
+        // keep the stored blocks alive until they are copied
+        let mut storage = self.storage.clone_without_refs();
+        storage.claim_block_refs(&stored_ids);
+
+        Ok(PendingBootstrapPart {
+            blocks,
+            storage,
+            outdated_ids,
+            cursor,
+        })
+    }
+}
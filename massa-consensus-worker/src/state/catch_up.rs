@@ -0,0 +1,73 @@
+//! Catch-up mode, entered when the final slots lag far behind the wall clock.
+//!
+//! While catching up, the pending registrations are processed in batches with a single graph update,
+//! the block counts are refreshed at the slot ticks only and the divergence checks are postponed.
+//! The mode is left once the lag is back under half of the entry threshold, so that it does not flap.
+
+use massa_consensus_exports::catch_up::CatchUpStatus;
+use massa_models::slot::Slot;
+use massa_time::MassaTime;
+use tracing::info;
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Whether the worker is in catch-up mode
+    pub(crate) fn is_catching_up(&self) -> bool {
+        self.catch_up.read().active
+    }
+
+    /// Count registrations processed in a batch
+    pub(crate) fn note_batched_registrations(&self, count: usize) {
+        let mut catch_up = self.catch_up.write();
+        catch_up.batched_registrations =
+            catch_up.batched_registrations.saturating_add(count as u64);
+    }
+
+    /// Enter or leave the catch-up mode from the lag of the oldest latest final block behind the current slot,
+    /// and publish the status to the controller
+    ///
+    /// # Arguments
+    /// * `current_slot`: the slot of the tick
+    pub(crate) fn refresh_catch_up(&self, current_slot: Slot) {
+        let oldest_final_period = self
+            .latest_final_blocks_periods
+            .iter()
+            .map(|(_, period)| *period)
+            .min()
+            .unwrap_or_default();
+        let lag_periods = current_slot.period.saturating_sub(oldest_final_period);
+        let threshold = self.config.catch_up_lag_periods;
+
+        let mut catch_up = self.catch_up.write();
+        if threshold == 0 {
+            *catch_up = CatchUpStatus {
+                lag_periods,
+                ..Default::default()
+            };
+        } else if !catch_up.active && lag_periods > threshold {
+            info!(
+                "final slots lag {} periods behind the current slot, entering catch-up mode",
+                lag_periods
+            );
+            *catch_up = CatchUpStatus {
+                active: true,
+                lag_periods,
+                initial_lag_periods: lag_periods,
+                started_at: Some(MassaTime::now()),
+                batched_registrations: 0,
+            };
+        } else if catch_up.active && lag_periods <= threshold / 2 {
+            info!(
+                "final slots caught up ({} periods of lag), leaving catch-up mode after {} batched registrations",
+                lag_periods, catch_up.batched_registrations
+            );
+            *catch_up = CatchUpStatus {
+                lag_periods,
+                ..Default::default()
+            };
+        } else {
+            catch_up.lag_periods = lag_periods;
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use massa_consensus_exports::{
+    block_status::BlockStatus,
+    checkpoint::{CheckpointDiscardedBlock, ConsensusCheckpoint},
+};
+use massa_models::slot::Slot;
+use massa_time::MassaTime;
+use tracing::warn;
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Build a summary of the current consensus state
+    ///
+    /// # Arguments
+    /// * `slot`: latest slot processed by the worker
+    pub fn get_checkpoint(&self, slot: Option<Slot>) -> ConsensusCheckpoint {
+        let discarded_blocks = self
+            .blocks_state
+            .discarded_blocks()
+            .iter()
+            .filter_map(|block_id| match self.blocks_state.get(block_id) {
+                Some(BlockStatus::Discarded { slot, reason, .. }) => {
+                    Some(CheckpointDiscardedBlock {
+                        block_id: *block_id,
+                        slot: *slot,
+                        reason: reason.clone(),
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+        ConsensusCheckpoint {
+            timestamp: MassaTime::now(),
+            slot,
+            best_parents: self.best_parents.clone(),
+            latest_final_blocks_periods: self.latest_final_blocks_periods.clone(),
+            discarded_blocks,
+        }
+    }
+
+    /// Write a checkpoint to the node database if `checkpoint_interval` elapsed since the previous one.
+    /// Called at each slot tick.
+    pub(crate) fn checkpoint_tick(&mut self, current_slot: Slot) {
+        let Some(db) = self.channels.db.as_ref() else {
+            return;
+        };
+        let interval = self.config.checkpoint_interval;
+        if interval == MassaTime::from_millis(0) {
+            return;
+        }
+        let now = MassaTime::now();
+        if let Some(last_checkpoint) = self.last_checkpoint {
+            if now.saturating_sub(last_checkpoint) < interval {
+                return;
+            }
+        }
+        if let Err(err) = self.get_checkpoint(Some(current_slot)).save(db) {
+            warn!("could not write the consensus checkpoint: {}", err);
+        }
+        self.last_checkpoint = Some(now);
+    }
+}
@@ -56,11 +56,90 @@ pub fn compute_max_cliques(
     max_cliques
 }
 
+/// Updates the max cliques of compatible blocks after the insertion of a single block,
+/// without recomputing them from the incompatibility graph.
+///
+/// Every max clique containing the new block is made of the new block and of the blocks of
+/// a previous max clique that are compatible with it:
+/// * previous cliques compatible with the new block are extended with it
+/// * the other previous cliques stay max cliques, and their restriction to the blocks compatible
+///   with the new block, plus the new block, is a candidate clique
+/// * candidates that are strictly included in another candidate or clique are not maximal and are dropped
+///
+/// # Arguments
+/// * `max_cliques`: max cliques before the insertion of the block
+/// * `block_id`: the inserted block
+/// * `incomp`: the blocks incompatible with the inserted block
+pub fn update_max_cliques(
+    max_cliques: &[PreHashSet<BlockId>],
+    block_id: BlockId,
+    incomp: &PreHashSet<BlockId>,
+) -> Vec<PreHashSet<BlockId>> {
+    let mut updated: Vec<PreHashSet<BlockId>> = Vec::with_capacity(max_cliques.len());
+    let mut candidates: Vec<PreHashSet<BlockId>> = Vec::new();
+    for clique in max_cliques {
+        if clique.is_disjoint(incomp) {
+            let mut extended = clique.clone();
+            extended.insert(block_id);
+            updated.push(extended);
+        } else {
+            updated.push(clique.clone());
+            let mut candidate: PreHashSet<BlockId> = clique - incomp;
+            candidate.insert(block_id);
+            candidates.push(candidate);
+        }
+    }
+    // largest first, so that a candidate only needs to be compared with the ones kept before it
+    candidates.sort_unstable_by_key(|c| std::cmp::Reverse(c.len()));
+    let mut kept: Vec<PreHashSet<BlockId>> = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let included = kept
+            .iter()
+            .chain(updated.iter().filter(|c| c.contains(&block_id)))
+            .any(|other| candidate.is_subset(other));
+        if !included {
+            kept.push(candidate);
+        }
+    }
+    updated.extend(kept);
+    updated
+}
+
+/// Whether the max cliques of compatible blocks are still all the max cliques of the graph
+/// once the cliques below the fitness threshold are dropped and their stale blocks removed from the graph.
+///
+/// Every max clique of the remaining blocks is the restriction of a previous max clique to them,
+/// and the kept cliques contain no stale block, so it is the case if the restriction of each dropped clique
+/// is included in a kept clique.
+///
+/// # Arguments
+/// * `kept`: the max cliques above the fitness threshold
+/// * `dropped`: the max cliques below the fitness threshold
+/// * `stale`: the blocks of the dropped cliques that are in none of the kept ones
+pub fn max_cliques_exact_after_stale_removal(
+    kept: &[PreHashSet<BlockId>],
+    dropped: &[PreHashSet<BlockId>],
+    stale: &PreHashSet<BlockId>,
+) -> bool {
+    dropped.iter().all(|clique| {
+        let remaining: PreHashSet<BlockId> = clique - stale;
+        kept.iter().any(|other| remaining.is_subset(other))
+    })
+}
+
+/// Whether two lists of cliques contain the same cliques, in any order
+pub fn same_cliques(a: &[PreHashSet<BlockId>], b: &[PreHashSet<BlockId>]) -> bool {
+    a.len() == b.len() && a.iter().all(|clique| b.contains(clique))
+}
+
 /// Tests
 
 #[cfg(test)]
 mod tests {
-    use crate::state::clique_computation::compute_max_cliques;
+    use crate::state::clique_computation::{
+        compute_max_cliques, max_cliques_exact_after_stale_removal, same_cliques,
+        update_max_cliques,
+    };
     use itertools::Itertools;
     use massa_models::{
         block_id::BlockId,
@@ -113,6 +192,113 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_update_max_cliques() {
+        const MAX_SIZE: usize = 10;
+        const ITERATIONS: usize = 1000;
+
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..ITERATIONS {
+            let size = rng.gen_range(1..=MAX_SIZE);
+            let ids: Vec<BlockId> = (0..size)
+                .map(|i| {
+                    BlockId::generate_from_hash(massa_hash::Hash::compute_from(&i.to_be_bytes()))
+                })
+                .collect();
+
+            // insert the blocks one by one with random incompatibilities with the previous ones
+            let mut gi_head: PreHashMap<BlockId, PreHashSet<BlockId>> = PreHashMap::default();
+            let mut max_cliques = vec![PreHashSet::default()];
+            for (i, id) in ids.iter().enumerate() {
+                let incomp: PreHashSet<BlockId> = ids[..i]
+                    .iter()
+                    .filter(|_| rng.gen_bool(0.3))
+                    .copied()
+                    .collect();
+                for incomp_id in incomp.iter() {
+                    gi_head.get_mut(incomp_id).unwrap().insert(*id);
+                }
+                gi_head.insert(*id, incomp.clone());
+
+                max_cliques = update_max_cliques(&max_cliques, *id, &incomp);
+                assert_cliques_valid(&gi_head, &max_cliques);
+
+                // same cliques as a full computation
+                assert!(same_cliques(&max_cliques, &compute_max_cliques(&gi_head)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_max_cliques_after_stale_removal() {
+        const MAX_SIZE: usize = 10;
+        const ITERATIONS: usize = 1000;
+
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..ITERATIONS {
+            let size = rng.gen_range(1..=MAX_SIZE);
+            let ids: Vec<BlockId> = (0..=size)
+                .map(|i| {
+                    BlockId::generate_from_hash(massa_hash::Hash::compute_from(&i.to_be_bytes()))
+                })
+                .collect();
+            let (new_id, ids) = ids.split_last().unwrap();
+
+            // random incompatibility graph
+            let mut gi_head: PreHashMap<BlockId, PreHashSet<BlockId>> =
+                ids.iter().map(|id| (*id, PreHashSet::default())).collect();
+            for (i, j) in (0..size).tuple_combinations() {
+                if rng.gen_bool(0.3) {
+                    gi_head.get_mut(&ids[i]).unwrap().insert(ids[j]);
+                    gi_head.get_mut(&ids[j]).unwrap().insert(ids[i]);
+                }
+            }
+
+            // drop random cliques, as if they were below the fitness threshold, and remove their stale blocks
+            let (dropped, kept): (Vec<_>, Vec<_>) = compute_max_cliques(&gi_head)
+                .into_iter()
+                .partition(|_| rng.gen_bool(0.3));
+            if kept.is_empty() {
+                continue;
+            }
+            let stale: PreHashSet<BlockId> = dropped
+                .iter()
+                .flatten()
+                .filter(|id| kept.iter().all(|c| !c.contains(*id)))
+                .copied()
+                .collect();
+            for stale_id in stale.iter() {
+                for other in gi_head.remove(stale_id).unwrap() {
+                    if let Some(other_incomps) = gi_head.get_mut(&other) {
+                        other_incomps.remove(stale_id);
+                    }
+                }
+            }
+            let exact = max_cliques_exact_after_stale_removal(&kept, &dropped, &stale);
+            assert_eq!(exact, same_cliques(&kept, &compute_max_cliques(&gi_head)));
+
+            // insert a forking block, updating the cliques incrementally only if they are exact
+            let incomp: PreHashSet<BlockId> = gi_head
+                .keys()
+                .filter(|_| rng.gen_bool(0.3))
+                .copied()
+                .collect();
+            for incomp_id in incomp.iter() {
+                gi_head.get_mut(incomp_id).unwrap().insert(*new_id);
+            }
+            gi_head.insert(*new_id, incomp.clone());
+            let max_cliques = if exact {
+                update_max_cliques(&kept, *new_id, &incomp)
+            } else {
+                compute_max_cliques(&gi_head)
+            };
+            assert_cliques_valid(&gi_head, &max_cliques);
+            assert!(same_cliques(&max_cliques, &compute_max_cliques(&gi_head)));
+        }
+    }
+
     /// Assert that a set of cliques is valid
     fn assert_cliques_valid(
         gi_head: &PreHashMap<BlockId, PreHashSet<BlockId>>,
@@ -0,0 +1,63 @@
+use massa_consensus_exports::{ban_list::CreatorBan, error::ConsensusError};
+use massa_models::address::Address;
+use massa_time::MassaTime;
+use tracing::{info, warn};
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Whether the headers of a creator must be dropped
+    pub fn is_creator_banned(&self, creator: &Address) -> bool {
+        self.creator_bans.is_banned(creator, MassaTime::now())
+    }
+
+    /// Ban a creator for `creator_ban_duration`, or `duration` if given
+    pub fn ban_creator(
+        &mut self,
+        creator: Address,
+        reason: String,
+        duration: Option<MassaTime>,
+    ) -> Result<(), ConsensusError> {
+        let expires_at =
+            MassaTime::now().saturating_add(duration.unwrap_or(self.config.creator_ban_duration));
+        info!(
+            "banning block creator {} until {}: {}",
+            creator,
+            expires_at.format_instant(),
+            reason
+        );
+        self.creator_bans.ban(creator, reason, expires_at)
+    }
+
+    /// Ban the creator of a block discarded as invalid, failures to save the list are only logged
+    pub(crate) fn ban_invalid_block_creator(&mut self, creator: Address, reason: &str) {
+        if let Err(err) = self.ban_creator(creator, format!("invalid block: {}", reason), None) {
+            warn!(
+                "could not save the ban of block creator {}: {}",
+                creator, err
+            );
+        }
+    }
+
+    /// Lift the bans of some creators
+    pub fn unban_creators(&mut self, creators: &[Address]) -> Result<(), ConsensusError> {
+        self.creator_bans.unban(creators)
+    }
+
+    /// Get the active bans
+    pub fn list_creator_bans(&self) -> Vec<CreatorBan> {
+        let now = MassaTime::now();
+        self.creator_bans
+            .list()
+            .into_iter()
+            .filter(|ban| ban.expires_at > now)
+            .collect()
+    }
+
+    /// Drop the expired bans, called at each slot tick
+    pub(crate) fn prune_creator_bans(&mut self) {
+        if let Err(err) = self.creator_bans.prune_expired(MassaTime::now()) {
+            warn!("could not save the block creator ban list: {}", err);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use massa_consensus_exports::{
+    block_status::{BlockStatus, DiscardReason},
+    divergence::{BlockcliqueSummary, DivergentPeer},
+};
+use massa_models::{block_id::BlockId, prehash::PreHashSet, slot::Slot};
+use massa_time::MassaTime;
+use tracing::{info, warn};
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Summary of the local blockclique, gossiped to the peers
+    pub fn get_blockclique_summary(&self) -> BlockcliqueSummary {
+        let final_slot = self
+            .latest_final_blocks_periods
+            .iter()
+            .enumerate()
+            .map(|(thread, (_, period))| Slot::new(*period, thread as u8))
+            .max()
+            .unwrap_or_else(|| Slot::new(self.config.last_start_period, 0));
+        BlockcliqueSummary {
+            final_slot,
+            blockclique_tips: self.best_parents.iter().map(|(id, _)| *id).collect(),
+        }
+    }
+
+    /// Whether a block announced as a blockclique tip by a peer diverges from the local blockclique:
+    /// it was discarded as stale or invalid, or it is active but out of the blockclique.
+    /// Unknown blocks are not considered diverging as they might not have been received yet.
+    fn is_diverging_tip(&self, blockclique: &PreHashSet<BlockId>, block_id: &BlockId) -> bool {
+        match self.blocks_state.get(block_id) {
+            Some(BlockStatus::Discarded { reason, .. }) => !matches!(reason, DiscardReason::Final),
+            Some(BlockStatus::Active { a_block, .. }) => {
+                !a_block.is_final && !blockclique.contains(block_id)
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the peers whose latest blockclique summary diverges from the local blockclique
+    ///
+    /// Summaries older than `peer_blockclique_summary_lifetime` are ignored.
+    pub fn get_divergent_peers(&self) -> Vec<DivergentPeer> {
+        let now = MassaTime::now();
+        let lifetime = self.config.peer_blockclique_summary_lifetime;
+        let blockclique = self.get_blockclique();
+        let mut res: Vec<DivergentPeer> = self
+            .peer_blockclique_summaries
+            .read()
+            .iter()
+            .filter(|(_, peer_summary)| now.saturating_sub(peer_summary.received_at) <= lifetime)
+            .filter_map(|(peer_id, peer_summary)| {
+                let diverging_tips: Vec<BlockId> = peer_summary
+                    .summary
+                    .blockclique_tips
+                    .iter()
+                    .filter(|block_id| self.is_diverging_tip(&blockclique, block_id))
+                    .copied()
+                    .collect();
+                (!diverging_tips.is_empty()).then_some(DivergentPeer {
+                    peer_id: *peer_id,
+                    final_slot: peer_summary.summary.final_slot,
+                    diverging_tips,
+                })
+            })
+            .collect();
+        res.sort_unstable_by_key(|peer| peer.peer_id);
+        res
+    }
+
+    /// Forget the expired peer summaries and warn the operator when the local blockclique
+    /// starts or stops differing from the one of the majority of the peers
+    pub fn check_blockclique_divergence(&mut self) {
+        let now = MassaTime::now();
+        let lifetime = self.config.peer_blockclique_summary_lifetime;
+        let peer_count = {
+            let mut summaries = self.peer_blockclique_summaries.write();
+            summaries
+                .retain(|_, peer_summary| now.saturating_sub(peer_summary.received_at) <= lifetime);
+            summaries.len()
+        };
+        if peer_count == 0 {
+            return;
+        }
+        let divergent_count = self.get_divergent_peers().len();
+        let majority_divergent = divergent_count * 2 > peer_count;
+        if majority_divergent && !self.majority_divergent {
+            warn!(
+                "the local blockclique differs from the one of the majority of the peers: {} out of {} peers diverge",
+                divergent_count, peer_count
+            );
+        } else if !majority_divergent && self.majority_divergent {
+            info!("the local blockclique agrees again with the majority of the peers");
+        }
+        self.majority_divergent = majority_divergent;
+    }
+}
@@ -0,0 +1,90 @@
+//! Draws of the upcoming slots, prefetched from the selector.
+//!
+//! At each cycle rollover, the draws from the first slot of the new cycle up to `draw_prefetch_periods`
+//! periods ahead are fetched in one call, so that checking the headers and endorsements of the incoming
+//! blocks does not query the selector. The cache is dropped at each rollover, the selector computing the
+//! draws of a cycle at that time. The draws that were not computed yet when the cache was filled are
+//! fetched at the following slot ticks, once available. Lookups outside of the cache fall back to the selector.
+
+use std::collections::BTreeMap;
+
+use massa_models::{address::Address, slot::Slot};
+use massa_pos_exports::{PosResult, Selection};
+use tracing::debug;
+
+use super::ConsensusState;
+
+/// Draws prefetched from the selector
+#[derive(Debug, Clone, Default)]
+pub struct DrawCache {
+    /// cycle during which the cache was filled
+    cycle: Option<u64>,
+    /// last slot to prefetch
+    window_end: Option<Slot>,
+    /// prefetched draws
+    draws: BTreeMap<Slot, Selection>,
+}
+
+impl ConsensusState {
+    /// Refill the draw cache at a cycle rollover, or complete it with the draws computed since it was filled.
+    /// Called at each slot tick.
+    pub(crate) fn refresh_draw_cache(&mut self, current_slot: Slot) {
+        if self.config.draw_prefetch_periods == 0 {
+            return;
+        }
+        let cycle = current_slot.get_cycle(self.config.periods_per_cycle);
+        if self.draw_cache.cycle != Some(cycle) {
+            self.draw_cache = DrawCache {
+                cycle: Some(cycle),
+                window_end: Some(Slot::new(
+                    current_slot
+                        .period
+                        .saturating_add(self.config.draw_prefetch_periods),
+                    self.config.thread_count.saturating_sub(1),
+                )),
+                draws: BTreeMap::new(),
+            };
+        }
+        let Some(window_end) = self.draw_cache.window_end else {
+            return;
+        };
+        let window_start = match self.draw_cache.draws.last_key_value() {
+            Some((last_slot, _)) if *last_slot >= window_end => return,
+            Some((last_slot, _)) => match last_slot.get_next_slot(self.config.thread_count) {
+                Ok(slot) => slot,
+                Err(_) => return,
+            },
+            None => match Slot::new_first_of_cycle(cycle, self.config.periods_per_cycle) {
+                Ok(slot) => slot,
+                Err(_) => return,
+            },
+        };
+        match self
+            .channels
+            .selector_controller
+            .get_available_selections_in_range(window_start..=window_end, None)
+        {
+            Ok(draws) => self.draw_cache.draws.extend(draws),
+            Err(err) => debug!(
+                "could not prefetch the draws from slot {} to {}: {}",
+                window_start, window_end, err
+            ),
+        }
+    }
+
+    /// Get the draws of a slot, from the draw cache if they were prefetched
+    pub(crate) fn get_selection(&self, slot: Slot) -> PosResult<Selection> {
+        match self.draw_cache.draws.get(&slot) {
+            Some(selection) => Ok(selection.clone()),
+            None => self.channels.selector_controller.get_selection(slot),
+        }
+    }
+
+    /// Get the block producer of a slot, from the draw cache if it was prefetched
+    pub(crate) fn get_producer(&self, slot: Slot) -> PosResult<Address> {
+        match self.draw_cache.draws.get(&slot) {
+            Some(selection) => Ok(selection.producer),
+            None => self.channels.selector_controller.get_producer(slot),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Endorsement misses of each address, counted on the final blocks.
+//!
+//! When a block becomes final, each endorsement draw of its slot is counted for the drawn address,
+//! and counted as missed if the block does not include the endorsement of that index.
+//! Slots without final block are not counted: no endorsement could be included there.
+//! The statistics of the latest `endorser_stats_cycles` cycles are kept in RAM only.
+
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use massa_consensus_exports::endorser_reliability::{EndorserCycleStats, EndorserReliability};
+use massa_models::{address::Address, prehash::PreHashMap, slot::Slot};
+use tracing::debug;
+
+use super::ConsensusState;
+
+/// Endorsement draws and misses of each address, per cycle
+pub type EndorserStats = BTreeMap<u64, PreHashMap<Address, EndorserCycleStats>>;
+
+impl ConsensusState {
+    /// Count the endorsement draws of the slot of a newly final block, and the ones it missed
+    ///
+    /// # Arguments
+    /// * `slot`: slot of the final block
+    /// * `included_indexes`: indexes of the endorsements included in the block
+    pub(crate) fn record_final_endorsements(&mut self, slot: Slot, included_indexes: &[u32]) {
+        let selection = match self.get_selection(slot) {
+            Ok(selection) => selection,
+            Err(err) => {
+                debug!(
+                    "could not count the endorsement misses of slot {}: {}",
+                    slot, err
+                );
+                return;
+            }
+        };
+        let cycle = slot.get_cycle(self.config.periods_per_cycle);
+        let cycle_stats = self.endorser_stats.entry(cycle).or_default();
+        for (index, address) in selection.endorsements.into_iter().enumerate() {
+            let stats = cycle_stats.entry(address).or_insert(EndorserCycleStats {
+                cycle,
+                ..Default::default()
+            });
+            stats.drawn += 1;
+            if !included_indexes.contains(&(index as u32)) {
+                stats.missed += 1;
+            }
+        }
+        while self.endorser_stats.len() as u64 > self.config.endorser_stats_cycles {
+            self.endorser_stats.pop_first();
+        }
+    }
+
+    /// Get the endorsement misses of an address over a range of cycles.
+    /// Only the latest `endorser_stats_cycles` cycles are tracked.
+    pub fn get_endorser_reliability(
+        &self,
+        address: Address,
+        cycles: RangeInclusive<u64>,
+    ) -> EndorserReliability {
+        EndorserReliability {
+            address,
+            cycles: self
+                .endorser_stats
+                .range(cycles)
+                .filter_map(|(_, cycle_stats)| cycle_stats.get(&address).copied())
+                .collect(),
+        }
+    }
+}
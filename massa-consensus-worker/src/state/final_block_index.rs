@@ -0,0 +1,95 @@
+//! Node-local index of the final block ids by slot.
+//!
+//! The graph forgets the final blocks once they are pruned from memory. The index keeps the id of the final block
+//! of each slot in its own RocksDB instance, so that the block of an old final slot can still be looked up.
+//! It is written at finalization, is not part of the final state and is not bootstrapped:
+//! it only covers the slots finalized while the node was running with it enabled.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use massa_models::{
+    block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer},
+    slot::Slot,
+};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use rocksdb::{WriteBatch, DB};
+
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const VALUE_ERROR: &str = "critical: invalid final block index value";
+
+pub(crate) struct FinalBlockIndex {
+    /// RocksDB database, keyed by slot
+    db: DB,
+    block_id_serializer: BlockIdSerializer,
+    block_id_deserializer: BlockIdDeserializer,
+}
+
+impl FinalBlockIndex {
+    /// Open the index, creating it if it does not exist
+    ///
+    /// # Arguments
+    /// * path: where to store the db
+    pub fn open(path: PathBuf) -> Self {
+        let db = DB::open_default(path).expect(OPEN_ERROR);
+        Self {
+            db,
+            block_id_serializer: BlockIdSerializer::new(),
+            block_id_deserializer: BlockIdDeserializer::new(),
+        }
+    }
+
+    /// Record the slots of newly final blocks
+    pub fn record(&self, final_block_slots: &HashMap<Slot, BlockId>) {
+        if final_block_slots.is_empty() {
+            return;
+        }
+        let mut batch = WriteBatch::default();
+        for (slot, block_id) in final_block_slots {
+            let mut value = Vec::new();
+            self.block_id_serializer
+                .serialize(block_id, &mut value)
+                .expect(VALUE_ERROR);
+            batch.put(slot.to_bytes_key(), value);
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get the id of the final block at a given slot, `None` if the slot has no block or was not indexed
+    pub fn get(&self, slot: &Slot) -> Option<BlockId> {
+        let value = self.db.get(slot.to_bytes_key()).expect(CRUD_ERROR)?;
+        let (_, block_id) = self
+            .block_id_deserializer
+            .deserialize::<DeserializeError>(&value)
+            .expect(VALUE_ERROR);
+        Some(block_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_get() {
+        let tmp_dir = TempDir::new().unwrap();
+        let block_a = BlockId::generate_from_hash(Hash::compute_from(b"a"));
+        let block_b = BlockId::generate_from_hash(Hash::compute_from(b"b"));
+        {
+            let index = FinalBlockIndex::open(tmp_dir.path().to_path_buf());
+            index.record(&HashMap::from([
+                (Slot::new(1, 0), block_a),
+                (Slot::new(1, 1), block_b),
+            ]));
+            assert_eq!(index.get(&Slot::new(1, 0)), Some(block_a));
+            assert_eq!(index.get(&Slot::new(2, 0)), None);
+        }
+
+        // the index is kept across restarts
+        let index = FinalBlockIndex::open(tmp_dir.path().to_path_buf());
+        assert_eq!(index.get(&Slot::new(1, 1)), Some(block_b));
+    }
+}
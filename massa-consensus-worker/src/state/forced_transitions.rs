@@ -0,0 +1,122 @@
+//! Forced block status transitions, for the tests and tooling of the modules driven by consensus.
+//!
+//! They let execution or pool tests bring the graph to a given state without crafting a full sequence
+//! of valid blocks. The fitness rules are bypassed, but the graph stays consistent: the blocks
+//! incompatible with forcibly finalized blocks are discarded as stale, and the cliques, the blockclique
+//! and the best parents are recomputed before the other modules are notified.
+
+use massa_consensus_exports::{block_status::BlockStatus, error::ConsensusError};
+use massa_models::{block_id::BlockId, clique::Clique, prehash::PreHashSet, slot::Slot};
+
+use super::{clique_computation::compute_max_cliques, ConsensusState};
+
+impl ConsensusState {
+    /// Make final all the blocks of the blockclique at or before `slot`.
+    ///
+    /// The active blocks incompatible with them are discarded as stale.
+    /// Execution, pool and protocol are notified as after the processing of a block.
+    ///
+    /// # Returns
+    /// The ids of the blocks that were made final
+    pub fn force_finalize_up_to(
+        &mut self,
+        slot: Slot,
+    ) -> Result<PreHashSet<BlockId>, ConsensusError> {
+        let final_blocks: PreHashSet<BlockId> = self
+            .get_blockclique()
+            .into_iter()
+            .filter(|block_id| {
+                matches!(
+                    self.blocks_state.get(block_id),
+                    Some(BlockStatus::Active { a_block, .. }) if !a_block.is_final && a_block.slot <= slot
+                )
+            })
+            .collect();
+        let Some(change_block_id) = final_blocks.iter().next().copied() else {
+            return Ok(final_blocks);
+        };
+
+        // the blocks incompatible with a final block can never become final
+        let stale_blocks: PreHashSet<BlockId> = final_blocks
+            .iter()
+            .filter_map(|block_id| self.gi_head.get(block_id))
+            .flatten()
+            .filter(|block_id| !final_blocks.contains(block_id))
+            .copied()
+            .collect();
+        for stale_block_id in stale_blocks.iter() {
+            self.remove_block(&change_block_id, stale_block_id);
+        }
+        self.mark_final_blocks(&change_block_id, final_blocks.clone())?;
+
+        self.refresh_cliques_after_forced_transition(&change_block_id)?;
+        Ok(final_blocks)
+    }
+
+    /// Discard the given active blocks as stale, along with their descendants.
+    ///
+    /// Execution, pool and protocol are notified as after the processing of a block.
+    ///
+    /// # Returns
+    /// The ids of the discarded blocks, including the descendants,
+    /// or an error if one of the blocks is not active or already final
+    pub fn force_discard(
+        &mut self,
+        block_ids: &[BlockId],
+    ) -> Result<PreHashSet<BlockId>, ConsensusError> {
+        let mut discarded_blocks = PreHashSet::<BlockId>::default();
+        for block_id in block_ids {
+            match self.blocks_state.get(block_id) {
+                Some(BlockStatus::Active { a_block, .. }) if !a_block.is_final => {
+                    discarded_blocks.insert(*block_id);
+                    discarded_blocks.extend(a_block.descendants.iter().copied());
+                }
+                _ => {
+                    return Err(ConsensusError::MissingBlock(format!(
+                        "block {} is not an active non-final block and can't be discarded",
+                        block_id
+                    )))
+                }
+            }
+        }
+        let Some(change_block_id) = block_ids.first().copied() else {
+            return Ok(discarded_blocks);
+        };
+
+        // descendants that are not active anymore were already discarded
+        discarded_blocks.retain(|block_id| {
+            matches!(
+                self.blocks_state.get(block_id),
+                Some(BlockStatus::Active { .. })
+            )
+        });
+        for block_id in discarded_blocks.iter() {
+            self.remove_block(&change_block_id, block_id);
+        }
+
+        self.refresh_cliques_after_forced_transition(&change_block_id)?;
+        Ok(discarded_blocks)
+    }
+
+    /// Recompute the cliques, the blockclique and the best parents after a forced transition,
+    /// then notify the other modules of the changes.
+    ///
+    /// The cliques are fully recomputed: removing blocks can leave cliques that are not maximal anymore.
+    fn refresh_cliques_after_forced_transition(
+        &mut self,
+        change_block_id: &BlockId,
+    ) -> Result<(), ConsensusError> {
+        self.max_cliques_inexact = false;
+        self.max_cliques = compute_max_cliques(&self.gi_head)
+            .into_iter()
+            .map(|block_ids| Clique {
+                block_ids,
+                fitness: 0,
+                is_blockclique: false,
+            })
+            .collect();
+        let position_blockclique = self.compute_fitness_find_blockclique()?;
+        self.update_best_parents(position_blockclique, change_block_id)?;
+        self.block_db_changed()
+    }
+}
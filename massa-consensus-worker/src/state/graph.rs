@@ -1,8 +1,10 @@
 use std::collections::VecDeque;
 
 use massa_consensus_exports::{
-    block_status::{BlockStatus, DiscardReason},
+    block_status::{BlockStatus, DiscardReason, StorageOrBlock},
     error::ConsensusError,
+    fitness_explanation::{CliqueFitnessExplanation, FitnessExplanation},
+    parent_candidates::{ParentCandidate, ParentCandidates},
 };
 use massa_logging::massa_trace;
 use massa_models::{
@@ -11,7 +13,9 @@ use massa_models::{
     prehash::PreHashSet,
     slot::Slot,
 };
+use massa_pool_exports::EndorsementFeedback;
 use massa_serialization::Serializer;
+use tracing::debug;
 
 use super::ConsensusState;
 
@@ -76,10 +80,7 @@ impl ConsensusState {
         }
     }
 
-    pub fn compute_fitness_find_blockclique(
-        &mut self,
-        add_block_id: &BlockId,
-    ) -> Result<usize, ConsensusError> {
+    pub fn compute_fitness_find_blockclique(&mut self) -> Result<usize, ConsensusError> {
         let block_id_serializer = BlockIdSerializer::new();
         let mut blockclique_i = 0usize;
         let mut max_clique_fitness = (0u64, num::BigInt::default());
@@ -88,10 +89,14 @@ impl ConsensusState {
             clique.is_blockclique = false;
             let mut sum_hash = num::BigInt::default();
             for block_h in clique.block_ids.iter() {
-                let fitness = match self.blocks_state.get(block_h) {
-                    Some(BlockStatus::Active { a_block, .. }) => a_block.fitness,
-                    _ => return Err(ConsensusError::ContainerInconsistency(format!("inconsistency inside block statuses computing fitness while adding {} - missing {}", add_block_id, block_h))),
-                };
+                let fitness =
+                    match self.blocks_state.get(block_h) {
+                        Some(BlockStatus::Active { a_block, .. }) => a_block.fitness,
+                        _ => return Err(ConsensusError::ContainerInconsistency(format!(
+                            "inconsistency inside block statuses computing fitness - missing {}",
+                            block_h
+                        ))),
+                    };
                 clique.fitness = clique
                     .fitness
                     .checked_add(fitness)
@@ -112,6 +117,34 @@ impl ConsensusState {
         Ok(blockclique_i)
     }
 
+    /// Set the best parents to the latest blocks of the blockclique in each thread, or to the latest final blocks
+    ///
+    /// # Arguments
+    /// * `position_blockclique`: index of the blockclique in `max_cliques`
+    /// * `change_block_id`: block whose processing changed the graph, for error reporting
+    pub fn update_best_parents(
+        &mut self,
+        position_blockclique: usize,
+        change_block_id: &BlockId,
+    ) -> Result<(), ConsensusError> {
+        let blockclique = &self.max_cliques[position_blockclique];
+
+        // init best parents as latest_final_blocks_periods
+        self.best_parents = self.latest_final_blocks_periods.clone();
+        // for each blockclique block, set it as best_parent in its own thread
+        // if its period is higher than the current best_parent in that thread
+        for block_h in blockclique.block_ids.iter() {
+            let b_slot = match self.blocks_state.get(block_h) {
+                Some(BlockStatus::Active { a_block, .. }) => a_block.slot,
+                _ => return Err(ConsensusError::ContainerInconsistency(format!("inconsistency inside block statuses updating best parents while adding {} - missing {}", change_block_id, block_h))),
+            };
+            if b_slot.period > self.best_parents[b_slot.thread as usize].1 {
+                self.best_parents[b_slot.thread as usize] = (*block_h, b_slot.period);
+            }
+        }
+        Ok(())
+    }
+
     pub fn list_stale_blocks(&self, fitness_threshold: u64) -> PreHashSet<BlockId> {
         // iterate from largest to smallest to minimize reallocations
         let mut indices: Vec<usize> = (0..self.max_cliques.len()).collect();
@@ -133,7 +166,7 @@ impl ConsensusState {
         self.blocks_state.transition_map(block_id, |block_status, block_statuses| {
         if let Some(BlockStatus::Active {
             a_block: active_block,
-            ..
+            storage_or_block,
         }) = block_status
         {
             if active_block.is_final {
@@ -182,6 +215,20 @@ impl ConsensusState {
                 "hash": block_id
             });
 
+            // keep the endorsements of the block for the pool
+            if let StorageOrBlock::Storage(storage) | StorageOrBlock::SharedOperations { storage, .. } = &storage_or_block {
+                let endorsement_ids = storage.get_endorsement_refs().clone();
+                if !endorsement_ids.is_empty() {
+                    let mut endorsements = storage.clone_without_refs();
+                    endorsements.claim_endorsement_refs(&endorsement_ids);
+                    self.new_orphaned_endorsements.push(EndorsementFeedback {
+                        block_id: *block_id,
+                        endorsement_ids,
+                        storage: endorsements,
+                    });
+                }
+            }
+
             // mark as stale
             self.new_stale_blocks
                 .insert(*block_id, (active_block.creator_address, active_block.slot));
@@ -270,6 +317,139 @@ impl ConsensusState {
         Ok(final_blocks)
     }
 
+    /// Explain the fitness of an active block and its contribution to each of its cliques
+    pub fn explain_fitness(
+        &self,
+        block_id: &BlockId,
+    ) -> Result<FitnessExplanation, ConsensusError> {
+        let Some(BlockStatus::Active { a_block, .. }) = self.blocks_state.get(block_id) else {
+            return Err(ConsensusError::MissingBlock(format!(
+                "block {} is not active in the graph",
+                block_id
+            )));
+        };
+        let cliques = self
+            .max_cliques
+            .iter()
+            .enumerate()
+            .filter(|(_, clique)| clique.block_ids.contains(block_id))
+            .map(|(clique_index, clique)| {
+                let mut descendants_counted = 0usize;
+                let mut descendants_fitness = 0u64;
+                for descendant_id in a_block.descendants.intersection(&clique.block_ids) {
+                    if let Some(BlockStatus::Active { a_block: ab, .. }) =
+                        self.blocks_state.get(descendant_id)
+                    {
+                        descendants_counted += 1;
+                        descendants_fitness = descendants_fitness.saturating_add(ab.fitness);
+                    }
+                }
+                CliqueFitnessExplanation {
+                    clique_index,
+                    is_blockclique: clique.is_blockclique,
+                    clique_fitness: clique.fitness,
+                    descendants_counted,
+                    descendants_fitness,
+                }
+            })
+            .collect();
+        Ok(FitnessExplanation {
+            block_id: *block_id,
+            slot: a_block.slot,
+            endorsement_count: a_block.fitness.saturating_sub(1),
+            own_fitness: a_block.fitness,
+            is_final: a_block.is_final,
+            delta_f0: self.config.delta_f0,
+            cliques,
+        })
+    }
+
+    /// Latest period of the blocks that can be proposed as parents, given the progress of the speculative execution.
+    ///
+    /// # Returns
+    /// `None` if `max_parent_execution_lag` is 0
+    fn max_parent_period(&self) -> Option<u64> {
+        if self.config.max_parent_execution_lag == 0 {
+            return None;
+        }
+        let candidate_cursor = self.channels.execution_controller.get_candidate_cursor();
+        Some(
+            candidate_cursor
+                .period
+                .saturating_add(self.config.max_parent_execution_lag),
+        )
+    }
+
+    /// Get the best parents proposed to the block factory.
+    ///
+    /// The blockclique blocks more than `max_parent_execution_lag` periods ahead of the speculative execution
+    /// are left out. The descendants of a block being later than it, they are left out too, so the latest
+    /// remaining block of each thread still forms a consistent choice of parents.
+    pub fn get_exposed_best_parents(&self) -> Vec<(BlockId, u64)> {
+        let Some(max_period) = self.max_parent_period() else {
+            return self.best_parents.clone();
+        };
+        if self
+            .best_parents
+            .iter()
+            .all(|(_, period)| *period <= max_period)
+        {
+            return self.best_parents.clone();
+        }
+        let mut best_parents = self.latest_final_blocks_periods.clone();
+        for block_id in self.get_blockclique().iter() {
+            if let Some(BlockStatus::Active { a_block, .. }) = self.blocks_state.get(block_id) {
+                if a_block.slot.period <= max_period
+                    && a_block.slot.period > best_parents[a_block.slot.thread as usize].1
+                {
+                    best_parents[a_block.slot.thread as usize] = (*block_id, a_block.slot.period);
+                }
+            }
+        }
+        debug!(
+            "best parents held back because the speculative execution lags: {:?} instead of {:?}",
+            best_parents, self.best_parents
+        );
+        best_parents
+    }
+
+    /// List the blocks that can be referenced as parents by the next block:
+    /// the blockclique blocks and the latest final block of each thread, from the latest to the oldest.
+    /// The blocks too far ahead of the speculative execution are left out, see `get_exposed_best_parents`.
+    pub fn get_parent_candidates(&self) -> ParentCandidates {
+        let mut candidates = vec![Vec::new(); self.config.thread_count as usize];
+        let max_period = self.max_parent_period().unwrap_or(u64::MAX);
+        let blockclique = self.get_blockclique();
+        let candidate_ids = blockclique.iter().chain(
+            self.latest_final_blocks_periods
+                .iter()
+                .map(|(block_id, _period)| block_id),
+        );
+        for block_id in candidate_ids {
+            if let Some(BlockStatus::Active { a_block, .. }) = self.blocks_state.get(block_id) {
+                if !a_block.is_final && a_block.slot.period > max_period {
+                    continue;
+                }
+                candidates[a_block.slot.thread as usize].push(ParentCandidate {
+                    block_id: *block_id,
+                    slot: a_block.slot,
+                    creator_address: a_block.creator_address,
+                    endorsement_count: a_block.fitness.saturating_sub(1),
+                    parents: a_block.parents.clone(),
+                    is_final: a_block.is_final,
+                });
+            }
+        }
+        for thread_candidates in candidates.iter_mut() {
+            thread_candidates.sort_unstable_by(|a, b| b.slot.cmp(&a.slot));
+        }
+        ParentCandidates {
+            best_parents: self.get_exposed_best_parents(),
+            candidates,
+            generation: self.snapshots.read().generation,
+        }
+    }
+
     /// get the clique of higher fitness
     pub fn get_blockclique(&self) -> PreHashSet<BlockId> {
         self.max_cliques
@@ -280,6 +460,52 @@ impl ConsensusState {
             .clone()
     }
 
+    /// List the indices of the cliques exposed to the callers of `get_cliques`, in `max_cliques` order.
+    ///
+    /// The cliques whose fitness is more than `exposed_clique_fitness_margin` below the fitness of the blockclique
+    /// are left out, then only the `max_exposed_cliques` fittest ones are kept (0 for no limit).
+    /// The blockclique is always exposed. The graph keeps all the cliques: they are still needed
+    /// to mark stale and final blocks.
+    fn exposed_clique_indices(&self) -> Vec<usize> {
+        let blockclique_fitness = self
+            .max_cliques
+            .iter()
+            .find(|c| c.is_blockclique)
+            .map_or(0, |c| c.fitness);
+        let fitness_threshold =
+            blockclique_fitness.saturating_sub(self.config.exposed_clique_fitness_margin);
+        let mut indices: Vec<usize> = (0..self.max_cliques.len())
+            .filter(|&i| {
+                self.max_cliques[i].is_blockclique
+                    || self.max_cliques[i].fitness >= fitness_threshold
+            })
+            .collect();
+        if self.config.max_exposed_cliques > 0 && indices.len() > self.config.max_exposed_cliques {
+            indices.sort_unstable_by_key(|&i| {
+                (
+                    std::cmp::Reverse(self.max_cliques[i].is_blockclique),
+                    std::cmp::Reverse(self.max_cliques[i].fitness),
+                )
+            });
+            indices.truncate(self.config.max_exposed_cliques);
+            indices.sort_unstable();
+        }
+        indices
+    }
+
+    /// Get the cliques exposed to the callers of `get_cliques`, see `exposed_clique_indices`
+    pub fn get_exposed_cliques(&self) -> Vec<Clique> {
+        self.exposed_clique_indices()
+            .into_iter()
+            .map(|i| self.max_cliques[i].clone())
+            .collect()
+    }
+
+    /// Number of cliques left out of the cliques exposed to the callers of `get_cliques`
+    pub fn get_pruned_clique_count(&self) -> usize {
+        self.max_cliques.len() - self.exposed_clique_indices().len()
+    }
+
     pub fn mark_final_blocks(
         &mut self,
         add_block_id: &BlockId,
@@ -296,15 +522,10 @@ impl ConsensusState {
             }
 
             // mark as final and update latest_final_blocks_periods
-            if let Some(BlockStatus::Active {
-                a_block: final_block,
-                ..
-            }) = self.blocks_state.get_mut(&block_id)
-            {
+            if let Some(final_block) = self.blocks_state.mark_final(&block_id) {
                 massa_trace!("consensus.block_graph.add_block_to_graph.final", {
                     "hash": block_id
                 });
-                final_block.is_final = true;
                 // remove from cliques
                 let final_block_fitness = final_block.fitness;
                 self.max_cliques.iter_mut().for_each(|c| {
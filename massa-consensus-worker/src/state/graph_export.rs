@@ -0,0 +1,186 @@
+//! Graph exports served from a snapshot, so that long exports do not block the worker.
+//!
+//! The worker publishes a snapshot of the whole graph (statuses, children, cliques, best parents...) along
+//! with the other graph snapshots (see `snapshots.rs`), and rebuilds it only when the graph changed since
+//! the previous one. The controller filters the snapshot by slot and clones the exported blocks without
+//! taking the lock of the consensus state: exports never delay the processing of the blocks, and each
+//! export is consistent with the graph as it was when the snapshot was published.
+//! A block never changes once in the graph, so its header is shared between successive snapshots.
+//! The time spent building the snapshots is reported by the `consensus_graph_snapshot_time` metric.
+
+use std::sync::Arc;
+
+use massa_consensus_exports::{
+    block_graph_export::BlockGraphExport,
+    block_provenance::BlockProvenance,
+    block_status::{BlockStatus, DiscardReason, ExportCompiledBlock, StorageOrBlock},
+};
+use massa_models::{
+    address::Address,
+    block_header::SecuredHeader,
+    block_id::BlockId,
+    clique::Clique,
+    prehash::{CapacityAllocator, PreHashMap, PreHashSet},
+    slot::Slot,
+};
+
+use super::ConsensusState;
+
+/// Active block of a graph snapshot
+#[derive(Debug)]
+struct SnapshotBlock {
+    /// header of the block, shared with the next snapshots
+    header: Arc<SecuredHeader>,
+    /// children of the block in each thread
+    children: Vec<PreHashSet<BlockId>>,
+    /// true if the block is final
+    is_final: bool,
+    /// origin and first-seen time of the block
+    provenance: Option<BlockProvenance>,
+}
+
+/// Snapshot of the whole graph, filtered by slot into graph exports
+#[derive(Debug, Default)]
+pub struct GraphExportSnapshot {
+    /// generations of the block statuses and of the other snapshots it was built from, `None` if never built
+    source_generation: Option<(u64, u64)>,
+    /// genesis blocks
+    genesis_blocks: Vec<BlockId>,
+    /// active blocks
+    active_blocks: PreHashMap<BlockId, SnapshotBlock>,
+    /// discarded blocks
+    discarded_blocks: PreHashMap<BlockId, (DiscardReason, (Slot, Address, Vec<BlockId>))>,
+    /// best parents in each thread
+    best_parents: Vec<(BlockId, u64)>,
+    /// latest final period and block id in each thread
+    latest_final_blocks_periods: Vec<(BlockId, u64)>,
+    /// head of the incompatibility graph
+    gi_head: PreHashMap<BlockId, PreHashSet<BlockId>>,
+    /// list of maximal cliques of compatible blocks
+    max_cliques: Vec<Clique>,
+}
+
+impl GraphExportSnapshot {
+    /// Export the part of the snapshot between two slots
+    ///
+    /// # Arguments
+    /// * `slot_start`: first slot of the exported blocks, included
+    /// * `slot_end`: end slot of the exported blocks, excluded
+    pub fn export(&self, slot_start: Option<Slot>, slot_end: Option<Slot>) -> BlockGraphExport {
+        let filter = |s: &Slot| {
+            slot_start.map_or(true, |s_start| *s >= s_start)
+                && slot_end.map_or(true, |s_end| *s < s_end)
+        };
+        BlockGraphExport {
+            genesis_blocks: self.genesis_blocks.clone(),
+            active_blocks: self
+                .active_blocks
+                .iter()
+                .filter(|(_, block)| filter(&block.header.content.slot))
+                .map(|(block_id, block)| {
+                    (
+                        *block_id,
+                        ExportCompiledBlock {
+                            header: (*block.header).clone(),
+                            children: block.children.clone(),
+                            is_final: block.is_final,
+                            provenance: block.provenance,
+                        },
+                    )
+                })
+                .collect(),
+            discarded_blocks: self
+                .discarded_blocks
+                .iter()
+                .filter(|(_, (_, (slot, _, _)))| filter(slot))
+                .map(|(block_id, discarded)| (*block_id, discarded.clone()))
+                .collect(),
+            best_parents: self.best_parents.clone(),
+            latest_final_blocks_periods: self.latest_final_blocks_periods.clone(),
+            gi_head: self.gi_head.clone(),
+            max_cliques: self.max_cliques.clone(),
+        }
+    }
+}
+
+impl ConsensusState {
+    /// Build a snapshot of the whole graph, or return `None` if the graph did not change since `previous`
+    ///
+    /// # Arguments
+    /// * `previous`: snapshot currently published, whose headers are reused
+    /// * `snapshots_generation`: generation of the published cliques and best parents
+    pub(crate) fn build_graph_export_snapshot(
+        &self,
+        previous: &GraphExportSnapshot,
+        snapshots_generation: u64,
+    ) -> Option<GraphExportSnapshot> {
+        let source_generation = (self.blocks_state.generation(), snapshots_generation);
+        if previous.source_generation == Some(source_generation) {
+            return None;
+        }
+        let mut active_blocks = PreHashMap::with_capacity(self.blocks_state.len());
+        let mut discarded_blocks = PreHashMap::with_capacity(self.blocks_state.len());
+        let stored_blocks = self.storage.read_blocks();
+        for (block_id, block) in self.blocks_state.iter() {
+            match block {
+                BlockStatus::Discarded {
+                    slot,
+                    creator,
+                    parents,
+                    reason,
+                    ..
+                } => {
+                    discarded_blocks.insert(
+                        *block_id,
+                        (reason.clone(), (*slot, *creator, parents.clone())),
+                    );
+                }
+                BlockStatus::Active {
+                    a_block,
+                    storage_or_block,
+                    ..
+                } => {
+                    let header = match previous.active_blocks.get(block_id) {
+                        Some(block) => block.header.clone(),
+                        None => Arc::new(match storage_or_block {
+                            StorageOrBlock::Storage(_)
+                            | StorageOrBlock::SharedOperations { .. } => stored_blocks
+                                .get(block_id)
+                                .expect("active block absent from the storage")
+                                .content
+                                .header
+                                .clone(),
+                            StorageOrBlock::Block(block) => block.content.header.clone(),
+                        }),
+                    };
+                    active_blocks.insert(
+                        *block_id,
+                        SnapshotBlock {
+                            header,
+                            children: a_block
+                                .children
+                                .iter()
+                                .map(|thread| {
+                                    thread.keys().copied().collect::<PreHashSet<BlockId>>()
+                                })
+                                .collect(),
+                            is_final: a_block.is_final,
+                            provenance: self.block_provenance.get(block_id).copied(),
+                        },
+                    );
+                }
+                _ => continue,
+            }
+        }
+        Some(GraphExportSnapshot {
+            source_generation: Some(source_generation),
+            genesis_blocks: self.genesis_hashes.clone(),
+            active_blocks,
+            discarded_blocks,
+            best_parents: self.best_parents.clone(),
+            latest_final_blocks_periods: self.latest_final_blocks_periods.clone(),
+            gi_head: self.gi_head.clone(),
+            max_cliques: self.max_cliques.clone(),
+        })
+    }
+}
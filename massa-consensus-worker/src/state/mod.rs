@@ -1,12 +1,24 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeSet, HashMap, VecDeque},
+    ops::RangeInclusive,
+    sync::Arc,
     vec,
 };
 
 use massa_consensus_exports::{
-    block_graph_export::BlockGraphExport,
-    block_status::{BlockStatus, ExportCompiledBlock, HeaderOrBlock, StorageOrBlock},
+    audit_log::AuditLog,
+    ban_list::CreatorBanList,
+    block_provenance::BlockProvenance,
+    block_status::{BlockCountsByStatus, BlockStatus, HeaderOrBlock, StorageOrBlock},
+    block_summary::BlockSummary,
+    catch_up::CatchUpStatus,
+    divergence::PeerBlockcliqueSummary,
     error::ConsensusError,
+    health::ConsensusHealth,
+    reorg_report::ReorgReport,
+    required_blocks::{RequiredBlockExplanation, RequiredBlockReason},
+    slot_occupancy::{SlotOccupancy, ThreadSlotOccupancy},
+    submission::SubmissionNotifier,
     ConsensusChannels, ConsensusConfig,
 };
 use massa_execution_exports::ExecutionBlockMetadata;
@@ -18,24 +30,63 @@ use massa_models::{
     block_header::SecuredHeader,
     block_id::BlockId,
     clique::Clique,
-    prehash::{CapacityAllocator, PreHashMap, PreHashSet},
+    prehash::{PreHashMap, PreHashSet},
     slot::Slot,
+    timeslots::get_latest_block_slot_at_timestamp,
 };
+use massa_pool_exports::EndorsementFeedback;
+use massa_protocol_exports::PeerId;
 use massa_storage::Storage;
 use massa_time::MassaTime;
+use parking_lot::{Mutex, RwLock};
 use tracing::debug;
 
 use self::blocks_state::BlocksState;
+use self::draw_cache::DrawCache;
+use self::endorser_stats::EndorserStats;
+pub(crate) use self::final_block_index::FinalBlockIndex;
+use self::operation_inclusions::OperationInclusions;
+use self::shared_operations::SharedOperationSets;
+pub(crate) use self::snapshots::GraphSnapshots;
+pub(crate) use self::verifications::{
+    check_endorsement_draws, check_header_creator, EndorsementsCheckOutcome,
+};
+use crate::dedup::IncomingBlockDedup;
+use crate::insertion_shards::PrecheckedHeaders;
 
+mod audit_log;
+mod best_parents_history;
+mod block_provenance;
+mod blockclique_history;
 pub mod blocks_state;
+mod bootstrap_part;
+mod catch_up;
+mod checkpoint;
 mod clique_computation;
+mod creator_bans;
+mod divergence;
+mod draw_cache;
+mod endorser_stats;
+mod final_block_index;
+// only reachable from the tests and tooling built with the test exports
+#[cfg(any(test, feature = "testing"))]
+mod forced_transitions;
 mod graph;
+mod graph_export;
+mod operation_inclusions;
+mod pages;
 mod process;
 mod process_commands;
 mod prune;
+mod repair;
+mod shared_operations;
+mod snapshots;
+mod stall_diagnostics;
 mod stats;
+mod submissions;
 mod tick;
 mod verifications;
+mod version_quarantine;
 
 #[derive(Clone)]
 pub struct ConsensusState {
@@ -52,6 +103,9 @@ pub struct ConsensusState {
     pub gi_head: PreHashMap<BlockId, PreHashSet<BlockId>>,
     /// All the cliques
     pub max_cliques: Vec<Clique>,
+    /// Whether stale block removals left max cliques of `gi_head` out of `max_cliques`,
+    /// in which case the next fork recomputes them fully
+    pub max_cliques_inexact: bool,
     /// ids of active blocks without ops
     pub active_index_without_ops: PreHashSet<BlockId>,
     /// Save of latest periods
@@ -70,6 +124,8 @@ pub struct ConsensusState {
     pub new_final_blocks: PreHashSet<BlockId>,
     /// Newly stale block mapped to creator and slot
     pub new_stale_blocks: PreHashMap<BlockId, (Address, Slot)>,
+    /// Endorsements of the newly stale blocks, to give back to the pool
+    pub new_orphaned_endorsements: Vec<EndorsementFeedback>,
     /// time at which the node was launched (used for desynchronization detection)
     pub launch_time: MassaTime,
     /// Final block stats `(time, creator, is_from_protocol)`
@@ -89,6 +145,56 @@ pub struct ConsensusState {
     /// Blocks indexed by slot (used for multi-stake limiting). Blocks
     /// should be saved in this map when we receive the header or the full block directly.
     pub nonfinal_active_blocks_per_slot: HashMap<Slot, PreHashSet<BlockId>>,
+    /// Bounded history of the blockclique compositions, with the slot at which they were adopted
+    pub blockclique_history: VecDeque<(Slot, PreHashSet<BlockId>)>,
+    /// Bounded history of the best parents, as they were after the tick of each slot
+    pub best_parents_history: VecDeque<(Slot, Vec<(BlockId, u64)>)>,
+    /// Operation sets shared by the active blocks that have the same operations
+    pub shared_operations: SharedOperationSets,
+    /// Blocks of the graph including each operation
+    pub operation_inclusions: OperationInclusions,
+    /// Impact of the latest reorganization of the blockclique on the inclusion of the operations
+    pub last_reorg_report: Option<ReorgReport>,
+    /// Number of blocks in each status, shared with the controller and refreshed on each graph change
+    pub block_counts: Arc<RwLock<BlockCountsByStatus>>,
+    /// Snapshots of the exposed cliques and best parents, shared with the controller and refreshed on each graph change
+    pub(crate) snapshots: Arc<RwLock<GraphSnapshots>>,
+    /// Health of the worker, shared with the controller and refreshed at each slot tick
+    pub health: Arc<RwLock<ConsensusHealth>>,
+    /// Catch-up mode of the worker, shared with the controller and refreshed at each slot tick
+    pub catch_up: Arc<RwLock<CatchUpStatus>>,
+    /// Latest blockclique summary received from each peer, shared with the controller that registers them
+    pub peer_blockclique_summaries: Arc<RwLock<HashMap<PeerId, PeerBlockcliqueSummary>>>,
+    /// Whether the local blockclique differed from the one of the majority of the peers at the last check
+    pub majority_divergent: bool,
+    /// Number of headers dropped since launch because their block did not arrive in time
+    pub expired_header_count: u64,
+    /// Creators whose headers are dropped because they produced invalid blocks
+    pub creator_bans: CreatorBanList,
+    /// Draws of the current cycle and of the upcoming periods, prefetched from the selector
+    pub draw_cache: DrawCache,
+    /// Endorsement draws and misses of each address over the latest cycles, counted on the final blocks
+    pub endorser_stats: EndorserStats,
+    /// Origin and first-seen time of the blocks of the graph
+    pub block_provenance: PreHashMap<BlockId, BlockProvenance>,
+    /// Headers of network versions not activated yet, registered once their version is active at their slot
+    pub quarantined_headers: PreHashMap<BlockId, SecuredHeader>,
+    /// Time at which the latest checkpoint was written to the node database
+    pub last_checkpoint: Option<MassaTime>,
+    /// Time at which blocks last became final, or at which the worker was launched if none did since
+    pub last_finalization: MassaTime,
+    /// Whether the diagnostics of the ongoing finality stall were already collected
+    pub finality_stall_reported: bool,
+    /// Recently registered blocks, shared with the controller that drops their duplicate registrations
+    pub(crate) incoming_block_dedup: Arc<Mutex<IncomingBlockDedup>>,
+    /// Notifiers of the submission tickets of the followed blocks, shared with the controller that issues the tickets
+    pub(crate) submission_notifiers: Arc<Mutex<PreHashMap<BlockId, SubmissionNotifier>>>,
+    /// Headers whose graph-free checks were passed in the insertion shards, shared with the shards
+    pub(crate) prechecked_headers: Arc<Mutex<PrecheckedHeaders>>,
+    /// Hash-chained log of the consensus decisions, `None` if disabled
+    pub audit_log: Option<Arc<Mutex<AuditLog>>>,
+    /// On-disk index of the final block ids by slot, `None` if disabled
+    pub(crate) final_block_index: Option<Arc<FinalBlockIndex>>,
     /// massa metrics
     pub(crate) massa_metrics: MassaMetrics,
 }
@@ -103,6 +209,7 @@ impl ConsensusState {
             Some(BlockStatus::Active {
                 a_block,
                 storage_or_block,
+                ..
             }) => Some((a_block.as_ref(), storage_or_block)),
             _ => None,
         }
@@ -120,11 +227,23 @@ impl ConsensusState {
         })
     }
 
+    /// Apply the runtime-reloadable parameters of a new configuration.
+    /// Fails without applying anything if a structural parameter differs.
+    pub fn update_config(&mut self, new_config: &ConsensusConfig) -> Result<(), ConsensusError> {
+        self.config.apply_reloadable(new_config)?;
+        self.stats_history_timespan = std::cmp::max(
+            self.stats_desync_detection_timespan,
+            self.config.stats_timespan,
+        );
+        Ok(())
+    }
+
     pub fn get_clique_count(&self) -> usize {
         self.max_cliques.len()
     }
 
-    /// get the blockclique (or final) block ID at a given slot, if any
+    /// get the blockclique (or final) block ID at a given slot, if any.
+    /// The final blocks pruned from memory are found in the final block index if it is enabled.
     pub fn get_blockclique_block_at_slot(&self, slot: &Slot) -> Option<BlockId> {
         // List all blocks at this slot.
         // The list should be small: make a copy of it to avoid holding the storage lock.
@@ -132,7 +251,8 @@ impl ConsensusState {
             let storage_read = self.storage.read_blocks();
             let returned = match storage_read.get_blocks_by_slot(slot) {
                 Some(v) => v.clone(),
-                None => return None,
+                // the final blocks pruned from memory are looked up in the index
+                None => return self.final_block_index.as_ref()?.get(slot),
             };
             returned
         };
@@ -159,6 +279,7 @@ impl ConsensusState {
                 Some(BlockStatus::Active { a_block, .. }) => a_block.is_final,
                 _ => false,
             })
+            .or_else(|| self.final_block_index.as_ref()?.get(slot))
     }
 
     /// get the latest blockclique (or final) block ID at a given slot, if any
@@ -192,6 +313,44 @@ impl ConsensusState {
         best_block_id
     }
 
+    /// get the latest blockclique (or final) block whose slot timestamp is at or before a given timestamp.
+    /// Missed slots are skipped. Returns `None` if no such block is still in the graph.
+    pub fn get_latest_blockclique_block_before_time(
+        &self,
+        timestamp: MassaTime,
+    ) -> Result<Option<BlockId>, ConsensusError> {
+        let Some(max_slot) = get_latest_block_slot_at_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            timestamp,
+        )?
+        else {
+            return Ok(None);
+        };
+
+        // blockclique and final blocks sorted by slot
+        let blockclique = self.get_blockclique();
+        let mut blocks: Vec<(Slot, BlockId)> = self
+            .blocks_state
+            .active_blocks()
+            .iter()
+            .filter_map(|block_id| match self.blocks_state.get(block_id) {
+                Some(BlockStatus::Active { a_block, .. })
+                    if a_block.is_final || blockclique.contains(block_id) =>
+                {
+                    Some((a_block.slot, *block_id))
+                }
+                _ => None,
+            })
+            .collect();
+        blocks.sort_unstable();
+
+        // there is at most one blockclique or final block per slot
+        let count_before = blocks.partition_point(|(slot, _)| *slot <= max_slot);
+        Ok(count_before.checked_sub(1).map(|index| blocks[index].1))
+    }
+
     pub fn get_block_status(&self, block_id: &BlockId) -> BlockGraphStatus {
         match self.blocks_state.get(block_id) {
             None => BlockGraphStatus::NotFound,
@@ -220,6 +379,28 @@ impl ConsensusState {
         }
     }
 
+    /// Get the status of a block along with a storage referencing it, its endorsements and its operations
+    ///
+    /// Returns `None` if the block is unknown, discarded, only known by its header or if its operations were dropped
+    pub fn get_block_with_storage(
+        &self,
+        block_id: &BlockId,
+    ) -> Option<(BlockGraphStatus, Storage)> {
+        let storage = match self.blocks_state.get(block_id)? {
+            BlockStatus::Active {
+                storage_or_block, ..
+            } => storage_or_block.clone_storage()?,
+            BlockStatus::Incoming(HeaderOrBlock::Block { storage, .. })
+            | BlockStatus::WaitingForSlot(HeaderOrBlock::Block { storage, .. })
+            | BlockStatus::WaitingForDependencies {
+                header_or_block: HeaderOrBlock::Block { storage, .. },
+                ..
+            } => storage.clone(),
+            _ => return None,
+        };
+        Some((self.get_block_status(block_id), storage))
+    }
+
     /// list the latest final blocks at the given slot
     ///
     /// exclusively used by `list_required_active_blocks`
@@ -296,9 +477,10 @@ impl ConsensusState {
     /// exclusively used by `list_required_active_blocks`
     fn add_active_blocks_after(
         &self,
-        kept_blocks: &mut PreHashSet<BlockId>,
+        kept_blocks: &mut PreHashMap<BlockId, RequiredBlockReason>,
         lower_bound: &[(BlockId, u64)],
         end_slot: Option<Slot>,
+        reason: RequiredBlockReason,
     ) {
         for id in self.blocks_state.active_blocks().iter() {
             if let Some((block, _storage)) = self.get_full_active_block(id) {
@@ -308,7 +490,7 @@ impl ConsensusState {
                     }
                 }
                 if block.slot.period >= lower_bound[block.slot.thread as usize].1 {
-                    kept_blocks.insert(*id);
+                    kept_blocks.entry(*id).or_insert_with(|| reason.clone());
                 }
             }
         }
@@ -333,6 +515,43 @@ impl ConsensusState {
         &self,
         end_slot: Option<Slot>,
     ) -> Result<PreHashSet<BlockId>, ConsensusError> {
+        let kept_blocks: PreHashSet<BlockId> = self
+            .list_required_active_blocks_with_reasons(end_slot)?
+            .into_keys()
+            .collect();
+
+        // debug log for an easier diagnostic if needed
+        debug!("list_required_active_blocks return: {:?}", kept_blocks);
+
+        Ok(kept_blocks)
+    }
+
+    /// Same as `list_required_active_blocks`, with the reason why each block is retained,
+    /// sorted by slot
+    pub fn list_required_active_blocks_explained(
+        &self,
+        end_slot: Option<Slot>,
+    ) -> Result<Vec<RequiredBlockExplanation>, ConsensusError> {
+        let mut explained = self
+            .list_required_active_blocks_with_reasons(end_slot)?
+            .into_iter()
+            .map(|(block_id, reason)| {
+                Ok(RequiredBlockExplanation {
+                    block_id,
+                    slot: self.try_get_full_active_block(&block_id)?.0.slot,
+                    reason,
+                })
+            })
+            .collect::<Result<Vec<_>, ConsensusError>>()?;
+        explained.sort_unstable_by_key(|explanation| (explanation.slot, explanation.block_id));
+        Ok(explained)
+    }
+
+    /// Implementation of `list_required_active_blocks`, keeping the first reason each block was retained for
+    fn list_required_active_blocks_with_reasons(
+        &self,
+        end_slot: Option<Slot>,
+    ) -> Result<PreHashMap<BlockId, RequiredBlockReason>, ConsensusError> {
         // if an end_slot is provided compute the lastest final block for that given slot
         // if not use the latest_final_blocks_periods
         let effective_latest_finals: Vec<(BlockId, u64)> = if let Some(slot) = end_slot {
@@ -342,116 +561,146 @@ impl ConsensusState {
         };
 
         // init kept_blocks using effective_latest_finals
-        let mut kept_blocks: PreHashSet<BlockId> = effective_latest_finals
+        let mut kept_blocks: PreHashMap<BlockId, RequiredBlockReason> = effective_latest_finals
             .iter()
-            .map(|(id, _period)| *id)
+            .map(|(id, _period)| (*id, RequiredBlockReason::LatestFinal))
             .collect();
 
         // add all the active blocks that are after the effective_latest_finals of their thread
-        self.add_active_blocks_after(&mut kept_blocks, &effective_latest_finals, end_slot);
+        self.add_active_blocks_after(
+            &mut kept_blocks,
+            &effective_latest_finals,
+            end_slot,
+            RequiredBlockReason::AfterLatestFinal,
+        );
 
         // do the following 2 times
         for _ in 0..2 {
             // extend kept_blocks with the parents of the current kept_blocks
-            let mut cumulated_parents: PreHashSet<BlockId> = PreHashSet::default();
-            for id in kept_blocks.iter() {
+            let mut cumulated_parents: PreHashMap<BlockId, RequiredBlockReason> =
+                PreHashMap::default();
+            for id in kept_blocks.keys() {
                 let parents = self
                     .try_get_full_active_block(id)?
                     .0
                     .parents
                     .iter()
                     .map(|(id, _period)| *id);
-                cumulated_parents.extend(parents);
+                for parent in parents {
+                    cumulated_parents
+                        .entry(parent)
+                        .or_insert(RequiredBlockReason::ParentOfRequired { child: *id });
+                }
+            }
+            for (parent, reason) in cumulated_parents {
+                kept_blocks.entry(parent).or_insert(reason);
             }
-            kept_blocks.extend(cumulated_parents);
             // add all the active blocks whose slot is after the earliest kept_blocks of their thread
-            let earliest_blocks = self.list_earliest_blocks_of(&kept_blocks, end_slot)?;
-            self.add_active_blocks_after(&mut kept_blocks, &earliest_blocks, end_slot);
+            let kept_ids: PreHashSet<BlockId> = kept_blocks.keys().copied().collect();
+            let earliest_blocks = self.list_earliest_blocks_of(&kept_ids, end_slot)?;
+            self.add_active_blocks_after(
+                &mut kept_blocks,
+                &earliest_blocks,
+                end_slot,
+                RequiredBlockReason::FillsHole,
+            );
         }
 
         // check that we have the full blocks for every id we are about to return
-        for id in kept_blocks.iter() {
+        for id in kept_blocks.keys() {
             self.try_get_full_active_block(id)?;
         }
 
-        // debug log for an easier diagnostic if needed
-        debug!("list_required_active_blocks return: {:?}", kept_blocks);
-
-        // return kept_blocks
         Ok(kept_blocks)
     }
 
-    pub fn extract_block_graph_part(
-        &self,
-        slot_start: Option<Slot>,
-        slot_end: Option<Slot>,
-    ) -> Result<BlockGraphExport, ConsensusError> {
-        let mut export = BlockGraphExport {
-            genesis_blocks: self.genesis_hashes.clone(),
-            active_blocks: PreHashMap::with_capacity(self.blocks_state.len()),
-            discarded_blocks: PreHashMap::with_capacity(self.blocks_state.len()),
-            best_parents: self.best_parents.clone(),
-            latest_final_blocks_periods: self.latest_final_blocks_periods.clone(),
-            gi_head: self.gi_head.clone(),
-            max_cliques: self.max_cliques.clone(),
-        };
+    /// Get the summaries of the active blocks whose slot is in a given range, sorted by slot
+    pub fn get_block_summaries(&self, slot_range: &RangeInclusive<Slot>) -> Vec<BlockSummary> {
+        let mut summaries: Vec<BlockSummary> = self
+            .blocks_state
+            .active_blocks()
+            .iter()
+            .filter_map(|block_id| match self.blocks_state.get(block_id) {
+                Some(BlockStatus::Active {
+                    a_block, summary, ..
+                }) if slot_range.contains(&a_block.slot) => Some(summary.clone()),
+                _ => None,
+            })
+            .collect();
+        summaries.sort_unstable_by_key(|summary| (summary.slot, summary.block_id));
+        summaries
+    }
 
-        let filter = |&s| {
-            if let Some(s_start) = slot_start {
-                if s < s_start {
-                    return false;
-                }
-            }
-            if let Some(s_end) = slot_end {
-                if s >= s_end {
-                    return false;
-                }
+    /// Get the filled and missed slots of a cycle per thread.
+    /// For each thread, only the slots between the oldest final block still in the graph
+    /// and the latest final block are inspected, as older blocks may have been pruned.
+    /// Genesis slots are not counted.
+    pub fn get_slot_occupancy(&self, cycle: u64) -> Result<SlotOccupancy, ConsensusError> {
+        let cycle_first_period =
+            Slot::new_first_of_cycle(cycle, self.config.periods_per_cycle)?.period;
+        let cycle_last_period = Slot::new_last_of_cycle(
+            cycle,
+            self.config.periods_per_cycle,
+            self.config.thread_count,
+        )?
+        .period;
+
+        // periods of the final blocks of each thread that are still in the graph
+        let mut final_periods: Vec<BTreeSet<u64>> =
+            vec![BTreeSet::new(); self.config.thread_count as usize];
+        let mut oldest_covered_periods: Vec<Option<u64>> =
+            vec![None; self.config.thread_count as usize];
+        for block_id in self.blocks_state.active_blocks() {
+            let Some(BlockStatus::Active { a_block, .. }) = self.blocks_state.get(block_id) else {
+                continue;
+            };
+            if !a_block.is_final {
+                continue;
             }
-            true
-        };
+            let thread = a_block.slot.thread as usize;
+            let covered_from = if self.genesis_hashes.contains(block_id) {
+                a_block.slot.period.saturating_add(1)
+            } else {
+                final_periods[thread].insert(a_block.slot.period);
+                a_block.slot.period
+            };
+            let oldest = oldest_covered_periods[thread].get_or_insert(covered_from);
+            *oldest = (*oldest).min(covered_from);
+        }
 
-        for (block_id, block) in self.blocks_state.iter() {
-            match block {
-                BlockStatus::Discarded {
-                    slot,
-                    creator,
-                    parents,
-                    reason,
-                    ..
-                } => {
-                    if filter(slot) {
-                        export.discarded_blocks.insert(
-                            *block_id,
-                            (reason.clone(), (*slot, *creator, parents.clone())),
-                        );
-                    }
-                }
-                BlockStatus::Active {
-                    a_block,
-                    storage_or_block,
-                } => {
-                    if filter(&a_block.slot) {
-                        export.active_blocks.insert(
-                            *block_id,
-                            ExportCompiledBlock {
-                                header: storage_or_block.clone_block(block_id).content.header,
-                                children: a_block
-                                    .children
-                                    .iter()
-                                    .map(|thread| {
-                                        thread.keys().copied().collect::<PreHashSet<BlockId>>()
-                                    })
-                                    .collect(),
-                                is_final: a_block.is_final,
-                            },
-                        );
-                    }
+        let mut threads = Vec::with_capacity(self.config.thread_count as usize);
+        for thread in 0..self.config.thread_count {
+            let mut occupancy = ThreadSlotOccupancy {
+                thread,
+                ..Default::default()
+            };
+            let Some(oldest_covered_period) = oldest_covered_periods[thread as usize] else {
+                threads.push(occupancy);
+                continue;
+            };
+            let first_period = cycle_first_period.max(oldest_covered_period);
+            let last_period =
+                cycle_last_period.min(self.latest_final_blocks_periods[thread as usize].1);
+            if first_period > last_period {
+                threads.push(occupancy);
+                continue;
+            }
+            occupancy.covered_periods = Some((first_period, last_period));
+            for period in first_period..=last_period {
+                if final_periods[thread as usize].contains(&period) {
+                    occupancy.filled_slots += 1;
+                    continue;
                 }
-                _ => continue,
+                occupancy.missed_slots += 1;
+                let producer = self
+                    .get_producer(Slot::new(period, thread))
+                    .map_err(|err| ConsensusError::PosCycleUnavailable(err.to_string()))?;
+                *occupancy.misses_by_address.entry(producer).or_default() += 1;
             }
+            threads.push(occupancy);
         }
 
-        Ok(export)
+        Ok(SlotOccupancy { cycle, threads })
     }
 
     /// Gets all stored final blocks, not only the still-useful ones
@@ -466,15 +715,13 @@ impl ConsensusState {
                 if let Some(BlockStatus::Active {
                     a_block,
                     storage_or_block,
+                    ..
                 }) = self.blocks_state.get(b_id)
                 {
                     if !a_block.is_final {
                         return None;
                     }
-                    let storage = match storage_or_block {
-                        StorageOrBlock::Storage(storage) => Some(storage.clone()),
-                        _ => None,
-                    };
+                    let storage = storage_or_block.clone_storage();
                     return Some((
                         *b_id,
                         (
@@ -491,6 +738,53 @@ impl ConsensusState {
             .collect()
     }
 
+    /// Get the header of a block known by the graph, as it was registered
+    ///
+    /// Discarded blocks are not kept with their header.
+    pub(crate) fn get_secured_header(&self, block_id: &BlockId) -> Option<SecuredHeader> {
+        let header_or_block = match self.blocks_state.get(block_id) {
+            Some(BlockStatus::Active {
+                storage_or_block, ..
+            }) => return Some(storage_or_block.clone_block(block_id).content.header),
+            Some(
+                BlockStatus::Incoming(header_or_block)
+                | BlockStatus::WaitingForSlot(header_or_block)
+                | BlockStatus::WaitingForDependencies {
+                    header_or_block, ..
+                },
+            ) => header_or_block,
+            Some(BlockStatus::Discarded { .. }) => return None,
+            None => return self.quarantined_headers.get(block_id).cloned(),
+        };
+        match header_or_block {
+            HeaderOrBlock::Header(header) => Some(header.clone()),
+            HeaderOrBlock::Block { storage, .. } => storage
+                .read_blocks()
+                .get(block_id)
+                .map(|block| block.content.header.clone()),
+        }
+    }
+
+    /// List the blocks known by their header only, with their slot
+    pub(crate) fn header_only_blocks(&self) -> impl Iterator<Item = (BlockId, Slot)> + '_ {
+        self.blocks_state
+            .incoming_blocks()
+            .iter()
+            .chain(self.blocks_state.waiting_for_slot_blocks().iter())
+            .chain(self.blocks_state.waiting_for_dependencies_blocks().iter())
+            .filter_map(|block_id| match self.blocks_state.get(block_id) {
+                Some(
+                    BlockStatus::Incoming(HeaderOrBlock::Header(header))
+                    | BlockStatus::WaitingForSlot(HeaderOrBlock::Header(header))
+                    | BlockStatus::WaitingForDependencies {
+                        header_or_block: HeaderOrBlock::Header(header),
+                        ..
+                    },
+                ) => Some((*block_id, header.content.slot)),
+                _ => None,
+            })
+    }
+
     /// get the current block wish list, including the operations hash.
     pub fn get_block_wishlist(
         &self,
@@ -0,0 +1,178 @@
+use massa_models::{
+    block::BlockGraphStatus,
+    block_id::BlockId,
+    operation::OperationId,
+    prehash::{PreHashMap, PreHashSet},
+};
+
+use super::ConsensusState;
+
+/// Index of the blocks of the graph that include each operation
+#[derive(Clone, Default)]
+pub struct OperationInclusions {
+    /// ids of the blocks including each operation
+    by_operation: PreHashMap<OperationId, PreHashSet<BlockId>>,
+    /// operations of each indexed block
+    by_block: PreHashMap<BlockId, Vec<OperationId>>,
+}
+
+impl OperationInclusions {
+    /// Index the operations of a block
+    pub fn insert(&mut self, block_id: BlockId, operations: Vec<OperationId>) {
+        for op_id in operations.iter() {
+            self.by_operation
+                .entry(*op_id)
+                .or_default()
+                .insert(block_id);
+        }
+        self.by_block.insert(block_id, operations);
+    }
+
+    /// Forget the operations of a block
+    pub fn remove(&mut self, block_id: &BlockId) {
+        let Some(operations) = self.by_block.remove(block_id) else {
+            return;
+        };
+        for op_id in operations.iter() {
+            if let Some(block_ids) = self.by_operation.get_mut(op_id) {
+                block_ids.remove(block_id);
+                if block_ids.is_empty() {
+                    self.by_operation.remove(op_id);
+                }
+            }
+        }
+    }
+
+    /// Get the ids of the indexed blocks including an operation
+    pub fn get(&self, op_id: &OperationId) -> Option<&PreHashSet<BlockId>> {
+        self.by_operation.get(op_id)
+    }
+
+    /// List the operations of the `removed` blocks that are not included anymore
+    ///
+    /// # Arguments
+    /// * `removed`: blocks that stopped including their operations
+    /// * `includes`: whether an indexed block still includes its operations
+    ///
+    /// # Returns
+    /// The operations of the removed blocks included by no other block, sorted
+    pub fn reverted_operations(
+        &self,
+        removed: &PreHashSet<BlockId>,
+        includes: impl Fn(&BlockId) -> bool,
+    ) -> Vec<OperationId> {
+        let mut reverted: Vec<OperationId> = removed
+            .iter()
+            .filter_map(|block_id| self.by_block.get(block_id))
+            .flatten()
+            .filter(|op_id| {
+                !self.by_operation.get(op_id).map_or(false, |block_ids| {
+                    block_ids
+                        .iter()
+                        .any(|block_id| !removed.contains(block_id) && includes(block_id))
+                })
+            })
+            .copied()
+            .collect();
+        reverted.sort_unstable();
+        reverted.dedup();
+        reverted
+    }
+}
+
+impl ConsensusState {
+    /// Index the operations of an active block
+    pub fn index_block_operations(&mut self, block_id: &BlockId) {
+        let Some((_, storage_or_block)) = self.get_full_active_block(block_id) else {
+            return;
+        };
+        let operations = storage_or_block.clone_block(block_id).content.operations;
+        self.operation_inclusions.insert(*block_id, operations);
+    }
+
+    /// Forget the operations of the blocks that are not active anymore
+    pub fn prune_operation_inclusions(&mut self) {
+        let removed: Vec<BlockId> = self
+            .operation_inclusions
+            .by_block
+            .keys()
+            .filter(|block_id| !self.blocks_state.active_blocks().contains(block_id))
+            .copied()
+            .collect();
+        for block_id in removed.iter() {
+            self.operation_inclusions.remove(block_id);
+        }
+    }
+
+    /// Get the blocks of the graph that include an operation, along with their status
+    ///
+    /// Final blocks that were pruned from the graph are not listed.
+    pub fn get_operation_inclusion(&self, op_id: &OperationId) -> Vec<(BlockId, BlockGraphStatus)> {
+        let Some(block_ids) = self.operation_inclusions.get(op_id) else {
+            return Vec::new();
+        };
+        let mut res: Vec<(BlockId, BlockGraphStatus)> = block_ids
+            .iter()
+            .map(|block_id| (*block_id, self.get_block_status(block_id)))
+            .collect();
+        res.sort_unstable_by_key(|(block_id, _)| *block_id);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_models::secure_share::Id;
+
+    #[test]
+    fn test_operation_inclusions() {
+        let block_a = BlockId::generate_from_hash(Hash::compute_from(b"a"));
+        let block_b = BlockId::generate_from_hash(Hash::compute_from(b"b"));
+        let op_1 = OperationId::new(Hash::compute_from(b"1"));
+        let op_2 = OperationId::new(Hash::compute_from(b"2"));
+
+        let mut inclusions = OperationInclusions::default();
+        inclusions.insert(block_a, vec![op_1, op_2]);
+        inclusions.insert(block_b, vec![op_1]);
+        assert_eq!(
+            inclusions.get(&op_1),
+            Some(&PreHashSet::from_iter([block_a, block_b]))
+        );
+
+        inclusions.remove(&block_a);
+        assert_eq!(
+            inclusions.get(&op_1),
+            Some(&PreHashSet::from_iter([block_b]))
+        );
+        assert_eq!(inclusions.get(&op_2), None);
+        assert!(!inclusions.by_block.contains_key(&block_a));
+    }
+
+    #[test]
+    fn test_reverted_operations() {
+        let block_a = BlockId::generate_from_hash(Hash::compute_from(b"a"));
+        let block_b = BlockId::generate_from_hash(Hash::compute_from(b"b"));
+        let block_c = BlockId::generate_from_hash(Hash::compute_from(b"c"));
+        let op_1 = OperationId::new(Hash::compute_from(b"1"));
+        let op_2 = OperationId::new(Hash::compute_from(b"2"));
+        let op_3 = OperationId::new(Hash::compute_from(b"3"));
+
+        let mut inclusions = OperationInclusions::default();
+        inclusions.insert(block_a, vec![op_1, op_2]);
+        inclusions.insert(block_b, vec![op_2, op_3]);
+        inclusions.insert(block_c, vec![op_1]);
+
+        // block_a leaves the blockclique, block_b joins it, block_c is not in the blockclique
+        let removed = PreHashSet::from_iter([block_a]);
+        let reverted = inclusions.reverted_operations(&removed, |block_id| *block_id == block_b);
+        assert_eq!(reverted, vec![op_1]);
+
+        // a removed block does not keep the operations it shares with another removed block
+        let removed = PreHashSet::from_iter([block_a, block_b]);
+        let mut expected = vec![op_2, op_3];
+        expected.sort_unstable();
+        assert_eq!(inclusions.reverted_operations(&removed, |_| true), expected);
+    }
+}
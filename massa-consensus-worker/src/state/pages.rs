@@ -0,0 +1,137 @@
+use massa_consensus_exports::{
+    block_filter::BlockFilter,
+    block_status::{BlockStatus, DiscardReason, ExportCompiledBlock},
+    block_summary::BlockSummary,
+    stale_block::StaleBlock,
+};
+use massa_models::{
+    block_id::BlockId,
+    pagination::{PageKey, PageRequest, PageResponse},
+    prehash::PreHashSet,
+    secure_share::Id,
+    slot::Slot,
+};
+
+use super::ConsensusState;
+
+/// Position of a block in the paginated lists of blocks
+fn block_page_key(slot: Slot, block_id: &BlockId) -> PageKey {
+    PageKey::new(slot, block_id.get_hash().to_bytes().to_vec())
+}
+
+impl ConsensusState {
+    /// Get a page of the active blocks, ordered by slot then block id
+    pub fn get_block_graph_page(
+        &self,
+        request: &PageRequest,
+    ) -> PageResponse<(BlockId, ExportCompiledBlock)> {
+        let start_slot = request.start_slot().unwrap_or(Slot::new(0, 0));
+        let mut keyed_ids: Vec<(PageKey, BlockId)> = self
+            .blocks_state
+            .active_blocks()
+            .iter()
+            .filter_map(|block_id| match self.blocks_state.get(block_id) {
+                Some(BlockStatus::Active { a_block, .. }) if a_block.slot >= start_slot => {
+                    Some((block_page_key(a_block.slot, block_id), *block_id))
+                }
+                _ => None,
+            })
+            .collect();
+        keyed_ids.sort_unstable_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+
+        // only the blocks of the page are exported
+        PageResponse::from_sorted(keyed_ids, request).map(|block_id| {
+            let (a_block, storage_or_block) = self
+                .get_full_active_block(&block_id)
+                .expect("paginated active block is missing");
+            let export = ExportCompiledBlock {
+                header: storage_or_block.clone_block(&block_id).content.header,
+                children: a_block
+                    .children
+                    .iter()
+                    .map(|thread| thread.keys().copied().collect::<PreHashSet<BlockId>>())
+                    .collect(),
+                is_final: a_block.is_final,
+                provenance: self.block_provenance.get(&block_id).copied(),
+            };
+            (block_id, export)
+        })
+    }
+
+    /// Get a page of the stale blocks remembered among the discarded blocks, ordered by slot then block id
+    pub fn get_stale_blocks(&self, request: &PageRequest) -> PageResponse<StaleBlock> {
+        let start_slot = request.start_slot().unwrap_or(Slot::new(0, 0));
+        let mut stale_blocks: Vec<(PageKey, StaleBlock)> = self
+            .blocks_state
+            .discarded_blocks()
+            .iter()
+            .filter_map(|block_id| match self.blocks_state.get(block_id) {
+                Some(BlockStatus::Discarded {
+                    slot,
+                    creator,
+                    parents,
+                    reason: DiscardReason::Stale,
+                    ..
+                }) if *slot >= start_slot => Some((
+                    block_page_key(*slot, block_id),
+                    StaleBlock {
+                        block_id: *block_id,
+                        slot: *slot,
+                        creator: *creator,
+                        parents: parents.clone(),
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+        stale_blocks.sort_unstable_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+        PageResponse::from_sorted(stale_blocks, request)
+    }
+
+    /// Get a page of the active blocks matching a filter, ordered by slot then block id.
+    /// When filtering by parent, only the children of the parent are inspected.
+    pub fn find_blocks(
+        &self,
+        filter: &BlockFilter,
+        request: &PageRequest,
+    ) -> PageResponse<BlockSummary> {
+        let start_slot = request
+            .start_slot()
+            .into_iter()
+            .chain(filter.start_slot)
+            .max()
+            .unwrap_or(Slot::new(0, 0));
+        let candidates: PreHashSet<BlockId> = match filter.parent {
+            Some(parent) => match self.blocks_state.get(&parent) {
+                Some(BlockStatus::Active { a_block, .. }) => a_block
+                    .children
+                    .iter()
+                    .flat_map(|thread_children| thread_children.keys().copied())
+                    .collect(),
+                _ => PreHashSet::default(),
+            },
+            None => self.blocks_state.active_blocks().clone(),
+        };
+        let mut found: Vec<(PageKey, BlockSummary)> = candidates
+            .iter()
+            .filter_map(|block_id| match self.blocks_state.get(block_id) {
+                Some(BlockStatus::Active {
+                    a_block, summary, ..
+                }) if a_block.slot >= start_slot
+                    && filter.matches(
+                        &a_block.creator_address,
+                        &a_block.slot,
+                        summary.endorsement_count,
+                        &a_block.parents,
+                        a_block.is_final,
+                    ) =>
+                {
+                    Some((block_page_key(a_block.slot, block_id), summary.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        found.sort_unstable_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+        PageResponse::from_sorted(found, request)
+    }
+}
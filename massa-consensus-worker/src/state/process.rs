@@ -4,8 +4,10 @@ use std::{
 };
 
 use massa_consensus_exports::{
-    block_status::{BlockStatus, DiscardReason, HeaderOrBlock, StorageOrBlock},
+    audit_log::AuditEvent,
+    block_status::{BlockStatus, DiscardReason, HeaderOrBlock},
     error::ConsensusError,
+    reorg_report::ReorgReport,
 };
 use massa_execution_exports::ExecutionBlockMetadata;
 use massa_logging::massa_trace;
@@ -14,7 +16,9 @@ use massa_models::{
     address::Address,
     block_header::SecuredHeader,
     block_id::BlockId,
+    block_reward::BlockRewardSplit,
     clique::Clique,
+    node_event::NodeEvent,
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
     timeslots,
@@ -22,10 +26,13 @@ use massa_models::{
 use massa_signature::PublicKey;
 use massa_storage::Storage;
 use massa_time::MassaTime;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::state::{
-    clique_computation::compute_max_cliques,
+    clique_computation::{
+        compute_max_cliques, max_cliques_exact_after_stale_removal, same_cliques,
+        update_max_cliques,
+    },
     verifications::{BlockCheckOutcome, HeaderCheckOutcome},
 };
 
@@ -168,9 +175,11 @@ impl ConsensusState {
                             current_slot,
                         );
                         match &res {
-                            HeaderCheckOutcome::Discard(reason) => {
-                                self.maybe_note_attack_attempt(reason, &block_id)
-                            }
+                            HeaderCheckOutcome::Discard(reason) => self.maybe_note_attack_attempt(
+                                reason,
+                                &block_id,
+                                &stored_block.content.header,
+                            ),
                             _ => {
                                 if self.detect_multistake(&stored_block.content.header) {
                                     return Ok(BTreeSet::new());
@@ -185,6 +194,7 @@ impl ConsensusState {
                         )
                     };
                 let mut block_infos = None;
+                let mut discarded = None;
                 let sequence_number = self.blocks_state.sequence_counter();
                 self.blocks_state
                     .transition_map(&block_id, |block_status, _| {
@@ -244,6 +254,12 @@ impl ConsensusState {
                                             block_id,
                                             (header.content_creator_address, header.content.slot),
                                         );
+                                    } else {
+                                        discarded = Some(AuditEvent::BlockDiscarded {
+                                            block_id,
+                                            slot: header.content.slot,
+                                            reason: reason.clone(),
+                                        });
                                     }
                                     // discard
                                     Some(BlockCheckOutcome::BlockStatus(BlockStatus::Discarded {
@@ -264,7 +280,10 @@ impl ConsensusState {
                                         infos.inherited_incompatibilities_count,
                                     ));
 
+                                    let storage_or_block =
+                                        self.shared_operations.share(infos.storage);
                                     Some(BlockStatus::Active {
+                                        summary: storage_or_block.summarize(&block_id),
                                         a_block: Box::new(ActiveBlock {
                                             creator_address: Address::from_public_key(
                                                 &infos.creator,
@@ -281,7 +300,7 @@ impl ConsensusState {
                                             fitness: infos.fitness,
                                             same_thread_parent_creator: None, // added below in add_block_to_graph
                                         }),
-                                        storage_or_block: StorageOrBlock::Storage(infos.storage),
+                                        storage_or_block,
                                     })
                                 }
                                 Some(BlockCheckOutcome::BlockStatus(status)) => Some(status),
@@ -294,6 +313,9 @@ impl ConsensusState {
                             )
                         }
                     });
+                if let Some(event) = discarded {
+                    self.audit(event);
+                }
                 match block_infos {
                     Some(valid_block_infos) => {
                         if let Err(err) = self.add_block_to_graph(
@@ -305,6 +327,10 @@ impl ConsensusState {
                         ) {
                             panic!("error adding block to graph: {:?}", err);
                         }
+                        self.audit(AuditEvent::BlockAccepted {
+                            block_id,
+                            slot: valid_block_infos.1,
+                        });
                     }
                     None => return Ok(BTreeSet::new()),
                 }
@@ -371,15 +397,16 @@ impl ConsensusState {
         };
 
         // if the block was added, update linked dependencies and mark satisfied ones for recheck
-        if let Some(BlockStatus::Active {
-            storage_or_block: StorageOrBlock::Storage(storage),
-            ..
-        }) = self.blocks_state.get(&block_id)
-        {
+        if let Some(storage) = match self.blocks_state.get(&block_id) {
+            Some(BlockStatus::Active {
+                storage_or_block, ..
+            }) => storage_or_block.clone_storage(),
+            _ => None,
+        } {
             massa_trace!("consensus.block_graph.process.is_active", {
                 "block_id": block_id
             });
-            self.to_propagate.insert(block_id, storage.clone());
+            self.to_propagate.insert(block_id, storage);
             for itm_block_id in self
                 .blocks_state
                 .waiting_for_dependencies_blocks()
@@ -429,6 +456,8 @@ impl ConsensusState {
             "block_id": add_block_id
         });
 
+        self.index_block_operations(&add_block_id);
+
         // add as child to parents
         // add as descendant to ancestors. Note: descendants are never removed.
         self.insert_parents_descendants(
@@ -471,13 +500,39 @@ impl ConsensusState {
                     c.block_ids.insert(add_block_id);
                 });
         } else {
-            // fully recompute max cliques
+            // incrementally update max cliques
             massa_trace!(
-                "consensus.block_graph.add_block_to_graph.clique_full_computing",
+                "consensus.block_graph.add_block_to_graph.clique_incremental_computing",
                 { "hash": add_block_id }
             );
             let before = self.max_cliques.len();
-            self.max_cliques = compute_max_cliques(&self.gi_head)
+            let previous_cliques: Vec<PreHashSet<BlockId>> = self
+                .max_cliques
+                .iter()
+                .map(|c| c.block_ids.clone())
+                .collect();
+            // the incremental update relies on the previous cliques being exactly the max cliques of the graph,
+            // recompute them fully if stale removals left some out
+            let mut cliques = if std::mem::take(&mut self.max_cliques_inexact) {
+                compute_max_cliques(&self.gi_head)
+            } else {
+                update_max_cliques(&previous_cliques, add_block_id, &incomp)
+            };
+            // checked fallback
+            let consistent = cliques
+                .iter()
+                .all(|c| c.iter().all(|id| self.gi_head.contains_key(id)));
+            if !consistent
+                || (cfg!(debug_assertions)
+                    && !same_cliques(&cliques, &compute_max_cliques(&self.gi_head)))
+            {
+                warn!(
+                    "incremental clique update inconsistent after adding {}: fully recomputing cliques",
+                    add_block_id
+                );
+                cliques = compute_max_cliques(&self.gi_head);
+            }
+            self.max_cliques = cliques
                 .into_iter()
                 .map(|c| Clique {
                     block_ids: c,
@@ -502,30 +557,14 @@ impl ConsensusState {
         // compute clique fitnesses and find blockclique
         massa_trace!("consensus.block_graph.add_block_to_graph.compute_clique_fitnesses_and_find_blockclique", {});
         // note: clique_fitnesses is pair (fitness, -hash_sum) where the second parameter is negative for sorting
-        let position_blockclique = self.compute_fitness_find_blockclique(&add_block_id)?;
+        let position_blockclique = self.compute_fitness_find_blockclique()?;
 
         // update best parents
         massa_trace!(
             "consensus.block_graph.add_block_to_graph.update_best_parents",
             {}
         );
-        {
-            let blockclique = &self.max_cliques[position_blockclique];
-
-            // init best parents as latest_final_blocks_periods
-            self.best_parents = self.latest_final_blocks_periods.clone();
-            // for each blockclique block, set it as best_parent in its own thread
-            // if its period is higher than the current best_parent in that thread
-            for block_h in blockclique.block_ids.iter() {
-                let b_slot = match self.blocks_state.get(block_h) {
-                    Some(BlockStatus::Active { a_block, .. }) => a_block.slot,
-                    _ => return Err(ConsensusError::ContainerInconsistency(format!("inconsistency inside block statuses updating best parents while adding {} - missing {}", add_block_id, block_h))),
-                };
-                if b_slot.period > self.best_parents[b_slot.thread as usize].1 {
-                    self.best_parents[b_slot.thread as usize] = (*block_h, b_slot.period);
-                }
-            }
-        }
+        self.update_best_parents(position_blockclique, &add_block_id)?;
 
         // list stale blocks
         massa_trace!(
@@ -536,7 +575,25 @@ impl ConsensusState {
             .fitness
             .saturating_sub(self.config.delta_f0);
         let stale_blocks = self.list_stale_blocks(fitness_threshold);
-        self.max_cliques.retain(|c| c.fitness >= fitness_threshold);
+        let (dropped_cliques, kept_cliques): (Vec<Clique>, Vec<Clique>) = self
+            .max_cliques
+            .drain(..)
+            .partition(|c| c.fitness < fitness_threshold);
+        self.max_cliques = kept_cliques;
+        // the dropped cliques may leave max cliques of the remaining blocks that are not kept,
+        // in which case the next fork recomputes them fully instead of updating them incrementally
+        if !dropped_cliques.is_empty() {
+            let kept: Vec<PreHashSet<BlockId>> = self
+                .max_cliques
+                .iter()
+                .map(|c| c.block_ids.clone())
+                .collect();
+            let dropped: Vec<PreHashSet<BlockId>> =
+                dropped_cliques.into_iter().map(|c| c.block_ids).collect();
+            if !max_cliques_exact_after_stale_removal(&kept, &dropped, &stale_blocks) {
+                self.max_cliques_inexact = true;
+            }
+        }
         // mark stale blocks
         massa_trace!(
             "consensus.block_graph.add_block_to_graph.mark_stale_blocks",
@@ -579,7 +636,16 @@ impl ConsensusState {
     }
 
     /// Note an attack attempt if the discard reason indicates one.
-    pub fn maybe_note_attack_attempt(&mut self, reason: &DiscardReason, hash: &BlockId) {
+    ///
+    /// The creator of the block is only banned for the violations every node sees the same way:
+    /// a bad header signature or a creator that was not drawn for the slot.
+    /// Invalidity coming from node-local checks (e.g. header extensions, versions) does not ban.
+    pub fn maybe_note_attack_attempt(
+        &mut self,
+        reason: &DiscardReason,
+        hash: &BlockId,
+        header: &SecuredHeader,
+    ) {
         massa_trace!("consensus.block_graph.maybe_note_attack_attempt", {"hash": hash, "reason": reason});
         // If invalid, note the attack attempt.
         if let DiscardReason::Invalid(reason) = reason {
@@ -588,6 +654,15 @@ impl ConsensusState {
                 reason
             );
             self.attack_attempts.push(*hash);
+            let creator = header.content_creator_address;
+            let bad_signature = header.verify_signature().is_err();
+            let not_drawn = matches!(
+                self.get_producer(header.content.slot),
+                Ok(producer) if producer != creator
+            );
+            if bad_signature || not_drawn {
+                self.ban_invalid_block_creator(creator, reason);
+            }
         }
     }
 
@@ -608,11 +683,15 @@ impl ConsensusState {
                 let metadata = match self.blocks_state.get(b_id) {
                     Some(BlockStatus::Active {
                         a_block,
-                        storage_or_block: StorageOrBlock::Storage(storage),
+                        storage_or_block,
                         ..
                     }) => ExecutionBlockMetadata {
                         same_thread_parent_creator: a_block.same_thread_parent_creator,
-                        storage: Some(storage.clone()),
+                        storage: Some(
+                            storage_or_block
+                                .clone_storage()
+                                .expect("final block operations are missing"),
+                        ),
                     },
                     _ => panic!(
                         "final block not found in active blocks and/or its operations are missing"
@@ -624,6 +703,7 @@ impl ConsensusState {
 
         // Get new blockclique block list with slots.
         let mut blockclique_changed = false;
+        let mut added_to_blockclique: PreHashSet<BlockId> = PreHashSet::default();
         let new_blockclique: PreHashMap<BlockId, Slot> = self
             .get_blockclique()
             .iter()
@@ -637,14 +717,16 @@ impl ConsensusState {
                     // The block was not present in the previous blockclique:
                     // the blockclique has changed => get the block's slot by querying Storage.
                     blockclique_changed = true;
+                    added_to_blockclique.insert(*b_id);
                     let (a_block, storage) = match self.blocks_state.get(b_id) {
                         Some(BlockStatus::Active {
                             a_block,
-                            storage_or_block: StorageOrBlock::Storage(storage),
-                        }) => (a_block, storage),
+                            storage_or_block,
+                            ..
+                        }) => (a_block, storage_or_block.clone_storage().expect("blockclique block operations are missing")),
                         _ => panic!("blockclique block not found in active blocks and/or its operations are missing"),
                     };
-                    new_blocks_metadata.insert(*b_id, ExecutionBlockMetadata { same_thread_parent_creator: a_block.same_thread_parent_creator, storage: Some(storage.clone()) });
+                    new_blocks_metadata.insert(*b_id, ExecutionBlockMetadata { same_thread_parent_creator: a_block.same_thread_parent_creator, storage: Some(storage) });
                     (*b_id, a_block.slot)
                 }
             })
@@ -654,10 +736,58 @@ impl ConsensusState {
             // If `prev_blockclique` is not empty here, it means that it contained elements that are not in the new blockclique anymore.
             // In that case, we mark the blockclique as having changed.
             blockclique_changed = true;
+
+            // Blocks that left the blockclique without becoming final were reorganized out of it.
+            let final_block_ids: PreHashSet<BlockId> = finalized_blocks.values().copied().collect();
+            let removed_from_blockclique: PreHashSet<BlockId> = self
+                .prev_blockclique
+                .keys()
+                .filter(|b_id| !final_block_ids.contains(b_id))
+                .copied()
+                .collect();
+            if !removed_from_blockclique.is_empty() {
+                let mut removed: Vec<BlockId> = removed_from_blockclique.iter().copied().collect();
+                let mut added: Vec<BlockId> = added_to_blockclique.iter().copied().collect();
+                removed.sort_unstable();
+                added.sort_unstable();
+                // the operations of the removed blocks stay included if a block of the new blockclique
+                // or a final block includes them too
+                let reverted_operations = self.operation_inclusions.reverted_operations(
+                    &removed_from_blockclique,
+                    |block_id| {
+                        new_blockclique.contains_key(block_id)
+                            || matches!(
+                                self.blocks_state.get(block_id),
+                                Some(BlockStatus::Active { a_block, .. }) if a_block.is_final
+                            )
+                    },
+                );
+                let slot = removed_from_blockclique
+                    .iter()
+                    .filter_map(|block_id| self.prev_blockclique.get(block_id))
+                    .max()
+                    .copied()
+                    .expect("removed blocks come from the previous blockclique");
+                self.last_reorg_report = Some(ReorgReport {
+                    time: MassaTime::now(),
+                    slot,
+                    removed_blocks: removed.clone(),
+                    added_blocks: added.clone(),
+                    reverted_operations,
+                });
+                self.audit(AuditEvent::BlockcliqueSwitch { removed, added });
+                self.channels.event_bus.publish(NodeEvent::ReorgHappened {
+                    removed: removed_from_blockclique,
+                    added: added_to_blockclique,
+                });
+            }
         }
         // Overwrite previous blockclique.
         // Should still be done even if unchanged because elements were removed from it above.
         self.prev_blockclique = new_blockclique.clone();
+        if blockclique_changed {
+            self.record_blockclique_change(new_blockclique.keys().copied().collect());
+        }
 
         if finalized_blocks.is_empty() && !blockclique_changed {
             // There are no changes (neither block finalizations not blockclique changes) to send to execution.
@@ -692,6 +822,7 @@ impl ConsensusState {
     /// 9. notify protocol of block wish list
     /// 10. note new latest final periods (prune graph if changed)
     /// 11. add stale blocks to stats
+    /// 12. notify the submission tickets of the blocks that reached a terminal status
     pub fn block_db_changed(&mut self) -> Result<(), ConsensusError> {
         let final_block_slots = {
             massa_trace!("consensus.consensus_worker.block_db_changed", {});
@@ -721,11 +852,41 @@ impl ConsensusState {
             let finalized_blocks = mem::take(&mut self.new_final_blocks);
             let mut final_block_slots = HashMap::with_capacity(finalized_blocks.len());
             let mut final_block_stats = VecDeque::with_capacity(finalized_blocks.len());
+            let mut final_endorsement_indexes = Vec::with_capacity(finalized_blocks.len());
             for b_id in finalized_blocks {
-                if let Some(BlockStatus::Active { a_block, .. }) = self.blocks_state.get(&b_id) {
+                if let Some(BlockStatus::Active {
+                    a_block,
+                    storage_or_block,
+                    ..
+                }) = self.blocks_state.get(&b_id)
+                {
                     // add to final blocks to notify execution
                     final_block_slots.insert(a_block.slot, b_id);
 
+                    // publish on the node event bus
+                    let (endorsement_creators, endorsement_indexes): (Vec<Address>, Vec<u32>) =
+                        storage_or_block.with_block(&b_id, |block| {
+                            block
+                                .content
+                                .header
+                                .content
+                                .endorsements
+                                .iter()
+                                .map(|endo| (endo.content_creator_address, endo.content.index))
+                                .unzip()
+                        });
+                    self.channels.event_bus.publish(NodeEvent::BlockFinal {
+                        block_id: b_id,
+                        slot: a_block.slot,
+                        reward_split: BlockRewardSplit::compute(
+                            self.config.block_reward,
+                            self.config.endorsement_count as u64,
+                            a_block.creator_address,
+                            &endorsement_creators,
+                            a_block.same_thread_parent_creator,
+                        ),
+                    });
+
                     // add to stats
                     let block_is_from_protocol = self
                         .protocol_blocks
@@ -736,15 +897,47 @@ impl ConsensusState {
                         a_block.creator_address,
                         block_is_from_protocol,
                     ));
+
+                    // note the included endorsements to count the missed ones
+                    if !self.genesis_hashes.contains(&b_id) {
+                        final_endorsement_indexes.push((a_block.slot, endorsement_indexes));
+                    }
                 }
             }
             self.final_block_stats.extend(final_block_stats);
+            for (slot, included_indexes) in final_endorsement_indexes {
+                self.record_final_endorsements(slot, &included_indexes);
+            }
+            for (slot, block_id) in final_block_slots.iter() {
+                self.audit(AuditEvent::BlockFinal {
+                    block_id: *block_id,
+                    slot: *slot,
+                });
+            }
+            if let Some(final_block_index) = self.final_block_index.as_ref() {
+                final_block_index.record(&final_block_slots);
+            }
+            if !final_block_slots.is_empty() {
+                self.note_finalization();
+            }
 
             // add stale blocks to stats
             let new_stale_block_ids_creators_slots = mem::take(&mut self.new_stale_blocks);
             let timestamp = MassaTime::now();
-            for (_b_id, (_b_creator, _b_slot)) in new_stale_block_ids_creators_slots.into_iter() {
+            for (b_id, (_b_creator, b_slot)) in new_stale_block_ids_creators_slots.into_iter() {
                 self.stale_block_stats.push_back(timestamp);
+                self.audit(AuditEvent::BlockDiscarded {
+                    block_id: b_id,
+                    slot: b_slot,
+                    reason: DiscardReason::Stale,
+                });
+            }
+
+            // give the endorsements of the stale blocks back to the pool
+            for feedback in mem::take(&mut self.new_orphaned_endorsements) {
+                self.channels
+                    .pool_controller
+                    .notify_endorsement_feedback(feedback);
             }
             final_block_slots
         };
@@ -799,6 +992,13 @@ impl ConsensusState {
             self.save_final_periods = latest_final_periods;
         }
 
+        // the block counts are refreshed at the slot ticks only while catching up
+        if !self.is_catching_up() {
+            self.refresh_block_counts();
+        }
+        self.refresh_snapshots();
+        self.notify_submissions();
+
         Ok(())
     }
 }
@@ -1,6 +1,7 @@
 use std::collections::BTreeSet;
 
 use massa_consensus_exports::{
+    audit_log::AuditEvent,
     block_status::{BlockStatus, DiscardReason, HeaderOrBlock},
     error::ConsensusError,
 };
@@ -40,10 +41,34 @@ impl ConsensusState {
             .pool_controller
             .add_denunciation_precursor(de_p);
 
+        // drop the headers of banned creators, after noting the precursor so that they can still be denounced
+        if self.is_creator_banned(&header.content_creator_address) {
+            debug!(
+                "dropping header {} of banned creator {}",
+                block_id, header.content_creator_address
+            );
+            return Ok(());
+        }
+
+        // hold the headers of network versions not activated locally yet instead of processing them
+        if self.quarantine_if_version_inactive(block_id, &header)? {
+            return Ok(());
+        }
+
         debug!(
             "received header {} for slot {}",
             block_id, header.content.slot
         );
+        self.register_incoming_header(block_id, header, current_slot)
+    }
+
+    /// Insert a header in the graph as incoming, if its block is not known yet, and process it.
+    pub(crate) fn register_incoming_header(
+        &mut self,
+        block_id: BlockId,
+        header: SecuredHeader,
+        current_slot: Option<Slot>,
+    ) -> Result<(), ConsensusError> {
         massa_trace!("consensus.block_graph.incoming_header", {"block_id": block_id, "header": header});
         let mut to_ack: BTreeSet<(Slot, BlockId)> = BTreeSet::new();
         self.blocks_state
@@ -84,11 +109,26 @@ impl ConsensusState {
             return Ok(());
         }
 
-        if let Some(verifiable_block) = storage.read_blocks().get(&block_id) {
-            let de_p = DenunciationPrecursor::from(&verifiable_block.content.header);
-            self.channels
-                .pool_controller
-                .add_denunciation_precursor(de_p);
+        // the caller-provided slot is used to index the block: check it against the decoded header
+        let header = match storage.read_blocks().get(&block_id) {
+            Some(verifiable_block) => verifiable_block.content.header.clone(),
+            None => {
+                return Err(ConsensusError::MissingBlock(format!(
+                    "block {} registered without its content in storage",
+                    block_id
+                )))
+            }
+        };
+        self.check_registered_slot(&block_id, slot, &header)?;
+
+        let de_p = DenunciationPrecursor::from(&header);
+        self.channels
+            .pool_controller
+            .add_denunciation_precursor(de_p);
+
+        // the body is dropped: it is asked again once the header is released from the quarantine
+        if self.quarantine_if_version_inactive(block_id, &header)? {
+            return Ok(());
         }
 
         // Block is coming from protocol mark it for desync calculation
@@ -152,6 +192,24 @@ impl ConsensusState {
         Ok(())
     }
 
+    /// Check that the slot a block is registered for matches its header.
+    /// The creator draw is then verified by `check_header` against that same slot.
+    fn check_registered_slot(
+        &self,
+        block_id: &BlockId,
+        slot: Slot,
+        header: &SecuredHeader,
+    ) -> Result<(), ConsensusError> {
+        if header.content.slot != slot {
+            return Err(ConsensusError::BlockSlotMismatch {
+                block_id: *block_id,
+                registered_slot: slot,
+                header_slot: header.content.slot,
+            });
+        }
+        Ok(())
+    }
+
     /// Mark a block that is in the graph as invalid.
     ///
     /// # Arguments:
@@ -159,8 +217,13 @@ impl ConsensusState {
     /// * `header`: Header of the block to mark as invalid
     pub fn mark_invalid_block(&mut self, block_id: &BlockId, header: SecuredHeader) {
         let reason = DiscardReason::Invalid("invalid".to_string());
-        self.maybe_note_attack_attempt(&reason, block_id);
+        self.maybe_note_attack_attempt(&reason, block_id, &header);
         massa_trace!("consensus.block_graph.process.invalid_block", {"block_id": block_id, "reason": reason});
+        self.audit(AuditEvent::BlockDiscarded {
+            block_id: *block_id,
+            slot: header.content.slot,
+            reason: reason.clone(),
+        });
         let sequence_number = self.blocks_state.sequence_counter();
         self.blocks_state.transition_map(block_id, |_, _| {
             Some(BlockStatus::Discarded {
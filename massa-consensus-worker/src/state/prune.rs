@@ -10,7 +10,9 @@ use massa_models::{
     block_id::BlockId,
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
+    timeslots,
 };
+use massa_time::MassaTime;
 use tracing::debug;
 
 use super::ConsensusState;
@@ -21,12 +23,19 @@ impl ConsensusState {
         // list required active blocks
         let mut retain_active: PreHashSet<BlockId> = self.list_required_active_blocks(None)?;
 
+        // latest final slot applied by execution, only needed if the operations of executed blocks are dropped
+        let executed_final_slot = self
+            .config
+            .drop_executed_block_bodies
+            .then(|| self.channels.execution_controller.get_final_cursor());
+
         // retain extra history according to the config
         // this is useful to avoid desync on temporary connection loss
         for a_block in self.blocks_state.active_blocks().clone().iter() {
             if let Some(BlockStatus::Active {
                 a_block: active_block,
                 storage_or_block,
+                ..
             }) = self.blocks_state.get_mut(a_block)
             {
                 let (_b_id, latest_final_period) =
@@ -45,6 +54,20 @@ impl ConsensusState {
                         self.active_index_without_ops.insert(*a_block);
                         // reset the list of descendants
                         active_block.descendants = Default::default();
+                    } else if self.config.light_mode
+                        && active_block.is_final
+                        && !self.active_index_without_ops.contains(a_block)
+                    {
+                        // in light mode, final blocks were already handed to execution: drop their operations
+                        storage_or_block.strip_to_block(a_block);
+                        self.active_index_without_ops.insert(*a_block);
+                    } else if active_block.is_final
+                        && executed_final_slot.map_or(false, |slot| active_block.slot <= slot)
+                        && !self.active_index_without_ops.contains(a_block)
+                    {
+                        // execution applied the block to the final state: its operations are not needed anymore
+                        storage_or_block.strip_to_block(a_block);
+                        self.active_index_without_ops.insert(*a_block);
                     }
                 } else {
                     self.active_index_without_ops.remove(a_block);
@@ -162,6 +185,47 @@ impl ConsensusState {
         Ok(())
     }
 
+    /// Drop the headers whose block did not arrive within `config.header_only_expiry_periods` periods
+    /// to avoid high memory consumption under header spam
+    fn prune_expired_headers(&mut self) -> Result<(), ConsensusError> {
+        if self.config.header_only_expiry_periods == 0 {
+            return Ok(());
+        }
+        let Some(current_slot) = timeslots::get_latest_block_slot_at_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            MassaTime::now(),
+        )?
+        else {
+            return Ok(());
+        };
+        let expired: Vec<BlockId> = self
+            .header_only_blocks()
+            .filter(|(_, slot)| {
+                current_slot.period.saturating_sub(slot.period)
+                    >= self.config.header_only_expiry_periods
+            })
+            .map(|(block_id, _)| block_id)
+            .collect();
+        for block_id in expired.iter() {
+            massa_trace!("consensus.block_graph.prune_expired_headers", {
+                "block_id": block_id
+            });
+            self.blocks_state.transition_map(block_id, |_, _| None);
+        }
+        if !expired.is_empty() {
+            debug!(
+                "dropped {} headers whose block did not arrive in time",
+                expired.len()
+            );
+        }
+        self.expired_header_count = self
+            .expired_header_count
+            .saturating_add(expired.len() as u64);
+        Ok(())
+    }
+
     fn prune_waiting_for_dependencies(&mut self) -> Result<(), ConsensusError> {
         let mut to_discard: PreHashMap<BlockId, Option<DiscardReason>> = PreHashMap::default();
         let mut to_keep: PreHashMap<BlockId, (u64, Slot)> = PreHashMap::default();
@@ -190,6 +254,9 @@ impl ConsensusState {
                                 }
                                 DiscardReason::Stale => discard_reason = Some(DiscardReason::Stale),
                                 DiscardReason::Final => discard_reason = Some(DiscardReason::Stale),
+                                DiscardReason::ParentTooOld { .. } => {
+                                    discard_reason = Some(reason.clone())
+                                }
                             }
                         }
                     }
@@ -237,6 +304,9 @@ impl ConsensusState {
                                 Some(DiscardReason::Final) => {
                                     discard_reason = Some(DiscardReason::Stale)
                                 }
+                                Some(reason @ DiscardReason::ParentTooOld { .. }) => {
+                                    discard_reason = Some(reason.clone())
+                                }
                                 None => {} // leave as None
                             }
                         }
@@ -340,15 +410,34 @@ impl ConsensusState {
         // Step 2: prune slot waiting blocks
         self.prune_slot_waiting();
 
-        // Step 3: prune dependency waiting blocks
+        // Step 3: drop the headers whose block never arrived
+        self.prune_expired_headers()?;
+
+        // Step 4: prune dependency waiting blocks
         self.prune_waiting_for_dependencies()?;
 
-        // Step 4: prune discarded
+        // Step 5: prune discarded
         self.prune_discarded()?;
 
-        // Step 5: prune nonfinal blocks per slot
+        // Step 6: prune nonfinal blocks per slot
         self.prune_nonfinal_blocks_per_slot();
 
+        // Step 7: forget the operation sets that are not used by active blocks anymore
+        self.shared_operations.prune();
+
+        // Step 8: forget the operations of the blocks that are not active anymore
+        self.prune_operation_inclusions();
+
+        // Step 9: forget the provenance of the blocks that left the graph
+        self.prune_block_provenance();
+
+        // Step 10: forget the checks of the insertion shards on the headers of final slots
+        self.prechecked_headers
+            .lock()
+            .prune(&self.latest_final_blocks_periods);
+
+        self.refresh_block_counts();
+
         let after = self.max_cliques.len();
         if before != after {
             debug!(
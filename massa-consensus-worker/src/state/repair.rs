@@ -0,0 +1,139 @@
+use massa_consensus_exports::{block_status::BlockStatus, graph_repair::GraphRepairReport};
+use massa_models::{block_id::BlockId, clique::Clique, prehash::PreHashSet};
+use tracing::{info, warn};
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Scan the graph for recoverable inconsistencies and fix them.
+    ///
+    /// The following inconsistencies are fixed:
+    /// * status indexes that do not match the block statuses
+    /// * children of active blocks that are not active anymore
+    /// * incompatibility graph entries that are missing, asymmetric or that reference blocks that are not active and non-final
+    /// * clique members and blocks without operations that are not active anymore
+    ///
+    /// Missing parents of non-final blocks and missing best or final parents can't be fixed and are only reported.
+    pub fn repair_graph(&mut self) -> GraphRepairReport {
+        let mut report = GraphRepairReport {
+            status_index_entries_fixed: self.blocks_state.repair_indexes(),
+            ..Default::default()
+        };
+        let active_blocks = self.blocks_state.active_blocks().clone();
+        let nonfinal_active_blocks: PreHashSet<BlockId> = active_blocks
+            .iter()
+            .filter(|block_id| {
+                matches!(
+                    self.blocks_state.get(block_id),
+                    Some(BlockStatus::Active { a_block, .. }) if !a_block.is_final
+                )
+            })
+            .copied()
+            .collect();
+
+        // children of active blocks must be active, parents of non-final blocks must be active
+        for block_id in active_blocks.iter() {
+            if let Some(BlockStatus::Active { a_block, .. }) = self.blocks_state.get_mut(block_id) {
+                for thread_children in a_block.children.iter_mut() {
+                    let count_before = thread_children.len();
+                    thread_children.retain(|child_id, _| active_blocks.contains(child_id));
+                    report.dangling_references_removed += count_before - thread_children.len();
+                }
+                if !a_block.is_final {
+                    for (parent_id, _) in a_block.parents.iter() {
+                        if !active_blocks.contains(parent_id) {
+                            report.unrecoverable.push(format!(
+                                "non-final block {} has a missing parent {}",
+                                block_id, parent_id
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        // the incompatibility graph has exactly one symmetric entry per non-final active block
+        let count_before = self.gi_head.len();
+        self.gi_head
+            .retain(|block_id, _| nonfinal_active_blocks.contains(block_id));
+        report.incompatibility_entries_fixed += count_before - self.gi_head.len();
+        for block_id in nonfinal_active_blocks.iter() {
+            if !self.gi_head.contains_key(block_id) {
+                self.gi_head.insert(*block_id, Default::default());
+                report.incompatibility_entries_fixed += 1;
+            }
+        }
+        let mut missing_symmetric = Vec::new();
+        for (block_id, incompatibilities) in self.gi_head.iter_mut() {
+            let count_before = incompatibilities.len();
+            incompatibilities.retain(|incomp_id| nonfinal_active_blocks.contains(incomp_id));
+            report.incompatibility_entries_fixed += count_before - incompatibilities.len();
+            missing_symmetric.extend(
+                incompatibilities
+                    .iter()
+                    .map(|incomp_id| (*incomp_id, *block_id)),
+            );
+        }
+        for (block_id, incomp_id) in missing_symmetric {
+            if let Some(incompatibilities) = self.gi_head.get_mut(&block_id) {
+                if incompatibilities.insert(incomp_id) {
+                    report.incompatibility_entries_fixed += 1;
+                }
+            }
+        }
+
+        // cliques only contain non-final active blocks
+        for clique in self.max_cliques.iter_mut() {
+            let count_before = clique.block_ids.len();
+            clique
+                .block_ids
+                .retain(|block_id| nonfinal_active_blocks.contains(block_id));
+            report.clique_entries_removed += count_before - clique.block_ids.len();
+        }
+        if report.clique_entries_removed > 0 {
+            // the repaired cliques may not be maximal anymore
+            self.max_cliques_inexact = true;
+            self.max_cliques.retain(|c| !c.block_ids.is_empty());
+            if self.max_cliques.is_empty() {
+                // make sure at least one clique remains
+                self.max_cliques = vec![Clique {
+                    block_ids: PreHashSet::<BlockId>::default(),
+                    fitness: 0,
+                    is_blockclique: true,
+                }];
+            }
+            if let Err(err) = self.compute_fitness_find_blockclique() {
+                report.unrecoverable.push(format!(
+                    "could not recompute the fitness of the cliques: {}",
+                    err
+                ));
+            }
+        }
+
+        // blocks without operations must be active
+        let count_before = self.active_index_without_ops.len();
+        self.active_index_without_ops
+            .retain(|block_id| active_blocks.contains(block_id));
+        report.without_ops_entries_removed = count_before - self.active_index_without_ops.len();
+
+        // best and latest final parents must be active
+        for (block_id, _period) in self
+            .best_parents
+            .iter()
+            .chain(self.latest_final_blocks_periods.iter())
+        {
+            if !active_blocks.contains(block_id) {
+                report
+                    .unrecoverable
+                    .push(format!("parent {} is not active", block_id));
+            }
+        }
+
+        if report.is_clean() {
+            info!("graph repair: no inconsistency found");
+        } else {
+            warn!("graph repair: {:?}", report);
+        }
+        report
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::{Arc, Weak};
+
+use massa_consensus_exports::block_status::StorageOrBlock;
+use massa_hash::Hash;
+use massa_models::{
+    operation::OperationId,
+    prehash::{PreHashMap, PreHashSet},
+    secure_share::Id,
+};
+use massa_storage::Storage;
+
+/// Registry of the operation sets of the active blocks.
+///
+/// Competing blocks often contain the same operations (factories picking the same pool contents).
+/// The operations of such blocks are owned by a single shared `Storage` instead of one per block.
+/// The registry only holds weak references: a set is released when the last block using it is dropped.
+#[derive(Clone, Default)]
+pub struct SharedOperationSets {
+    /// shared operation storages indexed by the hash of their sorted operation ids
+    sets: PreHashMap<Hash, Weak<Storage>>,
+}
+
+impl SharedOperationSets {
+    /// Takes the storage of a newly active block and moves its operations to a shared storage,
+    /// reusing the one of another block if it has exactly the same operations.
+    pub fn share(&mut self, mut storage: Storage) -> StorageOrBlock {
+        let operations = storage.get_op_refs().clone();
+        if operations.is_empty() {
+            return StorageOrBlock::Storage(storage);
+        }
+        let key = operation_set_key(&operations);
+        let existing = self
+            .sets
+            .get(&key)
+            .and_then(Weak::upgrade)
+            .filter(|shared| shared.get_op_refs() == &operations);
+        let block_operations =
+            storage.split_off(&Default::default(), &operations, &Default::default());
+        let operations = match existing {
+            // the references of `block_operations` are released when it is dropped
+            Some(shared) => shared,
+            None => {
+                let shared = Arc::new(block_operations);
+                self.sets.insert(key, Arc::downgrade(&shared));
+                shared
+            }
+        };
+        StorageOrBlock::SharedOperations {
+            storage,
+            operations,
+        }
+    }
+
+    /// Forget the operation sets that are not used by any block anymore
+    pub fn prune(&mut self) {
+        self.sets.retain(|_, shared| shared.strong_count() > 0);
+    }
+}
+
+/// Hash of the sorted ids of an operation set
+fn operation_set_key(operations: &PreHashSet<OperationId>) -> Hash {
+    let mut ids: Vec<&OperationId> = operations.iter().collect();
+    ids.sort_unstable();
+    let bytes: Vec<&[u8]> = ids
+        .into_iter()
+        .map(|id| id.get_hash().to_bytes().as_slice())
+        .collect();
+    Hash::compute_from_tuple(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_protocol_exports::test_exports::tools::create_operation_with_expire_period;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_share_identical_operation_sets() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let mut root = Storage::create_root();
+        let operations = vec![
+            create_operation_with_expire_period(&keypair, 10),
+            create_operation_with_expire_period(&keypair, 11),
+        ];
+        let ids: PreHashSet<OperationId> = operations.iter().map(|op| op.id).collect();
+        root.store_operations(operations);
+
+        let mut shared_operations = SharedOperationSets::default();
+        let first = shared_operations.share(root.clone());
+        let second = shared_operations.share(root.clone());
+        match (&first, &second) {
+            (
+                StorageOrBlock::SharedOperations {
+                    operations: first_ops,
+                    ..
+                },
+                StorageOrBlock::SharedOperations {
+                    operations: second_ops,
+                    ..
+                },
+            ) => {
+                assert!(Arc::ptr_eq(first_ops, second_ops));
+                assert_eq!(first_ops.get_op_refs(), &ids);
+            }
+            _ => panic!("operations were not shared"),
+        }
+        assert_eq!(second.clone_storage().unwrap().get_op_refs(), &ids);
+
+        // the set is forgotten once no block uses it anymore
+        drop(first);
+        drop(second);
+        shared_operations.prune();
+        assert!(shared_operations.sets.is_empty());
+    }
+}
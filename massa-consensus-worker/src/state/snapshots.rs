@@ -0,0 +1,57 @@
+//! Snapshots of the graph getters called on every API request.
+//!
+//! Cloning the cliques or the best parents under the read lock of the consensus state at each call
+//! delays the worker and the other readers. The worker instead publishes `Arc`-wrapped snapshots on each
+//! graph change and slot tick, and the controller hands out clones of the `Arc`s. A snapshot is never
+//! mutated in place: it is replaced when its content changes, and readers holding the previous one
+//! keep a consistent view. The graph exports are served the same way, see `graph_export.rs`.
+
+use std::{sync::Arc, time::Instant};
+
+use massa_models::{block_id::BlockId, clique::Clique};
+
+use super::{graph_export::GraphExportSnapshot, ConsensusState};
+
+/// Snapshots of the exposed graph, shared with the controller
+#[derive(Debug, Default)]
+pub struct GraphSnapshots {
+    /// cliques exposed to the callers, see `get_exposed_cliques`
+    pub cliques: Arc<Vec<Clique>>,
+    /// best parents proposed to the block factory, see `get_exposed_best_parents`
+    pub best_parents: Arc<Vec<(BlockId, u64)>>,
+    /// generation of the graph, incremented each time the cliques or the best parents are replaced
+    pub generation: u64,
+    /// whole graph, filtered by slot into the graph exports
+    pub graph_export: Arc<GraphExportSnapshot>,
+}
+
+impl ConsensusState {
+    /// Publish new snapshots of the exposed cliques, best parents and graph export to the controller
+    /// The snapshots whose content did not change are kept, so that readers can keep sharing them.
+    /// The generation of the graph is incremented if the cliques or the best parents changed.
+    pub fn refresh_snapshots(&self) {
+        let cliques = self.get_exposed_cliques();
+        let best_parents = self.get_exposed_best_parents();
+        let mut snapshots = self.snapshots.write();
+        let mut changed = false;
+        if *snapshots.cliques != cliques {
+            snapshots.cliques = Arc::new(cliques);
+            changed = true;
+        }
+        if *snapshots.best_parents != best_parents {
+            snapshots.best_parents = Arc::new(best_parents);
+            changed = true;
+        }
+        if changed {
+            snapshots.generation = snapshots.generation.wrapping_add(1);
+        }
+        let start = Instant::now();
+        if let Some(graph_export) =
+            self.build_graph_export_snapshot(&snapshots.graph_export, snapshots.generation)
+        {
+            snapshots.graph_export = Arc::new(graph_export);
+            self.massa_metrics
+                .set_consensus_graph_snapshot_time(start.elapsed().as_secs_f64());
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use massa_consensus_exports::{error::ConsensusError, stall_diagnostics::FinalityStallDiagnostics};
+use massa_models::{node_event::NodeEvent, slot::Slot};
+use massa_time::MassaTime;
+use tracing::warn;
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Collect the diagnostics bundle of a finality stall
+    ///
+    /// # Arguments
+    /// * `slot`: latest slot processed by the worker
+    /// * `command_queue_length`: number of commands waiting in the channel of the worker
+    pub fn get_finality_stall_diagnostics(
+        &self,
+        slot: Slot,
+        command_queue_length: usize,
+    ) -> Result<FinalityStallDiagnostics, ConsensusError> {
+        let now = MassaTime::now();
+        let lifetime = self.config.peer_blockclique_summary_lifetime;
+        let peer_summary_count = self
+            .peer_blockclique_summaries
+            .read()
+            .values()
+            .filter(|peer_summary| now.saturating_sub(peer_summary.received_at) <= lifetime)
+            .count();
+        Ok(FinalityStallDiagnostics {
+            timestamp: now,
+            last_finalization: self.last_finalization,
+            slot,
+            stats: self.get_stats()?,
+            health: self.health.read().clone(),
+            catch_up: self.catch_up.read().clone(),
+            block_counts: self.block_counts.read().clone(),
+            command_queue_length,
+            quarantined_header_count: self.quarantined_headers.len(),
+            wishlist_length: self.wishlist.len(),
+            latest_final_blocks_periods: self.latest_final_blocks_periods.clone(),
+            best_parents: self.best_parents.clone(),
+            cliques: self.max_cliques.clone(),
+            peer_summary_count,
+            divergent_peers: self.get_divergent_peers(),
+        })
+    }
+
+    /// Collect a diagnostics bundle and publish a `FinalityStalled` event if no block became final during
+    /// `finality_stall_timeout`. A single bundle is collected per stall. Called at each slot tick.
+    ///
+    /// # Arguments
+    /// * `current_slot`: the slot of the tick
+    /// * `command_queue_length`: number of commands waiting in the channel of the worker
+    pub(crate) fn check_finality_stall(&mut self, current_slot: Slot, command_queue_length: usize) {
+        let timeout = self.config.finality_stall_timeout;
+        if timeout == MassaTime::from_millis(0) || self.finality_stall_reported {
+            return;
+        }
+        if MassaTime::now().saturating_sub(self.last_finalization) < timeout {
+            return;
+        }
+        self.finality_stall_reported = true;
+        let diagnostics =
+            match self.get_finality_stall_diagnostics(current_slot, command_queue_length) {
+                Ok(diagnostics) => diagnostics,
+                Err(err) => {
+                    warn!(
+                        "could not collect the diagnostics of the finality stall: {}",
+                        err
+                    );
+                    return;
+                }
+            };
+        let diagnostics_path = self
+            .config
+            .finality_stall_diagnostics_dir
+            .as_ref()
+            .and_then(|dir| match diagnostics.save(dir) {
+                Ok(path) => Some(path),
+                Err(err) => {
+                    warn!(
+                        "could not write the diagnostics of the finality stall: {}",
+                        err
+                    );
+                    None
+                }
+            });
+        warn!(
+            "no block became final for {} ms, diagnostics: {}",
+            diagnostics.stalled_for().as_millis(),
+            diagnostics_path.as_ref().map_or_else(
+                || "not written".to_string(),
+                |path| path.display().to_string()
+            )
+        );
+        self.channels.event_bus.publish(NodeEvent::FinalityStalled {
+            slot: current_slot,
+            stalled_for: diagnostics.stalled_for(),
+            diagnostics_path,
+        });
+    }
+
+    /// Note that blocks became final, ending the ongoing finality stall if any
+    pub(crate) fn note_finalization(&mut self) {
+        self.last_finalization = MassaTime::now();
+        self.finality_stall_reported = false;
+    }
+}
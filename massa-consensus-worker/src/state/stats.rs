@@ -1,6 +1,9 @@
 use super::ConsensusState;
 use massa_consensus_exports::error::ConsensusError;
+use massa_consensus_exports::health::ConsensusHealth;
+use massa_models::slot::Slot;
 use massa_models::stats::ConsensusStats;
+use massa_models::timeslots::{get_block_slot_timestamp, slot_count_in_range};
 use massa_time::MassaTime;
 use std::cmp::max;
 
@@ -11,6 +14,73 @@ use tracing::warn;
 use massa_consensus_exports::events::ConsensusEvent;
 
 impl ConsensusState {
+    /// Publish the number of blocks in each status to the controller, as maintained by the blocks state
+    pub fn refresh_block_counts(&self) {
+        *self.block_counts.write() = self.blocks_state.counts().clone();
+    }
+
+    /// Record a tick of the worker and the current finality lag, and publish them to the controller
+    pub fn refresh_health(&self) {
+        let oldest_latest_final_timestamp = self
+            .latest_final_blocks_periods
+            .iter()
+            .enumerate()
+            .filter_map(|(thread, (_, period))| {
+                get_block_slot_timestamp(
+                    self.config.thread_count,
+                    self.config.t0,
+                    self.config.genesis_timestamp,
+                    Slot::new(*period, thread as u8),
+                )
+                .ok()
+            })
+            .min()
+            .unwrap_or(self.config.genesis_timestamp);
+        let execution_finality_gap = (self.config.readiness_max_execution_gap > 0)
+            .then(|| self.get_execution_finality_gap());
+        *self.health.write() = ConsensusHealth {
+            last_tick: Some(MassaTime::now()),
+            oldest_latest_final_timestamp,
+            liveness_timeout: self.config.liveness_timeout,
+            readiness_max_final_lag: self.config.readiness_max_final_lag,
+            execution_finality_gap,
+            readiness_max_execution_gap: self.config.readiness_max_execution_gap,
+        };
+    }
+
+    /// Latest slot up to which all the slots are final, `None` if no slot is final yet
+    pub fn get_final_slot_watermark(&self) -> Option<Slot> {
+        self.latest_final_blocks_periods
+            .iter()
+            .enumerate()
+            .filter_map(|(thread, (_, period))| {
+                period
+                    .checked_add(1)
+                    .map(|next_period| Slot::new(next_period, thread as u8))
+            })
+            .min()
+            .and_then(|first_non_final| {
+                first_non_final.get_prev_slot(self.config.thread_count).ok()
+            })
+    }
+
+    /// Number of final slots not yet applied by execution, 0 if execution is up to date
+    pub fn get_execution_finality_gap(&self) -> u64 {
+        let Some(final_watermark) = self.get_final_slot_watermark() else {
+            return 0;
+        };
+        let execution_final_cursor = self.channels.execution_controller.get_final_cursor();
+        if final_watermark <= execution_final_cursor {
+            return 0;
+        }
+        slot_count_in_range(
+            execution_final_cursor,
+            final_watermark,
+            self.config.thread_count,
+        )
+        .unwrap_or(u64::MAX)
+    }
+
     /// Calculate and return stats about consensus
     pub fn get_stats(&self) -> Result<ConsensusStats, ConsensusError> {
         let timespan_end = max(self.launch_time, MassaTime::now());
@@ -29,10 +99,16 @@ impl ConsensusState {
             .filter(|t| **t >= timespan_start && **t < timespan_end)
             .count() as u64;
         let clique_count = self.get_clique_count() as u64;
+        let pruned_clique_count = self.get_pruned_clique_count() as u64;
         Ok(ConsensusStats {
             final_block_count,
             stale_block_count,
             clique_count,
+            pruned_clique_count,
+            duplicate_block_count: self.incoming_block_dedup.lock().hit_count(),
+            header_only_count: self.header_only_blocks().count() as u64,
+            expired_header_count: self.expired_header_count,
+            execution_finality_gap: self.health.read().execution_finality_gap,
             start_timespan: timespan_start,
             end_timespan: timespan_end,
         })
@@ -0,0 +1,42 @@
+use massa_consensus_exports::{block_status::BlockStatus, submission::SubmissionOutcome};
+use massa_models::block_id::BlockId;
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Mark the submissions of a block as processed, so that its absence from the graph is then reported as a drop
+    pub(crate) fn arm_submission(&self, block_id: &BlockId) {
+        if let Some(notifier) = self.submission_notifiers.lock().get_mut(block_id) {
+            notifier.arm();
+        }
+    }
+
+    /// Notify the tickets of the processed submissions whose block reached a terminal graph status,
+    /// and forget the blocks for which no ticket is held anymore
+    pub(crate) fn notify_submissions(&self) {
+        let mut notifiers = self.submission_notifiers.lock();
+        notifiers.retain(|_, notifier| notifier.is_observed());
+        let resolved: Vec<(BlockId, SubmissionOutcome)> = notifiers
+            .iter()
+            .filter(|(_, notifier)| notifier.is_armed())
+            .filter_map(|(block_id, _)| {
+                let outcome = match self.blocks_state.get(block_id) {
+                    None => SubmissionOutcome::Dropped,
+                    Some(BlockStatus::Active { a_block, .. }) if a_block.is_final => {
+                        SubmissionOutcome::Final
+                    }
+                    Some(BlockStatus::Discarded { reason, .. }) => {
+                        SubmissionOutcome::Discarded(reason.clone())
+                    }
+                    Some(_) => return None,
+                };
+                Some((*block_id, outcome))
+            })
+            .collect();
+        for (block_id, outcome) in resolved {
+            if let Some(notifier) = notifiers.remove(&block_id) {
+                notifier.resolve(outcome);
+            }
+        }
+    }
+}
@@ -39,6 +39,15 @@ impl ConsensusState {
 
         massa_trace!("consensus.block_graph.slot_tick", {});
 
+        // the non-essential statistics are postponed while catching up
+        let catching_up = self.is_catching_up();
+
+        // prefetch the draws before checking the blocks waiting for this slot
+        self.refresh_draw_cache(current_slot);
+
+        // register the quarantined headers whose network version got activated
+        self.release_quarantined_headers(current_slot)?;
+
         // process those elements
         self.rec_process(to_process, Some(current_slot))?;
 
@@ -47,6 +56,13 @@ impl ConsensusState {
 
         // take care of block db changes
         self.block_db_changed()?;
+        if catching_up {
+            // not refreshed on each graph change while catching up
+            self.refresh_block_counts();
+        }
+
+        // remember the best parents a block created at this slot should reference
+        self.record_best_parents(current_slot);
 
         for i in 0..self.latest_final_blocks_periods.len() {
             if let Some((_blockid, period)) = self.latest_final_blocks_periods.get(i) {
@@ -62,6 +78,15 @@ impl ConsensusState {
             self.active_index_without_ops.len(),
         );
 
+        self.refresh_health();
+        self.refresh_catch_up(current_slot);
+        if !catching_up {
+            // the peers are expected to be ahead while catching up
+            self.check_blockclique_divergence();
+        }
+        self.prune_creator_bans();
+        self.checkpoint_tick(current_slot);
+
         Ok(())
     }
 }
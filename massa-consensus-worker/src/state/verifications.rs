@@ -1,9 +1,15 @@
 use super::{process::BlockInfos, ConsensusState};
-use massa_consensus_exports::block_status::{BlockStatus, DiscardReason, HeaderOrBlock};
+use massa_consensus_exports::{
+    audit_log::AuditEvent,
+    block_status::{BlockStatus, DiscardReason, HeaderOrBlock},
+    ConsensusConfig,
+};
 use massa_logging::massa_trace;
 use massa_models::{
-    block_header::SecuredHeader, block_id::BlockId, prehash::PreHashSet, slot::Slot,
+    address::Address, block_header::SecuredHeader, block_header_extension::check_header_extensions,
+    block_id::BlockId, prehash::PreHashSet, slot::Slot, timeslots::get_block_slot_timestamp,
 };
+use massa_versioning::versioning::{MipComponent, MipStore};
 use tracing::warn;
 
 /// Possible output of a header check
@@ -45,6 +51,102 @@ pub enum EndorsementsCheckOutcome {
     WaitForSlot,
 }
 
+/// Whether the header extensions are enabled by the network version active at `slot`
+fn header_extensions_enabled_at(
+    config: &ConsensusConfig,
+    mip_store: &MipStore,
+    slot: Slot,
+) -> bool {
+    get_block_slot_timestamp(
+        config.thread_count,
+        config.t0,
+        config.genesis_timestamp,
+        slot,
+    )
+    .map_or(false, |timestamp| {
+        mip_store.get_latest_component_version_at(&MipComponent::BlockHeaderExtensions, timestamp)
+            > 0
+    })
+}
+
+/// Check that the creator of a header was drawn for its slot and that its header extensions are accepted.
+/// These checks do not access the graph.
+///
+/// # Arguments:
+/// * `producer`: the address drawn to produce the block of the slot, `None` if the draw is not available yet
+///
+/// # Returns:
+/// `None` if the checks passed, the outcome of the header check otherwise
+pub(crate) fn check_header_creator(
+    config: &ConsensusConfig,
+    mip_store: &MipStore,
+    header: &SecuredHeader,
+    producer: Option<Address>,
+) -> Option<HeaderCheckOutcome> {
+    // check if it was the creator's turn to create this block
+    // (step 1 in consensus/pos.md)
+    let Some(slot_draw_address) = producer else {
+        return Some(HeaderCheckOutcome::WaitForSlot); // TODO properly handle PoS errors
+    };
+    if header.content_creator_address != slot_draw_address {
+        // it was not the creator's turn to create a block for this slot
+        return Some(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+            format!("Bad creator turn for the slot:{}", header.content.slot),
+        )));
+    }
+
+    // the extension section is only accepted once a network version enabling it is active
+    if !header.content.extensions.is_empty()
+        && !header_extensions_enabled_at(config, mip_store, header.content.slot)
+    {
+        return Some(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+            format!(
+                "header extensions are not enabled at slot {}",
+                header.content.slot
+            ),
+        )));
+    }
+
+    // check the header extensions known by the node, unknown ones are ignored
+    if let Err(err) = check_header_extensions(&header.content.extensions, &config.header_extensions)
+    {
+        return Some(HeaderCheckOutcome::Discard(DiscardReason::Invalid(err)));
+    }
+    None
+}
+
+/// Check that the endorsers of a header were drawn for their indexes. This check does not access the graph.
+///
+/// # Arguments:
+/// * `endorsement_draws`: the addresses drawn to endorse at the slot of the header, `None` if the draws are not available yet
+pub(crate) fn check_endorsement_draws(
+    header: &SecuredHeader,
+    endorsement_draws: Option<&[Address]>,
+) -> EndorsementsCheckOutcome {
+    let Some(endorsement_draws) = endorsement_draws else {
+        return EndorsementsCheckOutcome::WaitForSlot;
+    };
+    for endorsement in header.content.endorsements.iter() {
+        // check that the draw is correct
+        if endorsement.content_creator_address
+            != endorsement_draws[endorsement.content.index as usize]
+        {
+            return EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(format!(
+                "endorser draw mismatch for header in slot: {}",
+                header.content.slot
+            )));
+        }
+
+        // note that the following aspects are checked in protocol
+        // * signature
+        // * index reuse
+        // * slot matching the block's
+        // * the endorsed block is the containing block's parent
+    }
+
+    EndorsementsCheckOutcome::Proceed
+}
+
 impl ConsensusState {
     // Verify that we haven't already received 2 blocks for this slot
     // If the block isn't already present two times we save it and return false
@@ -130,7 +232,7 @@ impl ConsensusState {
         block_id: BlockId,
         header: SecuredHeader,
     ) -> BlockStatus {
-        self.maybe_note_attack_attempt(&reason, &block_id);
+        self.maybe_note_attack_attempt(&reason, &block_id, &header);
         massa_trace!("consensus.block_graph.process.incoming_header.discarded", {"block_id": block_id, "reason": reason});
         // count stales
         if reason == DiscardReason::Stale {
@@ -138,6 +240,12 @@ impl ConsensusState {
                 block_id,
                 (header.content_creator_address, header.content.slot),
             );
+        } else {
+            self.audit(AuditEvent::BlockDiscarded {
+                block_id,
+                slot: header.content.slot,
+                reason: reason.clone(),
+            });
         }
         // discard
         BlockStatus::Discarded {
@@ -157,6 +265,7 @@ impl ConsensusState {
     /// - Valid thread.
     /// - Check that the block is older than the latest final one in thread.
     /// - Check if it was the creator's turn to create this block.
+    /// - Check that header extensions are enabled, and check the known ones.
     /// - Check parents are present.
     /// - Check the topological consistency of the parents.
     /// - Check endorsements.
@@ -177,7 +286,6 @@ impl ConsensusState {
             Vec::with_capacity(self.config.thread_count as usize);
         let mut incomp = PreHashSet::<BlockId>::default();
         let mut missing_deps = PreHashSet::<BlockId>::default();
-        let creator_addr = header.content_creator_address;
 
         // check that is older than the latest final block in that thread
         // Note: this excludes genesis blocks
@@ -187,22 +295,15 @@ impl ConsensusState {
             return HeaderCheckOutcome::Discard(DiscardReason::Stale);
         }
 
-        // check if it was the creator's turn to create this block
-        // (step 1 in consensus/pos.md)
-        let slot_draw_address = match self
-            .channels
-            .selector_controller
-            .get_producer(header.content.slot)
-        {
-            Ok(draw) => draw,
-            Err(_) => return HeaderCheckOutcome::WaitForSlot, // TODO properly handle PoS errors
-        };
-        if creator_addr != slot_draw_address {
-            // it was not the creator's turn to create a block for this slot
-            return HeaderCheckOutcome::Discard(DiscardReason::Invalid(format!(
-                "Bad creator turn for the slot:{}",
-                header.content.slot
-            )));
+        // check the draw of the creator and the header extensions, unless an insertion shard already did
+        let precheck = self.prechecked_headers.lock().get(block_id);
+        if !precheck.map_or(false, |precheck| precheck.creator) {
+            let producer = self.get_producer(header.content.slot).ok();
+            if let Some(outcome) =
+                check_header_creator(&self.config, &self.channels.mip_store, header, producer)
+            {
+                return outcome;
+            }
         }
 
         // check if block is in the future: queue it
@@ -243,6 +344,23 @@ impl ConsensusState {
                         )));
                     }
 
+                    // check that the parent is not too far behind (genesis parents excepted)
+                    let parent_age = header
+                        .content
+                        .slot
+                        .period
+                        .saturating_sub(parent.slot.period);
+                    if self.config.max_parent_age > 0
+                        && parent.slot.period != self.config.last_start_period
+                        && parent_age > self.config.max_parent_age
+                    {
+                        return HeaderCheckOutcome::Discard(DiscardReason::ParentTooOld {
+                            parent: parent_hash,
+                            age: parent_age,
+                            max_age: self.config.max_parent_age,
+                        });
+                    }
+
                     // inherit parent incompatibilities
                     // and ensure parents are mutually compatible
                     if let Some(p_incomp) = self.gi_head.get(&parent_hash) {
@@ -325,8 +443,13 @@ impl ConsensusState {
             }
         }
 
-        // check endorsements
-        match self.check_endorsements(header) {
+        // check endorsements, unless an insertion shard already did
+        let endorsements_outcome = if precheck.map_or(false, |precheck| precheck.endorsements) {
+            EndorsementsCheckOutcome::Proceed
+        } else {
+            self.check_endorsements(header)
+        };
+        match endorsements_outcome {
             EndorsementsCheckOutcome::Proceed => {}
             EndorsementsCheckOutcome::Discard(reason) => {
                 return HeaderCheckOutcome::Discard(reason)
@@ -470,33 +593,12 @@ impl ConsensusState {
     /// * endorser was selected for that (slot, index)
     /// * endorsed slot is `parent_in_own_thread` slot
     pub fn check_endorsements(&self, header: &SecuredHeader) -> EndorsementsCheckOutcome {
-        // check endorsements
-        let endorsement_draws = match self
-            .channels
-            .selector_controller
-            .get_selection(header.content.slot)
-        {
-            Ok(sel) => sel.endorsements,
-            Err(_) => return EndorsementsCheckOutcome::WaitForSlot,
-        };
-        for endorsement in header.content.endorsements.iter() {
-            // check that the draw is correct
-            if endorsement.content_creator_address
-                != endorsement_draws[endorsement.content.index as usize]
-            {
-                return EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(format!(
-                    "endorser draw mismatch for header in slot: {}",
-                    header.content.slot
-                )));
-            }
-
-            // note that the following aspects are checked in protocol
-            // * signature
-            // * index reuse
-            // * slot matching the block's
-            // * the endorsed block is the containing block's parent
-        }
-
-        EndorsementsCheckOutcome::Proceed
+        let selection = self.get_selection(header.content.slot).ok();
+        check_endorsement_draws(
+            header,
+            selection
+                .as_ref()
+                .map(|selection| selection.endorsements.as_slice()),
+        )
     }
 }
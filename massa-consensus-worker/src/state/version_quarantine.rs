@@ -0,0 +1,146 @@
+//! Quarantine of the headers of network versions not activated yet.
+//!
+//! During a network upgrade, the peers may activate a new network version slightly before the local
+//! versioning store does. Their headers are held in quarantine instead of being discarded, and are
+//! registered in the graph at the first slot tick at which their version is active at their slot.
+//! Only the versions that the local versioning store has started or locked in are quarantined,
+//! the headers of other versions are discarded as invalid.
+//! Quarantined headers that become stale are dropped, and at most `max_quarantined_headers` are held.
+
+use massa_consensus_exports::{
+    audit_log::AuditEvent,
+    block_status::{BlockStatus, DiscardReason},
+    error::ConsensusError,
+};
+use massa_models::{
+    block_header::SecuredHeader, block_id::BlockId, slot::Slot, timeslots::get_block_slot_timestamp,
+};
+use massa_versioning::versioning::ComponentStateTypeId;
+use tracing::debug;
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Network version active at `slot` according to the local versioning store
+    fn network_version_at(&self, slot: Slot) -> Result<u32, ConsensusError> {
+        let timestamp = get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            slot,
+        )?;
+        Ok(self
+            .channels
+            .mip_store
+            .get_network_version_active_at(timestamp))
+    }
+
+    /// Whether the local versioning store has started or locked in the MIP of network `version`,
+    /// so that it may become active soon
+    fn is_network_version_scheduled(&self, version: u32) -> bool {
+        self.channels
+            .mip_store
+            .get_mip_status()
+            .iter()
+            .any(|(mip_info, state)| {
+                mip_info.version == version
+                    && matches!(
+                        state,
+                        ComponentStateTypeId::Started | ComponentStateTypeId::LockedIn
+                    )
+            })
+    }
+
+    /// Quarantine a header whose network version is not active at its slot yet,
+    /// or discard it as invalid if its version is not scheduled.
+    ///
+    /// # Returns
+    /// Whether the header was taken out of the regular processing, either quarantined,
+    /// dropped because the quarantine is full or discarded
+    pub(crate) fn quarantine_if_version_inactive(
+        &mut self,
+        block_id: BlockId,
+        header: &SecuredHeader,
+    ) -> Result<bool, ConsensusError> {
+        if self.blocks_state.get(&block_id).is_some() {
+            return Ok(false);
+        }
+        let active_version = self.network_version_at(header.content.slot)?;
+        if header.content.current_version <= active_version {
+            return Ok(false);
+        }
+        if !self.is_network_version_scheduled(header.content.current_version) {
+            let reason = DiscardReason::Invalid(format!(
+                "network version {} is neither started nor locked in",
+                header.content.current_version
+            ));
+            debug!("discarding header {}: {:?}", block_id, reason);
+            self.audit(AuditEvent::BlockDiscarded {
+                block_id,
+                slot: header.content.slot,
+                reason: reason.clone(),
+            });
+            let sequence_number = self.blocks_state.sequence_counter();
+            self.blocks_state.transition_map(&block_id, |_, _| {
+                Some(BlockStatus::Discarded {
+                    slot: header.content.slot,
+                    creator: header.content_creator_address,
+                    parents: header.content.parents.clone(),
+                    reason,
+                    sequence_number,
+                })
+            });
+            return Ok(true);
+        }
+        if !self.quarantined_headers.contains_key(&block_id)
+            && self.quarantined_headers.len() >= self.config.max_quarantined_headers
+        {
+            debug!(
+                "dropping header {} of network version {}: the version quarantine is full",
+                block_id, header.content.current_version
+            );
+            return Ok(true);
+        }
+        debug!(
+            "quarantining header {} of network version {}, version {} being active at slot {}",
+            block_id, header.content.current_version, active_version, header.content.slot
+        );
+        self.quarantined_headers.insert(block_id, header.clone());
+        Ok(true)
+    }
+
+    /// Register the quarantined headers whose network version is now active at their slot,
+    /// and drop the ones that became stale. Called at each slot tick.
+    pub(crate) fn release_quarantined_headers(
+        &mut self,
+        current_slot: Slot,
+    ) -> Result<(), ConsensusError> {
+        if self.quarantined_headers.is_empty() {
+            return Ok(());
+        }
+        let mut stale = Vec::new();
+        let mut released = Vec::new();
+        for (block_id, header) in self.quarantined_headers.iter() {
+            let slot = header.content.slot;
+            if slot.period <= self.latest_final_blocks_periods[slot.thread as usize].1 {
+                stale.push(*block_id);
+            } else if header.content.current_version <= self.network_version_at(slot)? {
+                released.push(*block_id);
+            }
+        }
+        for block_id in stale {
+            debug!("dropping stale quarantined header {}", block_id);
+            self.quarantined_headers.remove(&block_id);
+        }
+        for block_id in released {
+            if let Some(header) = self.quarantined_headers.remove(&block_id) {
+                debug!(
+                    "releasing quarantined header {}: network version {} is now active",
+                    block_id, header.content.current_version
+                );
+                self.register_incoming_header(block_id, header, Some(current_slot))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+//! Entry point of the forced block status transitions.
+//!
+//! Execution or pool tests can start a consensus worker with `start_consensus_worker_with_forced_transitions`
+//! and use the returned `ForcedTransitions` to finalize or discard blocks without crafting
+//! the sequence of blocks that would lead the graph there.
+
+use std::sync::Arc;
+
+use massa_consensus_exports::{
+    bootstrapable_graph::BootstrapableGraph, error::ConsensusError, ConsensusChannels,
+    ConsensusConfig, ConsensusController, ConsensusManager,
+};
+use massa_metrics::MassaMetrics;
+use massa_models::{block_id::BlockId, prehash::PreHashSet, slot::Slot};
+use massa_storage::Storage;
+use parking_lot::RwLock;
+
+use crate::state::ConsensusState;
+use crate::worker::launch_consensus_worker;
+
+/// Handle forcing block status transitions on a running consensus worker
+#[derive(Clone)]
+pub struct ForcedTransitions {
+    shared_state: Arc<RwLock<ConsensusState>>,
+}
+
+impl ForcedTransitions {
+    /// Make final all the blocks of the blockclique at or before `slot`,
+    /// discarding the active blocks incompatible with them as stale.
+    ///
+    /// # Returns
+    /// The ids of the blocks that were made final
+    pub fn finalize_up_to(&self, slot: Slot) -> Result<PreHashSet<BlockId>, ConsensusError> {
+        self.shared_state.write().force_finalize_up_to(slot)
+    }
+
+    /// Discard the given active non-final blocks as stale, along with their descendants.
+    ///
+    /// # Returns
+    /// The ids of the discarded blocks, including the descendants
+    pub fn discard(&self, block_ids: &[BlockId]) -> Result<PreHashSet<BlockId>, ConsensusError> {
+        self.shared_state.write().force_discard(block_ids)
+    }
+}
+
+/// Create a new consensus worker thread, like `start_consensus_worker`,
+/// along with a handle forcing block status transitions on it.
+pub fn start_consensus_worker_with_forced_transitions(
+    config: ConsensusConfig,
+    channels: ConsensusChannels,
+    init_graph: Option<BootstrapableGraph>,
+    storage: Storage,
+    massa_metrics: MassaMetrics,
+) -> (
+    Box<dyn ConsensusController>,
+    Box<dyn ConsensusManager>,
+    ForcedTransitions,
+) {
+    let (controller, manager, shared_state) =
+        launch_consensus_worker(config, channels, init_graph, storage, massa_metrics);
+    (controller, manager, ForcedTransitions { shared_state })
+}
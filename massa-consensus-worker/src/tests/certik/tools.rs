@@ -3,7 +3,8 @@ use std::{future::Future, sync::Arc, time::Duration};
 use crate::start_consensus_worker;
 use crossbeam_channel::Receiver;
 use massa_consensus_exports::{
-    events::ConsensusEvent, ConsensusChannels, ConsensusConfig, ConsensusController,
+    block_provenance::BlockOrigin, events::ConsensusEvent, ConsensusChannels, ConsensusConfig,
+    ConsensusController,
 };
 use massa_execution_exports::test_exports::MockExecutionController;
 use massa_hash::Hash;
@@ -232,7 +233,7 @@ pub fn register_block(
         block.id,
         block.content.header.content.slot,
         storage.clone(),
-        false,
+        BlockOrigin::Api,
     );
     match selector_receiver
         .recv_timeout(Duration::from_millis(1000))
@@ -2,5 +2,7 @@ mod tools;
 mod universe;
 
 pub mod scenarios;
+#[cfg(feature = "simulation")]
+pub mod simulation_scenarios;
 pub mod three_four_threads_scenarios;
 pub mod two_threads_scenarios;
@@ -1,5 +1,6 @@
 use std::{
     collections::{HashSet, VecDeque},
+    sync::Arc,
     time::Duration,
 };
 
@@ -7,20 +8,46 @@ use super::{
     tools::{consensus_test, register_block},
     universe::{ConsensusForeignControllers, ConsensusTestUniverse},
 };
-use crate::tests::tools::create_block;
-use massa_consensus_exports::ConsensusConfig;
+use crate::tests::tools::{create_block, mip_store_with_locked_in_version};
+use massa_consensus_exports::{
+    block_filter::BlockFilter,
+    block_provenance::BlockOrigin,
+    block_status::{BlockCountsByStatus, DiscardReason, HeaderKnowledge},
+    checkpoint::ConsensusCheckpoint,
+    error::ConsensusError,
+    required_blocks::RequiredBlockReason,
+    stall_diagnostics::FinalityStallDiagnostics,
+    submission::{ProcessingResult, SubmissionOutcome},
+    ConsensusConfig,
+};
+use massa_db_exports::{MassaDBConfig, MassaDBController, ShareableMassaDBController};
+use massa_db_worker::MassaDB;
 use massa_execution_exports::MockExecutionController;
+use massa_hash::Hash;
 use massa_models::{
-    address::Address, block::BlockGraphStatus, block_id::BlockId, config::ENDORSEMENT_COUNT,
+    address::Address,
+    amount::Amount,
+    block::{Block, BlockGraphStatus, BlockSerializer},
+    block_header::{BlockHeader, BlockHeaderSerializer},
+    block_header_extension::BlockHeaderExtension,
+    block_id::BlockId,
+    config::{CHAINID, ENDORSEMENT_COUNT},
+    pagination::PageRequest,
+    secure_share::{SecureShareContent, SecureShareSerializer},
     slot::Slot,
+    streaming_step::StreamingStep,
+    timeslots::get_latest_block_slot_at_timestamp,
 };
 use massa_pool_exports::MockPoolController;
 use massa_pos_exports::{MockSelectorController, Selection};
+use massa_protocol_exports::PeerId;
+use massa_serialization::Serializer;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use massa_test_framework::TestUniverse;
 use massa_time::MassaTime;
 use mockall::Sequence;
+use parking_lot::RwLock;
 
 #[test]
 fn test_genesis_block_creation() {
@@ -59,9 +86,8 @@ fn test_genesis_block_creation() {
     assert_eq!(genesis_hashes.len() as u8, thread_count);
 }
 
-/// This test tests that the blocks are well processed by consensus even if they are not sent in a sorted way.
 #[test]
-fn test_unsorted_block() {
+fn test_explain_fitness_genesis() {
     let staking_key: KeyPair = KeyPair::generate(0).unwrap();
     let cfg = ConsensusConfig {
         t0: MassaTime::from_millis(1000),
@@ -73,28 +99,11 @@ fn test_unsorted_block() {
         genesis_key: staking_key.clone(),
         ..ConsensusConfig::default()
     };
-
-    let start_period = 3;
-    let sent_slot_order = vec![
-        Slot::new(1 + start_period, 0),
-        Slot::new(1 + start_period, 1),
-        Slot::new(3 + start_period, 0),
-        Slot::new(4 + start_period, 1),
-        Slot::new(4 + start_period, 0),
-        Slot::new(2 + start_period, 0),
-        Slot::new(3 + start_period, 1),
-        Slot::new(2 + start_period, 1),
-    ];
-    let staking_address = Address::from_public_key(&staking_key.get_public_key());
-
     let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
-    let storage = foreign_controllers.storage.clone();
-
     foreign_controllers
         .execution_controller
         .expect_update_blockclique_status()
         .return_once(|_, _, _| {});
-    //TODO: Improve checks here
     foreign_controllers
         .pool_controller
         .expect_notify_final_cs_periods()
@@ -103,211 +112,85 @@ fn test_unsorted_block() {
         .pool_controller
         .expect_add_denunciation_precursor()
         .returning(|_| {});
-    let mut sequence = Sequence::new();
-    for slot in sent_slot_order.into_iter() {
-        foreign_controllers
-            .selector_controller
-            .expect_get_producer()
-            .times(1)
-            .in_sequence(&mut sequence)
-            .returning(move |sent_slot| {
-                assert_eq!(sent_slot, slot);
-                Ok(staking_address)
-            });
-    }
-    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg.clone());
     let genesis_hashes = universe
         .module_controller
         .get_block_graph_status(None, None)
         .expect("could not get block graph status")
         .genesis_blocks;
-    // create test blocks
-
-    let t0s1 = create_block(
-        Slot::new(1 + start_period, 0),
-        genesis_hashes.clone(),
-        &staking_key,
-    );
-
-    let t1s1 = create_block(
-        Slot::new(1 + start_period, 1),
-        genesis_hashes.clone(),
-        &staking_key,
-    );
-
-    let t0s2 = create_block(
-        Slot::new(2 + start_period, 0),
-        vec![t0s1.id, t1s1.id],
-        &staking_key,
-    );
-    let t1s2 = create_block(
-        Slot::new(2 + start_period, 1),
-        vec![t0s1.id, t1s1.id],
-        &staking_key,
-    );
-
-    let t0s3 = create_block(
-        Slot::new(3 + start_period, 0),
-        vec![t0s2.id, t1s2.id],
-        &staking_key,
-    );
-    let t1s3 = create_block(
-        Slot::new(3 + start_period, 1),
-        vec![t0s2.id, t1s2.id],
-        &staking_key,
-    );
 
-    let t0s4 = create_block(
-        Slot::new(4 + start_period, 0),
-        vec![t0s3.id, t1s3.id],
-        &staking_key,
-    );
-    let t1s4 = create_block(
-        Slot::new(4 + start_period, 1),
-        vec![t0s3.id, t1s3.id],
-        &staking_key,
-    );
+    // genesis blocks are final, carry no endorsements and are not part of any clique
+    let explanation = universe
+        .module_controller
+        .explain_fitness(genesis_hashes[0])
+        .expect("could not explain genesis fitness");
+    assert_eq!(explanation.block_id, genesis_hashes[0]);
+    assert!(explanation.is_final);
+    assert_eq!(explanation.endorsement_count, 0);
+    assert_eq!(explanation.own_fitness, 1);
+    assert_eq!(explanation.delta_f0, cfg.delta_f0);
+    assert!(explanation.cliques.is_empty());
 
-    // send blocks  t0s1, t1s1,
-    register_block(&universe.module_controller, t0s1.clone(), storage.clone());
-    register_block(&universe.module_controller, t1s1.clone(), storage.clone());
-    //register blocks t0s3, t1s4, t0s4, t0s2, t1s3, t1s2
-    register_block(&universe.module_controller, t0s3.clone(), storage.clone());
-    register_block(&universe.module_controller, t1s4.clone(), storage.clone());
-    register_block(&universe.module_controller, t0s4.clone(), storage.clone());
-    register_block(&universe.module_controller, t0s2.clone(), storage.clone());
-    register_block(&universe.module_controller, t1s3.clone(), storage.clone());
-    register_block(&universe.module_controller, t1s2.clone(), storage.clone());
+    // unknown blocks cannot be explained
+    let unknown_block = create_block(Slot::new(1, 0), genesis_hashes.clone(), &staking_key);
+    assert!(universe
+        .module_controller
+        .explain_fitness(unknown_block.id)
+        .is_err());
 }
 
 #[test]
-fn test_parallel_incompatibility() {
-    let thread_count = 2;
+fn test_list_required_active_blocks_explained_genesis() {
     let staking_key: KeyPair = KeyPair::generate(0).unwrap();
     let cfg = ConsensusConfig {
-        t0: MassaTime::from_millis(100),
-        thread_count,
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
         genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
         force_keep_final_periods_without_ops: 128,
-        force_keep_final_periods: 10,
-        delta_f0: 32,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key,
         ..ConsensusConfig::default()
     };
-    let storage = Storage::create_root();
-    let staking_address = Address::from_public_key(&staking_key.get_public_key());
-
-    let mut execution_controller = Box::new(MockExecutionController::new());
-    execution_controller
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
         .expect_update_blockclique_status()
-        .returning(|_, _, _| {});
-    let mut pool_controller = Box::new(MockPoolController::new());
-    pool_controller
+        .return_once(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
         .expect_notify_final_cs_periods()
         .returning(|_| {});
-    pool_controller
+    foreign_controllers
+        .pool_controller
         .expect_add_denunciation_precursor()
         .returning(|_| {});
-    let mut selector_controller = Box::new(MockSelectorController::new());
-    selector_controller
-        .expect_get_producer()
-        .returning(move |_| Ok(staking_address));
-    selector_controller
-        .expect_get_selection()
-        .returning(move |_| {
-            Ok(Selection {
-                producer: staking_address,
-                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
-            })
-        });
-    consensus_test(
-        cfg.clone(),
-        execution_controller,
-        pool_controller,
-        selector_controller,
-        move |consensus_controller| {
-            let genesis = consensus_controller
-                .get_block_graph_status(None, None)
-                .expect("could not get block graph status")
-                .genesis_blocks;
-
-            let block_1 = create_block(Slot::new(1, 0), vec![genesis[0], genesis[1]], &staking_key);
-            register_block(&consensus_controller, block_1.clone(), storage.clone());
-
-            let block_2 = create_block(Slot::new(1, 1), vec![genesis[0], genesis[1]], &staking_key);
-            register_block(&consensus_controller, block_2.clone(), storage.clone());
-
-            let block_3 = create_block(Slot::new(2, 0), vec![block_1.id, genesis[1]], &staking_key);
-            register_block(&consensus_controller, block_3.clone(), storage.clone());
-
-            let block_4 = create_block(Slot::new(2, 1), vec![genesis[0], block_2.id], &staking_key);
-            register_block(&consensus_controller, block_4.clone(), storage.clone());
-
-            std::thread::sleep(Duration::from_millis(500));
-            let status = consensus_controller
-                .get_block_graph_status(None, None)
-                .expect("could not get block graph status");
-
-            assert!(if let Some(h) = status.gi_head.get(&block_4.id) {
-                h.contains(&block_3.id)
-            } else {
-                panic!("missing block in gi_head")
-            });
-
-            assert_eq!(status.max_cliques.len(), 2);
-
-            for clique in status.max_cliques.clone() {
-                if clique.block_ids.contains(&block_3.id) && clique.block_ids.contains(&block_4.id)
-                {
-                    panic!("incompatible blocks in the same clique")
-                }
-            }
-
-            assert_eq!(
-                &status.best_parents,
-                &vec![
-                    (block_3.id, block_3.content.header.content.slot.period),
-                    (block_2.id, block_2.content.header.content.slot.period),
-                ],
-                "wrong best_parents"
-            );
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
 
-            let mut latest_extra_blocks = VecDeque::new();
-            for extend_i in 0..33 {
-                let status = consensus_controller
-                    .get_block_graph_status(None, None)
-                    .expect("could not get block graph status");
-                let block = create_block(
-                    Slot::new(3 + extend_i, 0),
-                    status.best_parents.iter().map(|(b, _p)| *b).collect(),
-                    &staking_key,
-                );
-                register_block(&consensus_controller, block.clone(), storage.clone());
-                std::thread::sleep(Duration::from_millis(100));
-                latest_extra_blocks.push_back(block.id);
-                while latest_extra_blocks.len() > cfg.delta_f0 as usize + 1 {
-                    latest_extra_blocks.pop_front();
-                }
-            }
-            let latest_extra_blocks: HashSet<BlockId> = latest_extra_blocks.into_iter().collect();
-            let status = consensus_controller
-                .get_block_graph_status(None, None)
-                .expect("could not get block graph status");
-            assert_eq!(status.max_cliques.len(), 1, "wrong cliques (len)");
-            assert_eq!(
-                status.max_cliques[0]
-                    .block_ids
-                    .iter()
-                    .cloned()
-                    .collect::<HashSet<BlockId>>(),
-                latest_extra_blocks,
-                "wrong cliques"
-            );
-        },
+    // only the genesis blocks are retained, as the latest final blocks of their thread
+    let required = universe
+        .module_controller
+        .list_required_active_blocks_explained(None)
+        .expect("could not list required blocks");
+    assert_eq!(
+        required
+            .iter()
+            .map(|explanation| (explanation.block_id, explanation.reason.clone()))
+            .collect::<Vec<_>>(),
+        genesis_hashes
+            .iter()
+            .map(|id| (*id, RequiredBlockReason::LatestFinal))
+            .collect::<Vec<_>>()
     );
 }
 
 #[test]
-fn test_parent_in_the_future() {
+fn test_block_summaries_genesis() {
     let staking_key: KeyPair = KeyPair::generate(0).unwrap();
     let cfg = ConsensusConfig {
         t0: MassaTime::from_millis(1000),
@@ -315,21 +198,15 @@ fn test_parent_in_the_future() {
         genesis_timestamp: MassaTime::now(),
         force_keep_final_periods: 50,
         force_keep_final_periods_without_ops: 128,
-        max_future_processing_blocks: 2,
-        block_db_prune_interval: MassaTime::from_millis(250),
-        genesis_key: staking_key.clone(),
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key,
         ..ConsensusConfig::default()
     };
-    let staking_address = Address::from_public_key(&staking_key.get_public_key());
-    let start_period = 100;
-
     let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
-    let storage = foreign_controllers.storage.clone();
-
     foreign_controllers
         .execution_controller
         .expect_update_blockclique_status()
-        .returning(|_, _, _| {});
+        .return_once(|_, _, _| {});
     foreign_controllers
         .pool_controller
         .expect_notify_final_cs_periods()
@@ -338,65 +215,2642 @@ fn test_parent_in_the_future() {
         .pool_controller
         .expect_add_denunciation_precursor()
         .returning(|_| {});
-    foreign_controllers
-        .selector_controller
-        .expect_get_producer()
-        .returning(move |_| Ok(staking_address));
-    foreign_controllers
-        .selector_controller
-        .expect_get_selection()
-        .returning(move |_| {
-            Ok(Selection {
-                producer: staking_address,
-                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
-            })
-        });
-
+    let storage = foreign_controllers.storage.clone();
     let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
     let genesis_hashes = universe
         .module_controller
         .get_block_graph_status(None, None)
         .expect("could not get block graph status")
         .genesis_blocks;
-    // create test blocks
 
-    let t0s1 = create_block(
-        Slot::new(start_period, 0),
-        genesis_hashes.clone(),
-        &staking_key,
+    // genesis blocks are empty, sorted by thread
+    let summaries = universe
+        .module_controller
+        .get_block_summaries(Slot::new(0, 0)..=Slot::new(0, 1));
+    assert_eq!(
+        summaries
+            .iter()
+            .map(|summary| summary.block_id)
+            .collect::<Vec<_>>(),
+        genesis_hashes
     );
+    for summary in &summaries {
+        assert_eq!(summary.operation_count, 0);
+        assert_eq!(summary.total_fees, Some(Amount::zero()));
+        assert_eq!(summary.endorsement_count, 0);
+        // the size covers the signature and the public key, not only the content
+        let mut serialized = Vec::new();
+        SecureShareSerializer::new()
+            .serialize(
+                storage
+                    .read_blocks()
+                    .get(&summary.block_id)
+                    .expect("genesis block not in storage"),
+                &mut serialized,
+            )
+            .unwrap();
+        assert_eq!(summary.size, serialized.len());
+    }
 
-    let t1s1 = create_block(
+    // no block after genesis yet
+    assert!(universe
+        .module_controller
+        .get_block_summaries(Slot::new(1, 0)..=Slot::new(10, 1))
+        .is_empty());
+}
+
+#[test]
+fn test_find_blocks_genesis() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let genesis_creator = Address::from_public_key(&staking_key.get_public_key());
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key,
+        ..ConsensusConfig::default()
+    };
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .return_once(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+    let find = |filter: BlockFilter, request: PageRequest| {
+        let page = universe.module_controller.find_blocks(filter, request);
+        let ids: Vec<BlockId> = page.items.iter().map(|summary| summary.block_id).collect();
+        (ids, page.next_cursor)
+    };
+
+    // the final genesis blocks of the genesis creator, one page per block
+    let filter = BlockFilter {
+        creator: Some(genesis_creator),
+        is_final: Some(true),
+        ..Default::default()
+    };
+    let (first_page, cursor) = find(filter.clone(), PageRequest::first(1));
+    assert_eq!(first_page, vec![genesis_hashes[0]]);
+    let (second_page, cursor) = find(filter, PageRequest::after(cursor.unwrap(), 1));
+    assert_eq!(second_page, vec![genesis_hashes[1]]);
+    assert!(cursor.is_none());
+
+    // the filters are combined
+    let filter = BlockFilter {
+        start_slot: Some(Slot::new(0, 1)),
+        end_slot: Some(Slot::new(0, 1)),
+        ..Default::default()
+    };
+    assert_eq!(
+        find(filter, PageRequest::first(10)).0,
+        vec![genesis_hashes[1]]
+    );
+    for filter in [
+        BlockFilter {
+            is_final: Some(false),
+            ..Default::default()
+        },
+        BlockFilter {
+            min_endorsement_count: Some(1),
+            ..Default::default()
+        },
+        BlockFilter {
+            parent: Some(genesis_hashes[0]),
+            ..Default::default()
+        },
+        BlockFilter {
+            creator: Some(Address::from_public_key(
+                &KeyPair::generate(0).unwrap().get_public_key(),
+            )),
+            ..Default::default()
+        },
+    ] {
+        assert!(find(filter, PageRequest::first(10)).0.is_empty());
+    }
+}
+
+#[test]
+fn test_slot_occupancy_genesis() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key,
+        ..ConsensusConfig::default()
+    };
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .return_once(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+
+    // only the genesis blocks are final: no slot of the first cycle can be inspected yet
+    let occupancy = universe
+        .module_controller
+        .get_slot_occupancy(0)
+        .expect("could not get slot occupancy");
+    assert_eq!(occupancy.cycle, 0);
+    assert_eq!(occupancy.threads.len(), 2);
+    assert!(occupancy
+        .threads
+        .iter()
+        .all(|thread| thread.covered_periods.is_none()));
+    assert_eq!(occupancy.filled_slots(), 0);
+    assert_eq!(occupancy.missed_slots(), 0);
+}
+
+#[test]
+fn test_light_mode_bootstrap_not_available() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key,
+        light_mode: true,
+        ..ConsensusConfig::default()
+    };
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .return_once(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+
+    // a light node cannot serve bootstrap parts, they require the operations of the final blocks
+    let res = universe
+        .module_controller
+        .get_bootstrap_part(StreamingStep::Started, StreamingStep::Started);
+    assert!(matches!(
+        res,
+        Err(ConsensusError::NotAvailableInLightMode(_))
+    ));
+}
+
+#[test]
+fn test_drop_executed_block_bodies_bootstrap_not_available() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(500);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key,
+        block_db_prune_interval: MassaTime::from_millis(100),
+        drop_executed_block_bodies: true,
+        ..ConsensusConfig::default()
+    };
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    // execution only applied the genesis block of thread 0
+    foreign_controllers
+        .execution_controller
+        .expect_get_final_cursor()
+        .returning(|| Slot::new(0, 0));
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // the operations of the executed final blocks are dropped, bootstrap parts cannot be served
+    let res = universe
+        .module_controller
+        .get_bootstrap_part(StreamingStep::Started, StreamingStep::Started);
+    assert!(matches!(
+        res,
+        Err(ConsensusError::ExecutedBlockBodiesDropped(_))
+    ));
+
+    // wait for a slot tick followed by a pruning
+    std::thread::sleep(t0.saturating_mul(3).to_duration());
+
+    // the body of the executed genesis block was stripped
+    let res = universe
+        .module_controller
+        .get_blocks_with_storage(&genesis_hashes[..1]);
+    assert!(matches!(
+        res,
+        Err(ConsensusError::ExecutedBlockBodiesDropped(_))
+    ));
+    // the genesis block after the final cursor of execution keeps its storage
+    let blocks = universe
+        .module_controller
+        .get_blocks_with_storage(&genesis_hashes[1..])
+        .expect("the block after the final cursor should keep its storage");
+    let (status, storage) = blocks[0].as_ref().expect("genesis block should be known");
+    assert_eq!(*status, BlockGraphStatus::Final);
+    assert!(storage.read_blocks().contains(&genesis_hashes[1]));
+}
+
+#[test]
+fn test_update_config() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .return_once(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg.clone());
+
+    // non-structural parameters are reloaded
+    universe
+        .module_controller
+        .update_config(ConsensusConfig {
+            max_future_processing_blocks: 20,
+            bootstrap_part_size: 5,
+            ..cfg.clone()
+        })
+        .expect("could not reload non-structural parameters");
+
+    // structural parameters are rejected
+    let err = universe
+        .module_controller
+        .update_config(ConsensusConfig {
+            thread_count: 4,
+            max_future_processing_blocks: 30,
+            ..cfg.clone()
+        })
+        .expect_err("thread_count change should be rejected");
+    assert!(err.to_string().contains("thread_count"));
+
+    // the parent limits are network constants, not reloadable settings
+    let err = universe
+        .module_controller
+        .update_config(ConsensusConfig {
+            max_parent_age: 3,
+            max_parent_execution_lag: 1,
+            ..cfg.clone()
+        })
+        .expect_err("parent limit changes should be rejected");
+    assert!(err.to_string().contains("max_parent_age"));
+    assert!(err.to_string().contains("max_parent_execution_lag"));
+
+    // the constants in effect are those of the initial configuration
+    let summary = universe.module_controller.get_config_summary();
+    assert_eq!(summary.thread_count, 2);
+    assert_eq!(summary.t0, cfg.t0);
+    assert_eq!(summary.endorsement_count, cfg.endorsement_count);
+    assert_eq!(summary.finality_threshold, cfg.delta_f0);
+    assert_eq!(summary.genesis_timestamp, cfg.genesis_timestamp);
+
+    // slots and timestamps are converted with those constants
+    let slot = Slot::new(3, 1);
+    let timestamp = universe
+        .module_controller
+        .slot_to_timestamp(slot)
+        .expect("could not get the slot timestamp");
+    assert_eq!(
+        timestamp,
+        cfg.genesis_timestamp
+            .saturating_add(cfg.t0.checked_mul(3).unwrap())
+            .saturating_add(cfg.t0.checked_div_u64(2).unwrap())
+    );
+    assert_eq!(
+        universe
+            .module_controller
+            .timestamp_to_slot(timestamp)
+            .unwrap(),
+        Some(slot)
+    );
+    assert_eq!(
+        universe
+            .module_controller
+            .timestamp_to_slot(cfg.genesis_timestamp.saturating_sub(cfg.t0))
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_parent_candidates_genesis() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .return_once(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // the only candidates are the genesis blocks, which are also the best parents
+    let candidates = universe.module_controller.get_parent_candidates();
+    assert_eq!(candidates.candidates.len(), 2);
+    for (thread, thread_candidates) in candidates.candidates.iter().enumerate() {
+        assert_eq!(thread_candidates.len(), 1);
+        assert_eq!(thread_candidates[0].block_id, genesis_hashes[thread]);
+        assert!(thread_candidates[0].is_final);
+    }
+    assert!(candidates.is_valid_choice(Slot::new(1, 0), &candidates.best_parents));
+
+    // parents must be earlier than the produced block
+    assert!(!candidates.is_valid_choice(Slot::new(0, 1), &candidates.best_parents));
+}
+
+#[test]
+fn test_repair_graph_clean() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .return_once(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+
+    // a freshly created graph has nothing to repair
+    let report = universe.module_controller.repair_graph();
+    assert!(report.is_clean(), "unexpected repair: {:?}", report);
+}
+
+#[test]
+fn test_latest_blockclique_block_before_time() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .return_once(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg.clone());
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // no slot before genesis
+    assert_eq!(
+        universe
+            .module_controller
+            .get_latest_blockclique_block_before_time(cfg.genesis_timestamp.saturating_sub(cfg.t0))
+            .unwrap(),
+        None
+    );
+    // only the first genesis block is at genesis time
+    assert_eq!(
+        universe
+            .module_controller
+            .get_latest_blockclique_block_before_time(cfg.genesis_timestamp)
+            .unwrap(),
+        Some(genesis_hashes[0])
+    );
+    // missed slots are skipped
+    assert_eq!(
+        universe
+            .module_controller
+            .get_latest_blockclique_block_before_time(
+                cfg.genesis_timestamp
+                    .saturating_add(cfg.t0.checked_mul(10).unwrap())
+            )
+            .unwrap(),
+        Some(genesis_hashes[1])
+    );
+}
+
+#[test]
+fn test_active_block_count_by_status() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .return_once(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg.clone());
+
+    // only the genesis blocks are known, and they are final
+    assert_eq!(
+        universe
+            .module_controller
+            .get_active_block_count_by_status(),
+        BlockCountsByStatus {
+            active_final: cfg.thread_count as usize,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_blockclique_history() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(200),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(MassaTime::from_millis(1000)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // nothing is recorded before the first blockclique change
+    assert_eq!(
+        universe
+            .module_controller
+            .get_blockclique_at(Slot::new(u64::MAX, 0)),
+        None
+    );
+
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes, &staking_key);
+    register_block(&universe.module_controller, t0s1.clone(), storage);
+    std::thread::sleep(Duration::from_millis(500));
+
+    // the change is recorded at the slot it happened: it is only visible from that slot on
+    let blockclique = universe
+        .module_controller
+        .get_blockclique_at(Slot::new(u64::MAX, 0))
+        .expect("blockclique change not recorded");
+    assert!(blockclique.contains(&t0s1.id));
+    assert_eq!(
+        universe
+            .module_controller
+            .get_blockclique_at(Slot::new(0, 0)),
+        None
+    );
+}
+
+#[test]
+fn test_best_parents_history() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(200);
+    let genesis_timestamp = MassaTime::now().saturating_sub(MassaTime::from_millis(1000));
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp,
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // nothing is recorded before the first tick
+    assert_eq!(
+        universe
+            .module_controller
+            .get_best_parents_at(Slot::new(0, 0)),
+        None
+    );
+    std::thread::sleep(Duration::from_millis(300));
+    let genesis_parents: Vec<(BlockId, u64)> = genesis_hashes.iter().map(|id| (*id, 0)).collect();
+    assert_eq!(
+        universe
+            .module_controller
+            .get_best_parents_at(Slot::new(u64::MAX, 0)),
+        Some(genesis_parents.clone())
+    );
+
+    // let the tick of the current slot happen before the registration
+    let slot_before_registration =
+        get_latest_block_slot_at_timestamp(2, t0, genesis_timestamp, MassaTime::now())
+            .unwrap()
+            .unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes.clone(), &staking_key);
+    register_block(&universe.module_controller, t0s1.clone(), storage);
+    std::thread::sleep(Duration::from_millis(300));
+
+    // the ticks after the registration see the new block, the earlier ones still see the genesis blocks
+    let best_parents = universe
+        .module_controller
+        .get_best_parents_at(Slot::new(u64::MAX, 0))
+        .expect("best parents not recorded");
+    assert_eq!(best_parents[0], (t0s1.id, 1));
+    assert_eq!(
+        best_parents,
+        *universe.module_controller.get_best_parents_snapshot()
+    );
+    assert_eq!(
+        universe
+            .module_controller
+            .get_best_parents_at(slot_before_registration),
+        Some(genesis_parents)
+    );
+}
+
+/// This test tests that the blocks are well processed by consensus even if they are not sent in a sorted way.
+#[test]
+fn test_unsorted_block() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+
+    let start_period = 3;
+    let sent_slot_order = vec![
+        Slot::new(1 + start_period, 0),
+        Slot::new(1 + start_period, 1),
+        Slot::new(3 + start_period, 0),
+        Slot::new(4 + start_period, 1),
+        Slot::new(4 + start_period, 0),
+        Slot::new(2 + start_period, 0),
+        Slot::new(3 + start_period, 1),
+        Slot::new(2 + start_period, 1),
+    ];
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let storage = foreign_controllers.storage.clone();
+
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .return_once(|_, _, _| {});
+    //TODO: Improve checks here
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let mut sequence = Sequence::new();
+    for slot in sent_slot_order.into_iter() {
+        foreign_controllers
+            .selector_controller
+            .expect_get_producer()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(move |sent_slot| {
+                assert_eq!(sent_slot, slot);
+                Ok(staking_address)
+            });
+    }
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+    // create test blocks
+
+    let t0s1 = create_block(
+        Slot::new(1 + start_period, 0),
+        genesis_hashes.clone(),
+        &staking_key,
+    );
+
+    let t1s1 = create_block(
+        Slot::new(1 + start_period, 1),
+        genesis_hashes.clone(),
+        &staking_key,
+    );
+
+    let t0s2 = create_block(
+        Slot::new(2 + start_period, 0),
+        vec![t0s1.id, t1s1.id],
+        &staking_key,
+    );
+    let t1s2 = create_block(
+        Slot::new(2 + start_period, 1),
+        vec![t0s1.id, t1s1.id],
+        &staking_key,
+    );
+
+    let t0s3 = create_block(
+        Slot::new(3 + start_period, 0),
+        vec![t0s2.id, t1s2.id],
+        &staking_key,
+    );
+    let t1s3 = create_block(
+        Slot::new(3 + start_period, 1),
+        vec![t0s2.id, t1s2.id],
+        &staking_key,
+    );
+
+    let t0s4 = create_block(
+        Slot::new(4 + start_period, 0),
+        vec![t0s3.id, t1s3.id],
+        &staking_key,
+    );
+    let t1s4 = create_block(
+        Slot::new(4 + start_period, 1),
+        vec![t0s3.id, t1s3.id],
+        &staking_key,
+    );
+
+    // send blocks  t0s1, t1s1,
+    register_block(&universe.module_controller, t0s1.clone(), storage.clone());
+    register_block(&universe.module_controller, t1s1.clone(), storage.clone());
+    //register blocks t0s3, t1s4, t0s4, t0s2, t1s3, t1s2
+    register_block(&universe.module_controller, t0s3.clone(), storage.clone());
+    register_block(&universe.module_controller, t1s4.clone(), storage.clone());
+    register_block(&universe.module_controller, t0s4.clone(), storage.clone());
+    register_block(&universe.module_controller, t0s2.clone(), storage.clone());
+    register_block(&universe.module_controller, t1s3.clone(), storage.clone());
+    register_block(&universe.module_controller, t1s2.clone(), storage.clone());
+}
+
+#[test]
+fn test_parallel_incompatibility() {
+    let thread_count = 2;
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(100),
+        thread_count,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods_without_ops: 128,
+        force_keep_final_periods: 10,
+        delta_f0: 32,
+        ..ConsensusConfig::default()
+    };
+    let storage = Storage::create_root();
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut execution_controller = Box::new(MockExecutionController::new());
+    execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    let mut pool_controller = Box::new(MockPoolController::new());
+    pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let mut selector_controller = Box::new(MockSelectorController::new());
+    selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+    consensus_test(
+        cfg.clone(),
+        execution_controller,
+        pool_controller,
+        selector_controller,
+        move |consensus_controller| {
+            let genesis = consensus_controller
+                .get_block_graph_status(None, None)
+                .expect("could not get block graph status")
+                .genesis_blocks;
+
+            let block_1 = create_block(Slot::new(1, 0), vec![genesis[0], genesis[1]], &staking_key);
+            register_block(&consensus_controller, block_1.clone(), storage.clone());
+
+            let block_2 = create_block(Slot::new(1, 1), vec![genesis[0], genesis[1]], &staking_key);
+            register_block(&consensus_controller, block_2.clone(), storage.clone());
+
+            let block_3 = create_block(Slot::new(2, 0), vec![block_1.id, genesis[1]], &staking_key);
+            register_block(&consensus_controller, block_3.clone(), storage.clone());
+
+            let block_4 = create_block(Slot::new(2, 1), vec![genesis[0], block_2.id], &staking_key);
+            register_block(&consensus_controller, block_4.clone(), storage.clone());
+
+            std::thread::sleep(Duration::from_millis(500));
+            let status = consensus_controller
+                .get_block_graph_status(None, None)
+                .expect("could not get block graph status");
+
+            assert!(if let Some(h) = status.gi_head.get(&block_4.id) {
+                h.contains(&block_3.id)
+            } else {
+                panic!("missing block in gi_head")
+            });
+
+            assert_eq!(status.max_cliques.len(), 2);
+
+            for clique in status.max_cliques.clone() {
+                if clique.block_ids.contains(&block_3.id) && clique.block_ids.contains(&block_4.id)
+                {
+                    panic!("incompatible blocks in the same clique")
+                }
+            }
+
+            assert_eq!(
+                &status.best_parents,
+                &vec![
+                    (block_3.id, block_3.content.header.content.slot.period),
+                    (block_2.id, block_2.content.header.content.slot.period),
+                ],
+                "wrong best_parents"
+            );
+
+            let mut latest_extra_blocks = VecDeque::new();
+            for extend_i in 0..33 {
+                let status = consensus_controller
+                    .get_block_graph_status(None, None)
+                    .expect("could not get block graph status");
+                let block = create_block(
+                    Slot::new(3 + extend_i, 0),
+                    status.best_parents.iter().map(|(b, _p)| *b).collect(),
+                    &staking_key,
+                );
+                register_block(&consensus_controller, block.clone(), storage.clone());
+                std::thread::sleep(Duration::from_millis(100));
+                latest_extra_blocks.push_back(block.id);
+                while latest_extra_blocks.len() > cfg.delta_f0 as usize + 1 {
+                    latest_extra_blocks.pop_front();
+                }
+            }
+            let latest_extra_blocks: HashSet<BlockId> = latest_extra_blocks.into_iter().collect();
+            let status = consensus_controller
+                .get_block_graph_status(None, None)
+                .expect("could not get block graph status");
+            assert_eq!(status.max_cliques.len(), 1, "wrong cliques (len)");
+            assert_eq!(
+                status.max_cliques[0]
+                    .block_ids
+                    .iter()
+                    .cloned()
+                    .collect::<HashSet<BlockId>>(),
+                latest_extra_blocks,
+                "wrong cliques"
+            );
+        },
+    );
+}
+
+#[test]
+fn test_exposed_cliques_pruning() {
+    let thread_count = 2;
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(100),
+        thread_count,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods_without_ops: 128,
+        force_keep_final_periods: 10,
+        delta_f0: 32,
+        exposed_clique_fitness_margin: 0,
+        ..ConsensusConfig::default()
+    };
+    let storage = Storage::create_root();
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut execution_controller = Box::new(MockExecutionController::new());
+    execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    let mut pool_controller = Box::new(MockPoolController::new());
+    pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    let mut selector_controller = Box::new(MockSelectorController::new());
+    selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+    consensus_test(
+        cfg.clone(),
+        execution_controller,
+        pool_controller,
+        selector_controller,
+        move |consensus_controller| {
+            let genesis = consensus_controller
+                .get_block_graph_status(None, None)
+                .expect("could not get block graph status")
+                .genesis_blocks;
+
+            // two cliques of the same fitness
+            let block_1 = create_block(Slot::new(1, 0), vec![genesis[0], genesis[1]], &staking_key);
+            register_block(&consensus_controller, block_1.clone(), storage.clone());
+            let block_2 = create_block(Slot::new(1, 1), vec![genesis[0], genesis[1]], &staking_key);
+            register_block(&consensus_controller, block_2.clone(), storage.clone());
+            let block_3 = create_block(Slot::new(2, 0), vec![block_1.id, genesis[1]], &staking_key);
+            register_block(&consensus_controller, block_3.clone(), storage.clone());
+            let block_4 = create_block(Slot::new(2, 1), vec![genesis[0], block_2.id], &staking_key);
+            register_block(&consensus_controller, block_4.clone(), storage.clone());
+            std::thread::sleep(Duration::from_millis(500));
+
+            // none of them is strictly dominated
+            assert_eq!(consensus_controller.get_cliques_snapshot().len(), 2);
+            assert_eq!(
+                consensus_controller
+                    .get_stats()
+                    .unwrap()
+                    .pruned_clique_count,
+                0
+            );
+
+            // extend the blockclique: the other clique is dominated but stays in the graph
+            let status = consensus_controller
+                .get_block_graph_status(None, None)
+                .expect("could not get block graph status");
+            let block_5 = create_block(
+                Slot::new(3, 0),
+                status.best_parents.iter().map(|(b, _p)| *b).collect(),
+                &staking_key,
+            );
+            register_block(&consensus_controller, block_5.clone(), storage.clone());
+            std::thread::sleep(Duration::from_millis(500));
+
+            let cliques = consensus_controller.get_cliques_snapshot();
+            assert_eq!(cliques.len(), 1);
+            assert!(cliques[0].is_blockclique);
+            assert!(cliques[0].block_ids.contains(&block_5.id));
+            let stats = consensus_controller.get_stats().unwrap();
+            assert_eq!(stats.clique_count, 2);
+            assert_eq!(stats.pruned_clique_count, 1);
+            let status = consensus_controller
+                .get_block_graph_status(None, None)
+                .expect("could not get block graph status");
+            assert_eq!(status.max_cliques.len(), 2);
+        },
+    );
+}
+
+#[test]
+fn test_parent_in_the_future() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 2,
+        block_db_prune_interval: MassaTime::from_millis(250),
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+    let start_period = 100;
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let storage = foreign_controllers.storage.clone();
+
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+    // create test blocks
+
+    let t0s1 = create_block(
+        Slot::new(start_period, 0),
+        genesis_hashes.clone(),
+        &staking_key,
+    );
+
+    let t1s1 = create_block(
         Slot::new(start_period, 1),
         genesis_hashes.clone(),
         &staking_key,
     );
 
-    let t0s2 = create_block(
-        Slot::new(start_period.saturating_add(1), 0),
-        vec![t0s1.id, t1s1.id],
-        &staking_key,
+    let t0s2 = create_block(
+        Slot::new(start_period.saturating_add(1), 0),
+        vec![t0s1.id, t1s1.id],
+        &staking_key,
+    );
+
+    // send blocks  t0s1, t1s1,
+    register_block(&universe.module_controller, t0s1.clone(), storage.clone());
+    register_block(&universe.module_controller, t1s1.clone(), storage.clone());
+    //register blocks t0s2
+    register_block(&universe.module_controller, t0s2.clone(), storage.clone());
+
+    // Wait for blocks_db to be pruned
+    std::thread::sleep(Duration::from_millis(1500));
+    // We only keep t0s2 and t1s1 because of max_future_processing_blocks set to 2
+    let status = universe
+        .module_controller
+        .get_block_statuses(&[t0s1.id, t1s1.id, t0s2.id]);
+    assert_eq!(
+        status,
+        vec![
+            BlockGraphStatus::WaitingForSlot,
+            BlockGraphStatus::WaitingForSlot,
+            BlockGraphStatus::NotFound
+        ],
+        "wrong status"
+    );
+}
+
+#[test]
+fn test_max_parent_age() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        max_parent_age: 3,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // genesis parents are never too old
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes.clone(), &staking_key);
+    let t1s1 = create_block(Slot::new(1, 1), genesis_hashes, &staking_key);
+    let t0s4 = create_block(Slot::new(4, 0), vec![t0s1.id, t1s1.id], &staking_key);
+    let t0s5 = create_block(Slot::new(5, 0), vec![t0s1.id, t1s1.id], &staking_key);
+    register_block(&universe.module_controller, t0s1.clone(), storage.clone());
+    register_block(&universe.module_controller, t1s1.clone(), storage.clone());
+    register_block(&universe.module_controller, t0s4.clone(), storage.clone());
+    register_block(&universe.module_controller, t0s5.clone(), storage.clone());
+    std::thread::sleep(Duration::from_millis(500));
+
+    let status = universe
+        .module_controller
+        .get_block_statuses(&[t0s1.id, t1s1.id, t0s4.id, t0s5.id]);
+    assert!(status[..3]
+        .iter()
+        .all(|status| *status != BlockGraphStatus::Discarded));
+    assert_eq!(status[3], BlockGraphStatus::Discarded);
+
+    // the discard reason tells which parent is too old
+    let graph = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status");
+    assert_eq!(
+        graph
+            .discarded_blocks
+            .get(&t0s5.id)
+            .map(|(reason, _)| reason),
+        Some(&DiscardReason::ParentTooOld {
+            parent: t0s1.id,
+            age: 4,
+            max_age: 3,
+        })
+    );
+}
+
+#[test]
+fn test_forced_transitions() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes.clone(), &staking_key);
+    let t1s1 = create_block(Slot::new(1, 1), genesis_hashes, &staking_key);
+    let t0s2 = create_block(Slot::new(2, 0), vec![t0s1.id, t1s1.id], &staking_key);
+    register_block(&universe.module_controller, t0s1.clone(), storage.clone());
+    register_block(&universe.module_controller, t1s1.clone(), storage.clone());
+    register_block(&universe.module_controller, t0s2.clone(), storage.clone());
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(
+        universe
+            .module_controller
+            .get_block_statuses(&[t0s1.id, t1s1.id, t0s2.id]),
+        vec![BlockGraphStatus::ActiveInBlockclique; 3]
+    );
+
+    // discarding a block discards its descendants
+    let discarded = universe
+        .forced_transitions
+        .discard(&[t0s1.id])
+        .expect("could not discard t0s1");
+    assert_eq!(discarded, [t0s1.id, t0s2.id].into_iter().collect());
+    assert_eq!(
+        universe
+            .module_controller
+            .get_block_statuses(&[t0s1.id, t1s1.id, t0s2.id]),
+        vec![
+            BlockGraphStatus::Discarded,
+            BlockGraphStatus::ActiveInBlockclique,
+            BlockGraphStatus::Discarded
+        ]
+    );
+    universe
+        .forced_transitions
+        .discard(&[t0s1.id])
+        .expect_err("a discarded block can't be discarded again");
+
+    // the remaining blockclique blocks are made final
+    let finalized = universe
+        .forced_transitions
+        .finalize_up_to(Slot::new(1, 1))
+        .expect("could not finalize up to slot (1, 1)");
+    assert_eq!(finalized, [t1s1.id].into_iter().collect());
+    assert_eq!(
+        universe.module_controller.get_block_statuses(&[t1s1.id]),
+        vec![BlockGraphStatus::Final]
+    );
+    universe
+        .forced_transitions
+        .discard(&[t1s1.id])
+        .expect_err("a final block can't be discarded");
+}
+
+#[test]
+fn test_register_block_slot_mismatch() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let mut storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // a block registered under another slot than the one of its header is not indexed
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes.clone(), &staking_key);
+    storage.store_block(t0s1.clone());
+    let ticket = universe.module_controller.register_block(
+        t0s1.id,
+        Slot::new(2, 0),
+        storage.clone(),
+        BlockOrigin::Api,
+    );
+    assert!(ticket.queue_position.is_some());
+    // a block registered under the slot of its header is processed
+    let t1s1 = create_block(Slot::new(1, 1), genesis_hashes, &staking_key);
+    register_block(&universe.module_controller, t1s1.clone(), storage.clone());
+    std::thread::sleep(Duration::from_millis(500));
+
+    let status = universe
+        .module_controller
+        .get_block_statuses(&[t0s1.id, t1s1.id]);
+    assert_eq!(status[0], BlockGraphStatus::NotFound);
+    assert_ne!(status[1], BlockGraphStatus::NotFound);
+    // the ticket of the rejected registration reports the block as dropped
+    assert_eq!(ticket.outcome(), Some(SubmissionOutcome::Dropped));
+}
+
+#[test]
+fn test_register_block_with_result() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let mut storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // a valid block is accepted
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes.clone(), &staking_key);
+    storage.store_block(t0s1.clone());
+    let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+        t0s1.id,
+        t0s1.content.header.content.slot,
+        storage.clone(),
+        BlockOrigin::Api,
+    );
+    assert_eq!(
+        result_rx.blocking_recv().unwrap(),
+        ProcessingResult::Accepted
+    );
+
+    // a second registration of the same block is reported as a duplicate
+    let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+        t0s1.id,
+        t0s1.content.header.content.slot,
+        storage.clone(),
+        BlockOrigin::Api,
+    );
+    assert_eq!(
+        result_rx.blocking_recv().unwrap(),
+        ProcessingResult::Duplicate
+    );
+
+    // a block registered under another slot than the one of its header is dropped
+    let t1s1 = create_block(Slot::new(1, 1), genesis_hashes, &staking_key);
+    storage.store_block(t1s1.clone());
+    let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+        t1s1.id,
+        Slot::new(2, 1),
+        storage.clone(),
+        BlockOrigin::Api,
+    );
+    assert_eq!(
+        result_rx.blocking_recv().unwrap(),
+        ProcessingResult::Dropped
+    );
+}
+
+#[test]
+fn test_has_block() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let mut storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+    assert_eq!(
+        universe.module_controller.has_block(genesis_hashes[0]),
+        HeaderKnowledge::Final
+    );
+
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes, &staking_key);
+    assert_eq!(
+        universe.module_controller.has_block(t0s1.id),
+        HeaderKnowledge::Unknown
+    );
+    assert_eq!(
+        universe
+            .module_controller
+            .get_block_header_bytes(t0s1.id)
+            .unwrap(),
+        None
+    );
+    let mut header_bytes = Vec::new();
+    SecureShareSerializer::new()
+        .serialize(&t0s1.content.header, &mut header_bytes)
+        .unwrap();
+
+    // a block known by its header only still needs its body
+    let header = t0s1.content.header.clone();
+    let (_ticket, result_rx) = universe
+        .module_controller
+        .register_block_header_with_result(t0s1.id, header, BlockOrigin::Api);
+    result_rx.blocking_recv().unwrap();
+    assert_eq!(
+        universe.module_controller.has_block(t0s1.id),
+        HeaderKnowledge::HeaderOnly
+    );
+    assert_eq!(
+        universe
+            .module_controller
+            .get_block_header_bytes(t0s1.id)
+            .unwrap(),
+        Some(header_bytes.clone())
+    );
+
+    storage.store_block(t0s1.clone());
+    let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+        t0s1.id,
+        t0s1.content.header.content.slot,
+        storage.clone(),
+        BlockOrigin::Api,
+    );
+    result_rx.blocking_recv().unwrap();
+    assert_eq!(
+        universe.module_controller.has_block(t0s1.id),
+        HeaderKnowledge::Full
+    );
+    assert_eq!(
+        universe
+            .module_controller
+            .get_block_header_bytes(t0s1.id)
+            .unwrap(),
+        Some(header_bytes)
+    );
+}
+
+#[test]
+fn test_draw_prefetch() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(500);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        draw_prefetch_periods: 10,
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let mut storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    // the draws of the whole window are available at the first tick
+    foreign_controllers
+        .selector_controller
+        .expect_get_available_selections_in_range()
+        .returning(move |slot_range, _| {
+            let mut draws = std::collections::BTreeMap::new();
+            let mut slot = *slot_range.start();
+            while slot <= *slot_range.end() {
+                draws.insert(
+                    slot,
+                    Selection {
+                        producer: staking_address,
+                        endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+                    },
+                );
+                slot = slot.get_next_slot(2).unwrap();
+            }
+            Ok(draws)
+        });
+    // the block checks don't query the selector once the draws are prefetched
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .never();
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .never();
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+    // wait for the first slot tick
+    std::thread::sleep(t0.saturating_mul(2).to_duration());
+
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes, &staking_key);
+    storage.store_block(t0s1.clone());
+    let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+        t0s1.id,
+        t0s1.content.header.content.slot,
+        storage.clone(),
+        BlockOrigin::Api,
+    );
+    assert_eq!(
+        result_rx.blocking_recv().unwrap(),
+        ProcessingResult::Accepted
+    );
+}
+
+#[test]
+fn test_insertion_shards() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(500);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        insertion_shards: true,
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let mut storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+    // the draw of the creator is checked by the shard of the thread, not by the worker
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .never();
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+    // wait for the first slot tick
+    std::thread::sleep(t0.saturating_mul(2).to_duration());
+
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes.clone(), &staking_key);
+    let t1s1 = create_block(Slot::new(1, 1), genesis_hashes, &staking_key);
+    for block in [&t0s1, &t1s1] {
+        storage.store_block(block.clone());
+        let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+            block.id,
+            block.content.header.content.slot,
+            storage.clone(),
+            BlockOrigin::Api,
+        );
+        assert_eq!(
+            result_rx.blocking_recv().unwrap(),
+            ProcessingResult::Accepted
+        );
+    }
+}
+
+#[test]
+fn test_header_only_expiry() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(500);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        block_db_prune_interval: MassaTime::from_millis(100),
+        header_only_expiry_periods: 5,
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // the body of this block never arrives and its slot is more than 5 periods old
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes, &staking_key);
+    let header = t0s1.content.header.clone();
+    let (_ticket, result_rx) = universe
+        .module_controller
+        .register_block_header_with_result(t0s1.id, header, BlockOrigin::Api);
+    result_rx.blocking_recv().unwrap();
+
+    // wait for a slot tick followed by a pruning
+    std::thread::sleep(t0.saturating_mul(3).to_duration());
+    assert_eq!(
+        universe.module_controller.has_block(t0s1.id),
+        HeaderKnowledge::Unknown
+    );
+    let stats = universe.module_controller.get_stats().unwrap();
+    assert_eq!(stats.header_only_count, 0);
+    assert_eq!(stats.expired_header_count, 1);
+}
+
+#[test]
+fn test_best_parents_held_back_by_execution_lag() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        max_parent_execution_lag: 1,
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let mut storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    // the speculative execution did not go past genesis
+    foreign_controllers
+        .execution_controller
+        .expect_get_candidate_cursor()
+        .returning(|| Slot::new(0, 1));
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes.clone(), &staking_key);
+    let t0s3 = create_block(
+        Slot::new(3, 0),
+        vec![t0s1.id, genesis_hashes[1]],
+        &staking_key,
+    );
+    for block in [&t0s1, &t0s3] {
+        storage.store_block(block.clone());
+        let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+            block.id,
+            block.content.header.content.slot,
+            storage.clone(),
+            BlockOrigin::Api,
+        );
+        assert_eq!(
+            result_rx.blocking_recv().unwrap(),
+            ProcessingResult::Accepted
+        );
+    }
+
+    // the graph builds on the latest block, the factory only on the one close enough to the execution
+    let graph_best_parents = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .best_parents;
+    assert_eq!(graph_best_parents[0], (t0s3.id, 3));
+    let best_parents = universe.module_controller.get_best_parents_snapshot();
+    assert_eq!(*best_parents, vec![(t0s1.id, 1), (genesis_hashes[1], 0)]);
+    let candidates = universe.module_controller.get_parent_candidates();
+    assert_eq!(candidates.best_parents, *best_parents);
+    // the snapshot is shared until the graph changes
+    assert!(Arc::ptr_eq(
+        &best_parents,
+        &universe.module_controller.get_best_parents_snapshot()
+    ));
+    assert!(candidates.candidates[0]
+        .iter()
+        .all(|candidate| candidate.block_id != t0s3.id));
+}
+
+#[test]
+fn test_header_of_inactive_version_quarantined() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let mut storage = foreign_controllers.storage.clone();
+    // network version 1 is locked in but not active yet
+    foreign_controllers.mip_store = mip_store_with_locked_in_version(1);
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // only network version 0 is active
+    let header = BlockHeader::new_verifiable(
+        BlockHeader {
+            current_version: 1,
+            announced_version: None,
+            denunciations: vec![],
+            extensions: Vec::new(),
+            slot: Slot::new(1, 0),
+            parents: genesis_hashes.clone(),
+            operation_merkle_root: Hash::compute_from("default_val".as_bytes()),
+            endorsements: Vec::new(),
+        },
+        BlockHeaderSerializer::new(),
+        &staking_key,
+        *CHAINID,
+    )
+    .unwrap();
+    let t0s1 = Block::new_verifiable(
+        Block {
+            header: header.clone(),
+            operations: Default::default(),
+        },
+        BlockSerializer::new(),
+        &staking_key,
+        *CHAINID,
+    )
+    .unwrap();
+    let (_ticket, result_rx) = universe
+        .module_controller
+        .register_block_header_with_result(t0s1.id, header, BlockOrigin::Api);
+    assert_eq!(
+        result_rx.blocking_recv().unwrap(),
+        ProcessingResult::Dropped
+    );
+    assert_eq!(
+        universe.module_controller.has_block(t0s1.id),
+        HeaderKnowledge::Unknown
+    );
+
+    // the body does not bypass the quarantine
+    storage.store_block(t0s1.clone());
+    let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+        t0s1.id,
+        Slot::new(1, 0),
+        storage.clone(),
+        BlockOrigin::Api,
+    );
+    assert_eq!(
+        result_rx.blocking_recv().unwrap(),
+        ProcessingResult::Dropped
+    );
+    assert_eq!(
+        universe.module_controller.has_block(t0s1.id),
+        HeaderKnowledge::Unknown
+    );
+
+    // the headers of a version that is not scheduled are discarded
+    let header = BlockHeader::new_verifiable(
+        BlockHeader {
+            current_version: 2,
+            announced_version: None,
+            denunciations: vec![],
+            extensions: Vec::new(),
+            slot: Slot::new(1, 0),
+            parents: genesis_hashes.clone(),
+            operation_merkle_root: Hash::compute_from("default_val".as_bytes()),
+            endorsements: Vec::new(),
+        },
+        BlockHeaderSerializer::new(),
+        &staking_key,
+        *CHAINID,
+    )
+    .unwrap();
+    let (_ticket, result_rx) = universe
+        .module_controller
+        .register_block_header_with_result(header.id, header.clone(), BlockOrigin::Api);
+    assert!(matches!(
+        result_rx.blocking_recv().unwrap(),
+        ProcessingResult::Discarded(DiscardReason::Invalid(_))
+    ));
+    assert_eq!(
+        universe.module_controller.has_block(header.id),
+        HeaderKnowledge::Discarded
+    );
+
+    // the headers of the active version are processed
+    let t1s1 = create_block(Slot::new(1, 1), genesis_hashes, &staking_key);
+    let (_ticket, result_rx) = universe
+        .module_controller
+        .register_block_header_with_result(t1s1.id, t1s1.content.header.clone(), BlockOrigin::Api);
+    assert_eq!(
+        result_rx.blocking_recv().unwrap(),
+        ProcessingResult::Accepted
+    );
+    assert_ne!(
+        universe.module_controller.has_block(t1s1.id),
+        HeaderKnowledge::Unknown
+    );
+}
+
+#[test]
+fn test_header_extensions_not_enabled_discarded() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // no network version enabling the header extensions is active
+    let header = BlockHeader::new_verifiable(
+        BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            denunciations: vec![],
+            extensions: vec![BlockHeaderExtension {
+                id: 1,
+                version: 0,
+                data: vec![1, 2, 3],
+            }],
+            slot: Slot::new(1, 0),
+            parents: genesis_hashes.clone(),
+            operation_merkle_root: Hash::compute_from("default_val".as_bytes()),
+            endorsements: Vec::new(),
+        },
+        BlockHeaderSerializer::new(),
+        &staking_key,
+        *CHAINID,
+    )
+    .unwrap();
+    let (_ticket, result_rx) = universe
+        .module_controller
+        .register_block_header_with_result(header.id, header.clone(), BlockOrigin::Api);
+    assert!(matches!(
+        result_rx.blocking_recv().unwrap(),
+        ProcessingResult::Discarded(DiscardReason::Invalid(_))
+    ));
+    // a node-local check does not ban the creator
+    assert!(universe.module_controller.list_bans().is_empty());
+
+    // while a creator that was not drawn for the slot is banned
+    let intruder_key = KeyPair::generate(0).unwrap();
+    let intruder_block = create_block(Slot::new(1, 1), genesis_hashes, &intruder_key);
+    let (_ticket, result_rx) = universe
+        .module_controller
+        .register_block_header_with_result(
+            intruder_block.id,
+            intruder_block.content.header.clone(),
+            BlockOrigin::Api,
+        );
+    assert!(matches!(
+        result_rx.blocking_recv().unwrap(),
+        ProcessingResult::Discarded(DiscardReason::Invalid(_))
+    ));
+    let bans = universe.module_controller.list_bans();
+    assert_eq!(bans.len(), 1);
+    assert_eq!(
+        bans[0].address,
+        Address::from_public_key(&intruder_key.get_public_key())
+    );
+}
+
+#[test]
+fn test_consensus_checkpoint() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(500);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        checkpoint_interval: MassaTime::from_millis(100),
+        ..ConsensusConfig::default()
+    };
+
+    let temp_dir_db = tempfile::tempdir().expect("Unable to create a temp folder");
+    let db: ShareableMassaDBController =
+        Arc::new(RwLock::new(Box::new(MassaDB::new(MassaDBConfig {
+            path: temp_dir_db.path().to_path_buf(),
+            max_history_length: 100,
+            max_final_state_elements_size: 100,
+            max_versioning_elements_size: 100,
+            thread_count: 2,
+        })) as Box<dyn MassaDBController>));
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers.db = Some(db.clone());
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // wait for a slot tick
+    std::thread::sleep(t0.saturating_mul(2).to_duration());
+    let checkpoint = universe
+        .module_controller
+        .get_checkpoint()
+        .unwrap()
+        .expect("no checkpoint was written");
+    let genesis_periods: Vec<(BlockId, u64)> = genesis_hashes
+        .iter()
+        .map(|block_id| (*block_id, 0))
+        .collect();
+    assert!(checkpoint.slot.is_some());
+    assert_eq!(checkpoint.best_parents, genesis_periods);
+    assert_eq!(checkpoint.latest_final_blocks_periods, genesis_periods);
+    assert!(checkpoint.discarded_blocks.is_empty());
+
+    // the checkpoint is readable from the database without the worker
+    assert_eq!(ConsensusCheckpoint::load(&db).unwrap(), Some(checkpoint));
+}
+
+#[test]
+fn test_finality_stall_diagnostics() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(500);
+    let diagnostics_dir = tempfile::tempdir().expect("Unable to create a temp folder");
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        finality_stall_timeout: MassaTime::from_millis(100),
+        finality_stall_diagnostics_dir: Some(diagnostics_dir.path().to_path_buf()),
+        ..ConsensusConfig::default()
+    };
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+
+    let _universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+
+    // no block becomes final: a single bundle is written over several slot ticks
+    std::thread::sleep(t0.saturating_mul(4).to_duration());
+    let bundles: Vec<_> = std::fs::read_dir(diagnostics_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(bundles.len(), 1);
+    let diagnostics = FinalityStallDiagnostics::load(&bundles[0]).unwrap();
+    assert!(diagnostics.stalled_for() >= MassaTime::from_millis(100));
+    assert_eq!(diagnostics.latest_final_blocks_periods.len(), 2);
+    assert_eq!(diagnostics.cliques.len(), 1);
+    assert!(diagnostics.divergent_peers.is_empty());
+}
+
+#[test]
+fn test_endorser_reliability() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        delta_f0: 4,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let mut storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // nothing is counted before a block becomes final
+    let reliability = universe
+        .module_controller
+        .get_endorser_reliability(staking_address, 0..=10);
+    assert!(reliability.cycles.is_empty());
+    assert_eq!(reliability.fulfillment_rate(), None);
+
+    // chain blocks without endorsements until the first block of thread 0 is final
+    let mut parents = genesis_hashes;
+    let mut first_block = None;
+    for period in 1..=3 {
+        for thread in 0..2u8 {
+            let block = create_block(Slot::new(period, thread), parents.clone(), &staking_key);
+            parents[thread as usize] = block.id;
+            first_block.get_or_insert(block.id);
+            storage.store_block(block.clone());
+            let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+                block.id,
+                block.content.header.content.slot,
+                storage.clone(),
+                BlockOrigin::Api,
+            );
+            result_rx.blocking_recv().unwrap();
+        }
+    }
+    assert_eq!(
+        universe
+            .module_controller
+            .get_block_statuses(&[first_block.unwrap()]),
+        [BlockGraphStatus::Final]
+    );
+
+    // all the endorsement draws of the final slot were missed
+    let reliability = universe
+        .module_controller
+        .get_endorser_reliability(staking_address, 0..=10);
+    assert_eq!(reliability.address, staking_address);
+    assert_eq!(reliability.cycles.len(), 1);
+    assert_eq!(reliability.cycles[0].cycle, 0);
+    assert_eq!(reliability.drawn(), ENDORSEMENT_COUNT as u64);
+    assert_eq!(reliability.missed(), ENDORSEMENT_COUNT as u64);
+    assert_eq!(reliability.fulfillment_rate(), Some(0.0));
+
+    // cycles outside of the range are not returned
+    assert!(universe
+        .module_controller
+        .get_endorser_reliability(staking_address, 1..=10)
+        .cycles
+        .is_empty());
+}
+
+#[test]
+fn test_block_provenance() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(20)),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+    let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let mut storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // the header of a block is received from a peer, then its body is submitted through the API
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes.clone(), &staking_key);
+    let (_ticket, result_rx) = universe
+        .module_controller
+        .register_block_header_with_result(
+            t0s1.id,
+            t0s1.content.header.clone(),
+            BlockOrigin::Peer(peer_id),
+        );
+    result_rx.blocking_recv().unwrap();
+    storage.store_block(t0s1.clone());
+    let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+        t0s1.id,
+        t0s1.content.header.content.slot,
+        storage.clone(),
+        BlockOrigin::Api,
     );
+    result_rx.blocking_recv().unwrap();
+    let t1s1 = create_block(Slot::new(1, 1), genesis_hashes.clone(), &staking_key);
+    storage.store_block(t1s1.clone());
+    let (_ticket, result_rx) = universe.module_controller.register_block_with_result(
+        t1s1.id,
+        t1s1.content.header.content.slot,
+        storage.clone(),
+        BlockOrigin::Api,
+    );
+    result_rx.blocking_recv().unwrap();
 
-    // send blocks  t0s1, t1s1,
-    register_block(&universe.module_controller, t0s1.clone(), storage.clone());
-    register_block(&universe.module_controller, t1s1.clone(), storage.clone());
-    //register blocks t0s2
-    register_block(&universe.module_controller, t0s2.clone(), storage.clone());
+    // the first registration of each block is kept
+    let export = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status");
+    let provenance = |block_id: &BlockId| {
+        export
+            .active_blocks
+            .get(block_id)
+            .expect("block missing from the graph")
+            .provenance
+            .map(|provenance| provenance.origin)
+    };
+    assert_eq!(provenance(&t0s1.id), Some(BlockOrigin::Peer(peer_id)));
+    assert_eq!(provenance(&t1s1.id), Some(BlockOrigin::Api));
+    assert_eq!(provenance(&genesis_hashes[0]), None);
 
-    // Wait for blocks_db to be pruned
-    std::thread::sleep(Duration::from_millis(1500));
-    // We only keep t0s2 and t1s1 because of max_future_processing_blocks set to 2
-    let status = universe
+    // the header was received about 19 periods after its slot
+    let stats = universe.module_controller.get_peer_propagation_stats();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].peer_id, peer_id);
+    assert_eq!(stats[0].block_count, 1);
+    assert_eq!(stats[0].mean_latency, stats[0].max_latency);
+    assert!(stats[0].mean_latency >= t0.saturating_mul(18));
+}
+
+#[test]
+fn test_catch_up_mode() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(200);
+    // the final slots lag about 20 periods behind the current slot
+    let genesis_timestamp = MassaTime::now().saturating_sub(MassaTime::from_millis(4000));
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        genesis_timestamp,
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        catch_up_lag_periods: 10,
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg.clone());
+    let genesis_hashes = universe
         .module_controller
-        .get_block_statuses(&[t0s1.id, t1s1.id, t0s2.id]);
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // the mode is entered at the first tick
+    assert!(!universe.module_controller.get_catch_up_status().active);
+    std::thread::sleep(Duration::from_millis(300));
+    let status = universe.module_controller.get_catch_up_status();
+    assert!(status.active);
+    assert!(status.lag_periods > cfg.catch_up_lag_periods);
+    assert!(status.initial_lag_periods > cfg.catch_up_lag_periods);
+    assert!(status.started_at.is_some());
+    assert!(status.progress_percent() < 100);
+
+    // the block counts are refreshed at the next tick
+    let t0s1 = create_block(Slot::new(1, 0), genesis_hashes, &staking_key);
+    register_block(&universe.module_controller, t0s1, storage);
+    std::thread::sleep(Duration::from_millis(300));
+    let counts = universe
+        .module_controller
+        .get_active_block_count_by_status();
     assert_eq!(
-        status,
-        vec![
-            BlockGraphStatus::WaitingForSlot,
-            BlockGraphStatus::WaitingForSlot,
-            BlockGraphStatus::NotFound
-        ],
-        "wrong status"
+        counts.active_final + counts.active_non_final,
+        cfg.thread_count as usize + 1
     );
 }
+
+#[test]
+fn test_execution_finality_gap() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(400),
+        thread_count: 4,
+        genesis_timestamp: MassaTime::now(),
+        force_keep_final_periods: 50,
+        force_keep_final_periods_without_ops: 128,
+        max_future_processing_blocks: 10,
+        genesis_key: staking_key.clone(),
+        readiness_max_execution_gap: 2,
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+    // execution is stalled at the first genesis slot
+    let execution_final_cursor = Arc::new(RwLock::new(Slot::new(0, 0)));
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    let cursor = execution_final_cursor.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_get_final_cursor()
+        .returning(move || *cursor.read());
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+
+    // all the genesis slots are final, execution only applied the first one
+    assert_eq!(universe.module_controller.get_finality_gap(), 3);
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(universe.module_controller.liveness());
+    assert!(!universe.module_controller.is_ready());
+    let stats = universe.module_controller.get_stats().unwrap();
+    assert_eq!(stats.execution_finality_gap, Some(3));
+
+    // execution caught up: the node is ready again at the next tick
+    *execution_final_cursor.write() = Slot::new(0, 3);
+    assert_eq!(universe.module_controller.get_finality_gap(), 0);
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(universe.module_controller.is_ready());
+}
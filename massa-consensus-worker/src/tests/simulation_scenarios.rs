@@ -0,0 +1,59 @@
+use massa_consensus_exports::ConsensusConfig;
+use massa_time::MassaTime;
+
+use crate::simulation::{FabricConfig, SimulationConfig, SimulationHarness};
+
+fn simulation_config(seed: u64, loss_rate: f64) -> SimulationConfig {
+    SimulationConfig {
+        node_count: 4,
+        consensus: ConsensusConfig {
+            t0: MassaTime::from_millis(1000),
+            thread_count: 2,
+            force_keep_final_periods: 10,
+            ..ConsensusConfig::default()
+        },
+        fabric: FabricConfig {
+            latency: MassaTime::from_millis(100),
+            jitter: MassaTime::from_millis(200),
+            loss_rate,
+            seed,
+        },
+        max_periods: 200,
+    }
+}
+
+#[test]
+fn test_simulation_finality_under_packet_loss() {
+    let mut harness = SimulationHarness::new(simulation_config(1, 0.1));
+    harness.run_slots(60);
+    harness.check_finality_agreement().unwrap();
+    for node in 0..4 {
+        assert!(
+            !harness.final_blocks(node).is_empty(),
+            "node {} finalized nothing",
+            node
+        );
+    }
+}
+
+#[test]
+fn test_simulation_partition_and_heal() {
+    let mut harness = SimulationHarness::new(simulation_config(2, 0.0));
+    harness.run_slots(20);
+    harness.fabric().partition(&[vec![0, 1], vec![2, 3]]);
+    harness.run_slots(20);
+    harness.check_finality_agreement().unwrap();
+
+    // after healing, the nodes converge and keep finalizing the same blocks
+    harness.fabric().heal();
+    harness.run_slots(60);
+    harness.check_finality_agreement().unwrap();
+    assert_eq!(
+        harness.final_blocks(0).keys().last(),
+        harness.final_blocks(3).keys().last()
+    );
+    assert_eq!(
+        harness.node(0).get_best_parents_snapshot(),
+        harness.node(3).get_best_parents_snapshot()
+    );
+}
@@ -1,14 +1,15 @@
 use std::{time::Duration, vec};
 
 use crate::start_consensus_worker;
-use massa_channel::MassaChannel;
+use massa_channel::{event_bus::MassaEventBus, MassaChannel};
 use massa_consensus_exports::{
-    ConsensusBroadcasts, ConsensusChannels, ConsensusConfig, ConsensusController,
+    block_provenance::BlockOrigin, ConsensusBroadcasts, ConsensusChannels, ConsensusConfig,
+    ConsensusController,
 };
 use massa_execution_exports::MockExecutionController;
 use massa_hash::Hash;
 use massa_metrics::MassaMetrics;
-use massa_models::config::CHAINID;
+use massa_models::config::{CHAINID, MIP_STORE_STATS_BLOCK_CONSIDERED};
 use massa_models::{
     block::{Block, BlockSerializer, SecureShareBlock},
     block_header::{BlockHeader, BlockHeaderSerializer},
@@ -22,6 +23,12 @@ use massa_pos_exports::MockSelectorController;
 use massa_protocol_exports::MockProtocolController;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
+use massa_time::MassaTime;
+use massa_versioning::test_helpers::versioning_helpers::advance_state_until;
+use massa_versioning::versioning::{
+    ComponentState, MipComponent, MipInfo, MipStatsConfig, MipStore,
+};
+use num::rational::Ratio;
 
 pub fn consensus_test<F>(
     cfg: ConsensusConfig,
@@ -65,6 +72,9 @@ pub fn consensus_test<F>(
             protocol_controller,
             pool_controller,
             selector_controller,
+            mip_store: empty_mip_store(),
+            db: None,
+            event_bus: MassaEventBus::new("test_node_event".to_string()),
         },
         None,
         storage.clone(),
@@ -83,6 +93,42 @@ pub fn consensus_test<F>(
     consensus_manager.stop();
 }
 
+/// MIP store without any MIP: the network version is 0 at every slot
+pub fn empty_mip_store() -> MipStore {
+    MipStore::try_from((
+        [],
+        MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            warn_announced_version_ratio: Ratio::new_raw(30, 100),
+        },
+    ))
+    .unwrap()
+}
+
+/// MIP store in which network `version` is locked in, its activation being far in the future
+pub fn mip_store_with_locked_in_version(version: u32) -> MipStore {
+    let mip_info = MipInfo {
+        name: format!("MIP-{:04}", version),
+        version,
+        components: [(MipComponent::Block, version)].into_iter().collect(),
+        start: MassaTime::from_millis(2),
+        timeout: MassaTime::from_millis(5),
+        activation_delay: MassaTime::from_millis(365 * 24 * 3600 * 1000),
+    };
+    let mip_state = advance_state_until(
+        ComponentState::locked_in(MassaTime::from_millis(4)),
+        &mip_info,
+    );
+    MipStore::try_from((
+        [(mip_info, mip_state)],
+        MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            warn_announced_version_ratio: Ratio::new_raw(30, 100),
+        },
+    ))
+    .unwrap()
+}
+
 // returns hash and resulting discarded blocks
 pub fn create_block(slot: Slot, best_parents: Vec<BlockId>, creator: &KeyPair) -> SecureShareBlock {
     create_block_with_merkle_root(
@@ -105,6 +151,7 @@ pub fn create_block_with_merkle_root(
             current_version: 0,
             announced_version: None,
             denunciations: vec![],
+            extensions: Vec::new(),
             slot,
             parents: best_parents,
             operation_merkle_root,
@@ -139,6 +186,6 @@ pub fn register_block(
         block.id,
         block.content.header.content.slot,
         storage.clone(),
-        false,
+        BlockOrigin::Api,
     );
 }
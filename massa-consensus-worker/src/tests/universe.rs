@@ -1,9 +1,10 @@
 use std::time::Duration;
 
-use massa_channel::MassaChannel;
+use massa_channel::{event_bus::MassaEventBus, MassaChannel};
 use massa_consensus_exports::{
     ConsensusBroadcasts, ConsensusChannels, ConsensusConfig, ConsensusController,
 };
+use massa_db_exports::ShareableMassaDBController;
 use massa_execution_exports::MockExecutionController;
 use massa_metrics::MassaMetrics;
 use massa_models::config::THREAD_COUNT;
@@ -12,14 +13,18 @@ use massa_pos_exports::MockSelectorController;
 use massa_protocol_exports::MockProtocolController;
 use massa_storage::Storage;
 use massa_test_framework::TestUniverse;
+use massa_versioning::versioning::MipStore;
 
-use crate::start_consensus_worker;
+use super::tools::empty_mip_store;
+use crate::testing::{start_consensus_worker_with_forced_transitions, ForcedTransitions};
 
 pub struct ConsensusForeignControllers {
     pub execution_controller: Box<MockExecutionController>,
     pub protocol_controller: Box<MockProtocolController>,
     pub pool_controller: Box<MockPoolController>,
     pub selector_controller: Box<MockSelectorController>,
+    pub mip_store: MipStore,
+    pub db: Option<ShareableMassaDBController>,
     pub storage: Storage,
 }
 
@@ -30,6 +35,8 @@ impl ConsensusForeignControllers {
             protocol_controller: Box::new(MockProtocolController::new()),
             pool_controller: Box::new(MockPoolController::new()),
             selector_controller: Box::new(MockSelectorController::new()),
+            mip_store: empty_mip_store(),
+            db: None,
             storage: Storage::create_root(),
         }
     }
@@ -37,6 +44,7 @@ impl ConsensusForeignControllers {
 
 pub struct ConsensusTestUniverse {
     pub module_controller: Box<dyn ConsensusController>,
+    pub forced_transitions: ForcedTransitions,
 }
 
 impl TestUniverse for ConsensusTestUniverse {
@@ -63,32 +71,37 @@ impl TestUniverse for ConsensusTestUniverse {
         let (block_sender, _block_receiver) = tokio::sync::broadcast::channel(10);
         let (block_header_sender, _block_header_receiver) = tokio::sync::broadcast::channel(10);
         let (filled_block_sender, _filled_block_receiver) = tokio::sync::broadcast::channel(10);
-        let (consensus_controller, _) = start_consensus_worker(
-            config,
-            ConsensusChannels {
-                broadcasts: ConsensusBroadcasts {
-                    block_sender,
-                    block_header_sender,
-                    filled_block_sender,
+        let (consensus_controller, _, forced_transitions) =
+            start_consensus_worker_with_forced_transitions(
+                config,
+                ConsensusChannels {
+                    broadcasts: ConsensusBroadcasts {
+                        block_sender,
+                        block_header_sender,
+                        filled_block_sender,
+                    },
+                    controller_event_tx: consensus_event_sender,
+                    execution_controller: foreign_controllers.execution_controller,
+                    protocol_controller: foreign_controllers.protocol_controller,
+                    pool_controller: foreign_controllers.pool_controller,
+                    selector_controller: foreign_controllers.selector_controller,
+                    mip_store: foreign_controllers.mip_store,
+                    db: foreign_controllers.db,
+                    event_bus: MassaEventBus::new("test_node_event".to_string()),
                 },
-                controller_event_tx: consensus_event_sender,
-                execution_controller: foreign_controllers.execution_controller,
-                protocol_controller: foreign_controllers.protocol_controller,
-                pool_controller: foreign_controllers.pool_controller,
-                selector_controller: foreign_controllers.selector_controller,
-            },
-            None,
-            foreign_controllers.storage.clone(),
-            MassaMetrics::new(
-                false,
-                "0.0.0.0:9898".parse().unwrap(),
-                THREAD_COUNT,
-                Duration::from_secs(1),
-            )
-            .0,
-        );
+                None,
+                foreign_controllers.storage.clone(),
+                MassaMetrics::new(
+                    false,
+                    "0.0.0.0:9898".parse().unwrap(),
+                    THREAD_COUNT,
+                    Duration::from_secs(1),
+                )
+                .0,
+            );
         let universe = Self {
             module_controller: consensus_controller,
+            forced_transitions,
         };
         universe.initialize();
         universe
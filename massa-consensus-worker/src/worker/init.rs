@@ -1,5 +1,6 @@
 use massa_channel::receiver::MassaReceiver;
 use massa_consensus_exports::{
+    block_provenance::{BlockOrigin, BlockProvenance},
     block_status::{BlockStatus, StorageOrBlock},
     bootstrapable_graph::BootstrapableGraph,
     error::ConsensusError,
@@ -53,6 +54,7 @@ pub fn create_genesis_block(
             operation_merkle_root: Hash::compute_from(&Vec::new()),
             endorsements: Vec::new(),
             denunciations: Vec::new(),
+            extensions: Vec::new(),
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -108,6 +110,7 @@ impl ConsensusWorker {
             })?;
             let mut storage = storage.clone_without_refs();
             storage.store_block(block.clone());
+            let storage_or_block = StorageOrBlock::Storage(storage);
             genesis_block_ids.push(block.id);
             block_statuses.insert(
                 block.id,
@@ -123,7 +126,8 @@ impl ConsensusWorker {
                         fitness: block.get_fitness(),
                         same_thread_parent_creator: None,
                     }),
-                    storage_or_block: StorageOrBlock::Storage(storage),
+                    summary: storage_or_block.summarize(&block.id),
+                    storage_or_block,
                 },
             );
         }
@@ -226,10 +230,15 @@ impl ConsensusWorker {
                 write_shared_state.best_parents = latest_final_blocks_periods.clone();
                 write_shared_state.latest_final_blocks_periods = latest_final_blocks_periods;
                 for (b, storage_or_block) in final_blocks {
+                    write_shared_state.note_block_provenance(
+                        b.block_id,
+                        BlockProvenance::now(BlockOrigin::Bootstrap),
+                    );
                     write_shared_state.blocks_state.transition_map(
                         &(b.block_id.clone()),
                         |_, _| {
                             Some(BlockStatus::Active {
+                                summary: storage_or_block.summarize(&b.block_id),
                                 a_block: Box::new(b),
                                 storage_or_block,
                             })
@@ -263,6 +272,15 @@ impl ConsensusWorker {
         // because the two modules run concurrently and out of sync.
         {
             let mut write_shared_state = res_consensus.shared_state.write();
+            let active_block_ids: Vec<BlockId> = write_shared_state
+                .blocks_state
+                .active_blocks()
+                .iter()
+                .copied()
+                .collect();
+            for block_id in active_block_ids.iter() {
+                write_shared_state.index_block_operations(block_id);
+            }
             let mut block_metadata: PreHashMap<BlockId, ExecutionBlockMetadata> =
                 Default::default();
             let notify_finals: HashMap<Slot, BlockId> = write_shared_state
@@ -280,10 +298,7 @@ impl ConsensusWorker {
                     let (a_block, storage_or_block) = write_shared_state
                         .get_full_active_block(b_id)
                         .expect("active block missing from block_db");
-                    let storage = match storage_or_block {
-                        StorageOrBlock::Storage(storage) => Some(storage.clone()),
-                        _ => None,
-                    };
+                    let storage = storage_or_block.clone_storage();
                     block_metadata.insert(
                         *b_id,
                         ExecutionBlockMetadata {
@@ -300,6 +315,8 @@ impl ConsensusWorker {
                 .channels
                 .execution_controller
                 .update_blockclique_status(notify_finals, Some(notify_blockclique), block_metadata);
+            write_shared_state.refresh_block_counts();
+            write_shared_state.refresh_snapshots();
         }
 
         Ok(res_consensus)
@@ -1,17 +1,57 @@
 use std::time::Instant;
 
-use massa_consensus_exports::{error::ConsensusError, events::ConsensusEvent};
+use massa_consensus_exports::{
+    block_status::BlockStatus,
+    error::ConsensusError,
+    events::ConsensusEvent,
+    submission::{ProcessingResult, ProcessingResultSender},
+};
 use massa_models::{
+    block_id::BlockId,
     slot::Slot,
     timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
 };
 use massa_time::MassaTime;
 use tracing::{info, warn};
 
-use crate::commands::ConsensusCommand;
+use crate::{commands::ConsensusCommand, state::ConsensusState};
 
 use super::ConsensusWorker;
 
+/// Result of the processing of a registration, from the status of the block after the processing
+///
+/// # Arguments:
+/// * `state`: the consensus state after the processing
+/// * `block_id`: the registered block
+/// * `known`: whether the registration could not change the block because it was already known
+/// * `res`: the result of the processing
+fn processing_result(
+    state: &ConsensusState,
+    block_id: &BlockId,
+    known: bool,
+    res: Result<(), &ConsensusError>,
+) -> ProcessingResult {
+    if let Err(err) = res {
+        return ProcessingResult::Failed(err.to_string());
+    }
+    if known {
+        return ProcessingResult::Duplicate;
+    }
+    match state.blocks_state.get(block_id) {
+        None => ProcessingResult::Dropped,
+        Some(BlockStatus::Discarded { reason, .. }) => ProcessingResult::Discarded(reason.clone()),
+        Some(_) => ProcessingResult::Accepted,
+    }
+}
+
+/// Registration waiting for the update of the graph to report its processing result
+struct PendingRegistration {
+    block_id: BlockId,
+    known: bool,
+    res: Result<(), ConsensusError>,
+    result_tx: Option<ProcessingResultSender>,
+}
+
 enum WaitingStatus {
     Ended,
     Interrupted,
@@ -19,35 +59,104 @@ enum WaitingStatus {
 }
 
 impl ConsensusWorker {
-    /// Execute a command received from the controller also run an update of the graph after processing the command.
+    /// Execute commands received from the controller, then run a single update of the graph
+    /// if a block or a header was registered.
     ///
     /// # Arguments:
-    /// * `command`: the command to execute
+    /// * `commands`: the commands to execute, a single one unless the worker is catching up
     ///
     /// # Returns:
-    /// An error if the command failed
-    fn manage_command(&mut self, command: ConsensusCommand) -> Result<(), ConsensusError> {
+    /// The errors of the commands and of the update of the graph
+    fn manage_commands(&mut self, commands: Vec<ConsensusCommand>) -> Vec<ConsensusError> {
         let mut write_shared_state = self.shared_state.write();
-        match command {
-            ConsensusCommand::RegisterBlockHeader(block_id, header) => {
-                write_shared_state.register_block_header(block_id, header, self.previous_slot)?;
-                write_shared_state.block_db_changed()
-            }
-            ConsensusCommand::RegisterBlock(block_id, slot, block_storage, created) => {
-                write_shared_state.register_block(
+        let mut pending = Vec::with_capacity(commands.len());
+        for command in commands {
+            match command {
+                ConsensusCommand::RegisterBlockHeader(block_id, header, provenance, result_tx) => {
+                    write_shared_state.arm_submission(&block_id);
+                    write_shared_state.note_block_provenance(block_id, provenance);
+                    // a known block is not changed by its header
+                    let known = write_shared_state.genesis_hashes.contains(&block_id)
+                        || write_shared_state.blocks_state.get(&block_id).is_some();
+                    let res = write_shared_state.register_block_header(
+                        block_id,
+                        header,
+                        self.previous_slot,
+                    );
+                    pending.push(PendingRegistration {
+                        block_id,
+                        known,
+                        res,
+                        result_tx,
+                    });
+                }
+                ConsensusCommand::RegisterBlock(
                     block_id,
                     slot,
-                    self.previous_slot,
                     block_storage,
-                    created,
-                )?;
-                write_shared_state.block_db_changed()
+                    provenance,
+                    result_tx,
+                ) => {
+                    write_shared_state.arm_submission(&block_id);
+                    write_shared_state.note_block_provenance(block_id, provenance);
+                    // a block that was processed past its header is not changed by a new registration
+                    let known = write_shared_state.genesis_hashes.contains(&block_id)
+                        || matches!(
+                            write_shared_state.blocks_state.get(&block_id),
+                            Some(BlockStatus::Active { .. } | BlockStatus::Discarded { .. })
+                        );
+                    let res = write_shared_state.register_block(
+                        block_id,
+                        slot,
+                        self.previous_slot,
+                        block_storage,
+                        provenance.origin.is_local(),
+                    );
+                    pending.push(PendingRegistration {
+                        block_id,
+                        known,
+                        res,
+                        result_tx,
+                    });
+                }
+                ConsensusCommand::MarkInvalidBlock(block_id, header) => {
+                    write_shared_state.mark_invalid_block(&block_id, header);
+                }
+            }
+        }
+        if pending.is_empty() {
+            write_shared_state.refresh_snapshots();
+            return Vec::new();
+        }
+
+        // update the graph once for all the registrations
+        let db_res = write_shared_state.block_db_changed();
+        if pending.len() > 1 {
+            write_shared_state.note_batched_registrations(pending.len());
+        }
+        let mut errors = Vec::new();
+        for registration in pending {
+            if let Some(result_tx) = registration.result_tx {
+                let res = registration
+                    .res
+                    .as_ref()
+                    .map(|_| ())
+                    .and(db_res.as_ref().map(|_| ()));
+                let _ = result_tx.send(processing_result(
+                    &write_shared_state,
+                    &registration.block_id,
+                    registration.known,
+                    res,
+                ));
             }
-            ConsensusCommand::MarkInvalidBlock(block_id, header) => {
-                write_shared_state.mark_invalid_block(&block_id, header);
-                Ok(())
+            if let Err(err) = registration.res {
+                errors.push(err);
             }
         }
+        if let Err(err) = db_res {
+            errors.push(err);
+        }
+        errors
     }
 
     /// Wait and interrupt if we receive a command, a stop signal or we reach the `instant`
@@ -60,7 +169,24 @@ impl ConsensusWorker {
         match self.command_receiver.recv_deadline(deadline) {
             // message received => manage it
             Ok(command) => {
-                if let Err(err) = self.manage_command(command) {
+                // while catching up, the commands waiting in the channel are processed in a batch
+                // with a single update of the graph
+                let batch_size = {
+                    let read_shared_state = self.shared_state.read();
+                    if read_shared_state.is_catching_up() {
+                        read_shared_state.config.catch_up_batch_size
+                    } else {
+                        1
+                    }
+                };
+                let mut commands = vec![command];
+                while commands.len() < batch_size {
+                    match self.command_receiver.try_recv() {
+                        Ok(command) => commands.push(command),
+                        Err(_) => break,
+                    }
+                }
+                for err in self.manage_commands(commands) {
                     warn!("Error in consensus: {}", err);
                 }
                 WaitingStatus::Interrupted
@@ -148,14 +274,15 @@ impl ConsensusWorker {
                         if let Err(err) = write_shared_state.slot_tick(self.next_slot) {
                             warn!("Error while processing block tick: {}", err);
                         }
+                        write_shared_state
+                            .check_finality_stall(self.next_slot, self.command_receiver.len());
                     };
                     if last_prune.elapsed().as_millis()
                         > self.config.block_db_prune_interval.as_millis() as u128
                     {
-                        self.shared_state
-                            .write()
-                            .prune()
-                            .expect("Error while pruning");
+                        let mut write_shared_state = self.shared_state.write();
+                        write_shared_state.prune().expect("Error while pruning");
+                        write_shared_state.refresh_snapshots();
                         last_prune = Instant::now();
                     }
                     self.previous_slot = Some(self.next_slot);
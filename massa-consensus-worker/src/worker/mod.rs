@@ -1,25 +1,30 @@
 use massa_channel::{receiver::MassaReceiver, MassaChannel};
 use massa_consensus_exports::{
-    bootstrapable_graph::BootstrapableGraph, ConsensusChannels, ConsensusConfig,
-    ConsensusController, ConsensusManager,
+    audit_log::AuditLog, ban_list::CreatorBanList, block_status::BlockCountsByStatus,
+    bootstrapable_graph::BootstrapableGraph, health::ConsensusHealth, ConsensusChannels,
+    ConsensusConfig, ConsensusController, ConsensusManager,
 };
 use massa_metrics::MassaMetrics;
 use massa_models::block_id::BlockId;
 use massa_models::clique::Clique;
 use massa_models::config::CHANNEL_SIZE;
-use massa_models::prehash::PreHashSet;
+use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::slot::Slot;
 use massa_storage::Storage;
 use massa_time::MassaTime;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::thread;
 use std::time::Instant;
 
 use crate::commands::ConsensusCommand;
 use crate::controller::ConsensusControllerImpl;
+use crate::dedup::IncomingBlockDedup;
+use crate::insertion_shards::start_insertion_shards;
 use crate::manager::ConsensusManagerImpl;
-use crate::state::{blocks_state::BlocksState, ConsensusState};
+use crate::query_quota::QueryQuotas;
+use crate::state::{blocks_state::BlocksState, ConsensusState, FinalBlockIndex};
 
 /// The consensus worker structure that contains all information and tools for the consensus worker thread.
 pub struct ConsensusWorker {
@@ -58,13 +63,106 @@ pub fn start_consensus_worker(
     storage: Storage,
     massa_metrics: MassaMetrics,
 ) -> (Box<dyn ConsensusController>, Box<dyn ConsensusManager>) {
+    let (controller, manager, _) =
+        launch_consensus_worker(config, channels, init_graph, storage, massa_metrics);
+    (controller, manager)
+}
+
+/// Create a new consensus worker thread, also returning the state it shares with its controller.
+pub(crate) fn launch_consensus_worker(
+    config: ConsensusConfig,
+    channels: ConsensusChannels,
+    init_graph: Option<BootstrapableGraph>,
+    storage: Storage,
+    massa_metrics: MassaMetrics,
+) -> (
+    Box<dyn ConsensusController>,
+    Box<dyn ConsensusManager>,
+    Arc<RwLock<ConsensusState>>,
+) {
     let (tx, rx) = MassaChannel::new("consensus_command".to_string(), Some(CHANNEL_SIZE));
+    let broadcasts = channels.broadcasts.clone();
+    let db = channels.db.clone();
+    let state = new_consensus_state(&config, channels, &storage, massa_metrics);
+    let block_counts = state.block_counts.clone();
+    let snapshots = state.snapshots.clone();
+    let health = state.health.clone();
+    let catch_up = state.catch_up.clone();
+    let peer_blockclique_summaries = state.peer_blockclique_summaries.clone();
+    let incoming_block_dedup = state.incoming_block_dedup.clone();
+    let query_quotas = Arc::new(Mutex::new(QueryQuotas::new(
+        config.graph_export_quota_per_minute,
+        config.bootstrap_part_quota_per_minute,
+    )));
+    let submission_notifiers = state.submission_notifiers.clone();
+    // one insertion shard per thread checks the draws of the registrations before queuing them for the worker
+    let (shard_senders, shard_threads) = if config.insertion_shards {
+        start_insertion_shards(
+            &config,
+            state.channels.selector_controller.as_ref(),
+            &state.channels.mip_store,
+            &state.prechecked_headers,
+            &tx,
+        )
+    } else {
+        Default::default()
+    };
+    let shared_state = Arc::new(RwLock::new(state));
+
+    let shared_state_cloned = shared_state.clone();
+    let mut consensus_worker =
+        ConsensusWorker::new(config.clone(), rx, shared_state_cloned, init_graph, storage).unwrap();
+
+    let consensus_thread = thread::Builder::new()
+        .name("consensus worker".into())
+        .spawn(move || consensus_worker.run())
+        .expect("Can't spawn consensus thread.");
+
+    let manager = ConsensusManagerImpl {
+        consensus_thread: Some((tx.clone(), consensus_thread)),
+        shard_threads,
+    };
+
+    let controller = ConsensusControllerImpl::new(
+        tx,
+        shard_senders,
+        broadcasts,
+        shared_state.clone(),
+        block_counts,
+        snapshots,
+        health,
+        catch_up,
+        peer_blockclique_summaries,
+        incoming_block_dedup,
+        query_quotas,
+        submission_notifiers,
+        db,
+        config.broadcast_enabled,
+    );
+
+    (Box::new(controller), Box::new(manager), shared_state)
+}
+
+/// Create the state of a consensus worker, without any block: the genesis blocks or the bootstrap graph
+/// are loaded by `ConsensusWorker::new`.
+pub(crate) fn new_consensus_state(
+    config: &ConsensusConfig,
+    channels: ConsensusChannels,
+    storage: &Storage,
+    massa_metrics: MassaMetrics,
+) -> ConsensusState {
     // desync detection timespan
-    let bootstrap_part_size = config.bootstrap_part_size;
     let stats_desync_detection_timespan =
         config.t0.checked_mul(config.periods_per_cycle * 2).unwrap();
-    let broadcasts = channels.broadcasts.clone();
-    let shared_state = Arc::new(RwLock::new(ConsensusState {
+    let block_counts = Arc::new(RwLock::new(BlockCountsByStatus::default()));
+    let health = Arc::new(RwLock::new(ConsensusHealth::default()));
+    let peer_blockclique_summaries = Arc::new(RwLock::new(HashMap::new()));
+    let incoming_block_dedup = Arc::new(Mutex::new(IncomingBlockDedup::new(
+        config.block_dedup_window,
+        config.block_dedup_capacity,
+    )));
+    let submission_notifiers = Arc::new(Mutex::new(PreHashMap::default()));
+    ConsensusState {
         storage: storage.clone(),
         config: config.clone(),
         channels,
@@ -73,11 +171,13 @@ pub fn start_consensus_worker(
             fitness: 0,
             is_blockclique: true,
         }],
+        max_cliques_inexact: false,
         blocks_state: BlocksState::new(),
         to_propagate: Default::default(),
         attack_attempts: Default::default(),
         new_final_blocks: Default::default(),
         new_stale_blocks: Default::default(),
+        new_orphaned_endorsements: Default::default(),
         active_index_without_ops: Default::default(),
         save_final_periods: Default::default(),
         latest_final_blocks_periods: Default::default(),
@@ -96,29 +196,44 @@ pub fn start_consensus_worker(
         ),
         prev_blockclique: Default::default(),
         nonfinal_active_blocks_per_slot: Default::default(),
+        blockclique_history: Default::default(),
+        best_parents_history: Default::default(),
+        shared_operations: Default::default(),
+        operation_inclusions: Default::default(),
+        last_reorg_report: None,
+        block_counts,
+        snapshots: Default::default(),
+        health,
+        catch_up: Default::default(),
+        peer_blockclique_summaries,
+        majority_divergent: false,
+        expired_header_count: 0,
+        creator_bans: CreatorBanList::load(config.creator_ban_list_path.clone())
+            .expect("could not load the block creator ban list"),
+        draw_cache: Default::default(),
+        endorser_stats: Default::default(),
+        block_provenance: Default::default(),
+        quarantined_headers: Default::default(),
+        last_checkpoint: None,
+        last_finalization: MassaTime::now(),
+        finality_stall_reported: false,
+        incoming_block_dedup,
+        submission_notifiers,
+        prechecked_headers: Default::default(),
+        audit_log: config.audit_log_path.clone().map(|path| {
+            Arc::new(Mutex::new(
+                AuditLog::open(
+                    path,
+                    config.audit_log_keypair.clone(),
+                    config.audit_log_checkpoint_interval,
+                )
+                .expect("could not open the consensus audit log"),
+            ))
+        }),
+        final_block_index: config
+            .final_block_index_path
+            .clone()
+            .map(|path| Arc::new(FinalBlockIndex::open(path))),
         massa_metrics,
-    }));
-
-    let shared_state_cloned = shared_state.clone();
-    let mut consensus_worker =
-        ConsensusWorker::new(config.clone(), rx, shared_state_cloned, init_graph, storage).unwrap();
-
-    let consensus_thread = thread::Builder::new()
-        .name("consensus worker".into())
-        .spawn(move || consensus_worker.run())
-        .expect("Can't spawn consensus thread.");
-
-    let manager = ConsensusManagerImpl {
-        consensus_thread: Some((tx.clone(), consensus_thread)),
-    };
-
-    let controller = ConsensusControllerImpl::new(
-        tx,
-        broadcasts,
-        shared_state,
-        bootstrap_part_size,
-        config.broadcast_enabled,
-    );
-
-    (Box::new(controller), Box::new(manager))
+    }
 }
@@ -13,6 +13,9 @@ pub const CHANGE_ID_KEY: &[u8; 1] = b"c";
 pub const CHANGE_ID_DESER_ERROR: &str = "critical: change_id deserialization failed";
 pub const CHANGE_ID_SER_ERROR: &str = "critical: change_id serialization failed";
 
+// Consensus checkpoint, in the metadata column family
+pub const CONSENSUS_CHECKPOINT_KEY: &[u8] = b"consensus_checkpoint";
+
 // Errors
 pub const CF_ERROR: &str = "critical: rocksdb column family operation failed";
 pub const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
@@ -22,6 +25,7 @@ pub const STATE_HASH_ERROR: &str = "critical: saved state hash is corrupted";
 // Prefixes
 pub const CYCLE_HISTORY_PREFIX: &str = "cycle_history/";
 pub const DEFERRED_CREDITS_PREFIX: &str = "deferred_credits/";
+pub const PAYOUT_ADDRESSES_PREFIX: &str = "payout_addresses/";
 pub const ASYNC_POOL_PREFIX: &str = "async_pool/";
 pub const EXECUTED_OPS_PREFIX: &str = "executed_ops/";
 pub const EXECUTED_DENUNCIATIONS_PREFIX: &str = "executed_denunciations/";
@@ -41,6 +45,8 @@ pub const CYCLE_HISTORY_DESER_ERROR: &str = "critical: cycle_history deserializa
 pub const CYCLE_HISTORY_SER_ERROR: &str = "critical: cycle_history serialization failed";
 pub const DEFERRED_CREDITS_DESER_ERROR: &str = "critical: deferred_credits deserialization failed";
 pub const DEFERRED_CREDITS_SER_ERROR: &str = "critical: deferred_credits serialization failed";
+pub const PAYOUT_ADDRESSES_DESER_ERROR: &str = "critical: payout_addresses deserialization failed";
+pub const PAYOUT_ADDRESSES_SER_ERROR: &str = "critical: payout_addresses serialization failed";
 
 // Executed Ops
 pub const EXECUTED_OPS_ID_DESER_ERROR: &str = "critical: executed_ops_id deserialization failed";
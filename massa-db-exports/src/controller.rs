@@ -40,6 +40,10 @@ pub trait MassaDBController: Send + Sync + Debug {
     /// Exposes RocksDB's "get_cf" function
     fn get_cf(&self, handle_cf: &str, key: Key) -> Result<Option<Value>, MassaDBError>;
 
+    /// Write an entry of the metadata column family directly, outside of the batches and of the state hash.
+    /// Only for the node-local data that is not part of the final state and is not bootstrapped
+    fn put_metadata(&self, key: Key, value: Value) -> Result<(), MassaDBError>;
+
     /// Exposes RocksDB's "multi_get_cf" function
     fn multi_get_cf(&self, query: Vec<(&str, Key)>) -> Vec<Result<Option<Value>, MassaDBError>>;
 
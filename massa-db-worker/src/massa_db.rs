@@ -169,11 +169,11 @@ where
             // Creates an iterator from the next element after the last if defined, otherwise initialize it at the first key.
             let db_iterator = match &last_state_step {
                 StreamingStep::Ongoing(max_key) => {
-                    let mut iter = self
-                        .db
-                        .iterator_cf(handle, IteratorMode::From(max_key, Direction::Forward));
-                    iter.next();
-                    iter
+                    // seek the smallest key after the last one, which may not be in the database
+                    let mut next_key = max_key.clone();
+                    next_key.push(0);
+                    self.db
+                        .iterator_cf(handle, IteratorMode::From(&next_key, Direction::Forward))
                 }
                 _ => self.db.iterator_cf(handle, IteratorMode::Start),
             };
@@ -736,6 +736,14 @@ impl MassaDBController for RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
             .map_err(|e| MassaDBError::RocksDBError(format!("{:?}", e)))
     }
 
+    fn put_metadata(&self, key: Key, value: Value) -> Result<(), MassaDBError> {
+        let db = &self.db;
+        let handle = db.cf_handle(METADATA_CF).expect(CF_ERROR);
+
+        db.put_cf(handle, key, value)
+            .map_err(|e| MassaDBError::RocksDBError(format!("{:?}", e)))
+    }
+
     /// Exposes RocksDB's "multi_get_cf" function
     fn multi_get_cf(&self, query: Vec<(&str, Key)>) -> Vec<Result<Option<Value>, MassaDBError>> {
         let db = &self.db;
@@ -1801,4 +1809,35 @@ mod test {
         assert!(stream_batch_.is_err());
         assert!(stream_batch_.unwrap_err().to_string().contains("all our changes are strictly after last_change_id, we can't be sure we did not miss any"));
     }
+
+    #[test]
+    fn test_put_metadata() {
+        // Metadata entries written directly are readable and do not change the state hash
+
+        let temp_dir_db = tempdir().expect("Unable to create a temp folder");
+        let db_config = MassaDBConfig {
+            path: temp_dir_db.path().to_path_buf(),
+            max_history_length: 100,
+            max_final_state_elements_size: 100,
+            max_versioning_elements_size: 100,
+            thread_count: THREAD_COUNT,
+        };
+        let db = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ));
+
+        db.read()
+            .put_metadata(b"node_local".to_vec(), vec![1, 2, 3])
+            .unwrap();
+        assert_eq!(
+            db.read()
+                .get_cf(METADATA_CF, b"node_local".to_vec())
+                .unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(
+            Hash::compute_from(db.read().get_xof_db_hash().to_bytes()),
+            initial_hash()
+        );
+    }
 }
@@ -1,10 +1,14 @@
 // Copyright (c) 2023 MASSA LABS <info@massa.net>
 
 use crate::types::SlotExecutionOutput;
+use massa_channel::event_bus::MassaEventBus;
+use massa_models::node_event::NodeEvent;
 
 /// channels used by the execution worker
 #[derive(Clone)]
 pub struct ExecutionChannels {
     /// Broadcast channel for new slot execution outputs
     pub slot_execution_output_sender: tokio::sync::broadcast::Sender<SlotExecutionOutput>,
+    /// Bus used by execution to publish paid credits and cycle ends
+    pub event_bus: MassaEventBus<NodeEvent>,
 }
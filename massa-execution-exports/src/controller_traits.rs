@@ -7,6 +7,7 @@ use crate::types::{
 };
 use crate::ExecutionError;
 use crate::{ExecutionAddressInfo, ReadOnlyExecutionOutput};
+use massa_final_state::StateHashComposition;
 use massa_models::address::Address;
 use massa_models::amount::Amount;
 use massa_models::block_id::BlockId;
@@ -14,11 +15,14 @@ use massa_models::denunciation::DenunciationIndex;
 use massa_models::execution::EventFilter;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
+use massa_models::pagination::{PageRequest, PageResponse};
 use massa_models::prehash::PreHashMap;
 use massa_models::slot::Slot;
 use massa_models::stats::ExecutionStats;
+use massa_pos_exports::{CreditOriginAmounts, CreditOriginKind};
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::path::Path;
 
 #[cfg_attr(feature = "test-exports", mockall::automock)]
 /// interface that communicates with the execution worker thread
@@ -111,6 +115,54 @@ pub trait ExecutionController: Send + Sync {
     /// Get execution statistics
     fn get_stats(&self) -> ExecutionStats;
 
+    /// Get the latest final slot applied to the final state
+    fn get_final_cursor(&self) -> Slot;
+
+    /// Get the latest candidate slot executed speculatively
+    fn get_candidate_cursor(&self) -> Slot;
+
+    /// Get the deferred credits that were paid to an address at or after a given final slot.
+    ///
+    /// # Arguments
+    /// * `address`: the address that received the credits
+    /// * `from_slot`: the first slot to include
+    ///
+    /// # Returns
+    /// The paid credits ordered by slot.
+    /// Credits paid before the node-local retention window are forgotten.
+    fn get_paid_credits(&self, address: &Address, from_slot: Slot) -> Vec<(Slot, Amount)>;
+
+    /// Get a page of the final deferred credits, ordered by slot then address
+    ///
+    /// # Arguments
+    /// * `request`: the page to get, the first one or the one following a cursor
+    ///
+    /// # Returns
+    /// The `(slot, address, amount)` credits of the page, with the cursor of the next page
+    fn get_deferred_credits(&self, request: PageRequest) -> PageResponse<(Slot, Address, Amount)>;
+
+    /// Get the cumulative amounts of deferred credits scheduled and paid, per origin.
+    ///
+    /// The totals are maintained at each final slot since the node started.
+    /// Paid credits are attributed to the origins of the credits scheduled for their slot,
+    /// those scheduled before the node started being `Unattributed`.
+    fn get_credit_origin_totals(&self) -> BTreeMap<CreditOriginKind, CreditOriginAmounts>;
+
+    /// Break the final state hash down into the hashes of the components of the final state
+    /// (ledger, async pool, cycle history, deferred credits, executed ops...) at the latest final slot.
+    /// This scans the whole final state and is meant for debugging hash divergences between nodes.
+    fn get_final_state_hash_composition(&self) -> StateHashComposition;
+
+    /// Inject the deferred credits of a CSV file of `period,thread,address,amount` lines into the speculative PoS state,
+    /// to test payout scenarios without crafting operations. Only available on sandbox builds.
+    ///
+    /// The file is validated as a whole, and the credits are added at the next final slot execution,
+    /// after which the resulting final state hash is logged.
+    ///
+    /// # Returns
+    /// The number of injected credits
+    fn inject_deferred_credits(&self, csv_path: &Path) -> Result<usize, ExecutionError>;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn ExecutionController>`.
     fn clone_box(&self) -> Box<dyn ExecutionController>;
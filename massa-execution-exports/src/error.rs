@@ -30,6 +30,12 @@ pub enum ExecutionError {
     /// `RollSell` error: {0}
     RollSellError(String),
 
+    /// `SetPayoutAddress` error: {0}
+    SetPayoutAddressError(String),
+
+    /// `Delegate` or `Undelegate` error: {0}
+    DelegationError(String),
+
     /// Slash roll or deferred credits  error: {0}
     SlashError(String),
 
@@ -67,6 +73,9 @@ pub enum ExecutionError {
 
     /// Factory error: {0}
     FactoryError(#[from] FactoryError),
+
+    /// Deferred credits injection error: {0}
+    DeferredCreditsInjectionError(String),
 }
 
 /// Execution query errors
@@ -82,6 +82,17 @@ pub struct ExecutionConfig {
     pub hd_cache_size: usize,
     /// Amount of entries removed when `hd_cache_size` is reached
     pub snip_amount: usize,
+    /// Path to the node-local history of paid deferred credits
+    pub paid_credits_history_path: PathBuf,
+    /// Number of periods during which paid deferred credits are kept in the history
+    pub paid_credits_history_periods: u64,
+    /// roll sale reimbursements strictly above this amount are split across consecutive slots,
+    /// once a network version enables it (`MipComponent::RollSellCreditSplit`)
+    pub roll_sell_credit_split_threshold: Amount,
+    /// number of consecutive slots a split roll sale reimbursement is spread over
+    pub roll_sell_credit_split_parts: u64,
+    /// number of periods by which the pending deferred credits are postponed when the emergency payout pause is activated
+    pub payout_pause_delay_periods: u64,
     /// Number of roll to remove per denunciation
     pub roll_count_to_slash_on_denunciation: u64,
     /// Denunciation expire delta
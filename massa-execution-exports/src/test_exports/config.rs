@@ -61,6 +61,11 @@ impl Default for ExecutionConfig {
             lru_cache_size: 1000,
             hd_cache_size: 10_000,
             snip_amount: 10,
+            paid_credits_history_path: TempDir::new().unwrap().path().to_path_buf(),
+            paid_credits_history_periods: 1000,
+            roll_sell_credit_split_threshold: ROLL_SELL_CREDIT_SPLIT_THRESHOLD,
+            roll_sell_credit_split_parts: ROLL_SELL_CREDIT_SPLIT_PARTS,
+            payout_pause_delay_periods: PAYOUT_PAUSE_DELAY_PERIODS,
             roll_count_to_slash_on_denunciation: 1,
             denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
             broadcast_enabled: true,
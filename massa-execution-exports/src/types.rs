@@ -17,7 +17,7 @@ use massa_models::prehash::PreHashSet;
 use massa_models::{
     address::Address, address::ExecutionAddressCycleInfo, amount::Amount, slot::Slot,
 };
-use massa_pos_exports::ProductionStats;
+use massa_pos_exports::{ProductionStats, ScheduledCredit};
 use massa_storage::Storage;
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -199,6 +199,13 @@ pub struct ExecutionAddressInfo {
     /// future deferred credits
     pub future_deferred_credits: BTreeMap<Slot, Amount>,
 
+    /// final payout address, receiving the deferred credits and staking rewards of the address
+    pub final_payout_address: Option<Address>,
+    /// candidate payout address
+    pub candidate_payout_address: Option<Address>,
+    /// addresses that designated this address as their payout address, at the latest final slot
+    pub payout_address_sources: Vec<Address>,
+
     /// cycle information
     pub cycle_infos: Vec<ExecutionAddressCycleInfo>,
 }
@@ -235,6 +242,8 @@ pub struct ExecutionOutput {
     pub state_changes: StateChanges,
     /// events emitted by the execution step
     pub events: EventStore,
+    /// deferred credits scheduled by the execution step, with their payment slot and origin
+    pub scheduled_credits: Vec<ScheduledCredit>,
 }
 
 /// structure describing the output of a read only execution
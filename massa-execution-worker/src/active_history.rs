@@ -197,6 +197,49 @@ impl ActiveHistory {
         })
     }
 
+    /// Traverse the whole history and return the latest change of the payout address of an address
+    ///
+    /// # Returns
+    /// `Some(payout_address)` if the payout address was set (`Some`) or removed (`None`) in the history, `None` otherwise
+    pub fn fetch_payout_address(&self, addr: &Address) -> Option<Option<Address>> {
+        self.0.iter().rev().find_map(|output| {
+            output
+                .state_changes
+                .pos_changes
+                .payout_addresses
+                .get(addr)
+                .cloned()
+        })
+    }
+
+    /// Traverse the whole history and return the latest change of the operator an address delegates its rolls to
+    ///
+    /// # Returns
+    /// `Some(operator)` if the delegation was set (`Some`) or removed (`None`) in the history, `None` otherwise
+    pub fn fetch_delegation(&self, addr: &Address) -> Option<Option<Address>> {
+        self.0.iter().rev().find_map(|output| {
+            output
+                .state_changes
+                .pos_changes
+                .delegations
+                .get(addr)
+                .cloned()
+        })
+    }
+
+    /// Gets all the deferred credits that will be credited from a given slot (included)
+    pub fn get_all_deferred_credits_from(&self, slot: &Slot) -> DeferredCredits {
+        self.0.iter().fold(DeferredCredits::new(), |mut acc, e| {
+            acc.extend(
+                e.state_changes
+                    .pos_changes
+                    .deferred_credits
+                    .get_slot_range(slot..),
+            );
+            acc
+        })
+    }
+
     /// Gets all the deferred credits that will be credited until a given slot (included)
     pub fn get_all_deferred_credits_until(&self, slot: &Slot) -> DeferredCredits {
         self.0.iter().fold(DeferredCredits::new(), |mut acc, e| {
@@ -37,17 +37,19 @@ use massa_models::{
     slot::Slot,
 };
 use massa_module_cache::controller::ModuleCache;
-use massa_pos_exports::PoSChanges;
+use massa_pos_exports::{
+    resolve_payout_address, DeferredCreditOrigin, DeferredCredits, PoSChanges, ScheduledCredit,
+};
 use massa_serialization::Serializer;
 use massa_versioning::address_factory::{AddressArgs, AddressFactory};
-use massa_versioning::versioning::MipStore;
+use massa_versioning::versioning::{MipComponent, MipStore};
 use massa_versioning::versioning_factory::{FactoryStrategy, VersioningFactory};
 use parking_lot::RwLock;
 use rand::SeedableRng;
 use rand_xoshiro::Xoshiro256PlusPlus;
 use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
 /// A snapshot taken from an `ExecutionContext` and that represents its current state.
 /// The `ExecutionContext` state can then be restored later from this snapshot.
@@ -91,6 +93,9 @@ pub struct ExecutionContextSnapshot {
     /// The gas remaining before the last subexecution.
     /// so *excluding* the gas used by the last sc call.
     pub gas_remaining_before_subexecution: Option<u64>,
+
+    /// deferred credits scheduled so far during the slot, with their payment slot and origin
+    pub scheduled_credits: Vec<ScheduledCredit>,
 }
 
 /// An execution context that needs to be initialized before executing bytecode,
@@ -179,6 +184,9 @@ pub struct ExecutionContext {
     /// The gas remaining before the last subexecution.
     /// so *excluding* the gas used by the last sc call.
     pub gas_remaining_before_subexecution: Option<u64>,
+
+    /// deferred credits scheduled during the slot, with their payment slot and origin
+    pub scheduled_credits: Vec<ScheduledCredit>,
 }
 
 impl ExecutionContext {
@@ -243,6 +251,7 @@ impl ExecutionContext {
             address_factory: AddressFactory { mip_store },
             execution_trail_hash,
             gas_remaining_before_subexecution: None,
+            scheduled_credits: Default::default(),
         }
     }
 
@@ -264,6 +273,7 @@ impl ExecutionContext {
             event_count: self.events.0.len(),
             unsafe_rng: self.unsafe_rng.clone(),
             gas_remaining_before_subexecution: self.gas_remaining_before_subexecution,
+            scheduled_credits: self.scheduled_credits.clone(),
         }
     }
 
@@ -292,6 +302,7 @@ impl ExecutionContext {
         self.stack = snapshot.stack;
         self.unsafe_rng = snapshot.unsafe_rng;
         self.gas_remaining_before_subexecution = snapshot.gas_remaining_before_subexecution;
+        self.scheduled_credits = snapshot.scheduled_credits;
 
         // For events, set snapshot delta to error events.
         for event in self.events.0.range_mut(snapshot.event_count..) {
@@ -738,14 +749,48 @@ impl ExecutionContext {
         seller_addr: &Address,
         roll_count: u64,
     ) -> Result<(), ExecutionError> {
-        self.speculative_roll_state.try_sell_rolls(
+        // large reimbursements are only split once a network version enables it
+        let slot_timestamp = get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            self.slot,
+        )?;
+        let (split_threshold, split_parts) = if self
+            .address_factory
+            .mip_store
+            .get_latest_component_version_at(&MipComponent::RollSellCreditSplit, slot_timestamp)
+            > 0
+        {
+            (
+                self.config.roll_sell_credit_split_threshold,
+                self.config.roll_sell_credit_split_parts,
+            )
+        } else {
+            (Amount::MAX, 1)
+        };
+        let credit_parts = self.speculative_roll_state.try_sell_rolls(
             seller_addr,
             self.slot,
             roll_count,
             self.config.periods_per_cycle,
             self.config.thread_count,
             self.config.roll_price,
-        )
+            split_threshold,
+            split_parts,
+        )?;
+        self.scheduled_credits
+            .extend(
+                credit_parts
+                    .into_iter()
+                    .map(|(slot, amount)| ScheduledCredit {
+                        slot,
+                        address: *seller_addr,
+                        origin: DeferredCreditOrigin::RollSale,
+                        amount,
+                    }),
+            );
+        Ok(())
     }
 
     /// Try to slash `roll_count` rolls from the denounced address. If not enough rolls,
@@ -811,6 +856,88 @@ impl ExecutionContext {
         Ok(slashed_coins)
     }
 
+    /// Pay the share of a block reward of an operator to a delegator of its rolls,
+    /// as a deferred credit at the end of the next cycle.
+    ///
+    /// # Arguments
+    /// * `delegator`: the delegator credited
+    /// * `operator`: the operator that produced the block
+    /// * `amount`: the share of the delegator
+    ///
+    /// # Returns
+    /// The slot at which the share is paid
+    pub fn schedule_delegation_reward(
+        &mut self,
+        delegator: &Address,
+        operator: Address,
+        amount: Amount,
+    ) -> Result<Slot, ExecutionError> {
+        let cur_cycle = self.slot.get_cycle(self.config.periods_per_cycle);
+        let target_slot = Slot::new_last_of_cycle(
+            cur_cycle.checked_add(1).ok_or_else(|| {
+                ExecutionError::RuntimeError(
+                    "cycle overflow when scheduling a delegation reward".into(),
+                )
+            })?,
+            self.config.periods_per_cycle,
+            self.config.thread_count,
+        )
+        .map_err(|_| {
+            ExecutionError::RuntimeError("slot overflow when scheduling a delegation reward".into())
+        })?;
+        self.speculative_roll_state
+            .add_deferred_credits(target_slot, delegator, amount);
+
+        // tag the origin of the credit in an event
+        let origin = DeferredCreditOrigin::DelegationReward {
+            operator,
+            block_slot: self.slot,
+        };
+        self.scheduled_credits.push(ScheduledCredit {
+            slot: target_slot,
+            address: *delegator,
+            origin: origin.clone(),
+            amount,
+        });
+        let event = self.event_create(
+            serde_json::json!({
+                "deferred_credit": {
+                    "address": delegator.to_string(),
+                    "amount": amount.to_canonical_string(),
+                    "slot": target_slot,
+                    "origin": origin,
+                }
+            })
+            .to_string(),
+            false,
+        );
+        self.event_emit(event);
+
+        Ok(target_slot)
+    }
+
+    /// Add deferred credits injected for scenario testing to the speculative PoS state,
+    /// on top of the credits already scheduled.
+    /// Validity checks must be performed _outside_ of this function.
+    ///
+    /// # Arguments
+    /// * `credits`: the credits to add
+    pub fn inject_deferred_credits(&mut self, credits: DeferredCredits) {
+        for (credit_slot, slot_credits) in credits.credits {
+            let slot = credit_slot.slot();
+            for (address, amount) in slot_credits {
+                self.speculative_roll_state
+                    .add_deferred_credits(slot, &address, amount);
+                self.scheduled_credits.push(ScheduledCredit {
+                    slot,
+                    address,
+                    origin: DeferredCreditOrigin::Injected,
+                    amount,
+                });
+            }
+        }
+    }
+
     /// Update production statistics of an address.
     ///
     /// # Arguments
@@ -827,27 +954,129 @@ impl ExecutionContext {
             .update_production_stats(creator, slot, block_id);
     }
 
+    /// Set (`Some`) or remove (`None`) the address receiving the deferred credits and staking rewards of an address
+    pub fn set_payout_address(&mut self, address: &Address, payout_address: Option<Address>) {
+        self.speculative_roll_state
+            .set_payout_address(address, payout_address);
+    }
+
+    /// Set (`Some`) or remove (`None`) the operator an address delegates its rolls to
+    pub fn set_delegation(&mut self, address: &Address, operator: Option<Address>) {
+        self.speculative_roll_state
+            .set_delegation(address, operator);
+    }
+
+    /// Get the operator an address delegates its rolls to, if any
+    pub fn get_delegation(&self, address: &Address) -> Option<Address> {
+        self.speculative_roll_state.get_delegation(address)
+    }
+
+    /// Get the address receiving the deferred credits and staking rewards of an address:
+    /// its payout address if it designated one, the address itself otherwise
+    pub fn get_payout_address(&self, address: &Address) -> Address {
+        resolve_payout_address(
+            *address,
+            self.speculative_roll_state.get_payout_address(address),
+        )
+    }
+
     /// Execute the deferred credits of `slot`.
+    /// Each credit is paid to the payout address of the credited address.
+    /// A `DEFERRED_CREDIT_PAID` event is emitted for each credit paid.
+    /// The credits of an address at a slot are merged in the final state, so the event does not report their origins.
     ///
     /// # Arguments
     /// * `slot`: associated slot of the deferred credits to be executed
     pub fn execute_deferred_credits(&mut self, slot: &Slot) {
-        for (_slot, map) in self
+        for (credit_slot, map) in self
             .speculative_roll_state
             .take_unexecuted_deferred_credits(slot)
             .credits
         {
             for (address, amount) in map {
-                if let Err(e) = self.transfer_coins(None, Some(address), amount, false) {
+                let payout_address = self.get_payout_address(&address);
+                if let Err(e) = self.transfer_coins(None, Some(payout_address), amount, false) {
                     debug!(
-                        "could not credit {} deferred coins to {} at slot {}: {}",
-                        amount, address, slot, e
+                        "could not credit {} deferred coins of {} to {} at slot {}: {}",
+                        amount, address, payout_address, slot, e
                     );
+                    continue;
                 }
+                let event = self.event_create(
+                    serde_json::json!({
+                        "DEFERRED_CREDIT_PAID": {
+                            "address": address.to_string(),
+                            "payout_address": payout_address.to_string(),
+                            "amount": amount.to_canonical_string(),
+                            "slot": credit_slot,
+                        }
+                    })
+                    .to_string(),
+                    false,
+                );
+                self.event_emit(event);
             }
         }
     }
 
+    /// Postpone all the pending deferred credits by `payout_pause_delay_periods` periods
+    /// if a new version of the emergency payout pause (`MipComponent::PoSPayoutPause`) becomes active at `slot`.
+    /// Each activation postpones the credits once, including those of `slot` itself.
+    /// A `DEFERRED_CREDITS_POSTPONED` event is emitted when the credits are postponed.
+    ///
+    /// # Arguments
+    /// * `slot`: the slot being settled
+    fn postpone_payouts_on_pause(&mut self, slot: &Slot) {
+        let pause_version_at = |slot: &Slot| {
+            let slot_timestamp = get_block_slot_timestamp(
+                self.config.thread_count,
+                self.config.t0,
+                self.config.genesis_timestamp,
+                *slot,
+            )
+            .expect("could not compute slot timestamp");
+            self.address_factory
+                .mip_store
+                .get_latest_component_version_at(&MipComponent::PoSPayoutPause, slot_timestamp)
+        };
+        let version = pause_version_at(slot);
+        let previous_version = slot
+            .get_prev_slot(self.config.thread_count)
+            .map_or(0, |previous_slot| pause_version_at(&previous_slot));
+        if version <= previous_version {
+            return;
+        }
+
+        let delay_periods = self.config.payout_pause_delay_periods;
+        match self
+            .speculative_roll_state
+            .postpone_deferred_credits(slot, delay_periods)
+        {
+            Some(count) => {
+                info!(
+                    "emergency payout pause activated at slot {}: {} deferred credits postponed by {} periods",
+                    slot, count, delay_periods
+                );
+                let event = self.event_create(
+                    serde_json::json!({
+                        "DEFERRED_CREDITS_POSTPONED": {
+                            "slot": slot,
+                            "count": count,
+                            "delay_periods": delay_periods,
+                        }
+                    })
+                    .to_string(),
+                    false,
+                );
+                self.event_emit(event);
+            }
+            None => warn!(
+                "emergency payout pause activated at slot {} but the deferred credits could not be postponed by {} periods",
+                slot, delay_periods
+            ),
+        }
+    }
+
     /// Finishes a slot and generates the execution output.
     /// Settles emitted asynchronous messages, reimburse the senders of deleted messages.
     /// Moves the output of the execution out of the context,
@@ -858,6 +1087,9 @@ impl ExecutionContext {
     pub fn settle_slot(&mut self, block_info: Option<ExecutedBlockInfo>) -> ExecutionOutput {
         let slot = self.slot;
 
+        // postpone the pending payouts if the emergency payout pause activates at this slot
+        self.postpone_payouts_on_pause(&slot);
+
         // execute the deferred credits coming from roll sells
         self.execute_deferred_credits(&slot);
 
@@ -886,13 +1118,21 @@ impl ExecutionContext {
             .slot
             .is_last_of_cycle(self.config.periods_per_cycle, self.config.thread_count)
         {
-            self.speculative_roll_state.settle_production_stats(
+            let refunds = self.speculative_roll_state.settle_production_stats(
                 &slot,
                 self.config.periods_per_cycle,
                 self.config.thread_count,
                 self.config.roll_price,
                 self.config.max_miss_ratio,
             );
+            self.scheduled_credits.extend(refunds.into_iter().map(
+                |(address, refund_slot, amount)| ScheduledCredit {
+                    slot: refund_slot,
+                    address,
+                    origin: DeferredCreditOrigin::DeactivationRefund,
+                    amount,
+                },
+            ));
         }
 
         // generate the execution output
@@ -911,6 +1151,7 @@ impl ExecutionContext {
             block_info,
             state_changes,
             events: std::mem::take(&mut self.events),
+            scheduled_credits: std::mem::take(&mut self.scheduled_credits),
         }
     }
 
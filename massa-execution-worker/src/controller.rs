@@ -12,16 +12,20 @@ use massa_execution_exports::{
     ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionQueryResponse,
     ExecutionQueryResponseItem, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
 };
+use massa_final_state::StateHashComposition;
 use massa_models::denunciation::DenunciationIndex;
 use massa_models::execution::EventFilter;
 use massa_models::output_event::SCOutputEvent;
+use massa_models::pagination::{PageRequest, PageResponse};
 use massa_models::prehash::PreHashMap;
 use massa_models::stats::ExecutionStats;
 use massa_models::{address::Address, amount::Amount, operation::OperationId};
 use massa_models::{block_id::BlockId, slot::Slot};
+use massa_pos_exports::{CreditOriginAmounts, CreditOriginKind};
 use parking_lot::{Condvar, Mutex, RwLock};
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
+use std::path::Path;
 use std::sync::Arc;
 use tracing::info;
 
@@ -447,6 +451,8 @@ impl ExecutionController for ExecutionControllerImpl {
                 exec_state.get_final_and_candidate_rolls(addr);
             let future_deferred_credits =
                 exec_state.get_address_future_deferred_credits(addr, deferred_credits_max_slot);
+            let (final_payout_address, candidate_payout_address) =
+                exec_state.get_final_and_candidate_payout_address(addr);
             res.push(ExecutionAddressInfo {
                 final_datastore_keys: final_datastore_keys.unwrap_or_default(),
                 candidate_datastore_keys: candidate_datastore_keys.unwrap_or_default(),
@@ -455,6 +461,9 @@ impl ExecutionController for ExecutionControllerImpl {
                 final_roll_count,
                 candidate_roll_count,
                 future_deferred_credits,
+                final_payout_address,
+                candidate_payout_address,
+                payout_address_sources: exec_state.get_payout_address_sources(addr),
                 cycle_infos: exec_state.get_address_cycle_infos(addr),
             });
         }
@@ -466,6 +475,68 @@ impl ExecutionController for ExecutionControllerImpl {
         self.execution_state.read().get_stats()
     }
 
+    /// See trait definition
+    fn get_final_cursor(&self) -> Slot {
+        self.execution_state.read().final_cursor
+    }
+
+    /// See trait definition
+    fn get_candidate_cursor(&self) -> Slot {
+        self.execution_state.read().active_cursor
+    }
+
+    /// See trait definition
+    fn get_paid_credits(&self, address: &Address, from_slot: Slot) -> Vec<(Slot, Amount)> {
+        self.execution_state
+            .read()
+            .get_paid_credits(address, from_slot)
+    }
+
+    /// See trait definition
+    fn get_deferred_credits(&self, request: PageRequest) -> PageResponse<(Slot, Address, Amount)> {
+        self.execution_state
+            .read()
+            .get_deferred_credits_page(&request)
+    }
+
+    /// See trait definition
+    fn get_credit_origin_totals(&self) -> BTreeMap<CreditOriginKind, CreditOriginAmounts> {
+        self.execution_state.read().get_credit_origin_totals()
+    }
+
+    /// See trait definition
+    fn get_final_state_hash_composition(&self) -> StateHashComposition {
+        self.execution_state
+            .read()
+            .get_final_state_hash_composition()
+    }
+
+    /// See trait definition
+    fn inject_deferred_credits(&self, csv_path: &Path) -> Result<usize, ExecutionError> {
+        if cfg!(not(feature = "sandbox")) {
+            return Err(ExecutionError::DeferredCreditsInjectionError(
+                "deferred credits can only be injected on sandbox builds".into(),
+            ));
+        }
+        let content = std::fs::read_to_string(csv_path).map_err(|err| {
+            ExecutionError::DeferredCreditsInjectionError(format!(
+                "could not read {}: {}",
+                csv_path.display(),
+                err
+            ))
+        })?;
+        let count = self
+            .execution_state
+            .write()
+            .queue_injected_deferred_credits(&content)?;
+        info!(
+            "queued {} deferred credits from {} for injection at the next final slot",
+            count,
+            csv_path.display()
+        );
+        Ok(count)
+    }
+
     /// Returns a boxed clone of self.
     /// Allows cloning `Box<dyn ExecutionController>`,
     /// see `massa-execution-exports/controller_traits.rs`
@@ -10,7 +10,9 @@
 
 use crate::active_history::{ActiveHistory, HistorySearchResult};
 use crate::context::{ExecutionContext, ExecutionContextSnapshot};
+use crate::injected_credits::parse_deferred_credits_csv;
 use crate::interface_impl::InterfaceImpl;
+use crate::paid_credits_history::PaidCreditsHistory;
 use crate::stats::ExecutionStatsCounter;
 use massa_async_pool::AsyncMessage;
 use massa_execution_exports::{
@@ -19,15 +21,18 @@ use massa_execution_exports::{
     ExecutionStackElement, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
     ReadOnlyExecutionTarget, SlotExecutionOutput,
 };
-use massa_final_state::FinalStateController;
+use massa_final_state::{FinalStateController, StateHashComposition};
 use massa_ledger_exports::{SetOrDelete, SetUpdateOrDelete};
 use massa_metrics::MassaMetrics;
 use massa_models::address::ExecutionAddressCycleInfo;
+use massa_models::block_reward::endorsement_reward_part;
 use massa_models::bytecode::Bytecode;
 use massa_models::datastore::get_prefix_bounds;
 use massa_models::denunciation::{Denunciation, DenunciationIndex};
 use massa_models::execution::EventFilter;
+use massa_models::node_event::NodeEvent;
 use massa_models::output_event::SCOutputEvent;
+use massa_models::pagination::{PageKey, PageRequest, PageResponse};
 use massa_models::prehash::PreHashSet;
 use massa_models::stats::ExecutionStats;
 use massa_models::timeslots::get_block_slot_timestamp;
@@ -39,7 +44,9 @@ use massa_models::{
 use massa_models::{amount::Amount, slot::Slot};
 use massa_module_cache::config::ModuleCacheConfig;
 use massa_module_cache::controller::ModuleCache;
-use massa_pos_exports::SelectorController;
+use massa_pos_exports::{
+    CreditOriginAmounts, CreditOriginKind, CreditOriginTotals, DeferredCredits, SelectorController,
+};
 use massa_sc_runtime::{Interface, Response, VMError};
 use massa_versioning::versioning::MipStore;
 use massa_wallet::Wallet;
@@ -82,6 +89,12 @@ pub(crate) struct ExecutionState {
     stats_counter: ExecutionStatsCounter,
     // cache of pre compiled sc modules
     module_cache: Arc<RwLock<ModuleCache>>,
+    // history of the deferred credits paid at final slots
+    paid_credits_history: PaidCreditsHistory,
+    // totals of the deferred credits scheduled and paid at final slots since the node started, per origin
+    credit_origin_totals: CreditOriginTotals,
+    // deferred credits injected for scenario testing, applied at the next final slot execution
+    injected_deferred_credits: DeferredCredits,
     // MipStore (Versioning)
     mip_store: MipStore,
     // wallet used to verify double staking on local addresses
@@ -135,6 +148,12 @@ impl ExecutionState {
             max_module_length: config.max_bytecode_size,
         })));
 
+        // Open the history of paid deferred credits
+        let paid_credits_history = PaidCreditsHistory::new(
+            config.paid_credits_history_path.clone(),
+            config.paid_credits_history_periods,
+        );
+
         // Create an empty placeholder execution context, with shared atomic access
         let execution_context = Arc::new(Mutex::new(ExecutionContext::new(
             config.clone(),
@@ -165,6 +184,9 @@ impl ExecutionState {
             final_cursor: last_final_slot,
             stats_counter: ExecutionStatsCounter::new(config.stats_time_window_duration),
             module_cache,
+            paid_credits_history,
+            credit_origin_totals: Default::default(),
+            injected_deferred_credits: Default::default(),
             config,
             mip_store,
             selector,
@@ -181,8 +203,58 @@ impl ExecutionState {
 
     /// Get execution statistics
     pub fn get_stats(&self) -> ExecutionStats {
-        self.stats_counter
-            .get_stats(self.active_cursor, self.final_cursor)
+        self.stats_counter.get_stats(
+            self.active_cursor,
+            self.final_cursor,
+            self.final_state.read().get_pos_state().get_stats(),
+        )
+    }
+
+    /// Get the hashes of the components of the final state at the latest final slot
+    pub fn get_final_state_hash_composition(&self) -> StateHashComposition {
+        self.final_state.read().get_state_hash_composition()
+    }
+
+    /// Get the deferred credits paid to an address at or after `from_slot`, ordered by slot.
+    /// Only the credits paid within the configured retention window are returned.
+    pub fn get_paid_credits(&self, address: &Address, from_slot: Slot) -> Vec<(Slot, Amount)> {
+        self.paid_credits_history
+            .get_paid_credits(address, from_slot)
+    }
+
+    /// Get the cumulative amounts of deferred credits scheduled and paid at final slots since the node started, per origin
+    pub fn get_credit_origin_totals(&self) -> BTreeMap<CreditOriginKind, CreditOriginAmounts> {
+        self.credit_origin_totals.get_totals()
+    }
+
+    /// Queue the deferred credits of a CSV file, to be added to the speculative PoS state
+    /// at the next final slot execution. Meant for scenario testing on sandbox nodes.
+    ///
+    /// # Arguments
+    /// * `content`: content of the CSV file, see `injected_credits.rs` for the format
+    ///
+    /// # Returns
+    /// The number of queued credits, or an error if the file is invalid, in which case nothing is queued
+    pub fn queue_injected_deferred_credits(
+        &mut self,
+        content: &str,
+    ) -> Result<usize, ExecutionError> {
+        let credits =
+            parse_deferred_credits_csv(content, self.config.thread_count, self.final_cursor)?;
+        let mut count = 0;
+        for (credit_slot, slot_credits) in credits.credits {
+            let slot = credit_slot.slot();
+            for (address, amount) in slot_credits {
+                let total = self
+                    .injected_deferred_credits
+                    .get_address_credits_for_slot(&address, &slot)
+                    .unwrap_or_default()
+                    .saturating_add(amount);
+                self.injected_deferred_credits.insert(slot, address, total);
+                count += 1;
+            }
+        }
+        Ok(count)
     }
 
     /// Applies the output of an execution to the final execution state.
@@ -211,12 +283,57 @@ impl ExecutionState {
         // as it will also write the MIP store on disk
         self.update_versioning_stats(&exec_out.block_info, &exec_out.slot);
 
+        // the deferred credits of this slot are paid by its execution and removed when finalizing
+        let paid_credits = self
+            .final_state
+            .read()
+            .get_pos_state()
+            .get_deferred_credits_range(exec_out.slot..=exec_out.slot, None);
+
         let exec_out_2 = exec_out.clone();
         // apply state changes to the final ledger
         self.final_state
             .write()
             .finalize(exec_out.slot, exec_out.state_changes);
 
+        // record the paid credits in the node-local history
+        if let Some(credits) = paid_credits.credits.get(&exec_out.slot) {
+            self.paid_credits_history.record(exec_out.slot, credits);
+        }
+
+        // update the totals of the credits per origin
+        let paid_amount = paid_credits
+            .credits
+            .values()
+            .flat_map(|credits| credits.values())
+            .fold(Amount::zero(), |total, amount| {
+                total.saturating_add(*amount)
+            });
+        for credit in exec_out.scheduled_credits.iter() {
+            self.credit_origin_totals.record_scheduled(credit);
+        }
+        self.credit_origin_totals
+            .record_paid(exec_out.slot, paid_amount);
+
+        // publish paid credits and cycle ends on the node event bus
+        for (slot, credits) in paid_credits.credits {
+            for (address, amount) in credits {
+                self.channels.event_bus.publish(NodeEvent::CreditPaid {
+                    slot: slot.slot(),
+                    address,
+                    amount,
+                });
+            }
+        }
+        if exec_out
+            .slot
+            .is_last_of_cycle(self.config.periods_per_cycle, self.config.thread_count)
+        {
+            self.channels.event_bus.publish(NodeEvent::CycleEnd {
+                cycle: exec_out.slot.get_cycle(self.config.periods_per_cycle),
+            });
+        }
+
         // update the final ledger's slot
         self.final_cursor = exec_out.slot;
 
@@ -405,6 +522,28 @@ impl ExecutionState {
             ));
         }
 
+        // check that the operation type is enabled by an active network version
+        let slot_timestamp = get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            block_slot,
+        )
+        .map_err(|err| {
+            ExecutionError::IncludeOperationError(format!(
+                "could not compute the timestamp of the block slot: {}",
+                err
+            ))
+        })?;
+        if !self
+            .mip_store
+            .is_operation_type_active_at(&operation.content.op, slot_timestamp)
+        {
+            return Err(ExecutionError::IncludeOperationError(
+                "operation type not enabled yet by an active network version".to_string(),
+            ));
+        }
+
         // get operation ID
         let operation_id = operation.id;
 
@@ -436,6 +575,15 @@ impl ExecutionState {
             OperationType::Transaction { .. } => {
                 self.execute_transaction_op(&operation.content.op, sender_addr)
             }
+            OperationType::SetPayoutAddress { .. } => {
+                self.execute_set_payout_address_op(&operation.content.op, sender_addr)
+            }
+            OperationType::Delegate { .. } => {
+                self.execute_delegate_op(&operation.content.op, sender_addr)
+            }
+            OperationType::Undelegate { .. } => {
+                self.execute_undelegate_op(&operation.content.op, sender_addr)
+            }
         };
 
         {
@@ -672,6 +820,130 @@ impl ExecutionState {
         Ok(())
     }
 
+    /// Execute an operation of type `SetPayoutAddress`
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be a `SetPayoutAddress`
+    /// * `sender_addr`: address of the sender
+    pub fn execute_set_payout_address_op(
+        &self,
+        operation: &OperationType,
+        sender_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process set payout address operations only
+        let payout_address = match operation {
+            OperationType::SetPayoutAddress { payout_address } => payout_address,
+            _ => panic!("unexpected operation type"),
+        };
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: sender_addr,
+            coins: Amount::default(),
+            owned_addresses: vec![sender_addr],
+            operation_datastore: None,
+        }];
+
+        // an address is its own payout address by default, designating it would only take room in the registry
+        if payout_address == &Some(sender_addr) {
+            return Err(ExecutionError::SetPayoutAddressError(format!(
+                "{} cannot designate itself as payout address, remove its payout address instead",
+                sender_addr
+            )));
+        }
+        context.set_payout_address(&sender_addr, *payout_address);
+        Ok(())
+    }
+
+    /// Execute an operation of type `Delegate`
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be a `Delegate`
+    /// * `delegator_addr`: address of the delegator
+    pub fn execute_delegate_op(
+        &self,
+        operation: &OperationType,
+        delegator_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process delegate operations only
+        let operator = match operation {
+            OperationType::Delegate { operator } => operator,
+            _ => panic!("unexpected operation type"),
+        };
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: delegator_addr,
+            coins: Amount::default(),
+            owned_addresses: vec![delegator_addr],
+            operation_datastore: None,
+        }];
+
+        // an address is drawn with its own rolls by default
+        if operator == &delegator_addr {
+            return Err(ExecutionError::DelegationError(format!(
+                "{} cannot delegate its rolls to itself, undelegate them instead",
+                delegator_addr
+            )));
+        }
+        if !matches!(operator, Address::User(..)) {
+            return Err(ExecutionError::DelegationError(format!(
+                "rolls can only be delegated to user addresses, got {}",
+                operator
+            )));
+        }
+        context.set_delegation(&delegator_addr, Some(*operator));
+        Ok(())
+    }
+
+    /// Execute an operation of type `Undelegate`
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be an `Undelegate`
+    /// * `delegator_addr`: address of the delegator
+    pub fn execute_undelegate_op(
+        &self,
+        operation: &OperationType,
+        delegator_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process undelegate operations only
+        if !matches!(operation, OperationType::Undelegate {}) {
+            panic!("unexpected operation type");
+        }
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: delegator_addr,
+            coins: Amount::default(),
+            owned_addresses: vec![delegator_addr],
+            operation_datastore: None,
+        }];
+
+        if context.get_delegation(&delegator_addr).is_none() {
+            return Err(ExecutionError::DelegationError(format!(
+                "{} does not delegate its rolls",
+                delegator_addr
+            )));
+        }
+        context.set_delegation(&delegator_addr, None);
+        Ok(())
+    }
+
     /// Execute an operation of type `RollBuy`
     /// Will panic if called with another operation type
     ///
@@ -1045,6 +1317,7 @@ impl ExecutionState {
     /// * `slot`: slot to execute
     /// * `exec_target`: metadata of the block to execute, if not miss
     /// * `selector`: Reference to the selector
+    /// * `injected_credits`: deferred credits injected for scenario testing, added to the speculative PoS state
     ///
     /// # Returns
     /// An `ExecutionOutput` structure summarizing the output of the executed slot
@@ -1053,6 +1326,7 @@ impl ExecutionState {
         slot: &Slot,
         exec_target: Option<&(BlockId, ExecutionBlockMetadata)>,
         selector: Box<dyn SelectorController>,
+        injected_credits: DeferredCredits,
     ) -> ExecutionOutput {
         // Create a new execution context for the whole active slot
         let mut execution_context = ExecutionContext::active_slot(
@@ -1074,6 +1348,11 @@ impl ExecutionState {
         // Apply the created execution context for slot execution
         *context_guard!(self) = execution_context;
 
+        // Add the injected deferred credits before the credits of this slot are paid
+        if !injected_credits.is_empty() {
+            context_guard!(self).inject_deferred_credits(injected_credits);
+        }
+
         // Try executing asynchronous messages.
         // Effects are cancelled on failure and the sender is reimbursed.
         for (opt_bytecode, message) in messages {
@@ -1173,22 +1452,32 @@ impl ExecutionState {
             // Get block creator address
             let block_creator_addr = stored_block.content_creator_address;
 
+            // Get the rolls delegated to the block creator for the draws of this slot
+            let operator_rolls = selector
+                .get_operator_rolls(
+                    slot.get_cycle(self.config.periods_per_cycle),
+                    &block_creator_addr,
+                )
+                .expect("couldn't get the rolls delegated to the block creator");
+
             // acquire lock on execution context
             let mut context = context_guard!(self);
 
             // Update speculative rolls state production stats
             context.update_production_stats(&block_creator_addr, *slot, Some(*block_id));
 
-            // Credit endorsement producers and endorsed block producers
+            // Credit endorsement producers and endorsed block producers,
+            // through their payout addresses
+            let endorsement_target_payout = context.get_payout_address(&endorsement_target_creator);
             let mut remaining_credit = block_credits;
-            let block_credit_part = block_credits
-                .checked_div_u64(3 * (1 + (self.config.endorsement_count)))
-                .expect("critical: block_credits checked_div factor is 0");
+            let block_credit_part =
+                endorsement_reward_part(block_credits, self.config.endorsement_count);
             for endorsement_creator in endorsement_creators {
                 // credit creator of the endorsement with coins
+                let endorsement_creator_payout = context.get_payout_address(&endorsement_creator);
                 match context.transfer_coins(
                     None,
-                    Some(endorsement_creator),
+                    Some(endorsement_creator_payout),
                     block_credit_part,
                     false,
                 ) {
@@ -1206,7 +1495,7 @@ impl ExecutionState {
                 // credit creator of the endorsed block with coins
                 match context.transfer_coins(
                     None,
-                    Some(endorsement_target_creator),
+                    Some(endorsement_target_payout),
                     block_credit_part,
                     false,
                 ) {
@@ -1222,9 +1511,29 @@ impl ExecutionState {
                 }
             }
 
+            // Split the remaining credit with the delegators of the block creator,
+            // in proportion to the rolls it was drawn with
+            if let Some(operator_rolls) = operator_rolls {
+                for (delegator, share) in operator_rolls.delegator_shares(remaining_credit) {
+                    match context.schedule_delegation_reward(&delegator, block_creator_addr, share)
+                    {
+                        Ok(_) => {
+                            remaining_credit = remaining_credit.saturating_sub(share);
+                        }
+                        Err(err) => {
+                            debug!(
+                                "failed to schedule the {} coins reward of delegator {} of block creator {}: {}",
+                                share, delegator, block_creator_addr, err
+                            )
+                        }
+                    }
+                }
+            }
+
             // Credit block creator with remaining_credit
+            let block_creator_payout = context.get_payout_address(&block_creator_addr);
             if let Err(err) =
-                context.transfer_coins(None, Some(block_creator_addr), remaining_credit, false)
+                context.transfer_coins(None, Some(block_creator_payout), remaining_credit, false)
             {
                 debug!(
                     "failed to credit {} coins to block creator {} on block execution: {}",
@@ -1295,7 +1604,7 @@ impl ExecutionState {
                 .get_prev_slot(self.config.thread_count)
                 .expect("overflow when iterating on slots");
         }
-        let exec_out = self.execute_slot(slot, exec_target, selector);
+        let exec_out = self.execute_slot(slot, exec_target, selector, DeferredCredits::new());
 
         // apply execution output to active state
         self.apply_active_execution_output(exec_out);
@@ -1323,10 +1632,19 @@ impl ExecutionState {
             return;
         }
 
+        // the injected deferred credits are applied by executing the final slot again
+        let injected_credits = std::mem::take(&mut self.injected_deferred_credits);
+        let injected_count: usize = injected_credits.credits.values().map(|c| c.len()).sum();
+
         // check if the final slot execution result is already cached at the front of the speculative execution history
         let first_exec_output = self.active_history.write().0.pop_front();
         if let Some(exec_out) = first_exec_output {
-            if &exec_out.slot == slot
+            if injected_count > 0 {
+                info!(
+                    "executing final slot {} again to inject {} deferred credits",
+                    slot, injected_count
+                );
+            } else if &exec_out.slot == slot
                 && exec_out.block_info.as_ref().map(|i| i.block_id) == target_id
             {
                 // speculative execution front result matches what we want to compute
@@ -1354,11 +1672,20 @@ impl ExecutionState {
 
         // execute slot
         debug!("execute_final_slot: execution started");
-        let exec_out = self.execute_slot(slot, exec_target, selector);
+        let exec_out = self.execute_slot(slot, exec_target, selector, injected_credits);
 
         // apply execution output to final state
         self.apply_final_execution_output(exec_out);
 
+        if injected_count > 0 {
+            info!(
+                "injected {} deferred credits at final slot {}, resulting final state hash: {}",
+                injected_count,
+                slot,
+                self.get_final_state_fingerprint()
+            );
+        }
+
         debug!(
             "execute_final_slot: execution finished & result applied & versioning stats updated"
         );
@@ -1584,6 +1911,32 @@ impl ExecutionState {
         (final_rolls, active_rolls)
     }
 
+    /// Gets payout addresses both at the latest final and active executed slots
+    pub fn get_final_and_candidate_payout_address(
+        &self,
+        address: &Address,
+    ) -> (Option<Address>, Option<Address>) {
+        let final_payout_address = self
+            .final_state
+            .read()
+            .get_pos_state()
+            .get_payout_address(address);
+        let candidate_payout_address = self
+            .active_history
+            .read()
+            .fetch_payout_address(address)
+            .unwrap_or(final_payout_address);
+        (final_payout_address, candidate_payout_address)
+    }
+
+    /// Gets the addresses that designated `payout_address` as payout address, at the latest final slot
+    pub fn get_payout_address_sources(&self, payout_address: &Address) -> Vec<Address> {
+        self.final_state
+            .read()
+            .get_pos_state()
+            .get_payout_address_sources(payout_address)
+    }
+
     /// Gets a data entry both at the latest final and active executed slots
     pub fn get_final_and_active_data_entry(
         &self,
@@ -1829,6 +2182,40 @@ impl ExecutionState {
         )
     }
 
+    /// Get a page of the final deferred credits, ordered by slot then address
+    pub fn get_deferred_credits_page(
+        &self,
+        request: &PageRequest,
+    ) -> PageResponse<(Slot, Address, Amount)> {
+        let credits = match request.start_slot() {
+            Some(start_slot) => self
+                .final_state
+                .read()
+                .get_pos_state()
+                .get_deferred_credits_range(start_slot.., None),
+            None => self
+                .final_state
+                .read()
+                .get_pos_state()
+                .get_deferred_credits_range(.., None),
+        };
+        let mut keyed_credits: Vec<(PageKey, (Slot, Address, Amount))> = credits
+            .credits
+            .into_iter()
+            .flat_map(|(credit_slot, slot_credits)| {
+                let slot = credit_slot.slot();
+                slot_credits.into_iter().map(move |(address, amount)| {
+                    (
+                        PageKey::new(slot, address.to_prefixed_bytes()),
+                        (slot, address, amount),
+                    )
+                })
+            })
+            .collect();
+        keyed_credits.sort_unstable_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+        PageResponse::from_sorted(keyed_credits, request)
+    }
+
     /// Get future deferred credits of an address
     /// Returns tuple: (speculative, final)
     pub fn get_address_deferred_credits(
@@ -1844,7 +2231,9 @@ impl ExecutionState {
             .credits
             .iter()
             .filter_map(|(slot, addr_amount)| {
-                addr_amount.get(address).map(|amount| (*slot, *amount))
+                addr_amount
+                    .get(address)
+                    .map(|amount| (slot.slot(), *amount))
             })
             .collect();
 
@@ -1854,7 +2243,7 @@ impl ExecutionState {
             for (slot, addr_amount) in &hist_item.state_changes.pos_changes.deferred_credits.credits
             {
                 if let Some(amount) = addr_amount.get(address) {
-                    res_speculative.entry(*slot).or_insert(*amount);
+                    res_speculative.entry(slot.slot()).or_insert(*amount);
                 };
             }
         }
@@ -0,0 +1,141 @@
+//! Deferred credits injected from a CSV file, to test payout scenarios on sandbox nodes
+//! without crafting operations.
+//!
+//! Each line of the file is a credit `period,thread,address,amount`, the slot being written as
+//! its period and thread. An optional `period,thread,address,amount` header line, blank lines and
+//! lines starting with `#` are ignored. Several credits of an address at the same slot are summed.
+//! The whole file is rejected if any line is invalid.
+
+use std::str::FromStr;
+
+use massa_execution_exports::ExecutionError;
+use massa_models::{address::Address, amount::Amount, slot::Slot};
+use massa_pos_exports::DeferredCredits;
+
+/// Header line of the CSV files
+const CSV_HEADER: &str = "period,thread,address,amount";
+
+/// Parse and validate the deferred credits of a CSV file.
+///
+/// # Arguments
+/// * `content`: content of the CSV file
+/// * `thread_count`: number of threads
+/// * `final_cursor`: latest final slot, the credits must be paid after it
+///
+/// # Returns
+/// The parsed credits, or an error pointing at the first invalid line
+pub(crate) fn parse_deferred_credits_csv(
+    content: &str,
+    thread_count: u8,
+    final_cursor: Slot,
+) -> Result<DeferredCredits, ExecutionError> {
+    let mut credits = DeferredCredits::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line == CSV_HEADER {
+            continue;
+        }
+        let invalid_line = |reason: String| {
+            ExecutionError::DeferredCreditsInjectionError(format!("line {}: {}", index + 1, reason))
+        };
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [period, thread, address, amount] = fields[..] else {
+            return Err(invalid_line(format!(
+                "expected 4 fields ({}), got {}",
+                CSV_HEADER,
+                fields.len()
+            )));
+        };
+        let period = period
+            .parse::<u64>()
+            .map_err(|err| invalid_line(format!("invalid period {}: {}", period, err)))?;
+        let thread = thread
+            .parse::<u8>()
+            .map_err(|err| invalid_line(format!("invalid thread {}: {}", thread, err)))?;
+        if thread >= thread_count {
+            return Err(invalid_line(format!(
+                "thread {} is not below the thread count {}",
+                thread, thread_count
+            )));
+        }
+        let slot = Slot::new(period, thread);
+        if slot <= final_cursor {
+            return Err(invalid_line(format!(
+                "slot {} is not after the latest final slot {}",
+                slot, final_cursor
+            )));
+        }
+        let address = Address::from_str(address)
+            .map_err(|err| invalid_line(format!("invalid address {}: {}", address, err)))?;
+        let amount = Amount::from_str(amount)
+            .map_err(|err| invalid_line(format!("invalid amount {}: {}", amount, err)))?;
+        let total = credits
+            .get_address_credits_for_slot(&address, &slot)
+            .unwrap_or_default()
+            .checked_add(amount)
+            .ok_or_else(|| {
+                invalid_line(format!(
+                    "the credits of {} at slot {} overflow",
+                    address, slot
+                ))
+            })?;
+        credits.insert(slot, address, total);
+    }
+    Ok(credits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    fn address() -> Address {
+        Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_parse_deferred_credits_csv() {
+        let address_a = address();
+        let address_b = address();
+        let content = format!(
+            "{}\n# payout storm\n10,0,{},100\n\n10,0,{},0.5\n12,1,{},3\n10,0,{},1\n",
+            CSV_HEADER, address_a, address_b, address_a, address_a
+        );
+        let credits = parse_deferred_credits_csv(&content, 2, Slot::new(5, 1)).unwrap();
+        assert_eq!(credits.credits.len(), 2);
+        assert_eq!(
+            credits.get_address_credits_for_slot(&address_a, &Slot::new(10, 0)),
+            Some(Amount::from_str("101").unwrap())
+        );
+        assert_eq!(
+            credits.get_address_credits_for_slot(&address_b, &Slot::new(10, 0)),
+            Some(Amount::from_str("0.5").unwrap())
+        );
+        assert_eq!(
+            credits.get_address_credits_for_slot(&address_a, &Slot::new(12, 1)),
+            Some(Amount::from_str("3").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_deferred_credits_csv_invalid() {
+        let address = address();
+        for content in [
+            format!("10,0,{}", address),
+            format!("10,2,{},1", address),
+            format!("5,1,{},1", address),
+            "10,0,AU0,1".to_string(),
+            format!("10,0,{},-1", address),
+            format!("ten,0,{},1", address),
+        ] {
+            assert!(
+                matches!(
+                    parse_deferred_credits_csv(&content, 2, Slot::new(5, 1)),
+                    Err(ExecutionError::DeferredCreditsInjectionError(_))
+                ),
+                "accepted invalid credits: {}",
+                content
+            );
+        }
+    }
+}
@@ -88,6 +88,7 @@ impl InterfaceImpl {
         sender_addr: Address,
         operation_datastore: Option<Datastore>,
     ) -> InterfaceImpl {
+        use massa_channel::event_bus::MassaEventBus;
         use massa_db_exports::{MassaDBConfig, MassaDBController};
         use massa_db_worker::MassaDB;
         use massa_final_state::test_exports::get_sample_state;
@@ -106,8 +107,11 @@ impl InterfaceImpl {
             warn_announced_version_ratio: Ratio::new_raw(30, 100),
         };
         let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
-        let (_, selector_controller) = start_selector_worker(SelectorConfig::default())
-            .expect("could not start selector controller");
+        let (_, selector_controller) = start_selector_worker(
+            SelectorConfig::default(),
+            MassaEventBus::new("test_node_event".to_string()),
+        )
+        .expect("could not start selector controller");
         let disk_ledger = TempDir::new().expect("cannot create temp directory");
         let db_config = MassaDBConfig {
             path: disk_ledger.path().to_path_buf(),
@@ -73,6 +73,9 @@
 //! This module contains the implementation of a generic finite-size execution request queue.
 //! It handles requests that come with an MPSC to send back the result of their execution once it's done.
 //!
+//! ## `paid_credits_history.rs`
+//! A node-local, bounded history of the deferred credits paid at final slots.
+//!
 //! ## `stats.rs`
 //! Defines a structure that gathers execution statistics.
 
@@ -83,7 +86,9 @@ mod active_history;
 mod context;
 mod controller;
 mod execution;
+mod injected_credits;
 mod interface_impl;
+mod paid_credits_history;
 mod request_queue;
 mod slot_sequencer;
 mod speculative_async_pool;
@@ -0,0 +1,189 @@
+//! Node-local history of the deferred credits paid at final slots.
+//!
+//! The history is stored in its own RocksDB instance: it is not part of the final state,
+//! is not hashed and is not bootstrapped. Entries older than a configured number of periods are pruned.
+
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    slot::{Slot, SLOT_KEY_SIZE},
+};
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+use std::path::PathBuf;
+
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const KEY_ERROR: &str = "critical: invalid paid credits history key";
+const VALUE_ERROR: &str = "critical: invalid paid credits history value";
+const ADDRESS_IDENT: u8 = 0u8;
+const SLOT_IDENT: u8 = 1u8;
+
+/// Key of a paid credit, ordered by address then slot
+macro_rules! address_slot_key {
+    ($address_bytes:expr, $slot:expr) => {
+        [
+            &[ADDRESS_IDENT],
+            &$address_bytes[..],
+            &$slot.to_bytes_key()[..],
+        ]
+        .concat()
+    };
+}
+
+/// Key of the slot index, ordered by slot then address
+macro_rules! slot_address_key {
+    ($slot:expr, $address_bytes:expr) => {
+        [
+            &[SLOT_IDENT],
+            &$slot.to_bytes_key()[..],
+            &$address_bytes[..],
+        ]
+        .concat()
+    };
+}
+
+pub(crate) struct PaidCreditsHistory {
+    /// RocksDB database
+    db: DB,
+    /// Number of periods during which a paid credit is kept in the history
+    retention_periods: u64,
+}
+
+impl PaidCreditsHistory {
+    /// Create a new `PaidCreditsHistory`
+    ///
+    /// # Arguments
+    /// * path: where to store the db
+    /// * retention_periods: number of periods during which a paid credit is kept
+    pub fn new(path: PathBuf, retention_periods: u64) -> Self {
+        let db = DB::open_default(path).expect(OPEN_ERROR);
+        Self {
+            db,
+            retention_periods,
+        }
+    }
+
+    /// Record the credits paid at a final slot and prune the entries that left the retention window
+    ///
+    /// # Arguments
+    /// * `slot`: the final slot at which the credits were paid
+    /// * `credits`: the paid credits
+    pub fn record<'a>(
+        &self,
+        slot: Slot,
+        credits: impl IntoIterator<Item = (&'a Address, &'a Amount)>,
+    ) {
+        let mut batch = WriteBatch::default();
+        for (address, amount) in credits {
+            let address_bytes = address.to_prefixed_bytes();
+            batch.put(
+                address_slot_key!(address_bytes, slot),
+                amount.to_raw().to_be_bytes(),
+            );
+            batch.put(slot_address_key!(slot, address_bytes), &address_bytes);
+        }
+        self.prune(
+            slot.period.saturating_sub(self.retention_periods),
+            &mut batch,
+        );
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Add to `batch` the deletion of all the entries paid before `min_period`
+    fn prune(&self, min_period: u64, batch: &mut WriteBatch) {
+        let prefix = [SLOT_IDENT];
+        for (key, address_bytes) in self
+            .db
+            .iterator(IteratorMode::From(&prefix, Direction::Forward))
+            .map(|item| item.expect(CRUD_ERROR))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+        {
+            let slot = Slot::from_bytes_key(key[1..1 + SLOT_KEY_SIZE].try_into().expect(KEY_ERROR));
+            if slot.period >= min_period {
+                break;
+            }
+            batch.delete(address_slot_key!(address_bytes, slot));
+            batch.delete(key);
+        }
+    }
+
+    /// Get the credits paid to an address at or after a given slot, ordered by slot
+    ///
+    /// # Arguments
+    /// * `address`: the address that received the credits
+    /// * `from_slot`: the first slot to include
+    pub fn get_paid_credits(&self, address: &Address, from_slot: Slot) -> Vec<(Slot, Amount)> {
+        let address_bytes = address.to_prefixed_bytes();
+        let prefix = [&[ADDRESS_IDENT], &address_bytes[..]].concat();
+        let start_key = address_slot_key!(address_bytes, from_slot);
+        self.db
+            .iterator(IteratorMode::From(&start_key, Direction::Forward))
+            .map(|item| item.expect(CRUD_ERROR))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, value)| {
+                let slot = Slot::from_bytes_key(key[prefix.len()..].try_into().expect(KEY_ERROR));
+                let amount =
+                    Amount::from_raw(u64::from_be_bytes(value[..].try_into().expect(VALUE_ERROR)));
+                (slot, amount)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+    use tempfile::TempDir;
+
+    fn setup(retention_periods: u64) -> (PaidCreditsHistory, TempDir) {
+        let tmp_dir = TempDir::new().unwrap();
+        let history = PaidCreditsHistory::new(tmp_dir.path().to_path_buf(), retention_periods);
+        (history, tmp_dir)
+    }
+
+    #[test]
+    fn test_record_and_get() {
+        let (history, _tmp_dir) = setup(100);
+        let address_a = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let address_b = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let amount_1 = Amount::from_str("1").unwrap();
+        let amount_2 = Amount::from_str("2").unwrap();
+
+        history.record(
+            Slot::new(1, 0),
+            [(&address_a, &amount_1), (&address_b, &amount_2)],
+        );
+        history.record(Slot::new(2, 3), [(&address_a, &amount_2)]);
+
+        assert_eq!(
+            history.get_paid_credits(&address_a, Slot::new(0, 0)),
+            vec![(Slot::new(1, 0), amount_1), (Slot::new(2, 3), amount_2)]
+        );
+        assert_eq!(
+            history.get_paid_credits(&address_a, Slot::new(1, 1)),
+            vec![(Slot::new(2, 3), amount_2)]
+        );
+        assert_eq!(
+            history.get_paid_credits(&address_b, Slot::new(0, 0)),
+            vec![(Slot::new(1, 0), amount_2)]
+        );
+    }
+
+    #[test]
+    fn test_prune_old_entries() {
+        let (history, _tmp_dir) = setup(2);
+        let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let amount = Amount::from_str("1").unwrap();
+
+        history.record(Slot::new(1, 0), [(&address, &amount)]);
+        history.record(Slot::new(2, 0), [(&address, &amount)]);
+        history.record(Slot::new(4, 0), [(&address, &amount)]);
+
+        assert_eq!(
+            history.get_paid_credits(&address, Slot::new(0, 0)),
+            vec![(Slot::new(2, 0), amount), (Slot::new(4, 0), amount)]
+        );
+    }
+}
@@ -7,7 +7,9 @@ use massa_models::address::ExecutionAddressCycleInfo;
 use massa_models::{
     address::Address, amount::Amount, block_id::BlockId, prehash::PreHashMap, slot::Slot,
 };
-use massa_pos_exports::{DeferredCredits, PoSChanges, ProductionStats};
+use massa_pos_exports::{
+    roll_refund_slot, split_credit, DeferredCredits, PoSChanges, ProductionStats,
+};
 use num::rational::Ratio;
 use parking_lot::RwLock;
 use std::cmp::min;
@@ -104,6 +106,12 @@ impl SpeculativeRollState {
     /// # Arguments
     /// * `seller_addr`: address to sell the rolls from
     /// * `roll_count`: number of rolls to sell
+    /// * `credit_split_threshold`: reimbursements above this amount are split across consecutive slots
+    /// * `credit_split_parts`: number of slots a split reimbursement is spread over
+    ///
+    /// # Returns
+    /// The reimbursement parts with the slot at which each of them is paid
+    #[allow(clippy::too_many_arguments)]
     pub fn try_sell_rolls(
         &mut self,
         seller_addr: &Address,
@@ -112,7 +120,9 @@ impl SpeculativeRollState {
         periods_per_cycle: u64,
         thread_count: u8,
         roll_price: Amount,
-    ) -> Result<(), ExecutionError> {
+        credit_split_threshold: Amount,
+        credit_split_parts: u64,
+    ) -> Result<Vec<(Slot, Amount)>, ExecutionError> {
         // fetch the roll count from: current changes > active history > final state
         let owned_count = self.get_rolls(seller_addr);
 
@@ -125,33 +135,60 @@ impl SpeculativeRollState {
         }
 
         // compute deferred credit slot
-        let cur_cycle = slot.get_cycle(periods_per_cycle);
-        let target_slot = Slot::new_last_of_cycle(
-            cur_cycle
-                .checked_add(3)
-                .expect("unexpected cycle overflow in try_sell_rolls"),
-            periods_per_cycle,
-            thread_count,
-        )
-        .expect("unexpected slot overflow in try_sell_rolls");
-
-        // Note 1: Deferred credits are stored as absolute value
-        let new_deferred_credits = self
-            .get_address_deferred_credit_for_slot(seller_addr, &target_slot)
-            .unwrap_or_default()
-            .saturating_add(roll_price.saturating_mul_u64(roll_count));
+        let mut target_slot = roll_refund_slot(slot, periods_per_cycle, thread_count)
+            .expect("unexpected slot overflow in try_sell_rolls");
 
         // Remove the rolls
         self.added_changes
             .roll_changes
             .insert(*seller_addr, owned_count.saturating_sub(roll_count));
 
-        // Add deferred credits (reimbursement) corresponding to the sold rolls value
+        // Add deferred credits (reimbursement) corresponding to the sold rolls value,
+        // large reimbursements being spread over consecutive slots starting at the target slot
+        let credit_parts = split_credit(
+            roll_price.saturating_mul_u64(roll_count),
+            credit_split_threshold,
+            credit_split_parts,
+        );
+        let mut scheduled_parts = Vec::with_capacity(credit_parts.len());
+        for (index, credit_part) in credit_parts.into_iter().enumerate() {
+            if index > 0 {
+                target_slot = target_slot
+                    .get_next_slot(thread_count)
+                    .expect("unexpected slot overflow in try_sell_rolls");
+            }
+            // Note 1: Deferred credits are stored as absolute value
+            let new_deferred_credits = self
+                .get_address_deferred_credit_for_slot(seller_addr, &target_slot)
+                .unwrap_or_default()
+                .saturating_add(credit_part);
+            self.added_changes.deferred_credits.insert(
+                target_slot,
+                *seller_addr,
+                new_deferred_credits,
+            );
+            scheduled_parts.push((target_slot, credit_part));
+        }
+
+        Ok(scheduled_parts)
+    }
+
+    /// Add `amount` to the deferred credits of an address at a given slot.
+    /// Validity checks must be performed _outside_ of this function.
+    ///
+    /// # Arguments
+    /// * `slot`: slot at which the credits will be paid
+    /// * `addr`: address that will receive the credits
+    /// * `amount`: amount of credits to add
+    pub fn add_deferred_credits(&mut self, slot: Slot, addr: &Address, amount: Amount) {
+        // Deferred credits are stored as absolute value
+        let new_deferred_credits = self
+            .get_address_deferred_credit_for_slot(addr, &slot)
+            .unwrap_or_default()
+            .saturating_add(amount);
         self.added_changes
             .deferred_credits
-            .insert(target_slot, *seller_addr, new_deferred_credits);
-
-        Ok(())
+            .insert(slot, *addr, new_deferred_credits);
     }
 
     /// Try to slash `roll_count` rolls from the given address. If not enough roll, slash
@@ -247,6 +284,9 @@ impl SpeculativeRollState {
     ///
     /// # Arguments:
     /// `slot`: the final slot of the cycle to compute
+    ///
+    /// # Returns
+    /// The address, slot and amount of the reimbursements of the deactivated stakers
     pub fn settle_production_stats(
         &mut self,
         slot: &Slot,
@@ -254,7 +294,7 @@ impl SpeculativeRollState {
         thread_count: u8,
         roll_price: Amount,
         max_miss_ratio: Ratio<u64>,
-    ) {
+    ) -> Vec<(Address, Slot, Amount)> {
         let cycle = slot.get_cycle(periods_per_cycle);
 
         let (production_stats, full) =
@@ -265,16 +305,12 @@ impl SpeculativeRollState {
             )
         }
 
-        let target_slot = Slot::new_last_of_cycle(
-            cycle
-                .checked_add(3)
-                .expect("unexpected cycle overflow in settle_production_stats"),
-            periods_per_cycle,
-            thread_count,
-        )
-        .expect("unexpected slot overflow in settle_production_stats");
+        // the rolls of the stakers that missed too many blocks are sold and reimbursed like regular roll sales
+        let target_slot = roll_refund_slot(*slot, periods_per_cycle, thread_count)
+            .expect("unexpected slot overflow in settle_production_stats");
 
         let mut target_credits = PreHashMap::default();
+        let mut refunds = Vec::new();
         for (addr, stats) in production_stats {
             if !stats.is_satisfying(&max_miss_ratio) {
                 let owned_count = self.get_rolls(&addr);
@@ -286,15 +322,17 @@ impl SpeculativeRollState {
                             .saturating_add(amount);
                         target_credits.insert(addr, new_deferred_credits);
                         self.added_changes.roll_changes.insert(addr, 0);
+                        refunds.push((addr, target_slot, amount));
                     }
                 }
             }
         }
         if !target_credits.is_empty() {
             let mut credits = DeferredCredits::new();
-            credits.credits.insert(target_slot, target_credits);
+            credits.credits.insert(target_slot.into(), target_credits);
             self.added_changes.deferred_credits.extend(credits);
         }
+        refunds
     }
 
     /// Get deferred credits of an address starting from a given slot
@@ -316,7 +354,7 @@ impl SpeculativeRollState {
             .range(slot_range.clone())
         {
             if let Some(amount) = addr_amount.get(address) {
-                res.entry(*slot).or_insert(*amount);
+                res.entry(slot.slot()).or_insert(*amount);
             };
         }
 
@@ -332,7 +370,7 @@ impl SpeculativeRollState {
                     .range(slot_range.clone())
                 {
                     if let Some(amount) = addr_amount.get(address) {
-                        res.entry(*slot).or_insert(*amount);
+                        res.entry(slot.slot()).or_insert(*amount);
                     };
                 }
             }
@@ -347,7 +385,7 @@ impl SpeculativeRollState {
                 .credits
             {
                 if let Some(amount) = addr_amount.get(address) {
-                    res.entry(slot).or_insert(*amount);
+                    res.entry(slot.slot()).or_insert(*amount);
                 };
             }
         }
@@ -355,6 +393,58 @@ impl SpeculativeRollState {
         res.into_iter().filter(|(_s, v)| !v.is_zero()).collect()
     }
 
+    /// Set (`Some`) or remove (`None`) the payout address of an address.
+    /// Validity checks must be performed _outside_ of this function.
+    pub fn set_payout_address(&mut self, addr: &Address, payout_address: Option<Address>) {
+        self.added_changes
+            .payout_addresses
+            .insert(*addr, payout_address);
+    }
+
+    /// Get the payout address of an address, if it designated one
+    pub fn get_payout_address(&self, addr: &Address) -> Option<Address> {
+        // search in the added changes
+        if let Some(payout_address) = self.added_changes.payout_addresses.get(addr) {
+            return *payout_address;
+        }
+
+        // search in the history
+        if let Some(payout_address) = self.active_history.read().fetch_payout_address(addr) {
+            return payout_address;
+        }
+
+        // search in the final state
+        self.final_state
+            .read()
+            .get_pos_state()
+            .get_payout_address(addr)
+    }
+
+    /// Set (`Some`) or remove (`None`) the operator an address delegates its rolls to.
+    /// Validity checks must be performed _outside_ of this function.
+    pub fn set_delegation(&mut self, addr: &Address, operator: Option<Address>) {
+        self.added_changes.delegations.insert(*addr, operator);
+    }
+
+    /// Get the operator an address delegates its rolls to, if any
+    pub fn get_delegation(&self, addr: &Address) -> Option<Address> {
+        // search in the added changes
+        if let Some(operator) = self.added_changes.delegations.get(addr) {
+            return *operator;
+        }
+
+        // search in the history
+        if let Some(operator) = self.active_history.read().fetch_delegation(addr) {
+            return operator;
+        }
+
+        // search in the final state
+        self.final_state
+            .read()
+            .get_pos_state()
+            .get_delegation_operator(addr)
+    }
+
     /// Gets the deferred credits for a given address that will be credited at a given slot
     fn get_address_deferred_credit_for_slot(&self, addr: &Address, slot: &Slot) -> Option<Amount> {
         // search in the added changes
@@ -597,4 +687,44 @@ impl SpeculativeRollState {
         // return taken credits
         credits
     }
+
+    /// Postpone all the non-zero deferred credits at or after `slot` by `delay_periods` periods.
+    ///
+    /// # Arguments
+    /// * `slot`: first slot of the deferred credits to postpone
+    /// * `delay_periods`: number of periods by which the credits are postponed
+    ///
+    /// # Returns
+    /// The number of postponed credits, or `None` if a postponed period overflows, in which case nothing is postponed
+    pub fn postpone_deferred_credits(&mut self, slot: &Slot, delay_periods: u64) -> Option<usize> {
+        // get final deferred credits
+        let mut credits = self
+            .final_state
+            .read()
+            .get_pos_state()
+            .get_deferred_credits_range(slot.., None);
+
+        // fetch active history deferred credits
+        credits.extend(
+            self.active_history
+                .read()
+                .get_all_deferred_credits_from(slot),
+        );
+
+        // added deferred credits
+        credits.extend(self.added_changes.deferred_credits.get_slot_range(slot..));
+
+        // filter out zeros
+        credits.remove_zeros();
+
+        let changes = credits.postponed_changes(delay_periods)?;
+        self.added_changes.deferred_credits.extend(changes);
+        Some(
+            credits
+                .credits
+                .values()
+                .map(|slot_credits| slot_credits.len())
+                .sum(),
+        )
+    }
 }
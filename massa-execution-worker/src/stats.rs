@@ -1,7 +1,7 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::slot::Slot;
-use massa_models::stats::ExecutionStats;
+use massa_models::stats::{ExecutionStats, PoSStats};
 use massa_time::MassaTime;
 use std::collections::VecDeque;
 
@@ -74,7 +74,12 @@ impl ExecutionStatsCounter {
     }
 
     /// get statistics
-    pub fn get_stats(&self, active_cursor: Slot, final_cursor: Slot) -> ExecutionStats {
+    pub fn get_stats(
+        &self,
+        active_cursor: Slot,
+        final_cursor: Slot,
+        pos_stats: PoSStats,
+    ) -> ExecutionStats {
         let current_time = MassaTime::now();
         let start_time = current_time.saturating_sub(self.time_window_duration);
         let map_func = |pair: &(usize, MassaTime)| -> usize {
@@ -92,6 +97,7 @@ impl ExecutionStatsCounter {
             time_window_end: current_time,
             active_cursor,
             final_cursor,
+            pos_stats,
         }
     }
 }
@@ -15,7 +15,8 @@ use massa_ledger_exports::{
 };
 use massa_models::bytecode::Bytecode;
 use massa_models::config::{
-    CHAINID, ENDORSEMENT_COUNT, LEDGER_ENTRY_DATASTORE_BASE_SIZE, THREAD_COUNT,
+    CHAINID, ENDORSEMENT_COUNT, LEDGER_ENTRY_DATASTORE_BASE_SIZE, MIP_STORE_STATS_BLOCK_CONSIDERED,
+    THREAD_COUNT,
 };
 use massa_models::prehash::PreHashMap;
 use massa_models::test_exports::gen_endorsements_for_denunciation;
@@ -31,6 +32,11 @@ use massa_pos_exports::{
 };
 use massa_signature::KeyPair;
 use massa_test_framework::{TestUniverse, WaitPoint};
+use massa_time::MassaTime;
+use massa_versioning::test_helpers::versioning_helpers::advance_state_until;
+use massa_versioning::versioning::{
+    ComponentState, MipComponent, MipInfo, MipStatsConfig, MipStore,
+};
 use mockall::predicate;
 use num::rational::Ratio;
 use parking_lot::RwLock;
@@ -165,7 +171,7 @@ fn selector_boilerplate(mock_selector: &mut MockSelectorControllerWrapper) {
     mock_selector.set_expectations(|selector_controller| {
         selector_controller
             .expect_feed_cycle()
-            .returning(move |_, _, _| Ok(()));
+            .returning(move |_, _, _, _| Ok(()));
         selector_controller
             .expect_wait_for_draws()
             .returning(move |cycle| Ok(cycle + 1));
@@ -176,6 +182,9 @@ fn selector_boilerplate(mock_selector: &mut MockSelectorControllerWrapper) {
                     &KeyPair::from_str(TEST_SK_1).unwrap().get_public_key(),
                 ))
             });
+        selector_controller
+            .expect_get_operator_rolls()
+            .returning(move |_, _| Ok(None));
     });
 }
 
@@ -2569,6 +2578,137 @@ fn test_rewards() {
     finalized_waitpoint.wait();
 }
 
+#[test]
+fn set_payout_address_redirects_rewards() {
+    // setup
+    let exec_cfg = ExecutionConfig {
+        thread_count: 2,
+        periods_per_cycle: 2,
+        last_start_period: 2,
+        max_miss_ratio: Ratio::new(1, 1),
+        ..Default::default()
+    };
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+    // enable the payout addresses
+    let mip_info = MipInfo {
+        name: "MIP-0003".to_string(),
+        version: 3,
+        components: BTreeMap::from([(MipComponent::PayoutAddress, 1)]),
+        start: MassaTime::from_millis(2),
+        timeout: MassaTime::from_millis(5),
+        activation_delay: MassaTime::from_millis(2),
+    };
+    let mip_state = advance_state_until(ComponentState::active(MassaTime::now()), &mip_info);
+    foreign_controllers.mip_store = MipStore::try_from((
+        [(mip_info, mip_state)],
+        MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            warn_announced_version_ratio: Ratio::new_raw(30, 100),
+        },
+    ))
+    .unwrap();
+    let finalized_waitpoint = WaitPoint::new();
+    let finalized_waitpoint_trigger_handle = finalized_waitpoint.get_trigger_handle();
+    let keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+    let address = Address::from_public_key(&keypair.get_public_key());
+    let payout_address =
+        Address::from_public_key(&KeyPair::from_str(TEST_SK_2).unwrap().get_public_key());
+    let (rolls_path, _) = get_initials();
+    let mut batch = DBBatch::new();
+    let initial_deferred_credits = Amount::from_str("100").unwrap();
+    let mut pos_final_state = PoSFinalState::new(
+        PoSConfig::default(),
+        "",
+        &rolls_path.into_temp_path().to_path_buf(),
+        foreign_controllers.selector_controller.clone(),
+        foreign_controllers.db.clone(),
+    )
+    .unwrap();
+    pos_final_state.create_initial_cycle(&mut batch);
+    pos_final_state.put_deferred_credits_entry(
+        &Slot::new(1, 0),
+        &address,
+        &initial_deferred_credits,
+        &mut batch,
+    );
+    foreign_controllers
+        .db
+        .write()
+        .write_batch(batch, Default::default(), None);
+    pos_final_state.recompute_pos_state_caches();
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        Some(pos_final_state),
+    );
+    foreign_controllers
+        .final_state
+        .write()
+        .expect_finalize()
+        .times(1)
+        .with(predicate::eq(Slot::new(3, 0)), predicate::always())
+        .returning(move |_, changes| {
+            assert_eq!(
+                changes.pos_changes.payout_addresses.get(&address),
+                Some(&Some(payout_address))
+            );
+            // the block reward and the deferred credits of the block creator land at its payout address
+            assert_eq!(
+                changes
+                    .ledger_changes
+                    .get_balance_or_else(&payout_address, || None),
+                Some(
+                    // 100 from the boilerplate
+                    Amount::from_mantissa_scale(100, 0)
+                        .unwrap()
+                        // + deferred credits set above
+                        .saturating_add(initial_deferred_credits)
+                        // + block rewards
+                        .saturating_add(exec_cfg.block_reward)
+                )
+            );
+            // while the block creator itself is not credited
+            assert!(changes
+                .ledger_changes
+                .get_balance_or_else(&address, || None)
+                .map_or(true, |balance| balance
+                    == Amount::from_mantissa_scale(100, 0).unwrap()));
+            finalized_waitpoint_trigger_handle.trigger();
+        });
+    let mut universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg.clone());
+
+    // create the block containing the set payout address operation
+    let operation = Operation::new_verifiable(
+        Operation {
+            fee: Amount::zero(),
+            expire_period: 10,
+            op: OperationType::SetPayoutAddress {
+                payout_address: Some(payout_address),
+            },
+        },
+        OperationSerializer::new(),
+        &keypair,
+        *CHAINID,
+    )
+    .unwrap();
+    universe.storage.store_operations(vec![operation.clone()]);
+    let block = ExecutionTestUniverse::create_block(
+        &keypair,
+        Slot::new(3, 0),
+        vec![operation],
+        vec![],
+        vec![],
+    );
+    // set our block as a final block so the payout address is applied
+    universe.send_and_finalize(&keypair, block);
+    finalized_waitpoint.wait();
+}
+
 #[test]
 fn chain_id() {
     // setup the period duration
@@ -35,7 +35,7 @@ fn test_active_history_deferred_credits() {
     ph2.insert(addr2, amount_a2_s2);
 
     let mut credits = DeferredCredits::new();
-    credits.credits = BTreeMap::from([(slot1, ph1), (slot2, ph2)]);
+    credits.credits = BTreeMap::from([(slot1.into(), ph1), (slot2.into(), ph2)]);
 
     let exec_output_1 = ExecutionOutput {
         slot: Slot::new(1, 0),
@@ -48,12 +48,15 @@ fn test_active_history_deferred_credits() {
                 roll_changes: Default::default(),
                 production_stats: Default::default(),
                 deferred_credits: credits,
+                payout_addresses: Default::default(),
+                delegations: Default::default(),
             },
             executed_ops_changes: Default::default(),
             executed_denunciations_changes: Default::default(),
             execution_trail_hash_change: Default::default(),
         },
         events: Default::default(),
+        scheduled_credits: Default::default(),
     };
 
     let active_history = ActiveHistory(VecDeque::from([exec_output_1]));
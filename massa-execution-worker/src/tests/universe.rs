@@ -4,6 +4,7 @@ use std::{
     sync::Arc,
 };
 
+use massa_channel::event_bus::MassaEventBus;
 use massa_db_exports::{MassaDBConfig, MassaDBController, ShareableMassaDBController};
 use massa_db_worker::MassaDB;
 use massa_execution_exports::{
@@ -45,6 +46,7 @@ pub struct ExecutionForeignControllers {
     pub final_state: Arc<RwLock<MockFinalStateController>>,
     pub ledger_controller: MockLedgerControllerWrapper,
     pub db: ShareableMassaDBController,
+    pub mip_store: MipStore,
 }
 
 impl ExecutionForeignControllers {
@@ -61,11 +63,16 @@ impl ExecutionForeignControllers {
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
         ));
+        let mip_stats_config = MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            warn_announced_version_ratio: Ratio::new_raw(30, 100),
+        };
         Self {
             selector_controller: Box::new(MockSelectorControllerWrapper::new()),
             ledger_controller: MockLedgerControllerWrapper::new(),
             final_state: Arc::new(RwLock::new(MockFinalStateController::new())),
             db,
+            mip_store: MipStore::try_from(([], mip_stats_config)).unwrap(),
         }
     }
 }
@@ -83,19 +90,15 @@ impl TestUniverse for ExecutionTestUniverse {
 
     fn new(controllers: Self::ForeignControllers, config: Self::Config) -> Self {
         let storage = Storage::create_root();
-        let mip_stats_config = MipStatsConfig {
-            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
-            warn_announced_version_ratio: Ratio::new_raw(30, 100),
-        };
-        let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
         let (tx, _) = broadcast::channel(16);
         let (module_manager, module_controller) = start_execution_worker(
             config.clone(),
             controllers.final_state.clone(),
             controllers.selector_controller,
-            mip_store,
+            controllers.mip_store,
             ExecutionChannels {
                 slot_execution_output_sender: tx,
+                event_bus: MassaEventBus::new("test_node_event".to_string()),
             },
             Arc::new(RwLock::new(create_test_wallet(Some(PreHashMap::default())))),
             MassaMetrics::new(
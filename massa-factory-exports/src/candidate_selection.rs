@@ -0,0 +1,78 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines how the block factory chooses among the block contents proposed by external builders
+
+use massa_consensus_exports::external_candidates::ExternalBlockCandidate;
+use massa_models::slot::Slot;
+use serde::Deserialize;
+
+/// Policy used by the block factory to choose the external candidate it signs
+pub trait CandidateSelectionPolicy: Send + Sync {
+    /// Choose the candidate to sign for a block produced at `slot`
+    ///
+    /// # Arguments
+    /// * `slot`: slot of the block being produced
+    /// * `candidates`: candidates accepted by consensus for the slot, in submission order
+    ///
+    /// # Returns
+    /// The chosen candidate, or `None` to build the block locally.
+    /// If the parents of the chosen candidate are no longer valid, the factory builds the block locally.
+    fn select_candidate<'a>(
+        &self,
+        slot: Slot,
+        candidates: &'a [ExternalBlockCandidate],
+    ) -> Option<&'a ExternalBlockCandidate>;
+}
+
+/// Uses the first submitted candidate
+pub struct FirstSubmittedPolicy;
+
+impl CandidateSelectionPolicy for FirstSubmittedPolicy {
+    fn select_candidate<'a>(
+        &self,
+        _slot: Slot,
+        candidates: &'a [ExternalBlockCandidate],
+    ) -> Option<&'a ExternalBlockCandidate> {
+        candidates.first()
+    }
+}
+
+/// Uses the candidate with the most operations, the first submitted one on ties
+pub struct MostOperationsPolicy;
+
+impl CandidateSelectionPolicy for MostOperationsPolicy {
+    fn select_candidate<'a>(
+        &self,
+        _slot: Slot,
+        candidates: &'a [ExternalBlockCandidate],
+    ) -> Option<&'a ExternalBlockCandidate> {
+        // `max_by_key` returns the last maximum: iterate in reverse so that ties go to the first submitted
+        candidates
+            .iter()
+            .rev()
+            .max_by_key(|candidate| candidate.operations.len())
+    }
+}
+
+/// External candidate selection policy of the block factory, as read from the configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub enum CandidateSelectionPolicyConfig {
+    /// ignore the external candidates and always build the blocks locally
+    #[default]
+    LocalOnly,
+    /// sign the first submitted candidate
+    FirstSubmitted,
+    /// sign the candidate with the most operations
+    MostOperations,
+}
+
+impl CandidateSelectionPolicyConfig {
+    /// Instantiate the configured policy, `None` if the external candidates are ignored
+    pub fn build(&self) -> Option<Box<dyn CandidateSelectionPolicy>> {
+        match self {
+            CandidateSelectionPolicyConfig::LocalOnly => None,
+            CandidateSelectionPolicyConfig::FirstSubmitted => Some(Box::new(FirstSubmittedPolicy)),
+            CandidateSelectionPolicyConfig::MostOperations => Some(Box::new(MostOperationsPolicy)),
+        }
+    }
+}
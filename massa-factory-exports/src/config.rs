@@ -2,6 +2,7 @@
 
 //! This file defines the factory settings
 
+use crate::{CandidateSelectionPolicyConfig, ParentSelectionPolicyConfig};
 use massa_time::MassaTime;
 
 /// Structure defining the settings of the factory
@@ -29,6 +30,12 @@ pub struct FactoryConfig {
     pub denunciation_expire_periods: u64,
     /// choose whether to stop production when zero connections on protocol
     pub stop_production_when_zero_connections: bool,
+    /// policy used to choose the parents of the produced blocks
+    pub parent_selection_policy: ParentSelectionPolicyConfig,
+    /// policy used to choose among the block contents proposed by external builders
+    pub candidate_selection_policy: CandidateSelectionPolicyConfig,
+    /// whether blocks are produced, disabled for nodes running consensus in light mode
+    pub block_production_enabled: bool,
     /// chain id
     pub chain_id: u64,
 }
@@ -6,14 +6,18 @@
 
 #![warn(missing_docs)]
 
+mod candidate_selection;
 mod config;
 mod controller_traits;
 mod error;
+mod parent_selection;
 mod types;
 
+pub use candidate_selection::*;
 pub use config::FactoryConfig;
 pub use controller_traits::FactoryManager;
 pub use error::*;
+pub use parent_selection::*;
 pub use types::*;
 
 /// Tests utils
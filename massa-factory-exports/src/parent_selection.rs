@@ -0,0 +1,115 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines how the block factory chooses the parents of the blocks it produces
+
+use massa_consensus_exports::parent_candidates::{ParentCandidate, ParentCandidates};
+use massa_models::{address::Address, block_id::BlockId, prehash::PreHashSet, slot::Slot};
+use serde::Deserialize;
+
+/// Policy used by the block factory to choose the parents of a new block
+pub trait ParentSelectionPolicy: Send + Sync {
+    /// Choose the parents of a block produced at `slot`
+    ///
+    /// # Arguments
+    /// * `slot`: slot of the block being produced
+    /// * `candidates`: parents proposed by consensus
+    ///
+    /// # Returns
+    /// One (block id, period) per thread.
+    /// If the choice is not valid, the factory falls back to the best parents of consensus.
+    fn select_parents(&self, slot: Slot, candidates: &ParentCandidates) -> Vec<(BlockId, u64)>;
+}
+
+/// Uses the best parents of consensus
+pub struct BestParentsPolicy;
+
+impl ParentSelectionPolicy for BestParentsPolicy {
+    fn select_parents(&self, _slot: Slot, candidates: &ParentCandidates) -> Vec<(BlockId, u64)> {
+        candidates.best_parents.clone()
+    }
+}
+
+/// In each thread, prefers the candidate with the most endorsements, then the latest one
+pub struct MostEndorsedParentsPolicy;
+
+impl ParentSelectionPolicy for MostEndorsedParentsPolicy {
+    fn select_parents(&self, slot: Slot, candidates: &ParentCandidates) -> Vec<(BlockId, u64)> {
+        select_per_thread(slot, candidates, |thread_candidates| {
+            // candidates are sorted from the latest to the oldest
+            // and `max_by_key` returns the last maximum: iterate in reverse so that ties go to the latest
+            thread_candidates
+                .iter()
+                .rev()
+                .max_by_key(|candidate| candidate.endorsement_count)
+                .copied()
+        })
+    }
+}
+
+/// In each thread, uses the latest candidate that was not created by one of the given addresses
+pub struct AvoidCreatorsPolicy {
+    /// creators whose blocks are not used as parents
+    pub creators: PreHashSet<Address>,
+}
+
+impl ParentSelectionPolicy for AvoidCreatorsPolicy {
+    fn select_parents(&self, slot: Slot, candidates: &ParentCandidates) -> Vec<(BlockId, u64)> {
+        select_per_thread(slot, candidates, |thread_candidates| {
+            thread_candidates
+                .iter()
+                .find(|candidate| !self.creators.contains(&candidate.creator_address))
+                .copied()
+        })
+    }
+}
+
+/// Picks one candidate per thread among the ones earlier than `slot`,
+/// keeping the best parent of the thread when `pick` does not return any candidate
+fn select_per_thread<'a, F>(
+    slot: Slot,
+    candidates: &'a ParentCandidates,
+    pick: F,
+) -> Vec<(BlockId, u64)>
+where
+    F: Fn(&[&'a ParentCandidate]) -> Option<&'a ParentCandidate>,
+{
+    candidates
+        .candidates
+        .iter()
+        .zip(candidates.best_parents.iter())
+        .map(|(thread_candidates, best_parent)| {
+            let eligible: Vec<&ParentCandidate> = thread_candidates
+                .iter()
+                .filter(|candidate| candidate.slot < slot)
+                .collect();
+            pick(&eligible)
+                .map(|candidate| (candidate.block_id, candidate.slot.period))
+                .unwrap_or(*best_parent)
+        })
+        .collect()
+}
+
+/// Parent selection policy of the block factory, as read from the configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub enum ParentSelectionPolicyConfig {
+    /// use the best parents of consensus
+    #[default]
+    BestParents,
+    /// prefer the parents with the most endorsements
+    MostEndorsed,
+    /// avoid the parents created by the given addresses
+    AvoidCreators(Vec<Address>),
+}
+
+impl ParentSelectionPolicyConfig {
+    /// Instantiate the configured policy
+    pub fn build(&self) -> Box<dyn ParentSelectionPolicy> {
+        match self {
+            ParentSelectionPolicyConfig::BestParents => Box::new(BestParentsPolicy),
+            ParentSelectionPolicyConfig::MostEndorsed => Box::new(MostEndorsedParentsPolicy),
+            ParentSelectionPolicyConfig::AvoidCreators(creators) => Box::new(AvoidCreatorsPolicy {
+                creators: creators.iter().copied().collect(),
+            }),
+        }
+    }
+}
@@ -18,6 +18,9 @@ impl Default for FactoryConfig {
             periods_per_cycle: PERIODS_PER_CYCLE,
             denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
             stop_production_when_zero_connections: false,
+            parent_selection_policy: Default::default(),
+            candidate_selection_policy: Default::default(),
+            block_production_enabled: true,
             chain_id: *CHAINID,
         }
     }
@@ -1,23 +1,36 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_channel::receiver::MassaReceiver;
-use massa_factory_exports::{FactoryChannels, FactoryConfig};
+use massa_consensus_exports::{
+    block_provenance::BlockOrigin, external_candidates::ExternalBlockCandidate,
+    parent_candidates::ParentCandidates,
+};
+use massa_factory_exports::{
+    CandidateSelectionPolicy, FactoryChannels, FactoryConfig, ParentSelectionPolicy,
+};
 use massa_models::{
+    address::Address,
     block::{Block, BlockSerializer},
     block_header::{BlockHeader, BlockHeaderSerializer, SecuredHeader},
     block_id::BlockId,
     endorsement::SecureShareEndorsement,
-    operation::{compute_operations_hash, OperationIdSerializer},
+    operation::{compute_operations_hash, OperationId, OperationIdSerializer},
+    prehash::PreHashSet,
     secure_share::SecureShareContent,
     slot::Slot,
     timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
 };
+use massa_signature::KeyPair;
+use massa_storage::Storage;
 use massa_time::MassaTime;
 use massa_versioning::versioning::MipStore;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::{sync::Arc, thread, time::Instant};
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+/// Number of times the parents of a block are chosen if the graph keeps changing during the choice
+const MAX_PARENT_SELECTION_ATTEMPTS: u32 = 3;
 
 /// Structure gathering all elements needed by the factory thread
 pub(crate) struct BlockFactoryWorker {
@@ -27,6 +40,8 @@ pub(crate) struct BlockFactoryWorker {
     factory_receiver: MassaReceiver<()>,
     mip_store: MipStore,
     op_id_serializer: OperationIdSerializer,
+    parent_selection_policy: Box<dyn ParentSelectionPolicy>,
+    candidate_selection_policy: Option<Box<dyn CandidateSelectionPolicy>>,
 }
 
 impl BlockFactoryWorker {
@@ -43,6 +58,8 @@ impl BlockFactoryWorker {
             .name("block-factory".into())
             .spawn(|| {
                 let mut this = Self {
+                    parent_selection_policy: cfg.parent_selection_policy.build(),
+                    candidate_selection_policy: cfg.candidate_selection_policy.build(),
                     cfg,
                     wallet,
                     channels,
@@ -145,6 +162,13 @@ impl BlockFactoryWorker {
             // the selected block producer is not managed locally => quit
             return;
         };
+        if !self.cfg.block_production_enabled {
+            warn!(
+                "block factory did not produce the block of address {} for slot {} because block production is disabled in consensus light mode",
+                block_producer_addr, slot
+            );
+            return;
+        }
         let mut block_storage = self.channels.storage.clone_without_refs();
         {
             let block_lock = block_storage.read_blocks();
@@ -170,15 +194,125 @@ impl BlockFactoryWorker {
             }
         }
 
-        // get best parents and their periods
-        let parents: Vec<(BlockId, u64)> = self.channels.consensus.get_best_parents(); // Vec<(parent_id, parent_period)>
-                                                                                       // generate the local storage object
+        // sign the contents proposed by an external builder if the selection policy picks one
+        if let Some(candidate) = self.select_external_candidate(slot) {
+            let header = BlockHeader {
+                current_version: self.mip_store.get_network_version_current(),
+                announced_version: self.mip_store.get_network_version_to_announce(),
+                ..candidate.header_template
+            };
+            block_storage.extend(candidate.storage);
+            info!(
+                "block factory signs the external candidate {} for slot {}",
+                candidate.id, slot
+            );
+            self.sign_and_register(
+                header,
+                candidate.operations,
+                block_producer_keypair,
+                block_producer_addr,
+                block_storage,
+            );
+            return;
+        }
+
+        // choose parents and gather their endorsements,
+        // again if the graph changed in the meantime so that the block does not build on outdated data
+        let mut attempt = 1;
+        let (parents, endorsements, endo_storage) = loop {
+            let candidates = self.channels.consensus.get_parent_candidates();
+            let generation = candidates.generation;
+            let parents = self.choose_parents(slot, candidates);
+            let (endorsements, endo_storage) = self.gather_endorsements(slot, &parents);
+            match self.channels.consensus.if_unchanged(generation) {
+                Ok(()) => break (parents, endorsements, endo_storage),
+                Err(err) if attempt < MAX_PARENT_SELECTION_ATTEMPTS => {
+                    debug!(
+                        "block factory: {}, choosing the parents of slot {} again",
+                        err, slot
+                    );
+                    attempt += 1;
+                }
+                Err(err) => {
+                    warn!(
+                        "block factory: {}, producing the block of slot {} with the parents chosen last",
+                        err, slot
+                    );
+                    break (parents, endorsements, endo_storage);
+                }
+            }
+        };
+        block_storage.extend(endo_storage);
+
+        // gather operations and compute global operations hash
+        let (mut op_ids, mut op_storage) = self.channels.pool.get_block_operations(&slot);
+        if op_ids.len() > self.cfg.max_operations_per_block as usize {
+            warn!("Too many operations returned");
+            return;
+        }
+
+        // leave out the operations whose type is not enabled yet, the block would be invalid
+        let inactive_ops = self.get_inactive_operations(slot, &op_storage);
+        if !inactive_ops.is_empty() {
+            debug!(
+                "block factory leaves out {} operations whose type is not enabled yet at slot {}",
+                inactive_ops.len(),
+                slot
+            );
+            op_ids.retain(|op_id| !inactive_ops.contains(op_id));
+            op_storage.drop_operation_refs(&inactive_ops);
+        }
+
+        block_storage.extend(op_storage);
+
+        // create header
+        let header = BlockHeader {
+            current_version: self.mip_store.get_network_version_current(),
+            announced_version: self.mip_store.get_network_version_to_announce(),
+            slot,
+            parents: parents.into_iter().map(|(id, _period)| id).collect(),
+            operation_merkle_root: compute_operations_hash(&op_ids, &self.op_id_serializer),
+            endorsements,
+            denunciations: self.channels.pool.get_block_denunciations(&slot),
+            // no extension is produced yet: they may only be emitted once a network version
+            // enabling them (`MipComponent::BlockHeaderExtensions`) is active at the slot
+            extensions: Vec::new(),
+        };
+        self.sign_and_register(
+            header,
+            op_ids.into_iter().collect(),
+            block_producer_keypair,
+            block_producer_addr,
+            block_storage,
+        );
+    }
+
+    /// Choose the parents of a block at `slot` and their periods among the candidates of consensus,
+    /// falling back to the best parents if the selection policy makes an invalid choice
+    fn choose_parents(&self, slot: Slot, candidates: ParentCandidates) -> Vec<(BlockId, u64)> {
+        let parents: Vec<(BlockId, u64)> = self
+            .parent_selection_policy
+            .select_parents(slot, &candidates); // Vec<(parent_id, parent_period)>
+        if parents != candidates.best_parents && !candidates.is_valid_choice(slot, &parents) {
+            warn!(
+                "block factory: invalid parents chosen for slot {}, falling back to the best parents",
+                slot
+            );
+            return candidates.best_parents;
+        }
+        parents
+    }
 
+    /// Gather the endorsements of the parent in the thread of `slot` from the pool
+    fn gather_endorsements(
+        &self,
+        slot: Slot,
+        parents: &[(BlockId, u64)],
+    ) -> (Vec<SecureShareEndorsement>, Storage) {
         // get the parent in the same thread, with its period
         // will not panic because the thread is validated before the call
         let (same_thread_parent_id, _) = parents[slot.thread as usize];
 
-        // gather endorsements
         let (endorsements_ids, endo_storage) = self
             .channels
             .pool
@@ -197,40 +331,73 @@ impl BlockFactoryWorker {
                 })
                 .collect()
         };
-        block_storage.extend(endo_storage);
+        (endorsements, endo_storage)
+    }
 
-        // gather operations and compute global operations hash
-        let (op_ids, op_storage) = self.channels.pool.get_block_operations(&slot);
-        if op_ids.len() > self.cfg.max_operations_per_block as usize {
-            warn!("Too many operations returned");
-            return;
+    /// Pick the external candidate to sign for `slot` with the candidate selection policy, if one is configured.
+    /// The graph may have changed since the candidate was accepted: it is skipped if its parents are no longer valid.
+    fn select_external_candidate(&self, slot: Slot) -> Option<ExternalBlockCandidate> {
+        let policy = self.candidate_selection_policy.as_ref()?;
+        let candidates = self.channels.consensus.get_external_block_candidates(slot);
+        let candidate = policy.select_candidate(slot, &candidates)?;
+        let parent_candidates = self.channels.consensus.get_parent_candidates();
+        match parent_candidates.with_periods(&candidate.header_template.parents) {
+            Some(parents) if parent_candidates.is_valid_choice(slot, &parents) => {
+                Some(candidate.clone())
+            }
+            _ => {
+                warn!(
+                    "block factory: the parents of the external candidate {} are no longer valid for slot {}, building the block locally",
+                    candidate.id, slot
+                );
+                None
+            }
         }
+    }
 
-        block_storage.extend(op_storage);
+    /// Operations of `storage` whose type is not enabled at `slot` by an active network version
+    fn get_inactive_operations(&self, slot: Slot, storage: &Storage) -> PreHashSet<OperationId> {
+        let slot_timestamp = get_block_slot_timestamp(
+            self.cfg.thread_count,
+            self.cfg.t0,
+            self.cfg.genesis_timestamp,
+            slot,
+        )
+        .expect("could not get block slot timestamp");
+        let ops = storage.read_operations();
+        storage
+            .get_op_refs()
+            .iter()
+            .filter(|op_id| {
+                ops.get(op_id).map_or(false, |op| {
+                    !self
+                        .mip_store
+                        .is_operation_type_active_at(&op.content.op, slot_timestamp)
+                })
+            })
+            .copied()
+            .collect()
+    }
 
-        // create header
-        let current_version = self.mip_store.get_network_version_current();
-        let announced_version = self.mip_store.get_network_version_to_announce();
+    /// Sign a block header and its block, store the block and send it to consensus.
+    fn sign_and_register(
+        &self,
+        header: BlockHeader,
+        operations: Vec<OperationId>,
+        block_producer_keypair: &KeyPair,
+        block_producer_addr: Address,
+        mut block_storage: Storage,
+    ) {
+        let slot = header.slot;
         let header: SecuredHeader = BlockHeader::new_verifiable::<BlockHeaderSerializer, BlockId>(
-            BlockHeader {
-                current_version,
-                announced_version,
-                slot,
-                parents: parents.into_iter().map(|(id, _period)| id).collect(),
-                operation_merkle_root: compute_operations_hash(&op_ids, &self.op_id_serializer),
-                endorsements,
-                denunciations: self.channels.pool.get_block_denunciations(&slot),
-            },
+            header,
             BlockHeaderSerializer::new(), // TODO reuse self.block_header_serializer
             block_producer_keypair,
             self.cfg.chain_id,
         )
         .expect("error while producing block header");
         // create block
-        let block_ = Block {
-            header,
-            operations: op_ids.into_iter().collect(),
-        };
+        let block_ = Block { header, operations };
 
         let block = Block::new_verifiable(
             block_,
@@ -252,7 +419,7 @@ impl BlockFactoryWorker {
         // send full block to consensus
         self.channels
             .consensus
-            .register_block(block_id, slot, block_storage, true);
+            .register_block(block_id, slot, block_storage, BlockOrigin::Local);
     }
 
     /// main run loop of the block creator thread
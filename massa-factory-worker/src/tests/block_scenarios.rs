@@ -1,7 +1,13 @@
 use std::{str::FromStr, sync::Arc};
 
 use super::BlockTestFactory;
-use massa_consensus_exports::MockConsensusController;
+use massa_consensus_exports::{
+    block_provenance::BlockOrigin,
+    error::ConsensusError,
+    parent_candidates::ParentCandidates,
+    submission::{SubmissionOutcome, SubmissionTicket},
+    MockConsensusController,
+};
 use massa_hash::Hash;
 use massa_models::config::CHAINID;
 use massa_models::{
@@ -41,20 +47,124 @@ fn basic_creation() {
     let pair2 = pair.clone();
     let mut consensus_controller = Box::new(MockConsensusController::new());
     consensus_controller
-        .expect_get_best_parents()
+        .expect_get_parent_candidates()
+        .times(1)
+        .return_once(move || ParentCandidates {
+            best_parents: parents,
+            candidates: vec![Vec::new(); THREAD_COUNT as usize],
+            generation: 0,
+        });
+    consensus_controller
+        .expect_if_unchanged()
+        .times(1)
+        .return_once(|_| Ok(()));
+    consensus_controller
+        .expect_register_block()
+        .times(1)
+        .return_once(move |block_id, _, storage, origin| {
+            assert_eq!(origin, BlockOrigin::Local);
+            let block = storage.get_block_refs();
+            assert_eq!(block.len(), 1);
+            let (lock, cvar) = &*pair2;
+            let mut started = lock.lock();
+            *started = true;
+            cvar.notify_one();
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
+        });
+    let mut selector_controller = Box::new(MockSelectorController::new());
+    selector_controller
+        .expect_get_producer()
         .times(1)
-        .return_once(move || parents);
+        .return_once(move |_| Ok(staking_address));
+    let mut pool_controller = Box::new(MockPoolController::new());
+    pool_controller
+        .expect_get_block_denunciations()
+        .returning(|slot| {
+            assert_eq!(*slot, Slot::new(1, 0));
+            vec![]
+        });
+    pool_controller
+        .expect_get_block_operations()
+        .returning(|slot| {
+            assert_eq!(*slot, Slot::new(1, 0));
+            (vec![], Storage::create_root())
+        });
+    pool_controller
+        .expect_get_block_endorsements()
+        .returning(|_, slot| {
+            assert_eq!(*slot, Slot::new(1, 0));
+            (vec![], Storage::create_root())
+        });
+    let mut test_factory = BlockTestFactory::new(
+        &keypair,
+        storage,
+        consensus_controller,
+        selector_controller,
+        pool_controller,
+    );
+    let (ref lock, ref cvar) = *pair;
+    let mut started = lock.lock();
+    if !*started {
+        cvar.wait(&mut started);
+    }
+    test_factory.stop();
+}
+
+/// Chooses the parents again when the graph changes during the choice.
+#[test]
+#[serial]
+fn creation_after_graph_change() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+    let keypair = KeyPair::generate(0).unwrap();
+    let storage = Storage::create_root();
+    let staking_address = Address::from_public_key(&keypair.get_public_key());
+    let parent = BlockId::generate_from_hash(Hash::compute_from("test".as_bytes()));
+    let mut parents = Vec::new();
+    for i in 0..THREAD_COUNT as u64 {
+        parents.push((parent, i));
+    }
+    let pair = Arc::new((Mutex::new(false), Condvar::new()));
+    let pair2 = pair.clone();
+    let mut consensus_controller = Box::new(MockConsensusController::new());
+    consensus_controller
+        .expect_get_parent_candidates()
+        .times(2)
+        .returning(move || ParentCandidates {
+            best_parents: parents.clone(),
+            candidates: vec![Vec::new(); THREAD_COUNT as usize],
+            generation: 0,
+        });
+    // the graph changes once after the first choice of parents
+    let mut changed = false;
+    consensus_controller
+        .expect_if_unchanged()
+        .times(2)
+        .returning(move |generation| {
+            if changed {
+                return Ok(());
+            }
+            changed = true;
+            Err(ConsensusError::GraphChanged {
+                read: generation,
+                current: generation + 1,
+            })
+        });
     consensus_controller
         .expect_register_block()
         .times(1)
-        .return_once(move |_, _, storage, created| {
-            assert!(created);
+        .return_once(move |block_id, _, storage, origin| {
+            assert_eq!(origin, BlockOrigin::Local);
             let block = storage.get_block_refs();
             assert_eq!(block.len(), 1);
             let (lock, cvar) = &*pair2;
             let mut started = lock.lock();
             *started = true;
             cvar.notify_one();
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     let mut selector_controller = Box::new(MockSelectorController::new());
     selector_controller
@@ -156,14 +266,22 @@ fn basic_creation_with_operation() {
     let pair2 = pair.clone();
     let mut consensus_controller = Box::new(MockConsensusController::new());
     consensus_controller
-        .expect_get_best_parents()
+        .expect_get_parent_candidates()
+        .times(1)
+        .return_once(move || ParentCandidates {
+            best_parents: parents,
+            candidates: vec![Vec::new(); THREAD_COUNT as usize],
+            generation: 0,
+        });
+    consensus_controller
+        .expect_if_unchanged()
         .times(1)
-        .return_once(move || parents);
+        .return_once(|_| Ok(()));
     consensus_controller
         .expect_register_block()
         .times(1)
-        .return_once(move |_, _, storage, created| {
-            assert!(created);
+        .return_once(move |block_id, _, storage, origin| {
+            assert_eq!(origin, BlockOrigin::Local);
             let block = storage.get_block_refs();
             assert_eq!(block.len(), 1);
             let ops = storage.get_op_refs();
@@ -172,6 +290,7 @@ fn basic_creation_with_operation() {
             let mut started = lock.lock();
             *started = true;
             cvar.notify_one();
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     let mut test_factory = BlockTestFactory::new(
         &keypair,
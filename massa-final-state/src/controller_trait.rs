@@ -7,7 +7,7 @@ use massa_models::{operation::OperationId, slot::Slot};
 use massa_pos_exports::PoSFinalState;
 use massa_versioning::versioning::MipStore;
 
-use crate::{FinalStateError, StateChanges};
+use crate::{FinalStateError, StateChanges, StateHashComposition};
 
 /// Trait for final state controller.
 #[cfg_attr(feature = "test-exports", mockall::automock)]
@@ -19,6 +19,10 @@ pub trait FinalStateController: Send + Sync {
     /// Get the slot at the end of which the final state is attached
     fn get_slot(&self) -> Slot;
 
+    /// Break the final state hash down into the hashes of the components of the final state.
+    /// This scans the whole final state and is meant for debugging hash divergences between nodes.
+    fn get_state_hash_composition(&self) -> StateHashComposition;
+
     /// Gets the hash of the execution trail
     fn get_execution_trail_hash(&self) -> Hash;
 
@@ -19,6 +19,8 @@ pub enum FinalStateError {
     PosError(String),
     /// Snapshot error: {0}
     SnapshotError(String),
+    /// deferred credits recovery error: {0}
+    DeferredCreditsRecoveryError(String),
     /// ExtendFromDbError
     ExtendFromDbError(#[from] ExtendFromDbError),
     /// IsConsistentWithShutdownPeriodError
@@ -6,25 +6,35 @@
 //! and need to be bootstrapped by nodes joining the network.
 
 use crate::controller_trait::FinalStateController;
-use crate::{config::FinalStateConfig, error::FinalStateError, state_changes::StateChanges};
+use crate::{
+    config::FinalStateConfig, error::FinalStateError, state_changes::StateChanges,
+    StateHashComponent, StateHashComposition,
+};
 
 use anyhow::{anyhow, Result as AnyResult};
 use massa_async_pool::AsyncPool;
 use massa_db_exports::{
     DBBatch, MassaIteratorMode, ShareableMassaDBController, ASYNC_POOL_PREFIX,
     CYCLE_HISTORY_PREFIX, DEFERRED_CREDITS_PREFIX, EXECUTED_DENUNCIATIONS_PREFIX,
-    EXECUTED_OPS_PREFIX, LEDGER_PREFIX, MIP_STORE_PREFIX, STATE_CF,
+    EXECUTED_OPS_PREFIX, LEDGER_PREFIX, MIP_STORE_PREFIX, PAYOUT_ADDRESSES_PREFIX, STATE_CF,
+    STATE_HASH_INITIAL_BYTES,
 };
 use massa_db_exports::{EXECUTION_TRAIL_HASH_PREFIX, MIP_STORE_STATS_PREFIX, VERSIONING_CF};
 use massa_executed_ops::ExecutedDenunciations;
 use massa_executed_ops::ExecutedOps;
-use massa_hash::Hash;
+use massa_hash::{Hash, HashXof};
 use massa_ledger_exports::LedgerController;
 use massa_ledger_exports::SetOrKeep;
 use massa_models::operation::OperationId;
 use massa_models::slot::Slot;
-use massa_pos_exports::{PoSFinalState, SelectorController};
-use massa_versioning::versioning::MipStore;
+use massa_pos_exports::{
+    DeferredCredits, DeferredCreditsRecovery, DeferredCreditsSerializer, DelegationActivation,
+    PoSFinalState, SelectorController,
+};
+use massa_serialization::Serializer;
+use massa_time::MassaTime;
+use massa_versioning::versioning::{MipComponent, MipStore};
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
 #[cfg(feature = "bootstrap_server")]
@@ -61,6 +71,38 @@ pub struct FinalState {
     pub db: ShareableMassaDBController,
 }
 
+/// Activation of the delegated draws by the `Delegation` versioning component
+struct MipDelegationActivation {
+    mip_store: MipStore,
+    thread_count: u8,
+    periods_per_cycle: u64,
+    t0: MassaTime,
+    genesis_timestamp: MassaTime,
+}
+
+impl DelegationActivation for MipDelegationActivation {
+    /// The delegations of a lookback cycle weight the draws if the component is active at its last slot,
+    /// once the registry of the cycle is complete
+    fn is_active_at_cycle(&self, lookback_cycle: u64) -> bool {
+        let Ok(last_slot) =
+            Slot::new_last_of_cycle(lookback_cycle, self.periods_per_cycle, self.thread_count)
+        else {
+            return false;
+        };
+        let Ok(timestamp) = get_block_slot_timestamp(
+            self.thread_count,
+            self.t0,
+            self.genesis_timestamp,
+            last_slot,
+        ) else {
+            return false;
+        };
+        self.mip_store
+            .get_latest_component_version_at(&MipComponent::Delegation, timestamp)
+            > 0
+    }
+}
+
 impl FinalState {
     /// Initializes a new `FinalState`
     ///
@@ -83,7 +125,7 @@ impl FinalState {
             .map_err(|_| FinalStateError::InvalidSlot(String::from("Could not get slot in db")))?;
 
         // create the pos state
-        let pos_state = PoSFinalState::new(
+        let mut pos_state = PoSFinalState::new(
             config.pos_config.clone(),
             &config.initial_seed_string,
             &config.initial_rolls_path,
@@ -91,6 +133,13 @@ impl FinalState {
             db.clone(),
         )
         .map_err(|err| FinalStateError::PosError(format!("PoS final state init error: {}", err)))?;
+        pos_state.delegation_activation = Some(Arc::new(MipDelegationActivation {
+            mip_store: mip_store.clone(),
+            thread_count: config.thread_count,
+            periods_per_cycle: config.periods_per_cycle,
+            t0: config.t0,
+            genesis_timestamp: config.genesis_timestamp,
+        }));
 
         // attach at the output of the latest initial final slot, that is the last genesis slot
         let slot = if reset_final_state {
@@ -553,6 +602,7 @@ impl FinalState {
             }
         }
 
+        let last_final_slot = db.get_change_id()?;
         for (serialized_key, serialized_value) in db.iterator_cf(STATE_CF, MassaIteratorMode::Start)
         {
             #[allow(clippy::if_same_then_else)]
@@ -571,16 +621,31 @@ impl FinalState {
                     ));
                 }
             } else if serialized_key.starts_with(DEFERRED_CREDITS_PREFIX.as_bytes()) {
+                if !self.pos_state.is_deferred_credits_key_value_valid(
+                    &serialized_key,
+                    &serialized_value,
+                    last_final_slot,
+                ) {
+                    warn!(
+                        "Wrong key/value for DEFERRED_CREDITS PREFIX serialized_key: {:?}, serialized_value: {:?}",
+                        serialized_key, serialized_value
+                    );
+                    return Err(anyhow!(
+                        "Wrong key/value for DEFERRED_CREDITS PREFIX serialized_key: {:?}, serialized_value: {:?}",
+                        serialized_key, serialized_value
+                    ));
+                }
+            } else if serialized_key.starts_with(PAYOUT_ADDRESSES_PREFIX.as_bytes()) {
                 if !self
                     .pos_state
-                    .is_deferred_credits_key_value_valid(&serialized_key, &serialized_value)
+                    .is_payout_address_key_value_valid(&serialized_key, &serialized_value)
                 {
                     warn!(
-                        "Wrong key/value for DEFERRED_CREDITS PREFIX serialized_key: {:?}, serialized_value: {:?}",
+                        "Wrong key/value for PAYOUT_ADDRESSES PREFIX serialized_key: {:?}, serialized_value: {:?}",
                         serialized_key, serialized_value
                     );
                     return Err(anyhow!(
-                        "Wrong key/value for DEFERRED_CREDITS PREFIX serialized_key: {:?}, serialized_value: {:?}",
+                        "Wrong key/value for PAYOUT_ADDRESSES PREFIX serialized_key: {:?}, serialized_value: {:?}",
                         serialized_key, serialized_value
                     ));
                 }
@@ -785,6 +850,61 @@ impl FinalState {
 
         Ok(final_state)
     }
+
+    /// Write back the deferred credits of a previous state after the final state was reset
+    ///
+    /// The credits are read with `massa_pos_exports::read_deferred_credits` before the reset.
+    /// Credits that conflict with the current state, or whose slot is already final, are not written
+    /// and are listed in the returned report. Once written, the state hash is checked to have changed
+    /// by exactly the hash of the restored credits.
+    pub fn recover_deferred_credits(
+        &mut self,
+        recovered: DeferredCredits,
+    ) -> Result<DeferredCreditsRecovery, FinalStateError> {
+        let slot =
+            self.db.read().get_change_id().map_err(|_| {
+                FinalStateError::InvalidSlot(String::from("Could not get slot in db"))
+            })?;
+
+        let mut batch = DBBatch::new();
+        let recovery = self
+            .pos_state
+            .recover_deferred_credits(recovered, slot, &mut batch);
+        if batch.is_empty() {
+            return Ok(recovery);
+        }
+
+        // hash of the restored credits, computed the way they are streamed during bootstrap
+        let mut serialized_credits = Vec::new();
+        DeferredCreditsSerializer::new()
+            .serialize(&recovery.restored, &mut serialized_credits)
+            .map_err(|err| FinalStateError::DeferredCreditsRecoveryError(err.to_string()))?;
+        let restored_hash = self
+            .pos_state
+            .deferred_credits_deserializer
+            .compute_hash_streaming(serialized_credits.as_slice())
+            .map_err(|err| FinalStateError::DeferredCreditsRecoveryError(err.to_string()))?;
+
+        let previous_hash = self.db.read().get_xof_db_hash();
+        self.db
+            .write()
+            .write_batch(batch, Default::default(), Some(slot));
+        let new_hash = self.db.read().get_xof_db_hash();
+        if new_hash != previous_hash ^ restored_hash {
+            return Err(FinalStateError::DeferredCreditsRecoveryError(format!(
+                "final state hash {} after the recovery does not match the hash {} of the state before the recovery and of the {} restored credits",
+                new_hash,
+                previous_hash ^ restored_hash,
+                recovery.restored_count()
+            )));
+        }
+        info!(
+            "final_state hash at slot {} after the deferred credits recovery: {}",
+            slot, new_hash
+        );
+
+        Ok(recovery)
+    }
 }
 
 impl FinalStateController for FinalState {
@@ -825,6 +945,61 @@ impl FinalStateController for FinalState {
             .expect("Critical error: Final state has no slot attached")
     }
 
+    fn get_state_hash_composition(&self) -> StateHashComposition {
+        let prefixes = [
+            LEDGER_PREFIX,
+            ASYNC_POOL_PREFIX,
+            CYCLE_HISTORY_PREFIX,
+            DEFERRED_CREDITS_PREFIX,
+            PAYOUT_ADDRESSES_PREFIX,
+            EXECUTED_OPS_PREFIX,
+            EXECUTED_DENUNCIATIONS_PREFIX,
+            MIP_STORE_PREFIX,
+            MIP_STORE_STATS_PREFIX,
+            EXECUTION_TRAIL_HASH_PREFIX,
+        ];
+
+        // hold the lock during the whole scan so that the components match the final state hash
+        let db = self.db.read();
+
+        // one (entry count, xor hash) per prefix, and a last one for the entries matching no prefix
+        let mut component_hashes =
+            vec![(0u64, HashXof(*STATE_HASH_INITIAL_BYTES)); prefixes.len() + 1];
+        for (serialized_key, serialized_value) in db.iterator_cf(STATE_CF, MassaIteratorMode::Start)
+        {
+            let index = prefixes
+                .iter()
+                .position(|prefix| serialized_key.starts_with(prefix.as_bytes()))
+                .unwrap_or(prefixes.len());
+            let (entry_count, hash) = &mut component_hashes[index];
+            *entry_count += 1;
+            *hash ^= HashXof::compute_from_tuple(&[
+                serialized_key.as_slice(),
+                serialized_value.as_slice(),
+            ]);
+        }
+
+        let components = prefixes
+            .iter()
+            .map(|prefix| prefix.trim_end_matches('/'))
+            .chain(std::iter::once("other"))
+            .zip(component_hashes)
+            .map(|(name, (entry_count, hash))| StateHashComponent {
+                name: name.to_string(),
+                entry_count,
+                hash: Hash::compute_from(hash.to_bytes()),
+            })
+            .collect();
+
+        StateHashComposition {
+            slot: db
+                .get_change_id()
+                .expect("Critical error: Final state has no slot attached"),
+            final_state_hash: Hash::compute_from(db.get_xof_db_hash().to_bytes()),
+            components,
+        }
+    }
+
     fn init_execution_trail_hash_to_batch(&mut self, batch: &mut DBBatch) {
         batch.insert(
             EXECUTION_TRAIL_HASH_PREFIX.as_bytes().to_vec(),
@@ -931,7 +1106,7 @@ mod test {
     use tempfile::tempdir;
 
     use massa_async_pool::{AsyncMessage, AsyncPoolChanges, AsyncPoolConfig};
-    use massa_db_exports::{MassaDBConfig, MassaDBController, STATE_HASH_INITIAL_BYTES};
+    use massa_db_exports::{MassaDBConfig, MassaDBController};
     use massa_db_worker::MassaDB;
     use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
     use massa_hash::Hash;
@@ -1170,7 +1345,7 @@ mod test {
         // TODO: more checks
         selector_controller
             .expect_feed_cycle()
-            .returning(|_, _, _| Ok(()));
+            .returning(|_, _, _, _| Ok(()));
         selector_controller
             .expect_wait_for_draws()
             .returning(|_| Ok(1));
@@ -1230,7 +1405,7 @@ mod test {
         selector_controller
             .expect_feed_cycle()
             .times(4)
-            .returning(|_, _, _| Ok(()));
+            .returning(|_, _, _, _| Ok(()));
         selector_controller
             .expect_wait_for_draws()
             .returning(|_| Ok(1));
@@ -1268,6 +1443,39 @@ mod test {
         assert!(fstate2.is_db_valid());
     }
 
+    #[test]
+    fn test_state_hash_composition() {
+        let mut fstate = get_final_state();
+
+        // empty final state: every component has the initial hash
+        let composition = fstate.get_state_hash_composition();
+        assert_eq!(composition.final_state_hash, fstate.get_fingerprint());
+        assert!(composition.components.iter().all(|component| {
+            component.entry_count == 0
+                && component.hash == Hash::compute_from(STATE_HASH_INITIAL_BYTES)
+        }));
+
+        // the execution trail hash is the only entry, so it is the only contributor
+        let mut batch = DBBatch::new();
+        fstate.init_execution_trail_hash_to_batch(&mut batch);
+        fstate
+            .db
+            .write()
+            .write_batch(batch, Default::default(), None);
+
+        let composition = fstate.get_state_hash_composition();
+        assert_eq!(composition.final_state_hash, fstate.get_fingerprint());
+        for component in composition.components {
+            if component.name == "execution_trail_hash" {
+                assert_eq!(component.entry_count, 1);
+                assert_eq!(component.hash, composition.final_state_hash);
+            } else {
+                assert_eq!(component.entry_count, 0);
+                assert_eq!(component.hash, Hash::compute_from(STATE_HASH_INITIAL_BYTES));
+            }
+        }
+    }
+
     #[test]
     fn test_final_state_reset() {
         // Create a final state
@@ -1320,4 +1528,101 @@ mod test {
             Hash::compute_from(STATE_HASH_INITIAL_BYTES)
         );
     }
+
+    #[test]
+    fn test_deferred_credits_recovery() {
+        // Write deferred credits, reset the final state and write them back:
+        // * a credit at a slot before the state slot is expired
+        // * a credit with another amount in the reset state is a conflict
+        // * the other credits are restored
+
+        let mut fstate = get_final_state();
+        let addr_1 =
+            Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+        let addr_2 =
+            Address::from_str("AU12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap();
+        let expired_slot = Slot::new(0, 0);
+        let future_slot = Slot::new(10, 3);
+
+        let mut batch = DBBatch::new();
+        for (slot, address, amount) in [
+            (expired_slot, addr_1, "1"),
+            (future_slot, addr_1, "20"),
+            (future_slot, addr_2, "300"),
+        ] {
+            fstate.pos_state.put_deferred_credits_entry(
+                &slot,
+                &address,
+                &Amount::from_str(amount).unwrap(),
+                &mut batch,
+            );
+        }
+        fstate
+            .db
+            .write()
+            .write_batch(batch, Default::default(), None);
+
+        let recovered =
+            massa_pos_exports::read_deferred_credits(&fstate.db, &fstate.config.pos_config)
+                .unwrap();
+        assert_eq!(
+            recovered.get_address_credits_for_slot(&addr_2, &future_slot),
+            Some(Amount::from_str("300").unwrap())
+        );
+
+        fstate.reset();
+        assert!(fstate.pos_state.get_deferred_credits().is_empty());
+
+        // the reset state already has another amount for the credit of addr_2
+        let mut batch = DBBatch::new();
+        fstate.pos_state.put_deferred_credits_entry(
+            &future_slot,
+            &addr_2,
+            &Amount::from_str("7").unwrap(),
+            &mut batch,
+        );
+        fstate
+            .db
+            .write()
+            .write_batch(batch, Default::default(), None);
+
+        let recovery = fstate.recover_deferred_credits(recovered).unwrap();
+        assert_eq!(recovery.restored_count(), 1);
+        assert_eq!(recovery.restored_amount(), Amount::from_str("20").unwrap());
+        assert_eq!(recovery.already_present, 0);
+        assert_eq!(
+            recovery
+                .expired
+                .get_address_credits_for_slot(&addr_1, &expired_slot),
+            Some(Amount::from_str("1").unwrap())
+        );
+        assert_eq!(
+            recovery.conflicts,
+            vec![massa_pos_exports::DeferredCreditConflict {
+                slot: future_slot,
+                address: addr_2,
+                recovered: Amount::from_str("300").unwrap(),
+                current: Amount::from_str("7").unwrap(),
+            }]
+        );
+
+        assert_eq!(
+            fstate
+                .pos_state
+                .get_address_credits_for_slot(&addr_1, &future_slot),
+            Some(Amount::from_str("20").unwrap())
+        );
+        assert_eq!(
+            fstate
+                .pos_state
+                .get_address_credits_for_slot(&addr_2, &future_slot),
+            Some(Amount::from_str("7").unwrap())
+        );
+        assert_eq!(
+            fstate
+                .pos_state
+                .get_address_credits_for_slot(&addr_1, &expired_slot),
+            None
+        );
+    }
 }
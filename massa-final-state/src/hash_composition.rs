@@ -0,0 +1,32 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Breakdown of the final state hash into the contributions of the components of the final state
+
+use massa_hash::Hash;
+use massa_models::slot::Slot;
+use serde::{Deserialize, Serialize};
+
+/// Contribution of a component of the final state to the final state hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateHashComponent {
+    /// name of the component (its database key prefix, without the trailing slash)
+    pub name: String,
+    /// number of database entries of the component
+    pub entry_count: u64,
+    /// fingerprint of the XOR of the hashes of the entries of the component,
+    /// computed in the same way as the final state fingerprint
+    pub hash: Hash,
+}
+
+/// Component hashes feeding the final state hash at a final slot.
+/// The XOR of the component hashes (before fingerprinting) is the final state hash,
+/// so two nodes with different final state hashes differ in at least one component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateHashComposition {
+    /// final slot the composition was computed at
+    pub slot: Slot,
+    /// fingerprint of the final state
+    pub final_state_hash: Hash,
+    /// contribution of each component, entries matching no known component being reported as `other`
+    pub components: Vec<StateHashComponent>,
+}
@@ -91,6 +91,7 @@ mod config;
 mod controller_trait;
 mod error;
 mod final_state;
+mod hash_composition;
 mod mapping_grpc;
 mod state_changes;
 
@@ -98,6 +99,7 @@ pub use config::FinalStateConfig;
 pub use controller_trait::FinalStateController;
 pub use error::FinalStateError;
 pub use final_state::FinalState;
+pub use hash_composition::{StateHashComponent, StateHashComposition};
 use num as _;
 pub use state_changes::{StateChanges, StateChangesDeserializer, StateChangesSerializer};
 
@@ -69,6 +69,12 @@ impl StateChangesSerializer {
             execution_trail_hash_change_serializer: SetOrKeepSerializer::new(HashSerializer::new()),
         }
     }
+
+    /// Serialize the PoS changes in the format of the given `MipComponent::PayoutAddress` version
+    pub fn with_pos_changes_version(mut self, version: u32) -> Self {
+        self.pos_changes_serializer = PoSChangesSerializer::with_version(version);
+        self
+    }
 }
 
 impl Serializer<StateChanges> for StateChangesSerializer {
@@ -153,6 +159,12 @@ impl StateChangesDeserializer {
             ),
         }
     }
+
+    /// Deserialize the PoS changes in the format of the given `MipComponent::PayoutAddress` version
+    pub fn with_pos_changes_version(mut self, version: u32) -> Self {
+        self.pos_changes_deserializer = self.pos_changes_deserializer.with_version(version);
+        self
+    }
 }
 
 impl Deserializer<StateChanges> for StateChangesDeserializer {
@@ -6,6 +6,7 @@ use crate::{
     FinalState, FinalStateConfig, StateChanges,
 };
 use massa_async_pool::{AsyncMessage, AsyncPoolChanges, AsyncPoolConfig};
+use massa_channel::event_bus::MassaEventBus;
 use massa_db_exports::{DBBatch, MassaDBConfig, MassaDBController};
 use massa_db_worker::MassaDB;
 use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
@@ -102,8 +103,11 @@ fn create_final_state(temp_dir: &TempDir, reset_final_state: bool) -> Arc<RwLock
     };
 
     // start proof-of-stake selectors
-    let (mut _selector_manager, selector_controller) = start_selector_worker(selector_local_config)
-        .expect("could not start server selector controller");
+    let (mut _selector_manager, selector_controller) = start_selector_worker(
+        selector_local_config,
+        MassaEventBus::new("test_node_event".to_string()),
+    )
+    .expect("could not start server selector controller");
 
     // MIP store
     let mip_store = MipStore::try_from((
@@ -465,8 +465,8 @@ pub(crate) fn get_next_block_best_parents(
 ) -> Result<grpc_api::GetNextBlockBestParentsResponse, GrpcError> {
     let block_parents = grpc
         .consensus_controller
-        .get_best_parents()
-        .into_iter()
+        .get_best_parents_snapshot()
+        .iter()
         .map(|p| grpc_model::BlockParent {
             block_id: p.0.to_string(),
             period: p.1,
@@ -8,6 +8,7 @@ use parking_lot::RwLock;
 use std::convert::Infallible;
 use std::path::Path;
 use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
 
 use crate::config::{GrpcConfig, ServiceName};
 use crate::error::GrpcError;
@@ -34,10 +35,14 @@ use tonic::codegen::CompressionEncoding;
 use tonic::transport::NamedService;
 use tonic::transport::{Certificate, Identity, ServerTlsConfig};
 use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
 use tonic_web::GrpcWebLayer;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::{info, warn};
 
+/// interval between two refreshes of the health status
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 /// gRPC PRIVATE API content
 #[derive(Clone)]
 pub struct MassaPrivateGrpc {
@@ -71,6 +76,9 @@ pub struct MassaPrivateGrpc {
 impl MassaPrivateGrpc {
     /// Start the gRPC PRIVATE API
     pub async fn serve(self, config: &GrpcConfig) -> Result<StopHandle, GrpcError> {
+        let consensus_controller = config
+            .enable_health
+            .then(|| self.consensus_controller.clone());
         let mut service = PrivateServiceServer::new(self)
             .max_decoding_message_size(config.max_decoding_message_size)
             .max_encoding_message_size(config.max_encoding_message_size);
@@ -87,7 +95,7 @@ impl MassaPrivateGrpc {
             };
         }
 
-        serve(service, config).await
+        serve(service, config, consensus_controller).await
     }
 }
 
@@ -127,6 +135,9 @@ pub struct MassaPublicGrpc {
 impl MassaPublicGrpc {
     /// Start the gRPC PUBLIC API
     pub async fn serve(self, config: &GrpcConfig) -> Result<StopHandle, GrpcError> {
+        let consensus_controller = config
+            .enable_health
+            .then(|| self.consensus_controller.clone());
         let mut service = PublicServiceServer::new(self)
             .max_decoding_message_size(config.max_decoding_message_size)
             .max_encoding_message_size(config.max_encoding_message_size);
@@ -142,7 +153,7 @@ impl MassaPublicGrpc {
                 service = service.send_compressed(CompressionEncoding::Gzip);
             };
         }
-        serve(service, config).await
+        serve(service, config, consensus_controller).await
     }
 }
 
@@ -162,16 +173,32 @@ impl StopHandle {
     }
 }
 
-/// Massa service health check implementation
-async fn massa_service_status(mut reporter: HealthReporter) {
-    //TODO add a complete health check based on Massa modules health
-    reporter
-        .set_serving::<PublicServiceServer<MassaPublicGrpc>>()
-        .await;
+/// Massa service health check implementation.
+/// The served service, and the server as a whole (empty service name), are reported as serving
+/// only while consensus is ready, see `ConsensusController::is_ready`.
+async fn massa_service_status<S: NamedService>(
+    mut reporter: HealthReporter,
+    consensus_controller: Box<dyn ConsensusController>,
+) {
+    let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let status = if consensus_controller.is_ready() {
+            ServingStatus::Serving
+        } else {
+            ServingStatus::NotServing
+        };
+        reporter.set_service_status(S::NAME, status).await;
+        reporter.set_service_status("", status).await;
+    }
 }
 
 // Configure and start the gRPC API with the given service
-async fn serve<S>(service: S, config: &GrpcConfig) -> Result<StopHandle, GrpcError>
+async fn serve<S>(
+    service: S,
+    config: &GrpcConfig,
+    consensus_controller: Option<Box<dyn ConsensusController>>,
+) -> Result<StopHandle, GrpcError>
 where
     S: Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible>
         + NamedService
@@ -248,12 +275,12 @@ where
         None
     };
 
-    let health_service_opt = if config.enable_health {
-        let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
-        health_reporter
-            .set_serving::<PublicServiceServer<MassaPublicGrpc>>()
-            .await;
-        tokio::spawn(massa_service_status(health_reporter.clone()));
+    let health_service_opt = if let Some(consensus_controller) = consensus_controller {
+        let (health_reporter, health_service) = tonic_health::server::health_reporter();
+        tokio::spawn(massa_service_status::<S>(
+            health_reporter,
+            consensus_controller,
+        ));
         info!("gRPC health service enabled");
         Some(health_service)
     } else {
@@ -3,6 +3,7 @@
 use crate::error::{match_for_io_error, GrpcError};
 use crate::server::MassaPublicGrpc;
 use futures_util::StreamExt;
+use massa_consensus_exports::block_provenance::BlockOrigin;
 use massa_models::block::{BlockDeserializer, BlockDeserializerArgs, SecureShareBlock};
 use massa_models::error::ModelsError;
 use massa_models::secure_share::SecureShareDeserializer;
@@ -117,7 +118,7 @@ pub(crate) async fn send_blocks(
                                 block_id,
                                 slot,
                                 block_storage.clone(),
-                                false,
+                                BlockOrigin::Api,
                             );
 
                             // Propagate the block(header) to the network
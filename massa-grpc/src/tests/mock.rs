@@ -3,6 +3,7 @@ use std::net::SocketAddr;
 
 use crate::config::{GrpcConfig, ServiceName};
 use crate::server::MassaPublicGrpc;
+use massa_channel::event_bus::MassaEventBus;
 use massa_consensus_exports::{ConsensusBroadcasts, MockConsensusController};
 use massa_execution_exports::{ExecutionChannels, MockExecutionController};
 use massa_models::{
@@ -49,7 +50,8 @@ pub(crate) fn grpc_public_service(addr: &SocketAddr) -> MassaPublicGrpc {
         enabled: true,
         accept_http1: true,
         enable_cors: true,
-        enable_health: true,
+        // the health service polls the consensus controller, which is mocked
+        enable_health: false,
         enable_reflection: true,
         enable_tls: false,
         enable_mtls: false,
@@ -128,6 +130,7 @@ pub(crate) fn grpc_public_service(addr: &SocketAddr) -> MassaPublicGrpc {
         execution_controller: execution_ctrl,
         execution_channels: ExecutionChannels {
             slot_execution_output_sender,
+            event_bus: MassaEventBus::new("test_node_event".to_string()),
         },
         pool_broadcasts: PoolBroadcasts {
             endorsement_sender,
@@ -35,6 +35,7 @@ use massa_time::MassaTime;
 use std::collections::{BTreeMap, VecDeque};
 use std::net::SocketAddr;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
 #[tokio::test]
@@ -87,6 +88,7 @@ async fn get_transactions_throughput() {
         final_executed_operations_count: 0,
         active_cursor: Slot::new(0, 0),
         final_cursor: Slot::new(0, 0),
+        pos_stats: Default::default(),
     });
 
     public_server.execution_controller = exec_ctrl;
@@ -355,6 +357,7 @@ async fn execute_read_only_call() {
                     block_info: None,
                     state_changes: massa_final_state::StateChanges::default(),
                     events: EventStore::default(),
+                    scheduled_credits: Default::default(),
                 },
                 gas_cost: 100,
                 call_result: "toto".as_bytes().to_vec(),
@@ -546,18 +549,22 @@ async fn get_next_block_best_parents() {
     let config = public_server.grpc_config.clone();
 
     let mut consensus_ctrl = Box::new(MockConsensusController::new());
-    consensus_ctrl.expect_get_best_parents().returning(|| {
-        vec![
-            (
-                BlockId::from_str("B1q4CBcuYo8YANEV34W4JRWVHrzcYns19VJfyAB7jT4qfitAnMC").unwrap(),
-                1,
-            ),
-            (
-                BlockId::from_str("B12VVLWiMVjBLW7eoZqiv5eVWmqEQokZL7pAjFCaHHuyUnSo9LPb").unwrap(),
-                2,
-            ),
-        ]
-    });
+    consensus_ctrl
+        .expect_get_best_parents_snapshot()
+        .returning(|| {
+            Arc::new(vec![
+                (
+                    BlockId::from_str("B1q4CBcuYo8YANEV34W4JRWVHrzcYns19VJfyAB7jT4qfitAnMC")
+                        .unwrap(),
+                    1,
+                ),
+                (
+                    BlockId::from_str("B12VVLWiMVjBLW7eoZqiv5eVWmqEQokZL7pAjFCaHHuyUnSo9LPb")
+                        .unwrap(),
+                    2,
+                ),
+            ])
+        });
 
     public_server.consensus_controller = consensus_ctrl;
 
@@ -59,6 +59,7 @@ async fn transactions_throughput_stream() {
                     period: 3,
                     thread: 15,
                 },
+                pos_stats: Default::default(),
             }
         });
         exec_ctrl
@@ -86,6 +87,7 @@ async fn transactions_throughput_stream() {
                     period: 3,
                     thread: 15,
                 },
+                pos_stats: Default::default(),
             }
         });
         exec_ctrl
@@ -1067,6 +1069,7 @@ async fn new_slot_execution_outputs() {
         block_info: None,
         state_changes: massa_final_state::StateChanges::default(),
         events: Default::default(),
+        scheduled_credits: Default::default(),
     };
 
     let (tx_request, rx) = tokio::sync::mpsc::channel(10);
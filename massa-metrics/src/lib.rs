@@ -133,6 +133,8 @@ pub struct MassaMetrics {
 
     /// block slot delay
     block_slot_delay: Histogram,
+    /// time spent by consensus building the graph export snapshot, in seconds
+    consensus_graph_snapshot_time: Histogram,
 
     /// active in connections peer
     active_in_connections: IntGauge,
@@ -406,6 +408,15 @@ impl MassaMetrics {
         )
         .unwrap();
 
+        let consensus_graph_snapshot_time = Histogram::with_opts(
+            prometheus::HistogramOpts::new(
+                "consensus_graph_snapshot_time",
+                "time spent building the consensus graph export snapshot",
+            )
+            .buckets(vec![0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5]),
+        )
+        .unwrap();
+
         let mut stopper = MetricsStopper::default();
 
         if enabled {
@@ -458,6 +469,7 @@ impl MassaMetrics {
                 let _ = prometheus::register(Box::new(current_time_period.clone()));
                 let _ = prometheus::register(Box::new(current_time_thread.clone()));
                 let _ = prometheus::register(Box::new(block_slot_delay.clone()));
+                let _ = prometheus::register(Box::new(consensus_graph_snapshot_time.clone()));
 
                 stopper = server::bind_metrics(addr);
             }
@@ -490,6 +502,7 @@ impl MassaMetrics {
                 peernet_total_bytes_received,
                 peernet_total_bytes_sent,
                 block_slot_delay,
+                consensus_graph_snapshot_time,
                 active_in_connections,
                 active_out_connections,
                 operations_final_counter,
@@ -702,6 +715,10 @@ impl MassaMetrics {
         self.block_slot_delay.observe(delay);
     }
 
+    pub fn set_consensus_graph_snapshot_time(&self, duration: f64) {
+        self.consensus_graph_snapshot_time.observe(duration);
+    }
+
     /// Update the bandwidth metrics for all peers
     /// HashMap<peer_id, (tx, rx)>
     pub fn update_peers_tx_rx(&self, data: HashMap<String, (u64, u64)>) {
@@ -255,6 +255,66 @@ impl Amount {
     pub fn checked_rem_u64(&self, divisor: u64) -> Option<Amount> {
         Some(Amount(self.0.checked_rem(divisor)?))
     }
+
+    /// Canonical string form of the amount: the integer part without leading zeros,
+    /// a dot and exactly `AMOUNT_DECIMAL_SCALE` decimals.
+    /// Unlike `to_string`, every amount has a single representation, independent of its value and of the locale.
+    /// ```
+    /// # use massa_models::amount::Amount;
+    /// # use std::str::FromStr;
+    /// assert_eq!(Amount::from_str("11.1").unwrap().to_canonical_string(), "11.100000000");
+    /// assert_eq!(Amount::zero().to_canonical_string(), "0.000000000");
+    /// assert_eq!(Amount::MAX.to_canonical_string(), "18446744073.709551615");
+    /// ```
+    pub fn to_canonical_string(&self) -> String {
+        format!(
+            "{}.{:0width$}",
+            self.0 / AMOUNT_DECIMAL_FACTOR,
+            self.0 % AMOUNT_DECIMAL_FACTOR,
+            width = AMOUNT_DECIMAL_SCALE as usize
+        )
+    }
+
+    /// Parse the canonical string form produced by `to_canonical_string`.
+    /// Any other form (missing or extra decimals, leading zeros, signs, exponents, spaces...) is rejected.
+    /// ```
+    /// # use massa_models::amount::Amount;
+    /// # use std::str::FromStr;
+    /// let amount = Amount::from_canonical_string("11.100000000").unwrap();
+    /// assert_eq!(amount, Amount::from_str("11.1").unwrap());
+    /// assert!(Amount::from_canonical_string("11.1").is_err());
+    /// assert!(Amount::from_canonical_string("011.100000000").is_err());
+    /// assert!(Amount::from_canonical_string("+11.100000000").is_err());
+    /// assert!(Amount::from_canonical_string("18446744073.709551616").is_err());
+    /// ```
+    pub fn from_canonical_string(str_amount: &str) -> Result<Self, ModelsError> {
+        let err = || {
+            ModelsError::AmountParseError(format!(
+                "{} is not a canonical amount: expected digits, a dot and {} decimals",
+                str_amount, AMOUNT_DECIMAL_SCALE
+            ))
+        };
+        let (integer, decimals) = str_amount.split_once('.').ok_or_else(err)?;
+        if integer.is_empty()
+            || (integer.len() > 1 && integer.starts_with('0'))
+            || decimals.len() != AMOUNT_DECIMAL_SCALE as usize
+            || !integer
+                .bytes()
+                .chain(decimals.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(err());
+        }
+        let integer: u64 = integer.parse().map_err(|_| err())?;
+        let decimals: u64 = decimals.parse().map_err(|_| err())?;
+        integer
+            .checked_mul(AMOUNT_DECIMAL_FACTOR)
+            .and_then(|raw| raw.checked_add(decimals))
+            .map(Amount)
+            .ok_or_else(|| {
+                ModelsError::AmountParseError(format!("amount {} is too large", str_amount))
+            })
+    }
 }
 
 /// display an Amount in decimal string form (like "10.33")
@@ -433,3 +493,27 @@ impl serde::Serialize for Amount {
         serializer.serialize_str(&self.to_string())
     }
 }
+
+/// Serde helpers rendering amounts in their canonical string form,
+/// to be used with `#[serde(with = "massa_models::amount::canonical")]` in exports read by external tools
+pub mod canonical {
+    use super::Amount;
+    use serde::de::Error;
+
+    /// Serialize an amount in its canonical string form
+    pub fn serialize<S>(amount: &Amount, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&amount.to_canonical_string())
+    }
+
+    /// Deserialize an amount from its canonical string form
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Amount, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let str_amount: String = serde::Deserialize::deserialize(deserializer)?;
+        Amount::from_canonical_string(&str_amount).map_err(D::Error::custom)
+    }
+}
@@ -191,6 +191,7 @@ impl Serializer<Block> for BlockSerializer {
     ///             .unwrap(),
     ///         ],
     ///         denunciations: Vec::new(),
+    ///         extensions: Vec::new(),
     ///     },
     ///     BlockHeaderSerializer::new(),
     ///     &keypair,
@@ -304,6 +305,7 @@ impl Deserializer<Block> for BlockDeserializer {
     ///             .unwrap(),
     ///         ],
     ///         denunciations: Vec::new(),
+    ///         extensions: Vec::new(),
     ///     },
     ///     BlockHeaderSerializer::new(),
     ///     &keypair,
@@ -502,6 +504,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![endo1, endo2],
                 denunciations: Vec::new(), // FIXME
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -593,6 +596,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -694,6 +698,7 @@ mod test {
                 )
                 .unwrap()],
                 denunciations: vec![],
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -753,6 +758,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -807,6 +813,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -864,6 +871,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -945,6 +953,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements,
                 denunciations: vec![],
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1004,6 +1013,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1076,6 +1086,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements,
                 denunciations: vec![],
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1153,6 +1164,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![endo1],
                 denunciations: vec![],
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1243,6 +1255,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![endo1, endo2],
                 denunciations: vec![],
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1335,6 +1348,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![endo1, endo2],
                 denunciations: Vec::new(), // FIXME
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1,4 +1,8 @@
+use crate::block_header_extension::{
+    BlockHeaderExtension, BlockHeaderExtensionsDeserializer, BlockHeaderExtensionsSerializer,
+};
 use crate::block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer};
+use crate::config::{MAX_BLOCK_HEADER_EXTENSIONS, MAX_BLOCK_HEADER_EXTENSION_DATA_SIZE};
 use crate::denunciation::{Denunciation, DenunciationDeserializer, DenunciationSerializer};
 use crate::endorsement::{
     Endorsement, EndorsementDeserializerLW, EndorsementId, EndorsementSerializer,
@@ -14,11 +18,12 @@ use massa_serialization::{
     U32VarIntDeserializer, U32VarIntSerializer,
 };
 use massa_signature::PublicKey;
-use nom::branch::alt;
 use nom::bytes::complete::tag;
+use nom::combinator::verify;
 use nom::error::{context, ContextError, ParseError};
 use nom::multi::{count, length_count};
-use nom::sequence::{preceded, tuple};
+use nom::number::complete::be_u8;
+use nom::sequence::tuple;
 use nom::{IResult, Parser};
 use serde::{Deserialize, Serialize};
 use std::collections::Bound::{Excluded, Included};
@@ -42,8 +47,16 @@ pub struct BlockHeader {
     pub endorsements: Vec<SecureShareEndorsement>,
     /// denunciations
     pub denunciations: Vec<Denunciation>,
+    /// versioned extension fields, sorted by id
+    #[serde(default)]
+    pub extensions: Vec<BlockHeaderExtension>,
 }
 
+/// flag set in the parents tag when the header has parents
+const HEADER_FLAG_PARENTS: u8 = 0b01;
+/// flag set in the parents tag when the header has an extension section
+const HEADER_FLAG_EXTENSIONS: u8 = 0b10;
+
 // TODO: gh-issue #3398
 #[cfg(any(test, feature = "test-exports"))]
 impl BlockHeader {
@@ -139,6 +152,7 @@ pub struct BlockHeaderSerializer {
     u32_serializer: U32VarIntSerializer,
     opt_serializer: OptionSerializer<u32, U32VarIntSerializer>,
     block_id_serializer: BlockIdSerializer,
+    extensions_serializer: BlockHeaderExtensionsSerializer,
 }
 
 impl BlockHeaderSerializer {
@@ -152,6 +166,10 @@ impl BlockHeaderSerializer {
             endorsement_content_serializer: EndorsementSerializerLW::new(),
             denunciation_serializer: DenunciationSerializer::new(),
             block_id_serializer: BlockIdSerializer::new(),
+            extensions_serializer: BlockHeaderExtensionsSerializer::new(
+                MAX_BLOCK_HEADER_EXTENSIONS,
+                MAX_BLOCK_HEADER_EXTENSION_DATA_SIZE,
+            ),
         }
     }
 }
@@ -209,6 +227,7 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
     ///     .unwrap(),
     ///    ],
     ///   denunciations: vec![],
+    ///   extensions: Vec::new(),
     /// };
     /// let mut buffer = vec![];
     /// BlockHeaderSerializer::new().serialize(&header, &mut buffer).unwrap();
@@ -224,11 +243,15 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
         self.slot_serializer.serialize(&value.slot, buffer)?;
 
         // parents (note: there should be none if slot period=0)
-        if value.parents.is_empty() {
-            buffer.push(0);
-        } else {
-            buffer.push(1);
+        // the extension section is flagged in the same byte, so that headers without extensions keep their encoding
+        let mut flags = 0;
+        if !value.parents.is_empty() {
+            flags |= HEADER_FLAG_PARENTS;
+        }
+        if !value.extensions.is_empty() {
+            flags |= HEADER_FLAG_EXTENSIONS;
         }
+        buffer.push(flags);
         for parent_h in value.parents.iter() {
             self.block_id_serializer.serialize(parent_h, buffer)?;
         }
@@ -261,6 +284,11 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
                 .serialize(denunciation, buffer)?;
         }
 
+        if !value.extensions.is_empty() {
+            self.extensions_serializer
+                .serialize(&value.extensions, buffer)?;
+        }
+
         Ok(())
     }
 }
@@ -279,6 +307,7 @@ pub struct BlockHeaderDeserializer {
     network_versions_deserializer: U32VarIntDeserializer,
     opt_deserializer: OptionDeserializer<u32, U32VarIntDeserializer>,
     block_id_deserializer: BlockIdDeserializer,
+    extensions_deserializer: BlockHeaderExtensionsDeserializer,
     chain_id: u64,
 }
 
@@ -320,6 +349,10 @@ impl BlockHeaderDeserializer {
                 endorsement_count,
             ),
             block_id_deserializer: BlockIdDeserializer::new(),
+            extensions_deserializer: BlockHeaderExtensionsDeserializer::new(
+                MAX_BLOCK_HEADER_EXTENSIONS,
+                MAX_BLOCK_HEADER_EXTENSION_DATA_SIZE,
+            ),
             thread_count,
             endorsement_count,
             last_start_period,
@@ -375,6 +408,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
     ///     .unwrap(),
     ///    ],
     ///    denunciations: vec![],
+    ///    extensions: Vec::new(),
     /// };
     /// let mut buffer = vec![];
     /// BlockHeaderSerializer::new().serialize(&header, &mut buffer).unwrap();
@@ -389,69 +423,93 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
         &self,
         buffer: &'a [u8],
     ) -> IResult<&'a [u8], BlockHeader, E> {
-        let (rest, (current_version, announced_version, slot, parents, operation_merkle_root)): (
-            &[u8],
-            (u32, Option<u32>, Slot, Vec<BlockId>, Hash),
-        ) = context("Failed BlockHeader deserialization", |input| {
-            let (rest, (current_version, announced_version, slot, parents)) = tuple((
-                context("Failed current_version deserialization", |input| {
-                    self.network_versions_deserializer.deserialize(input)
-                }),
-                context("Failed announced_version deserialization", |input| {
-                    self.opt_deserializer.deserialize(input)
-                }),
-                context("Failed slot deserialization", |input| {
-                    self.slot_deserializer.deserialize(input)
-                }),
-                context(
-                    "Failed parents deserialization",
-                    alt((
-                        preceded(tag(&[0]), |input| Ok((input, Vec::new()))),
-                        preceded(
-                            tag(&[1]),
-                            count(
-                                context("Failed block_id deserialization", |input| {
-                                    self.block_id_deserializer.deserialize(input)
-                                }),
-                                self.thread_count as usize,
-                            ),
-                        ),
-                    )),
-                ),
-            ))
-            .parse(input)?;
-
-            // validate the parent/slot invariants before moving on to other fields
-            if let Some(last_start_period) = self.last_start_period {
-                if slot.period == last_start_period && !parents.is_empty() {
-                    return Err(nom::Err::Failure(ContextError::add_context(
-                        rest,
-                        "Genesis block cannot contain parents",
-                        ParseError::from_error_kind(rest, nom::error::ErrorKind::Fail),
-                    )));
-                } else if slot.period != last_start_period
-                    && parents.len() != self.thread_count as usize
-                {
-                    return Err(nom::Err::Failure(ContextError::add_context(
-                        rest,
-                        "Non-genesis block must have same numbers of parents as threads count",
-                        ParseError::from_error_kind(rest, nom::error::ErrorKind::Fail),
-                    )));
+        let (
+            rest,
+            (current_version, announced_version, slot, flags, parents, operation_merkle_root),
+        ): (&[u8], (u32, Option<u32>, Slot, u8, Vec<BlockId>, Hash)) =
+            context("Failed BlockHeader deserialization", |input| {
+                let (rest, (current_version, announced_version, slot, flags)) = tuple((
+                    context("Failed current_version deserialization", |input| {
+                        self.network_versions_deserializer.deserialize(input)
+                    }),
+                    context("Failed announced_version deserialization", |input| {
+                        self.opt_deserializer.deserialize(input)
+                    }),
+                    context("Failed slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                    context(
+                        "Failed flags deserialization",
+                        verify(be_u8, |flags: &u8| {
+                            flags & !(HEADER_FLAG_PARENTS | HEADER_FLAG_EXTENSIONS) == 0
+                        }),
+                    ),
+                ))
+                .parse(input)?;
+                let (rest, parents) = context("Failed parents deserialization", |input| {
+                    if flags & HEADER_FLAG_PARENTS == 0 {
+                        return Ok((input, Vec::new()));
+                    }
+                    count(
+                        context("Failed block_id deserialization", |input| {
+                            self.block_id_deserializer.deserialize(input)
+                        }),
+                        self.thread_count as usize,
+                    )
+                    .parse(input)
+                })
+                .parse(rest)?;
+
+                // validate the parent/slot invariants before moving on to other fields
+                if let Some(last_start_period) = self.last_start_period {
+                    if slot.period == last_start_period && !parents.is_empty() {
+                        return Err(nom::Err::Failure(ContextError::add_context(
+                            rest,
+                            "Genesis block cannot contain parents",
+                            ParseError::from_error_kind(rest, nom::error::ErrorKind::Fail),
+                        )));
+                    } else if slot.period != last_start_period
+                        && parents.len() != self.thread_count as usize
+                    {
+                        return Err(nom::Err::Failure(ContextError::add_context(
+                            rest,
+                            "Non-genesis block must have same numbers of parents as threads count",
+                            ParseError::from_error_kind(rest, nom::error::ErrorKind::Fail),
+                        )));
+                    }
                 }
-            }
 
-            let (rest, merkle) = context("Failed operation_merkle_root", |input| {
-                self.hash_deserializer.deserialize(input)
+                let (rest, merkle) = context("Failed operation_merkle_root", |input| {
+                    self.hash_deserializer.deserialize(input)
+                })
+                .parse(rest)?;
+                Ok((
+                    rest,
+                    (
+                        current_version,
+                        announced_version,
+                        slot,
+                        flags,
+                        parents,
+                        merkle,
+                    ),
+                ))
             })
-            .parse(rest)?;
-            Ok((
-                rest,
-                (current_version, announced_version, slot, parents, merkle),
-            ))
-        })
-        .parse(buffer)?;
+            .parse(buffer)?;
+
+        // the extension section is the last one, only present if flagged
+        let deserialize_extensions = |input: &'a [u8]| {
+            if flags & HEADER_FLAG_EXTENSIONS == 0 {
+                return Ok((input, Vec::new()));
+            }
+            self.extensions_deserializer.deserialize(input)
+        };
 
         if parents.is_empty() {
+            // As we have 0 endorsements & 0 denunciations, rest = [0, 0] (length 0 & length 0)
+            // As we want to return an empty "res" we use nom tag
+            let (rest2, _) = tag(&[0, 0])(rest)?;
+            let (rest2, extensions) = deserialize_extensions(rest2)?;
             let res = BlockHeader {
                 current_version,
                 announced_version,
@@ -460,6 +518,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
                 operation_merkle_root,
                 endorsements: Vec::new(),
                 denunciations: Vec::new(),
+                extensions,
             };
 
             // TODO: gh-issue #3398
@@ -467,9 +526,6 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
             res.assert_invariants(self.thread_count, self.endorsement_count)
                 .unwrap();
 
-            // As we have 0 endorsements & 0 denunciations, rest = [0, 0] (length 0 & length 0)
-            // As we want to return an empty "res" we use nom tag
-            let (rest2, _) = tag(&[0, 0])(rest)?;
             return Ok((rest2, res));
         }
 
@@ -533,6 +589,8 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
         )
         .parse(rest)?;
 
+        let (rest, extensions) = deserialize_extensions(rest)?;
+
         let header = BlockHeader {
             current_version,
             announced_version,
@@ -541,6 +599,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
             operation_merkle_root,
             endorsements,
             denunciations,
+            extensions,
         };
 
         // TODO: gh-issue #3398
@@ -621,7 +680,8 @@ mod test {
     };
 
     use crate::test_exports::{
-        gen_block_headers_for_denunciation, gen_endorsements_for_denunciation,
+        assert_golden_vector, gen_block_headers_for_denunciation,
+        gen_endorsements_for_denunciation, read_golden_vector,
     };
     use massa_signature::{verify_signature_batch, KeyPair};
 
@@ -633,6 +693,7 @@ mod test {
                 && self.operation_merkle_root == other.operation_merkle_root
                 && self.endorsements == other.endorsements
                 && self.denunciations == other.denunciations
+                && self.extensions == other.extensions
         }
     }
 
@@ -676,6 +737,7 @@ mod test {
             operation_merkle_root: Hash::compute_from("mno".as_bytes()),
             endorsements: vec![s_endorsement_1],
             denunciations: vec![de_a, de_b],
+            extensions: Vec::new(),
         };
 
         let mut buffer = Vec::new();
@@ -706,6 +768,7 @@ mod test {
             operation_merkle_root: Hash::compute_from("mno".as_bytes()),
             endorsements: vec![],
             denunciations: vec![],
+            extensions: Vec::new(),
         };
 
         let mut buffer = Vec::new();
@@ -725,6 +788,57 @@ mod test {
         assert_eq!(block_header_1, block_header_der);
     }
 
+    #[test]
+    fn test_block_header_extensions_ser_der() {
+        let parents: Vec<BlockId> = (0..THREAD_COUNT)
+            .map(|i| BlockId::generate_from_hash(Hash::compute_from(&[i])))
+            .collect();
+        let mut block_header_1 = BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            slot: Slot::new(7, 1),
+            parents,
+            operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+            endorsements: vec![],
+            denunciations: vec![],
+            extensions: vec![],
+        };
+        let ser = BlockHeaderSerializer::new();
+        let der = BlockHeaderDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+            None,
+            *CHAINID,
+        );
+
+        // headers without extensions keep the encoding they had before extensions existed
+        let mut buffer_without = Vec::new();
+        ser.serialize(&block_header_1, &mut buffer_without).unwrap();
+
+        block_header_1.extensions = vec![BlockHeaderExtension {
+            id: 3,
+            version: 1,
+            data: vec![42; 32],
+        }];
+        let mut buffer = Vec::new();
+        ser.serialize(&block_header_1, &mut buffer).unwrap();
+        let (rem, block_header_der) = der.deserialize::<DeserializeError>(&buffer).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(block_header_1, block_header_der);
+
+        // only the flags and the trailing extension section differ
+        assert!(buffer.len() > buffer_without.len());
+        assert_eq!(
+            buffer
+                .iter()
+                .zip(buffer_without.iter())
+                .filter(|(a, b)| a != b)
+                .count(),
+            1
+        );
+    }
+
     #[test]
     fn test_verify_sig_batch() {
         let (_slot, _keypair, secured_header_1, secured_header_2, secured_header_3) =
@@ -799,6 +913,7 @@ mod test {
             operation_merkle_root: Hash::compute_from("mno".as_bytes()),
             endorsements: vec![s_endorsement_1],
             denunciations: vec![de_a, de_b],
+            extensions: Vec::new(),
         };
 
         let serialized_block_header = serde_json::to_string(&block_header_1).unwrap();
@@ -817,4 +932,53 @@ mod test {
             res_block_header["slot"]["thread"]
         );
     }
+
+    /// Check the serialization of a header against a golden vector, then deserialize the vector back
+    fn check_block_header_golden_vector(header: &BlockHeader, name: &str, thread_count: u8) {
+        let mut buf = Vec::new();
+        BlockHeaderSerializer::new()
+            .serialize(header, &mut buf)
+            .unwrap();
+        assert_golden_vector(env!("CARGO_MANIFEST_DIR"), name, &buf);
+
+        let golden = read_golden_vector(env!("CARGO_MANIFEST_DIR"), name);
+        let (rest, header_der) =
+            BlockHeaderDeserializer::new(thread_count, ENDORSEMENT_COUNT, 0, Some(0), *CHAINID)
+                .deserialize::<DeserializeError>(&golden)
+                .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(&header_der, header);
+        assert_eq!(header_der.current_version, header.current_version);
+        assert_eq!(header_der.announced_version, header.announced_version);
+    }
+
+    #[test]
+    fn test_block_header_golden_vectors() {
+        let genesis_header = BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            slot: Slot::new(0, 5),
+            parents: Vec::new(),
+            operation_merkle_root: Hash::from_bytes(&[0x11; 32]),
+            endorsements: Vec::new(),
+            denunciations: Vec::new(),
+            extensions: Vec::new(),
+        };
+        check_block_header_golden_vector(&genesis_header, "block_header_genesis", THREAD_COUNT);
+
+        let header = BlockHeader {
+            current_version: 0,
+            announced_version: Some(1),
+            slot: Slot::new(1, 1),
+            parents: vec![
+                BlockId::generate_from_hash(Hash::from_bytes(&[0x01; 32])),
+                BlockId::generate_from_hash(Hash::from_bytes(&[0x02; 32])),
+            ],
+            operation_merkle_root: Hash::from_bytes(&[0x22; 32]),
+            endorsements: Vec::new(),
+            denunciations: Vec::new(),
+            extensions: Vec::new(),
+        };
+        check_block_header_golden_vector(&header, "block_header_with_parents", 2);
+    }
 }
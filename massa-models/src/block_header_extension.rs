@@ -0,0 +1,317 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Versioned extension fields of the block headers.
+//!
+//! Extensions allow introducing new header data (e.g. VDF outputs, data availability commitments)
+//! without breaking the deserialization of headers on nodes that do not know them yet:
+//! every extension carries its own id, version and opaque data, unknown extensions are kept
+//! (they are signed as part of the header) but ignored, known ones are validated against their spec.
+//! Headers only carry extensions once a network version enabling them
+//! (`MipComponent::BlockHeaderExtensions`) is active at their slot, earlier ones are rejected.
+
+use massa_serialization::{
+    Deserializer, SerializeError, Serializer, U32VarIntDeserializer, U32VarIntSerializer,
+};
+use nom::error::{context, ContextError, ParseError};
+use nom::multi::length_count;
+use nom::sequence::tuple;
+use nom::{IResult, Parser};
+use serde::{Deserialize, Serialize};
+use std::ops::Bound::Included;
+
+/// An extension field of a block header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeaderExtension {
+    /// identifier of the feature the extension belongs to
+    pub id: u32,
+    /// version of the format of `data`
+    pub version: u32,
+    /// opaque data, only interpreted by the nodes knowing the extension
+    pub data: Vec<u8>,
+}
+
+/// Specification of a header extension known by the node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeaderExtensionSpec {
+    /// identifier of the extension
+    pub id: u32,
+    /// lowest supported version
+    pub min_version: u32,
+    /// highest supported version
+    pub max_version: u32,
+    /// maximum size of the extension data in bytes
+    pub max_data_size: u32,
+}
+
+impl BlockHeaderExtensionSpec {
+    /// Check that an extension with the id of this spec follows it
+    pub fn check(&self, extension: &BlockHeaderExtension) -> Result<(), String> {
+        if extension.version < self.min_version || extension.version > self.max_version {
+            return Err(format!(
+                "unsupported version {} of header extension {} (supported: {} to {})",
+                extension.version, extension.id, self.min_version, self.max_version
+            ));
+        }
+        if extension.data.len() > self.max_data_size as usize {
+            return Err(format!(
+                "header extension {} data too large: {} bytes (max {})",
+                extension.id,
+                extension.data.len(),
+                self.max_data_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Check the known extensions of a header against their specs, unknown extensions are ignored
+pub fn check_header_extensions(
+    extensions: &[BlockHeaderExtension],
+    specs: &[BlockHeaderExtensionSpec],
+) -> Result<(), String> {
+    for extension in extensions {
+        if let Some(spec) = specs.iter().find(|spec| spec.id == extension.id) {
+            spec.check(extension)?;
+        }
+    }
+    Ok(())
+}
+
+/// Serializer for the extensions of a block header
+pub struct BlockHeaderExtensionsSerializer {
+    u32_serializer: U32VarIntSerializer,
+    max_extensions: u32,
+    max_extension_data_size: u32,
+}
+
+impl BlockHeaderExtensionsSerializer {
+    /// Creates a new `BlockHeaderExtensionsSerializer`
+    ///
+    /// # Arguments
+    /// * `max_extensions`: maximum number of extensions in a header
+    /// * `max_extension_data_size`: maximum size of the data of an extension, whether it is known or not
+    pub fn new(max_extensions: u32, max_extension_data_size: u32) -> Self {
+        Self {
+            u32_serializer: U32VarIntSerializer::new(),
+            max_extensions,
+            max_extension_data_size,
+        }
+    }
+}
+
+impl Serializer<Vec<BlockHeaderExtension>> for BlockHeaderExtensionsSerializer {
+    fn serialize(
+        &self,
+        value: &Vec<BlockHeaderExtension>,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        // only produce what the deserializer accepts, so that the encoding stays canonical
+        let count: u32 = value.len().try_into().map_err(|err| {
+            SerializeError::GeneralError(format!("too many header extensions: {}", err))
+        })?;
+        if count == 0 || count > self.max_extensions {
+            return Err(SerializeError::NumberTooBig(format!(
+                "invalid number of header extensions: {} (expected 1 to {})",
+                count, self.max_extensions
+            )));
+        }
+        if value.windows(2).any(|pair| pair[0].id >= pair[1].id) {
+            return Err(SerializeError::GeneralError(
+                "header extensions must be sorted by strictly increasing id".to_string(),
+            ));
+        }
+        self.u32_serializer.serialize(&count, buffer)?;
+        for extension in value.iter() {
+            let data_len: u32 = extension.data.len().try_into().map_err(|err| {
+                SerializeError::GeneralError(format!("header extension too large: {}", err))
+            })?;
+            if data_len > self.max_extension_data_size {
+                return Err(SerializeError::NumberTooBig(format!(
+                    "header extension {} data too large: {} bytes (max {})",
+                    extension.id, data_len, self.max_extension_data_size
+                )));
+            }
+            self.u32_serializer.serialize(&extension.id, buffer)?;
+            self.u32_serializer.serialize(&extension.version, buffer)?;
+            self.u32_serializer.serialize(&data_len, buffer)?;
+            buffer.extend(&extension.data);
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for the extensions of a block header
+pub struct BlockHeaderExtensionsDeserializer {
+    count_deserializer: U32VarIntDeserializer,
+    u32_deserializer: U32VarIntDeserializer,
+    data_len_deserializer: U32VarIntDeserializer,
+}
+
+impl BlockHeaderExtensionsDeserializer {
+    /// Creates a new `BlockHeaderExtensionsDeserializer`
+    ///
+    /// # Arguments
+    /// * `max_extensions`: maximum number of extensions in a header
+    /// * `max_extension_data_size`: maximum size of the data of an extension, whether it is known or not
+    pub fn new(max_extensions: u32, max_extension_data_size: u32) -> Self {
+        Self {
+            count_deserializer: U32VarIntDeserializer::new(Included(1), Included(max_extensions)),
+            u32_deserializer: U32VarIntDeserializer::new(Included(0), Included(u32::MAX)),
+            data_len_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(max_extension_data_size),
+            ),
+        }
+    }
+}
+
+impl Deserializer<Vec<BlockHeaderExtension>> for BlockHeaderExtensionsDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Vec<BlockHeaderExtension>, E> {
+        let (rest, extensions) = context(
+            "Failed header extensions deserialization",
+            length_count(
+                context("Failed length deserialization", |input| {
+                    self.count_deserializer.deserialize(input)
+                }),
+                |input| {
+                    let (rest, (id, version, data_len)) = tuple((
+                        context("Failed id deserialization", |input| {
+                            self.u32_deserializer.deserialize(input)
+                        }),
+                        context("Failed version deserialization", |input| {
+                            self.u32_deserializer.deserialize(input)
+                        }),
+                        context("Failed data length deserialization", |input| {
+                            self.data_len_deserializer.deserialize(input)
+                        }),
+                    ))
+                    .parse(input)?;
+                    let (rest, data) = context(
+                        "Failed data deserialization",
+                        nom::bytes::complete::take(data_len),
+                    )
+                    .parse(rest)?;
+                    Ok((
+                        rest,
+                        BlockHeaderExtension {
+                            id,
+                            version,
+                            data: data.to_vec(),
+                        },
+                    ))
+                },
+            ),
+        )
+        .parse(buffer)?;
+
+        // ids must be strictly increasing so that the encoding is canonical
+        if extensions.windows(2).any(|pair| pair[0].id >= pair[1].id) {
+            return Err(nom::Err::Failure(ContextError::add_context(
+                rest,
+                "Header extensions must be sorted by strictly increasing id",
+                ParseError::from_error_kind(rest, nom::error::ErrorKind::Fail),
+            )));
+        }
+        Ok((rest, extensions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_serialization::DeserializeError;
+
+    #[test]
+    fn test_header_extensions_ser_deser() {
+        let extensions = vec![
+            BlockHeaderExtension {
+                id: 1,
+                version: 0,
+                data: vec![1, 2, 3],
+            },
+            BlockHeaderExtension {
+                id: 7,
+                version: 2,
+                data: Vec::new(),
+            },
+        ];
+        let serializer = BlockHeaderExtensionsSerializer::new(16, 16);
+        let mut buffer = Vec::new();
+        serializer.serialize(&extensions, &mut buffer).unwrap();
+        let deserializer = BlockHeaderExtensionsDeserializer::new(16, 16);
+        let (rest, deserialized) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, extensions);
+
+        // duplicate ids are rejected: count 2, then twice id 1, version 0, empty data
+        let buffer = vec![2, 1, 0, 0, 1, 0, 0];
+        assert!(deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .is_err());
+    }
+
+    #[test]
+    fn test_header_extensions_serializer_limits() {
+        let extension = |id, size| BlockHeaderExtension {
+            id,
+            version: 0,
+            data: vec![0; size],
+        };
+        let serializer = BlockHeaderExtensionsSerializer::new(2, 4);
+        let deserializer = BlockHeaderExtensionsDeserializer::new(2, 4);
+
+        // unsorted or duplicate ids are not serialized
+        let mut unsorted = vec![extension(7, 1), extension(1, 2)];
+        assert!(serializer.serialize(&unsorted, &mut Vec::new()).is_err());
+        assert!(serializer
+            .serialize(&vec![extension(1, 1), extension(1, 2)], &mut Vec::new())
+            .is_err());
+
+        // once sorted they round-trip
+        unsorted.sort_by_key(|extension| extension.id);
+        let mut buffer = Vec::new();
+        serializer.serialize(&unsorted, &mut buffer).unwrap();
+        let (rest, deserialized) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, unsorted);
+
+        // the count and data size limits of the deserializer are enforced
+        assert!(serializer.serialize(&Vec::new(), &mut Vec::new()).is_err());
+        assert!(serializer
+            .serialize(
+                &vec![extension(1, 0), extension(2, 0), extension(3, 0)],
+                &mut Vec::new()
+            )
+            .is_err());
+        assert!(serializer
+            .serialize(&vec![extension(1, 5)], &mut Vec::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_check_header_extensions() {
+        let specs = vec![BlockHeaderExtensionSpec {
+            id: 1,
+            min_version: 1,
+            max_version: 2,
+            max_data_size: 4,
+        }];
+        let extension = |id, version, size| BlockHeaderExtension {
+            id,
+            version,
+            data: vec![0; size],
+        };
+        assert!(check_header_extensions(&[extension(1, 2, 4)], &specs).is_ok());
+        // unknown extensions are ignored
+        assert!(check_header_extensions(&[extension(2, 9, 64)], &specs).is_ok());
+        assert!(check_header_extensions(&[extension(1, 3, 4)], &specs).is_err());
+        assert!(check_header_extensions(&[extension(1, 1, 5)], &specs).is_err());
+    }
+}
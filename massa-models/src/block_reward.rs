@@ -0,0 +1,105 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::address::Address;
+use crate::amount::Amount;
+use serde::{Deserialize, Serialize};
+
+/// Share of the block credits paid to the creator of each endorsement of a block,
+/// and paid again to the creator of the endorsed block for each endorsement.
+///
+/// The credits are split in `3 * (1 + endorsement_count)` parts.
+pub fn endorsement_reward_part(block_credits: Amount, endorsement_count: u64) -> Amount {
+    block_credits
+        .checked_div_u64(3 * (1 + endorsement_count))
+        .expect("critical: block_credits checked_div factor is 0")
+}
+
+/// Split of the credits of a block between its creator, the creators of its endorsements
+/// and the creator of the endorsed block
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockRewardSplit {
+    /// creator of the block and the amount it receives
+    pub block_creator: (Address, Amount),
+    /// creator of each endorsement of the block and the amount it receives, in the order of the endorsements
+    pub endorsement_creators: Vec<(Address, Amount)>,
+    /// creator of the endorsed block (same-thread parent) and the amount it receives,
+    /// `None` if the block has no endorsed block
+    pub endorsed_block_creator: Option<(Address, Amount)>,
+}
+
+impl BlockRewardSplit {
+    /// Compute the split of `block_credits` the same way execution credits them
+    ///
+    /// # Arguments
+    /// * `block_credits`: credits of the block to split
+    /// * `endorsement_count`: target number of endorsements per block
+    /// * `block_creator`: creator of the block
+    /// * `endorsement_creators`: creators of the endorsements included in the block
+    /// * `endorsed_block_creator`: creator of the endorsed block, if any
+    pub fn compute(
+        block_credits: Amount,
+        endorsement_count: u64,
+        block_creator: Address,
+        endorsement_creators: &[Address],
+        endorsed_block_creator: Option<Address>,
+    ) -> Self {
+        let part = endorsement_reward_part(block_credits, endorsement_count);
+        let mut remaining_credit = block_credits;
+        let mut endorsed_block_credit = Amount::zero();
+        let endorsement_creators: Vec<(Address, Amount)> = endorsement_creators
+            .iter()
+            .map(|addr| {
+                remaining_credit = remaining_credit.saturating_sub(part);
+                if endorsed_block_creator.is_some() {
+                    remaining_credit = remaining_credit.saturating_sub(part);
+                    endorsed_block_credit = endorsed_block_credit.saturating_add(part);
+                }
+                (*addr, part)
+            })
+            .collect();
+        let endorsed_block_creator =
+            endorsed_block_creator.map(|addr| (addr, endorsed_block_credit));
+        BlockRewardSplit {
+            block_creator: (block_creator, remaining_credit),
+            endorsement_creators,
+            endorsed_block_creator,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_block_reward_split() {
+        let address = || Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let (creator, endorser_a, endorser_b, endorsed) =
+            (address(), address(), address(), address());
+
+        let split = BlockRewardSplit::compute(
+            Amount::from_str("9").unwrap(),
+            2,
+            creator,
+            &[endorser_a, endorser_b],
+            Some(endorsed),
+        );
+        assert_eq!(
+            split.endorsement_creators,
+            vec![
+                (endorser_a, Amount::from_str("1").unwrap()),
+                (endorser_b, Amount::from_str("1").unwrap())
+            ]
+        );
+        assert_eq!(
+            split.endorsed_block_creator,
+            Some((endorsed, Amount::from_str("2").unwrap()))
+        );
+        assert_eq!(
+            split.block_creator,
+            (creator, Amount::from_str("5").unwrap())
+        );
+    }
+}
@@ -21,7 +21,7 @@ use crate::prehash::PreHashSet;
 use std::ops::Bound::{Excluded, Included};
 
 /// Mutually compatible blocks in the graph
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Clique {
     /// the block ids of the blocks in that clique
     pub block_ids: PreHashSet<BlockId>,
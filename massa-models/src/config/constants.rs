@@ -70,9 +70,9 @@ lazy_static::lazy_static! {
     /// node version
     pub static ref VERSION: Version = {
         if cfg!(feature = "sandbox") {
-            "SAND.2.1"
+            "SAND.2.2"
         } else {
-            "MAIN.2.1"
+            "MAIN.2.2"
         }
         .parse()
         .unwrap()
@@ -136,6 +136,12 @@ pub const THREAD_COUNT: u8 = 32;
 pub const ENDORSEMENT_COUNT: u32 = 16;
 /// Threshold for fitness.
 pub const DELTA_F0: u64 = 64 * (ENDORSEMENT_COUNT as u64 + 1);
+/// Maximum number of periods between a block and its parents (genesis parents excepted), 0 for no limit.
+/// Changing it changes which blocks are valid: it must ship with a new network version.
+pub const MAX_PARENT_AGE: u64 = 0;
+/// Blocks more than this number of periods ahead of the speculative execution are not proposed as parents
+/// to the block factory, 0 for no limit
+pub const MAX_PARENT_EXECUTION_LAG: u64 = 4;
 /// Maximum number of operations per block
 pub const MAX_OPERATIONS_PER_BLOCK: u32 = 5000;
 /// Maximum block size in bytes
@@ -148,6 +154,12 @@ pub const OPERATION_VALIDITY_PERIODS: u64 = 10;
 pub const KEEP_EXECUTED_HISTORY_EXTRA_PERIODS: u64 = 10;
 /// cycle duration in periods
 pub const PERIODS_PER_CYCLE: u64 = 128;
+/// roll sale reimbursements strictly above this amount are split across consecutive slots
+pub const ROLL_SELL_CREDIT_SPLIT_THRESHOLD: Amount = Amount::const_init(1_000_000, 0);
+/// number of consecutive slots a split roll sale reimbursement is spread over
+pub const ROLL_SELL_CREDIT_SPLIT_PARTS: u64 = 32;
+/// number of periods by which the pending deferred credits are postponed when the emergency payout pause is activated
+pub const PAYOUT_PAUSE_DELAY_PERIODS: u64 = 8 * PERIODS_PER_CYCLE;
 /// Number of periods between two backups
 pub const PERIODS_BETWEEN_BACKUPS: u64 = 100 * PERIODS_PER_CYCLE;
 /// Maximum number of backups to keep. If reached, will delete the oldest ones.
@@ -173,6 +185,8 @@ pub const MAX_ROLLS_COUNT_LENGTH: u64 = 10_000;
 pub const MAX_PRODUCTION_STATS_LENGTH: u64 = 10_000;
 /// Maximum size proof-of-stake deferred credits
 pub const MAX_DEFERRED_CREDITS_LENGTH: u64 = 10_000;
+/// Maximum number of periods between the last final slot and the slot of a deferred credit (about 100 years)
+pub const MAX_DEFERRED_CREDIT_PERIOD_HORIZON: u64 = 200_000_000;
 /// Maximum size of executed ops
 pub const MAX_EXECUTED_OPS_LENGTH: u64 = 1_000;
 /// Maximum size of executed ops changes
@@ -337,6 +351,10 @@ pub const VERSIONING_ACTIVATION_DELAY_MIN: MassaTime = T0.saturating_mul(PERIODS
 pub const DENUNCIATION_EXPIRE_PERIODS: u64 = PERIODS_PER_CYCLE;
 /// Max number of denunciations that can be included in a block header
 pub const MAX_DENUNCIATIONS_PER_BLOCK_HEADER: u32 = 128;
+/// Max number of extensions in a block header
+pub const MAX_BLOCK_HEADER_EXTENSIONS: u32 = 16;
+/// Max size of the data of a block header extension, whether it is known or not
+pub const MAX_BLOCK_HEADER_EXTENSION_DATA_SIZE: u32 = 1024;
 /// Number of roll to remove per denunciation
 pub const ROLL_COUNT_TO_SLASH_ON_DENUNCIATION: u64 = 1;
 /// Maximum size of executed denunciations
@@ -16,8 +16,12 @@ pub mod amount;
 pub mod block;
 /// block-related structure: block_header
 pub mod block_header;
+/// versioned extension fields of the block headers
+pub mod block_header_extension;
 /// block-related structure: block_id
 pub mod block_id;
+/// split of the block rewards
+pub mod block_reward;
 /// bytecode structures
 pub mod bytecode;
 /// clique
@@ -42,10 +46,14 @@ pub mod ledger;
 pub mod mapping_grpc;
 /// node related structure
 pub mod node;
+/// events published on the node event bus
+pub mod node_event;
 /// operations
 pub mod operation;
 /// smart contract output events
 pub mod output_event;
+/// pagination of the controller queries
+pub mod pagination;
 /// pre-hashed trait, for hash less hashmap/set
 pub mod prehash;
 /// rolls
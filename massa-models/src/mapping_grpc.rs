@@ -226,6 +226,10 @@ impl From<OperationType> for grpc_model::OperationType {
                 grpc_operation_type.r#type =
                     Some(grpc_model::operation_type::Type::CallSc(call_sc));
             }
+            // not part of the gRPC API yet: the type is left unset
+            OperationType::SetPayoutAddress { .. } => {}
+            OperationType::Delegate { .. } => {}
+            OperationType::Undelegate { .. } => {}
         }
 
         grpc_operation_type
@@ -250,6 +254,9 @@ impl From<OperationType> for grpc_model::OpType {
             OperationType::RollSell { .. } => grpc_model::OpType::RollSell,
             OperationType::ExecuteSC { .. } => grpc_model::OpType::ExecuteSc,
             OperationType::CallSC { .. } => grpc_model::OpType::CallSc,
+            OperationType::SetPayoutAddress { .. } => grpc_model::OpType::Unspecified,
+            OperationType::Delegate { .. } => grpc_model::OpType::Unspecified,
+            OperationType::Undelegate { .. } => grpc_model::OpType::Unspecified,
         }
     }
 }
@@ -0,0 +1,69 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::address::Address;
+use crate::amount::Amount;
+use crate::block_id::BlockId;
+use crate::block_reward::BlockRewardSplit;
+use crate::operation::OperationId;
+use crate::prehash::PreHashSet;
+use crate::slot::Slot;
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Domain events published on the node event bus.
+///
+/// Producers (consensus, execution, pool, selector...) publish them without knowing their subscribers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeEvent {
+    /// A block became final
+    BlockFinal {
+        /// id of the block
+        block_id: BlockId,
+        /// slot of the block
+        slot: Slot,
+        /// split of the base block reward between the block creator, the endorsement creators
+        /// and the endorsed block creator (operation fees and denunciation penalties are not included)
+        reward_split: BlockRewardSplit,
+    },
+    /// A deferred credit was paid during the execution of a final slot
+    CreditPaid {
+        /// slot at which the credit was paid
+        slot: Slot,
+        /// credited address
+        address: Address,
+        /// credited amount
+        amount: Amount,
+    },
+    /// The draws of a cycle were computed by the selector
+    DrawsComputed {
+        /// the cycle whose draws were computed
+        cycle: u64,
+    },
+    /// Operations were removed from the pool because they can't be included in a block anymore
+    OperationsExpired {
+        /// ids of the expired operations
+        operation_ids: PreHashSet<OperationId>,
+    },
+    /// The last slot of a cycle was executed as final
+    CycleEnd {
+        /// the cycle that ended
+        cycle: u64,
+    },
+    /// The blockclique changed and some of its previous blocks were left out
+    ReorgHappened {
+        /// blocks that were in the previous blockclique but are not in the new one
+        removed: PreHashSet<BlockId>,
+        /// blocks that are in the new blockclique but were not in the previous one
+        added: PreHashSet<BlockId>,
+    },
+    /// No block became final during the finality stall timeout of consensus
+    FinalityStalled {
+        /// latest slot processed by consensus
+        slot: Slot,
+        /// time elapsed since a block last became final
+        stalled_for: MassaTime,
+        /// file the diagnostics bundle was written to, `None` if it was not written
+        diagnostics_path: Option<PathBuf>,
+    },
+}
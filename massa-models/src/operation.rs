@@ -14,9 +14,9 @@ use crate::{
 };
 use massa_hash::{Hash, HashDeserializer};
 use massa_serialization::{
-    DeserializeError, Deserializer, SerializeError, Serializer, U16VarIntDeserializer,
-    U16VarIntSerializer, U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer,
-    U64VarIntSerializer,
+    DeserializeError, Deserializer, OptionDeserializer, OptionSerializer, SerializeError,
+    Serializer, U16VarIntDeserializer, U16VarIntSerializer, U32VarIntDeserializer,
+    U32VarIntSerializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
 use massa_signature::PublicKey;
 use nom::error::{context, ErrorKind};
@@ -386,6 +386,9 @@ enum OperationTypeId {
     RollSell = 2,
     ExecuteSC = 3,
     CallSC = 4,
+    SetPayoutAddress = 5,
+    Delegate = 6,
+    Undelegate = 7,
 }
 
 /// the operation as sent in the network
@@ -629,6 +632,18 @@ pub enum OperationType {
         /// Extra coins that are spent from the caller's balance and transferred to the target
         coins: Amount,
     },
+    /// the sender designates the address receiving its deferred credits and staking rewards
+    SetPayoutAddress {
+        /// address receiving the payouts of the sender, `None` to receive them again
+        payout_address: Option<Address>,
+    },
+    /// the sender delegates the voting power of its rolls to an operator, that is drawn in its place
+    Delegate {
+        /// address producing the blocks and endorsements drawn with the rolls of the sender
+        operator: Address,
+    },
+    /// the sender takes back the voting power of its rolls from its operator
+    Undelegate {},
 }
 
 impl std::fmt::Display for OperationType {
@@ -674,6 +689,20 @@ impl std::fmt::Display for OperationType {
                 writeln!(f, "\t- max_gas:{}", max_gas)?;
                 writeln!(f, "\t- coins:{}", coins)?;
             }
+            OperationType::SetPayoutAddress { payout_address } => {
+                writeln!(f, "Set payout address:")?;
+                match payout_address {
+                    Some(payout_address) => writeln!(f, "\t- Payout address:{}", payout_address)?,
+                    None => writeln!(f, "\t- Payout address: none")?,
+                }
+            }
+            OperationType::Delegate { operator } => {
+                writeln!(f, "Delegate:")?;
+                writeln!(f, "\t- Operator:{}", operator)?;
+            }
+            OperationType::Undelegate {} => {
+                writeln!(f, "Undelegate")?;
+            }
         }
         Ok(())
     }
@@ -686,6 +715,7 @@ pub struct OperationTypeSerializer {
     vec_u8_serializer: VecU8Serializer,
     amount_serializer: AmountSerializer,
     address_serializer: AddressSerializer,
+    opt_address_serializer: OptionSerializer<Address, AddressSerializer>,
     function_name_serializer: StringSerializer<U16VarIntSerializer, u16>,
     datastore_serializer: DatastoreSerializer,
 }
@@ -699,6 +729,7 @@ impl OperationTypeSerializer {
             vec_u8_serializer: VecU8Serializer::new(),
             amount_serializer: AmountSerializer::new(),
             address_serializer: AddressSerializer::new(),
+            opt_address_serializer: OptionSerializer::new(AddressSerializer::new()),
             function_name_serializer: StringSerializer::new(U16VarIntSerializer::new()),
             datastore_serializer: DatastoreSerializer::new(),
         }
@@ -781,6 +812,21 @@ impl Serializer<OperationType> for OperationTypeSerializer {
                     .serialize(target_func, buffer)?;
                 self.vec_u8_serializer.serialize(param, buffer)?;
             }
+            OperationType::SetPayoutAddress { payout_address } => {
+                self.u32_serializer
+                    .serialize(&u32::from(OperationTypeId::SetPayoutAddress), buffer)?;
+                self.opt_address_serializer
+                    .serialize(payout_address, buffer)?;
+            }
+            OperationType::Delegate { operator } => {
+                self.u32_serializer
+                    .serialize(&u32::from(OperationTypeId::Delegate), buffer)?;
+                self.address_serializer.serialize(operator, buffer)?;
+            }
+            OperationType::Undelegate {} => {
+                self.u32_serializer
+                    .serialize(&u32::from(OperationTypeId::Undelegate), buffer)?;
+            }
         }
         Ok(())
     }
@@ -792,6 +838,7 @@ pub struct OperationTypeDeserializer {
     rolls_number_deserializer: U64VarIntDeserializer,
     max_gas_deserializer: U64VarIntDeserializer,
     address_deserializer: AddressDeserializer,
+    opt_address_deserializer: OptionDeserializer<Address, AddressDeserializer>,
     data_deserializer: VecU8Deserializer,
     amount_deserializer: AmountDeserializer,
     function_name_deserializer: StringDeserializer<U16VarIntDeserializer, u16>,
@@ -814,6 +861,7 @@ impl OperationTypeDeserializer {
             rolls_number_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
             max_gas_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
             address_deserializer: AddressDeserializer::new(),
+            opt_address_deserializer: OptionDeserializer::new(AddressDeserializer::new()),
             data_deserializer: VecU8Deserializer::new(
                 Included(0),
                 Included(max_datastore_value_length),
@@ -969,6 +1017,19 @@ impl Deserializer<OperationType> for OperationTypeDeserializer {
                     },
                 )
                 .parse(input),
+                OperationTypeId::SetPayoutAddress => {
+                    context("Failed SetPayoutAddress deserialization", |input| {
+                        self.opt_address_deserializer.deserialize(input)
+                    })
+                    .map(|payout_address| OperationType::SetPayoutAddress { payout_address })
+                    .parse(input)
+                }
+                OperationTypeId::Delegate => context("Failed Delegate deserialization", |input| {
+                    self.address_deserializer.deserialize(input)
+                })
+                .map(|operator| OperationType::Delegate { operator })
+                .parse(input),
+                OperationTypeId::Undelegate => Ok((input, OperationType::Undelegate {})),
             }
         })
         .parse(buffer)
@@ -997,6 +1058,9 @@ impl SecureShareOperation {
             OperationType::RollBuy { .. } => 0,
             OperationType::RollSell { .. } => 0,
             OperationType::Transaction { .. } => 0,
+            OperationType::SetPayoutAddress { .. } => 0,
+            OperationType::Delegate { .. } => 0,
+            OperationType::Undelegate { .. } => 0,
         }
         .saturating_add(base_operation_gas_cost)
     }
@@ -1018,6 +1082,9 @@ impl SecureShareOperation {
             OperationType::CallSC { target_addr, .. } => {
                 res.insert(*target_addr);
             }
+            OperationType::SetPayoutAddress { .. } => {}
+            OperationType::Delegate { .. } => {}
+            OperationType::Undelegate { .. } => {}
         }
         res
     }
@@ -1031,6 +1098,9 @@ impl SecureShareOperation {
             OperationType::RollSell { .. } => Amount::zero(),
             OperationType::ExecuteSC { max_coins, .. } => *max_coins,
             OperationType::CallSC { coins, .. } => *coins,
+            OperationType::SetPayoutAddress { .. } => Amount::zero(),
+            OperationType::Delegate { .. } => Amount::zero(),
+            OperationType::Undelegate { .. } => Amount::zero(),
         };
 
         // add all fees and return
@@ -1050,6 +1120,9 @@ impl SecureShareOperation {
             }
             OperationType::ExecuteSC { .. } => {}
             OperationType::CallSC { .. } => {}
+            OperationType::SetPayoutAddress { .. } => {}
+            OperationType::Delegate { .. } => {}
+            OperationType::Undelegate { .. } => {}
         }
         Ok(res)
     }
@@ -1909,4 +1982,66 @@ mod tests {
         assert_eq!(orig_operation.fee.to_string(), res_operation["fee"]);
         assert_eq!(orig_operation.expire_period, res_operation["expire_period"]);
     }
+
+    #[test]
+    #[serial]
+    fn test_set_payout_address() {
+        let payout_keypair = KeyPair::generate(0).unwrap();
+        let payout_address = Address::from_public_key(&payout_keypair.get_public_key());
+
+        for op in [
+            OperationType::SetPayoutAddress {
+                payout_address: Some(payout_address),
+            },
+            OperationType::SetPayoutAddress {
+                payout_address: None,
+            },
+        ] {
+            let mut ser_type = Vec::new();
+            OperationTypeSerializer::new()
+                .serialize(&op, &mut ser_type)
+                .unwrap();
+            let (rest, res_type) = OperationTypeDeserializer::new(
+                MAX_DATASTORE_VALUE_LENGTH,
+                MAX_FUNCTION_NAME_LENGTH,
+                MAX_PARAMETERS_SIZE,
+                MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+                MAX_OPERATION_DATASTORE_KEY_LENGTH,
+                MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+            )
+            .deserialize::<DeserializeError>(&ser_type)
+            .unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(res_type, op);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_delegation_operations() {
+        let operator_keypair = KeyPair::generate(0).unwrap();
+        let operator = Address::from_public_key(&operator_keypair.get_public_key());
+
+        for op in [
+            OperationType::Delegate { operator },
+            OperationType::Undelegate {},
+        ] {
+            let mut ser_type = Vec::new();
+            OperationTypeSerializer::new()
+                .serialize(&op, &mut ser_type)
+                .unwrap();
+            let (rest, res_type) = OperationTypeDeserializer::new(
+                MAX_DATASTORE_VALUE_LENGTH,
+                MAX_FUNCTION_NAME_LENGTH,
+                MAX_PARAMETERS_SIZE,
+                MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+                MAX_OPERATION_DATASTORE_KEY_LENGTH,
+                MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+            )
+            .deserialize::<DeserializeError>(&ser_type)
+            .unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(res_type, op);
+        }
+    }
 }
@@ -0,0 +1,260 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Pagination of the controller queries returning long lists.
+//!
+//! The items of a paginated list are ordered by a `PageKey`: a slot, then the bytes of an identifier
+//! (block id, address...). A page ends with an opaque `PageCursor` encoding the key of its last item,
+//! and the next page starts strictly after it. As the cursor holds the position itself and not a
+//! server-side session, it stays valid across reconnects and node restarts: items inserted or removed
+//! in between are simply seen or skipped.
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ModelsError,
+    slot::{Slot, SLOT_KEY_SIZE},
+};
+
+/// Maximum number of items in a page
+pub const MAX_PAGE_LIMIT: usize = 1000;
+
+/// Version of the encoding of the cursors
+const PAGE_CURSOR_VERSION: u8 = 0;
+
+/// Position of an item in a paginated list
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PageKey {
+    /// slot of the item
+    pub slot: Slot,
+    /// bytes of the identifier of the item, ordering the items of a slot
+    pub id: Vec<u8>,
+}
+
+impl PageKey {
+    /// Create a new `PageKey`
+    pub fn new(slot: Slot, id: impl Into<Vec<u8>>) -> Self {
+        PageKey {
+            slot,
+            id: id.into(),
+        }
+    }
+}
+
+/// Opaque position after which the next page starts, serialized as a base58check string
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PageCursor(PageKey);
+
+impl PageCursor {
+    /// Key of the last item of the page the cursor was returned with
+    pub fn key(&self) -> &PageKey {
+        &self.0
+    }
+}
+
+impl From<PageKey> for PageCursor {
+    fn from(key: PageKey) -> Self {
+        PageCursor(key)
+    }
+}
+
+impl Display for PageCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut bytes = Vec::with_capacity(1 + SLOT_KEY_SIZE + self.0.id.len());
+        bytes.push(PAGE_CURSOR_VERSION);
+        bytes.extend_from_slice(&self.0.slot.to_bytes_key());
+        bytes.extend_from_slice(&self.0.id);
+        write!(f, "{}", bs58::encode(bytes).with_check().into_string())
+    }
+}
+
+impl FromStr for PageCursor {
+    type Err = ModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s).with_check(None).into_vec().map_err(|err| {
+            ModelsError::DeserializeError(format!("invalid page cursor: {}", err))
+        })?;
+        match bytes.split_first() {
+            Some((&PAGE_CURSOR_VERSION, key)) if key.len() >= SLOT_KEY_SIZE => {
+                let (slot, id) = key.split_at(SLOT_KEY_SIZE);
+                let slot = Slot::from_bytes_key(slot.try_into().expect("slot key size checked"));
+                Ok(PageCursor(PageKey::new(slot, id)))
+            }
+            _ => Err(ModelsError::DeserializeError(
+                "invalid page cursor: unknown version or truncated key".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for PageCursor {
+    type Error = ModelsError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        PageCursor::from_str(&value)
+    }
+}
+
+impl From<PageCursor> for String {
+    fn from(cursor: PageCursor) -> Self {
+        cursor.to_string()
+    }
+}
+
+/// Request of a page of a list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageRequest {
+    /// cursor returned with the previous page, `None` for the first page
+    pub cursor: Option<PageCursor>,
+    /// maximum number of items of the page, capped to `MAX_PAGE_LIMIT`
+    pub limit: usize,
+}
+
+impl PageRequest {
+    /// Request the first page of a list
+    pub fn first(limit: usize) -> Self {
+        PageRequest {
+            cursor: None,
+            limit,
+        }
+    }
+
+    /// Request the page following a cursor
+    pub fn after(cursor: PageCursor, limit: usize) -> Self {
+        PageRequest {
+            cursor: Some(cursor),
+            limit,
+        }
+    }
+
+    /// Slot from which the items of the page must be looked up
+    pub fn start_slot(&self) -> Option<Slot> {
+        self.cursor.as_ref().map(|cursor| cursor.key().slot)
+    }
+
+    /// Number of items of the page, between 1 and `MAX_PAGE_LIMIT`
+    pub fn effective_limit(&self) -> usize {
+        self.limit.clamp(1, MAX_PAGE_LIMIT)
+    }
+}
+
+/// Page of a list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageResponse<T> {
+    /// items of the page
+    pub items: Vec<T>,
+    /// cursor to request the next page with, `None` if this page is the last one
+    pub next_cursor: Option<PageCursor>,
+}
+
+impl<T> PageResponse<T> {
+    /// Build the requested page from all the items of a list, sorted by key.
+    /// The items up to the cursor of the request are skipped.
+    pub fn from_sorted<I>(items: I, request: &PageRequest) -> Self
+    where
+        I: IntoIterator<Item = (PageKey, T)>,
+    {
+        let limit = request.effective_limit();
+        let mut items = items
+            .into_iter()
+            .skip_while(|(key, _)| {
+                request
+                    .cursor
+                    .as_ref()
+                    .map_or(false, |cursor| key <= cursor.key())
+            })
+            .peekable();
+        let mut page = Vec::with_capacity(limit);
+        let mut last_key = None;
+        while page.len() < limit {
+            let Some((key, item)) = items.next() else {
+                break;
+            };
+            page.push(item);
+            last_key = Some(key);
+        }
+        let next_cursor = match (items.peek(), last_key) {
+            (Some(_), Some(key)) => Some(PageCursor::from(key)),
+            _ => None,
+        };
+        PageResponse {
+            items: page,
+            next_cursor,
+        }
+    }
+
+    /// Convert the items of the page, keeping its cursor
+    pub fn map<U, F>(self, f: F) -> PageResponse<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        PageResponse {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyed_items() -> Vec<(PageKey, u64)> {
+        (0..5u64)
+            .flat_map(|period| {
+                [
+                    (PageKey::new(Slot::new(period, 0), [1u8]), period * 10),
+                    (PageKey::new(Slot::new(period, 0), [2u8]), period * 10 + 1),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_page_cursor_roundtrip() {
+        let cursor = PageCursor::from(PageKey::new(Slot::new(12, 3), vec![7u8, 8, 9]));
+        let encoded = cursor.to_string();
+        assert_eq!(PageCursor::from_str(&encoded).unwrap(), cursor);
+        let json = serde_json::to_string(&cursor).unwrap();
+        assert_eq!(json, format!("\"{}\"", encoded));
+        assert_eq!(serde_json::from_str::<PageCursor>(&json).unwrap(), cursor);
+        assert!(PageCursor::from_str("not a cursor").is_err());
+        assert!(PageCursor::from_str(&bs58::encode([0u8, 1]).with_check().into_string()).is_err());
+    }
+
+    #[test]
+    fn test_paginate_sorted_items() {
+        let mut request = PageRequest::first(3);
+        let mut pages = Vec::new();
+        loop {
+            let page = PageResponse::from_sorted(keyed_items(), &request);
+            pages.push(page.items.clone());
+            match page.next_cursor {
+                // the cursor is serialized between the requests, as by a client reconnecting
+                Some(cursor) => {
+                    let cursor = PageCursor::from_str(&cursor.to_string()).unwrap();
+                    request = PageRequest::after(cursor, 3);
+                }
+                None => break,
+            }
+        }
+        assert_eq!(
+            pages,
+            vec![vec![0, 1, 10], vec![11, 20, 21], vec![30, 31, 40], vec![41]]
+        );
+        assert_eq!(request.start_slot(), Some(Slot::new(4, 0)));
+
+        // an exhausted list ends without cursor
+        let page = PageResponse::from_sorted(keyed_items(), &PageRequest::first(10));
+        assert_eq!(page.items.len(), 10);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(PageRequest::first(0).effective_limit(), 1);
+        assert_eq!(
+            PageRequest::first(usize::MAX).effective_limit(),
+            MAX_PAGE_LIMIT
+        );
+    }
+}
@@ -20,6 +20,9 @@ pub struct ExecutionStats {
     pub active_cursor: Slot,
     /// final execution cursor slot
     pub final_cursor: Slot,
+    /// statistics of the proof-of-stake final state
+    #[serde(default)]
+    pub pos_stats: PoSStats,
 }
 
 impl std::fmt::Display for ExecutionStats {
@@ -47,6 +50,74 @@ impl std::fmt::Display for ExecutionStats {
         )?;
         writeln!(f, "\tActive cursor: {}", self.active_cursor)?;
         writeln!(f, "\tFinal cursor: {}", self.final_cursor)?;
+        write!(f, "{}", self.pos_stats)?;
+        Ok(())
+    }
+}
+
+/// Proof-of-stake final state statistics, measuring the maintenance cost of its XOR state hash
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoSStats {
+    /// number of slots applied to the PoS final state since the node started
+    pub applied_slot_count: u64,
+    /// upper bound of the number of state hash computations caused by the PoS writes since the node started:
+    /// a written entry XORs out the hash of its previous value and XORs in the hash of the new one,
+    /// a deleted entry XORs out the hash of its previous value
+    pub hash_computation_count: u64,
+    /// number of pending deferred credits at the last sample
+    pub deferred_credit_count: u64,
+    /// number of slots with pending deferred credits at the last sample
+    pub deferred_credit_slot_count: u64,
+    /// number of pending deferred credits sampled at the end of each cycle, as (cycle, count), oldest first
+    pub deferred_credit_count_history: Vec<(u64, u64)>,
+}
+
+impl PoSStats {
+    /// Average number of state hash computations per applied slot
+    pub fn average_hash_computations_per_slot(&self) -> f64 {
+        if self.applied_slot_count == 0 {
+            return 0.0;
+        }
+        self.hash_computation_count as f64 / self.applied_slot_count as f64
+    }
+
+    /// Average number of pending deferred credits per slot having credits, at the last sample
+    pub fn average_credits_per_slot(&self) -> f64 {
+        if self.deferred_credit_slot_count == 0 {
+            return 0.0;
+        }
+        self.deferred_credit_count as f64 / self.deferred_credit_slot_count as f64
+    }
+}
+
+impl std::fmt::Display for PoSStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "PoS stats:")?;
+        writeln!(f, "\tApplied slot count: {}", self.applied_slot_count)?;
+        writeln!(
+            f,
+            "\tHash computation count: {} ({:.2} per slot)",
+            self.hash_computation_count,
+            self.average_hash_computations_per_slot()
+        )?;
+        writeln!(
+            f,
+            "\tPending deferred credits: {} over {} slots ({:.2} per slot)",
+            self.deferred_credit_count,
+            self.deferred_credit_slot_count,
+            self.average_credits_per_slot()
+        )?;
+        if !self.deferred_credit_count_history.is_empty() {
+            writeln!(
+                f,
+                "\tPending deferred credits per cycle: {}",
+                self.deferred_credit_count_history
+                    .iter()
+                    .map(|(cycle, count)| format!("{}: {}", cycle, count))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
         Ok(())
     }
 }
@@ -91,6 +162,17 @@ pub struct ConsensusStats {
     pub stale_block_count: u64,
     ///  number of actives cliques
     pub clique_count: u64,
+    /// number of active cliques left out of `get_cliques` because they are dominated by the blockclique
+    pub pruned_clique_count: u64,
+    /// number of block registrations dropped since launch because the block was registered recently
+    pub duplicate_block_count: u64,
+    /// number of headers currently waiting for their block
+    pub header_only_count: u64,
+    /// number of headers dropped since launch because their block never arrived
+    pub expired_header_count: u64,
+    /// number of final slots not yet applied by execution at the latest slot tick, `None` if not monitored
+    #[serde(default)]
+    pub execution_finality_gap: Option<u64>,
 }
 
 impl std::fmt::Display for ConsensusStats {
@@ -109,6 +191,17 @@ impl std::fmt::Display for ConsensusStats {
         writeln!(f, "\tFinal block count: {}", self.final_block_count)?;
         writeln!(f, "\tStale block count: {}", self.stale_block_count)?;
         writeln!(f, "\tClique count: {}", self.clique_count)?;
+        writeln!(f, "\tPruned clique count: {}", self.pruned_clique_count)?;
+        writeln!(
+            f,
+            "\tDuplicate block registrations: {}",
+            self.duplicate_block_count
+        )?;
+        writeln!(f, "\tHeader-only blocks: {}", self.header_only_count)?;
+        writeln!(f, "\tExpired headers: {}", self.expired_header_count)?;
+        if let Some(gap) = self.execution_finality_gap {
+            writeln!(f, "\tFinal slots not yet executed: {}", gap)?;
+        }
         Ok(())
     }
 }
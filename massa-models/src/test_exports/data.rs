@@ -97,6 +97,7 @@ pub fn gen_block_headers_for_denunciation(
         operation_merkle_root: Hash::compute_from("mno".as_bytes()),
         endorsements: vec![s_endorsement_1.clone()],
         denunciations: vec![],
+        extensions: Vec::new(),
     };
 
     // create header
@@ -116,6 +117,7 @@ pub fn gen_block_headers_for_denunciation(
         operation_merkle_root: Hash::compute_from("mno".as_bytes()),
         endorsements: vec![s_endorsement_1.clone()],
         denunciations: vec![],
+        extensions: Vec::new(),
     };
 
     // create header
@@ -135,6 +137,7 @@ pub fn gen_block_headers_for_denunciation(
         operation_merkle_root: Hash::compute_from("mno".as_bytes()),
         endorsements: vec![s_endorsement_1],
         denunciations: vec![],
+        extensions: Vec::new(),
     };
 
     // create header
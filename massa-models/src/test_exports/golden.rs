@@ -0,0 +1,76 @@
+//! Golden vectors: canonical serializations checked into the repository.
+//!
+//! Each fixture is a hex file under the `golden` directory of the crate that owns the serialized type.
+//! The tests compare their serialization to the fixture, so that any change of the format shows up
+//! as an explicit fixture update in the diff. After an intended format change, run the tests with
+//! `MASSA_UPDATE_GOLDEN_VECTORS=1` to rewrite the fixtures.
+
+use std::path::{Path, PathBuf};
+
+/// Environment variable that makes `assert_golden_vector` rewrite the fixtures instead of checking them
+pub const UPDATE_GOLDEN_VECTORS_ENV: &str = "MASSA_UPDATE_GOLDEN_VECTORS";
+
+/// Path of the fixture `name` in the `golden` directory of the crate at `manifest_dir`
+pub fn golden_vector_path(manifest_dir: &str, name: &str) -> PathBuf {
+    Path::new(manifest_dir)
+        .join("golden")
+        .join(format!("{}.hex", name))
+}
+
+/// Read the bytes of the fixture `name` in the `golden` directory of the crate at `manifest_dir`
+///
+/// The whitespace of the hex file is ignored, so that long vectors can be wrapped.
+pub fn read_golden_vector(manifest_dir: &str, name: &str) -> Vec<u8> {
+    let path = golden_vector_path(manifest_dir, name);
+    let content = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("could not read golden vector {}: {}", path.display(), err));
+    let digits: Vec<u8> = content
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    assert!(
+        digits.len() % 2 == 0,
+        "golden vector {} has an odd number of hex digits",
+        path.display()
+    );
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).expect("non-ASCII hex digits");
+            u8::from_str_radix(pair, 16).unwrap_or_else(|_| {
+                panic!(
+                    "golden vector {} contains invalid hex {:?}",
+                    path.display(),
+                    pair
+                )
+            })
+        })
+        .collect()
+}
+
+/// Check `bytes` against the fixture `name` in the `golden` directory of the crate at `manifest_dir`
+///
+/// If `MASSA_UPDATE_GOLDEN_VECTORS` is set, the fixture is rewritten with `bytes` instead.
+pub fn assert_golden_vector(manifest_dir: &str, name: &str, bytes: &[u8]) {
+    if std::env::var_os(UPDATE_GOLDEN_VECTORS_ENV).is_some() {
+        let path = golden_vector_path(manifest_dir, name);
+        let lines: Vec<String> = bytes
+            .chunks(32)
+            .map(|chunk| chunk.iter().map(|byte| format!("{:02x}", byte)).collect())
+            .collect();
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap_or_else(|err| {
+            panic!("could not write golden vector {}: {}", path.display(), err)
+        });
+        return;
+    }
+    let expected = read_golden_vector(manifest_dir, name);
+    assert!(
+        bytes == expected.as_slice(),
+        "serialization does not match golden vector {}, \
+        run the tests with {}=1 to update it if the format change is intended\nexpected: {:02x?}\n     got: {:02x?}",
+        name,
+        UPDATE_GOLDEN_VECTORS_ENV,
+        expected,
+        bytes
+    );
+}
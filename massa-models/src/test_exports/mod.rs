@@ -1,5 +1,7 @@
 mod data;
+mod golden;
 mod tools;
 
 pub use data::*;
+pub use golden::*;
 pub use tools::*;
@@ -193,6 +193,16 @@ impl Version {
     pub fn is_compatible(&self, other: &Version) -> bool {
         self.instance == other.instance && self.major == other.major
     }
+
+    /// major part of the version, incremented by breaking changes
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    /// minor part of the version, incremented by backward compatible changes
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
 }
 
 impl fmt::Display for Version {
@@ -0,0 +1,58 @@
+//! Startup check of the initial supply of the network.
+//!
+//! The initial balances, the value of the initial rolls and the initial deferred credits are summed
+//! and checked against the configured maximum supply before the node starts, so that a network
+//! inflating its supply from genesis is refused instead of being noticed later by monitoring.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use massa_ledger_exports::LedgerEntry;
+use massa_models::address::Address;
+use massa_models::amount::Amount;
+use massa_models::config::constants::ROLL_PRICE;
+use massa_pos_exports::{
+    check_genesis_supply, read_initial_deferred_credits, read_initial_rolls, DeferredCredits,
+    GenesisSupply,
+};
+
+/// Read the initial ledger, rolls and deferred credits files and check their total against `max_supply`
+///
+/// # Returns
+/// The breakdown of the initial supply, or a detailed error if a file cannot be read or if the supply is exceeded
+pub fn check_initial_supply(
+    initial_ledger_path: &Path,
+    initial_rolls_path: &Path,
+    initial_deferred_credits_path: Option<&Path>,
+    max_supply: Amount,
+) -> Result<GenesisSupply, String> {
+    let initial_ledger: HashMap<Address, LedgerEntry> = serde_json::from_str(
+        &std::fs::read_to_string(initial_ledger_path).map_err(|err| {
+            format!(
+                "error loading initial ledger file {}: {}",
+                initial_ledger_path.display(),
+                err
+            )
+        })?,
+    )
+    .map_err(|err| {
+        format!(
+            "error parsing initial ledger file {}: {}",
+            initial_ledger_path.display(),
+            err
+        )
+    })?;
+    let initial_rolls = read_initial_rolls(initial_rolls_path).map_err(|err| err.to_string())?;
+    let initial_deferred_credits = match initial_deferred_credits_path {
+        Some(path) => read_initial_deferred_credits(path).map_err(|err| err.to_string())?,
+        None => DeferredCredits::new(),
+    };
+    check_genesis_supply(
+        initial_ledger.values().map(|entry| entry.balance),
+        &initial_rolls,
+        &initial_deferred_credits,
+        ROLL_PRICE,
+        max_supply,
+    )
+    .map_err(|err| err.to_string())
+}
@@ -5,10 +5,14 @@
 #![warn(unused_crate_dependencies)]
 extern crate massa_logging;
 
+use crate::genesis_supply::check_initial_supply;
 #[cfg(feature = "op_spammer")]
 use crate::operation_injector::start_operation_injector;
+use crate::parquet_export::{start_parquet_export, ParquetExportStopper};
+use crate::self_test::run_serialization_self_test;
 use crate::settings::SETTINGS;
 use crate::survey::MassaSurvey;
+use crate::verify_bootstrap::{report_graph_diff, verify_bootstrap_against};
 
 use clap::{crate_version, Parser};
 use crossbeam_channel::TryRecvError;
@@ -19,10 +23,12 @@ use massa_async_pool::AsyncPoolConfig;
 use massa_bootstrap::BootstrapError;
 use massa_bootstrap::{
     get_state, start_bootstrap_server, BootstrapConfig, BootstrapManager, BootstrapTcpListener,
-    DefaultConnector,
+    DefaultConnector, ResyncTarget,
 };
+use massa_channel::event_bus::MassaEventBus;
 use massa_channel::receiver::MassaReceiver;
 use massa_channel::MassaChannel;
+use massa_consensus_exports::checkpoint::ConsensusCheckpoint;
 use massa_consensus_exports::events::ConsensusEvent;
 use massa_consensus_exports::{
     ConsensusBroadcasts, ConsensusChannels, ConsensusConfig, ConsensusManager,
@@ -58,20 +64,21 @@ use massa_models::config::constants::{
     MAX_FUNCTION_NAME_LENGTH, MAX_GAS_PER_BLOCK, MAX_LEDGER_CHANGES_COUNT, MAX_LISTENERS_PER_PEER,
     MAX_OPERATIONS_PER_BLOCK, MAX_OPERATIONS_PER_MESSAGE, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
     MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
-    MAX_OPERATION_STORAGE_TIME, MAX_PARAMETERS_SIZE, MAX_PEERS_IN_ANNOUNCEMENT_LIST,
-    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, MAX_SIZE_CHANNEL_COMMANDS_CONNECTIVITY,
-    MAX_SIZE_CHANNEL_COMMANDS_PEERS, MAX_SIZE_CHANNEL_COMMANDS_PEER_TESTERS,
-    MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_BLOCKS,
+    MAX_OPERATION_STORAGE_TIME, MAX_PARAMETERS_SIZE, MAX_PARENT_AGE, MAX_PARENT_EXECUTION_LAG,
+    MAX_PEERS_IN_ANNOUNCEMENT_LIST, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
+    MAX_SIZE_CHANNEL_COMMANDS_CONNECTIVITY, MAX_SIZE_CHANNEL_COMMANDS_PEERS,
+    MAX_SIZE_CHANNEL_COMMANDS_PEER_TESTERS, MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_BLOCKS,
     MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_ENDORSEMENTS,
     MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_OPERATIONS, MAX_SIZE_CHANNEL_COMMANDS_RETRIEVAL_BLOCKS,
     MAX_SIZE_CHANNEL_COMMANDS_RETRIEVAL_ENDORSEMENTS,
     MAX_SIZE_CHANNEL_COMMANDS_RETRIEVAL_OPERATIONS, MAX_SIZE_CHANNEL_NETWORK_TO_BLOCK_HANDLER,
     MAX_SIZE_CHANNEL_NETWORK_TO_ENDORSEMENT_HANDLER, MAX_SIZE_CHANNEL_NETWORK_TO_OPERATION_HANDLER,
     MAX_SIZE_CHANNEL_NETWORK_TO_PEER_HANDLER, MIP_STORE_STATS_BLOCK_CONSIDERED,
-    OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE, POS_MISS_RATE_DEACTIVATION_THRESHOLD,
-    POS_SAVED_CYCLES, PROTOCOL_CONTROLLER_CHANNEL_SIZE, PROTOCOL_EVENT_CHANNEL_SIZE,
-    ROLL_COUNT_TO_SLASH_ON_DENUNCIATION, ROLL_PRICE, SELECTOR_DRAW_CACHE_SIZE, T0, THREAD_COUNT,
-    VERSION,
+    OPERATION_VALIDITY_PERIODS, PAYOUT_PAUSE_DELAY_PERIODS, PERIODS_PER_CYCLE,
+    POS_MISS_RATE_DEACTIVATION_THRESHOLD, POS_SAVED_CYCLES, PROTOCOL_CONTROLLER_CHANNEL_SIZE,
+    PROTOCOL_EVENT_CHANNEL_SIZE, ROLL_COUNT_TO_SLASH_ON_DENUNCIATION, ROLL_PRICE,
+    ROLL_SELL_CREDIT_SPLIT_PARTS, ROLL_SELL_CREDIT_SPLIT_THRESHOLD, SELECTOR_DRAW_CACHE_SIZE, T0,
+    THREAD_COUNT, VERSION,
 };
 use massa_models::config::{
     BASE_OPERATION_GAS_COST, CHAINID, KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
@@ -82,10 +89,12 @@ use massa_models::config::{
 use massa_models::slot::Slot;
 use massa_pool_exports::{PoolBroadcasts, PoolChannels, PoolConfig, PoolManager};
 use massa_pool_worker::start_pool_controller;
-use massa_pos_exports::{PoSConfig, SelectorConfig, SelectorManager};
+use massa_pos_exports::{read_deferred_credits, PoSConfig, SelectorConfig, SelectorManager};
 use massa_pos_worker::start_selector_worker;
 use massa_protocol_exports::{ProtocolConfig, ProtocolManager, TransportType};
-use massa_protocol_worker::{create_protocol_controller, start_protocol_controller};
+use massa_protocol_worker::{
+    create_protocol_controller, load_or_create_node_keypair, start_protocol_controller,
+};
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use massa_time::MassaTime;
@@ -97,6 +106,7 @@ use num::rational::Ratio;
 use parking_lot::RwLock;
 use settings::GrpcSettings;
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Condvar, Mutex};
@@ -108,10 +118,14 @@ use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::filter::{filter_fn, LevelFilter};
 
+mod genesis_supply;
 #[cfg(feature = "op_spammer")]
 mod operation_injector;
+mod parquet_export;
+mod self_test;
 mod settings;
 mod survey;
+mod verify_bootstrap;
 
 async fn launch(
     args: &Args,
@@ -133,6 +147,7 @@ async fn launch(
     Option<massa_grpc::server::StopHandle>,
     MetricsStopper,
     MassaSurveyStopper,
+    ParquetExportStopper,
 ) {
     let now = MassaTime::now();
 
@@ -142,6 +157,33 @@ async fn launch(
         }
     }
 
+    // check that consensus and PoS data is encoded as expected before touching any state
+    if let Err(report) = run_serialization_self_test() {
+        for failure in report.iter() {
+            error!("serialization self-test failed: {}", failure);
+        }
+        panic!(
+            "serialization self-test failed, aborting startup:\n{}",
+            report.join("\n")
+        );
+    }
+
+    // refuse to start a network whose initial balances, rolls and deferred credits exceed the maximum supply
+    if let Some(max_supply) = SETTINGS.ledger.max_supply {
+        match check_initial_supply(
+            &SETTINGS.ledger.initial_ledger_path,
+            &SETTINGS.selector.initial_rolls_path,
+            SETTINGS.ledger.initial_deferred_credits_path.as_deref(),
+            max_supply,
+        ) {
+            Ok(supply) => debug!("initial supply check passed:\n{}", supply),
+            Err(err) => {
+                error!("initial supply check failed: {}", err);
+                panic!("initial supply check failed, aborting startup:\n{}", err);
+            }
+        }
+    }
+
     // Storage shared by multiple components.
     let shared_storage: Storage = Storage::create_root();
 
@@ -206,7 +248,11 @@ async fn launch(
 
     // Remove current disk ledger if there is one and we don't want to restart from snapshot
     // NOTE: this is temporary, since we cannot currently handle bootstrap from remaining ledger
-    if args.keep_ledger || args.restart_from_snapshot_at_period.is_some() {
+    if args.keep_ledger
+        || args.fast_sync
+        || args.resync.is_some()
+        || args.restart_from_snapshot_at_period.is_some()
+    {
         info!("Loading old ledger for next episode");
     } else {
         if SETTINGS.ledger.disk_ledger_path.exists() {
@@ -217,6 +263,10 @@ async fn launch(
             std::fs::remove_dir_all(SETTINGS.execution.hd_cache_path.clone())
                 .expect("disk hd cache delete failed");
         }
+        if SETTINGS.execution.paid_credits_history_path.exists() {
+            std::fs::remove_dir_all(SETTINGS.execution.paid_credits_history_path.clone())
+                .expect("paid credits history delete failed");
+        }
     }
 
     let db_config = MassaDBConfig {
@@ -227,21 +277,40 @@ async fn launch(
         thread_count: THREAD_COUNT,
     };
     let db = Arc::new(RwLock::new(
-        Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        Box::new(MassaDB::new(db_config.clone())) as Box<(dyn MassaDBController + 'static)>
     ));
 
+    // the consensus checkpoint of the previous run is readable before the graph is bootstrapped again
+    match ConsensusCheckpoint::load(&db) {
+        Ok(Some(checkpoint)) => info!(
+            "Consensus checkpoint of the previous run at slot {:?}: latest final blocks {:?}",
+            checkpoint.slot, checkpoint.latest_final_blocks_periods
+        ),
+        Ok(None) => {}
+        Err(err) => warn!(
+            "could not read the consensus checkpoint of the previous run: {}",
+            err
+        ),
+    }
+
     // Create final ledger
     let ledger = FinalLedger::new(ledger_config.clone(), db.clone());
 
+    // bus on which the workers publish domain events, subscribers are added without touching producers
+    let node_event_bus = MassaEventBus::new("node_event".to_string());
+
     // launch selector worker
-    let (selector_manager, selector_controller) = start_selector_worker(SelectorConfig {
-        max_draw_cache: SELECTOR_DRAW_CACHE_SIZE,
-        channel_size: CHANNEL_SIZE,
-        thread_count: THREAD_COUNT,
-        endorsement_count: ENDORSEMENT_COUNT,
-        periods_per_cycle: PERIODS_PER_CYCLE,
-        genesis_address: Address::from_public_key(&GENESIS_KEY.get_public_key()),
-    })
+    let (selector_manager, selector_controller) = start_selector_worker(
+        SelectorConfig {
+            max_draw_cache: SELECTOR_DRAW_CACHE_SIZE,
+            channel_size: CHANNEL_SIZE,
+            thread_count: THREAD_COUNT,
+            endorsement_count: ENDORSEMENT_COUNT,
+            periods_per_cycle: PERIODS_PER_CYCLE,
+            genesis_address: Address::from_public_key(&GENESIS_KEY.get_public_key()),
+        },
+        node_event_bus.clone(),
+    )
     .expect("could not start selector worker");
 
     // Creates an empty default store
@@ -254,52 +323,91 @@ async fn launch(
     };
     // Ratio::new_raw(*SETTINGS.versioning.warn_announced_version_ratio, 100),
 
+    // Read the pending deferred credits before the final state resets them, to write them back once it is created
+    let recovered_deferred_credits = args.recover_deferred_credits.then(|| {
+        read_deferred_credits(&db, &final_state_config.pos_config)
+            .expect("could not read the deferred credits to recover")
+    });
+
     // Create final state, either from a snapshot, or from scratch
-    let final_state: Arc<RwLock<dyn FinalStateController>> = Arc::new(parking_lot::RwLock::new(
-        match args.restart_from_snapshot_at_period {
-            Some(last_start_period) => {
-                // The node is restarted from a snapshot:
-                // MIP store by reading from the db as it must have been updated by the massa ledger editor
-                // (to shift transitions that might have happened during the network shutdown)
-                // Note that FinalState::new_derived_from_snapshot will check if MIP store is consistent
-                // No Bootstrap are expected after this
-                let mip_store: MipStore = MipStore::try_from_db(db.clone(), mip_stats_config)
-                    .expect("MIP store creation failed");
-                debug!("After read from db, Mip store: {:?}", mip_store);
-
-                FinalState::new_derived_from_snapshot(
-                    db.clone(),
-                    final_state_config,
-                    Box::new(ledger),
-                    selector_controller.clone(),
-                    mip_store,
-                    last_start_period,
-                )
-                .expect("could not init final state")
-            }
-            None => {
-                // The node is started in a normal way
-                // Read the mip list supported by the current software
-                // The resulting MIP store will likely be updated by the bootstrap process in order
-                // to get the latest information for the MIP store (new states, votes...)
-
-                let mip_list = get_mip_list();
-                debug!("MIP list: {:?}", mip_list);
-                let mip_store = MipStore::try_from((mip_list, mip_stats_config))
-                    .expect("mip store creation failed");
-
-                FinalState::new(
-                    db.clone(),
-                    final_state_config,
-                    Box::new(ledger),
-                    selector_controller.clone(),
-                    mip_store,
-                    true,
-                )
-                .expect("could not init final state")
-            }
-        },
-    ));
+    let mut final_state = match args.restart_from_snapshot_at_period {
+        Some(last_start_period) => {
+            // The node is restarted from a snapshot:
+            // MIP store by reading from the db as it must have been updated by the massa ledger editor
+            // (to shift transitions that might have happened during the network shutdown)
+            // Note that FinalState::new_derived_from_snapshot will check if MIP store is consistent
+            // No Bootstrap are expected after this
+            let mip_store: MipStore = MipStore::try_from_db(db.clone(), mip_stats_config.clone())
+                .expect("MIP store creation failed");
+            debug!("After read from db, Mip store: {:?}", mip_store);
+
+            FinalState::new_derived_from_snapshot(
+                db.clone(),
+                final_state_config.clone(),
+                Box::new(ledger),
+                selector_controller.clone(),
+                mip_store,
+                last_start_period,
+            )
+            .expect("could not init final state")
+        }
+        None => {
+            // The node is started in a normal way
+            // Read the mip list supported by the current software
+            // The resulting MIP store will likely be updated by the bootstrap process in order
+            // to get the latest information for the MIP store (new states, votes...)
+
+            let mip_list = get_mip_list();
+            debug!("MIP list: {:?}", mip_list);
+            let mip_store = MipStore::try_from((mip_list, mip_stats_config.clone()))
+                .expect("mip store creation failed");
+
+            // When fast syncing, the final state is kept as is to be checked against the checkpoint,
+            // and when resynchronizing a part of it, the other parts are kept
+            FinalState::new(
+                db.clone(),
+                final_state_config.clone(),
+                Box::new(ledger),
+                selector_controller.clone(),
+                mip_store,
+                !args.fast_sync && args.resync.is_none(),
+            )
+            .expect("could not init final state")
+        }
+    };
+
+    if let Some(recovered_deferred_credits) = recovered_deferred_credits {
+        let recovery = final_state
+            .recover_deferred_credits(recovered_deferred_credits)
+            .expect("could not recover the deferred credits");
+        info!(
+            "Recovered {} deferred credits ({} coins), {} were already present",
+            recovery.restored_count(),
+            recovery.restored_amount(),
+            recovery.already_present
+        );
+        for conflict in &recovery.conflicts {
+            warn!(
+                "Deferred credit of {} at slot {} not recovered: the state has {} instead of {}",
+                conflict.address, conflict.slot, conflict.current, conflict.recovered
+            );
+        }
+        if !recovery.expired.is_empty() {
+            warn!(
+                "Deferred credits at already final slots not recovered: {}",
+                recovery.expired
+            );
+        }
+    }
+    let slot_alert_threshold = SETTINGS.ledger.deferred_credits_slot_alert_threshold;
+    final_state
+        .get_pos_state()
+        .credits_watcher
+        .set_slot_alert_threshold(
+            (!slot_alert_threshold.is_zero()).then_some(slot_alert_threshold),
+        );
+    let final_state: Arc<RwLock<dyn FinalStateController>> =
+        Arc::new(parking_lot::RwLock::new(final_state));
 
     let mip_store = final_state.read().get_mip_store().clone();
 
@@ -320,6 +428,13 @@ async fn launch(
         max_clock_delta: SETTINGS.bootstrap.max_clock_delta,
         cache_duration: SETTINGS.bootstrap.cache_duration,
         keep_ledger: args.keep_ledger,
+        fast_sync_checkpoint: args.fast_sync.then(|| {
+            SETTINGS
+                .bootstrap
+                .fast_sync_checkpoint
+                .expect("--fast-sync requires a fast_sync_checkpoint in the bootstrap settings")
+        }),
+        resync: args.resync,
         max_listeners_per_peer: MAX_LISTENERS_PER_PEER as u32,
         max_simultaneous_bootstraps: SETTINGS.bootstrap.max_simultaneous_bootstraps,
         per_ip_min_interval: SETTINGS.bootstrap.per_ip_min_interval,
@@ -382,6 +497,33 @@ async fn launch(
         panic!("critical: db is not valid after bootstrap");
     }
 
+    if let Some(server) = args.verify_bootstrap_against {
+        let Some(graph) = bootstrap_state.graph.as_ref() else {
+            error!("No block graph was bootstrapped, nothing to verify");
+            process::exit(1);
+        };
+        let final_slot = final_state.read().get_slot();
+        let matches = match verify_bootstrap_against(
+            server,
+            graph,
+            final_slot,
+            &bootstrap_config,
+            final_state_config,
+            db_config,
+            selector_controller.clone(),
+            mip_stats_config,
+            sig_int_toggled.clone(),
+            massa_metrics.clone(),
+        ) {
+            Ok(diff) => report_graph_diff(server, &diff),
+            Err(err) => {
+                error!("Could not verify the bootstrap against {}: {}", server, err);
+                false
+            }
+        };
+        process::exit(if matches { 0 } else { 1 });
+    }
+
     if args.restart_from_snapshot_at_period.is_none() {
         final_state.write().recompute_caches();
 
@@ -460,6 +602,11 @@ async fn launch(
         lru_cache_size: SETTINGS.execution.lru_cache_size,
         hd_cache_size: SETTINGS.execution.hd_cache_size,
         snip_amount: SETTINGS.execution.snip_amount,
+        paid_credits_history_path: SETTINGS.execution.paid_credits_history_path.clone(),
+        paid_credits_history_periods: SETTINGS.execution.paid_credits_history_periods,
+        roll_sell_credit_split_threshold: ROLL_SELL_CREDIT_SPLIT_THRESHOLD,
+        roll_sell_credit_split_parts: ROLL_SELL_CREDIT_SPLIT_PARTS,
+        payout_pause_delay_periods: PAYOUT_PAUSE_DELAY_PERIODS,
         roll_count_to_slash_on_denunciation: ROLL_COUNT_TO_SLASH_ON_DENUNCIATION,
         denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
         broadcast_enabled: SETTINGS.api.enable_broadcast,
@@ -472,11 +619,16 @@ async fn launch(
         chain_id: *CHAINID,
     };
 
+    // subscribe before the workers start publishing so that no final slot is missing from the export
+    let parquet_export_stopper =
+        start_parquet_export(&SETTINGS.parquet_export, &node_event_bus, PERIODS_PER_CYCLE);
+
     let execution_channels = ExecutionChannels {
         slot_execution_output_sender: broadcast::channel(
             execution_config.broadcast_slot_execution_output_channel_capacity,
         )
         .0,
+        event_bus: node_event_bus.clone(),
     };
 
     let (execution_manager, execution_controller) = start_execution_worker(
@@ -532,6 +684,7 @@ async fn launch(
         },
         selector: selector_controller.clone(),
         execution_controller: execution_controller.clone(),
+        event_bus: node_event_bus.clone(),
     };
 
     let (pool_manager, pool_controller) = start_pool_controller(
@@ -558,6 +711,9 @@ async fn launch(
         max_simultaneous_ask_blocks_per_node: SETTINGS
             .protocol
             .max_simultaneous_ask_blocks_per_node,
+        catch_up_max_simultaneous_ask_blocks_per_node: SETTINGS
+            .protocol
+            .catch_up_max_simultaneous_ask_blocks_per_node,
         max_send_wait: SETTINGS.protocol.max_send_wait,
         operation_batch_buffer_capacity: SETTINGS.protocol.operation_batch_buffer_capacity,
         operation_announcement_buffer_capacity: SETTINGS
@@ -585,6 +741,7 @@ async fn launch(
         keypair_file: SETTINGS.protocol.keypair_file.clone(),
         max_blocks_kept_for_propagation: SETTINGS.protocol.max_blocks_kept_for_propagation,
         block_propagation_tick: SETTINGS.protocol.block_propagation_tick,
+        blockclique_summary_gossip_interval: SETTINGS.protocol.blockclique_summary_gossip_interval,
         asked_operations_buffer_capacity: SETTINGS.protocol.asked_operations_buffer_capacity,
         thread_tester_count: SETTINGS.protocol.thread_tester_count,
         max_operation_storage_time: MAX_OPERATION_STORAGE_TIME,
@@ -640,6 +797,16 @@ async fn launch(
     let (protocol_controller, protocol_channels) =
         create_protocol_controller(protocol_config.clone());
 
+    // the audit log checkpoints are signed by the node key, loaded before protocol starts
+    let audit_log_keypair = if SETTINGS.consensus.audit_log_path.is_some() {
+        Some(
+            load_or_create_node_keypair(&SETTINGS.protocol.keypair_file, &mip_store)
+                .expect("could not load the node key to sign the consensus audit log"),
+        )
+    } else {
+        None
+    };
+
     let consensus_config = ConsensusConfig {
         genesis_timestamp: *GENESIS_TIMESTAMP,
         end_timestamp: *END_TIMESTAMP,
@@ -649,13 +816,49 @@ async fn launch(
         max_discarded_blocks: SETTINGS.consensus.max_discarded_blocks,
         max_future_processing_blocks: SETTINGS.consensus.max_future_processing_blocks,
         max_dependency_blocks: SETTINGS.consensus.max_dependency_blocks,
+        max_quarantined_headers: SETTINGS.consensus.max_quarantined_headers,
+        header_only_expiry_periods: SETTINGS.consensus.header_only_expiry_periods,
         delta_f0: DELTA_F0,
+        exposed_clique_fitness_margin: SETTINGS.consensus.exposed_clique_fitness_margin,
+        max_exposed_cliques: SETTINGS.consensus.max_exposed_cliques,
         operation_validity_periods: OPERATION_VALIDITY_PERIODS,
         periods_per_cycle: PERIODS_PER_CYCLE,
         stats_timespan: SETTINGS.consensus.stats_timespan,
+        blockclique_history_length: SETTINGS.consensus.blockclique_history_length,
+        best_parents_history_length: SETTINGS.consensus.best_parents_history_length,
+        endorser_stats_cycles: SETTINGS.consensus.endorser_stats_cycles,
+        liveness_timeout: SETTINGS.consensus.liveness_timeout,
+        readiness_max_final_lag: SETTINGS.consensus.readiness_max_final_lag,
+        readiness_max_execution_gap: SETTINGS.consensus.readiness_max_execution_gap,
+        catch_up_lag_periods: SETTINGS.consensus.catch_up_lag_periods,
+        catch_up_batch_size: SETTINGS.consensus.catch_up_batch_size,
+        peer_blockclique_summary_lifetime: SETTINGS.consensus.peer_blockclique_summary_lifetime,
+        finality_stall_timeout: SETTINGS.consensus.finality_stall_timeout,
+        finality_stall_diagnostics_dir: Some(
+            SETTINGS.consensus.finality_stall_diagnostics_dir.clone(),
+        ),
+        creator_ban_list_path: Some(SETTINGS.consensus.creator_ban_list_path.clone()),
+        creator_ban_duration: SETTINGS.consensus.creator_ban_duration,
+        max_parent_age: MAX_PARENT_AGE,
+        max_parent_execution_lag: MAX_PARENT_EXECUTION_LAG,
+        draw_prefetch_periods: SETTINGS.consensus.draw_prefetch_periods,
+        insertion_shards: SETTINGS.consensus.insertion_shards,
+        light_mode: SETTINGS.consensus.light_mode,
+        drop_executed_block_bodies: SETTINGS.consensus.drop_executed_block_bodies,
+        block_dedup_window: SETTINGS.consensus.block_dedup_window,
+        block_dedup_capacity: SETTINGS.consensus.block_dedup_capacity,
+        graph_export_quota_per_minute: SETTINGS.consensus.graph_export_quota_per_minute,
+        bootstrap_part_quota_per_minute: SETTINGS.consensus.bootstrap_part_quota_per_minute,
+        audit_log_path: SETTINGS.consensus.audit_log_path.clone(),
+        audit_log_keypair,
+        audit_log_checkpoint_interval: SETTINGS.consensus.audit_log_checkpoint_interval,
+        final_block_index_path: SETTINGS.consensus.final_block_index_path.clone(),
         force_keep_final_periods: SETTINGS.consensus.force_keep_final_periods,
         endorsement_count: ENDORSEMENT_COUNT,
+        block_reward: BLOCK_REWARD,
+        header_extensions: SETTINGS.consensus.header_extensions.clone(),
         block_db_prune_interval: SETTINGS.consensus.block_db_prune_interval,
+        checkpoint_interval: SETTINGS.consensus.checkpoint_interval,
         max_gas_per_block: MAX_GAS_PER_BLOCK,
         channel_size: CHANNEL_SIZE,
         bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
@@ -682,6 +885,8 @@ async fn launch(
         pool_controller: pool_controller.clone(),
         controller_event_tx: consensus_event_sender,
         protocol_controller: protocol_controller.clone(),
+        mip_store: mip_store.clone(),
+        db: Some(db.clone()),
         broadcasts: ConsensusBroadcasts {
             block_header_sender: broadcast::channel(
                 consensus_config.broadcast_blocks_headers_channel_capacity,
@@ -693,6 +898,7 @@ async fn launch(
             )
             .0,
         },
+        event_bus: node_event_bus,
     };
 
     let (consensus_controller, consensus_manager) = start_consensus_worker(
@@ -731,6 +937,9 @@ async fn launch(
         stop_production_when_zero_connections: SETTINGS
             .factory
             .stop_production_when_zero_connections,
+        parent_selection_policy: SETTINGS.factory.parent_selection_policy.clone(),
+        candidate_selection_policy: SETTINGS.factory.candidate_selection_policy.clone(),
+        block_production_enabled: !SETTINGS.consensus.light_mode,
         chain_id: *CHAINID,
     };
     let factory_channels = FactoryChannels {
@@ -814,6 +1023,7 @@ async fn launch(
         consensus_channels.broadcasts.clone(),
         execution_controller.clone(),
         pool_channels.broadcasts.clone(),
+        final_state.read().get_pos_state().credits_watcher.clone(),
         api_config.clone(),
         *VERSION,
     );
@@ -931,6 +1141,7 @@ async fn launch(
         api_config.clone(),
         sig_int_toggled,
         node_wallet,
+        final_state.read().get_pos_state().credits_watcher.clone(),
     );
     let api_private_handle = api_private
         .serve(&SETTINGS.api.bind_private, &api_config)
@@ -1023,6 +1234,7 @@ async fn launch(
         grpc_public_handle,
         metrics_stopper,
         massa_survey_stopper,
+        parquet_export_stopper,
     )
 }
 
@@ -1130,6 +1342,7 @@ async fn stop(
     grpc_public_handle: Option<massa_grpc::server::StopHandle>,
     mut metrics_stopper: MetricsStopper,
     mut massa_survey_stopper: MassaSurveyStopper,
+    mut parquet_export_stopper: ParquetExportStopper,
 ) {
     // stop bootstrap
     if let Some(bootstrap_manager) = bootstrap_manager {
@@ -1188,6 +1401,9 @@ async fn stop(
     // stop selector controller
     selector_manager.stop();
 
+    // stop parquet export thread once the final blocks and credits stopped being published
+    parquet_export_stopper.stop();
+
     // stop pool controller
     // TODO
     //let protocol_pool_event_receiver = pool_manager.stop().await.expect("pool shutdown failed");
@@ -1209,6 +1425,26 @@ struct Args {
     #[arg(long = "restart-from-snapshot-at-period")]
     restart_from_snapshot_at_period: Option<u64>,
 
+    /// Write back the deferred credits of the kept ledger after the final state is reset
+    /// (with --keep-ledger or --restart-from-snapshot-at-period, for nodes that do not bootstrap)
+    #[arg(long = "recover-deferred-credits")]
+    recover_deferred_credits: bool,
+
+    /// Keep the ledger, a snapshot of the final state at the `fast_sync_checkpoint` of the bootstrap settings,
+    /// and only bootstrap the final state changes since that checkpoint, along with the whole block graph
+    #[arg(long = "fast-sync")]
+    fast_sync: bool,
+
+    /// Keep the ledger and only resynchronize a part of the local state from the bootstrap servers:
+    /// `consensus` for the final blocks, `pos` for the cycle history and the deferred credits as well
+    #[arg(long = "resync", conflicts_with = "fast_sync")]
+    resync: Option<ResyncTarget>,
+
+    /// Bootstrap from the given server of the bootstrap list as well, report the differences
+    /// between its block graph and the bootstrapped one, then exit
+    #[arg(long = "verify-bootstrap-against")]
+    verify_bootstrap_against: Option<SocketAddr>,
+
     #[cfg(feature = "op_spammer")]
     /// number of operations
     #[arg(
@@ -1349,6 +1585,7 @@ async fn run(args: Args) -> anyhow::Result<()> {
             grpc_public_handle,
             metrics_stopper,
             massa_survey_stopper,
+            parquet_export_stopper,
         ) = launch(&cur_args, node_wallet.clone(), Arc::clone(&sig_int_toggled)).await;
 
         // loop over messages
@@ -1416,6 +1653,7 @@ async fn run(args: Args) -> anyhow::Result<()> {
             grpc_public_handle,
             metrics_stopper,
             massa_survey_stopper,
+            parquet_export_stopper,
         )
         .await;
 
@@ -1424,6 +1662,10 @@ async fn run(args: Args) -> anyhow::Result<()> {
         }
         // If we restart because of a desync, then we do not want to restart from a snapshot
         cur_args.restart_from_snapshot_at_period = None;
+        // nor to fast sync from a final state that is not at the checkpoint anymore
+        cur_args.fast_sync = false;
+        // nor to resynchronize again
+        cur_args.resync = None;
     }
     Ok(())
 }
@@ -0,0 +1,390 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Export of the finalized blocks and of the deferred credit payouts to Parquet files.
+//!
+//! The exporter subscribes to the node event bus and buffers one row per `BlockFinal` and per
+//! `CreditPaid` event. The rows are written every `flush_interval`, as soon as `max_buffered_rows`
+//! is reached and when the node stops, into Hive-style partitions that Spark, DuckDB or Polars
+//! read as a single table:
+//! * `<directory>/final_blocks/cycle=<cycle>/<first period>_<first thread>_<flush timestamp>.parquet`
+//! * `<directory>/credit_payouts/cycle=<cycle>/<first period>_<first thread>_<flush timestamp>.parquet`
+//!
+//! Amounts are written as raw `u64` values (nanoMAS). The writer is only built with the
+//! `parquet_export` feature.
+
+use std::thread::JoinHandle;
+
+use massa_channel::{event_bus::MassaEventBus, sender::MassaSender};
+use massa_models::node_event::NodeEvent;
+use tracing::{info, warn};
+
+use crate::settings::ParquetExportSettings;
+
+/// capacity of the event bus subscription of the exporter
+#[cfg(feature = "parquet_export")]
+const EVENT_QUEUE_CAPACITY: usize = 10_000;
+
+pub struct ParquetExportStopper {
+    tx_stopper: Option<MassaSender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ParquetExportStopper {
+    pub fn stop(&mut self) {
+        if let Some(tx) = self.tx_stopper.take() {
+            info!("ParquetExport | Stopping");
+            if let Err(e) = tx.send(()) {
+                warn!(
+                    "failed to send stop signal to parquet export thread: {:?}",
+                    e
+                );
+            }
+        }
+        if let Some(handle) = self.handle.take() {
+            match handle.join() {
+                Ok(_) => info!("ParquetExport | Stopped"),
+                Err(_) => warn!("failed to join parquet export thread"),
+            }
+        }
+    }
+}
+
+/// Start the Parquet export thread if it is enabled in the settings
+pub fn start_parquet_export(
+    settings: &ParquetExportSettings,
+    event_bus: &MassaEventBus<NodeEvent>,
+    periods_per_cycle: u64,
+) -> ParquetExportStopper {
+    if !settings.enabled {
+        return ParquetExportStopper {
+            tx_stopper: None,
+            handle: None,
+        };
+    }
+    spawn_exporter(settings, event_bus, periods_per_cycle)
+}
+
+#[cfg(not(feature = "parquet_export"))]
+fn spawn_exporter(
+    _settings: &ParquetExportSettings,
+    _event_bus: &MassaEventBus<NodeEvent>,
+    _periods_per_cycle: u64,
+) -> ParquetExportStopper {
+    warn!("ParquetExport | enabled in the configuration but the node was built without the `parquet_export` feature");
+    ParquetExportStopper {
+        tx_stopper: None,
+        handle: None,
+    }
+}
+
+#[cfg(feature = "parquet_export")]
+fn spawn_exporter(
+    settings: &ParquetExportSettings,
+    event_bus: &MassaEventBus<NodeEvent>,
+    periods_per_cycle: u64,
+) -> ParquetExportStopper {
+    use crossbeam_channel::{select, tick};
+    use massa_channel::MassaChannel;
+
+    const THREAD_NAME: &str = "parquet-export";
+
+    let events = event_bus.subscribe("parquet_export".to_string(), EVENT_QUEUE_CAPACITY);
+    let (tx_stop, rx_stop) = MassaChannel::new("parquet_export_stop".to_string(), Some(1));
+    let flush_tick = tick(settings.flush_interval.to_duration());
+    let max_buffered_rows = settings.max_buffered_rows;
+    let mut exporter = writer::ParquetExporter::new(settings.directory.clone(), periods_per_cycle);
+    let handle = std::thread::Builder::new()
+        .name(THREAD_NAME.to_string())
+        .spawn(move || {
+            loop {
+                select! {
+                    recv(rx_stop) -> _ => break,
+                    recv(events) -> event => match event {
+                        Ok(event) => {
+                            exporter.push(event);
+                            if exporter.buffered_rows() >= max_buffered_rows {
+                                exporter.flush();
+                            }
+                        }
+                        // the bus was dropped along with the workers
+                        Err(_) => break,
+                    },
+                    recv(flush_tick) -> _ => exporter.flush(),
+                }
+            }
+            // keep the events that were published before the stop signal
+            while let Ok(event) = events.try_recv() {
+                exporter.push(event);
+            }
+            exporter.flush();
+        })
+        .expect("failed to spawn thread : parquet-export");
+    info!(
+        "ParquetExport | exporting final blocks and credit payouts to {}",
+        settings.directory.display()
+    );
+
+    ParquetExportStopper {
+        tx_stopper: Some(tx_stop),
+        handle: Some(handle),
+    }
+}
+
+#[cfg(feature = "parquet_export")]
+mod writer {
+    use std::collections::BTreeMap;
+    use std::fs::{self, File};
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt32Array, UInt64Array, UInt8Array};
+    use arrow_schema::{ArrowError, DataType, Field, Schema};
+    use massa_models::{address::Address, block_id::BlockId, node_event::NodeEvent, slot::Slot};
+    use massa_time::MassaTime;
+    use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+    use tracing::{debug, warn};
+
+    const FINAL_BLOCKS_TABLE: &str = "final_blocks";
+    const CREDIT_PAYOUTS_TABLE: &str = "credit_payouts";
+
+    /// Row of the `final_blocks` table
+    struct FinalBlockRow {
+        slot: Slot,
+        block_id: BlockId,
+        creator: Address,
+        creator_reward: u64,
+        endorsement_count: u32,
+        endorsement_reward: u64,
+        endorsed_block_creator: Option<(Address, u64)>,
+    }
+
+    /// Row of the `credit_payouts` table
+    struct CreditPayoutRow {
+        slot: Slot,
+        address: Address,
+        amount: u64,
+    }
+
+    /// Rows waiting to be written, partitioned by cycle
+    pub(super) struct ParquetExporter {
+        directory: PathBuf,
+        periods_per_cycle: u64,
+        final_blocks: BTreeMap<u64, Vec<FinalBlockRow>>,
+        credit_payouts: BTreeMap<u64, Vec<CreditPayoutRow>>,
+        buffered_rows: usize,
+    }
+
+    impl ParquetExporter {
+        pub(super) fn new(directory: PathBuf, periods_per_cycle: u64) -> Self {
+            ParquetExporter {
+                directory,
+                periods_per_cycle,
+                final_blocks: BTreeMap::new(),
+                credit_payouts: BTreeMap::new(),
+                buffered_rows: 0,
+            }
+        }
+
+        pub(super) fn buffered_rows(&self) -> usize {
+            self.buffered_rows
+        }
+
+        /// Buffer the row of an event, events that are not exported are ignored
+        pub(super) fn push(&mut self, event: NodeEvent) {
+            match event {
+                NodeEvent::BlockFinal {
+                    block_id,
+                    slot,
+                    reward_split,
+                } => {
+                    let (creator, creator_reward) = reward_split.block_creator;
+                    let row = FinalBlockRow {
+                        slot,
+                        block_id,
+                        creator,
+                        creator_reward: creator_reward.to_raw(),
+                        endorsement_count: reward_split.endorsement_creators.len() as u32,
+                        endorsement_reward: reward_split
+                            .endorsement_creators
+                            .iter()
+                            .fold(0u64, |sum, (_, amount)| sum.saturating_add(amount.to_raw())),
+                        endorsed_block_creator: reward_split
+                            .endorsed_block_creator
+                            .map(|(address, amount)| (address, amount.to_raw())),
+                    };
+                    self.final_blocks
+                        .entry(slot.get_cycle(self.periods_per_cycle))
+                        .or_default()
+                        .push(row);
+                }
+                NodeEvent::CreditPaid {
+                    slot,
+                    address,
+                    amount,
+                } => {
+                    self.credit_payouts
+                        .entry(slot.get_cycle(self.periods_per_cycle))
+                        .or_default()
+                        .push(CreditPayoutRow {
+                            slot,
+                            address,
+                            amount: amount.to_raw(),
+                        });
+                }
+                NodeEvent::CycleEnd { .. }
+                | NodeEvent::DrawsComputed { .. }
+                | NodeEvent::OperationsExpired { .. }
+                | NodeEvent::ReorgHappened { .. }
+                | NodeEvent::FinalityStalled { .. } => return,
+            }
+            self.buffered_rows += 1;
+        }
+
+        /// Write one file per table and cycle with the buffered rows
+        ///
+        /// Rows that could not be written are dropped so that a failing disk does not make the buffer grow without bound.
+        pub(super) fn flush(&mut self) {
+            if self.buffered_rows == 0 {
+                return;
+            }
+            let flush_timestamp = MassaTime::now().as_millis();
+            for (cycle, rows) in std::mem::take(&mut self.final_blocks) {
+                self.write_partition(
+                    FINAL_BLOCKS_TABLE,
+                    cycle,
+                    rows.iter().map(|row| row.slot).min(),
+                    flush_timestamp,
+                    final_blocks_batch(&rows),
+                );
+            }
+            for (cycle, rows) in std::mem::take(&mut self.credit_payouts) {
+                self.write_partition(
+                    CREDIT_PAYOUTS_TABLE,
+                    cycle,
+                    rows.iter().map(|row| row.slot).min(),
+                    flush_timestamp,
+                    credit_payouts_batch(&rows),
+                );
+            }
+            self.buffered_rows = 0;
+        }
+
+        fn write_partition(
+            &self,
+            table: &str,
+            cycle: u64,
+            first_slot: Option<Slot>,
+            flush_timestamp: u64,
+            batch: Result<RecordBatch, ArrowError>,
+        ) {
+            let Some(first_slot) = first_slot else {
+                return;
+            };
+            let dir = self.directory.join(table).join(format!("cycle={}", cycle));
+            let file_name = format!(
+                "{}_{}_{}.parquet",
+                first_slot.period, first_slot.thread, flush_timestamp
+            );
+            let result = batch
+                .map_err(anyhow::Error::from)
+                .and_then(|batch| write_file(&dir, &file_name, &batch).map(|_| batch.num_rows()));
+            match result {
+                Ok(rows) => debug!(
+                    "ParquetExport | wrote {} rows to {}",
+                    rows,
+                    dir.join(&file_name).display()
+                ),
+                Err(err) => warn!(
+                    "ParquetExport | failed to write the {} rows of cycle {}: {}",
+                    table, cycle, err
+                ),
+            }
+        }
+    }
+
+    fn final_blocks_batch(rows: &[FinalBlockRow]) -> Result<RecordBatch, ArrowError> {
+        let schema = Schema::new(vec![
+            Field::new("period", DataType::UInt64, false),
+            Field::new("thread", DataType::UInt8, false),
+            Field::new("block_id", DataType::Utf8, false),
+            Field::new("creator", DataType::Utf8, false),
+            Field::new("creator_reward", DataType::UInt64, false),
+            Field::new("endorsement_count", DataType::UInt32, false),
+            Field::new("endorsement_reward", DataType::UInt64, false),
+            Field::new("endorsed_block_creator", DataType::Utf8, true),
+            Field::new("endorsed_block_creator_reward", DataType::UInt64, true),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|row| row.slot.period),
+            )),
+            Arc::new(UInt8Array::from_iter_values(
+                rows.iter().map(|row| row.slot.thread),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| row.block_id.to_string()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| row.creator.to_string()),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|row| row.creator_reward),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                rows.iter().map(|row| row.endorsement_count),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|row| row.endorsement_reward),
+            )),
+            Arc::new(StringArray::from_iter(rows.iter().map(|row| {
+                row.endorsed_block_creator
+                    .map(|(address, _)| address.to_string())
+            }))),
+            Arc::new(UInt64Array::from_iter(
+                rows.iter()
+                    .map(|row| row.endorsed_block_creator.map(|(_, amount)| amount)),
+            )),
+        ];
+        RecordBatch::try_new(Arc::new(schema), columns)
+    }
+
+    fn credit_payouts_batch(rows: &[CreditPayoutRow]) -> Result<RecordBatch, ArrowError> {
+        let schema = Schema::new(vec![
+            Field::new("period", DataType::UInt64, false),
+            Field::new("thread", DataType::UInt8, false),
+            Field::new("address", DataType::Utf8, false),
+            Field::new("amount", DataType::UInt64, false),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|row| row.slot.period),
+            )),
+            Arc::new(UInt8Array::from_iter_values(
+                rows.iter().map(|row| row.slot.thread),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| row.address.to_string()),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|row| row.amount),
+            )),
+        ];
+        RecordBatch::try_new(Arc::new(schema), columns)
+    }
+
+    /// Write the batch to a temporary file renamed once complete, so that readers never see a partial file
+    fn write_file(dir: &Path, file_name: &str, batch: &RecordBatch) -> anyhow::Result<()> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(file_name);
+        let tmp_path = dir.join(format!("{}.tmp", file_name));
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(File::create(&tmp_path)?, batch.schema(), Some(properties))?;
+        writer.write(batch)?;
+        writer.close()?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+//! Startup self-test of the consensus and proof-of-stake serializers.
+//!
+//! Representative values, including edge cases, are serialized and deserialized back before the node starts.
+//! A mismatch means that this build or platform does not encode data like the rest of the network:
+//! the node must not start, as it would corrupt its state or fork from the network.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::Bound::{Excluded, Included};
+
+use massa_consensus_exports::bootstrapable_graph::{
+    BootstrapableGraph, BootstrapableGraphDeserializer, BootstrapableGraphSerializer,
+};
+use massa_consensus_exports::export_active_block::{
+    ExportActiveBlock, ExportActiveBlockDeserializer, ExportActiveBlockSerializer,
+};
+use massa_hash::{Hash, HashXof, HASH_XOF_SIZE_BYTES};
+use massa_models::address::Address;
+use massa_models::amount::{Amount, AmountDeserializer, AmountSerializer};
+use massa_models::block::{Block, BlockDeserializerArgs, BlockSerializer};
+use massa_models::block_header::{BlockHeader, BlockHeaderSerializer};
+use massa_models::block_id::BlockId;
+use massa_models::config::constants::{
+    CHAINID, ENDORSEMENT_COUNT, MAX_BOOTSTRAP_BLOCKS, MAX_DEFERRED_CREDITS_LENGTH,
+    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_OPERATIONS_PER_BLOCK, MAX_PRODUCTION_STATS_LENGTH,
+    MAX_ROLLS_COUNT_LENGTH, PERIODS_PER_CYCLE, POS_SAVED_CYCLES, THREAD_COUNT,
+};
+use massa_models::secure_share::SecureShareContent;
+use massa_models::slot::{Slot, SlotDeserializer, SlotSerializer};
+use massa_pos_exports::{
+    compute_initial_cycle, CycleInfo, CycleInfoDeserializer, CycleInfoSerializer, DeferredCredits,
+    DeferredCreditsDeserializer, DeferredCreditsSerializer, PoSConfig, ProductionStats,
+};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_signature::KeyPair;
+
+/// Round-trip representative values through the consensus and proof-of-stake serializers.
+///
+/// # Returns
+/// The list of the failed checks, if any
+pub fn run_serialization_self_test() -> Result<(), Vec<String>> {
+    let mut report = Vec::new();
+    check_slots(&mut report);
+    check_amounts(&mut report);
+    check_deferred_credits(&mut report);
+    check_cycle_info(&mut report);
+    check_blocks(&mut report);
+    if report.is_empty() {
+        Ok(())
+    } else {
+        Err(report)
+    }
+}
+
+/// Serialize `value`, deserialize it back and check that the result is the same according to `same`
+fn check_round_trip<T, S, D, F>(
+    name: &str,
+    value: &T,
+    serializer: &S,
+    deserializer: &D,
+    same: F,
+    report: &mut Vec<String>,
+) where
+    T: Debug,
+    S: Serializer<T>,
+    D: Deserializer<T>,
+    F: Fn(&T, &T) -> bool,
+{
+    let mut buffer = Vec::new();
+    if let Err(err) = serializer.serialize(value, &mut buffer) {
+        report.push(format!("{}: serialization failed: {}", name, err));
+        return;
+    }
+    match deserializer.deserialize::<DeserializeError>(&buffer) {
+        Ok((rest, _)) if !rest.is_empty() => report.push(format!(
+            "{}: {} bytes left after deserialization",
+            name,
+            rest.len()
+        )),
+        Ok((_, deserialized)) if !same(value, &deserialized) => report.push(format!(
+            "{}: deserialized {:?} instead of {:?}",
+            name, deserialized, value
+        )),
+        Ok(_) => {}
+        Err(err) => report.push(format!("{}: deserialization failed: {}", name, err)),
+    }
+}
+
+fn random_address() -> Address {
+    Address::from_public_key(
+        &KeyPair::generate(0)
+            .expect("could not generate a keypair")
+            .get_public_key(),
+    )
+}
+
+fn check_slots(report: &mut Vec<String>) {
+    let serializer = SlotSerializer::new();
+    let deserializer = SlotDeserializer::new(
+        (Included(0), Included(u64::MAX)),
+        (Included(0), Excluded(THREAD_COUNT)),
+    );
+    for slot in [
+        Slot::new(0, 0),
+        Slot::new(1, THREAD_COUNT - 1),
+        Slot::new(u64::MAX, THREAD_COUNT - 1),
+    ] {
+        check_round_trip(
+            "slot",
+            &slot,
+            &serializer,
+            &deserializer,
+            PartialEq::eq,
+            report,
+        );
+    }
+}
+
+fn check_amounts(report: &mut Vec<String>) {
+    let serializer = AmountSerializer::new();
+    let deserializer = AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+    for amount in [Amount::MIN, Amount::from_raw(1), Amount::MAX] {
+        check_round_trip(
+            "amount",
+            &amount,
+            &serializer,
+            &deserializer,
+            PartialEq::eq,
+            report,
+        );
+    }
+}
+
+fn check_deferred_credits(report: &mut Vec<String>) {
+    let mut credits = DeferredCredits::new();
+    credits.insert(Slot::new(0, 0), random_address(), Amount::from_raw(1));
+    credits.insert(
+        Slot::new(u64::MAX, THREAD_COUNT - 1),
+        random_address(),
+        Amount::MAX,
+    );
+    credits.insert(
+        Slot::new(u64::MAX, THREAD_COUNT - 1),
+        random_address(),
+        Amount::MIN,
+    );
+    check_round_trip(
+        "deferred credits",
+        &credits,
+        &DeferredCreditsSerializer::new(),
+        &DeferredCreditsDeserializer::new(THREAD_COUNT, MAX_DEFERRED_CREDITS_LENGTH),
+        |a, b| a.credits == b.credits,
+        report,
+    );
+}
+
+fn check_cycle_info(report: &mut Vec<String>) {
+    let config = PoSConfig {
+        periods_per_cycle: PERIODS_PER_CYCLE,
+        thread_count: THREAD_COUNT,
+        cycle_history_length: POS_SAVED_CYCLES,
+        max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+        max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+        max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
+        initial_deferred_credits_path: None,
+    };
+    let address = random_address();
+    let mut cycle_info: CycleInfo =
+        compute_initial_cycle(&config, BTreeMap::from([(address, u64::MAX)]));
+    cycle_info.cycle = u64::MAX;
+    cycle_info.complete = true;
+    cycle_info.rng_seed.push(true);
+    cycle_info.production_stats.insert(
+        address,
+        ProductionStats {
+            block_success_count: u64::MAX,
+            block_failure_count: 0,
+        },
+    );
+    cycle_info.final_state_hash_snapshot = Some(HashXof([u8::MAX; HASH_XOF_SIZE_BYTES]));
+    check_round_trip(
+        "cycle info",
+        &cycle_info,
+        &CycleInfoSerializer::new(),
+        &CycleInfoDeserializer::new(MAX_ROLLS_COUNT_LENGTH, MAX_PRODUCTION_STATS_LENGTH),
+        PartialEq::eq,
+        report,
+    );
+}
+
+fn block_deserializer_args() -> BlockDeserializerArgs {
+    BlockDeserializerArgs {
+        thread_count: THREAD_COUNT,
+        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        endorsement_count: ENDORSEMENT_COUNT,
+        max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        last_start_period: None,
+        chain_id: *CHAINID,
+    }
+}
+
+fn check_blocks(report: &mut Vec<String>) {
+    let keypair = KeyPair::generate(0).expect("could not generate a keypair");
+    let parents: Vec<(BlockId, u64)> = (0..THREAD_COUNT)
+        .map(|thread| {
+            (
+                BlockId::generate_from_hash(Hash::compute_from(&[thread])),
+                0,
+            )
+        })
+        .collect();
+    let block = BlockHeader::new_verifiable(
+        BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            slot: Slot::new(1, THREAD_COUNT - 1),
+            parents: parents.iter().map(|(id, _)| *id).collect(),
+            operation_merkle_root: Hash::compute_from(&Vec::new()),
+            endorsements: Vec::new(),
+            denunciations: Vec::new(),
+            extensions: Vec::new(),
+        },
+        BlockHeaderSerializer::new(),
+        &keypair,
+        *CHAINID,
+    )
+    .and_then(|header| {
+        Block::new_verifiable(
+            Block {
+                header,
+                operations: Default::default(),
+            },
+            BlockSerializer::new(),
+            &keypair,
+            *CHAINID,
+        )
+    });
+    let block = match block {
+        Ok(block) => block,
+        Err(err) => {
+            report.push(format!("block: could not create a block: {}", err));
+            return;
+        }
+    };
+    let same_export = |a: &ExportActiveBlock, b: &ExportActiveBlock| {
+        a.block.id == b.block.id && a.parents == b.parents && a.is_final == b.is_final
+    };
+
+    let export_active_block = ExportActiveBlock {
+        block,
+        parents,
+        is_final: true,
+    };
+    check_round_trip(
+        "active block",
+        &export_active_block,
+        &ExportActiveBlockSerializer::new(),
+        &ExportActiveBlockDeserializer::new(block_deserializer_args()),
+        same_export,
+        report,
+    );
+    check_round_trip(
+        "bootstrap graph",
+        &BootstrapableGraph {
+            final_blocks: vec![export_active_block],
+        },
+        &BootstrapableGraphSerializer::new(),
+        &BootstrapableGraphDeserializer::new(block_deserializer_args(), MAX_BOOTSTRAP_BLOCKS),
+        |a, b| {
+            a.final_blocks.len() == b.final_blocks.len()
+                && a.final_blocks
+                    .iter()
+                    .zip(b.final_blocks.iter())
+                    .all(|(a, b)| same_export(a, b))
+        },
+        report,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialization_self_test_passes() {
+        assert_eq!(run_serialization_self_test(), Ok(()));
+    }
+
+    #[test]
+    fn test_serialization_self_test_reports_failures() {
+        let mut report = Vec::new();
+        let slot = Slot::new(1, THREAD_COUNT - 1);
+        // a deserializer rejecting the thread of the slot
+        let deserializer = SlotDeserializer::new(
+            (Included(0), Included(u64::MAX)),
+            (Included(0), Excluded(1)),
+        );
+        check_round_trip(
+            "slot",
+            &slot,
+            &SlotSerializer::new(),
+            &deserializer,
+            PartialEq::eq,
+            &mut report,
+        );
+        // a value that does not come back the same
+        check_round_trip(
+            "amount",
+            &Amount::from_raw(1),
+            &AmountSerializer::new(),
+            &AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX)),
+            |_, _| false,
+            &mut report,
+        );
+        assert_eq!(report.len(), 2);
+        assert!(report[0].starts_with("slot: deserialization failed"));
+        assert!(report[1].starts_with("amount: deserialized"));
+    }
+}
@@ -3,8 +3,12 @@
 //! Build here the default node settings from the configuration file toml
 use std::{collections::HashMap, path::PathBuf};
 
-use massa_bootstrap::IpType;
-use massa_models::{config::build_massa_settings, node::NodeId};
+use massa_bootstrap::{FastSyncCheckpoint, IpType};
+use massa_factory_exports::{CandidateSelectionPolicyConfig, ParentSelectionPolicyConfig};
+use massa_models::{
+    amount::Amount, block_header_extension::BlockHeaderExtensionSpec, config::build_massa_settings,
+    node::NodeId,
+};
 use massa_protocol_exports::PeerCategoryInfo;
 use massa_time::MassaTime;
 use serde::Deserialize;
@@ -32,6 +36,8 @@ pub struct ExecutionSettings {
     pub lru_cache_size: u32,
     pub hd_cache_size: usize,
     pub snip_amount: usize,
+    pub paid_credits_history_path: PathBuf,
+    pub paid_credits_history_periods: u64,
     /// slot execution outputs channel capacity
     pub broadcast_slot_execution_output_channel_capacity: usize,
 }
@@ -47,6 +53,10 @@ pub struct LedgerSettings {
     pub disk_ledger_path: PathBuf,
     pub final_history_length: usize,
     pub initial_deferred_credits_path: Option<PathBuf>,
+    pub deferred_credits_slot_alert_threshold: Amount,
+    /// the node refuses to start if the initial balances, rolls value and deferred credits total more than this amount,
+    /// unchecked if absent
+    pub max_supply: Option<Amount>,
 }
 
 /// Bootstrap configuration.
@@ -72,6 +82,8 @@ pub struct BootstrapSettings {
     pub rate_limit: u64,
     /// Allocated time with which to manage the bootstrap process
     pub bootstrap_timeout: MassaTime,
+    /// Trusted final state checkpoint used by the nodes started with `--fast-sync`
+    pub fast_sync_checkpoint: Option<FastSyncCheckpoint>,
 }
 
 /// Factory settings
@@ -83,6 +95,12 @@ pub struct FactorySettings {
     pub staking_wallet_path: PathBuf,
     /// stop the production in case we are not connected to anyone
     pub stop_production_when_zero_connections: bool,
+    /// policy used to choose the parents of the produced blocks
+    #[serde(default)]
+    pub parent_selection_policy: ParentSelectionPolicyConfig,
+    /// policy used to choose among the block contents proposed by external builders
+    #[serde(default)]
+    pub candidate_selection_policy: CandidateSelectionPolicyConfig,
 }
 
 /// Pool configuration, read from a file configuration
@@ -139,6 +157,7 @@ pub struct Settings {
     pub factory: FactorySettings,
     pub grpc: GrpcApiSettings,
     pub metrics: MetricsSettings,
+    pub parquet_export: ParquetExportSettings,
     pub versioning: VersioningSettings,
 }
 
@@ -152,14 +171,74 @@ pub struct ConsensusSettings {
     pub max_future_processing_blocks: usize,
     /// Maximum number of blocks allowed in `DependencyWaitingBlocks`.
     pub max_dependency_blocks: usize,
+    /// Maximum number of headers of network versions not activated yet held in quarantine.
+    pub max_quarantined_headers: usize,
+    /// headers whose block never arrived are dropped after this number of periods, 0 to keep them
+    pub header_only_expiry_periods: u64,
     /// stats time span
     pub stats_timespan: MassaTime,
+    /// number of blockclique changes kept in the blockclique history
+    pub blockclique_history_length: usize,
+    /// number of slots for which the best parents seen at the slot tick are kept
+    pub best_parents_history_length: usize,
+    /// number of cycles for which the endorsement misses of each address are kept
+    pub endorser_stats_cycles: u64,
+    /// the consensus worker is considered dead if it did not tick during this time
+    pub liveness_timeout: MassaTime,
+    /// the node is not ready if a thread has no final block during this time
+    pub readiness_max_final_lag: MassaTime,
+    /// the node is not ready if execution lags more than this number of slots behind the final slots, 0 to disable
+    pub readiness_max_execution_gap: u64,
+    /// the worker enters the catch-up mode when a thread has no final block for more than this number of periods, 0 to disable
+    pub catch_up_lag_periods: u64,
+    /// maximum number of pending registrations processed together in catch-up mode
+    pub catch_up_batch_size: usize,
+    /// blockclique summaries received from peers are ignored after this time
+    pub peer_blockclique_summary_lifetime: MassaTime,
+    /// a diagnostics bundle is collected when no block becomes final during this time, 0 to disable
+    pub finality_stall_timeout: MassaTime,
+    /// directory the diagnostics bundles of the finality stalls are written to
+    pub finality_stall_diagnostics_dir: PathBuf,
+    /// file the banned block creators are saved to
+    pub creator_ban_list_path: PathBuf,
+    /// time during which the creator of an invalid block is banned
+    pub creator_ban_duration: MassaTime,
+    /// number of periods of draws cached by consensus at each cycle rollover, 0 to disable
+    pub draw_prefetch_periods: u64,
+    /// one insertion shard per thread checks the draws of the registered blocks before the consensus worker
+    pub insertion_shards: bool,
+    /// cliques whose fitness is more than this margin below the blockclique's are not returned by `get_cliques`
+    pub exposed_clique_fitness_margin: u64,
+    /// maximum number of cliques returned by `get_cliques`, 0 for no limit
+    pub max_exposed_cliques: usize,
+    /// light mode for follower and API nodes: final block operations are not kept and no block is produced
+    pub light_mode: bool,
+    /// the operations of final blocks are dropped once executed as final, bootstrap is not served
+    pub drop_executed_block_bodies: bool,
+    /// registrations of a block already registered during this time are dropped
+    pub block_dedup_window: MassaTime,
+    /// maximum number of recently registered blocks remembered to drop duplicate registrations, 0 to disable
+    pub block_dedup_capacity: usize,
+    /// number of graph exports each API caller can request per minute, 0 for no limit
+    pub graph_export_quota_per_minute: u32,
+    /// number of consensus bootstrap parts each bootstrap client can request per minute, 0 for no limit
+    pub bootstrap_part_quota_per_minute: u32,
+    /// file the hash-chained log of the consensus decisions is appended to, disabled if absent
+    pub audit_log_path: Option<PathBuf>,
+    /// number of audit log entries between two checkpoints signed by the node key, 0 for none
+    pub audit_log_checkpoint_interval: u64,
+    /// directory of the on-disk index of the final block ids by slot, disabled if absent
+    pub final_block_index_path: Option<PathBuf>,
+    /// header extensions known by the node, validated when present in a header
+    pub header_extensions: Vec<BlockHeaderExtensionSpec>,
     /// force keep at least this number of final periods in RAM for each thread
     pub force_keep_final_periods: u64,
     /// force keep at least this number of final periods without operations in RAM for each thread
     pub force_keep_final_periods_without_ops: u64,
     /// old blocks are pruned every `block_db_prune_interval`
     pub block_db_prune_interval: MassaTime,
+    /// a summary of the consensus state is written to the database every `checkpoint_interval`, 0 to disable
+    pub checkpoint_interval: MassaTime,
     /// blocks headers channel capacity
     pub broadcast_blocks_headers_channel_capacity: usize,
     /// blocks channel capacity
@@ -185,6 +264,18 @@ pub struct MetricsSettings {
     pub tick_delay: MassaTime,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct ParquetExportSettings {
+    /// export the finalized blocks and the deferred credit payouts to Parquet files
+    pub enabled: bool,
+    /// directory of the exported files
+    pub directory: PathBuf,
+    /// interval at which buffered rows are written
+    pub flush_interval: MassaTime,
+    /// buffered rows are written before the interval when their number reaches this value
+    pub max_buffered_rows: usize,
+}
+
 /// Protocol Configuration, read from toml user configuration file
 #[derive(Debug, Deserialize, Clone)]
 pub struct ProtocolSettings {
@@ -196,6 +287,8 @@ pub struct ProtocolSettings {
     pub max_block_propagation_time: MassaTime,
     /// Block propagation tick interval, useful for propagating blocks quickly to newly connected peers.
     pub block_propagation_tick: MassaTime,
+    /// Interval between two sends of the local blockclique summary to the peers, used to detect blockclique divergences
+    pub blockclique_summary_gossip_interval: MassaTime,
     /// max known blocks our node keeps in its knowledge cache
     pub max_known_blocks_size: usize,
     /// max cache size for which blocks a foreign node knows about
@@ -214,6 +307,8 @@ pub struct ProtocolSettings {
     pub max_node_known_endorsements_size: usize,
     /// we ask for the same block `max_simultaneous_ask_blocks_per_node` times at the same time
     pub max_simultaneous_ask_blocks_per_node: usize,
+    /// max number of blocks asked simultaneously per node while consensus is in catch-up mode
+    pub catch_up_max_simultaneous_ask_blocks_per_node: usize,
     /// Max wait time for sending a Network or Node event.
     pub max_send_wait: MassaTime,
     /// Maximum number of batches in the memory buffer.
@@ -0,0 +1,167 @@
+//! Diagnostic mode comparing the block graph of the regular bootstrap with the one of another server.
+//!
+//! The node bootstraps a second time from the given server, into a temporary database that is
+//! deleted afterwards, then reports the differences between the two bootstrap graphs. The second
+//! bootstrap ends at a later final slot than the first one, so only the blocks of the slots covered by
+//! both graphs are compared: the blocks finalized or pruned in between are not reported.
+
+use std::net::SocketAddr;
+use std::ops::Bound::{Included, Unbounded};
+use std::sync::{Arc, Condvar, Mutex};
+
+use massa_bootstrap::{get_state, BootstrapConfig, DefaultConnector};
+use massa_consensus_exports::bootstrapable_graph::{BootstrapableGraph, BootstrapableGraphDiff};
+use massa_db_exports::{MassaDBConfig, MassaDBController};
+use massa_db_worker::MassaDB;
+use massa_final_state::{FinalState, FinalStateConfig, FinalStateController};
+use massa_ledger_worker::FinalLedger;
+use massa_metrics::MassaMetrics;
+use massa_models::config::constants::{GENESIS_TIMESTAMP, VERSION};
+use massa_models::slot::Slot;
+use massa_pos_exports::SelectorController;
+use massa_versioning::mips::get_mip_list;
+use massa_versioning::versioning::{MipStatsConfig, MipStore};
+use parking_lot::RwLock;
+use tracing::{info, warn};
+
+/// Bootstrap from `server` into a temporary final state and compare its block graph with `graph`
+///
+/// # Arguments
+/// * `server`: address of the server to compare with, it must be in the bootstrap list to know its node id
+/// * `graph`: block graph of the regular bootstrap
+/// * `final_slot`: slot of the final state of the regular bootstrap
+/// * `db_config`: configuration of the database of the node, the temporary one is created next to it
+///
+/// # Returns
+/// The differences between `graph` and the graph of `server`, within the slots covered by both
+#[allow(clippy::too_many_arguments)]
+pub fn verify_bootstrap_against(
+    server: SocketAddr,
+    graph: &BootstrapableGraph,
+    final_slot: Slot,
+    bootstrap_config: &BootstrapConfig,
+    final_state_config: FinalStateConfig,
+    db_config: MassaDBConfig,
+    selector_controller: Box<dyn SelectorController>,
+    mip_stats_config: MipStatsConfig,
+    interrupted: Arc<(Mutex<bool>, Condvar)>,
+    massa_metrics: MassaMetrics,
+) -> Result<BootstrapableGraphDiff, String> {
+    let Some(server_entry) = bootstrap_config
+        .bootstrap_list
+        .iter()
+        .find(|(address, _)| *address == server)
+    else {
+        return Err(format!(
+            "{} is not in the bootstrap list, its node id is unknown",
+            server
+        ));
+    };
+    let server_bootstrap_config = BootstrapConfig {
+        bootstrap_list: vec![*server_entry],
+        keep_ledger: false,
+        fast_sync_checkpoint: None,
+        resync: None,
+        ..bootstrap_config.clone()
+    };
+
+    // deleted when dropped, even if the bootstrap fails
+    let db_dir = tempfile::Builder::new()
+        .prefix("verify_bootstrap_")
+        .tempdir_in(db_config.path.parent().unwrap_or(&db_config.path))
+        .map_err(|err| format!("could not create the temporary database: {}", err))?;
+    let db_config = MassaDBConfig {
+        path: db_dir.path().to_path_buf(),
+        ..db_config
+    };
+    let db = Arc::new(RwLock::new(
+        Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+    ));
+    let ledger = FinalLedger::new(final_state_config.ledger_config.clone(), db.clone());
+    let mip_store = MipStore::try_from((get_mip_list(), mip_stats_config))
+        .map_err(|err| format!("could not create the MIP store: {}", err))?;
+    let final_state = FinalState::new(
+        db,
+        final_state_config,
+        Box::new(ledger),
+        selector_controller,
+        mip_store,
+        true,
+    )
+    .map_err(|err| format!("could not create the temporary final state: {}", err))?;
+    let final_state: Arc<RwLock<dyn FinalStateController>> = Arc::new(RwLock::new(final_state));
+
+    let server_state = get_state(
+        &server_bootstrap_config,
+        final_state,
+        DefaultConnector,
+        *VERSION,
+        *GENESIS_TIMESTAMP,
+        None,
+        None,
+        interrupted,
+        massa_metrics,
+    );
+    // the temporary final state is dropped with the bootstrap state, closing its database before its deletion
+    let server_state =
+        server_state.map_err(|err| format!("could not bootstrap from {}: {}", server, err))?;
+    let server_final_slot = server_state.final_state.read().get_slot();
+    let Some(server_graph) = server_state.graph else {
+        return Err(format!("{} did not send its block graph", server));
+    };
+    drop(server_state.final_state);
+    drop(db_dir);
+
+    // the slots final in both graphs, from the latest of their oldest blocks
+    let last_slot = std::cmp::min(final_slot, server_final_slot);
+    let first_slot = [graph, &server_graph]
+        .iter()
+        .filter_map(|graph| {
+            graph
+                .final_blocks
+                .iter()
+                .map(|export_block| export_block.block.content.header.content.slot)
+                .min()
+        })
+        .max();
+    info!(
+        "Comparing the blocks up to slot {}, final in the bootstrap graph (at slot {}) and in the graph of {} (at slot {})",
+        last_slot, final_slot, server, server_final_slot
+    );
+    Ok(graph.diff_in_slots(
+        &server_graph,
+        (first_slot.map_or(Unbounded, Included), Included(last_slot)),
+    ))
+}
+
+/// Log the differences between the bootstrap graphs
+///
+/// # Returns
+/// Whether the graphs are the same
+pub fn report_graph_diff(server: SocketAddr, diff: &BootstrapableGraphDiff) -> bool {
+    if diff.is_empty() {
+        info!("The block graph of {} matches the bootstrap graph", server);
+        return true;
+    }
+    let report = [
+        ("only in the bootstrap graph", &diff.only_in_self),
+        ("only in the graph of the server", &diff.only_in_other),
+        ("with different parents", &diff.mismatched_parents),
+        ("with a different finality", &diff.mismatched_finality),
+    ];
+    for (description, block_ids) in report {
+        if !block_ids.is_empty() {
+            warn!(
+                "{} blocks {}: {}",
+                block_ids.len(),
+                description,
+                block_ids
+                    .iter()
+                    .map(|block_id| block_id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+    false
+}
@@ -1,5 +1,8 @@
+use massa_channel::event_bus::MassaEventBus;
 use massa_execution_exports::ExecutionController;
-use massa_models::{endorsement::SecureShareEndorsement, operation::SecureShareOperation};
+use massa_models::{
+    endorsement::SecureShareEndorsement, node_event::NodeEvent, operation::SecureShareOperation,
+};
 use massa_pos_exports::SelectorController;
 
 /// channels used by the pool worker
@@ -11,6 +14,8 @@ pub struct PoolChannels {
     pub selector: Box<dyn SelectorController>,
     /// Broadcasts used by the pool worker to send new operations and endorsements
     pub broadcasts: PoolBroadcasts,
+    /// Bus used by the pool worker to publish expired operations
+    pub event_bus: MassaEventBus<NodeEvent>,
 }
 
 /// Broadcasts used by the pool worker to send new operations and endorsements
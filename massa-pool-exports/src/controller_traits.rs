@@ -9,6 +9,8 @@ use massa_models::{
 };
 use massa_storage::Storage;
 
+use crate::EndorsementFeedback;
+
 #[cfg(feature = "test-exports")]
 use std::sync::{Arc, RwLock};
 
@@ -24,9 +26,13 @@ pub trait PoolController: Send + Sync {
     /// Add denunciation precursor to pool
     fn add_denunciation_precursor(&self, denunciation_precursor: DenunciationPrecursor);
 
-    /// Asynchronously notify of new consensus final periods. Simply print a warning on failure.
+    /// Asynchronously notify of new consensus final periods, one per thread.
+    /// The operations whose validity range ended at these periods are expired. Simply print a warning on failure.
     fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]);
 
+    /// Asynchronously give back the endorsements of a stale block. Simply print a warning on failure.
+    fn notify_endorsement_feedback(&mut self, feedback: EndorsementFeedback);
+
     /// Get operations for block creation.
     fn get_block_operations(&self, slot: &Slot) -> (Vec<OperationId>, Storage);
 
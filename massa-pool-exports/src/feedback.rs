@@ -0,0 +1,17 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::{block_id::BlockId, endorsement::EndorsementId, prehash::PreHashSet};
+use massa_storage::Storage;
+
+/// Endorsements of a block that became stale, sent back by consensus to the pool.
+/// An endorsement is bound to the slot of the block that includes it: they can only be proposed again
+/// for another block of that slot, until the pool prunes the slot
+#[derive(Clone)]
+pub struct EndorsementFeedback {
+    /// the stale block
+    pub block_id: BlockId,
+    /// ids of the endorsements included in the stale block
+    pub endorsement_ids: PreHashSet<EndorsementId>,
+    /// storage holding references to the endorsements
+    pub storage: Storage,
+}
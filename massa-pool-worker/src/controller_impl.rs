@@ -6,7 +6,7 @@ use massa_models::{
     block_id::BlockId, denunciation::Denunciation, denunciation::DenunciationPrecursor,
     endorsement::EndorsementId, operation::OperationId, slot::Slot,
 };
-use massa_pool_exports::{PoolConfig, PoolController, PoolManager};
+use massa_pool_exports::{EndorsementFeedback, PoolConfig, PoolController, PoolManager};
 use massa_storage::Storage;
 use parking_lot::RwLock;
 use std::sync::mpsc::TrySendError;
@@ -27,6 +27,8 @@ pub enum Command {
     AddDenunciationPrecursor(DenunciationPrecursor),
     /// Notify of new final consensus periods
     NotifyFinalCsPeriods(Vec<u64>),
+    /// Give back the endorsements of a stale block
+    EndorsementFeedback(EndorsementFeedback),
     /// Stop the worker
     Stop,
 }
@@ -171,6 +173,22 @@ impl PoolController for PoolControllerImpl {
         }
     }
 
+    /// Asynchronously give back the endorsements of a stale block. Simply print a warning on failure.
+    fn notify_endorsement_feedback(&mut self, feedback: EndorsementFeedback) {
+        match self
+            .endorsements_input_sender
+            .try_send(Command::EndorsementFeedback(feedback))
+        {
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("Could not give back stale endorsements to pool: worker is unreachable.");
+            }
+            Err(TrySendError::Full(_)) => {
+                warn!("Could not give back stale endorsements to pool: worker channel is full.");
+            }
+            Ok(_) => {}
+        }
+    }
+
     /// get operations for block creation
     fn get_block_operations(&self, slot: &Slot) -> (Vec<OperationId>, Storage) {
         self.operation_pool.read().get_block_operations(slot)
@@ -6,7 +6,7 @@ use massa_models::{
     prehash::{CapacityAllocator, PreHashSet},
     slot::Slot,
 };
-use massa_pool_exports::{PoolChannels, PoolConfig};
+use massa_pool_exports::{EndorsementFeedback, PoolChannels, PoolConfig};
 use massa_storage::Storage;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
@@ -94,7 +94,23 @@ impl EndorsementPool {
     }
 
     /// Add a list of endorsements to the pool
-    pub(crate) fn add_endorsements(&mut self, mut endorsement_storage: Storage) {
+    pub(crate) fn add_endorsements(&mut self, endorsement_storage: Storage) {
+        self.insert_endorsements(endorsement_storage, self.config.broadcast_enabled);
+    }
+
+    /// Take back the endorsements of a stale block. Being bound to its slot, they can only be included
+    /// in another block of that slot. They were already broadcast when first received.
+    pub(crate) fn notify_endorsement_feedback(&mut self, feedback: EndorsementFeedback) {
+        trace!(
+            "taking back {} endorsements of stale block {}",
+            feedback.endorsement_ids.len(),
+            feedback.block_id
+        );
+        self.insert_endorsements(feedback.storage, false);
+    }
+
+    /// Insert endorsements in the pool, broadcasting them if `broadcast` is set
+    fn insert_endorsements(&mut self, mut endorsement_storage: Storage, broadcast: bool) {
         let items = endorsement_storage
             .get_endorsement_refs()
             .iter()
@@ -144,7 +160,7 @@ impl EndorsementPool {
                 }
 
                 // Broadcast endorsement to active channel subscribers.
-                if self.config.broadcast_enabled {
+                if broadcast {
                     if let Err(err) = self
                         .channels
                         .broadcasts
@@ -3,6 +3,7 @@
 use massa_models::{
     address::Address,
     amount::Amount,
+    node_event::NodeEvent,
     operation::OperationId,
     prehash::{CapacityAllocator, PreHashMap, PreHashSet},
     slot::Slot,
@@ -378,6 +379,30 @@ impl OperationPool {
             "notified of new final consensus periods: {:?}",
             self.last_cs_final_periods
         );
+
+        // expire the ops whose validity range ends at or before the final period of their thread:
+        // they can't be included in a block anymore, no need to wait for the next refresh
+        let mut removed = PreHashSet::default();
+        self.sorted_ops.retain(|op_info| {
+            let expired = *op_info.validity_period_range.end()
+                <= self.last_cs_final_periods[op_info.thread as usize];
+            if expired {
+                removed.insert(op_info.id);
+            }
+            !expired
+        });
+        if !removed.is_empty() {
+            debug!(
+                "{} operations expired with the new final consensus periods",
+                removed.len()
+            );
+            self.storage.drop_operation_refs(&removed);
+            self.channels
+                .event_bus
+                .publish(NodeEvent::OperationsExpired {
+                    operation_ids: removed,
+                });
+        }
     }
 
     /// Add a list of operations to the end of the pool.
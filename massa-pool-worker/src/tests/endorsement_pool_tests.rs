@@ -1,7 +1,8 @@
 use std::{collections::BTreeMap, time::Duration};
 
-use massa_models::{address::Address, config::THREAD_COUNT, slot::Slot};
-use massa_pool_exports::PoolConfig;
+use massa_hash::Hash;
+use massa_models::{address::Address, block_id::BlockId, config::THREAD_COUNT, slot::Slot};
+use massa_pool_exports::{EndorsementFeedback, PoolConfig};
 use massa_pos_exports::{MockSelectorController, Selection};
 use massa_signature::KeyPair;
 
@@ -71,6 +72,35 @@ fn test_add_endorsements() {
     );
 }
 
+#[test]
+fn test_endorsement_feedback() {
+    let sender_keypair = KeyPair::generate(0).unwrap();
+    let address = Address::from_public_key(&sender_keypair.get_public_key());
+    let execution_controller = default_mock_execution_controller();
+    let selector_controller = default_mock_selector(address);
+    pool_test(
+        PoolConfig::default(),
+        execution_controller,
+        selector_controller,
+        Some((address, sender_keypair.clone())),
+        |mut pool, mut storage| {
+            let endorsements = vec![
+                create_endorsement(&sender_keypair, 0, Slot::new(1, 2)),
+                create_endorsement(&sender_keypair, 1, Slot::new(1, 2)),
+            ];
+            storage.store_endorsements(endorsements);
+            pool.notify_endorsement_feedback(EndorsementFeedback {
+                block_id: BlockId::generate_from_hash(Hash::compute_from("stale".as_bytes())),
+                endorsement_ids: storage.get_endorsement_refs().clone(),
+                storage: storage.clone(),
+            });
+            // Allow some time for the pool to take the endorsements back
+            std::thread::sleep(Duration::from_secs(2));
+            assert_eq!(pool.get_endorsement_count(), 2);
+        },
+    );
+}
+
 #[test]
 fn test_dont_add_endorsements_bad_pos() {
     let sender_keypair = KeyPair::generate(0).unwrap();
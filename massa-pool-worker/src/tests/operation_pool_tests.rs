@@ -136,6 +136,73 @@ fn test_add_irrelevant_operation() {
     );
 }
 
+/// Test that the operations whose validity range ended are expired as soon as
+/// consensus notifies the new final periods.
+#[test]
+fn test_expire_operations_on_final_periods() {
+    let pool_config = PoolConfig::default();
+    let thread_count = pool_config.thread_count;
+    let execution_controller = default_mock_execution_controller();
+    let selector_controller = {
+        let mut res = Box::new(MockSelectorController::new());
+        res.expect_clone_box().times(2).returning(|| {
+            let mut story = MockSelectorController::new();
+            story
+                .expect_get_available_selections_in_range()
+                .returning(|slot_range, opt_addrs| {
+                    let mut all_slots = BTreeMap::new();
+                    let addr = *opt_addrs
+                        .expect("No addresses filter given")
+                        .iter()
+                        .next()
+                        .expect("No addresses given");
+                    for i in 0..15 {
+                        for j in 0..32 {
+                            let s = Slot::new(i, j);
+                            if slot_range.contains(&s) {
+                                all_slots.insert(
+                                    s,
+                                    Selection {
+                                        producer: addr,
+                                        endorsements: vec![addr; ENDORSEMENT_COUNT as usize],
+                                    },
+                                );
+                            }
+                        }
+                    }
+                    Ok(all_slots)
+                });
+            Box::new(story)
+        });
+        res
+    };
+    pool_test(
+        pool_config,
+        execution_controller,
+        selector_controller,
+        None,
+        |mut operation_pool, mut storage| {
+            storage.store_operations(create_some_operations(
+                10,
+                &OpGenerator::default().expirery(2),
+            ));
+            storage.store_operations(create_some_operations(
+                5,
+                &OpGenerator::default().expirery(10),
+            ));
+            operation_pool.add_operations(storage);
+            // Allow some time for the pool to add the operations
+            std::thread::sleep(Duration::from_secs(3));
+            assert_eq!(operation_pool.get_operation_count(), 15);
+
+            // the operations expiring at period 2 can't be included anymore
+            operation_pool.notify_final_cs_periods(&vec![2; thread_count.into()]);
+            std::thread::sleep(Duration::from_millis(500));
+            assert_eq!(operation_pool.get_operation_count(), 5);
+        },
+    );
+}
+
 #[test]
 fn test_pool() {
     let pool_config = PoolConfig {
@@ -4,6 +4,7 @@ use std::sync::Arc;
 
 use crate::start_pool_controller;
 use crossbeam_channel as _;
+use massa_channel::event_bus::MassaEventBus;
 use massa_execution_exports::MockExecutionController;
 use massa_hash::Hash;
 use massa_models::config::CHAINID;
@@ -123,6 +124,7 @@ impl PoolTestBoilerPlate {
                     operation_sender,
                 },
                 selector: selector_story,
+                event_bus: MassaEventBus::new("test_node_event".to_string()),
             },
             wallet,
         );
@@ -165,6 +167,7 @@ pub fn pool_test<F>(
                 operation_sender,
             },
             selector,
+            event_bus: MassaEventBus::new("test_node_event".to_string()),
         },
         wallet,
     );
@@ -61,6 +61,10 @@ impl EndorsementPoolThread {
                     .endorsement_pool
                     .write()
                     .notify_final_cs_periods(&final_cs_periods),
+                Ok(Command::EndorsementFeedback(feedback)) => self
+                    .endorsement_pool
+                    .write()
+                    .notify_endorsement_feedback(feedback),
                 _ => {
                     warn!("EndorsementPoolThread received an unexpected command");
                     continue;
@@ -175,6 +179,9 @@ impl DenunciationPoolThread {
                     .denunciation_pool
                     .write()
                     .notify_final_cs_periods(&final_cs_periods),
+                Ok(Command::EndorsementFeedback(_)) => {
+                    warn!("DenunciationPoolThread received an unexpected command");
+                }
             };
         }
     }
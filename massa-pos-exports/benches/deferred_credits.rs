@@ -0,0 +1,109 @@
+#[cfg(feature = "benchmarking")]
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+#[cfg(feature = "benchmarking")]
+fn criterion_benchmark(c: &mut Criterion) {
+    use massa_models::config::THREAD_COUNT;
+    use massa_pos_exports::{
+        test_exports::generate_deferred_credits, DeferredCredits, DeferredCreditsDeserializer,
+        DeferredCreditsSerializer,
+    };
+    use massa_serialization::{DeserializeError, Deserializer, Serializer};
+
+    const CREDIT_COUNT: u64 = 100_000;
+    const SLOT_COUNT: u64 = 1_000;
+
+    let entries = generate_deferred_credits(CREDIT_COUNT, SLOT_COUNT);
+    let mut credits = DeferredCredits::new();
+    credits.extend_from_iter(entries.iter().copied());
+    let mut serialized = Vec::new();
+    DeferredCreditsSerializer::new()
+        .serialize(&credits, &mut serialized)
+        .unwrap();
+    let deserializer = DeferredCreditsDeserializer::new(THREAD_COUNT, CREDIT_COUNT);
+
+    let mut group = c.benchmark_group("deferred credits");
+    group.throughput(Throughput::Elements(CREDIT_COUNT));
+    group.sample_size(10);
+
+    group.bench_function("insert", |b| {
+        b.iter(|| {
+            let mut credits = DeferredCredits::new();
+            for (slot, address, amount) in entries.iter() {
+                credits.insert(*slot, *address, *amount);
+            }
+            credits
+        })
+    });
+
+    group.bench_function("merge", |b| {
+        let (first, second) = entries.split_at(entries.len() / 2);
+        let mut first_credits = DeferredCredits::new();
+        first_credits.extend_from_iter(first.iter().copied());
+        let mut second_credits = DeferredCredits::new();
+        second_credits.extend_from_iter(second.iter().copied());
+        b.iter_batched(
+            || (first_credits.clone(), second_credits.clone()),
+            |(mut first_credits, second_credits)| {
+                first_credits.extend(second_credits);
+                first_credits
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("serialize", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::with_capacity(serialized.len());
+            DeferredCreditsSerializer::new()
+                .serialize(black_box(&credits), &mut buffer)
+                .unwrap();
+            buffer
+        })
+    });
+
+    group.bench_function("deserialize", |b| {
+        b.iter(|| {
+            deserializer
+                .deserialize::<DeserializeError>(black_box(&serialized))
+                .unwrap()
+        })
+    });
+
+    group.bench_function("hash", |b| {
+        b.iter(|| {
+            deserializer
+                .compute_hash_streaming(black_box(serialized.as_slice()))
+                .unwrap()
+        })
+    });
+
+    group.bench_function("payout drain", |b| {
+        b.iter_batched(
+            || credits.clone(),
+            |mut credits| {
+                let mut paid = 0u64;
+                while let Some((_slot, slot_credits)) = credits.credits.pop_first() {
+                    paid = slot_credits
+                        .values()
+                        .fold(paid, |paid, amount| paid.saturating_add(amount.to_raw()));
+                }
+                paid
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+#[cfg(feature = "benchmarking")]
+criterion_group!(benches, criterion_benchmark);
+
+#[cfg(feature = "benchmarking")]
+criterion_main!(benches);
+
+#[cfg(not(feature = "benchmarking"))]
+fn main() {
+    println!("Please use the `--features benchmarking` flag to run this benchmark.");
+}
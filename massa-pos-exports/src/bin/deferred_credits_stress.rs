@@ -0,0 +1,153 @@
+//! Stress test of the deferred credits.
+//!
+//! Generates synthetic credits and measures the throughput of their insertion, slot walk, merge,
+//! serialization, hash computation and payout, along with the peak memory of the process.
+//!
+//! Usage: `cargo run --release -p massa_pos_exports --features benchmarking --bin deferred_credits_stress -- [credit_count] [slot_count]`
+
+use std::time::{Duration, Instant};
+
+use massa_models::config::THREAD_COUNT;
+use massa_pos_exports::{
+    test_exports::generate_deferred_credits, DeferredCredits, DeferredCreditsDeserializer,
+    DeferredCreditsSerializer,
+};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+
+/// Number of generated credits if not given
+const DEFAULT_CREDIT_COUNT: u64 = 2_000_000;
+/// Number of slots the credits are spread over if not given
+const DEFAULT_SLOT_COUNT: u64 = 10_000;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let credit_count = parse_arg(args.next(), DEFAULT_CREDIT_COUNT, "credit_count");
+    let slot_count = parse_arg(args.next(), DEFAULT_SLOT_COUNT, "slot_count");
+    println!(
+        "{} deferred credits over {} slots",
+        credit_count, slot_count
+    );
+
+    let (entries, elapsed) = measure(|| generate_deferred_credits(credit_count, slot_count));
+    report("generate", credit_count, elapsed);
+
+    let (credits, elapsed) = measure(|| {
+        let mut credits = DeferredCredits::new();
+        for (slot, address, amount) in entries.iter() {
+            credits.insert(*slot, *address, *amount);
+        }
+        credits
+    });
+    report("insert", credit_count, elapsed);
+
+    // walk the credits of each slot, by copy and in place, as the payout of a slot does
+    let slots: Vec<_> = credits.credits.keys().map(|slot| slot.slot()).collect();
+    let (_, elapsed) = measure(|| {
+        slots
+            .iter()
+            .map(|slot| credits.get_slot_range(*slot..=*slot).credits.len())
+            .sum::<usize>()
+    });
+    report("slot copy", credit_count, elapsed);
+    let (_, elapsed) = measure(|| {
+        let mut walked = 0u64;
+        for slot in slots.iter() {
+            credits.for_each_credit_at(slot, |_address, _amount| walked += 1);
+        }
+        walked
+    });
+    report("slot walk", credit_count, elapsed);
+    drop(slots);
+
+    let (first, second) = entries.split_at(entries.len() / 2);
+    let mut merged = DeferredCredits::new();
+    merged.extend_from_iter(first.iter().copied());
+    let mut other = DeferredCredits::new();
+    other.extend_from_iter(second.iter().copied());
+    let ((), elapsed) = measure(|| merged.extend(other));
+    report("merge", second.len() as u64, elapsed);
+    drop(merged);
+    drop(entries);
+
+    let (serialized, elapsed) = measure(|| {
+        let mut buffer = Vec::new();
+        DeferredCreditsSerializer::new()
+            .serialize(&credits, &mut buffer)
+            .expect("could not serialize the deferred credits");
+        buffer
+    });
+    report("serialize", credit_count, elapsed);
+    println!("serialized size: {} bytes", serialized.len());
+
+    let deserializer = DeferredCreditsDeserializer::new(THREAD_COUNT, credit_count);
+    let (_, elapsed) = measure(|| {
+        deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .expect("could not deserialize the deferred credits")
+    });
+    report("deserialize", credit_count, elapsed);
+
+    let (_, elapsed) = measure(|| {
+        deserializer
+            .compute_hash_streaming(serialized.as_slice())
+            .expect("could not hash the deferred credits")
+    });
+    report("hash", credit_count, elapsed);
+    drop(serialized);
+
+    // pay the credits slot by slot, as done at each final slot
+    let (paid, elapsed) = measure(|| {
+        let mut credits = credits;
+        let mut paid = 0u64;
+        while let Some((_slot, slot_credits)) = credits.credits.pop_first() {
+            paid = slot_credits
+                .values()
+                .fold(paid, |paid, amount| paid.saturating_add(amount.to_raw()));
+        }
+        paid
+    });
+    report("payout drain", credit_count, elapsed);
+    println!("paid: {} raw units", paid);
+
+    match peak_memory_kib() {
+        Some(peak) => println!("peak memory: {} MiB", peak / 1024),
+        None => println!("peak memory: unavailable on this platform"),
+    }
+}
+
+/// Parse a positional argument, with a default value if absent
+fn parse_arg(arg: Option<String>, default: u64, name: &str) -> u64 {
+    match arg {
+        Some(arg) => arg
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid {}: {}", name, arg)),
+        None => default,
+    }
+}
+
+/// Run a phase of the stress test and measure its duration
+fn measure<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Print the duration and the throughput of a phase
+fn report(phase: &str, element_count: u64, elapsed: Duration) {
+    let throughput = element_count as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    println!(
+        "{:<12} {:>10.3} ms {:>14.0} credits/s",
+        phase,
+        elapsed.as_secs_f64() * 1000.0,
+        throughput
+    );
+}
+
+/// Peak resident memory of the process, in KiB
+fn peak_memory_kib() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
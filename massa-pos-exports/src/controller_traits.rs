@@ -5,7 +5,7 @@
 
 use std::collections::BTreeMap;
 
-use crate::PosResult;
+use crate::{OperatorRolls, PosResult};
 use massa_hash::Hash;
 use massa_models::{address::Address, prehash::PreHashSet, slot::Slot};
 
@@ -37,11 +37,14 @@ pub trait SelectorController: Send + Sync {
     /// # Arguments
     /// * `cycle`: cycle number to be drawn
     /// * `lookback_rolls`: look back rolls used for the draw (cycle - 3)
+    /// * `lookback_delegations`: operator of each delegator at look back (cycle - 3),
+    ///   the rolls of a delegator are drawn for its operator
     /// * `lookback_seed`: look back seed hash for the draw (cycle - 2)
     fn feed_cycle(
         &self,
         cycle: u64,
         lookback_rolls: BTreeMap<Address, u64>,
+        lookback_delegations: BTreeMap<Address, Address>,
         lookback_seed: Hash,
     ) -> PosResult<()>;
 
@@ -51,6 +54,27 @@ pub trait SelectorController: Send + Sync {
     /// Get [Address] of the selected block producer for a given slot
     fn get_producer(&self, slot: Slot) -> PosResult<Address>;
 
+    /// Get the rolls an operator was drawn with during a cycle,
+    /// waiting for the draws of the cycle if they are not performed yet
+    ///
+    /// # Returns
+    /// `None` if no rolls were delegated to the operator for the draws of the cycle
+    fn get_operator_rolls(
+        &self,
+        cycle: u64,
+        operator: &Address,
+    ) -> PosResult<Option<OperatorRolls>>;
+
+    /// Get the operator the rolls of a delegator were drawn for during a cycle
+    ///
+    /// # Returns
+    /// `None` if the rolls of the address were not delegated for the draws of the cycle
+    fn get_delegation_operator(
+        &self,
+        cycle: u64,
+        delegator: &Address,
+    ) -> PosResult<Option<Address>>;
+
     /// Get selections computed for a slot range (only returns available selections):
     /// # Arguments
     /// * `slot_range`: range of slots to get the selection for
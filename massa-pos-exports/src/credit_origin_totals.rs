@@ -0,0 +1,188 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Cumulative amounts of deferred credits scheduled and paid, per origin.
+//!
+//! Deferred credits are stored as a single amount per slot and address, so the origin of a paid credit
+//! is inferred from the amounts scheduled for its slot: a payout is attributed to the origins of the
+//! credits scheduled at that slot, in the order of `CreditOriginKind`, and the part that was not
+//! scheduled while the totals were maintained is `Unattributed`.
+//! Credits slashed or postponed before their slot are counted as scheduled but never as paid.
+
+use std::collections::BTreeMap;
+
+use massa_models::{address::Address, amount::Amount, slot::Slot};
+use serde::{Deserialize, Serialize};
+
+use crate::DeferredCreditOrigin;
+
+/// Origin of a deferred credit, without its details
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum CreditOriginKind {
+    /// reimbursement of sold rolls
+    RollSale,
+    /// reimbursement of the rolls of a deactivated staker
+    DeactivationRefund,
+    /// credit injected on a sandbox node
+    Injected,
+    /// share of a block reward paid to a delegator
+    DelegationReward,
+    /// paid credit that can't be traced back to an origin
+    Unattributed,
+}
+
+impl From<&DeferredCreditOrigin> for CreditOriginKind {
+    fn from(origin: &DeferredCreditOrigin) -> Self {
+        match origin {
+            DeferredCreditOrigin::RollSale => CreditOriginKind::RollSale,
+            DeferredCreditOrigin::DeactivationRefund => CreditOriginKind::DeactivationRefund,
+            DeferredCreditOrigin::Injected => CreditOriginKind::Injected,
+            DeferredCreditOrigin::DelegationReward { .. } => CreditOriginKind::DelegationReward,
+        }
+    }
+}
+
+/// A deferred credit scheduled during the execution of a slot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduledCredit {
+    /// slot at which the credit is paid
+    pub slot: Slot,
+    /// credited address
+    pub address: Address,
+    /// reason why the credit was scheduled
+    pub origin: DeferredCreditOrigin,
+    /// credited amount
+    pub amount: Amount,
+}
+
+/// Cumulative amounts of the credits of an origin
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreditOriginAmounts {
+    /// total amount scheduled
+    pub scheduled: Amount,
+    /// total amount paid
+    pub paid: Amount,
+}
+
+/// Incrementally maintained totals of the deferred credits scheduled and paid, per origin
+#[derive(Clone, Debug, Default)]
+pub struct CreditOriginTotals {
+    /// cumulative amounts per origin
+    totals: BTreeMap<CreditOriginKind, CreditOriginAmounts>,
+    /// amounts scheduled per origin at each slot that was not paid yet
+    pending: BTreeMap<Slot, BTreeMap<CreditOriginKind, Amount>>,
+}
+
+impl CreditOriginTotals {
+    /// Count a scheduled credit
+    pub fn record_scheduled(&mut self, credit: &ScheduledCredit) {
+        let kind = CreditOriginKind::from(&credit.origin);
+        let totals = self.totals.entry(kind).or_default();
+        totals.scheduled = totals.scheduled.saturating_add(credit.amount);
+        let pending = self
+            .pending
+            .entry(credit.slot)
+            .or_default()
+            .entry(kind)
+            .or_default();
+        *pending = pending.saturating_add(credit.amount);
+    }
+
+    /// Count the credits of `amount` paid at `slot`, attributing them to the origins scheduled at that slot.
+    /// The pending amounts of `slot` and of the slots before it are dropped.
+    pub fn record_paid(&mut self, slot: Slot, amount: Amount) {
+        let due_slots: Vec<Slot> = self.pending.range(..=slot).map(|(slot, _)| *slot).collect();
+        let mut due_per_kind = BTreeMap::<CreditOriginKind, Amount>::new();
+        for due_slot in due_slots {
+            for (kind, pending) in self.pending.remove(&due_slot).unwrap_or_default() {
+                let due = due_per_kind.entry(kind).or_default();
+                *due = due.saturating_add(pending);
+            }
+        }
+        let mut remaining = amount;
+        for (kind, due) in due_per_kind {
+            let paid = due.min(remaining);
+            remaining = remaining.saturating_sub(paid);
+            let totals = self.totals.entry(kind).or_default();
+            totals.paid = totals.paid.saturating_add(paid);
+        }
+        if !remaining.is_zero() {
+            let totals = self
+                .totals
+                .entry(CreditOriginKind::Unattributed)
+                .or_default();
+            totals.paid = totals.paid.saturating_add(remaining);
+        }
+    }
+
+    /// Get the cumulative amounts per origin
+    pub fn get_totals(&self) -> BTreeMap<CreditOriginKind, CreditOriginAmounts> {
+        self.totals.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_credit_origin_totals() {
+        let amount = |value: &str| Amount::from_str(value).unwrap();
+        let address =
+            Address::from_str("AU12Cyu2f7C7isA3ADAhoNuq9ZUFPKP24jmiGj3sh9D1pHoAWKDYY").unwrap();
+        let credit = |slot: Slot, origin: DeferredCreditOrigin, value: &str| ScheduledCredit {
+            slot,
+            address,
+            origin,
+            amount: amount(value),
+        };
+        let mut totals = CreditOriginTotals::default();
+        totals.record_scheduled(&credit(
+            Slot::new(3, 0),
+            DeferredCreditOrigin::RollSale,
+            "100",
+        ));
+        totals.record_scheduled(&credit(
+            Slot::new(3, 0),
+            DeferredCreditOrigin::DeactivationRefund,
+            "10",
+        ));
+        totals.record_scheduled(&credit(
+            Slot::new(5, 1),
+            DeferredCreditOrigin::RollSale,
+            "50",
+        ));
+
+        // 10 coins were slashed before the payout: the roll sale is paid first
+        totals.record_paid(Slot::new(3, 0), amount("100"));
+        // credits scheduled before the totals were maintained
+        totals.record_paid(Slot::new(4, 0), amount("7"));
+        totals.record_paid(Slot::new(5, 1), amount("50"));
+
+        let expected = BTreeMap::from([
+            (
+                CreditOriginKind::RollSale,
+                CreditOriginAmounts {
+                    scheduled: amount("150"),
+                    paid: amount("150"),
+                },
+            ),
+            (
+                CreditOriginKind::DeactivationRefund,
+                CreditOriginAmounts {
+                    scheduled: amount("10"),
+                    paid: amount("0"),
+                },
+            ),
+            (
+                CreditOriginKind::Unattributed,
+                CreditOriginAmounts {
+                    scheduled: amount("0"),
+                    paid: amount("7"),
+                },
+            ),
+        ]);
+        assert_eq!(totals.get_totals(), expected);
+        assert!(totals.pending.is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Slot at which deferred credits are paid.
+//!
+//! The credits are scheduled in slots, but their horizon, their refund delays and their payout are reasoned
+//! about in periods and cycles. Keying the credits by `CreditSlot` instead of a raw `Slot` keeps the scheduling
+//! code from mixing up the periods, the cycles and the slots of the credits: the cycle of a credit slot and
+//! whether it is payable are only available through its accessors.
+
+use std::borrow::Borrow;
+use std::fmt::Display;
+
+use massa_models::slot::Slot;
+use serde::{Deserialize, Serialize};
+
+/// Slot at which deferred credits are paid, key of `DeferredCredits`.
+///
+/// Credit slots are ordered as their slots (by period, then by thread), which is also the order of their
+/// database keys, so that the credits are iterated in payout order. They can be looked up by `Slot`.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CreditSlot(Slot);
+
+impl CreditSlot {
+    /// Credit slot paying at `slot`
+    pub const fn new(slot: Slot) -> Self {
+        CreditSlot(slot)
+    }
+
+    /// Slot at which the credits are paid
+    pub const fn slot(&self) -> Slot {
+        self.0
+    }
+
+    /// Cycle during which the credits are paid, with cycles of `periods_per_cycle` periods
+    pub fn cycle(&self, periods_per_cycle: u64) -> u64 {
+        self.0.get_cycle(periods_per_cycle)
+    }
+
+    /// Whether the credits are paid once `watermark` is executed, that is if they are paid at or before it
+    pub fn is_payable_at(&self, watermark: Slot) -> bool {
+        self.0 <= watermark
+    }
+}
+
+impl From<Slot> for CreditSlot {
+    fn from(slot: Slot) -> Self {
+        CreditSlot(slot)
+    }
+}
+
+impl From<CreditSlot> for Slot {
+    fn from(credit_slot: CreditSlot) -> Self {
+        credit_slot.0
+    }
+}
+
+/// Credit slots compare, hash and order as their slots, so that maps keyed by credit slot can be queried by slot
+impl Borrow<Slot> for CreditSlot {
+    fn borrow(&self) -> &Slot {
+        &self.0
+    }
+}
+
+impl Display for CreditSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::config::PERIODS_PER_CYCLE;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_credit_slot() {
+        let slots = [Slot::new(3, 1), Slot::new(3, 0), Slot::new(2, 31)];
+        let mut credit_slots: Vec<CreditSlot> = slots.iter().copied().map(Into::into).collect();
+        credit_slots.sort();
+
+        // same order as the slots and as their database keys
+        let mut sorted_slots = slots.to_vec();
+        sorted_slots.sort();
+        assert_eq!(
+            credit_slots
+                .iter()
+                .map(CreditSlot::slot)
+                .collect::<Vec<_>>(),
+            sorted_slots
+        );
+        assert!(credit_slots
+            .windows(2)
+            .all(|pair| pair[0].slot().to_bytes_key() < pair[1].slot().to_bytes_key()));
+
+        // maps keyed by credit slot are queried by slot
+        let credits = BTreeMap::from([(CreditSlot::new(Slot::new(3, 0)), 1u64)]);
+        assert_eq!(credits.get(&Slot::new(3, 0)), Some(&1));
+        assert_eq!(credits.range(Slot::new(3, 1)..).count(), 0);
+
+        let credit_slot = CreditSlot::new(Slot::new(PERIODS_PER_CYCLE * 2, 4));
+        assert_eq!(credit_slot.cycle(PERIODS_PER_CYCLE), 2);
+        assert_eq!(credit_slot.cycle(PERIODS_PER_CYCLE * 2), 1);
+        assert!(credit_slot.is_payable_at(Slot::new(PERIODS_PER_CYCLE * 2, 4)));
+        assert!(credit_slot.is_payable_at(Slot::new(PERIODS_PER_CYCLE * 2 + 1, 0)));
+        assert!(!credit_slot.is_payable_at(Slot::new(PERIODS_PER_CYCLE * 2, 3)));
+    }
+}
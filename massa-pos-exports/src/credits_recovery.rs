@@ -0,0 +1,117 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Recovery of the deferred credits across a reset of the PoS state.
+//!
+//! Resetting the final state (e.g. when a node keeps its ledger for a new episode) deletes the
+//! deferred credits, including pending roll reimbursements. The credits are read from the
+//! database before the reset with `read_deferred_credits`, then written back with
+//! `PoSFinalState::recover_deferred_credits`, which reports the credits it could not restore.
+
+use massa_db_exports::{ShareableMassaDBController, DEFERRED_CREDITS_PREFIX, STATE_CF};
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    slot::{Slot, SLOT_KEY_SIZE},
+};
+use massa_serialization::{DeserializeError, Deserializer};
+
+use crate::{DeferredCredits, DeferredCreditsDeserializer, PoSConfig, PosError, PosResult};
+
+/// Deferred credit that was not restored because the new state has another amount for its slot and address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeferredCreditConflict {
+    /// slot of the credit
+    pub slot: Slot,
+    /// credited address
+    pub address: Address,
+    /// amount of the previous state, left out
+    pub recovered: Amount,
+    /// amount of the new state, kept
+    pub current: Amount,
+}
+
+/// Outcome of the re-injection of the deferred credits of a previous state
+#[derive(Debug, Clone, Default)]
+pub struct DeferredCreditsRecovery {
+    /// credits written to the new state
+    pub restored: DeferredCredits,
+    /// number of credits that the new state already had with the same amount
+    pub already_present: usize,
+    /// credits at slots that the new state already executed, they would never be paid
+    pub expired: DeferredCredits,
+    /// credits left out because the new state has another amount for them
+    pub conflicts: Vec<DeferredCreditConflict>,
+}
+
+impl DeferredCreditsRecovery {
+    /// Number of restored credits
+    pub fn restored_count(&self) -> usize {
+        self.restored
+            .credits
+            .values()
+            .map(|credits| credits.len())
+            .sum()
+    }
+
+    /// Total amount of the restored credits
+    pub fn restored_amount(&self) -> Amount {
+        self.restored
+            .credits
+            .values()
+            .flat_map(|credits| credits.values())
+            .fold(Amount::zero(), |sum, amount| sum.saturating_add(*amount))
+    }
+}
+
+/// Read all the deferred credits stored in the database
+///
+/// Does not need a `PoSFinalState`, so that the credits can be read before the state is loaded and reset.
+pub fn read_deferred_credits(
+    db: &ShareableMassaDBController,
+    config: &PoSConfig,
+) -> PosResult<DeferredCredits> {
+    let deserializer = DeferredCreditsDeserializer::try_new(config)?;
+    let db = db.read();
+
+    let mut deferred_credits = DeferredCredits::new();
+    for (serialized_key, serialized_value) in
+        db.prefix_iterator_cf(STATE_CF, DEFERRED_CREDITS_PREFIX.as_bytes())
+    {
+        if !serialized_key.starts_with(DEFERRED_CREDITS_PREFIX.as_bytes()) {
+            break;
+        }
+
+        let rest_key = &serialized_key[DEFERRED_CREDITS_PREFIX.len()..];
+        let slot_key: &[u8; SLOT_KEY_SIZE] = rest_key
+            .get(..SLOT_KEY_SIZE)
+            .and_then(|slot_key| slot_key.try_into().ok())
+            .ok_or_else(|| {
+                PosError::ContainerInconsistency("deferred credit key is too short".to_string())
+            })?;
+        let slot = Slot::from_bytes_key(slot_key);
+
+        let (_, address) = deserializer
+            .credit_deserializer
+            .address_deserializer
+            .deserialize::<DeserializeError>(&rest_key[SLOT_KEY_SIZE..])
+            .map_err(|err| {
+                PosError::ContainerInconsistency(format!(
+                    "invalid address in deferred credit key at slot {}: {}",
+                    slot, err
+                ))
+            })?;
+        let (_, amount) = deserializer
+            .credit_deserializer
+            .amount_deserializer
+            .deserialize::<DeserializeError>(&serialized_value)
+            .map_err(|err| {
+                PosError::ContainerInconsistency(format!(
+                    "invalid amount of the deferred credit of {} at slot {}: {}",
+                    address, slot, err
+                ))
+            })?;
+
+        deferred_credits.insert(slot, address, amount);
+    }
+    Ok(deferred_credits)
+}
@@ -0,0 +1,348 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Observer of the deferred credits of a watch-list of addresses.
+//!
+//! Monitoring integrations register the addresses they follow and subscribe to the events
+//! emitted when the final deferred credits of these addresses change, instead of polling the state.
+//! External databases mirroring the whole payout schedule subscribe to the changes of all addresses.
+//! Alerts are raised when the total amount scheduled for an upcoming slot exceeds a threshold,
+//! to catch protocol bugs or attacks that would mint huge payouts before they are executed.
+
+use std::sync::Arc;
+
+use massa_models::{address::Address, amount::Amount, prehash::PreHashSet, slot::Slot};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// Capacity of the channel of the deferred credit events
+const DEFERRED_CREDIT_EVENTS_CHANNEL_CAPACITY: usize = 1024;
+
+/// Change of a deferred credit of a watched address, amounts are rendered in their canonical form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeferredCreditChange {
+    /// a new deferred credit was scheduled
+    Inserted {
+        /// credited amount
+        #[serde(with = "massa_models::amount::canonical")]
+        amount: Amount,
+    },
+    /// the amount of a scheduled deferred credit changed (e.g. slashing)
+    Modified {
+        /// amount before the change
+        #[serde(with = "massa_models::amount::canonical")]
+        previous: Amount,
+        /// amount after the change
+        #[serde(with = "massa_models::amount::canonical")]
+        amount: Amount,
+    },
+    /// the deferred credit was paid
+    Paid {
+        /// paid amount
+        #[serde(with = "massa_models::amount::canonical")]
+        amount: Amount,
+    },
+    /// the deferred credit was removed before being paid
+    Removed {
+        /// amount before the removal
+        #[serde(with = "massa_models::amount::canonical")]
+        previous: Amount,
+    },
+}
+
+/// Event emitted when a final deferred credit of a watched address changes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeferredCreditEvent {
+    /// final slot at which the change happened
+    pub slot: Slot,
+    /// slot of the deferred credit
+    pub credit_slot: Slot,
+    /// credited address
+    pub address: Address,
+    /// the change
+    pub change: DeferredCreditChange,
+}
+
+impl DeferredCreditEvent {
+    /// Build the event corresponding to the change of a deferred credit, if there is any
+    ///
+    /// # Arguments
+    /// * `slot`: final slot at which the change happened
+    /// * `credit_slot`: slot of the deferred credit
+    /// * `address`: credited address
+    /// * `previous`: amount of the credit before the change, if it existed
+    /// * `amount`: amount of the credit after the change, zero if it was removed or paid
+    pub fn from_change(
+        slot: Slot,
+        credit_slot: Slot,
+        address: Address,
+        previous: Option<Amount>,
+        amount: Amount,
+    ) -> Option<Self> {
+        let change = match previous.filter(|previous| !previous.is_zero()) {
+            None if amount.is_zero() => return None,
+            None => DeferredCreditChange::Inserted { amount },
+            Some(previous) if previous == amount => return None,
+            // credits are set to zero when they are paid at their slot
+            Some(previous) if amount.is_zero() && credit_slot <= slot => {
+                DeferredCreditChange::Paid { amount: previous }
+            }
+            Some(previous) if amount.is_zero() => DeferredCreditChange::Removed { previous },
+            Some(previous) => DeferredCreditChange::Modified { previous, amount },
+        };
+        Some(DeferredCreditEvent {
+            slot,
+            credit_slot,
+            address,
+            change,
+        })
+    }
+}
+
+/// Change of a final deferred credit of any address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreditChange {
+    /// final slot at which the change happened
+    pub slot: Slot,
+    /// slot of the deferred credit
+    pub credit_slot: Slot,
+    /// credited address
+    pub address: Address,
+    /// amount before the change, zero if the credit did not exist
+    #[serde(with = "massa_models::amount::canonical")]
+    pub old: Amount,
+    /// amount after the change, zero if the credit was paid or removed
+    #[serde(with = "massa_models::amount::canonical")]
+    pub new: Amount,
+}
+
+impl CreditChange {
+    /// Build the change of a deferred credit, `None` if the amount did not change
+    pub fn new(
+        slot: Slot,
+        credit_slot: Slot,
+        address: Address,
+        previous: Option<Amount>,
+        amount: Amount,
+    ) -> Option<Self> {
+        let old = previous.unwrap_or_default();
+        if old == amount {
+            return None;
+        }
+        Some(CreditChange {
+            slot,
+            credit_slot,
+            address,
+            old,
+            new: amount,
+        })
+    }
+}
+
+/// Alert raised when the total amount of the deferred credits of an upcoming slot exceeds the threshold
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotCreditsAlert {
+    /// final slot at which the credits were scheduled
+    pub slot: Slot,
+    /// slot of the deferred credits
+    pub credit_slot: Slot,
+    /// total amount scheduled for `credit_slot`
+    #[serde(with = "massa_models::amount::canonical")]
+    pub total: Amount,
+    /// threshold that was exceeded
+    #[serde(with = "massa_models::amount::canonical")]
+    pub threshold: Amount,
+}
+
+/// Shared watch-list of addresses and channels of the events about deferred credits
+#[derive(Clone)]
+pub struct DeferredCreditsWatcher {
+    /// watched addresses
+    watch_list: Arc<RwLock<PreHashSet<Address>>>,
+    /// sender of the events about the watched addresses
+    sender: broadcast::Sender<DeferredCreditEvent>,
+    /// sender of the changes of all addresses
+    changes_sender: broadcast::Sender<CreditChange>,
+    /// total amount of the credits of a slot above which an alert is raised, `None` to disable the alerts
+    slot_alert_threshold: Arc<RwLock<Option<Amount>>>,
+    /// sender of the alerts about the totals of the slots
+    alerts_sender: broadcast::Sender<SlotCreditsAlert>,
+}
+
+impl Default for DeferredCreditsWatcher {
+    fn default() -> Self {
+        Self::new(DEFERRED_CREDIT_EVENTS_CHANNEL_CAPACITY)
+    }
+}
+
+impl DeferredCreditsWatcher {
+    /// Creates a watcher with an empty watch-list
+    pub fn new(channel_capacity: usize) -> Self {
+        Self {
+            watch_list: Default::default(),
+            sender: broadcast::channel(channel_capacity).0,
+            changes_sender: broadcast::channel(channel_capacity).0,
+            slot_alert_threshold: Default::default(),
+            alerts_sender: broadcast::channel(channel_capacity).0,
+        }
+    }
+
+    /// Add addresses to the watch-list
+    pub fn watch(&self, addresses: impl IntoIterator<Item = Address>) {
+        self.watch_list.write().extend(addresses);
+    }
+
+    /// Remove addresses from the watch-list
+    pub fn unwatch(&self, addresses: &[Address]) {
+        let mut watch_list = self.watch_list.write();
+        for address in addresses {
+            watch_list.remove(address);
+        }
+    }
+
+    /// Get the watched addresses
+    pub fn get_watch_list(&self) -> PreHashSet<Address> {
+        self.watch_list.read().clone()
+    }
+
+    /// Whether an address is watched
+    pub fn is_watched(&self, address: &Address) -> bool {
+        self.watch_list.read().contains(address)
+    }
+
+    /// Whether events need to be computed: some addresses are watched and someone listens
+    pub fn is_active(&self) -> bool {
+        self.sender.receiver_count() > 0 && !self.watch_list.read().is_empty()
+    }
+
+    /// Get the sender of the events, to subscribe to them
+    pub fn get_sender(&self) -> broadcast::Sender<DeferredCreditEvent> {
+        self.sender.clone()
+    }
+
+    /// Emit an event, it is dropped if nobody listens
+    pub fn notify(&self, event: DeferredCreditEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Subscribe to the changes of the deferred credits of all addresses
+    pub fn subscribe_credit_changes(&self) -> broadcast::Receiver<CreditChange> {
+        self.changes_sender.subscribe()
+    }
+
+    /// Whether someone listens to the changes of all addresses
+    pub fn has_change_subscribers(&self) -> bool {
+        self.changes_sender.receiver_count() > 0
+    }
+
+    /// Emit a change, it is dropped if nobody listens
+    pub fn notify_change(&self, change: CreditChange) {
+        let _ = self.changes_sender.send(change);
+    }
+
+    /// Set the total amount of the credits of a slot above which an alert is raised, `None` to disable the alerts
+    pub fn set_slot_alert_threshold(&self, threshold: Option<Amount>) {
+        *self.slot_alert_threshold.write() = threshold;
+    }
+
+    /// Get the total amount of the credits of a slot above which an alert is raised
+    pub fn get_slot_alert_threshold(&self) -> Option<Amount> {
+        *self.slot_alert_threshold.read()
+    }
+
+    /// Subscribe to the alerts about the totals of the slots
+    pub fn subscribe_slot_alerts(&self) -> broadcast::Receiver<SlotCreditsAlert> {
+        self.alerts_sender.subscribe()
+    }
+
+    /// Log an alert and emit it, the event is dropped if nobody listens
+    pub fn notify_slot_alert(&self, alert: SlotCreditsAlert) {
+        warn!(
+            "deferred credits of slot {} total {} MAS, above the alert threshold of {} MAS (scheduled at slot {})",
+            alert.credit_slot, alert.total, alert.threshold, alert.slot
+        );
+        let _ = self.alerts_sender.send(alert);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_deferred_credit_event_from_change() {
+        let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let slot = Slot::new(10, 0);
+        let event = |credit_slot, previous, amount| {
+            DeferredCreditEvent::from_change(slot, credit_slot, address, previous, amount)
+                .map(|event| event.change)
+        };
+        let one = Amount::from_raw(1);
+        let two = Amount::from_raw(2);
+        let future = Slot::new(20, 0);
+
+        assert_eq!(
+            event(future, None, one),
+            Some(DeferredCreditChange::Inserted { amount: one })
+        );
+        assert_eq!(
+            event(future, Some(two), one),
+            Some(DeferredCreditChange::Modified {
+                previous: two,
+                amount: one
+            })
+        );
+        assert_eq!(
+            event(slot, Some(two), Amount::zero()),
+            Some(DeferredCreditChange::Paid { amount: two })
+        );
+        assert_eq!(
+            event(future, Some(two), Amount::zero()),
+            Some(DeferredCreditChange::Removed { previous: two })
+        );
+        assert_eq!(event(future, Some(one), one), None);
+        assert_eq!(event(future, None, Amount::zero()), None);
+    }
+
+    #[test]
+    fn test_credit_changes_subscription() {
+        let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let slot = Slot::new(10, 0);
+        let credit_slot = Slot::new(20, 0);
+        let watcher = DeferredCreditsWatcher::default();
+        assert!(!watcher.has_change_subscribers());
+
+        let mut receiver = watcher.subscribe_credit_changes();
+        assert!(watcher.has_change_subscribers());
+        let one = Amount::from_raw(1);
+        assert_eq!(
+            CreditChange::new(slot, credit_slot, address, Some(one), one),
+            None
+        );
+        let change = CreditChange::new(slot, credit_slot, address, None, one).unwrap();
+        assert_eq!(change.old, Amount::zero());
+        watcher.notify_change(change.clone());
+        assert_eq!(receiver.try_recv().unwrap(), change);
+    }
+
+    #[test]
+    fn test_slot_alerts_subscription() {
+        let watcher = DeferredCreditsWatcher::default();
+        assert_eq!(watcher.get_slot_alert_threshold(), None);
+        let threshold = Amount::from_raw(10);
+        watcher.set_slot_alert_threshold(Some(threshold));
+        assert_eq!(watcher.get_slot_alert_threshold(), Some(threshold));
+
+        let mut receiver = watcher.subscribe_slot_alerts();
+        let alert = SlotCreditsAlert {
+            slot: Slot::new(10, 0),
+            credit_slot: Slot::new(20, 0),
+            total: Amount::from_raw(11),
+            threshold,
+        };
+        watcher.notify_slot_alert(alert.clone());
+        assert_eq!(receiver.try_recv().unwrap(), alert);
+    }
+}
@@ -1,11 +1,19 @@
+use massa_db_exports::DEFERRED_CREDITS_PREFIX;
+use massa_hash::{HashXof, HASH_XOF_SIZE_BYTES};
 use massa_models::{
     address::{Address, AddressDeserializer, AddressSerializer},
     amount::{Amount, AmountDeserializer, AmountSerializer},
-    prehash::PreHashMap,
+    config::{
+        MAX_DEFERRED_CREDITS_LENGTH, MAX_DEFERRED_CREDIT_PERIOD_HORIZON, PERIODS_PER_CYCLE,
+        THREAD_COUNT,
+    },
+    error::ModelsError,
+    prehash::{PreHashMap, PreHashSet},
     slot::{Slot, SlotDeserializer, SlotSerializer},
 };
 use massa_serialization::{
-    Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+    DeserializeError, Deserializer, SerializeError, Serializer, U64VarIntDeserializer,
+    U64VarIntSerializer,
 };
 use nom::{
     error::{context, ContextError, ParseError},
@@ -14,22 +22,91 @@ use nom::{
     IResult, Parser,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, ops::RangeBounds};
+
+use crate::{CreditSlot, PoSConfig, PosError, PosResult, StateHasher, XorBlake3Hasher};
+use std::{borrow::Borrow, collections::BTreeMap, io::Read, ops::RangeBounds};
 use std::{
-    fmt::Debug,
+    fmt::{Debug, Display},
     ops::Bound::{Excluded, Included},
 };
 
+/// Reason why a deferred credit was scheduled.
+/// Deferred credits are stored as a single amount per slot and address: the origin of a credit is not part
+/// of the final state, it is only known by the execution step that schedules the credit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeferredCreditOrigin {
+    /// reimbursement of sold rolls
+    RollSale,
+    /// reimbursement of the rolls of a staker deactivated for missing too many blocks
+    DeactivationRefund,
+    /// credit injected from a CSV file on a sandbox node, for scenario testing
+    Injected,
+    /// share of the block reward of an operator, paid to a delegator of its rolls
+    DelegationReward {
+        /// the operator that produced the block
+        operator: Address,
+        /// slot of the rewarded block
+        block_slot: Slot,
+    },
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 /// Structure containing all the PoS deferred credits information
 pub struct DeferredCredits {
-    /// Deferred credits
-    pub credits: BTreeMap<Slot, PreHashMap<Address, Amount>>,
+    /// Deferred credits, by payout slot
+    pub credits: BTreeMap<CreditSlot, PreHashMap<Address, Amount>>,
+}
+
+/// Number of addresses listed in the summary of deferred credits
+const SUMMARY_TOP_ADDRESSES: usize = 3;
+
+impl Display for DeferredCredits {
+    /// Summarize the credits (count, slots, total amount and top addresses),
+    /// as there can be millions of them. `dump` gives all the credits.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (Some(first_slot), Some(last_slot)) =
+            (self.credits.keys().next(), self.credits.keys().next_back())
+        else {
+            return write!(f, "no deferred credits");
+        };
+        let mut count = 0usize;
+        let mut total = Amount::zero();
+        let mut address_totals = PreHashMap::<Address, Amount>::default();
+        for slot_credits in self.credits.values() {
+            for (address, amount) in slot_credits {
+                count += 1;
+                total = total.saturating_add(*amount);
+                let address_total = address_totals.entry(*address).or_default();
+                *address_total = address_total.saturating_add(*amount);
+            }
+        }
+        // ties are broken by address so that the summary is deterministic
+        let mut top_addresses: Vec<(Address, Amount)> = address_totals.into_iter().collect();
+        top_addresses.sort_unstable_by(|(address1, amount1), (address2, amount2)| {
+            amount2.cmp(amount1).then(address1.cmp(address2))
+        });
+        top_addresses.truncate(SUMMARY_TOP_ADDRESSES);
+
+        write!(
+            f,
+            "{} deferred credits at {} slots from {} to {}, total {} coins, top addresses:",
+            count,
+            self.credits.len(),
+            first_slot,
+            last_slot,
+            total
+        )?;
+        for (address, amount) in top_addresses {
+            write!(f, " {} ({})", address, amount)?;
+        }
+        Ok(())
+    }
 }
 
 impl Debug for DeferredCredits {
+    /// Same summary as `Display`, so that debug logs of the structures holding credits stay short
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.credits)
+        write!(f, "{}", self)
     }
 }
 
@@ -40,6 +117,11 @@ impl Default for DeferredCredits {
 }
 
 impl DeferredCredits {
+    /// Full dump of all the credits, for explicit debugging only as it can be huge
+    pub fn dump(&self) -> String {
+        format!("{:?}", self.credits)
+    }
+
     /// Check if the credits list is empty
     pub fn is_empty(&self) -> bool {
         self.credits.is_empty()
@@ -59,7 +141,7 @@ impl DeferredCredits {
     {
         for (slot, credits) in &mut self.credits {
             for (address, amount) in credits {
-                f(slot, address, amount);
+                f(slot.borrow(), address, amount);
             }
         }
     }
@@ -81,8 +163,28 @@ impl DeferredCredits {
     /// Extends the current `DeferredCredits` with another and replace the amounts for existing addresses
     pub fn extend(&mut self, other: Self) {
         for (slot, credits) in other.credits {
-            for (address, amount) in credits {
-                self.insert(slot, address, amount);
+            self.credits.entry(slot).or_default().extend(credits);
+        }
+    }
+
+    /// Insert a batch of unsorted `(slot, address, amount)` credits, replacing the amounts for existing addresses.
+    ///
+    /// The credits are grouped by slot beforehand so that the slot map is looked up once per slot
+    /// instead of once per credit. If the same address appears several times at the same slot,
+    /// the last amount wins, as with repeated calls to `insert`.
+    /// Credits that are already grouped by slot should be merged with `extend` instead.
+    pub fn extend_from_iter(&mut self, iter: impl Iterator<Item = (Slot, Address, Amount)>) {
+        let mut entries: Vec<(Slot, Address, Amount)> = iter.collect();
+        // stable sort to keep the insertion order within a slot
+        entries.sort_by_key(|(slot, _, _)| *slot);
+        let mut entries = entries.into_iter().peekable();
+        while let Some((slot, address, amount)) = entries.next() {
+            let slot_credits = self.credits.entry(slot.into()).or_default();
+            slot_credits.insert(address, amount);
+            while let Some((_, address, amount)) =
+                entries.next_if(|(next_slot, _, _)| *next_slot == slot)
+            {
+                slot_credits.insert(address, amount);
             }
         }
     }
@@ -111,6 +213,23 @@ impl DeferredCredits {
         }
     }
 
+    /// Check if there are credits at a given slot
+    pub fn contains(&self, slot: &Slot) -> bool {
+        self.credits.contains_key(slot)
+    }
+
+    /// Apply a function to each credit of a given slot, without copying the credits of the slot
+    pub fn for_each_credit_at<F>(&self, slot: &Slot, mut f: F)
+    where
+        F: FnMut(&Address, &Amount),
+    {
+        if let Some(slot_credits) = self.credits.get(slot) {
+            for (address, amount) in slot_credits {
+                f(address, amount);
+            }
+        }
+    }
+
     /// Gets the deferred credits for a given address that will be credited at a given slot
     pub fn get_address_credits_for_slot(&self, addr: &Address, slot: &Slot) -> Option<Amount> {
         self.credits
@@ -122,10 +241,108 @@ impl DeferredCredits {
     /// Insert an element
     pub fn insert(&mut self, slot: Slot, address: Address, amount: Amount) -> Option<Amount> {
         self.credits
-            .entry(slot)
+            .entry(slot.into())
             .or_default()
             .insert(address, amount)
     }
+
+    /// Compute the changes postponing all the credits by `delay_periods` periods, each credit keeping its thread.
+    ///
+    /// The credits must contain all the pending credits from some slot onwards, as the credits already
+    /// scheduled at the new slots are postponed as well. Each credit is moved with `reschedule`, starting
+    /// from the latest slot so that the credits already scheduled at a new slot have been moved away first.
+    /// The changes only depend on the credits, so that all the nodes compute the same ones.
+    ///
+    /// # Returns
+    /// The changes to apply on top of the credits, or `None` if a postponed period overflows
+    pub fn postponed_changes(&self, delay_periods: u64) -> Option<DeferredCredits> {
+        let mut changes = DeferredCredits::new();
+        for (credit_slot, slot_credits) in self.credits.iter().rev() {
+            let slot = credit_slot.slot();
+            let postponed_slot = Slot::new(slot.period.checked_add(delay_periods)?, slot.thread);
+            for (address, amount) in slot_credits {
+                if !amount.is_zero() {
+                    self.reschedule(&mut changes, address, slot, postponed_slot)?;
+                }
+            }
+        }
+        Some(changes)
+    }
+
+    /// Record in `changes` the move of the credit of an address from a slot to another, keeping its amount.
+    ///
+    /// The credits must contain all the pending credits of the address at both slots, and `changes` are
+    /// the changes to apply on top of them: the amount of a slot is read from `changes` if they set it,
+    /// from the credits otherwise. The credit at `from_slot` is set to zero in the changes, so that
+    /// applying them deletes it from the final state along with its contribution to the final state hash,
+    /// and the credit at `to_slot` is set to the sum of the amounts of both slots.
+    ///
+    /// # Returns
+    /// The amount credited at `to_slot` after the move, or `None` if the address has no credit at
+    /// `from_slot` or if the sum overflows, in which case the changes are left unchanged
+    pub fn reschedule(
+        &self,
+        changes: &mut DeferredCredits,
+        addr: &Address,
+        from_slot: Slot,
+        to_slot: Slot,
+    ) -> Option<Amount> {
+        let current = |slot: &Slot| {
+            changes
+                .get_address_credits_for_slot(addr, slot)
+                .or_else(|| self.get_address_credits_for_slot(addr, slot))
+        };
+        let amount = current(&from_slot).filter(|amount| !amount.is_zero())?;
+        if from_slot == to_slot {
+            return Some(amount);
+        }
+        let total = current(&to_slot).unwrap_or_default().checked_add(amount)?;
+        changes.insert(from_slot, *addr, Amount::zero());
+        changes.insert(to_slot, *addr, total);
+        Some(total)
+    }
+}
+
+/// Number of cycles between the cycle of a roll sale and the cycle at the end of which the sold rolls are reimbursed
+pub const ROLL_REFUND_CYCLE_DELAY: u64 = 3;
+
+/// Slot at which the reimbursement of the rolls sold at `sale_slot` is credited:
+/// the last slot of the `ROLL_REFUND_CYCLE_DELAY`-th cycle after the sale.
+/// A reimbursement split by `split_credit` is paid over the consecutive slots starting at this one.
+pub fn roll_refund_slot(
+    sale_slot: Slot,
+    periods_per_cycle: u64,
+    thread_count: u8,
+) -> Result<Slot, ModelsError> {
+    let refund_cycle = sale_slot
+        .get_cycle(periods_per_cycle)
+        .checked_add(ROLL_REFUND_CYCLE_DELAY)
+        .ok_or(ModelsError::PeriodOverflowError)?;
+    Slot::new_last_of_cycle(refund_cycle, periods_per_cycle, thread_count)
+}
+
+/// Estimate the slot at which the reimbursement of rolls sold at `current_slot` would be credited,
+/// with the cycle length and thread count of the network
+pub fn estimate_roll_refund_slot(current_slot: Slot) -> Result<Slot, ModelsError> {
+    roll_refund_slot(current_slot, PERIODS_PER_CYCLE, THREAD_COUNT)
+}
+
+/// Split a credit into `parts` equal credits if it is strictly above `threshold`.
+/// The remainder of the division, in the smallest unit, is added to the first part
+/// so that the parts always sum up to the original amount.
+/// The credit is returned as a single part if it is not above the threshold or if `parts` is lower than 2.
+pub fn split_credit(amount: Amount, threshold: Amount, parts: u64) -> Vec<Amount> {
+    if parts < 2 || amount <= threshold {
+        return vec![amount];
+    }
+    let (Some(part), Some(remainder)) =
+        (amount.checked_div_u64(parts), amount.checked_rem_u64(parts))
+    else {
+        return vec![amount];
+    };
+    let mut split = vec![part; parts as usize];
+    split[0] = part.saturating_add(remainder);
+    split
 }
 
 #[derive(Clone)]
@@ -166,7 +383,7 @@ impl Serializer<DeferredCredits> for DeferredCreditsSerializer {
         // deferred credits
         for (slot, credits) in &value.credits {
             // slot
-            self.slot_ser.serialize(slot, buffer)?;
+            self.slot_ser.serialize(slot.borrow(), buffer)?;
             // credits
             self.credits_ser.serialize(credits, buffer)?;
         }
@@ -181,6 +398,7 @@ pub struct DeferredCreditsDeserializer {
     pub u64_deserializer: U64VarIntDeserializer,
     pub slot_deserializer: SlotDeserializer,
     pub credit_deserializer: CreditsDeserializer,
+    thread_count: u8,
 }
 
 impl DeferredCreditsDeserializer {
@@ -196,7 +414,178 @@ impl DeferredCreditsDeserializer {
                 (Included(0), Excluded(thread_count)),
             ),
             credit_deserializer: CreditsDeserializer::new(max_credits_length),
+            thread_count,
+        }
+    }
+
+    /// Creates a new `DeferredCredits` deserializer from a configuration checked against the network constants
+    pub fn try_new(config: &PoSConfig) -> PosResult<DeferredCreditsDeserializer> {
+        if config.thread_count == 0 || config.thread_count > THREAD_COUNT {
+            return Err(PosError::InvalidThreadCount {
+                thread_count: config.thread_count,
+                max: THREAD_COUNT,
+            });
+        }
+        if config.max_credit_length == 0 || config.max_credit_length > MAX_DEFERRED_CREDITS_LENGTH {
+            return Err(PosError::InvalidMaxCreditLength {
+                max_credit_length: config.max_credit_length,
+                max: MAX_DEFERRED_CREDITS_LENGTH,
+            });
         }
+        Ok(Self::new(config.thread_count, config.max_credit_length))
+    }
+
+    /// Only accept the credit slots that can have been scheduled when `last_final_slot` is the last final slot
+    pub fn with_slot_watermark(mut self, last_final_slot: Slot) -> DeferredCreditsDeserializer {
+        self.slot_deserializer = SlotDeserializer::new(
+            (Included(0), Included(max_credit_period(last_final_slot))),
+            (Included(0), Excluded(self.thread_count)),
+        );
+        self
+    }
+}
+
+/// Latest period at which a deferred credit can be scheduled when `last_final_slot` is the last final slot
+pub fn max_credit_period(last_final_slot: Slot) -> u64 {
+    last_final_slot
+        .period
+        .saturating_add(MAX_DEFERRED_CREDIT_PERIOD_HORIZON)
+}
+
+/// Maximum serialized size of a single element of the deferred credits (length, slot, address or amount)
+const MAX_STREAMED_ELEMENT_SIZE: usize = 128;
+
+impl DeferredCreditsDeserializer {
+    /// Recompute the hash contribution of serialized deferred credits to the final state hash,
+    /// reading them from a stream without building the credits map.
+    ///
+    /// The result is the XOR of the hashes of the database entries the credits would be stored as,
+    /// so that it can be checked before committing the credits.
+    /// Slots must be sorted in increasing order, as produced by `DeferredCreditsSerializer`.
+    /// Memory usage only depends on the number of credits of the largest slot.
+    pub fn compute_hash_streaming<R: Read>(
+        &self,
+        reader: R,
+    ) -> PosResult<HashXof<HASH_XOF_SIZE_BYTES>> {
+        self.compute_hash_streaming_with(reader, &XorBlake3Hasher)
+    }
+
+    /// Same as `compute_hash_streaming`, with the entries hashed by `hasher`
+    pub fn compute_hash_streaming_with<R: Read, H: StateHasher>(
+        &self,
+        reader: R,
+        hasher: &H,
+    ) -> PosResult<H::Output> {
+        let mut stream = StreamBuffer::new(reader);
+        let address_ser = AddressSerializer::new();
+        let amount_ser = AmountSerializer::new();
+        let mut hash = hasher.empty();
+        let mut previous_slot: Option<Slot> = None;
+
+        let slot_count = stream.parse(|input| self.u64_deserializer.deserialize(input))?;
+        for _ in 0..slot_count {
+            let slot = stream.parse(|input| self.slot_deserializer.deserialize(input))?;
+            if previous_slot.map_or(false, |previous| previous >= slot) {
+                return Err(PosError::DeferredCreditsStreamError(format!(
+                    "slot {} is not sorted",
+                    slot
+                )));
+            }
+            previous_slot = Some(slot);
+
+            let credit_count = stream
+                .parse(|input| self.credit_deserializer.u64_deserializer.deserialize(input))?;
+            let mut slot_addresses = PreHashSet::<Address>::default();
+            for _ in 0..credit_count {
+                let address = stream.parse(|input| {
+                    self.credit_deserializer
+                        .address_deserializer
+                        .deserialize(input)
+                })?;
+                let amount = stream.parse(|input| {
+                    self.credit_deserializer
+                        .amount_deserializer
+                        .deserialize(input)
+                })?;
+                if !slot_addresses.insert(address) {
+                    return Err(PosError::DeferredCreditsStreamError(format!(
+                        "duplicate credit for address {} at slot {}",
+                        address, slot
+                    )));
+                }
+                // zero credits are not stored
+                if amount.is_zero() {
+                    continue;
+                }
+                let mut key = DEFERRED_CREDITS_PREFIX.as_bytes().to_vec();
+                key.extend_from_slice(&slot.to_bytes_key());
+                address_ser
+                    .serialize(&address, &mut key)
+                    .map_err(|err| PosError::DeferredCreditsStreamError(err.to_string()))?;
+                let mut value = Vec::new();
+                amount_ser
+                    .serialize(&amount, &mut value)
+                    .map_err(|err| PosError::DeferredCreditsStreamError(err.to_string()))?;
+                hasher.add(&mut hash, &key, &value);
+            }
+        }
+        if !stream.is_exhausted()? {
+            return Err(PosError::DeferredCreditsStreamError(
+                "trailing data after the deferred credits".into(),
+            ));
+        }
+        Ok(hash)
+    }
+}
+
+/// Bounded buffer over a reader, used to deserialize a stream one element at a time
+struct StreamBuffer<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: Read> StreamBuffer<R> {
+    fn new(reader: R) -> Self {
+        StreamBuffer {
+            reader,
+            buffer: Vec::with_capacity(2 * MAX_STREAMED_ELEMENT_SIZE),
+            eof: false,
+        }
+    }
+
+    /// Read from the stream until the buffer contains at least one full element, or the stream ends
+    fn fill(&mut self) -> PosResult<()> {
+        let mut chunk = [0u8; MAX_STREAMED_ELEMENT_SIZE];
+        while !self.eof && self.buffer.len() < MAX_STREAMED_ELEMENT_SIZE {
+            match self.reader.read(&mut chunk) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(PosError::DeferredCreditsStreamError(err.to_string())),
+            }
+        }
+        Ok(())
+    }
+
+    /// Deserialize the next element of the stream
+    fn parse<T, F>(&mut self, parser: F) -> PosResult<T>
+    where
+        F: for<'a> Fn(&'a [u8]) -> IResult<&'a [u8], T, DeserializeError<'a>>,
+    {
+        self.fill()?;
+        let (consumed, value) = match parser(&self.buffer) {
+            Ok((rest, value)) => (self.buffer.len() - rest.len(), value),
+            Err(err) => return Err(PosError::DeferredCreditsStreamError(err.to_string())),
+        };
+        self.buffer.drain(..consumed);
+        Ok(value)
+    }
+
+    /// Returns true if all the stream was consumed
+    fn is_exhausted(&mut self) -> PosResult<bool> {
+        self.fill()?;
+        Ok(self.buffer.is_empty())
     }
 }
 
@@ -222,7 +611,10 @@ impl Deserializer<DeferredCredits> for DeferredCreditsDeserializer {
             ),
         )
         .map(|elements| DeferredCredits {
-            credits: elements.into_iter().collect(),
+            credits: elements
+                .into_iter()
+                .map(|(slot, credits)| (CreditSlot::from(slot), credits))
+                .collect(),
         })
         .parse(buffer)
     }
@@ -277,7 +669,7 @@ impl Serializer<PreHashMap<Address, Amount>> for CreditsSerializer {
 #[allow(missing_docs)]
 /// Deserializer for a single credit
 pub struct CreditsDeserializer {
-    u64_deserializer: U64VarIntDeserializer,
+    pub u64_deserializer: U64VarIntDeserializer,
     pub address_deserializer: AddressDeserializer,
     pub amount_deserializer: AmountDeserializer,
 }
@@ -328,8 +720,7 @@ impl Deserializer<PreHashMap<Address, Amount>> for CreditsDeserializer {
 #[cfg(test)]
 mod test {
     use super::*;
-    use massa_models::config::{MAX_DEFERRED_CREDITS_LENGTH, THREAD_COUNT};
-    use massa_serialization::DeserializeError;
+    use massa_models::test_exports::{assert_golden_vector, read_golden_vector};
     use std::str::FromStr;
 
     #[test]
@@ -360,4 +751,397 @@ mod test {
 
         assert!(res.is_err());
     }
+
+    #[test]
+    fn test_deferred_credits_display() {
+        let addr1 =
+            Address::from_str("AU1jUbxeXW49QRT6Le5aPuNdcGWQV2kpnDyQkKoka4MmEUW3m8Xm").unwrap();
+        let addr2 =
+            Address::from_str("AU12nfJdBNotWffSEDDCS9mMXAxDbHbAVM9GW7pvVJoLxdCeeroX8").unwrap();
+
+        let mut def_credits = DeferredCredits::default();
+        assert_eq!(def_credits.to_string(), "no deferred credits");
+
+        def_credits.insert(Slot::new(3, 0), addr1, Amount::from_str("1").unwrap());
+        def_credits.insert(Slot::new(3, 0), addr2, Amount::from_str("2").unwrap());
+        def_credits.insert(Slot::new(5, 1), addr1, Amount::from_str("3").unwrap());
+        assert_eq!(
+            def_credits.to_string(),
+            format!(
+                "3 deferred credits at 2 slots from (period: 3, thread: 0) to (period: 5, thread: 1), \
+                 total 6 coins, top addresses: {} (4) {} (2)",
+                addr1, addr2
+            )
+        );
+        assert_eq!(format!("{:?}", def_credits), def_credits.to_string());
+        assert!(def_credits.dump().contains(&addr2.to_string()));
+    }
+
+    #[test]
+    fn test_deferred_credits_postponed_changes() {
+        let addr1 =
+            Address::from_str("AU1jUbxeXW49QRT6Le5aPuNdcGWQV2kpnDyQkKoka4MmEUW3m8Xm").unwrap();
+        let addr2 =
+            Address::from_str("AU12nfJdBNotWffSEDDCS9mMXAxDbHbAVM9GW7pvVJoLxdCeeroX8").unwrap();
+
+        let mut def_credits = DeferredCredits::default();
+        def_credits.insert(Slot::new(3, 0), addr1, Amount::from_str("1").unwrap());
+        def_credits.insert(Slot::new(3, 1), addr2, Amount::from_str("2").unwrap());
+        def_credits.insert(Slot::new(5, 0), addr1, Amount::from_str("3").unwrap());
+
+        let changes = def_credits.postponed_changes(2).unwrap();
+        // the credits of (3, 0) land on (5, 0) while those of (5, 0) are postponed as well
+        assert_eq!(
+            changes.get_address_credits_for_slot(&addr1, &Slot::new(3, 0)),
+            Some(Amount::zero())
+        );
+        assert_eq!(
+            changes.get_address_credits_for_slot(&addr1, &Slot::new(5, 0)),
+            Some(Amount::from_str("1").unwrap())
+        );
+        assert_eq!(
+            changes.get_address_credits_for_slot(&addr1, &Slot::new(7, 0)),
+            Some(Amount::from_str("3").unwrap())
+        );
+
+        let mut postponed = def_credits.clone();
+        postponed.extend(changes);
+        postponed.remove_zeros();
+        let mut expected = DeferredCredits::default();
+        expected.insert(Slot::new(5, 0), addr1, Amount::from_str("1").unwrap());
+        expected.insert(Slot::new(5, 1), addr2, Amount::from_str("2").unwrap());
+        expected.insert(Slot::new(7, 0), addr1, Amount::from_str("3").unwrap());
+        assert_eq!(postponed.credits, expected.credits);
+
+        // the postponed periods cannot overflow
+        assert!(def_credits.postponed_changes(u64::MAX).is_none());
+    }
+
+    #[test]
+    fn test_deferred_credits_at_slot() {
+        let addr1 =
+            Address::from_str("AU1jUbxeXW49QRT6Le5aPuNdcGWQV2kpnDyQkKoka4MmEUW3m8Xm").unwrap();
+        let addr2 =
+            Address::from_str("AU12nfJdBNotWffSEDDCS9mMXAxDbHbAVM9GW7pvVJoLxdCeeroX8").unwrap();
+
+        let mut def_credits = DeferredCredits::default();
+        def_credits.insert(Slot::new(3, 0), addr1, Amount::from_str("1").unwrap());
+        def_credits.insert(Slot::new(3, 0), addr2, Amount::from_str("5").unwrap());
+        def_credits.insert(Slot::new(4, 0), addr1, Amount::from_str("2").unwrap());
+
+        assert!(def_credits.contains(&Slot::new(3, 0)));
+        assert!(!def_credits.contains(&Slot::new(3, 1)));
+
+        let mut total = Amount::zero();
+        let mut addresses = Vec::new();
+        def_credits.for_each_credit_at(&Slot::new(3, 0), |address, amount| {
+            addresses.push(*address);
+            total = total.saturating_add(*amount);
+        });
+        addresses.sort_unstable();
+        let mut expected = vec![addr1, addr2];
+        expected.sort_unstable();
+        assert_eq!(addresses, expected);
+        assert_eq!(total, Amount::from_str("6").unwrap());
+
+        // nothing is walked at a slot without credits
+        def_credits.for_each_credit_at(&Slot::new(5, 0), |_, _| panic!("unexpected credit"));
+    }
+
+    #[test]
+    fn test_deferred_credits_reschedule() {
+        let addr1 =
+            Address::from_str("AU1jUbxeXW49QRT6Le5aPuNdcGWQV2kpnDyQkKoka4MmEUW3m8Xm").unwrap();
+        let addr2 =
+            Address::from_str("AU12nfJdBNotWffSEDDCS9mMXAxDbHbAVM9GW7pvVJoLxdCeeroX8").unwrap();
+
+        // pending credits, as stored in the final state
+        let mut def_credits = DeferredCredits::default();
+        def_credits.insert(Slot::new(3, 0), addr1, Amount::from_str("1").unwrap());
+        def_credits.insert(Slot::new(4, 0), addr1, Amount::from_str("2").unwrap());
+        def_credits.insert(Slot::new(3, 0), addr2, Amount::from_str("5").unwrap());
+
+        // the amount is summed with the pending credit at the target slot, which is kept once applied
+        let mut changes = DeferredCredits::default();
+        assert_eq!(
+            def_credits.reschedule(&mut changes, &addr1, Slot::new(3, 0), Slot::new(4, 0)),
+            Some(Amount::from_str("3").unwrap())
+        );
+        assert_eq!(
+            changes.get_address_credits_for_slot(&addr1, &Slot::new(3, 0)),
+            Some(Amount::zero())
+        );
+        assert_eq!(
+            changes.get_address_credits_for_slot(&addr2, &Slot::new(3, 0)),
+            None
+        );
+
+        // the credit lands on a new slot
+        assert_eq!(
+            def_credits.reschedule(&mut changes, &addr2, Slot::new(3, 0), Slot::new(6, 1)),
+            Some(Amount::from_str("5").unwrap())
+        );
+
+        // a credit already moved by the changes is moved again from its new slot
+        assert_eq!(
+            def_credits.reschedule(&mut changes, &addr1, Slot::new(4, 0), Slot::new(7, 0)),
+            Some(Amount::from_str("3").unwrap())
+        );
+
+        let mut applied = def_credits.clone();
+        applied.extend(changes.clone());
+        applied.remove_zeros();
+        let mut expected = DeferredCredits::default();
+        expected.insert(Slot::new(7, 0), addr1, Amount::from_str("3").unwrap());
+        expected.insert(Slot::new(6, 1), addr2, Amount::from_str("5").unwrap());
+        assert_eq!(applied.credits, expected.credits);
+
+        // missing credits and overflowing sums leave the changes unchanged
+        assert_eq!(
+            def_credits.reschedule(&mut changes, &addr1, Slot::new(3, 0), Slot::new(4, 0)),
+            None
+        );
+        changes.insert(Slot::new(8, 0), addr1, Amount::MAX);
+        let before = changes.clone();
+        assert_eq!(
+            def_credits.reschedule(&mut changes, &addr1, Slot::new(7, 0), Slot::new(8, 0)),
+            None
+        );
+        assert_eq!(changes.credits, before.credits);
+    }
+
+    #[test]
+    fn test_deferred_credits_golden_vector() {
+        let addr1 =
+            Address::from_str("AU1jUbxeXW49QRT6Le5aPuNdcGWQV2kpnDyQkKoka4MmEUW3m8Xm").unwrap();
+        let addr2 =
+            Address::from_str("AU12nfJdBNotWffSEDDCS9mMXAxDbHbAVM9GW7pvVJoLxdCeeroX8").unwrap();
+
+        // one credit per slot: the order of the credits of a slot is not deterministic
+        let mut def_credits = DeferredCredits::default();
+        def_credits.insert(Slot::new(3, 0), addr1, Amount::from_str("1.5").unwrap());
+        def_credits.insert(Slot::new(10, 7), addr2, Amount::from_raw(1));
+
+        let mut buf = Vec::new();
+        DeferredCreditsSerializer::new()
+            .serialize(&def_credits, &mut buf)
+            .unwrap();
+        assert_golden_vector(env!("CARGO_MANIFEST_DIR"), "deferred_credits", &buf);
+
+        let golden = read_golden_vector(env!("CARGO_MANIFEST_DIR"), "deferred_credits");
+        let (rest, def_credits_der) =
+            DeferredCreditsDeserializer::new(THREAD_COUNT, MAX_DEFERRED_CREDITS_LENGTH)
+                .deserialize::<DeserializeError>(&golden)
+                .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(def_credits_der.credits, def_credits.credits);
+    }
+
+    #[test]
+    fn test_deferred_credits_deserializer_try_new() {
+        let config = PoSConfig {
+            periods_per_cycle: 128,
+            thread_count: THREAD_COUNT,
+            cycle_history_length: 6,
+            max_rolls_length: 1000,
+            max_production_stats_length: 1000,
+            max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
+            initial_deferred_credits_path: None,
+        };
+        assert!(DeferredCreditsDeserializer::try_new(&config).is_ok());
+        assert!(matches!(
+            DeferredCreditsDeserializer::try_new(&PoSConfig {
+                thread_count: 0,
+                ..config.clone()
+            }),
+            Err(PosError::InvalidThreadCount { .. })
+        ));
+        assert!(matches!(
+            DeferredCreditsDeserializer::try_new(&PoSConfig {
+                max_credit_length: MAX_DEFERRED_CREDITS_LENGTH + 1,
+                ..config.clone()
+            }),
+            Err(PosError::InvalidMaxCreditLength { .. })
+        ));
+
+        // credit slots too far after the last final slot are rejected
+        let addr =
+            Address::from_str("AU1jUbxeXW49QRT6Le5aPuNdcGWQV2kpnDyQkKoka4MmEUW3m8Xm").unwrap();
+        let last_final_slot = Slot::new(10, 0);
+        let deserializer = DeferredCreditsDeserializer::try_new(&config)
+            .unwrap()
+            .with_slot_watermark(last_final_slot);
+        let serializer = DeferredCreditsSerializer::new();
+        let mut def_credits = DeferredCredits::default();
+        def_credits.insert(
+            Slot::new(max_credit_period(last_final_slot), 0),
+            addr,
+            Amount::from_raw(1),
+        );
+        let mut buf = Vec::new();
+        serializer.serialize(&def_credits, &mut buf).unwrap();
+        assert!(deserializer.deserialize::<DeserializeError>(&buf).is_ok());
+
+        def_credits.insert(
+            Slot::new(max_credit_period(last_final_slot) + 1, 0),
+            addr,
+            Amount::from_raw(1),
+        );
+        buf.clear();
+        serializer.serialize(&def_credits, &mut buf).unwrap();
+        assert!(deserializer.deserialize::<DeserializeError>(&buf).is_err());
+    }
+
+    #[test]
+    fn test_roll_refund_slot() {
+        // sold during cycle 1, reimbursed at the end of cycle 4
+        assert_eq!(
+            roll_refund_slot(Slot::new(12, 1), 10, 2).unwrap(),
+            Slot::new(49, 1)
+        );
+        // the last slot of a cycle still belongs to that cycle
+        assert_eq!(
+            roll_refund_slot(Slot::new(9, 1), 10, 2).unwrap(),
+            Slot::new(39, 1)
+        );
+        assert_eq!(
+            estimate_roll_refund_slot(Slot::new(0, 0)).unwrap(),
+            Slot::new(4 * PERIODS_PER_CYCLE - 1, THREAD_COUNT - 1)
+        );
+        assert!(roll_refund_slot(Slot::new(u64::MAX, 0), 1, 2).is_err());
+    }
+
+    #[test]
+    fn test_split_credit() {
+        let threshold = Amount::from_str("100").unwrap();
+
+        // credits up to the threshold are not split
+        assert_eq!(
+            split_credit(threshold, threshold, 3),
+            vec![Amount::from_str("100").unwrap()]
+        );
+
+        // the remainder goes to the first part
+        let amount = Amount::from_raw(1_000_000_000_001);
+        let split = split_credit(amount, threshold, 3);
+        assert_eq!(
+            split,
+            vec![
+                Amount::from_raw(333_333_333_335),
+                Amount::from_raw(333_333_333_333),
+                Amount::from_raw(333_333_333_333)
+            ]
+        );
+        assert_eq!(
+            split
+                .iter()
+                .fold(Amount::zero(), |total, part| total.saturating_add(*part)),
+            amount
+        );
+
+        // splitting is disabled with less than 2 parts
+        assert_eq!(split_credit(amount, threshold, 1), vec![amount]);
+        assert_eq!(split_credit(amount, threshold, 0), vec![amount]);
+    }
+
+    #[test]
+    fn test_deferred_credits_extend_from_iter() {
+        let addr1 =
+            Address::from_str("AU1jUbxeXW49QRT6Le5aPuNdcGWQV2kpnDyQkKoka4MmEUW3m8Xm").unwrap();
+        let addr2 =
+            Address::from_str("AU12nfJdBNotWffSEDDCS9mMXAxDbHbAVM9GW7pvVJoLxdCeeroX8").unwrap();
+        let batch = vec![
+            (Slot::new(3, 1), addr1, Amount::from_raw(1)),
+            (Slot::new(1, 0), addr2, Amount::from_raw(2)),
+            (Slot::new(3, 1), addr2, Amount::from_raw(3)),
+            (Slot::new(3, 1), addr1, Amount::from_raw(4)),
+        ];
+
+        let mut expected = DeferredCredits::new();
+        expected.insert(Slot::new(1, 0), addr1, Amount::from_raw(5));
+        let mut def_credits = expected.clone();
+        for (slot, address, amount) in batch.clone() {
+            expected.insert(slot, address, amount);
+        }
+        def_credits.extend_from_iter(batch.into_iter());
+
+        assert_eq!(def_credits.credits, expected.credits);
+        // the last amount given for an address at a slot wins
+        assert_eq!(
+            def_credits.get_address_credits_for_slot(&addr1, &Slot::new(3, 1)),
+            Some(Amount::from_raw(4))
+        );
+    }
+
+    /// Reader returning one byte at a time
+    struct OneByteReader<'a>(&'a [u8]);
+
+    impl Read for OneByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match (self.0.split_first(), buf.first_mut()) {
+                (Some((byte, rest)), Some(out)) => {
+                    *out = *byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_deferred_credits_hash_streaming() {
+        let addr1 =
+            Address::from_str("AU1jUbxeXW49QRT6Le5aPuNdcGWQV2kpnDyQkKoka4MmEUW3m8Xm").unwrap();
+        let addr2 =
+            Address::from_str("AU12nfJdBNotWffSEDDCS9mMXAxDbHbAVM9GW7pvVJoLxdCeeroX8").unwrap();
+        let serializer = DeferredCreditsSerializer::new();
+        let deserializer =
+            DeferredCreditsDeserializer::new(THREAD_COUNT, MAX_DEFERRED_CREDITS_LENGTH);
+
+        let mut def_credits = DeferredCredits::default();
+        def_credits.insert(Slot::new(1, 0), addr1, Amount::from_str("1.5").unwrap());
+        def_credits.insert(Slot::new(1, 0), addr2, Amount::from_str("2").unwrap());
+        def_credits.insert(Slot::new(3, 1), addr2, Amount::from_raw(u64::MAX));
+        let mut buf = Vec::new();
+        serializer.serialize(&def_credits, &mut buf).unwrap();
+        let hash = deserializer.compute_hash_streaming(&buf[..]).unwrap();
+
+        // small reads give the same result
+        assert_eq!(
+            deserializer
+                .compute_hash_streaming(OneByteReader(&buf))
+                .unwrap(),
+            hash
+        );
+
+        // zero credits are not stored and do not change the hash
+        def_credits.insert(Slot::new(2, 0), addr1, Amount::zero());
+        let mut buf_with_zero = Vec::new();
+        serializer
+            .serialize(&def_credits, &mut buf_with_zero)
+            .unwrap();
+        assert_eq!(
+            deserializer
+                .compute_hash_streaming(&buf_with_zero[..])
+                .unwrap(),
+            hash
+        );
+
+        // other credits give a different hash
+        def_credits.insert(Slot::new(2, 0), addr1, Amount::from_str("1").unwrap());
+        let mut other_buf = Vec::new();
+        serializer.serialize(&def_credits, &mut other_buf).unwrap();
+        assert_ne!(
+            deserializer.compute_hash_streaming(&other_buf[..]).unwrap(),
+            hash
+        );
+
+        // truncated and trailing data are rejected
+        assert!(deserializer
+            .compute_hash_streaming(&buf[..buf.len() - 1])
+            .is_err());
+        buf.push(0);
+        assert!(deserializer.compute_hash_streaming(&buf[..]).is_err());
+    }
 }
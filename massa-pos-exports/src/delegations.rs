@@ -0,0 +1,315 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Delegation of the voting power of rolls.
+//!
+//! With a `Delegate` operation, an address (the delegator) lets another address (the operator) be drawn
+//! with its rolls: the operator produces the blocks and endorsements, and the rolls stay owned by the
+//! delegator, who can still sell them. An `Undelegate` operation ends the delegation.
+//!
+//! The registry is kept in the cycle history next to the roll counts, so that the draws of a cycle use
+//! the delegations and the rolls of the same lookback cycle, on every node, bootstrapped or not.
+//! The block rewards of an operator are split with its delegators in proportion to the rolls it was drawn
+//! with, the shares of the delegators being paid as deferred credits.
+//! Delegations are not chained: the operator of an operator is not followed.
+//!
+//! Delegation is a network upgrade: the `Delegate` and `Undelegate` operations are only executed,
+//! and the draws only weighted by the delegations, once its versioning component is active
+//! (see `DelegationActivation`).
+
+use std::collections::BTreeMap;
+use std::ops::Bound::Included;
+
+use massa_models::{
+    address::{Address, AddressDeserializer, AddressSerializer},
+    amount::Amount,
+    prehash::PreHashMap,
+};
+use massa_serialization::{
+    Deserializer, OptionDeserializer, OptionSerializer, SerializeError, Serializer,
+    U64VarIntDeserializer, U64VarIntSerializer,
+};
+use nom::{
+    error::{context, ContextError, ParseError},
+    multi::length_count,
+    sequence::tuple,
+    IResult, Parser,
+};
+use serde::{Deserialize, Serialize};
+
+/// Activation of the delegated draws, decided by the versioning of the network
+pub trait DelegationActivation: Send + Sync {
+    /// Whether the delegations of the lookback cycle `lookback_cycle` weight the draws it is used for
+    fn is_active_at_cycle(&self, lookback_cycle: u64) -> bool;
+}
+
+/// Changes of the delegations: the new operator of each changed delegator, `None` to undelegate
+pub type DelegationChanges = PreHashMap<Address, Option<Address>>;
+
+/// Rolls an operator was drawn with during a cycle
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperatorRolls {
+    /// rolls of the operator itself, zero if it delegates them
+    pub own_rolls: u64,
+    /// rolls delegated by each delegator
+    pub delegated_rolls: BTreeMap<Address, u64>,
+}
+
+impl OperatorRolls {
+    /// Total number of rolls the operator was drawn with
+    pub fn total_rolls(&self) -> u64 {
+        self.delegated_rolls
+            .values()
+            .fold(self.own_rolls, |total, rolls| total.saturating_add(*rolls))
+    }
+
+    /// Split a reward of the operator in proportion to the rolls it was drawn with.
+    /// The parts of the delegators are rounded down, the remainder goes to the operator.
+    ///
+    /// # Returns
+    /// The non-zero part of each delegator
+    pub fn delegator_shares(&self, reward: Amount) -> Vec<(Address, Amount)> {
+        let total_rolls = self.total_rolls();
+        if total_rolls == 0 {
+            return Vec::new();
+        }
+        self.delegated_rolls
+            .iter()
+            .filter_map(|(delegator, rolls)| {
+                let share = (reward.to_raw() as u128) * (*rolls as u128) / (total_rolls as u128);
+                // the share is at most the reward
+                let share = Amount::from_raw(share as u64);
+                (!share.is_zero()).then_some((*delegator, share))
+            })
+            .collect()
+    }
+}
+
+/// Delegations the draws of a cycle were weighted with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CycleDelegations {
+    /// operator of each delegator that had rolls at the lookback cycle
+    pub delegators: BTreeMap<Address, Address>,
+    /// rolls each operator was drawn with, for the operators with at least one such delegator
+    pub operators: BTreeMap<Address, OperatorRolls>,
+}
+
+impl CycleDelegations {
+    /// Group the rolls delegated to each operator
+    ///
+    /// # Arguments
+    /// * `lookback_rolls`: roll counts at the lookback cycle
+    /// * `lookback_delegations`: operator of each delegator at the lookback cycle
+    pub fn new(
+        lookback_rolls: &BTreeMap<Address, u64>,
+        lookback_delegations: &BTreeMap<Address, Address>,
+    ) -> Self {
+        let mut cycle_delegations = CycleDelegations::default();
+        for (delegator, operator) in lookback_delegations {
+            let rolls = lookback_rolls.get(delegator).copied().unwrap_or_default();
+            if rolls == 0 || delegator == operator {
+                continue;
+            }
+            cycle_delegations.delegators.insert(*delegator, *operator);
+            cycle_delegations
+                .operators
+                .entry(*operator)
+                .or_default()
+                .delegated_rolls
+                .insert(*delegator, rolls);
+        }
+        for (operator, operator_rolls) in cycle_delegations.operators.iter_mut() {
+            if !cycle_delegations.delegators.contains_key(operator) {
+                operator_rolls.own_rolls =
+                    lookback_rolls.get(operator).copied().unwrap_or_default();
+            }
+        }
+        cycle_delegations
+    }
+
+    /// Roll counts the draws are weighted with: the rolls of the delegators are counted for their operator
+    pub fn draw_rolls(&self, lookback_rolls: BTreeMap<Address, u64>) -> BTreeMap<Address, u64> {
+        if self.delegators.is_empty() {
+            return lookback_rolls;
+        }
+        let mut draw_rolls = BTreeMap::new();
+        for (address, rolls) in lookback_rolls {
+            let drawn_address = self.delegators.get(&address).copied().unwrap_or(address);
+            let entry = draw_rolls.entry(drawn_address).or_insert(0u64);
+            *entry = entry.saturating_add(rolls);
+        }
+        draw_rolls
+    }
+}
+
+/// Serializer for `DelegationChanges`
+pub struct DelegationChangesSerializer {
+    u64_serializer: U64VarIntSerializer,
+    address_serializer: AddressSerializer,
+    opt_address_serializer: OptionSerializer<Address, AddressSerializer>,
+}
+
+impl Default for DelegationChangesSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DelegationChangesSerializer {
+    /// Creates a new `DelegationChanges` serializer
+    pub fn new() -> Self {
+        Self {
+            u64_serializer: U64VarIntSerializer::new(),
+            address_serializer: AddressSerializer::new(),
+            opt_address_serializer: OptionSerializer::new(AddressSerializer::new()),
+        }
+    }
+}
+
+impl Serializer<DelegationChanges> for DelegationChangesSerializer {
+    fn serialize(
+        &self,
+        value: &DelegationChanges,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        // sort the entries so that the serialization is deterministic
+        let mut entries: Vec<(&Address, &Option<Address>)> = value.iter().collect();
+        entries.sort_unstable_by_key(|(delegator, _)| **delegator);
+
+        self.u64_serializer
+            .serialize(&(entries.len() as u64), buffer)?;
+        for (delegator, operator) in entries {
+            self.address_serializer.serialize(delegator, buffer)?;
+            self.opt_address_serializer.serialize(operator, buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for `DelegationChanges`
+pub struct DelegationChangesDeserializer {
+    length_deserializer: U64VarIntDeserializer,
+    address_deserializer: AddressDeserializer,
+    opt_address_deserializer: OptionDeserializer<Address, AddressDeserializer>,
+}
+
+impl DelegationChangesDeserializer {
+    /// Creates a new `DelegationChanges` deserializer
+    pub fn new(max_changes_length: u64) -> Self {
+        Self {
+            length_deserializer: U64VarIntDeserializer::new(
+                Included(u64::MIN),
+                Included(max_changes_length),
+            ),
+            address_deserializer: AddressDeserializer::new(),
+            opt_address_deserializer: OptionDeserializer::new(AddressDeserializer::new()),
+        }
+    }
+}
+
+impl Deserializer<DelegationChanges> for DelegationChangesDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], DelegationChanges, E> {
+        context(
+            "Failed DelegationChanges deserialization",
+            length_count(
+                context("Failed length deserialization", |input| {
+                    self.length_deserializer.deserialize(input)
+                }),
+                tuple((
+                    context("Failed delegator deserialization", |input| {
+                        self.address_deserializer.deserialize(input)
+                    }),
+                    context("Failed operator deserialization", |input| {
+                        self.opt_address_deserializer.deserialize(input)
+                    }),
+                )),
+            ),
+        )
+        .map(|entries| entries.into_iter().collect())
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_serialization::DeserializeError;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    fn address() -> Address {
+        Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_delegation_changes_ser_der() {
+        let delegator = address();
+        let operator = address();
+        let mut changes = DelegationChanges::default();
+        changes.insert(delegator, Some(operator));
+        changes.insert(operator, None);
+
+        let mut buf = Vec::new();
+        DelegationChangesSerializer::new()
+            .serialize(&changes, &mut buf)
+            .unwrap();
+        let (rest, changes_der) = DelegationChangesDeserializer::new(2)
+            .deserialize::<DeserializeError>(&buf)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(changes_der, changes);
+
+        // more changes than allowed
+        assert!(DelegationChangesDeserializer::new(1)
+            .deserialize::<DeserializeError>(&buf)
+            .is_err());
+    }
+
+    #[test]
+    fn test_cycle_delegations() {
+        let operator = address();
+        let delegator_a = address();
+        let delegator_b = address();
+        let rollless_delegator = address();
+        let other = address();
+        let lookback_rolls: BTreeMap<Address, u64> = [
+            (operator, 2),
+            (delegator_a, 5),
+            (delegator_b, 3),
+            (other, 4),
+        ]
+        .into_iter()
+        .collect();
+        let lookback_delegations: BTreeMap<Address, Address> = [
+            (delegator_a, operator),
+            (delegator_b, operator),
+            (rollless_delegator, operator),
+        ]
+        .into_iter()
+        .collect();
+
+        let cycle_delegations = CycleDelegations::new(&lookback_rolls, &lookback_delegations);
+        assert_eq!(cycle_delegations.delegators.len(), 2);
+        let operator_rolls = &cycle_delegations.operators[&operator];
+        assert_eq!(operator_rolls.own_rolls, 2);
+        assert_eq!(operator_rolls.total_rolls(), 10);
+
+        // the operator is drawn with the rolls of its delegators
+        let draw_rolls = cycle_delegations.draw_rolls(lookback_rolls);
+        assert_eq!(
+            draw_rolls,
+            [(operator, 10), (other, 4)].into_iter().collect()
+        );
+
+        // the reward is split in proportion to the rolls
+        let shares = operator_rolls.delegator_shares(Amount::from_str("1").unwrap());
+        let shares: BTreeMap<Address, Amount> = shares.into_iter().collect();
+        assert_eq!(shares[&delegator_a], Amount::from_str("0.5").unwrap());
+        assert_eq!(shares[&delegator_b], Amount::from_str("0.3").unwrap());
+        assert!(OperatorRolls::default()
+            .delegator_shares(Amount::from_str("1").unwrap())
+            .is_empty());
+    }
+}
@@ -22,6 +22,24 @@ pub enum PosError {
     RollsFileLoadingError(String),
     /// Error while loading initial deferred credits file: {0}
     DeferredCreditsFileLoadingError(String),
+    /// Invalid genesis proof-of-stake state: {0}
+    InvalidGenesis(String),
+    /// Invalid deferred credits stream: {0}
+    DeferredCreditsStreamError(String),
     /// Communication channel was down: {0}
     ChannelDown(String),
+    /// Invalid thread count {thread_count}: must be between 1 and {max}
+    InvalidThreadCount {
+        /// configured thread count
+        thread_count: u8,
+        /// thread count of the network
+        max: u8,
+    },
+    /// Invalid maximum deferred credits length {max_credit_length}: must be between 1 and {max}
+    InvalidMaxCreditLength {
+        /// configured maximum length
+        max_credit_length: u64,
+        /// maximum length of the network
+        max: u64,
+    },
 }
@@ -0,0 +1,375 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Initialization of the proof-of-stake state of a new network from its initial rolls and ledger.
+//!
+//! This is the logic used by the node when no bootstrap happened.
+//! Network genesis tooling should use it instead of re-implementing it.
+
+use std::{collections::BTreeMap, fmt::Display, path::Path};
+
+use bitvec::vec::BitVec;
+use massa_hash::Hash;
+use massa_models::{address::Address, amount::Amount, prehash::PreHashMap, slot::Slot};
+use serde::Deserialize;
+
+use crate::{CycleInfo, DeferredCredits, PoSConfig, PosError, PosResult};
+
+/// Proof-of-stake state of a network at genesis
+#[derive(Debug, Clone)]
+pub struct GenesisPoSState {
+    /// initial rolls, used for negative cycle look back
+    pub initial_rolls: BTreeMap<Address, u64>,
+    /// initial seeds, used for negative cycle look back (cycles -2, -1 in that order)
+    pub initial_seeds: Vec<Hash>,
+    /// information of the first cycle
+    pub initial_cycle: CycleInfo,
+    /// deferred credits at genesis, always empty
+    pub deferred_credits: DeferredCredits,
+    /// total number of initial rolls
+    pub total_rolls: u64,
+    /// total value of the initial rolls
+    pub total_rolls_value: Amount,
+    /// total balance of the initial ledger
+    pub total_balance: Amount,
+}
+
+/// Seeds used as the initial seeds for negative cycles (-2 and -1 respectively)
+pub fn compute_initial_seeds(initial_seed_string: &str) -> Vec<Hash> {
+    let init_seed = Hash::compute_from(initial_seed_string.as_bytes());
+    vec![Hash::compute_from(init_seed.to_bytes()), init_seed]
+}
+
+/// Information of cycle 0, built from the initial rolls
+pub fn compute_initial_cycle(
+    config: &PoSConfig,
+    initial_rolls: BTreeMap<Address, u64>,
+) -> CycleInfo {
+    let mut rng_seed = BitVec::with_capacity(
+        config
+            .periods_per_cycle
+            .saturating_mul(config.thread_count as u64)
+            .try_into()
+            .unwrap(),
+    );
+    rng_seed.extend(vec![false; config.thread_count as usize]);
+    CycleInfo::new(0, false, initial_rolls, rng_seed, PreHashMap::default())
+}
+
+/// Build the proof-of-stake state of a new network and check it against the expected total supply.
+///
+/// # Arguments
+/// * `config`: proof-of-stake configuration
+/// * `initial_seed_string`: string the initial seeds are derived from
+/// * `initial_rolls`: rolls owned by each address at genesis
+/// * `initial_balances`: balance of each address of the initial ledger
+/// * `roll_price`: price of a roll
+/// * `total_supply`: expected total supply: balances plus value of the rolls
+///
+/// # Returns
+/// The genesis state, or an error if an address has zero rolls, if there are no rolls at all,
+/// or if the totals do not match the expected supply
+pub fn build_genesis_pos_state(
+    config: &PoSConfig,
+    initial_seed_string: &str,
+    initial_rolls: BTreeMap<Address, u64>,
+    initial_balances: &BTreeMap<Address, Amount>,
+    roll_price: Amount,
+    total_supply: Amount,
+) -> PosResult<GenesisPoSState> {
+    if let Some((address, _)) = initial_rolls.iter().find(|(_, count)| **count == 0) {
+        return Err(PosError::InvalidGenesis(format!(
+            "address {} has zero initial rolls",
+            address
+        )));
+    }
+    let total_rolls = total_initial_rolls(&initial_rolls)?;
+    if total_rolls == 0 {
+        return Err(PosError::InvalidGenesis(
+            "there are no initial rolls".into(),
+        ));
+    }
+    let total_rolls_value = roll_price
+        .checked_mul_u64(total_rolls)
+        .ok_or_else(|| PosError::OverflowError("total initial rolls value overflow".into()))?;
+    let total_balance = total_initial_balance(initial_balances.values().copied())?;
+    let total = total_balance
+        .checked_add(total_rolls_value)
+        .ok_or_else(|| PosError::OverflowError("total initial supply overflow".into()))?;
+    if total != total_supply {
+        return Err(PosError::InvalidGenesis(format!(
+            "initial balances ({}) plus initial rolls value ({}) is {} but the expected supply is {}",
+            total_balance, total_rolls_value, total, total_supply
+        )));
+    }
+
+    Ok(GenesisPoSState {
+        initial_cycle: compute_initial_cycle(config, initial_rolls.clone()),
+        initial_rolls,
+        initial_seeds: compute_initial_seeds(initial_seed_string),
+        deferred_credits: DeferredCredits::new(),
+        total_rolls,
+        total_rolls_value,
+        total_balance,
+    })
+}
+
+/// Breakdown of the initial supply of a network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisSupply {
+    /// total balance of the initial ledger
+    pub total_balance: Amount,
+    /// total number of initial rolls
+    pub total_rolls: u64,
+    /// total value of the initial rolls
+    pub total_rolls_value: Amount,
+    /// total amount of the initial deferred credits
+    pub total_deferred_credits: Amount,
+    /// sum of the above amounts
+    pub total: Amount,
+}
+
+impl Display for GenesisSupply {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "initial balances: {}", self.total_balance)?;
+        writeln!(
+            f,
+            "initial rolls value: {} ({} rolls)",
+            self.total_rolls_value, self.total_rolls
+        )?;
+        writeln!(
+            f,
+            "initial deferred credits: {}",
+            self.total_deferred_credits
+        )?;
+        write!(f, "total: {}", self.total)
+    }
+}
+
+/// Sum the initial balances, the value of the initial rolls and the initial deferred credits of a network,
+/// and check that they do not exceed its maximum supply. Meant to be run at startup,
+/// so that a node never starts a network whose genesis already inflates the supply.
+///
+/// # Arguments
+/// * `initial_balances`: balance of each address of the initial ledger
+/// * `initial_rolls`: rolls owned by each address at genesis
+/// * `initial_deferred_credits`: deferred credits scheduled at genesis
+/// * `roll_price`: price of a roll
+/// * `max_supply`: maximum supply of the network
+///
+/// # Returns
+/// The breakdown of the initial supply, or an error detailing it if it exceeds the maximum supply
+pub fn check_genesis_supply(
+    initial_balances: impl Iterator<Item = Amount>,
+    initial_rolls: &BTreeMap<Address, u64>,
+    initial_deferred_credits: &DeferredCredits,
+    roll_price: Amount,
+    max_supply: Amount,
+) -> PosResult<GenesisSupply> {
+    let total_balance = total_initial_balance(initial_balances)?;
+    let total_rolls = total_initial_rolls(initial_rolls)?;
+    let total_rolls_value = roll_price
+        .checked_mul_u64(total_rolls)
+        .ok_or_else(|| PosError::OverflowError("total initial rolls value overflow".into()))?;
+    let total_deferred_credits = initial_deferred_credits
+        .credits
+        .values()
+        .flat_map(|credits| credits.values())
+        .try_fold(Amount::zero(), |total, amount| total.checked_add(*amount))
+        .ok_or_else(|| PosError::OverflowError("total initial deferred credits overflow".into()))?;
+    let total = total_balance
+        .checked_add(total_rolls_value)
+        .and_then(|total| total.checked_add(total_deferred_credits))
+        .ok_or_else(|| PosError::OverflowError("total initial supply overflow".into()))?;
+    let supply = GenesisSupply {
+        total_balance,
+        total_rolls,
+        total_rolls_value,
+        total_deferred_credits,
+        total,
+    };
+    if total > max_supply {
+        return Err(PosError::InvalidGenesis(format!(
+            "the initial supply exceeds the maximum supply of {}:\n{}",
+            max_supply, supply
+        )));
+    }
+    Ok(supply)
+}
+
+/// Read the initial rolls of a network from a JSON file mapping each address to its roll count
+pub fn read_initial_rolls(path: &Path) -> PosResult<BTreeMap<Address, u64>> {
+    serde_json::from_str::<BTreeMap<Address, u64>>(&std::fs::read_to_string(path).map_err(
+        |err| PosError::RollsFileLoadingError(format!("error while deserializing: {}", err)),
+    )?)
+    .map_err(|err| PosError::RollsFileLoadingError(format!("error opening file: {}", err)))
+}
+
+/// Read the initial deferred credits of a network from a JSON file mapping each address
+/// to a list of `{ slot, amount }` credits
+pub fn read_initial_deferred_credits(path: &Path) -> PosResult<DeferredCredits> {
+    #[derive(Deserialize)]
+    struct AddressInitialDeferredCredits {
+        slot: Slot,
+        amount: Amount,
+    }
+
+    let initial_deferred_credits = serde_json::from_str::<
+        PreHashMap<Address, Vec<AddressInitialDeferredCredits>>,
+    >(&std::fs::read_to_string(path).map_err(|err| {
+        PosError::DeferredCreditsFileLoadingError(format!(
+            "error while deserializing initial deferred credits file {}: {}",
+            path.display(),
+            err
+        ))
+    })?)
+    .map_err(|err| {
+        PosError::DeferredCreditsFileLoadingError(format!(
+            "error loading initial deferred credits file {}: {}",
+            path.display(),
+            err
+        ))
+    })?;
+
+    let mut deferred_credits = DeferredCredits::new();
+    for (address, credits) in initial_deferred_credits {
+        for AddressInitialDeferredCredits { slot, amount } in credits {
+            deferred_credits.insert(slot, address, amount);
+        }
+    }
+    Ok(deferred_credits)
+}
+
+/// Total number of initial rolls
+fn total_initial_rolls(initial_rolls: &BTreeMap<Address, u64>) -> PosResult<u64> {
+    initial_rolls
+        .values()
+        .try_fold(0u64, |total, count| total.checked_add(*count))
+        .ok_or_else(|| PosError::OverflowError("total initial rolls overflow".into()))
+}
+
+/// Total balance of the initial ledger
+fn total_initial_balance(initial_balances: impl Iterator<Item = Amount>) -> PosResult<Amount> {
+    initial_balances
+        .try_fold(Amount::zero(), |total, balance| total.checked_add(balance))
+        .ok_or_else(|| PosError::OverflowError("total initial balance overflow".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    fn config() -> PoSConfig {
+        PoSConfig {
+            periods_per_cycle: 2,
+            thread_count: 2,
+            cycle_history_length: 2,
+            max_rolls_length: 1000,
+            max_production_stats_length: 1000,
+            max_credit_length: 1000,
+            initial_deferred_credits_path: None,
+        }
+    }
+
+    #[test]
+    fn test_build_genesis_pos_state() {
+        let addr_a = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let addr_b = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let initial_rolls = BTreeMap::from([(addr_a, 10), (addr_b, 5)]);
+        let initial_balances = BTreeMap::from([
+            (addr_a, Amount::from_str("100").unwrap()),
+            (addr_b, Amount::from_str("50").unwrap()),
+        ]);
+        let roll_price = Amount::from_str("100").unwrap();
+
+        let state = build_genesis_pos_state(
+            &config(),
+            "seed",
+            initial_rolls.clone(),
+            &initial_balances,
+            roll_price,
+            Amount::from_str("1650").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(state.total_rolls, 15);
+        assert_eq!(state.initial_cycle.cycle, 0);
+        assert_eq!(state.initial_cycle.roll_counts, initial_rolls);
+        assert_eq!(state.initial_seeds, compute_initial_seeds("seed"));
+        assert!(state.deferred_credits.credits.is_empty());
+
+        // the totals must match the expected supply
+        assert!(matches!(
+            build_genesis_pos_state(
+                &config(),
+                "seed",
+                initial_rolls,
+                &initial_balances,
+                roll_price,
+                Amount::from_str("1600").unwrap(),
+            ),
+            Err(PosError::InvalidGenesis(_))
+        ));
+
+        // addresses must own at least one roll
+        assert!(matches!(
+            build_genesis_pos_state(
+                &config(),
+                "seed",
+                BTreeMap::from([(addr_a, 0)]),
+                &initial_balances,
+                roll_price,
+                Amount::from_str("150").unwrap(),
+            ),
+            Err(PosError::InvalidGenesis(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_genesis_supply() {
+        let addr_a = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let addr_b = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let initial_rolls = BTreeMap::from([(addr_a, 10)]);
+        let initial_balances = [
+            Amount::from_str("100").unwrap(),
+            Amount::from_str("50").unwrap(),
+        ];
+        let mut initial_deferred_credits = DeferredCredits::new();
+        initial_deferred_credits.insert(Slot::new(3, 0), addr_a, Amount::from_str("30").unwrap());
+        initial_deferred_credits.insert(Slot::new(5, 1), addr_b, Amount::from_str("20").unwrap());
+        let roll_price = Amount::from_str("100").unwrap();
+
+        let supply = check_genesis_supply(
+            initial_balances.into_iter(),
+            &initial_rolls,
+            &initial_deferred_credits,
+            roll_price,
+            Amount::from_str("1200").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(supply.total_balance, Amount::from_str("150").unwrap());
+        assert_eq!(supply.total_rolls, 10);
+        assert_eq!(supply.total_rolls_value, Amount::from_str("1000").unwrap());
+        assert_eq!(
+            supply.total_deferred_credits,
+            Amount::from_str("50").unwrap()
+        );
+        assert_eq!(supply.total, Amount::from_str("1200").unwrap());
+
+        // the error details the breakdown of the supply
+        let Err(PosError::InvalidGenesis(msg)) = check_genesis_supply(
+            initial_balances.into_iter(),
+            &initial_rolls,
+            &initial_deferred_credits,
+            roll_price,
+            Amount::from_str("1199").unwrap(),
+        ) else {
+            panic!("the initial supply exceeds the maximum supply");
+        };
+        assert!(msg.contains(&format!(
+            "initial deferred credits: {}",
+            supply.total_deferred_credits
+        )));
+        assert!(msg.contains(&format!("total: {}", supply.total)));
+    }
+}
@@ -9,23 +9,39 @@
 
 mod config;
 mod controller_traits;
+mod credit_origin_totals;
+mod credit_slot;
+mod credits_recovery;
+mod credits_watch;
 mod cycle_info;
 mod deferred_credits;
+mod delegations;
 mod error;
+mod genesis;
+mod payout_addresses;
 mod pos_changes;
 mod pos_final_state;
 mod settings;
+mod state_hasher;
 
 pub use config::PoSConfig;
 #[cfg(any(test, feature = "test-exports"))]
 pub use controller_traits::{MockSelectorController, MockSelectorControllerWrapper};
 pub use controller_traits::{Selection, SelectorController, SelectorManager};
+pub use credit_origin_totals::*;
+pub use credit_slot::*;
+pub use credits_recovery::*;
+pub use credits_watch::*;
 pub use cycle_info::*;
 pub use deferred_credits::*;
+pub use delegations::*;
 pub use error::*;
+pub use genesis::*;
+pub use payout_addresses::*;
 pub use pos_changes::*;
 pub use pos_final_state::*;
 pub use settings::SelectorConfig;
+pub use state_hasher::*;
 
 #[cfg(feature = "test-exports")]
 pub mod test_exports;
@@ -0,0 +1,159 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Registry of the payout addresses.
+//!
+//! With a `SetPayoutAddress` operation, an address designates another address that receives its
+//! deferred credits and its staking rewards in its place. The registry is part of the final state,
+//! under `PAYOUT_ADDRESSES_PREFIX`, and execution resolves it when the coins are paid.
+//! Payout addresses are not chained: the payout address of a payout address is not followed.
+
+use std::ops::Bound::Included;
+
+use massa_models::{
+    address::{Address, AddressDeserializer, AddressSerializer},
+    prehash::PreHashMap,
+};
+use massa_serialization::{
+    Deserializer, OptionDeserializer, OptionSerializer, SerializeError, Serializer,
+    U64VarIntDeserializer, U64VarIntSerializer,
+};
+use nom::{
+    error::{context, ContextError, ParseError},
+    multi::length_count,
+    sequence::tuple,
+    IResult, Parser,
+};
+
+/// Changes of the payout addresses: the new payout address of each changed address, `None` to remove it
+pub type PayoutAddressChanges = PreHashMap<Address, Option<Address>>;
+
+/// Address to which the payouts of `address` are sent
+pub fn resolve_payout_address(address: Address, payout_address: Option<Address>) -> Address {
+    payout_address.unwrap_or(address)
+}
+
+/// Serializer for `PayoutAddressChanges`
+pub struct PayoutAddressChangesSerializer {
+    u64_serializer: U64VarIntSerializer,
+    address_serializer: AddressSerializer,
+    opt_address_serializer: OptionSerializer<Address, AddressSerializer>,
+}
+
+impl Default for PayoutAddressChangesSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PayoutAddressChangesSerializer {
+    /// Creates a new `PayoutAddressChanges` serializer
+    pub fn new() -> Self {
+        Self {
+            u64_serializer: U64VarIntSerializer::new(),
+            address_serializer: AddressSerializer::new(),
+            opt_address_serializer: OptionSerializer::new(AddressSerializer::new()),
+        }
+    }
+}
+
+impl Serializer<PayoutAddressChanges> for PayoutAddressChangesSerializer {
+    fn serialize(
+        &self,
+        value: &PayoutAddressChanges,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        // sort the entries so that the serialization is deterministic
+        let mut entries: Vec<(&Address, &Option<Address>)> = value.iter().collect();
+        entries.sort_unstable_by_key(|(address, _)| **address);
+
+        self.u64_serializer
+            .serialize(&(entries.len() as u64), buffer)?;
+        for (address, payout_address) in entries {
+            self.address_serializer.serialize(address, buffer)?;
+            self.opt_address_serializer
+                .serialize(payout_address, buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for `PayoutAddressChanges`
+pub struct PayoutAddressChangesDeserializer {
+    length_deserializer: U64VarIntDeserializer,
+    /// address deserializer, also used for the keys and values of the registry
+    pub address_deserializer: AddressDeserializer,
+    opt_address_deserializer: OptionDeserializer<Address, AddressDeserializer>,
+}
+
+impl PayoutAddressChangesDeserializer {
+    /// Creates a new `PayoutAddressChanges` deserializer
+    pub fn new(max_changes_length: u64) -> Self {
+        Self {
+            length_deserializer: U64VarIntDeserializer::new(
+                Included(u64::MIN),
+                Included(max_changes_length),
+            ),
+            address_deserializer: AddressDeserializer::new(),
+            opt_address_deserializer: OptionDeserializer::new(AddressDeserializer::new()),
+        }
+    }
+}
+
+impl Deserializer<PayoutAddressChanges> for PayoutAddressChangesDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], PayoutAddressChanges, E> {
+        context(
+            "Failed PayoutAddressChanges deserialization",
+            length_count(
+                context("Failed length deserialization", |input| {
+                    self.length_deserializer.deserialize(input)
+                }),
+                tuple((
+                    context("Failed address deserialization", |input| {
+                        self.address_deserializer.deserialize(input)
+                    }),
+                    context("Failed payout_address deserialization", |input| {
+                        self.opt_address_deserializer.deserialize(input)
+                    }),
+                )),
+            ),
+        )
+        .map(|entries| entries.into_iter().collect())
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_serialization::DeserializeError;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_payout_address_changes_ser_der() {
+        let addr1 =
+            Address::from_str("AU1jUbxeXW49QRT6Le5aPuNdcGWQV2kpnDyQkKoka4MmEUW3m8Xm").unwrap();
+        let addr2 =
+            Address::from_str("AU12nfJdBNotWffSEDDCS9mMXAxDbHbAVM9GW7pvVJoLxdCeeroX8").unwrap();
+        let mut changes = PayoutAddressChanges::default();
+        changes.insert(addr1, Some(addr2));
+        changes.insert(addr2, None);
+
+        let mut buf = Vec::new();
+        PayoutAddressChangesSerializer::new()
+            .serialize(&changes, &mut buf)
+            .unwrap();
+        let (rest, changes_der) = PayoutAddressChangesDeserializer::new(2)
+            .deserialize::<DeserializeError>(&buf)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(changes_der, changes);
+
+        // more changes than allowed
+        assert!(PayoutAddressChangesDeserializer::new(1)
+            .deserialize::<DeserializeError>(&buf)
+            .is_err());
+    }
+}
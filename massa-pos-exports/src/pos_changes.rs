@@ -1,5 +1,7 @@
 use crate::{
-    DeferredCredits, DeferredCreditsDeserializer, DeferredCreditsSerializer, ProductionStats,
+    DeferredCredits, DeferredCreditsDeserializer, DeferredCreditsSerializer, DelegationChanges,
+    DelegationChangesDeserializer, DelegationChangesSerializer, PayoutAddressChanges,
+    PayoutAddressChangesDeserializer, PayoutAddressChangesSerializer, ProductionStats,
     ProductionStatsDeserializer, ProductionStatsSerializer, RollsDeserializer,
 };
 use bitvec::prelude::*;
@@ -10,6 +12,7 @@ use massa_models::{
 };
 use massa_serialization::{Deserializer, SerializeError, Serializer, U64VarIntSerializer};
 use nom::{
+    combinator::cond,
     error::{context, ContextError, ParseError},
     sequence::tuple,
     IResult, Parser,
@@ -31,6 +34,12 @@ pub struct PoSChanges {
     /// set deferred credits indexed by target slot (can be set to 0 to cancel some, in case of slash)
     /// ordered structure to ensure slot iteration order is deterministic
     pub deferred_credits: DeferredCredits,
+
+    /// new payout addresses (can be `None` to remove the payout address of an address)
+    pub payout_addresses: PayoutAddressChanges,
+
+    /// new delegations (can be `None` to end the delegation of an address)
+    pub delegations: DelegationChanges,
 }
 
 impl Default for PoSChanges {
@@ -40,6 +49,8 @@ impl Default for PoSChanges {
             roll_changes: Default::default(),
             production_stats: Default::default(),
             deferred_credits: DeferredCredits::new(),
+            payout_addresses: Default::default(),
+            delegations: Default::default(),
         }
     }
 }
@@ -51,6 +62,8 @@ impl PoSChanges {
             && self.roll_changes.is_empty()
             && self.production_stats.is_empty()
             && self.deferred_credits.credits.is_empty()
+            && self.payout_addresses.is_empty()
+            && self.delegations.is_empty()
     }
 
     /// Extends the current `PosChanges` with another one
@@ -71,16 +84,30 @@ impl PoSChanges {
 
         // extend deferred credits
         self.deferred_credits.extend(other.deferred_credits);
+
+        // extend payout addresses
+        self.payout_addresses.extend(other.payout_addresses);
+
+        // extend delegations
+        self.delegations.extend(other.delegations);
     }
 }
 
+/// Version of the `PoSChanges` format that carries the payout address and delegation changes.
+/// It is the version of `MipComponent::PayoutAddress` enabling them,
+/// the format of the previous version (0) stops after the deferred credits.
+pub const POS_CHANGES_PAYOUT_ADDRESSES_VERSION: u32 = 1;
+
 /// `PoSChanges` Serializer
 pub struct PoSChangesSerializer {
+    version: u32,
     bit_vec_serializer: BitVecSerializer,
     u64_serializer: U64VarIntSerializer,
     production_stats_serializer: ProductionStatsSerializer,
     address_serializer: AddressSerializer,
     deferred_credits_serializer: DeferredCreditsSerializer,
+    payout_addresses_serializer: PayoutAddressChangesSerializer,
+    delegations_serializer: DelegationChangesSerializer,
 }
 
 impl Default for PoSChangesSerializer {
@@ -90,14 +117,22 @@ impl Default for PoSChangesSerializer {
 }
 
 impl PoSChangesSerializer {
-    /// Create a new `PoSChanges` Serializer
+    /// Create a new `PoSChanges` Serializer for the latest format version
     pub fn new() -> PoSChangesSerializer {
+        PoSChangesSerializer::with_version(POS_CHANGES_PAYOUT_ADDRESSES_VERSION)
+    }
+
+    /// Create a new `PoSChanges` Serializer for the format of the given `MipComponent::PayoutAddress` version
+    pub fn with_version(version: u32) -> PoSChangesSerializer {
         PoSChangesSerializer {
+            version,
             bit_vec_serializer: BitVecSerializer::new(),
             u64_serializer: U64VarIntSerializer::new(),
             production_stats_serializer: ProductionStatsSerializer::new(),
             address_serializer: AddressSerializer::new(),
             deferred_credits_serializer: DeferredCreditsSerializer::new(),
+            payout_addresses_serializer: PayoutAddressChangesSerializer::new(),
+            delegations_serializer: DelegationChangesSerializer::new(),
         }
     }
 }
@@ -124,20 +159,41 @@ impl Serializer<PoSChanges> for PoSChangesSerializer {
         self.deferred_credits_serializer
             .serialize(&value.deferred_credits, buffer)?;
 
+        if self.version < POS_CHANGES_PAYOUT_ADDRESSES_VERSION {
+            if !value.payout_addresses.is_empty() || !value.delegations.is_empty() {
+                return Err(SerializeError::GeneralError(
+                    "payout address and delegation changes are not supported by this PoSChanges version"
+                        .to_string(),
+                ));
+            }
+            return Ok(());
+        }
+
+        // payout_addresses
+        self.payout_addresses_serializer
+            .serialize(&value.payout_addresses, buffer)?;
+
+        // delegations
+        self.delegations_serializer
+            .serialize(&value.delegations, buffer)?;
+
         Ok(())
     }
 }
 
 /// `PoSChanges` Deserializer
 pub struct PoSChangesDeserializer {
+    version: u32,
     bit_vec_deserializer: BitVecDeserializer,
     rolls_deserializer: RollsDeserializer,
     production_stats_deserializer: ProductionStatsDeserializer,
     deferred_credits_deserializer: DeferredCreditsDeserializer,
+    payout_addresses_deserializer: PayoutAddressChangesDeserializer,
+    delegations_deserializer: DelegationChangesDeserializer,
 }
 
 impl PoSChangesDeserializer {
-    /// Create a new `PoSChanges` Deserializer
+    /// Create a new `PoSChanges` Deserializer for the latest format version
     pub fn new(
         thread_count: u8,
         max_rolls_length: u64,
@@ -145,6 +201,7 @@ impl PoSChangesDeserializer {
         max_credits_length: u64,
     ) -> PoSChangesDeserializer {
         PoSChangesDeserializer {
+            version: POS_CHANGES_PAYOUT_ADDRESSES_VERSION,
             bit_vec_deserializer: BitVecDeserializer::new(),
             rolls_deserializer: RollsDeserializer::new(max_rolls_length),
             production_stats_deserializer: ProductionStatsDeserializer::new(
@@ -154,8 +211,17 @@ impl PoSChangesDeserializer {
                 thread_count,
                 max_credits_length,
             ),
+            // the payout address changes are keyed by address, like the roll changes
+            payout_addresses_deserializer: PayoutAddressChangesDeserializer::new(max_rolls_length),
+            delegations_deserializer: DelegationChangesDeserializer::new(max_rolls_length),
         }
     }
+
+    /// Deserialize the format of the given `MipComponent::PayoutAddress` version instead of the latest one
+    pub fn with_version(mut self, version: u32) -> PoSChangesDeserializer {
+        self.version = version;
+        self
+    }
 }
 
 impl Deserializer<PoSChanges> for PoSChangesDeserializer {
@@ -163,6 +229,7 @@ impl Deserializer<PoSChanges> for PoSChangesDeserializer {
         &self,
         buffer: &'a [u8],
     ) -> IResult<&'a [u8], PoSChanges, E> {
+        let with_payout_addresses = self.version >= POS_CHANGES_PAYOUT_ADDRESSES_VERSION;
         context(
             "Failed PoSChanges deserialization",
             tuple((
@@ -178,14 +245,35 @@ impl Deserializer<PoSChanges> for PoSChangesDeserializer {
                 context("Failed deferred_credits deserialization", |input| {
                     self.deferred_credits_deserializer.deserialize(input)
                 }),
+                context(
+                    "Failed payout_addresses deserialization",
+                    cond(with_payout_addresses, |input| {
+                        self.payout_addresses_deserializer.deserialize(input)
+                    }),
+                ),
+                context(
+                    "Failed delegations deserialization",
+                    cond(with_payout_addresses, |input| {
+                        self.delegations_deserializer.deserialize(input)
+                    }),
+                ),
             )),
         )
         .map(
-            |(seed_bits, roll_changes, production_stats, deferred_credits)| PoSChanges {
+            |(
+                seed_bits,
+                roll_changes,
+                production_stats,
+                deferred_credits,
+                payout_addresses,
+                delegations,
+            )| PoSChanges {
                 seed_bits,
                 roll_changes: roll_changes.into_iter().collect(),
                 production_stats,
                 deferred_credits,
+                payout_addresses: payout_addresses.unwrap_or_default(),
+                delegations: delegations.unwrap_or_default(),
             },
         )
         .parse(buffer)
@@ -210,6 +298,8 @@ mod test {
                 && self.roll_changes == other.roll_changes
                 && self.production_stats == other.production_stats
                 && self.deferred_credits.credits == other.deferred_credits.credits
+                && self.payout_addresses == other.payout_addresses
+                && self.delegations == other.delegations
         }
     }
 
@@ -234,11 +324,21 @@ mod test {
         let mut def_credits = DeferredCredits::default();
         def_credits.insert(Slot::new(1, 0), addr1, Amount::from_str("300.0").unwrap());
 
+        let mut payout_addresses = PayoutAddressChanges::default();
+        payout_addresses.insert(addr1, Some(addr2));
+        payout_addresses.insert(addr2, None);
+
+        let mut delegations = DelegationChanges::default();
+        delegations.insert(addr2, Some(addr1));
+        delegations.insert(addr1, None);
+
         let pos_changes = PoSChanges {
             roll_changes,
             seed_bits: BitVec::from_vec(vec![1, 0, 1, 1]),
             production_stats: prod_stats,
             deferred_credits: DeferredCredits::default(),
+            payout_addresses,
+            delegations,
         };
 
         let mut buf = Vec::new();
@@ -255,4 +355,48 @@ mod test {
         assert!(rem.is_empty());
         assert_eq!(pos_changes_der, pos_changes);
     }
+
+    #[test]
+    fn test_pos_changes_ser_der_versions() {
+        let addr1 =
+            Address::from_str("AU1jUbxeXW49QRT6Le5aPuNdcGWQV2kpnDyQkKoka4MmEUW3m8Xm").unwrap();
+        let addr2 =
+            Address::from_str("AU12nfJdBNotWffSEDDCS9mMXAxDbHbAVM9GW7pvVJoLxdCeeroX8").unwrap();
+        let mut roll_changes = PreHashMap::default();
+        roll_changes.insert(addr1, 5);
+        let mut pos_changes = PoSChanges {
+            roll_changes,
+            seed_bits: BitVec::from_vec(vec![1, 0, 1, 1]),
+            ..Default::default()
+        };
+        let deserializer = PoSChangesDeserializer::new(
+            THREAD_COUNT,
+            MAX_ROLLS_COUNT_LENGTH,
+            MAX_PRODUCTION_STATS_LENGTH,
+            MAX_DEFERRED_CREDITS_LENGTH,
+        );
+
+        // before the payout addresses are enabled, the format stops after the deferred credits
+        let mut buf_v0 = Vec::new();
+        PoSChangesSerializer::with_version(0)
+            .serialize(&pos_changes, &mut buf_v0)
+            .unwrap();
+        let mut buf_latest = Vec::new();
+        PoSChangesSerializer::new()
+            .serialize(&pos_changes, &mut buf_latest)
+            .unwrap();
+        assert!(buf_v0.len() < buf_latest.len());
+        let (rem, pos_changes_der) = deserializer
+            .with_version(0)
+            .deserialize::<DeserializeError>(&buf_v0)
+            .unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(pos_changes_der, pos_changes);
+
+        // payout address changes cannot be serialized in the previous format
+        pos_changes.payout_addresses.insert(addr1, Some(addr2));
+        assert!(PoSChangesSerializer::with_version(0)
+            .serialize(&pos_changes, &mut Vec::new())
+            .is_err());
+    }
 }
@@ -1,24 +1,34 @@
 use crate::{
-    CycleHistoryDeserializer, CycleHistorySerializer, CycleInfo, DeferredCreditsDeserializer,
-    DeferredCreditsSerializer, PoSChanges, PosError, PosResult, ProductionStats,
-    SelectorController,
+    compute_initial_cycle, compute_initial_seeds, max_credit_period, read_initial_deferred_credits,
+    read_initial_rolls, CreditChange, CycleHistoryDeserializer, CycleHistorySerializer, CycleInfo,
+    DeferredCreditConflict, DeferredCreditEvent, DeferredCreditsDeserializer,
+    DeferredCreditsRecovery, DeferredCreditsSerializer, DeferredCreditsWatcher, PoSChanges,
+    PosError, PosResult, ProductionStats, SelectorController, SlotCreditsAlert,
 };
-use crate::{DeferredCredits, PoSConfig};
+use crate::{DeferredCredits, DelegationActivation, PoSConfig};
 use bitvec::vec::BitVec;
 use massa_db_exports::{
     DBBatch, MassaDirection, MassaIteratorMode, ShareableMassaDBController,
     CYCLE_HISTORY_DESER_ERROR, CYCLE_HISTORY_PREFIX, CYCLE_HISTORY_SER_ERROR,
-    DEFERRED_CREDITS_DESER_ERROR, DEFERRED_CREDITS_PREFIX, DEFERRED_CREDITS_SER_ERROR, STATE_CF,
+    DEFERRED_CREDITS_DESER_ERROR, DEFERRED_CREDITS_PREFIX, DEFERRED_CREDITS_SER_ERROR,
+    PAYOUT_ADDRESSES_DESER_ERROR, PAYOUT_ADDRESSES_PREFIX, PAYOUT_ADDRESSES_SER_ERROR, STATE_CF,
 };
 use massa_hash::{Hash, HashXof, HASH_XOF_SIZE_BYTES};
 use massa_models::amount::Amount;
-use massa_models::{address::Address, prehash::PreHashMap, slot::Slot};
+use massa_models::{
+    address::{Address, AddressDeserializer, AddressSerializer},
+    prehash::PreHashMap,
+    slot::{Slot, SLOT_KEY_SIZE},
+    stats::PoSStats,
+};
 use massa_serialization::{DeserializeError, Deserializer, Serializer, U64VarIntSerializer};
 use nom::AsBytes;
 use std::collections::VecDeque;
 use std::ops::Bound::{Excluded, Included, Unbounded};
 use std::ops::RangeBounds;
+use std::sync::Arc;
 use std::{collections::BTreeMap, path::PathBuf};
+use tokio::sync::broadcast;
 use tracing::debug;
 
 // Helper function to convert a slice to an array of a given size, if possible,
@@ -30,17 +40,39 @@ fn buf_to_array_ctr<F: Fn(&[u8; N]) -> V, V, const N: usize>(
     Some((&buf[N..], ctr(&buf.get(..N)?.try_into().ok()?)))
 }
 
+/// Upper bound of the number of state hash computations needed to write the PoS entries of a batch:
+/// two for a written entry (previous and new value), one for a deleted entry (previous value)
+fn pos_batch_hash_computations(batch: &DBBatch) -> u64 {
+    batch
+        .iter()
+        .filter(|(key, _)| {
+            [
+                CYCLE_HISTORY_PREFIX,
+                DEFERRED_CREDITS_PREFIX,
+                PAYOUT_ADDRESSES_PREFIX,
+            ]
+            .iter()
+            .any(|prefix| key.starts_with(prefix.as_bytes()))
+        })
+        .map(|(_, value)| if value.is_some() { 2 } else { 1 })
+        .sum()
+}
+
 // General cycle info idents
 const COMPLETE_IDENT: u8 = 0u8;
 const RNG_SEED_IDENT: u8 = 1u8;
 const FINAL_STATE_HASH_SNAPSHOT_IDENT: u8 = 2u8;
 const ROLL_COUNT_IDENT: u8 = 3u8;
 const PROD_STATS_IDENT: u8 = 4u8;
+const DELEGATION_IDENT: u8 = 5u8;
 
 // Production stats idents
 const PROD_STATS_FAIL_IDENT: u8 = 0u8;
 const PROD_STATS_SUCCESS_IDENT: u8 = 1u8;
 
+/// Number of cycles for which the pending deferred credit count is kept in the PoS stats
+const DEFERRED_CREDIT_COUNT_HISTORY_LENGTH: usize = 64;
+
 /// Complete key formatting macro
 #[macro_export]
 macro_rules! complete_key {
@@ -122,6 +154,27 @@ macro_rules! prod_stats_success_key {
     };
 }
 
+/// Delegation prefix macro
+#[macro_export]
+macro_rules! delegation_prefix {
+    ($cycle_prefix:expr) => {
+        [&$cycle_prefix[..], &[DELEGATION_IDENT]].concat()
+    };
+}
+
+/// Delegation key formatting macro
+#[macro_export]
+macro_rules! delegation_key {
+    ($cycle_prefix:expr, $addr:expr) => {
+        [
+            &$cycle_prefix[..],
+            &[DELEGATION_IDENT],
+            &$addr.to_prefixed_bytes()[..],
+        ]
+        .concat()
+    };
+}
+
 /// Deferred credits key formatting macro
 #[macro_export]
 macro_rules! deferred_credits_key {
@@ -155,6 +208,12 @@ pub struct PoSFinalState {
     pub cycle_info_serializer: CycleHistorySerializer,
     /// cycle info deserializer
     pub cycle_info_deserializer: CycleHistoryDeserializer,
+    /// observer of the deferred credits of watched addresses
+    pub credits_watcher: DeferredCreditsWatcher,
+    /// maintenance cost statistics, since the node started
+    pub stats: PoSStats,
+    /// activation of the delegated draws, the draws ignore the delegations if `None`
+    pub delegation_activation: Option<Arc<dyn DelegationActivation>>,
 }
 
 impl PoSFinalState {
@@ -167,19 +226,12 @@ impl PoSFinalState {
         db: ShareableMassaDBController,
     ) -> Result<Self, PosError> {
         // load get initial rolls from file
-        let initial_rolls = serde_json::from_str::<BTreeMap<Address, u64>>(
-            &std::fs::read_to_string(initial_rolls_path).map_err(|err| {
-                PosError::RollsFileLoadingError(format!("error while deserializing: {}", err))
-            })?,
-        )
-        .map_err(|err| PosError::RollsFileLoadingError(format!("error opening file: {}", err)))?;
+        let initial_rolls = read_initial_rolls(initial_rolls_path)?;
 
         // Seeds used as the initial seeds for negative cycles (-2 and -1 respectively)
-        let init_seed = Hash::compute_from(initial_seed_string.as_bytes());
-        let initial_seeds = vec![Hash::compute_from(init_seed.to_bytes()), init_seed];
+        let initial_seeds = compute_initial_seeds(initial_seed_string);
 
-        let deferred_credits_deserializer =
-            DeferredCreditsDeserializer::new(config.thread_count, config.max_credit_length);
+        let deferred_credits_deserializer = DeferredCreditsDeserializer::try_new(&config)?;
         let cycle_info_deserializer = CycleHistoryDeserializer::new(
             config.cycle_history_length as u64,
             config.max_rolls_length,
@@ -198,6 +250,9 @@ impl PoSFinalState {
             deferred_credits_deserializer,
             cycle_info_serializer: CycleHistorySerializer::new(),
             cycle_info_deserializer,
+            credits_watcher: Default::default(),
+            delegation_activation: None,
+            stats: Default::default(),
         };
 
         Ok(pos_state)
@@ -209,40 +264,57 @@ impl PoSFinalState {
             return Ok(());
         };
 
-        use serde::Deserialize;
-        #[derive(Deserialize)]
-        struct AddressInitialDeferredCredits {
-            slot: Slot,
-            amount: Amount,
-        }
-
         let initial_deferred_credits =
-            serde_json::from_str::<PreHashMap<Address, Vec<AddressInitialDeferredCredits>>>(
-                &std::fs::read_to_string(initial_deferred_credits_path).map_err(|err| {
-                    PosError::DeferredCreditsFileLoadingError(format!(
-                        "error while deserializing initial deferred credits file {}: {}",
-                        initial_deferred_credits_path.display(),
-                        err
-                    ))
-                })?,
-            )
-            .map_err(|err| {
-                PosError::DeferredCreditsFileLoadingError(format!(
-                    "error loading initial deferred credits file {}: {}",
-                    initial_deferred_credits_path.display(),
-                    err
-                ))
-            })?;
-
-        for (address, deferred_credits) in initial_deferred_credits {
-            for AddressInitialDeferredCredits { slot, amount } in deferred_credits {
-                self.put_deferred_credits_entry(&slot, &address, &amount, batch);
+            read_initial_deferred_credits(initial_deferred_credits_path)?;
+        for (credit_slot, credits) in initial_deferred_credits.credits {
+            for (address, amount) in credits {
+                self.put_deferred_credits_entry(&credit_slot.slot(), &address, &amount, batch);
             }
         }
 
         Ok(())
     }
 
+    /// Write back the deferred credits of a previous state, read before a reset with `read_deferred_credits`
+    ///
+    /// Credits at or before `last_final_slot` are reported as expired. Credits whose slot and address
+    /// already have an amount in the current state are not overwritten: they are reported as already
+    /// present or as conflicts.
+    pub fn recover_deferred_credits(
+        &self,
+        recovered: DeferredCredits,
+        last_final_slot: Slot,
+        batch: &mut DBBatch,
+    ) -> DeferredCreditsRecovery {
+        let mut recovery = DeferredCreditsRecovery::default();
+        for (credit_slot, credits) in recovered.credits {
+            let slot = credit_slot.slot();
+            for (address, amount) in credits {
+                if amount.is_zero() {
+                    continue;
+                }
+                if credit_slot.is_payable_at(last_final_slot) {
+                    recovery.expired.insert(slot, address, amount);
+                    continue;
+                }
+                match self.get_address_credits_for_slot(&address, &slot) {
+                    Some(current) if current == amount => recovery.already_present += 1,
+                    Some(current) => recovery.conflicts.push(DeferredCreditConflict {
+                        slot,
+                        address,
+                        recovered: amount,
+                        current,
+                    }),
+                    None => {
+                        self.put_deferred_credits_entry(&slot, &address, &amount, batch);
+                        recovery.restored.insert(slot, address, amount);
+                    }
+                }
+            }
+        }
+        recovery
+    }
+
     /// After bootstrap or load from disk, recompute the caches
     pub fn recompute_pos_state_caches(&mut self) {
         self.cycle_history_cache = self.get_cycle_history_cycles().into();
@@ -256,6 +328,8 @@ impl PoSFinalState {
         } else {
             self.rng_seed_cache = None;
         }
+
+        self.sample_deferred_credit_count(None);
     }
 
     /// Reset the state of the PoS final state
@@ -265,6 +339,7 @@ impl PoSFinalState {
         let mut db = self.db.write();
         db.delete_prefix(CYCLE_HISTORY_PREFIX, STATE_CF, None);
         db.delete_prefix(DEFERRED_CREDITS_PREFIX, STATE_CF, None);
+        db.delete_prefix(PAYOUT_ADDRESSES_PREFIX, STATE_CF, None);
         self.cycle_history_cache = Default::default();
         self.rng_seed_cache = None;
     }
@@ -273,23 +348,8 @@ impl PoSFinalState {
     ///
     /// This should be called only if bootstrap did not happen.
     pub fn create_initial_cycle(&mut self, batch: &mut DBBatch) {
-        let mut rng_seed = BitVec::with_capacity(
-            self.config
-                .periods_per_cycle
-                .saturating_mul(self.config.thread_count as u64)
-                .try_into()
-                .unwrap(),
-        );
-        rng_seed.extend(vec![false; self.config.thread_count as usize]);
-
         self.put_new_cycle_info(
-            &CycleInfo::new(
-                0,
-                false,
-                self.initial_rolls.clone(),
-                rng_seed,
-                PreHashMap::default(),
-            ),
+            &compute_initial_cycle(&self.config, self.initial_rolls.clone()),
             batch,
         );
     }
@@ -338,6 +398,11 @@ impl PoSFinalState {
             ),
             batch,
         );
+        if cycle != last_cycle_info.cycle {
+            for (delegator, operator) in self.get_cycle_delegations(last_cycle_info.cycle) {
+                self.put_cycle_history_delegation_entry(cycle, &delegator, Some(&operator), batch);
+            }
+        }
 
         Ok(())
     }
@@ -447,6 +512,7 @@ impl PoSFinalState {
                 // the previous cycle is complete, push a new incomplete/empty one to extend
 
                 let roll_counts = self.get_all_roll_counts(info.0);
+                let delegations = self.get_cycle_delegations(info.0);
                 self.put_new_cycle_info(
                     &CycleInfo::new(
                         cycle,
@@ -457,6 +523,14 @@ impl PoSFinalState {
                     ),
                     batch,
                 );
+                for (delegator, operator) in delegations {
+                    self.put_cycle_history_delegation_entry(
+                        cycle,
+                        &delegator,
+                        Some(&operator),
+                        batch,
+                    );
+                }
                 while self.cycle_history_cache.len() > self.config.cycle_history_length {
                     if let Some((old_cycle, _)) = self.cycle_history_cache.pop_front() {
                         self.delete_cycle_info(old_cycle, batch);
@@ -489,6 +563,11 @@ impl PoSFinalState {
             self.put_cycle_history_address_entry(cycle, &addr, Some(&roll_count), None, batch);
         }
 
+        // set or remove the delegations
+        for (delegator, operator) in changes.delegations.iter() {
+            self.put_cycle_history_delegation_entry(cycle, delegator, operator.as_ref(), batch);
+        }
+
         // extend production stats
         for (addr, stats) in changes.production_stats {
             if let Some(prev_production_stats) = self.get_production_stats_for_address(cycle, &addr)
@@ -516,13 +595,59 @@ impl PoSFinalState {
             );
         }
 
+        // raise an alert if the credits scheduled for an upcoming slot push its total above the threshold
+        if let Some(threshold) = self.credits_watcher.get_slot_alert_threshold() {
+            self.check_slot_credits_totals(slot, &changes.deferred_credits, threshold);
+        }
+
         // extend deferred_credits with changes.deferred_credits and remove zeros
-        for (slot, credits) in changes.deferred_credits.credits.iter() {
+        // notify the changes of the credits of the watched addresses, and of all of them to the subscribers
+        let watch_credits = self.credits_watcher.is_active();
+        let stream_changes = self.credits_watcher.has_change_subscribers();
+        for (credit_slot, credits) in changes.deferred_credits.credits.iter() {
+            let credit_slot = &credit_slot.slot();
             for (address, amount) in credits.iter() {
-                self.put_deferred_credits_entry(slot, address, amount, batch);
+                let watched = watch_credits && self.credits_watcher.is_watched(address);
+                if watched || stream_changes {
+                    let previous = self.get_address_credits_for_slot(address, credit_slot);
+                    if stream_changes {
+                        if let Some(change) =
+                            CreditChange::new(slot, *credit_slot, *address, previous, *amount)
+                        {
+                            self.credits_watcher.notify_change(change);
+                        }
+                    }
+                    if watched {
+                        if let Some(event) = DeferredCreditEvent::from_change(
+                            slot,
+                            *credit_slot,
+                            *address,
+                            previous,
+                            *amount,
+                        ) {
+                            self.credits_watcher.notify(event);
+                        }
+                    }
+                }
+                self.put_deferred_credits_entry(credit_slot, address, amount, batch);
             }
         }
 
+        // set or remove the payout addresses
+        for (address, payout_address) in changes.payout_addresses.iter() {
+            self.put_payout_address_entry(address, payout_address.as_ref(), batch);
+        }
+
+        // account for the state hash computations of the PoS entries of the batch
+        self.stats.applied_slot_count = self.stats.applied_slot_count.saturating_add(1);
+        self.stats.hash_computation_count = self
+            .stats
+            .hash_computation_count
+            .saturating_add(pos_batch_hash_computations(batch));
+        if complete {
+            self.sample_deferred_credit_count(Some(cycle));
+        }
+
         // feed the cycle if it is complete
         // notify the PoSDrawer about the newly ready draw data
         // to draw cycle + 2, we use the rng data from cycle - 1 and the seed from cycle
@@ -543,30 +668,43 @@ impl PoSFinalState {
     pub fn feed_selector(&self, draw_cycle: u64) -> PosResult<()> {
         // get roll lookback
 
-        let (lookback_rolls, lookback_state_hash) = match draw_cycle.checked_sub(3) {
-            // looking back in history
-            Some(c) => {
-                let index = self
-                    .get_cycle_index(c)
-                    .ok_or(PosError::CycleUnavailable(c))?;
-                let cycle_info = &self.cycle_history_cache[index];
-                if !cycle_info.1 {
-                    return Err(PosError::CycleUnfinished(c));
+        let (lookback_rolls, lookback_delegations, lookback_state_hash) =
+            match draw_cycle.checked_sub(3) {
+                // looking back in history
+                Some(c) => {
+                    let index = self
+                        .get_cycle_index(c)
+                        .ok_or(PosError::CycleUnavailable(c))?;
+                    let cycle_info = &self.cycle_history_cache[index];
+                    if !cycle_info.1 {
+                        return Err(PosError::CycleUnfinished(c));
+                    }
+                    // take the final_state_hash_snapshot at cycle - 3
+                    // it will later be combined with rng_seed from cycle - 2 to determine the selection seed
+                    // do this here to avoid a potential attacker manipulating the selections
+                    let state_hash = self.get_cycle_history_final_state_hash_snapshot(cycle_info.0);
+                    // the delegations only weight the draws once delegation is activated
+                    let delegations_active = self
+                        .delegation_activation
+                        .as_ref()
+                        .map_or(false, |activation| {
+                            activation.is_active_at_cycle(cycle_info.0)
+                        });
+                    (
+                        self.get_all_roll_counts(cycle_info.0),
+                        if delegations_active {
+                            self.get_cycle_delegations(cycle_info.0)
+                        } else {
+                            BTreeMap::new()
+                        },
+                        Some(state_hash.expect(
+                            "critical: a complete cycle must contain a final state hash snapshot",
+                        )),
+                    )
                 }
-                // take the final_state_hash_snapshot at cycle - 3
-                // it will later be combined with rng_seed from cycle - 2 to determine the selection seed
-                // do this here to avoid a potential attacker manipulating the selections
-                let state_hash = self.get_cycle_history_final_state_hash_snapshot(cycle_info.0);
-                (
-                    self.get_all_roll_counts(cycle_info.0),
-                    Some(state_hash.expect(
-                        "critical: a complete cycle must contain a final state hash snapshot",
-                    )),
-                )
-            }
-            // looking back to negative cycles
-            None => (self.initial_rolls.clone(), None),
-        };
+                // looking back to negative cycles
+                None => (self.initial_rolls.clone(), BTreeMap::new(), None),
+            };
 
         // get seed lookback
         let lookback_seed = match draw_cycle.checked_sub(2) {
@@ -597,9 +735,59 @@ impl PoSFinalState {
         };
 
         // feed selector
-        self.selector
-            .as_ref()
-            .feed_cycle(draw_cycle, lookback_rolls, lookback_seed)
+        self.selector.as_ref().feed_cycle(
+            draw_cycle,
+            lookback_rolls,
+            lookback_delegations,
+            lookback_seed,
+        )
+    }
+
+    /// Get the maintenance cost statistics of the PoS final state
+    pub fn get_stats(&self) -> PoSStats {
+        self.stats.clone()
+    }
+
+    /// Count the pending deferred credits and the slots they are spread over.
+    /// When a completed `cycle` is given, the count is also recorded in the history of the stats.
+    ///
+    /// The sample is taken from the database: when a cycle completes, the credits of its last slot
+    /// are still in the batch being built and are not counted.
+    fn sample_deferred_credit_count(&mut self, cycle: Option<u64>) {
+        let mut credit_count: u64 = 0;
+        let mut slot_count: u64 = 0;
+        let mut last_slot_key: Option<Vec<u8>> = None;
+        {
+            let db = self.db.read();
+            for (serialized_key, _) in
+                db.prefix_iterator_cf(STATE_CF, DEFERRED_CREDITS_PREFIX.as_bytes())
+            {
+                if !serialized_key.starts_with(DEFERRED_CREDITS_PREFIX.as_bytes()) {
+                    break;
+                }
+                credit_count += 1;
+                let slot_key = serialized_key
+                    .get(
+                        DEFERRED_CREDITS_PREFIX.len()
+                            ..DEFERRED_CREDITS_PREFIX.len() + SLOT_KEY_SIZE,
+                    )
+                    .map(|slot_key| slot_key.to_vec());
+                if slot_key != last_slot_key {
+                    slot_count += 1;
+                    last_slot_key = slot_key;
+                }
+            }
+        }
+
+        self.stats.deferred_credit_count = credit_count;
+        self.stats.deferred_credit_slot_count = slot_count;
+        if let Some(cycle) = cycle {
+            let history = &mut self.stats.deferred_credit_count_history;
+            history.push((cycle, credit_count));
+            if history.len() > DEFERRED_CREDIT_COUNT_HISTORY_LENGTH {
+                history.remove(0);
+            }
+        }
     }
 
     /// Feeds the selector targeting a given draw cycle
@@ -692,6 +880,39 @@ impl PoSFinalState {
         }
     }
 
+    /// Raise an alert for each upcoming slot whose total amount of deferred credits
+    /// is increased above `threshold` by the credit changes applied at `slot`
+    fn check_slot_credits_totals(&self, slot: Slot, changes: &DeferredCredits, threshold: Amount) {
+        let total = |credits: &PreHashMap<Address, Amount>| {
+            credits.values().fold(Amount::zero(), |total, amount| {
+                total.saturating_add(*amount)
+            })
+        };
+        for (credit_slot, slot_changes) in changes.credits.range((Excluded(slot), Unbounded)) {
+            let credit_slot = &credit_slot.slot();
+            let mut slot_credits = self
+                .get_deferred_credits_range(*credit_slot..=*credit_slot, None)
+                .credits
+                .remove(credit_slot)
+                .unwrap_or_default();
+            let previous_total = total(&slot_credits);
+            slot_credits.extend(
+                slot_changes
+                    .iter()
+                    .map(|(address, amount)| (*address, *amount)),
+            );
+            let new_total = total(&slot_credits);
+            if new_total > threshold && new_total > previous_total {
+                self.credits_watcher.notify_slot_alert(SlotCreditsAlert {
+                    slot,
+                    credit_slot: *credit_slot,
+                    total: new_total,
+                    threshold,
+                });
+            }
+        }
+    }
+
     /// Retrieves every deferred credit in a slot range
     /// Warning: this can be quite slow if there is tons of Deferred credits (e.g. just after genesis) to fetch
     ///          so there is an option to filter by an address to improve the speed (cf json rpc api get_addresses)
@@ -1019,6 +1240,12 @@ impl PoSFinalState {
         Some(cycle_info)
     }
 
+    /// Subscribe to the changes of the final deferred credits of all addresses,
+    /// emitted when the slot that makes them final is applied
+    pub fn subscribe_credit_changes(&self) -> broadcast::Receiver<CreditChange> {
+        self.credits_watcher.subscribe_credit_changes()
+    }
+
     /// Gets the deferred credits for a given address that will be credited at a given slot
     pub fn get_address_credits_for_slot(&self, addr: &Address, slot: &Slot) -> Option<Amount> {
         let db = self.db.read();
@@ -1045,6 +1272,103 @@ impl PoSFinalState {
         }
     }
 
+    /// Gets the address receiving the deferred credits and staking rewards of an address, if it designated one
+    pub fn get_payout_address(&self, addr: &Address) -> Option<Address> {
+        let db = self.db.read();
+
+        match db.get_cf(STATE_CF, self.payout_address_key(addr)) {
+            Ok(Some(serialized_payout_address)) => {
+                let (_, payout_address) = AddressDeserializer::new()
+                    .deserialize::<DeserializeError>(&serialized_payout_address)
+                    .expect(PAYOUT_ADDRESSES_DESER_ERROR);
+                Some(payout_address)
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets the addresses that designated `payout_address` as their payout address.
+    /// Scans the whole registry: meant for API queries, execution only resolves the other direction.
+    pub fn get_payout_address_sources(&self, payout_address: &Address) -> Vec<Address> {
+        let db = self.db.read();
+        let address_deserializer = AddressDeserializer::new();
+
+        let mut sources = Vec::new();
+        for (serialized_key, serialized_value) in db
+            .prefix_iterator_cf(STATE_CF, PAYOUT_ADDRESSES_PREFIX.as_bytes())
+            .take_while(|(key, _)| key.starts_with(PAYOUT_ADDRESSES_PREFIX.as_bytes()))
+        {
+            let (_, value) = address_deserializer
+                .deserialize::<DeserializeError>(&serialized_value)
+                .expect(PAYOUT_ADDRESSES_DESER_ERROR);
+            if &value != payout_address {
+                continue;
+            }
+            let (_, source) = address_deserializer
+                .deserialize::<DeserializeError>(&serialized_key[PAYOUT_ADDRESSES_PREFIX.len()..])
+                .expect(PAYOUT_ADDRESSES_DESER_ERROR);
+            sources.push(source);
+        }
+        sources
+    }
+
+    /// Gets the operator of each delegator at the end of a given cycle, or at the latest final slot
+    /// for the current cycle.
+    /// The delegations of a cycle are used with its roll counts for the draws three cycles later.
+    pub fn get_cycle_delegations(&self, cycle: u64) -> BTreeMap<Address, Address> {
+        let db = self.db.read();
+        let address_deserializer = AddressDeserializer::new();
+
+        let mut delegations = BTreeMap::new();
+        let prefix = delegation_prefix!(self.cycle_history_cycle_prefix(cycle));
+        for (serialized_key, serialized_value) in db
+            .prefix_iterator_cf(STATE_CF, &prefix)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+        {
+            let (_, delegator) = address_deserializer
+                .deserialize::<DeserializeError>(&serialized_key[prefix.len()..])
+                .expect(CYCLE_HISTORY_DESER_ERROR);
+            let (_, operator) = address_deserializer
+                .deserialize::<DeserializeError>(&serialized_value)
+                .expect(CYCLE_HISTORY_DESER_ERROR);
+            delegations.insert(delegator, operator);
+        }
+        delegations
+    }
+
+    /// Gets the operator an address delegates its rolls to, at the latest final slot
+    pub fn get_delegation_operator(&self, delegator: &Address) -> Option<Address> {
+        let (cycle, _) = self.cycle_history_cache.back()?;
+        let db = self.db.read();
+
+        match db.get_cf(
+            STATE_CF,
+            delegation_key!(self.cycle_history_cycle_prefix(*cycle), delegator),
+        ) {
+            Ok(Some(serialized_operator)) => {
+                let (_, operator) = AddressDeserializer::new()
+                    .deserialize::<DeserializeError>(&serialized_operator)
+                    .expect(CYCLE_HISTORY_DESER_ERROR);
+                Some(operator)
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets the addresses that delegate their rolls to `operator`, at the latest final slot.
+    /// Scans the whole registry: meant for API queries.
+    pub fn get_operator_delegators(&self, operator: &Address) -> Vec<Address> {
+        let Some((cycle, _)) = self.cycle_history_cache.back() else {
+            return Vec::new();
+        };
+        self.get_cycle_delegations(*cycle)
+            .into_iter()
+            .filter_map(|(delegator, delegator_operator)| {
+                (&delegator_operator == operator).then_some(delegator)
+            })
+            .collect()
+    }
+
     /// Gets the production stats for a given address
     pub fn get_production_stats_for_address(
         &self,
@@ -1261,6 +1585,34 @@ impl PoSFinalState {
         }
     }
 
+    /// Internal function to set (`Some`) or remove (`None`) the operator of a delegator in the cycle history
+    fn put_cycle_history_delegation_entry(
+        &self,
+        cycle: u64,
+        delegator: &Address,
+        operator: Option<&Address>,
+        batch: &mut DBBatch,
+    ) {
+        let db = self.db.read();
+
+        let prefix = self.cycle_history_cycle_prefix(cycle);
+
+        match operator {
+            Some(operator) => {
+                let mut serialized_operator = Vec::new();
+                AddressSerializer::new()
+                    .serialize(operator, &mut serialized_operator)
+                    .expect(CYCLE_HISTORY_SER_ERROR);
+                db.put_or_update_entry_value(
+                    batch,
+                    delegation_key!(prefix, delegator),
+                    &serialized_operator,
+                );
+            }
+            None => db.delete_key(batch, delegation_key!(prefix, delegator)),
+        }
+    }
+
     /// Internal function to put an entry
     pub fn put_deferred_credits_entry(
         &self,
@@ -1300,6 +1652,68 @@ impl PoSFinalState {
 
 /// Helpers for key and value management
 impl PoSFinalState {
+    /// Internal function to set (`Some`) or remove (`None`) the payout address of an address
+    fn put_payout_address_entry(
+        &self,
+        address: &Address,
+        payout_address: Option<&Address>,
+        batch: &mut DBBatch,
+    ) {
+        let db = self.db.read();
+
+        match payout_address {
+            Some(payout_address) => {
+                let mut serialized_payout_address = Vec::new();
+                AddressSerializer::new()
+                    .serialize(payout_address, &mut serialized_payout_address)
+                    .expect(PAYOUT_ADDRESSES_SER_ERROR);
+                db.put_or_update_entry_value(
+                    batch,
+                    self.payout_address_key(address),
+                    &serialized_payout_address,
+                );
+            }
+            None => db.delete_key(batch, self.payout_address_key(address)),
+        }
+    }
+
+    /// Helper function to construct the key of the payout address of an address
+    fn payout_address_key(&self, address: &Address) -> Vec<u8> {
+        let mut serialized_key = PAYOUT_ADDRESSES_PREFIX.as_bytes().to_vec();
+        AddressSerializer::new()
+            .serialize(address, &mut serialized_key)
+            .expect(PAYOUT_ADDRESSES_SER_ERROR);
+        serialized_key
+    }
+
+    /// Deserializes the key and value of a payout address, useful after bootstrap
+    pub fn is_payout_address_key_value_valid(
+        &self,
+        serialized_key: &[u8],
+        serialized_value: &[u8],
+    ) -> bool {
+        if !serialized_key.starts_with(PAYOUT_ADDRESSES_PREFIX.as_bytes()) {
+            return false;
+        }
+        let address_deserializer = AddressDeserializer::new();
+
+        let Ok((rest_key, _address)) = address_deserializer
+            .deserialize::<DeserializeError>(&serialized_key[PAYOUT_ADDRESSES_PREFIX.len()..])
+        else {
+            return false;
+        };
+        if !rest_key.is_empty() {
+            return false;
+        }
+
+        let Ok((rest_value, _payout_address)) =
+            address_deserializer.deserialize::<DeserializeError>(serialized_value)
+        else {
+            return false;
+        };
+        rest_value.is_empty()
+    }
+
     /// Helper function to construct the key prefix associated with a given cycle
     fn cycle_history_cycle_prefix(&self, cycle: u64) -> Vec<u8> {
         let mut serialized_key = Vec::new();
@@ -1453,6 +1867,25 @@ impl PoSFinalState {
                     }
                 }
             }
+            DELEGATION_IDENT => {
+                let address_deserializer = AddressDeserializer::new();
+                let Ok((rest_key, _delegator)) =
+                    address_deserializer.deserialize::<DeserializeError>(&rest_key[1..])
+                else {
+                    return false;
+                };
+                if !rest_key.is_empty() {
+                    return false;
+                }
+                let Ok((rest_value, _operator)) =
+                    address_deserializer.deserialize::<DeserializeError>(serialized_value)
+                else {
+                    return false;
+                };
+                if !rest_value.is_empty() {
+                    return false;
+                }
+            }
             _ => {
                 return false;
             }
@@ -1461,11 +1894,13 @@ impl PoSFinalState {
         true
     }
 
-    /// Deserializes the key and value, useful after bootstrap
+    /// Deserializes the key and value, useful after bootstrap.
+    /// The slot of the credit must be in a valid thread and not absurdly far after `last_final_slot`.
     pub fn is_deferred_credits_key_value_valid(
         &self,
         serialized_key: &[u8],
         serialized_value: &[u8],
+        last_final_slot: Slot,
     ) -> bool {
         if !serialized_key.starts_with(DEFERRED_CREDITS_PREFIX.as_bytes()) {
             return false;
@@ -1473,9 +1908,14 @@ impl PoSFinalState {
 
         // deserialize the slot
         let rest_key = &serialized_key[DEFERRED_CREDITS_PREFIX.len()..];
-        let Some((rest_key, _slot)) = buf_to_array_ctr(rest_key, Slot::from_bytes_key) else {
+        let Some((rest_key, slot)) = buf_to_array_ctr(rest_key, Slot::from_bytes_key) else {
             return false;
         };
+        if slot.thread >= self.config.thread_count
+            || slot.period > max_credit_period(last_final_slot)
+        {
+            return false;
+        }
 
         let Ok((rest_key, _addr)): std::result::Result<
             (&[u8], Address),
@@ -1513,39 +1953,7 @@ impl PoSFinalState {
 impl PoSFinalState {
     /// Queries all the deferred credits in the database
     pub fn get_deferred_credits(&self) -> DeferredCredits {
-        let db = self.db.read();
-
-        let mut deferred_credits = DeferredCredits::new();
-
-        for (serialized_key, serialized_value) in
-            db.prefix_iterator_cf(STATE_CF, DEFERRED_CREDITS_PREFIX.as_bytes())
-        {
-            if !serialized_key.starts_with(DEFERRED_CREDITS_PREFIX.as_bytes()) {
-                break;
-            }
-
-            // deserialize the slot
-            let rest_key = &serialized_key[DEFERRED_CREDITS_PREFIX.len()..];
-            let (rest_key, slot) = buf_to_array_ctr(rest_key, Slot::from_bytes_key)
-                .expect(DEFERRED_CREDITS_DESER_ERROR);
-
-            let (_, address) = self
-                .deferred_credits_deserializer
-                .credit_deserializer
-                .address_deserializer
-                .deserialize::<DeserializeError>(rest_key)
-                .expect(DEFERRED_CREDITS_DESER_ERROR);
-
-            let (_, amount) = self
-                .deferred_credits_deserializer
-                .credit_deserializer
-                .amount_deserializer
-                .deserialize::<DeserializeError>(&serialized_value)
-                .expect(DEFERRED_CREDITS_DESER_ERROR);
-
-            deferred_credits.insert(slot, address, amount);
-        }
-        deferred_credits
+        crate::read_deferred_credits(&self.db, &self.config).expect(DEFERRED_CREDITS_DESER_ERROR)
     }
 }
 
@@ -1560,12 +1968,16 @@ mod tests {
     use assert_matches::assert_matches;
     use bitvec::prelude::*;
     use parking_lot::RwLock;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
     use tempfile::TempDir;
 
-    use crate::MockSelectorController;
+    use crate::{CreditSlot, DelegationChanges, MockSelectorController};
 
     use massa_db_exports::{MassaDBConfig, MassaDBController};
     use massa_db_worker::MassaDB;
+    use massa_models::address::{UserAddress, UserAddressV0};
     use massa_models::config::constants::{
         MAX_DEFERRED_CREDITS_LENGTH, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
         POS_SAVED_CYCLES,
@@ -1609,50 +2021,13 @@ mod tests {
             deferred_credits_file_contents.as_bytes(),
         )
         .expect("failed writing initial deferred credits file");
-        let pos_config = PoSConfig {
-            periods_per_cycle: 2,
-            thread_count: 2,
-            cycle_history_length: POS_SAVED_CYCLES,
-            max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
-            max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
-            max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
-            initial_deferred_credits_path: Some(initial_deferred_credits_file.path().to_path_buf()),
-        };
         // initialize the database and pos_state
         let tempdir = tempfile::TempDir::new().expect("cannot create temp directory");
-        let db_config = MassaDBConfig {
-            path: tempdir.path().to_path_buf(),
-            max_history_length: 10,
-            max_final_state_elements_size: 100_000,
-            max_versioning_elements_size: 100_000,
-            thread_count: 2,
-        };
-        let db = Arc::new(RwLock::new(
-            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
-        ));
-        let selector_controller = Box::new(MockSelectorController::new());
-        let init_seed = Hash::compute_from(b"");
-        let initial_seeds = vec![Hash::compute_from(init_seed.to_bytes()), init_seed];
-        let deferred_credits_deserializer =
-            DeferredCreditsDeserializer::new(pos_config.thread_count, pos_config.max_credit_length);
-        let cycle_info_deserializer = CycleHistoryDeserializer::new(
-            pos_config.cycle_history_length as u64,
-            pos_config.max_rolls_length,
-            pos_config.max_production_stats_length,
+        let mut pos_state = test_pos_state(
+            tempdir.path(),
+            Some(initial_deferred_credits_file.path().to_path_buf()),
         );
-        let mut pos_state = PoSFinalState {
-            config: pos_config,
-            db: db.clone(),
-            cycle_history_cache: Default::default(),
-            rng_seed_cache: None,
-            selector: selector_controller,
-            initial_rolls: Default::default(),
-            initial_seeds,
-            deferred_credits_serializer: DeferredCreditsSerializer::new(),
-            deferred_credits_deserializer,
-            cycle_info_serializer: CycleHistorySerializer::new(),
-            cycle_info_deserializer,
-        };
+        let db = pos_state.db.clone();
         let mut batch = DBBatch::new();
         // load initial deferred credits
         pos_state
@@ -1668,25 +2043,25 @@ mod tests {
         let a_a2_s3 = Amount::from_str("2.01").unwrap();
         let expected_credits = vec![
             (
-                Slot::new(3, 0),
+                CreditSlot::new(Slot::new(3, 0)),
                 vec![(addr1, a_a1_s3), (addr2, a_a2_s3)]
                     .into_iter()
                     .collect(),
             ),
             (
-                Slot::new(4, 1),
+                CreditSlot::new(Slot::new(4, 1)),
                 vec![(addr1, Amount::from_str("6.0").unwrap())]
                     .into_iter()
                     .collect(),
             ),
             (
-                Slot::new(255, 0),
+                CreditSlot::new(Slot::new(255, 0)),
                 vec![(addr1, Amount::from_str("5.01").unwrap())]
                     .into_iter()
                     .collect(),
             ),
             (
-                Slot::new(256, 0),
+                CreditSlot::new(Slot::new(256, 0)),
                 vec![(addr1, Amount::from_str("6.0").unwrap())]
                     .into_iter()
                     .collect(),
@@ -1704,7 +2079,7 @@ mod tests {
         let credits_range_2 =
             pos_state.get_deferred_credits_range(Slot::new(2, 0)..Slot::new(3, 1), None);
         let expected_credits_range_2 = vec![(
-            Slot::new(3, 0),
+            CreditSlot::new(Slot::new(3, 0)),
             vec![(addr1, a_a1_s3), (addr2, a_a2_s3)]
                 .into_iter()
                 .collect(),
@@ -1720,7 +2095,7 @@ mod tests {
 
         let a_a1_s255 = Amount::from_str("5.01").unwrap();
         let expected_credits_range_4 = vec![(
-            Slot::new(255, 0),
+            CreditSlot::new(Slot::new(255, 0)),
             vec![(addr1, a_a1_s255)].into_iter().collect(),
         )]
         .into_iter()
@@ -1934,53 +2309,9 @@ mod tests {
     // If this is not handled properly, the node hangs as explained here: https://github.com/massalabs/massa/issues/4101
     #[test]
     fn test_pos_cache_recomputation() {
-        let pos_config = PoSConfig {
-            periods_per_cycle: 2,
-            thread_count: 2,
-            cycle_history_length: POS_SAVED_CYCLES,
-            max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
-            max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
-            max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
-            initial_deferred_credits_path: None,
-        };
-
         // initialize the database and pos_state
         let tempdir = TempDir::new().expect("cannot create temp directory");
-        let db_config = MassaDBConfig {
-            path: tempdir.path().to_path_buf(),
-            max_history_length: 10,
-            max_final_state_elements_size: 100_000,
-            max_versioning_elements_size: 100_000,
-            thread_count: 2,
-        };
-        let db = Arc::new(RwLock::new(
-            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
-        ));
-        let selector_controller = Box::new(MockSelectorController::new());
-        let init_seed = Hash::compute_from(b"");
-        let initial_seeds = vec![Hash::compute_from(init_seed.to_bytes()), init_seed];
-
-        let deferred_credits_deserializer =
-            DeferredCreditsDeserializer::new(pos_config.thread_count, pos_config.max_credit_length);
-        let cycle_info_deserializer = CycleHistoryDeserializer::new(
-            pos_config.cycle_history_length as u64,
-            pos_config.max_rolls_length,
-            pos_config.max_production_stats_length,
-        );
-
-        let mut pos_state = PoSFinalState {
-            config: pos_config,
-            db: db.clone(),
-            cycle_history_cache: Default::default(),
-            rng_seed_cache: None,
-            selector: selector_controller,
-            initial_rolls: Default::default(),
-            initial_seeds,
-            deferred_credits_serializer: DeferredCreditsSerializer::new(),
-            deferred_credits_deserializer,
-            cycle_info_serializer: CycleHistorySerializer::new(),
-            cycle_info_deserializer,
-        };
+        let mut pos_state = test_pos_state(tempdir.path(), None);
 
         // Populate the disk with some cycle infos
         let mut cycle_infos = Vec::new();
@@ -2033,60 +2364,10 @@ mod tests {
     // This test aims to check that the basic workflow of apply changes to the PoS state works.
     #[test]
     fn test_pos_final_state_hash_computation() {
-        let pos_config = PoSConfig {
-            periods_per_cycle: 2,
-            thread_count: 2,
-            cycle_history_length: POS_SAVED_CYCLES,
-            max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
-            max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
-            max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
-            initial_deferred_credits_path: None,
-        };
-
         // initialize the database and pos_state
         let tempdir = TempDir::new().expect("cannot create temp directory");
-        let db_config = MassaDBConfig {
-            path: tempdir.path().to_path_buf(),
-            max_history_length: 10,
-            max_final_state_elements_size: 100_000,
-            max_versioning_elements_size: 100_000,
-            thread_count: 2,
-        };
-        let db = Arc::new(RwLock::new(
-            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
-        ));
-        let selector_controller = Box::new(MockSelectorController::new());
-        let init_seed = Hash::compute_from(b"");
-        let initial_seeds = vec![Hash::compute_from(init_seed.to_bytes()), init_seed];
-
-        let deferred_credits_deserializer =
-            DeferredCreditsDeserializer::new(pos_config.thread_count, pos_config.max_credit_length);
-        let cycle_info_deserializer = CycleHistoryDeserializer::new(
-            pos_config.cycle_history_length as u64,
-            pos_config.max_rolls_length,
-            pos_config.max_production_stats_length,
-        );
-
-        let mut pos_state = PoSFinalState {
-            config: pos_config,
-            db: db.clone(),
-            cycle_history_cache: Default::default(),
-            rng_seed_cache: None,
-            selector: selector_controller,
-            initial_rolls: Default::default(),
-            initial_seeds,
-            deferred_credits_serializer: DeferredCreditsSerializer::new(),
-            deferred_credits_deserializer,
-            cycle_info_serializer: CycleHistorySerializer::new(),
-            cycle_info_deserializer,
-        };
-
-        pos_state.recompute_pos_state_caches();
-
-        let mut batch = DBBatch::new();
-        pos_state.create_initial_cycle(&mut batch);
-        db.write()
-            .write_batch(batch, Default::default(), Some(Slot::new(0, 0)));
+        let mut pos_state = initialized_test_pos_state(tempdir.path());
+        let db = pos_state.db.clone();
 
         // add changes
         let addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
@@ -2105,6 +2386,8 @@ mod tests {
             roll_changes: roll_changes.clone(),
             production_stats: production_stats.clone(),
             deferred_credits: DeferredCredits::new(),
+            payout_addresses: Default::default(),
+            delegations: Default::default(),
         };
 
         let mut batch = DBBatch::new();
@@ -2130,6 +2413,8 @@ mod tests {
             roll_changes: roll_changes.clone(),
             production_stats: production_stats.clone(),
             deferred_credits: DeferredCredits::new(),
+            payout_addresses: Default::default(),
+            delegations: Default::default(),
         };
 
         let mut batch = DBBatch::new();
@@ -2156,6 +2441,8 @@ mod tests {
             roll_changes,
             production_stats,
             deferred_credits: DeferredCredits::new(),
+            payout_addresses: Default::default(),
+            delegations: Default::default(),
         };
 
         let mut batch = DBBatch::new();
@@ -2192,6 +2479,219 @@ mod tests {
         assert_eq!(cycle_info_a, cycle_info_b, "cycle_info mismatch");
     }
 
+    // This test checks that the payout addresses are set, resolved in both directions and removed
+    #[test]
+    fn test_payout_addresses() {
+        // initialize the database and pos_state
+        let tempdir = TempDir::new().expect("cannot create temp directory");
+        let mut pos_state = initialized_test_pos_state(tempdir.path());
+        let db = pos_state.db.clone();
+
+        let addr_a = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let addr_b = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let payout = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let mut apply = |payout_addresses: PreHashMap<Address, Option<Address>>, slot: Slot| {
+            let changes = PoSChanges {
+                seed_bits: bitvec![u8, Lsb0; 0],
+                payout_addresses,
+                ..Default::default()
+            };
+            let mut batch = DBBatch::new();
+            pos_state
+                .apply_changes_to_batch(changes, slot, false, &mut batch)
+                .unwrap();
+            db.write()
+                .write_batch(batch, Default::default(), Some(slot));
+        };
+
+        // both addresses are paid to the same payout address
+        apply(
+            [(addr_a, Some(payout)), (addr_b, Some(payout))]
+                .into_iter()
+                .collect(),
+            Slot::new(0, 0),
+        );
+        // the first one is paid to itself again
+        apply([(addr_a, None)].into_iter().collect(), Slot::new(0, 1));
+
+        assert_eq!(pos_state.get_payout_address(&addr_a), None);
+        assert_eq!(pos_state.get_payout_address(&addr_b), Some(payout));
+        assert_eq!(pos_state.get_payout_address(&payout), None);
+        assert_eq!(pos_state.get_payout_address_sources(&payout), vec![addr_b]);
+        assert!(pos_state.get_payout_address_sources(&addr_b).is_empty());
+
+        let db_read = db.read();
+        for (key, value) in db_read
+            .prefix_iterator_cf(STATE_CF, PAYOUT_ADDRESSES_PREFIX.as_bytes())
+            .take_while(|(key, _)| key.starts_with(PAYOUT_ADDRESSES_PREFIX.as_bytes()))
+        {
+            assert!(pos_state.is_payout_address_key_value_valid(&key, &value));
+        }
+    }
+
+    #[test]
+    fn test_delegations() {
+        // initialize the database and pos_state
+        let tempdir = TempDir::new().expect("cannot create temp directory");
+        let mut pos_state = initialized_test_pos_state(tempdir.path());
+        let db = pos_state.db.clone();
+
+        let delegator_a = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let delegator_b = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let operator = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let mut apply = |delegations: DelegationChanges, slot: Slot| {
+            let changes = PoSChanges {
+                seed_bits: bitvec![u8, Lsb0; 0],
+                delegations,
+                ..Default::default()
+            };
+            let mut batch = DBBatch::new();
+            pos_state
+                .apply_changes_to_batch(changes, slot, false, &mut batch)
+                .unwrap();
+            db.write()
+                .write_batch(batch, Default::default(), Some(slot));
+        };
+
+        // both addresses delegate to the same operator
+        apply(
+            [(delegator_a, Some(operator)), (delegator_b, Some(operator))]
+                .into_iter()
+                .collect(),
+            Slot::new(0, 0),
+        );
+        // the first one undelegates
+        apply([(delegator_a, None)].into_iter().collect(), Slot::new(0, 1));
+        // complete cycle 0 and start cycle 1
+        apply(Default::default(), Slot::new(1, 0));
+        apply(Default::default(), Slot::new(1, 1));
+        apply(Default::default(), Slot::new(2, 0));
+
+        // the delegations are carried over to the new cycle
+        let expected: BTreeMap<Address, Address> = [(delegator_b, operator)].into_iter().collect();
+        assert_eq!(pos_state.get_cycle_delegations(0), expected);
+        assert_eq!(pos_state.get_cycle_delegations(1), expected);
+        assert_eq!(pos_state.get_delegation_operator(&delegator_a), None);
+        assert_eq!(
+            pos_state.get_delegation_operator(&delegator_b),
+            Some(operator)
+        );
+        assert_eq!(
+            pos_state.get_operator_delegators(&operator),
+            vec![delegator_b]
+        );
+        assert!(pos_state.get_operator_delegators(&delegator_b).is_empty());
+
+        let db_read = db.read();
+        for (key, value) in db_read
+            .prefix_iterator_cf(STATE_CF, CYCLE_HISTORY_PREFIX.as_bytes())
+            .take_while(|(key, _)| key.starts_with(CYCLE_HISTORY_PREFIX.as_bytes()))
+        {
+            assert!(pos_state.is_cycle_history_key_value_valid(&key, &value));
+        }
+    }
+
+    #[test]
+    fn test_pos_stats() {
+        // initialize the database and pos_state
+        let tempdir = TempDir::new().expect("cannot create temp directory");
+        let mut pos_state = initialized_test_pos_state(tempdir.path());
+        let db = pos_state.db.clone();
+        assert_eq!(pos_state.get_stats(), PoSStats::default());
+
+        let addr_a = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let addr_b = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let amount = Amount::from_str("10").unwrap();
+        let mut apply = |credits: Vec<(Slot, Address)>, slot: Slot| {
+            let mut deferred_credits = DeferredCredits::new();
+            for (credit_slot, address) in credits {
+                deferred_credits.insert(credit_slot, address, amount);
+            }
+            let changes = PoSChanges {
+                seed_bits: bitvec![u8, Lsb0; 0],
+                deferred_credits,
+                ..Default::default()
+            };
+            let mut batch = DBBatch::new();
+            pos_state
+                .apply_changes_to_batch(changes, slot, false, &mut batch)
+                .unwrap();
+            db.write()
+                .write_batch(batch, Default::default(), Some(slot));
+        };
+
+        // three credits over two slots, then the last slot of cycle 0
+        apply(
+            vec![(Slot::new(5, 0), addr_a), (Slot::new(5, 0), addr_b)],
+            Slot::new(0, 0),
+        );
+        apply(vec![(Slot::new(6, 1), addr_a)], Slot::new(0, 1));
+        apply(vec![], Slot::new(1, 0));
+        apply(vec![], Slot::new(1, 1));
+
+        let stats = pos_state.get_stats();
+        assert_eq!(stats.applied_slot_count, 4);
+        // at least the completion flag and the seed of the cycle are rewritten at each slot
+        assert!(stats.hash_computation_count >= 4 * 4 + 3 * 2);
+        assert_eq!(stats.deferred_credit_count, 3);
+        assert_eq!(stats.deferred_credit_slot_count, 2);
+        assert_eq!(stats.average_credits_per_slot(), 1.5);
+        assert_eq!(stats.deferred_credit_count_history, vec![(0, 3)]);
+    }
+
+    #[test]
+    fn test_slot_credits_alert() {
+        // initialize the database and pos_state
+        let tempdir = TempDir::new().expect("cannot create temp directory");
+        let mut pos_state = initialized_test_pos_state(tempdir.path());
+        let db = pos_state.db.clone();
+
+        let threshold = Amount::from_str("15").unwrap();
+        pos_state
+            .credits_watcher
+            .set_slot_alert_threshold(Some(threshold));
+        let mut alerts = pos_state.credits_watcher.subscribe_slot_alerts();
+
+        let addr_a = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let addr_b = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let credit_slot = Slot::new(5, 0);
+        let mut apply = |address: Address, amount: &str, slot: Slot| {
+            let mut deferred_credits = DeferredCredits::new();
+            deferred_credits.insert(credit_slot, address, Amount::from_str(amount).unwrap());
+            let changes = PoSChanges {
+                seed_bits: bitvec![u8, Lsb0; 0],
+                deferred_credits,
+                ..Default::default()
+            };
+            let mut batch = DBBatch::new();
+            pos_state
+                .apply_changes_to_batch(changes, slot, false, &mut batch)
+                .unwrap();
+            db.write()
+                .write_batch(batch, Default::default(), Some(slot));
+        };
+
+        // below the threshold
+        apply(addr_a, "10", Slot::new(0, 0));
+        assert!(alerts.try_recv().is_err());
+
+        // the second credit pushes the total of the slot above the threshold
+        apply(addr_b, "10", Slot::new(0, 1));
+        assert_eq!(
+            alerts.try_recv().unwrap(),
+            SlotCreditsAlert {
+                slot: Slot::new(0, 1),
+                credit_slot,
+                total: Amount::from_str("20").unwrap(),
+                threshold,
+            }
+        );
+
+        // decreasing a total that is still above the threshold raises no alert
+        apply(addr_b, "8", Slot::new(1, 0));
+        assert!(alerts.try_recv().is_err());
+    }
+
     #[test]
     #[should_panic]
     fn test_feed_selector() {
@@ -2337,7 +2837,7 @@ mod tests {
         selector_controller
             .expect_feed_cycle()
             .times(1)
-            .returning(|_, _, _| Ok(()));
+            .returning(|_, _, _, _| Ok(()));
 
         let pos_config = PoSConfig {
             periods_per_cycle: 2,
@@ -2398,4 +2898,300 @@ mod tests {
         // Note: by using cycle 2, feed_selector will use initial_rolls & initial_seeds
         let _ = pos_state_0.feed_selector(2);
     }
+
+    /// Number of randomized cases of the deferred credits hash properties
+    const HASH_PROPERTY_CASES: u64 = 32;
+
+    /// PoS state with the test configuration over a new database at `path`
+    fn test_pos_state(
+        path: &std::path::Path,
+        initial_deferred_credits_path: Option<std::path::PathBuf>,
+    ) -> PoSFinalState {
+        let pos_config = PoSConfig {
+            periods_per_cycle: 2,
+            thread_count: 2,
+            cycle_history_length: POS_SAVED_CYCLES,
+            max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+            max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+            max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
+            initial_deferred_credits_path,
+        };
+        let db_config = MassaDBConfig {
+            path: path.to_path_buf(),
+            max_history_length: 10,
+            max_final_state_elements_size: 100_000,
+            max_versioning_elements_size: 100_000,
+            thread_count: 2,
+        };
+        let db = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ));
+        let init_seed = Hash::compute_from(b"");
+        PoSFinalState {
+            deferred_credits_deserializer: DeferredCreditsDeserializer::new(
+                pos_config.thread_count,
+                pos_config.max_credit_length,
+            ),
+            cycle_info_deserializer: CycleHistoryDeserializer::new(
+                pos_config.cycle_history_length as u64,
+                pos_config.max_rolls_length,
+                pos_config.max_production_stats_length,
+            ),
+            config: pos_config,
+            db,
+            cycle_history_cache: Default::default(),
+            rng_seed_cache: None,
+            selector: Box::new(MockSelectorController::new()),
+            initial_rolls: Default::default(),
+            initial_seeds: vec![Hash::compute_from(init_seed.to_bytes()), init_seed],
+            deferred_credits_serializer: DeferredCreditsSerializer::new(),
+            cycle_info_serializer: CycleHistorySerializer::new(),
+            credits_watcher: Default::default(),
+            delegation_activation: None,
+            stats: Default::default(),
+        }
+    }
+
+    /// PoS state with the test configuration over a new database at `path`, with its initial cycle written
+    fn initialized_test_pos_state(path: &std::path::Path) -> PoSFinalState {
+        let mut pos_state = test_pos_state(path, None);
+        pos_state.recompute_pos_state_caches();
+        let mut batch = DBBatch::new();
+        pos_state.create_initial_cycle(&mut batch);
+        pos_state
+            .db
+            .write()
+            .write_batch(batch, Default::default(), Some(Slot::new(0, 0)));
+        pos_state
+    }
+
+    /// Write deferred credits entries to the database of `pos_state`, a zero amount removing the entry
+    fn write_deferred_credits_entries(
+        pos_state: &PoSFinalState,
+        entries: &[(Slot, Address, Amount)],
+        change_id: Slot,
+    ) {
+        let mut batch = DBBatch::new();
+        for (slot, address, amount) in entries {
+            pos_state.put_deferred_credits_entry(slot, address, amount, &mut batch);
+        }
+        pos_state
+            .db
+            .write()
+            .write_batch(batch, Default::default(), Some(change_id));
+    }
+
+    /// Address derived from random bytes, much faster to generate than from a key pair
+    fn random_address(rng: &mut StdRng) -> Address {
+        Address::User(UserAddress::UserAddressV0(UserAddressV0(
+            Hash::compute_from(&rng.gen::<[u8; 32]>()),
+        )))
+    }
+
+    /// Random changes of deferred credits over a small set of slots and addresses, so that entries are
+    /// inserted, updated and removed (zero amount) several times.
+    /// The changes of each entry are returned separately, in the order they apply.
+    fn random_deferred_credits_changes(rng: &mut StdRng) -> Vec<Vec<(Slot, Address, Amount)>> {
+        let addresses: Vec<Address> = (0..rng.gen_range(1..6))
+            .map(|_| random_address(rng))
+            .collect();
+        let mut entries = Vec::new();
+        for _ in 0..rng.gen_range(1..12) {
+            let slot = Slot::new(rng.gen_range(0..8), rng.gen_range(0..2));
+            let address = addresses[rng.gen_range(0..addresses.len())];
+            if entries
+                .iter()
+                .any(|changes: &Vec<(Slot, Address, Amount)>| {
+                    changes[0].0 == slot && changes[0].1 == address
+                })
+            {
+                continue;
+            }
+            let changes = (0..rng.gen_range(1..5))
+                .map(|_| {
+                    let amount = match rng.gen_range(0..4) {
+                        0 => Amount::zero(),
+                        1 => Amount::from_raw(u64::MAX),
+                        _ => Amount::from_raw(rng.gen_range(1..1_000_000)),
+                    };
+                    (slot, address, amount)
+                })
+                .collect();
+            entries.push(changes);
+        }
+        entries
+    }
+
+    /// Random interleaving of the changes of each entry, keeping the order of the changes of an entry
+    fn interleave_changes(
+        rng: &mut StdRng,
+        entries: &[Vec<(Slot, Address, Amount)>],
+    ) -> Vec<(Slot, Address, Amount)> {
+        let mut queues: Vec<_> = entries.iter().map(|changes| changes.iter()).collect();
+        let mut order: Vec<usize> = entries
+            .iter()
+            .enumerate()
+            .flat_map(|(index, changes)| std::iter::repeat(index).take(changes.len()))
+            .collect();
+        order.shuffle(rng);
+        order
+            .into_iter()
+            .map(|index| *queues[index].next().unwrap())
+            .collect()
+    }
+
+    // These tests check the properties the XOR hash of the deferred credits relies on:
+    // the hash only depends on the final credits, whatever the order and the batching of the changes,
+    // and removing an entry cancels its insertion.
+    // Any change to the hashing of the state must keep them passing: they are run as a dedicated CI step.
+    #[test]
+    fn test_deferred_credits_hash_order_independence() {
+        for seed in 0..HASH_PROPERTY_CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let entries = random_deferred_credits_changes(&mut rng);
+
+            // final credits, the latest change of each entry
+            let mut expected = DeferredCredits::new();
+            for changes in entries.iter() {
+                let (slot, address, amount) = changes.last().unwrap();
+                expected.insert(*slot, *address, *amount);
+            }
+            expected.remove_zeros();
+
+            // hash of the final credits written from scratch
+            let tempdir = TempDir::new().expect("cannot create temp directory");
+            let scratch_state = test_pos_state(tempdir.path(), None);
+            let final_entries: Vec<_> = expected
+                .credits
+                .iter()
+                .flat_map(|(slot, credits)| {
+                    credits
+                        .iter()
+                        .map(move |(address, amount)| (slot.slot(), *address, *amount))
+                })
+                .collect();
+            write_deferred_credits_entries(&scratch_state, &final_entries, Slot::new(0, 0));
+            let scratch_hash = scratch_state.db.read().get_xof_db_hash();
+
+            // the streaming recomputation from the serialized credits gives the same hash
+            let mut serialized = Vec::new();
+            DeferredCreditsSerializer::new()
+                .serialize(&expected, &mut serialized)
+                .unwrap();
+            assert_eq!(
+                scratch_state
+                    .deferred_credits_deserializer
+                    .compute_hash_streaming(&serialized[..])
+                    .unwrap(),
+                scratch_hash,
+                "streaming hash mismatch for seed {}",
+                seed
+            );
+
+            // the changes applied in random orders and batches give the same credits and hash
+            for _ in 0..3 {
+                let changes = interleave_changes(&mut rng, &entries);
+                let tempdir = TempDir::new().expect("cannot create temp directory");
+                let pos_state = test_pos_state(tempdir.path(), None);
+                let mut remaining = &changes[..];
+                let mut period = 0;
+                while !remaining.is_empty() {
+                    let (batch, rest) = remaining.split_at(rng.gen_range(1..=remaining.len()));
+                    write_deferred_credits_entries(&pos_state, batch, Slot::new(period, 0));
+                    remaining = rest;
+                    period += 1;
+                }
+                assert_eq!(
+                    pos_state.get_deferred_credits_range(.., None),
+                    expected,
+                    "credits mismatch for seed {}",
+                    seed
+                );
+                assert_eq!(
+                    pos_state.db.read().get_xof_db_hash(),
+                    scratch_hash,
+                    "incremental hash mismatch for seed {}",
+                    seed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_deferred_credits_hash_inverses() {
+        for seed in 0..HASH_PROPERTY_CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let tempdir = TempDir::new().expect("cannot create temp directory");
+            let pos_state = test_pos_state(tempdir.path(), None);
+            let empty_hash = pos_state.db.read().get_xof_db_hash();
+
+            let changes = interleave_changes(&mut rng, &random_deferred_credits_changes(&mut rng));
+            write_deferred_credits_entries(&pos_state, &changes, Slot::new(0, 0));
+            let hash = pos_state.db.read().get_xof_db_hash();
+            let credits = pos_state.get_deferred_credits_range(.., None);
+
+            // inserting then removing a new credit restores the hash
+            let slot = Slot::new(rng.gen_range(0..8), rng.gen_range(0..2));
+            let address = random_address(&mut rng);
+            let amount = Amount::from_raw(rng.gen_range(1..1_000_000));
+            write_deferred_credits_entries(&pos_state, &[(slot, address, amount)], Slot::new(1, 0));
+            assert_ne!(pos_state.db.read().get_xof_db_hash(), hash);
+            write_deferred_credits_entries(
+                &pos_state,
+                &[(slot, address, Amount::zero())],
+                Slot::new(2, 0),
+            );
+            assert_eq!(
+                pos_state.db.read().get_xof_db_hash(),
+                hash,
+                "removal did not restore the hash for seed {}",
+                seed
+            );
+
+            // updating a credit then setting it back restores the hash
+            if let Some((credit_slot, address, amount)) = credits
+                .credits
+                .iter()
+                .flat_map(|(slot, credits)| credits.iter().map(move |(a, v)| (*slot, *a, *v)))
+                .next()
+            {
+                let slot = credit_slot.slot();
+                let updated = amount.saturating_add(Amount::from_raw(1));
+                write_deferred_credits_entries(
+                    &pos_state,
+                    &[(slot, address, updated)],
+                    Slot::new(3, 0),
+                );
+                write_deferred_credits_entries(
+                    &pos_state,
+                    &[(slot, address, amount)],
+                    Slot::new(4, 0),
+                );
+                assert_eq!(
+                    pos_state.db.read().get_xof_db_hash(),
+                    hash,
+                    "update did not restore the hash for seed {}",
+                    seed
+                );
+            }
+
+            // removing all the credits gives back the hash of the empty state
+            let removals: Vec<_> = credits
+                .credits
+                .iter()
+                .flat_map(|(slot, credits)| {
+                    credits
+                        .keys()
+                        .map(move |address| (slot.slot(), *address, Amount::zero()))
+                })
+                .collect();
+            write_deferred_credits_entries(&pos_state, &removals, Slot::new(5, 0));
+            assert_eq!(
+                pos_state.db.read().get_xof_db_hash(),
+                empty_hash,
+                "removals did not restore the empty hash for seed {}",
+                seed
+            );
+        }
+    }
 }
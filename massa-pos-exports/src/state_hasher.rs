@@ -0,0 +1,62 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Hash scheme of the PoS entries contributing to the final state hash.
+//!
+//! The final state hash is the XOR of the Blake3 XOF hashes of the database entries,
+//! maintained by the database itself as the entries are written.
+//! `StateHasher` only covers the hashes recomputed outside of the database: the streaming check of
+//! bootstrapped deferred credits (`DeferredCreditsDeserializer::compute_hash_streaming_with`).
+//! It does not select the final state hash, which has a single scheme.
+
+use massa_hash::{HashXof, HASH_XOF_SIZE_BYTES};
+
+/// Incremental hash of a set of `(key, value)` database entries
+pub trait StateHasher {
+    /// Accumulated hash of a set of entries
+    type Output;
+
+    /// Hash of the empty set
+    fn empty(&self) -> Self::Output;
+
+    /// Add the contribution of an entry to an accumulated hash
+    fn add(&self, hash: &mut Self::Output, key: &[u8], value: &[u8]);
+}
+
+/// XOR of the Blake3 XOF hashes of the entries, as computed by the database
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XorBlake3Hasher;
+
+impl StateHasher for XorBlake3Hasher {
+    type Output = HashXof<HASH_XOF_SIZE_BYTES>;
+
+    fn empty(&self) -> Self::Output {
+        HashXof([0u8; HASH_XOF_SIZE_BYTES])
+    }
+
+    fn add(&self, hash: &mut Self::Output, key: &[u8], value: &[u8]) {
+        *hash ^= HashXof::compute_from_tuple(&[key, value]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xor_blake3_hasher() {
+        let hasher = XorBlake3Hasher;
+        let mut hash = hasher.empty();
+        hasher.add(&mut hash, b"key_1", b"value_1");
+        assert_eq!(
+            hash,
+            HashXof::compute_from_tuple(&[b"key_1".as_slice(), b"value_1".as_slice()])
+        );
+
+        // the entries are combined independently of their order
+        hasher.add(&mut hash, b"key_2", b"value_2");
+        let mut reversed = hasher.empty();
+        hasher.add(&mut reversed, b"key_2", b"value_2");
+        hasher.add(&mut reversed, b"key_1", b"value_1");
+        assert_eq!(hash, reversed);
+    }
+}
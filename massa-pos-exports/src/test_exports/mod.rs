@@ -3,6 +3,8 @@
 
 mod bootstrap;
 mod config;
+mod stress;
 
 pub use bootstrap::*;
 pub use config::*;
+pub use stress::*;
@@ -0,0 +1,36 @@
+//! Synthetic deferred credits, for the stress tests and benchmarks of the deferred credits
+
+use massa_hash::Hash;
+use massa_models::{
+    address::{Address, UserAddress, UserAddressV0},
+    amount::Amount,
+    config::THREAD_COUNT,
+    slot::Slot,
+};
+
+/// Generate `credit_count` credits spread over the first `slot_count` slots, each credit to a distinct address.
+/// The credits are generated in slot order, the same ones at each call.
+pub fn generate_deferred_credits(
+    credit_count: u64,
+    slot_count: u64,
+) -> Vec<(Slot, Address, Amount)> {
+    let slot_count = slot_count.max(1);
+    let credits_per_slot = credit_count.div_ceil(slot_count);
+    (0..credit_count)
+        .map(|index| {
+            let slot_index = index / credits_per_slot;
+            let slot = Slot::new(
+                slot_index / THREAD_COUNT as u64,
+                (slot_index % THREAD_COUNT as u64) as u8,
+            );
+            (slot, synthetic_address(index), Amount::from_raw(index + 1))
+        })
+        .collect()
+}
+
+/// Address derived from an index, much faster to generate than from a key pair
+pub fn synthetic_address(index: u64) -> Address {
+    Address::User(UserAddress::UserAddressV0(UserAddressV0(
+        Hash::compute_from(&index.to_be_bytes()),
+    )))
+}
@@ -8,7 +8,9 @@ use std::collections::BTreeMap;
 use crate::{Command, DrawCachePtr};
 use massa_hash::Hash;
 use massa_models::{address::Address, prehash::PreHashSet, slot::Slot};
-use massa_pos_exports::{PosError, PosResult, Selection, SelectorController, SelectorManager};
+use massa_pos_exports::{
+    OperatorRolls, PosError, PosResult, Selection, SelectorController, SelectorManager,
+};
 #[cfg(feature = "test-exports")]
 use std::collections::{HashMap, VecDeque};
 use std::sync::mpsc::SyncSender;
@@ -54,6 +56,7 @@ impl SelectorController for SelectorControllerImpl {
     /// # Arguments
     /// * `cycle`: cycle number to be drawn
     /// * `lookback_rolls`: look back rolls used for the draw (cycle - 3)
+    /// * `lookback_delegations`: operator of each delegator at look back (cycle - 3)
     /// * `lookback_seed`: look back seed hash for the draw (cycle - 2)
 
     /// * This a non-blocking function where the worker is separate,
@@ -66,6 +69,7 @@ impl SelectorController for SelectorControllerImpl {
         &self,
         cycle: u64,
         lookback_rolls: BTreeMap<Address, u64>,
+        lookback_delegations: BTreeMap<Address, Address>,
         lookback_seed: Hash,
     ) -> PosResult<()> {
         // check status
@@ -79,6 +83,7 @@ impl SelectorController for SelectorControllerImpl {
             .send(Command::DrawInput {
                 cycle,
                 lookback_rolls,
+                lookback_delegations,
                 lookback_seed,
             })
             .map_err(|_err| {
@@ -109,6 +114,40 @@ impl SelectorController for SelectorControllerImpl {
         self.get_selection(slot).map(|selection| selection.producer)
     }
 
+    /// Get the rolls an operator was drawn with during a cycle,
+    /// `None` if no rolls were delegated to it.
+    /// Waits for the draws of the cycle if they are not performed yet.
+    fn get_operator_rolls(
+        &self,
+        cycle: u64,
+        operator: &Address,
+    ) -> PosResult<Option<OperatorRolls>> {
+        self.wait_for_draws(cycle)?;
+        let (_cache_cv, cache_lock) = &*self.cache;
+        let cache_guard = cache_lock.read();
+        let cache = cache_guard.as_ref().map_err(|err| err.clone())?;
+        cache
+            .get(cycle)
+            .map(|cycle_draws| cycle_draws.delegations.operators.get(operator).cloned())
+            .ok_or(PosError::CycleUnavailable(cycle))
+    }
+
+    /// Get the operator the rolls of a delegator were drawn for during a cycle,
+    /// `None` if they were not delegated
+    fn get_delegation_operator(
+        &self,
+        cycle: u64,
+        delegator: &Address,
+    ) -> PosResult<Option<Address>> {
+        let (_cache_cv, cache_lock) = &*self.cache;
+        let cache_guard = cache_lock.read();
+        let cache = cache_guard.as_ref().map_err(|err| err.clone())?;
+        cache
+            .get(cycle)
+            .map(|cycle_draws| cycle_draws.delegations.delegators.get(delegator).copied())
+            .ok_or(PosError::CycleUnavailable(cycle))
+    }
+
     /// Get selections computed for a slot range (only lists available selections):
     /// # Arguments
     /// * `slot_range`: target slot of the selection (from included, to included)
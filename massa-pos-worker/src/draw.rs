@@ -1,7 +1,7 @@
 use crate::CycleDraws;
 use massa_hash::Hash;
 use massa_models::{address::Address, slot::Slot};
-use massa_pos_exports::{PosError, PosResult, Selection, SelectorConfig};
+use massa_pos_exports::{CycleDelegations, PosError, PosResult, Selection, SelectorConfig};
 use rand::{distributions::Distribution, SeedableRng};
 use rand_distr::WeightedAliasIndex;
 use rand_xoshiro::Xoshiro256PlusPlus;
@@ -17,6 +17,8 @@ use tracing::debug;
 /// # Parameters
 /// * `cycle`: Cycle to draw
 /// * `lookback_rolls`: Roll counts at look back (`cycle-3`)
+/// * `lookback_delegations`: Operator of each delegator at look back (`cycle-3`),
+///   the rolls of the delegators are counted for their operators
 /// * `lookback_seed`: RNG seed at look back (`cycle-2`)
 ///
 /// # Result
@@ -29,12 +31,16 @@ pub(crate) fn perform_draws(
     cfg: &SelectorConfig,
     cycle: u64,
     lookback_rolls: BTreeMap<Address, u64>,
+    lookback_delegations: &BTreeMap<Address, Address>,
     lookback_seed: Hash,
 ) -> PosResult<CycleDraws> {
     // get seeded RNG
     let mut rng = Xoshiro256PlusPlus::from_seed(*lookback_seed.to_bytes());
 
-    let (addresses, roll_counts): (Vec<_>, Vec<_>) = lookback_rolls.into_iter().unzip();
+    // draw the operators with the rolls delegated to them
+    let delegations = CycleDelegations::new(&lookback_rolls, lookback_delegations);
+    let (addresses, roll_counts): (Vec<_>, Vec<_>) =
+        delegations.draw_rolls(lookback_rolls).into_iter().unzip();
 
     // prepare distribution
     let dist = WeightedAliasIndex::new(roll_counts).map_err(|err| {
@@ -57,6 +63,7 @@ pub(crate) fn perform_draws(
         draws: HashMap::with_capacity(
             (cfg.periods_per_cycle as usize) * (cfg.thread_count as usize),
         ),
+        delegations,
     };
 
     let mut five_first_slots: Vec<(Slot, Selection)> = Vec::new();
@@ -9,7 +9,7 @@ mod worker;
 
 use massa_hash::Hash;
 use massa_models::{address::Address, slot::Slot};
-use massa_pos_exports::{PosResult, Selection};
+use massa_pos_exports::{CycleDelegations, PosResult, Selection};
 
 use parking_lot::{Condvar, Mutex, RwLock, RwLockReadGuard};
 use std::{
@@ -24,6 +24,7 @@ pub(crate) enum Command {
     DrawInput {
         cycle: u64,
         lookback_rolls: BTreeMap<Address, u64>,
+        lookback_delegations: BTreeMap<Address, Address>,
         lookback_seed: Hash,
     },
     /// Stop the thread (usually sent by the manager and pushed at the top
@@ -83,6 +84,8 @@ pub(crate) struct CycleDraws {
     pub cycle: u64,
     /// cache of draws
     pub draws: HashMap<Slot, Selection>,
+    /// delegations the draws were weighted with
+    pub delegations: CycleDelegations,
 }
 
 /// Structure of the shared pointer to the computed draws, or error if the draw system failed.
@@ -1,13 +1,15 @@
+use massa_channel::event_bus::MassaEventBus;
 use massa_hash::Hash;
 use massa_models::address::Address;
 use massa_models::config::PERIODS_PER_CYCLE;
 use massa_models::config::THREAD_COUNT;
+use massa_models::node_event::NodeEvent;
 use massa_models::slot::Slot;
 use massa_pos_exports::PosError;
 use massa_pos_exports::SelectorConfig;
 use rand::thread_rng;
 use rand::RngCore;
-use std::{collections::BTreeMap, str::FromStr};
+use std::{collections::BTreeMap, str::FromStr, time::Duration};
 
 use crate::start_selector_worker;
 
@@ -29,17 +31,23 @@ fn test_standalone_selection() {
     let lookback_seed = Hash::compute_from(&seed_bytes);
 
     // start the selector thread, get the controller and manager
-    let (mut manager, controller) = start_selector_worker(cfg).unwrap();
+    let event_bus = MassaEventBus::new("test_node_event".to_string());
+    let node_events = event_bus.subscribe("test".to_string(), 16);
+    let (mut manager, controller) = start_selector_worker(cfg, event_bus).unwrap();
 
     // feed the information used to compute the draws of a new cycle
     // this is supposed to take the rolls from C-3 and the seed from C-2
     // here we compute cycle 0 with dummy rolls and a random seed
     controller
-        .feed_cycle(0, lookback_rolls, lookback_seed)
+        .feed_cycle(0, lookback_rolls, BTreeMap::new(), lookback_seed)
         .unwrap();
 
     // wait for the draws to compute
     controller.wait_for_draws(0).unwrap();
+    assert_eq!(
+        node_events.recv_timeout(Duration::from_secs(5)).unwrap(),
+        NodeEvent::DrawsComputed { cycle: 0 }
+    );
 
     // get the draws of 2 slots
     let two_slot_selection = controller
@@ -93,12 +101,13 @@ fn test_invalid_roll_distribution() {
     let lookback_seed = Hash::compute_from(&seed_bytes);
 
     // start the selector thread, get the controller and manager
-    let (mut manager, controller) = start_selector_worker(cfg).unwrap();
+    let (mut manager, controller) =
+        start_selector_worker(cfg, MassaEventBus::new("test_node_event".to_string())).unwrap();
 
     // feed lookback_rolls with invalid roll distribution
     // everything is set to 0
     controller
-        .feed_cycle(0, lookback_rolls, lookback_seed)
+        .feed_cycle(0, lookback_rolls, BTreeMap::new(), lookback_seed)
         .unwrap();
 
     // wait for the draws to compute
@@ -110,3 +119,58 @@ fn test_invalid_roll_distribution() {
     // stop worker
     manager.stop();
 }
+
+#[test]
+fn test_delegated_selection() {
+    // initialize the selector configuration and the test inputs
+    let cfg = SelectorConfig::default();
+    let delegator =
+        Address::from_str("AU12Cyu2f7C7isA3ADAhoNuq9ZUFPKP24jmiGj3sh9D1pHoAWKDYY").unwrap();
+    let operator =
+        Address::from_str("AU12BTfZ7k1z6PsLEUZeHYNirz6WJ3NdrWto9H4TkVpkV9xE2TJg2").unwrap();
+    let mut lookback_rolls: BTreeMap<Address, u64> = BTreeMap::new();
+    lookback_rolls.insert(delegator, 3);
+    let mut lookback_delegations: BTreeMap<Address, Address> = BTreeMap::new();
+    lookback_delegations.insert(delegator, operator);
+    let mut seed_bytes = [0u8; 16];
+    thread_rng().fill_bytes(&mut seed_bytes);
+    let lookback_seed = Hash::compute_from(&seed_bytes);
+
+    // start the selector thread, get the controller and manager
+    let (mut manager, controller) =
+        start_selector_worker(cfg, MassaEventBus::new("test_node_event".to_string())).unwrap();
+    controller
+        .feed_cycle(0, lookback_rolls, lookback_delegations, lookback_seed)
+        .unwrap();
+    controller.wait_for_draws(0).unwrap();
+
+    // the operator is drawn with the rolls of the delegator, which is never drawn itself
+    let selection = controller
+        .get_selection(Slot {
+            period: 1,
+            thread: 0,
+        })
+        .unwrap();
+    assert_eq!(selection.producer, operator);
+    assert!(selection.endorsements.iter().all(|addr| *addr == operator));
+
+    // the registry of the cycle can be queried
+    let operator_rolls = controller
+        .get_operator_rolls(0, &operator)
+        .unwrap()
+        .unwrap();
+    assert_eq!(operator_rolls.own_rolls, 0);
+    assert_eq!(operator_rolls.total_rolls(), 3);
+    assert_eq!(
+        controller.get_delegation_operator(0, &delegator).unwrap(),
+        Some(operator)
+    );
+    assert_eq!(
+        controller.get_delegation_operator(0, &operator).unwrap(),
+        None
+    );
+    assert_eq!(controller.get_operator_rolls(0, &delegator).unwrap(), None);
+
+    // stop worker
+    manager.stop();
+}
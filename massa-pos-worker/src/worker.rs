@@ -7,6 +7,8 @@ use crate::CycleDraws;
 use crate::DrawCache;
 use crate::RwLockCondvar;
 use crate::{Command, DrawCachePtr};
+use massa_channel::event_bus::MassaEventBus;
+use massa_models::node_event::NodeEvent;
 use massa_pos_exports::PosError;
 use massa_pos_exports::PosResult;
 use massa_pos_exports::SelectorConfig;
@@ -29,6 +31,8 @@ pub(crate) struct SelectorThread {
     pub(crate) cache: DrawCachePtr,
     /// Configuration
     pub(crate) cfg: SelectorConfig,
+    /// Bus used to publish the cycles whose draws were computed
+    pub(crate) event_bus: MassaEventBus<NodeEvent>,
 }
 
 impl SelectorThread {
@@ -38,6 +42,7 @@ impl SelectorThread {
         input_mpsc: Receiver<Command>,
         cache: DrawCachePtr,
         cfg: SelectorConfig,
+        event_bus: MassaEventBus<NodeEvent>,
     ) -> JoinHandle<PosResult<()>> {
         let thread_builder = thread::Builder::new().name("selector".into());
         thread_builder
@@ -46,6 +51,7 @@ impl SelectorThread {
                     input_mpsc,
                     cache,
                     cfg,
+                    event_bus,
                 };
                 this.run()
             })
@@ -112,6 +118,7 @@ impl SelectorThread {
             let Ok(Command::DrawInput {
                 cycle,
                 lookback_rolls,
+                lookback_delegations,
                 lookback_seed,
             }) = self.input_mpsc.recv()
             else {
@@ -119,10 +126,17 @@ impl SelectorThread {
             };
 
             // perform draws
-            let draws_result = perform_draws(&self.cfg, cycle, lookback_rolls, lookback_seed);
+            let draws_result = perform_draws(
+                &self.cfg,
+                cycle,
+                lookback_rolls,
+                &lookback_delegations,
+                lookback_seed,
+            );
 
             // add result to cache and notify waiters
             self.process_draws_result(cycle, draws_result)?;
+            self.event_bus.publish(NodeEvent::DrawsComputed { cycle });
         }
         Ok(())
     }
@@ -131,7 +145,8 @@ impl SelectorThread {
 /// Launches a selector worker thread and returns a pair to interact with it.
 ///
 /// # parameters
-/// * none
+/// * `selector_config`: configuration of the selector
+/// * `event_bus`: bus on which the cycles whose draws were computed are published
 ///
 /// # Returns
 /// A pair `(selector_manager, selector_controller)` where:
@@ -139,6 +154,7 @@ impl SelectorThread {
 /// * `selector_controller`: allows sending requests and notifications to the worker
 pub fn start_selector_worker(
     selector_config: SelectorConfig,
+    event_bus: MassaEventBus<NodeEvent>,
 ) -> PosResult<(Box<dyn SelectorManager>, Box<dyn SelectorController>)> {
     let (input_sender, input_receiver) = sync_channel(selector_config.channel_size);
     let cache = Arc::new((
@@ -155,7 +171,7 @@ pub fn start_selector_worker(
     };
 
     // launch the selector thread
-    let thread_handle = SelectorThread::spawn(input_receiver, cache, selector_config);
+    let thread_handle = SelectorThread::spawn(input_receiver, cache, selector_config, event_bus);
 
     let manager = SelectorManagerImpl {
         thread_handle: Some(thread_handle),
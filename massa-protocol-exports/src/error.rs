@@ -61,6 +61,8 @@ pub enum ProtocolError {
         /// received announced network version
         announced_received: u32,
     },
+    /// Header extensions are not enabled by the network version {0} active at the slot of the header
+    HeaderExtensionsNotEnabled(u32),
     /// Versioned factory error: {0}
     FactoryError(#[from] FactoryError),
     /// PoS error: {0}
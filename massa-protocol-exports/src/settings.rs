@@ -36,6 +36,8 @@ pub struct ProtocolConfig {
     pub max_block_propagation_time: MassaTime,
     /// Block propagation tick interval, useful for propagating blocks quickly to newly connected peers.
     pub block_propagation_tick: MassaTime,
+    /// Interval between two sends of the local blockclique summary to the peers, used to detect blockclique divergences
+    pub blockclique_summary_gossip_interval: MassaTime,
     /// max known blocks of current nodes we keep in memory
     pub max_known_blocks_size: usize,
     /// max known blocks of foreign nodes we keep in memory (by node)
@@ -52,6 +54,8 @@ pub struct ProtocolConfig {
     pub max_node_known_endorsements_size: usize,
     /// we ask for the same block `max_simultaneous_ask_blocks_per_node` times at the same time
     pub max_simultaneous_ask_blocks_per_node: usize,
+    /// replaces `max_simultaneous_ask_blocks_per_node` while consensus is in catch-up mode
+    pub catch_up_max_simultaneous_ask_blocks_per_node: usize,
     /// Max wait time for sending a Network or Node event.
     pub max_send_wait: MassaTime,
     /// Maximum number of batches in the memory buffer.
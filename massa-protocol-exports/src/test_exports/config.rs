@@ -18,10 +18,12 @@ impl Default for ProtocolConfig {
             max_blocks_kept_for_propagation: 300,
             max_block_propagation_time: MassaTime::from_millis(40000),
             block_propagation_tick: MassaTime::from_millis(1000),
+            blockclique_summary_gossip_interval: MassaTime::from_millis(10000),
             max_known_blocks_size: 100,
             max_node_known_blocks_size: 100,
             max_node_wanted_blocks_size: 100,
             max_simultaneous_ask_blocks_per_node: 10,
+            catch_up_max_simultaneous_ask_blocks_per_node: 40,
             max_send_wait: MassaTime::from_millis(100),
             max_known_ops_size: 1000,
             max_node_known_ops_size: 1000,
@@ -36,6 +36,7 @@ pub fn create_block(keypair: &KeyPair) -> SecureShareBlock {
             operation_merkle_root: Hash::compute_from(&Vec::new()),
             endorsements: Vec::new(),
             denunciations: Vec::new(),
+            extensions: Vec::new(),
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -82,6 +83,7 @@ pub fn create_block_with_operations(
             operation_merkle_root,
             endorsements: Vec::new(),
             denunciations: Vec::new(),
+            extensions: Vec::new(),
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -124,6 +126,7 @@ pub fn create_block_with_endorsements(
             operation_merkle_root: Hash::compute_from(&Vec::new()),
             endorsements,
             denunciations: Vec::new(),
+            extensions: Vec::new(),
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -6,17 +6,19 @@ use massa_models::{
         SecureShareOperation,
     },
     secure_share::{SecureShareDeserializer, SecureShareSerializer},
+    slot::{Slot, SlotDeserializer, SlotSerializer},
 };
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
 use nom::{
     error::{context, ContextError, ParseError},
+    multi::length_count,
     sequence::tuple,
     IResult, Parser,
 };
 use num_enum::{IntoPrimitive, TryFromPrimitive};
-use std::ops::Bound::Included;
+use std::ops::Bound::{Excluded, Included};
 
 /// Request block data
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -64,6 +66,13 @@ pub enum BlockMessage {
         /// Block info reply.
         block_info: BlockInfoReply,
     },
+    /// Summary of the blockclique of the sender, used to detect blockclique divergences
+    CliqueSummary {
+        /// highest slot among the latest final blocks of all threads
+        final_slot: Slot,
+        /// tips of the blockclique: the best parent of each thread
+        blockclique_tips: Vec<BlockId>,
+    },
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -72,6 +81,7 @@ pub enum MessageTypeId {
     Header,
     DataRequest,
     DataResponse,
+    CliqueSummary,
 }
 
 impl From<&BlockMessage> for MessageTypeId {
@@ -80,6 +90,7 @@ impl From<&BlockMessage> for MessageTypeId {
             BlockMessage::Header(_) => MessageTypeId::Header,
             BlockMessage::DataRequest { .. } => MessageTypeId::DataRequest,
             BlockMessage::DataResponse { .. } => MessageTypeId::DataResponse,
+            BlockMessage::CliqueSummary { .. } => MessageTypeId::CliqueSummary,
         }
     }
 }
@@ -100,6 +111,7 @@ pub struct BlockMessageSerializer {
     length_serializer: U64VarIntSerializer,
     block_id_serializer: BlockIdSerializer,
     operation_id_serializer: OperationIdSerializer,
+    slot_serializer: SlotSerializer,
 }
 
 impl BlockMessageSerializer {
@@ -110,6 +122,7 @@ impl BlockMessageSerializer {
             length_serializer: U64VarIntSerializer::new(),
             block_id_serializer: BlockIdSerializer::new(),
             operation_id_serializer: OperationIdSerializer::new(),
+            slot_serializer: SlotSerializer::new(),
         }
     }
 }
@@ -192,6 +205,17 @@ impl Serializer<BlockMessage> for BlockMessageSerializer {
                     }
                 }
             }
+            BlockMessage::CliqueSummary {
+                final_slot,
+                blockclique_tips,
+            } => {
+                self.slot_serializer.serialize(final_slot, buffer)?;
+                self.length_serializer
+                    .serialize(&(blockclique_tips.len() as u64), buffer)?;
+                for block_id in blockclique_tips {
+                    self.block_id_serializer.serialize(block_id, buffer)?;
+                }
+            }
         }
         Ok(())
     }
@@ -203,6 +227,8 @@ pub struct BlockMessageDeserializer {
     block_id_deserializer: BlockIdDeserializer,
     operation_ids_deserializer: OperationIdsDeserializer,
     operations_deserializer: OperationsDeserializer,
+    slot_deserializer: SlotDeserializer,
+    tip_count_deserializer: U64VarIntDeserializer,
 }
 
 pub struct BlockMessageDeserializerArgs {
@@ -248,6 +274,14 @@ impl BlockMessageDeserializer {
                 args.max_op_datastore_value_length,
                 args.chain_id,
             ),
+            slot_deserializer: SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(args.thread_count)),
+            ),
+            tip_count_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(args.thread_count as u64),
+            ),
         }
     }
 }
@@ -356,6 +390,32 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                     block_info,
                 })
                 .parse(buffer),
+                MessageTypeId::CliqueSummary => context(
+                    "Failed CliqueSummary deserialization",
+                    tuple((
+                        context("Failed final_slot deserialization", |input| {
+                            self.slot_deserializer.deserialize(input)
+                        }),
+                        context(
+                            "Failed blockclique_tips deserialization",
+                            length_count(
+                                context("Failed length deserialization", |input| {
+                                    self.tip_count_deserializer.deserialize(input)
+                                }),
+                                context("Failed BlockId deserialization", |input| {
+                                    self.block_id_deserializer.deserialize(input)
+                                }),
+                            ),
+                        ),
+                    )),
+                )
+                .map(
+                    |(final_slot, blockclique_tips)| BlockMessage::CliqueSummary {
+                        final_slot,
+                        blockclique_tips,
+                    },
+                )
+                .parse(buffer),
             }
         })
         .parse(buffer)
@@ -367,7 +427,7 @@ mod tests {
     use std::str::FromStr;
 
     use massa_models::config::CHAINID;
-    use massa_models::{block_id::BlockId, operation::OperationId};
+    use massa_models::{block_id::BlockId, operation::OperationId, slot::Slot};
     use massa_serialization::{DeserializeError, Deserializer, Serializer};
 
     #[test]
@@ -521,4 +581,52 @@ mod tests {
             _ => panic!("Wrong message type"),
         }
     }
+
+    #[test]
+    fn test_clique_summary_message() {
+        let block_id =
+            BlockId::from_str("B12DvrcQkzF1Wi8BVoNfc4n93CD3E2qhCNe7nVhnEQGWHZ24fEmg").unwrap();
+        let message = super::BlockMessage::CliqueSummary {
+            final_slot: Slot::new(10, 1),
+            blockclique_tips: vec![block_id, block_id],
+        };
+        let mut buffer = Vec::new();
+        let serializer = super::BlockMessageSerializer::new();
+        serializer.serialize(&message, &mut buffer).unwrap();
+        let deserializer_args = |thread_count| super::BlockMessageDeserializerArgs {
+            thread_count,
+            endorsement_count: 1,
+            max_operations_per_block: 1,
+            max_datastore_value_length: 1,
+            max_function_name_length: 1,
+            max_parameters_size: 1,
+            max_op_datastore_entry_count: 1,
+            max_op_datastore_key_length: 1,
+            max_op_datastore_value_length: 1,
+            max_denunciations_in_block_header: 1,
+            last_start_period: None,
+            chain_id: *CHAINID,
+        };
+        let deserializer = super::BlockMessageDeserializer::new(deserializer_args(2));
+        let (rest, deserialized_message) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        match deserialized_message {
+            super::BlockMessage::CliqueSummary {
+                final_slot,
+                blockclique_tips,
+            } => {
+                assert_eq!(final_slot, Slot::new(10, 1));
+                assert_eq!(blockclique_tips, vec![block_id, block_id]);
+            }
+            _ => panic!("Wrong message type"),
+        }
+
+        // a summary can not hold more tips than threads, nor a slot out of the threads
+        let deserializer = super::BlockMessageDeserializer::new(deserializer_args(1));
+        deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .expect_err("Should raise error because there are two tips and only 1 thread");
+    }
 }
@@ -24,7 +24,9 @@ use crossbeam::{
     select,
 };
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
-use massa_consensus_exports::ConsensusController;
+use massa_consensus_exports::{
+    block_provenance::BlockOrigin, divergence::BlockcliqueSummary, ConsensusController,
+};
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::{
@@ -46,7 +48,7 @@ use massa_protocol_exports::{ProtocolConfig, ProtocolError};
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_storage::Storage;
 use massa_time::TimeError;
-use massa_versioning::versioning::MipStore;
+use massa_versioning::versioning::{MipComponent, MipStore};
 use rand::thread_rng;
 use rand::{seq::SliceRandom, Rng};
 use tracing::{debug, info, warn};
@@ -74,6 +76,8 @@ pub(crate) struct BlockInfo {
     pub(crate) header: Option<SecuredHeader>,
     /// Operations ids. None if not received yet
     pub(crate) operation_ids: Option<Vec<OperationId>>,
+    /// Peer that sent the operation ids, reported to consensus as the origin of the block
+    pub(crate) operation_ids_source: Option<PeerId>,
     /// Operations and endorsements contained in the block,
     /// if we've received them already, and none otherwise.
     pub(crate) storage: Storage,
@@ -84,6 +88,7 @@ impl BlockInfo {
         BlockInfo {
             header,
             operation_ids: None,
+            operation_ids_source: None,
             storage,
         }
     }
@@ -133,6 +138,11 @@ impl RetrievalThread {
             });
 
         let tick_update_metrics = tick(self.massa_metrics.tick_delay);
+        let tick_clique_summary = tick(
+            self.config
+                .blockclique_summary_gossip_interval
+                .to_duration(),
+        );
         loop {
             select! {
                 recv(self.receiver_network) -> msg => {
@@ -163,6 +173,12 @@ impl RetrievalThread {
                                     self.on_block_header_received(peer_id, header);
                                     self.update_block_retrieval();
                                 }
+                                BlockMessage::CliqueSummary{final_slot, blockclique_tips} => {
+                                    self.consensus_controller.register_peer_blockclique_summary(
+                                        peer_id,
+                                        BlockcliqueSummary { final_slot, blockclique_tips },
+                                    );
+                                }
                             }
                         },
                         Err(_) => {
@@ -233,6 +249,9 @@ impl RetrievalThread {
                         );
                     }
                 }
+                recv(tick_clique_summary) -> _ => {
+                    self.gossip_blockclique_summary();
+                }
                 recv(at(self.next_timer_ask_block)) -> _ => {
                     self.update_block_retrieval();
                 }
@@ -240,6 +259,32 @@ impl RetrievalThread {
         }
     }
 
+    /// Send the summary of the local blockclique to all the connected peers
+    /// so that they can detect whether their blockclique diverges from ours
+    fn gossip_blockclique_summary(&mut self) {
+        let BlockcliqueSummary {
+            final_slot,
+            blockclique_tips,
+        } = self.consensus_controller.get_blockclique_summary();
+        for peer_id in self.active_connections.get_peer_ids_connected() {
+            if let Err(err) = self.active_connections.send_to_peer(
+                &peer_id,
+                &self.block_message_serializer,
+                BlockMessage::CliqueSummary {
+                    final_slot,
+                    blockclique_tips: blockclique_tips.clone(),
+                }
+                .into(),
+                false,
+            ) {
+                debug!(
+                    "Error while sending blockclique summary to {}: {:?}",
+                    peer_id, err
+                );
+            }
+        }
+    }
+
     /// A remote node asked the local node for block data
     ///
     /// We send the block's operation ids if the foreign node asked for `AskForBlockInfo::Info`
@@ -459,8 +504,11 @@ impl RetrievalThread {
             }
         } else if is_new {
             // if not in wishlist, and if the header is new, we send it to consensus
-            self.consensus_controller
-                .register_block_header(block_id, header);
+            self.consensus_controller.register_block_header(
+                block_id,
+                header,
+                BlockOrigin::Peer(from_peer_id),
+            );
         }
     }
 
@@ -478,8 +526,9 @@ impl RetrievalThread {
         )?;
 
         let current_version = self.mip_store.get_network_version_active_at(timestamp);
-        if header.content.current_version != current_version {
-            // Received a block version different from current version given by mip store
+        // the headers of versions not activated locally yet are quarantined by consensus until they are
+        if header.content.current_version < current_version {
+            // Received a block version older than the current version given by mip store
             return Err(ProtocolError::IncompatibleNetworkVersion {
                 local: current_version,
                 received: header.content.current_version,
@@ -496,6 +545,16 @@ impl RetrievalThread {
             }
         }
 
+        // the extension section is only accepted once a network version enabling it is active
+        if !header.content.extensions.is_empty()
+            && self
+                .mip_store
+                .get_latest_component_version_at(&MipComponent::BlockHeaderExtensions, timestamp)
+                == 0
+        {
+            return Err(ProtocolError::HeaderExtensionsNotEnabled(current_version));
+        }
+
         Ok(())
     }
 
@@ -780,6 +839,7 @@ impl RetrievalThread {
 
         // Save the received operation ID list to the wishlist
         wishlist_info.operation_ids = Some(operation_ids);
+        wishlist_info.operation_ids_source = Some(from_peer_id);
 
         // free up all the nodes that we asked for that operation list
         self.remove_asked_blocks(&[block_id].into_iter().collect());
@@ -898,6 +958,42 @@ impl RetrievalThread {
             return;
         }
 
+        // a block including an operation whose type is not enabled yet at its slot is invalid
+        let block_slot = wishlist_info
+            .header
+            .as_ref()
+            .expect("header presence in wishlist should have been checked above")
+            .content
+            .slot;
+        match get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            block_slot,
+        ) {
+            Ok(slot_timestamp) => {
+                if let Some(op) = operations.values().find(|op| {
+                    !self
+                        .mip_store
+                        .is_operation_type_active_at(&op.content.op, slot_timestamp)
+                }) {
+                    warn!(
+                        "Peer id {} sent us operation {} for block id {} but its type is not enabled yet at slot {}",
+                        from_peer_id, op.id, block_id, block_slot
+                    );
+                    self.mark_block_as_invalid(&block_id);
+                    return;
+                }
+            }
+            Err(err) => {
+                warn!(
+                    "could not compute the timestamp of the slot of block id {}: {}",
+                    block_id, err
+                );
+                return;
+            }
+        }
+
         // add received operations to local storage and claim ref
         wishlist_info
             .storage
@@ -943,6 +1039,21 @@ impl RetrievalThread {
             return;
         }
 
+        // Do not ask for the blocks whose body consensus already holds, whatever their status.
+        let held_blocks: Vec<BlockId> = self
+            .block_wishlist
+            .keys()
+            .filter(|block_id| {
+                self.consensus_controller
+                    .has_block(**block_id)
+                    .is_body_known()
+            })
+            .copied()
+            .collect();
+        for block_id in held_blocks.iter() {
+            self.block_wishlist.remove(block_id);
+        }
+
         // Get connected peer list
         let connected_peers = self.active_connections.get_peer_ids_connected();
 
@@ -1001,6 +1112,13 @@ impl RetrievalThread {
             }
         }
 
+        // the peers are asked for more blocks at once while consensus catches up with the network
+        let max_asks_per_peer = if self.consensus_controller.get_catch_up_status().active {
+            self.config.catch_up_max_simultaneous_ask_blocks_per_node
+        } else {
+            self.config.max_simultaneous_ask_blocks_per_node
+        };
+
         // for each block to ask, choose a peer to ask it from and perform the ask
         let mut to_ask = to_ask.into_iter().collect::<Vec<_>>();
         to_ask.shuffle(&mut thread_rng()); // shuffle ask order
@@ -1011,7 +1129,7 @@ impl RetrievalThread {
                 .filter_map(|peer_id| {
                     // Get the peer load. Look for the minimum score for asking.
                     let peer_load = peer_loads.get(peer_id).copied().unwrap_or_default();
-                    if peer_load >= self.config.max_simultaneous_ask_blocks_per_node {
+                    if peer_load >= max_asks_per_peer {
                         // this peer is already loaded with too many asks
                         return None;
                     }
@@ -1236,6 +1354,11 @@ impl RetrievalThread {
             serialized_data: content_serialized,
         };
 
+        let origin = match wishlist_info.operation_ids_source {
+            Some(peer_id) => BlockOrigin::Peer(peer_id),
+            None => BlockOrigin::Unknown,
+        };
+
         // Get block storage.
         // It should contain only the operations.
         let mut block_storage = wishlist_info.storage;
@@ -1252,7 +1375,7 @@ impl RetrievalThread {
 
         // Send to consensus
         self.consensus_controller
-            .register_block(*block_id, slot, block_storage, false);
+            .register_block(*block_id, slot, block_storage, origin);
 
         // Remove from asked block history as it is not useful anymore
         self.remove_asked_blocks(&vec![*block_id].into_iter().collect());
@@ -10,7 +10,9 @@ mod worker;
 mod wrap_network;
 mod wrap_peer_db;
 
-pub use worker::{create_protocol_controller, start_protocol_controller};
+pub use worker::{
+    create_protocol_controller, load_or_create_node_keypair, start_protocol_controller,
+};
 
 #[cfg(test)]
 mod tests;
@@ -4,6 +4,7 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
+use massa_consensus_exports::submission::{SubmissionOutcome, SubmissionTicket};
 use massa_models::config::CHAINID;
 use massa_models::{block_id::BlockId, prehash::PreHashSet, slot::Slot};
 use massa_protocol_exports::PeerId;
@@ -110,9 +111,10 @@ fn test_protocol_bans_node_sending_block_header_with_invalid_signature() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     let mut shared_active_connections = MockActiveConnectionsTraitWrapper::new();
     shared_active_connections.set_expectations(|active_connections| {
@@ -289,9 +291,10 @@ fn test_protocol_bans_node_sending_header_with_invalid_signature() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     let mut shared_active_connections = MockActiveConnectionsTraitWrapper::new();
     shared_active_connections.set_expectations(
@@ -417,9 +420,10 @@ fn test_protocol_does_not_asks_for_block_from_banned_node_who_propagated_header(
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     shared_active_connections.set_expectations(
         |active_connections: &mut MockActiveConnectionsTrait| {
@@ -564,9 +568,10 @@ fn test_protocol_bans_all_nodes_propagating_an_attack_attempt() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     let mut shared_active_connections = MockActiveConnectionsTraitWrapper::new();
     shared_active_connections.set_expectations(
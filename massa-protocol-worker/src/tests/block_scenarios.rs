@@ -8,6 +8,8 @@ use crate::messages::Message;
 use crate::wrap_network::MockActiveConnectionsTraitWrapper;
 
 use super::universe::{ProtocolForeignControllers, ProtocolTestUniverse};
+use massa_consensus_exports::block_provenance::BlockOrigin;
+use massa_consensus_exports::submission::{SubmissionOutcome, SubmissionTicket};
 use massa_models::block_header::SecuredHeader;
 use massa_models::config::CHAINID;
 use massa_models::operation::{OperationId, OperationPrefixId};
@@ -179,6 +181,7 @@ fn block_retrieval_mock(
                     .return_once(move |block_id, _, _, _| {
                         assert_eq!(block_id, asked_block_id);
                         waitpoint_trigger_handle.trigger();
+                        SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
                     });
             }
             TestsStepMatch::OperationsPropagated((node_peer_id, sent_ops)) => {
@@ -247,9 +250,11 @@ fn test_full_ask_block_workflow() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, origin| {
             assert_eq!(block_id, block.id);
+            assert_eq!(origin, BlockOrigin::Peer(node_a_peer_id));
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     block_retrieval_mock(
         vec![
@@ -341,9 +346,10 @@ fn test_empty_block() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     block_retrieval_mock(
         vec![
@@ -423,9 +429,10 @@ fn test_dont_want_it_anymore() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     block_retrieval_mock(
         vec![TestsStepMatch::AskData((
@@ -503,9 +510,10 @@ fn test_no_one_has_it() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     let mut peer_ids: HashSet<PeerId> = HashSet::new();
     peer_ids.insert(node_a_peer_id);
@@ -574,7 +582,9 @@ fn test_multiple_blocks_without_a_priori() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .returning(move |_, _| {});
+        .returning(move |block_id, _, _| {
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
+        });
     let mut shared_active_connections = MockActiveConnectionsTraitWrapper::new();
     ProtocolTestUniverse::active_connections_boilerplate(
         &mut shared_active_connections,
@@ -652,9 +662,10 @@ fn test_protocol_sends_blocks_when_asked_for() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     let peer_ids: HashSet<PeerId> = vec![node_a_peer_id, node_b_peer_id].into_iter().collect();
     block_retrieval_mock(
@@ -739,10 +750,11 @@ fn test_protocol_propagates_block_to_node_who_asked_for_operations_and_only_head
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
             waipoint_trigger_handle.trigger();
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     let peer_ids: HashSet<PeerId> = vec![node_b_peer_id, node_c_peer_id].into_iter().collect();
     block_retrieval_mock(
@@ -825,9 +837,10 @@ fn test_noting_block_does_not_panic_with_one_max_node_known_blocks_size() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     block_retrieval_mock(
         vec![
@@ -927,9 +940,10 @@ fn test_protocol_does_propagate_operations_received_in_blocks() {
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, header| {
+        .return_once(move |block_id, header, _| {
             assert_eq!(block_id, block.id);
             assert_eq!(header.id, block.content.header.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     block_retrieval_mock(
         vec![
@@ -1,3 +1,4 @@
+use massa_consensus_exports::submission::{SubmissionOutcome, SubmissionTicket};
 use massa_models::slot::Slot;
 use massa_pos_exports::Selection;
 use massa_protocol_exports::PeerId;
@@ -296,8 +297,9 @@ fn test_protocol_propagates_endorsements_only_to_nodes_that_dont_know_about_it_b
     foreign_controllers
         .consensus_controller
         .expect_register_block_header()
-        .return_once(move |block_id, block| {
+        .return_once(move |block_id, block, _| {
             assert_eq!(block_id, block.id);
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
 
@@ -3,6 +3,7 @@
 use std::collections::HashSet;
 use std::time::Duration;
 
+use massa_consensus_exports::submission::{SubmissionOutcome, SubmissionTicket};
 use massa_models::config::CHAINID;
 use massa_models::operation::{OperationPrefixId, SecureShareOperation};
 use massa_models::{block_id::BlockId, prehash::PreHashSet, slot::Slot};
@@ -387,13 +388,16 @@ fn test_protocol_propagates_operations_only_to_nodes_that_dont_know_about_it_ind
         .consensus_controller
         .expect_register_block_header()
         .times(1)
-        .returning(move |_, _| {});
+        .returning(move |block_id, _, _| {
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
+        });
     foreign_controllers
         .consensus_controller
         .expect_register_block()
         .times(1)
-        .returning(move |_, _, _, _| {
+        .returning(move |block_id, _, _, _| {
             waitpoint_trigger_handle2.trigger();
+            SubmissionTicket::resolved(block_id, SubmissionOutcome::Final)
         });
     operation_workflow_mock(
         vec![
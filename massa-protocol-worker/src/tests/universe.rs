@@ -1,5 +1,8 @@
 use massa_channel::MassaChannel;
-use massa_consensus_exports::{ConsensusController, MockConsensusController};
+use massa_consensus_exports::{
+    block_status::HeaderKnowledge, catch_up::CatchUpStatus, ConsensusController,
+    MockConsensusController,
+};
 use massa_models::config::MIP_STORE_STATS_BLOCK_CONSIDERED;
 use massa_pool_exports::{MockPoolControllerWrapper, PoolController};
 use massa_pos_exports::{MockSelectorControllerWrapper, SelectorController};
@@ -58,8 +61,16 @@ pub struct ProtocolForeignControllers {
 
 impl ProtocolForeignControllers {
     pub fn new_with_mocks() -> Self {
+        let mut consensus_controller = Box::new(MockConsensusController::new());
+        // the wished blocks are only known by their header unless a test says otherwise
+        consensus_controller
+            .expect_has_block()
+            .returning(|_| HeaderKnowledge::HeaderOnly);
+        consensus_controller
+            .expect_get_catch_up_status()
+            .returning(CatchUpStatus::default);
         Self {
-            consensus_controller: Box::new(MockConsensusController::new()),
+            consensus_controller,
             pool_controller: Box::new(MockPoolControllerWrapper::new()),
             selector_controller: Box::new(MockSelectorControllerWrapper::new()),
             network_controller: Box::new(MockNetworkController::new()),
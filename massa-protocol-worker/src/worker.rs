@@ -22,7 +22,7 @@ use peernet::{
     config::{PeerNetCategoryInfo, PeerNetConfiguration},
     network_manager::PeerNetManager,
 };
-use std::{collections::HashMap, fs::read_to_string, ops::Bound::Included, sync::Arc};
+use std::{collections::HashMap, fs::read_to_string, ops::Bound::Included, path::Path, sync::Arc};
 use tracing::{debug, log::warn};
 
 use crate::{
@@ -168,6 +168,34 @@ pub fn create_protocol_controller(
     )
 }
 
+/// Read the node keypair from its file, otherwise generate it and write it to the file
+pub fn load_or_create_node_keypair(
+    keypair_file: &Path,
+    mip_store: &MipStore,
+) -> Result<KeyPair, ProtocolError> {
+    if keypair_file.is_file() {
+        // file exists: try to load it
+        let keypair_bs58_check_encoded = read_to_string(keypair_file).map_err(|err| {
+            std::io::Error::new(err.kind(), format!("could not load node key file: {}", err))
+        })?;
+        Ok(serde_json::from_slice::<KeyPair>(
+            keypair_bs58_check_encoded.as_bytes(),
+        )?)
+    } else {
+        // node file does not exist: generate the key and save it
+        // MERGE TODO
+        let keypair_factory = KeyPairFactory {
+            mip_store: mip_store.clone(),
+        };
+        let now = MassaTime::now();
+        let keypair = keypair_factory.create(&(), FactoryStrategy::At(now))?;
+        if let Err(e) = std::fs::write(keypair_file, serde_json::to_string(&keypair)?) {
+            warn!("could not generate node key file: {}", e);
+        }
+        Ok(keypair)
+    }
+}
+
 /// start a new `ProtocolController` from a `ProtocolConfig`
 ///
 /// # Arguments
@@ -217,25 +245,7 @@ pub fn start_protocol_controller(
     };
 
     // try to read node keypair from file, otherwise generate it & write to file. Then derive nodeId
-    let keypair = if std::path::Path::is_file(&config.keypair_file) {
-        // file exists: try to load it
-        let keypair_bs58_check_encoded = read_to_string(&config.keypair_file).map_err(|err| {
-            std::io::Error::new(err.kind(), format!("could not load node key file: {}", err))
-        })?;
-        serde_json::from_slice::<KeyPair>(keypair_bs58_check_encoded.as_bytes())?
-    } else {
-        // node file does not exist: generate the key and save it
-        // MERGE TODO
-        let keypair_factory = KeyPairFactory {
-            mip_store: mip_store.clone(),
-        };
-        let now = MassaTime::now();
-        let keypair = keypair_factory.create(&(), FactoryStrategy::At(now))?;
-        if let Err(e) = std::fs::write(&config.keypair_file, serde_json::to_string(&keypair)?) {
-            warn!("could not generate node key file: {}", e);
-        }
-        keypair
-    };
+    let keypair = load_or_create_node_keypair(&config.keypair_file, &mip_store)?;
 
     let mut peernet_config = PeerNetConfiguration::default(
         MassaHandshake::new(peer_db.clone(), config.clone()),
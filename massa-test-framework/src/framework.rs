@@ -61,6 +61,7 @@ pub trait TestUniverse {
                 operation_merkle_root,
                 endorsements,
                 denunciations,
+                extensions: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             keypair,
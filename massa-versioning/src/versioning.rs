@@ -21,6 +21,7 @@ use massa_models::config::MIP_STORE_STATS_BLOCK_CONSIDERED;
 use massa_models::config::VERSIONING_ACTIVATION_DELAY_MIN;
 use massa_models::config::VERSIONING_THRESHOLD_TRANSITION_ACCEPTED;
 use massa_models::error::ModelsError;
+use massa_models::operation::OperationType;
 use massa_models::slot::Slot;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_serialization::{DeserializeError, Deserializer, SerializeError, Serializer};
@@ -45,11 +46,34 @@ pub enum MipComponent {
     Block,
     VM,
     FinalStateHashKind,
+    // Emergency pause of the PoS payouts, activated through governance
+    PoSPayoutPause,
+    // Delegation of the rolls to an operator: Delegate / Undelegate operations and delegated draws
+    Delegation,
+    // Payout addresses: SetPayoutAddress operation and the matching PoS changes format
+    PayoutAddress,
+    // Versioned extension fields of the block headers
+    BlockHeaderExtensions,
+    // Split of the large roll sale reimbursements across consecutive slots
+    RollSellCreditSplit,
     #[doc(hidden)]
     #[num_enum(default)]
     __Nonexhaustive,
 }
 
+impl MipComponent {
+    /// Component whose activation enables an operation type, `None` for the operation types available since genesis
+    pub fn for_operation_type(op: &OperationType) -> Option<MipComponent> {
+        match op {
+            OperationType::Delegate { .. } | OperationType::Undelegate { .. } => {
+                Some(MipComponent::Delegation)
+            }
+            OperationType::SetPayoutAddress { .. } => Some(MipComponent::PayoutAddress),
+            _ => None,
+        }
+    }
+}
+
 /// MIP info (name & versions & time range for a MIP)
 #[derive(Clone, Debug)]
 pub struct MipInfo {
@@ -641,6 +665,14 @@ impl MipStore {
         guard.get_latest_component_version_at(component, ts)
     }
 
+    /// Whether an operation type is enabled at given timestamp (e.g. slot):
+    /// the component it belongs to (see `MipComponent::for_operation_type`) must be active
+    pub fn is_operation_type_active_at(&self, op: &OperationType, ts: MassaTime) -> bool {
+        MipComponent::for_operation_type(op).map_or(true, |component| {
+            self.get_latest_component_version_at(&component, ts) > 0
+        })
+    }
+
     /// Get all versions in 'Active state' for the given MipComponent
     pub(crate) fn get_all_active_component_versions(&self, component: &MipComponent) -> Vec<u32> {
         let guard = self.0.read();
@@ -1596,8 +1628,10 @@ mod test {
 
     use crate::test_helpers::versioning_helpers::advance_state_until;
 
+    use massa_models::address::Address;
     use massa_models::config::{MIP_STORE_STATS_BLOCK_CONSIDERED, T0, THREAD_COUNT};
     use massa_models::timeslots::get_closest_slot_to_timestamp;
+    use massa_signature::KeyPair;
 
     // Only for unit tests
     impl PartialEq<ComponentState> for MipState {
@@ -2265,6 +2299,45 @@ mod test {
         assert!(mip_store.is_ok());
     }
 
+    #[test]
+    fn test_operation_type_activation() {
+        // Test that the operation types of a component are only enabled once it is active
+
+        let mip_stats_config = MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            warn_announced_version_ratio: Ratio::new_raw(30, 100),
+        };
+        let operator = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let delegate = OperationType::Delegate { operator };
+        let transaction = OperationType::Transaction {
+            recipient_address: operator,
+            amount: Default::default(),
+        };
+
+        // operation types available since genesis are always enabled, the others are not by default
+        let mip_store = MipStore::try_from(([], mip_stats_config.clone())).unwrap();
+        assert!(mip_store.is_operation_type_active_at(&transaction, MassaTime::from_millis(0)));
+        assert!(!mip_store.is_operation_type_active_at(&delegate, MassaTime::from_millis(1000)));
+        let set_payout_address = OperationType::SetPayoutAddress {
+            payout_address: Some(operator),
+        };
+        assert!(!mip_store
+            .is_operation_type_active_at(&set_payout_address, MassaTime::from_millis(1000)));
+
+        let mi = MipInfo {
+            name: "MIP-0002".to_string(),
+            version: 2,
+            components: BTreeMap::from([(MipComponent::Delegation, 1)]),
+            start: MassaTime::from_millis(2),
+            timeout: MassaTime::from_millis(5),
+            activation_delay: MassaTime::from_millis(2),
+        };
+        let ms = advance_state_until(ComponentState::active(MassaTime::now()), &mi);
+        let mip_store = MipStore::try_from(([(mi, ms)], mip_stats_config)).unwrap();
+        assert!(!mip_store.is_operation_type_active_at(&delegate, MassaTime::from_millis(0)));
+        assert!(mip_store.is_operation_type_active_at(&delegate, MassaTime::from_millis(1000)));
+    }
+
     #[test]
     fn test_update_with_unknown() {
         // Test update_with with unknown MipComponent (can happen if a node software is outdated)